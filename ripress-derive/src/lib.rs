@@ -1,4 +1,5 @@
 use proc_macro::TokenStream;
+use syn::spanned::Spanned;
 
 /// A derive macro for automatically implementing the `FromParams` trait.
 ///
@@ -118,7 +119,7 @@ pub fn from_json_derive(input: TokenStream) -> TokenStream {
         impl ::ripress::req::body::json_data::FromJson for #struct_name {
             fn from_json(data: &::ripress::req::body::RequestBody) -> Result<Self, String> {
                 if let ::ripress::req::body::RequestBody::JSON(json_value) = data {
-                    serde_json::from_value::<Self>(json_value.to_owned())
+                    <Self as serde::Deserialize>::deserialize(json_value)
                         .map_err(|e| format!("Failed to deserialize JSON: {}", e))
                 } else {
                     Err("Request body is not JSON".to_string())
@@ -269,3 +270,205 @@ pub fn from_query_param_derive(input: TokenStream) -> TokenStream {
 
     TokenStream::from(expanded)
 }
+
+/// Where a `#[derive(FromRequest)]` field is read from.
+enum Source {
+    Path,
+    Query,
+    Body,
+}
+
+/// Parses the `#[from(...)]` attribute of a field.
+fn field_source(field: &syn::Field) -> syn::Result<Source> {
+    let ident = field.ident.as_ref().expect("named field");
+    let mut attrs = field.attrs.iter().filter(|a| a.path().is_ident("from"));
+
+    let attr = attrs.next().ok_or_else(|| {
+        syn::Error::new_spanned(
+            ident,
+            format!(
+                "missing `#[from(...)]` attribute on field `{}`; expected `#[from(path)]`, `#[from(query)]` or `#[from(body)]`",
+                ident
+            ),
+        )
+    })?;
+    if let Some(extra) = attrs.next() {
+        return Err(syn::Error::new_spanned(
+            extra,
+            "a field can only have one `#[from(...)]` attribute",
+        ));
+    }
+
+    let mut source = None;
+    attr.parse_nested_meta(|meta| {
+        if source.is_some() {
+            return Err(meta.error("expected a single source: `path`, `query` or `body`"));
+        }
+        source = Some(if meta.path.is_ident("path") {
+            Source::Path
+        } else if meta.path.is_ident("query") {
+            Source::Query
+        } else if meta.path.is_ident("body") {
+            Source::Body
+        } else {
+            return Err(meta.error("unknown source; expected `path`, `query` or `body`"));
+        });
+        if !meta.input.is_empty() && !meta.input.peek(syn::Token![,]) {
+            return Err(meta.error("a source doesn't take arguments"));
+        }
+        Ok(())
+    })?;
+
+    source.ok_or_else(|| {
+        syn::Error::new_spanned(attr, "expected a source: `path`, `query` or `body`")
+    })
+}
+
+/// A derive macro for implementing `FromRequest` on a struct whose fields come
+/// from different parts of the request.
+///
+/// Every field needs a `#[from(...)]` attribute naming its source, and is
+/// extracted by the same trait as the matching standalone extractor:
+///
+/// - `#[from(path)]` - a type implementing `FromParams`, as for `Params<T>`
+/// - `#[from(query)]` - a type implementing `FromQueryParam`, as for `QueryParam<T>`
+/// - `#[from(body)]` - a `JsonBody<T>`, where `T` implements `FromJson`
+///
+/// A field that fails to extract rejects the request with `400 Bad Request` and
+/// the extractor's own error message.
+///
+/// # Usage
+///
+/// ```rust,ignore
+/// use ripress::req::body::json_data::JsonBody;
+/// use ripress_derive::{FromJson, FromParams, FromQueryParam, FromRequest};
+/// use serde::Deserialize;
+///
+/// #[derive(FromParams)]
+/// struct PostPath {
+///     post_id: u32,
+/// }
+///
+/// #[derive(FromQueryParam)]
+/// struct Notify {
+///     notify: bool,
+/// }
+///
+/// #[derive(Deserialize, FromJson)]
+/// struct Comment {
+///     text: String,
+/// }
+///
+/// #[derive(FromRequest)]
+/// struct NewComment {
+///     #[from(path)]
+///     path: PostPath,
+///     #[from(query)]
+///     query: Notify,
+///     #[from(body)]
+///     comment: JsonBody<Comment>,
+/// }
+///
+/// app.post("/posts/:post_id/comments", |input: NewComment, res| async move {
+///     res.created().text(format!("{}: {}", input.path.post_id, input.comment.text))
+/// });
+/// ```
+#[proc_macro_derive(FromRequest, attributes(from))]
+pub fn from_request_derive(input: TokenStream) -> TokenStream {
+    let ast = syn::parse_macro_input!(input as syn::DeriveInput);
+
+    let struct_name = &ast.ident;
+
+    let fields = match ast.data {
+        syn::Data::Struct(ref s) => match &s.fields {
+            syn::Fields::Named(named) => &named.named,
+            _ => {
+                return syn::Error::new_spanned(
+                    struct_name,
+                    "FromRequest can only be derived for structs with named fields",
+                )
+                .to_compile_error()
+                .into();
+            }
+        },
+        _ => {
+            return syn::Error::new_spanned(
+                struct_name,
+                "FromRequest can only be derived for structs",
+            )
+            .to_compile_error()
+            .into();
+        }
+    };
+
+    let mut assigns = Vec::new();
+    let mut inits = Vec::new();
+    let mut errors: Option<syn::Error> = None;
+    for field in fields {
+        let ident = field.ident.as_ref().expect("named field");
+        let ty = &field.ty;
+        // Bound under a name of its own, so a field called `req` can't shadow
+        // the request the remaining fields are read from.
+        let local = quote::format_ident!("__ripress_{}", ident);
+
+        let source = match field_source(field) {
+            Ok(source) => source,
+            Err(e) => {
+                match errors.as_mut() {
+                    Some(errors) => errors.combine(e),
+                    None => errors = Some(e),
+                }
+                continue;
+            }
+        };
+
+        // Spanned to the field type, so a type that doesn't implement the
+        // source's trait is reported there.
+        assigns.push(match source {
+            Source::Path => quote::quote_spanned! {ty.span()=>
+                let #local = <#ty as ::ripress::req::route_params::FromParams>::from_params(&req.params)?;
+            },
+            Source::Query => quote::quote_spanned! {ty.span()=>
+                let #local = <#ty as ::ripress::req::query_params::FromQueryParam>::from_query_param(&req.query)?;
+            },
+            Source::Body => quote::quote_spanned! {ty.span()=>
+                let #local: #ty = <::ripress::req::body::json_data::JsonBody<_>
+                    as ::ripress::helpers::FromRequest>::from_request(req)?;
+            },
+        });
+        inits.push(quote::quote! { #ident: #local });
+    }
+
+    let (impl_generics, ty_generics, where_clause) = ast.generics.split_for_impl();
+
+    if let Some(errors) = errors {
+        // Still implement the trait so handlers using the struct don't add
+        // errors of their own.
+        let errors = errors.to_compile_error();
+        return TokenStream::from(quote::quote! {
+            #errors
+            impl #impl_generics ::ripress::helpers::FromRequest for #struct_name #ty_generics #where_clause {
+                type Error = String;
+
+                fn from_request(_req: &::ripress::req::HttpRequest) -> Result<Self, String> {
+                    unreachable!()
+                }
+            }
+        });
+    }
+
+    let expanded = quote::quote! {
+        impl #impl_generics ::ripress::helpers::FromRequest for #struct_name #ty_generics #where_clause {
+            type Error = String;
+
+            fn from_request(req: &::ripress::req::HttpRequest) -> Result<Self, String> {
+                #(#assigns)*
+                Ok(Self {
+                    #(#inits,)*
+                })
+            }
+        }
+    };
+
+    TokenStream::from(expanded)
+}
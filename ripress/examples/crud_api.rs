@@ -8,7 +8,7 @@
 //! - RESTful routes
 
 use ripress::{
-    app::App,
+    app::{state::State, App},
     req::{body::json_data::JsonBody, route_params::Params, HttpRequest},
     res::HttpResponse,
     types::RouterFns,
@@ -66,46 +66,40 @@ async fn main() -> Result<(), Box<dyn std::error::Error>> {
             age: 25,
         },
     ]));
+    app.state(users);
 
     // GET /users - List all users
-    let users_clone = users.clone();
-    app.get("/users", move |_: HttpRequest, res: HttpResponse| {
-        let users = users_clone.clone();
-        async move {
+    app.get(
+        "/users",
+        |State(users): State<UserStore>, res: HttpResponse| async move {
             let users = users.lock().unwrap();
             res.json(json!({
                 "users": *users,
                 "count": users.len()
             }))
-        }
-    });
+        },
+    );
 
     // GET /users/:id - Get single user
-    let users_clone = users.clone();
     app.get(
         "/users/:id",
-        move |params: Params<UserId>, res: HttpResponse| {
-            let users = users_clone.clone();
-            async move {
-                let users = users.lock().unwrap();
+        |(State(users), params): (State<UserStore>, Params<UserId>), res: HttpResponse| async move {
+            let users = users.lock().unwrap();
 
-                match users.iter().find(|u| u.id == params.id) {
-                    Some(user) => res.json(json!({"user": user})),
-                    None => res.status(404).json(json!({
-                        "error": "User not found",
-                        "id": params.id
-                    })),
-                }
+            match users.iter().find(|u| u.id == params.id) {
+                Some(user) => res.json(json!({"user": user})),
+                None => res.status(404).json(json!({
+                    "error": "User not found",
+                    "id": params.id
+                })),
             }
         },
     );
 
     // POST /users - Create new user
-    let users_clone = users.clone();
     app.post(
         "/users",
-        move |body: JsonBody<CreateUserInput>, res: HttpResponse| {
-            let users = users_clone.clone();
+        |(State(users), body): (State<UserStore>, JsonBody<CreateUserInput>), res: HttpResponse| {
             async move {
                 // Validation
                 if body.name.trim().is_empty() {
@@ -154,11 +148,14 @@ async fn main() -> Result<(), Box<dyn std::error::Error>> {
     );
 
     // PUT /users/:id - Update user
-    let users_clone = users.clone();
     app.put(
         "/users/:id",
-        move |(params, body): (Params<UserId>, JsonBody<UpdateUserInput>), res: HttpResponse| {
-            let users = users_clone.clone();
+        |(State(users), params, body): (
+            State<UserStore>,
+            Params<UserId>,
+            JsonBody<UpdateUserInput>,
+        ),
+         res: HttpResponse| {
             async move {
                 let mut users = users.lock().unwrap();
 
@@ -206,28 +203,24 @@ async fn main() -> Result<(), Box<dyn std::error::Error>> {
     );
 
     // DELETE /users/:id - Delete user
-    let users_clone = users.clone();
     app.delete(
         "/users/:id",
-        move |params: Params<UserId>, res: HttpResponse| {
-            let users = users_clone.clone();
-            async move {
-                let mut users = users.lock().unwrap();
-                let initial_len = users.len();
+        |(State(users), params): (State<UserStore>, Params<UserId>), res: HttpResponse| async move {
+            let mut users = users.lock().unwrap();
+            let initial_len = users.len();
 
-                users.retain(|u| u.id != params.id);
+            users.retain(|u| u.id != params.id);
 
-                if users.len() < initial_len {
-                    res.status(200).json(json!({
-                        "message": "User deleted successfully",
-                        "id": params.id
-                    }))
-                } else {
-                    res.status(404).json(json!({
-                        "error": "User not found",
-                        "id": params.id
-                    }))
-                }
+            if users.len() < initial_len {
+                res.status(200).json(json!({
+                    "message": "User deleted successfully",
+                    "id": params.id
+                }))
+            } else {
+                res.status(404).json(json!({
+                    "error": "User not found",
+                    "id": params.id
+                }))
             }
         },
     );
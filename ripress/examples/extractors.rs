@@ -6,6 +6,7 @@
 //! - JSON body (JsonBody)
 //! - Headers (Headers)
 //! - Combining multiple extractors
+//! - A derived extractor gathering path, query and body fields (FromRequest)
 
 use ripress::{
     app::App,
@@ -16,7 +17,7 @@ use ripress::{
     res::HttpResponse,
     types::RouterFns,
 };
-use ripress_derive::{FromJson, FromParams, FromQueryParam};
+use ripress_derive::{FromJson, FromParams, FromQueryParam, FromRequest};
 use serde::{Deserialize, Serialize};
 use serde_json::json;
 
@@ -63,6 +64,23 @@ impl ripress::req::body::json_data::FromJson for UpdatePost {
     }
 }
 
+#[derive(Debug, FromQueryParam)]
+struct Notify {
+    notify: bool,
+}
+
+// One extractor reading from several parts of the request, each field through
+// the trait its standalone extractor uses
+#[derive(FromRequest)]
+struct UpdateUser {
+    #[from(path)]
+    path: UserId,
+    #[from(query)]
+    query: Notify,
+    #[from(body)]
+    user: JsonBody<CreateUser>,
+}
+
 #[tokio::main]
 async fn main() -> Result<(), Box<dyn std::error::Error>> {
     let mut app = App::new();
@@ -217,6 +235,22 @@ async fn main() -> Result<(), Box<dyn std::error::Error>> {
         },
     );
 
+    // Example 9: Derived extractor - path + query + body in one struct
+    app.patch(
+        "/users/:id",
+        |input: UpdateUser, res: HttpResponse| async move {
+            println!(
+                "Patching user {} (notify: {})",
+                input.path.id, input.query.notify
+            );
+            res.json(json!({
+                "user_id": input.path.id,
+                "notify": input.query.notify,
+                "name": input.user.name
+            }))
+        },
+    );
+
     println!("🚀 Extractors example server starting on http://127.0.0.1:3000");
     println!("\nTry these curl commands:\n");
     println!("📌 Route parameter:");
@@ -235,6 +269,10 @@ async fn main() -> Result<(), Box<dyn std::error::Error>> {
     println!(
         r#"curl 'http://127.0.0.1:3000/posts/my-awesome-post?page=1' -H "Authorization: Bearer token123""#
     );
+    println!("\n🧩 Derived extractor:");
+    println!(
+        r#"curl -X PATCH 'http://127.0.0.1:3000/users/7?notify=true' -H "Content-Type: application/json" -d '{{"name":"Carol","email":"carol@example.com","age":28}}'"#
+    );
     println!("\n🔧 Headers:");
     println!("curl http://127.0.0.1:3000/debug/headers -H 'X-Custom-Header: test'");
 
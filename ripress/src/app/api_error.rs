@@ -2,9 +2,23 @@
 use http_body_util::Full;
 use hyper::body::Bytes;
 
-use crate::res::HttpResponse;
+use crate::{app::settings::ErrorFormat, res::HttpResponse};
 use std::convert::Infallible;
 
+tokio::task_local! {
+    /// The app's [`ErrorFormat`], scoped around every connection it serves.
+    pub(crate) static ERROR_FORMAT: ErrorFormat;
+}
+
+/// Renders a framework-generated error with the app's [`ErrorFormat`], falling
+/// back to plain text outside a server, e.g. when a middleware is called directly.
+pub(crate) fn error_response(res: HttpResponse, status: u16, message: &str) -> HttpResponse {
+    ERROR_FORMAT
+        .try_with(ErrorFormat::clone)
+        .unwrap_or_default()
+        .render(res, status, message)
+}
+
 #[derive(Debug)]
 pub enum ApiError {
     Generic(HttpResponse),
@@ -21,11 +35,7 @@ impl From<HttpResponse> for ApiError {
 
 impl From<Infallible> for ApiError {
     fn from(_: Infallible) -> Self {
-        ApiError::Generic(
-            HttpResponse::new()
-                .internal_server_error()
-                .text("Unhandled error"),
-        )
+        ApiError::Generic(error_response(HttpResponse::new(), 500, "Unhandled error"))
     }
 }
 
@@ -41,9 +51,9 @@ impl From<hyper::Error> for ApiError {
             500
         };
 
-        eprintln!("hyper error: {}", err);
+        tracing::debug!("hyper error: {}", err);
 
-        ApiError::Generic(HttpResponse::new().status(status).text(message))
+        ApiError::Generic(error_response(HttpResponse::new(), status, &message))
     }
 }
 
@@ -55,13 +65,9 @@ impl From<ApiError> for Box<dyn std::error::Error + Send> {
 
 impl From<Box<dyn std::error::Error>> for ApiError {
     fn from(error: Box<dyn std::error::Error>) -> Self {
-        eprintln!("internal error: {}", error);
+        tracing::error!("internal error: {}", error);
 
-        ApiError::Generic(
-            HttpResponse::new()
-                .internal_server_error()
-                .text(error.to_string()),
-        )
+        ApiError::Generic(error_response(HttpResponse::new(), 500, &error.to_string()))
     }
 }
 
@@ -0,0 +1,410 @@
+//! Per-connection settings, including read, write and idle timeouts.
+//!
+//! Hyper's own `header_read_timeout` also runs while a keep-alive connection
+//! waits for its next request, so it can't tell a slow client from an idle one.
+//! [`TimeoutStream`] wraps the socket instead and learns from the service, via
+//! [`ConnectionActivity`], when a request head has been parsed and whether a
+//! request is still being handled.
+
+use std::{
+    collections::HashMap,
+    future::Future,
+    io,
+    net::IpAddr,
+    pin::Pin,
+    sync::{
+        atomic::{AtomicBool, AtomicUsize, Ordering},
+        Arc, Mutex,
+    },
+    task::{Context, Poll},
+    time::Duration,
+};
+
+use tokio::{
+    io::{AsyncRead, AsyncWrite, ReadBuf},
+    time::{Instant, Sleep},
+};
+
+use crate::{
+    app::{
+        settings::{ErrorFormat, Http2Config, RequestSettings, TrustProxy},
+        state::TypeMap,
+    },
+    middlewares::{body_limit::BodyLimits, metrics::Metrics},
+};
+
+#[cfg(feature = "session")]
+use crate::middlewares::session::Sessions;
+
+/// Settings shared by every connection a listener accepts.
+#[derive(Clone)]
+pub(crate) struct ConnectionConfig {
+    pub(crate) http2_config: Http2Config,
+    pub(crate) error_format: ErrorFormat,
+    pub(crate) timeouts: ConnectionTimeouts,
+    /// Whether HTTP/1 connections are kept open for further requests
+    pub(crate) keep_alive: bool,
+    /// HTTP/1 connections are closed after this many responses
+    pub(crate) max_requests: Option<usize>,
+    /// Attached to every request, see [`RequestSettings`]
+    pub(crate) request: RequestSettings,
+    /// Decides per connection whether forwarding headers are honored
+    pub(crate) trust_proxy: Option<Arc<TrustProxy>>,
+    /// Caps the bytes read from each request body, for [`App::use_body_limit`](crate::app::App::use_body_limit)
+    /// and [`RouteHandle::body_limit`](crate::router::route_handle::RouteHandle::body_limit)
+    pub(crate) body_limits: Arc<BodyLimits>,
+    /// Records request metrics, for [`App::use_metrics`](crate::app::App::use_metrics)
+    pub(crate) metrics: Option<Arc<Metrics>>,
+    /// Attached to every request, for [`App::state`](crate::app::App::state)
+    pub(crate) state: TypeMap,
+    /// Attaches and saves sessions, for [`App::use_session`](crate::app::App::use_session)
+    #[cfg(feature = "session")]
+    pub(crate) sessions: Option<Arc<Sessions>>,
+    /// Terminates TLS before HTTP is served, for [`App::listen_tls`](crate::app::App::listen_tls)
+    #[cfg(feature = "tls")]
+    pub(crate) tls: Option<tokio_rustls::TlsAcceptor>,
+}
+
+impl ConnectionConfig {
+    /// The scheme of the transport, before any proxy headers are considered.
+    pub(crate) fn scheme(&self) -> Scheme {
+        #[cfg(feature = "tls")]
+        if self.tls.is_some() {
+            return Scheme::Https;
+        }
+        Scheme::Http
+    }
+}
+
+/// Request extension recording the scheme of the connection a request arrived on.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub(crate) enum Scheme {
+    Http,
+    Https,
+}
+
+impl Scheme {
+    pub(crate) fn as_str(&self) -> &'static str {
+        match self {
+            Scheme::Http => "http",
+            Scheme::Https => "https",
+        }
+    }
+}
+
+/// Request extension recording when the connection layer received a request,
+/// which the logger times the request from.
+#[derive(Clone, Copy, Debug)]
+pub(crate) struct ReceivedAt(pub(crate) std::time::Instant);
+
+/// Timeouts configured with [`App::connection_timeouts`](crate::app::App::connection_timeouts).
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub(crate) struct ConnectionTimeouts {
+    /// Time allowed from the first byte of an HTTP/1 request to the end of its headers
+    pub(crate) read: Option<Duration>,
+    /// Time a single write may stay blocked on a client that isn't reading
+    pub(crate) write: Option<Duration>,
+    /// Time a connection may sit between requests without any traffic
+    pub(crate) idle: Option<Duration>,
+}
+
+impl ConnectionTimeouts {
+    pub(crate) fn is_enabled(&self) -> bool {
+        self.read.is_some() || self.write.is_some() || self.idle.is_some()
+    }
+}
+
+/// Counts open connections per client address, for
+/// [`App::max_connections_per_ip`](crate::app::App::max_connections_per_ip).
+#[derive(Debug)]
+pub(crate) struct ConnectionLimiter {
+    max: usize,
+    open: Mutex<HashMap<IpAddr, usize>>,
+}
+
+impl ConnectionLimiter {
+    pub(crate) fn new(max: usize) -> Arc<Self> {
+        Arc::new(ConnectionLimiter {
+            max,
+            open: Mutex::new(HashMap::new()),
+        })
+    }
+
+    /// Counts a new connection from `ip`, or returns `None` if it already has
+    /// the maximum open. The connection is counted until the permit is dropped.
+    pub(crate) fn acquire(self: &Arc<Self>, ip: IpAddr) -> Option<ConnectionPermit> {
+        let mut open = self.open.lock().unwrap();
+        let count = open.entry(ip).or_insert(0);
+        if *count >= self.max {
+            return None;
+        }
+        *count += 1;
+        Some(ConnectionPermit {
+            limiter: Arc::clone(self),
+            ip,
+        })
+    }
+}
+
+/// An open connection counted by a [`ConnectionLimiter`].
+pub(crate) struct ConnectionPermit {
+    limiter: Arc<ConnectionLimiter>,
+    ip: IpAddr,
+}
+
+impl Drop for ConnectionPermit {
+    fn drop(&mut self) {
+        let mut open = self.limiter.open.lock().unwrap();
+        if let Some(count) = open.get_mut(&self.ip) {
+            *count -= 1;
+            if *count == 0 {
+                open.remove(&self.ip);
+            }
+        }
+    }
+}
+
+/// Request bookkeeping shared between a connection's service and its stream.
+#[derive(Debug, Default)]
+pub(crate) struct ConnectionActivity {
+    /// Requests whose head has been parsed, ever
+    started: AtomicUsize,
+    /// Requests currently being handled
+    in_flight: AtomicUsize,
+    /// Set once the connection switched protocols, e.g. to a WebSocket
+    upgraded: AtomicBool,
+}
+
+impl ConnectionActivity {
+    /// Marks a request as in flight until `response` resolves.
+    pub(crate) fn track<F, B, E>(
+        self: &Arc<Self>,
+        response: F,
+    ) -> impl Future<Output = Result<hyper::Response<B>, E>>
+    where
+        F: Future<Output = Result<hyper::Response<B>, E>>,
+    {
+        self.started.fetch_add(1, Ordering::SeqCst);
+        self.in_flight.fetch_add(1, Ordering::SeqCst);
+        let guard = InFlight(Arc::clone(self));
+
+        async move {
+            let result = response.await;
+            if let Ok(res) = &result {
+                if res.status() == hyper::StatusCode::SWITCHING_PROTOCOLS {
+                    guard.0.upgraded.store(true, Ordering::SeqCst);
+                }
+            }
+            drop(guard);
+            result
+        }
+    }
+}
+
+/// Decrements the in-flight count when the response future completes or is dropped.
+struct InFlight(Arc<ConnectionActivity>);
+
+impl Drop for InFlight {
+    fn drop(&mut self) {
+        self.0.in_flight.fetch_sub(1, Ordering::SeqCst);
+    }
+}
+
+/// A socket that fails with [`io::ErrorKind::TimedOut`] once a timeout expires,
+/// which makes hyper close the connection.
+///
+/// - `read` runs from the first byte of a request until the service receives it,
+///   so a client trickling its headers is dropped. It doesn't apply to HTTP/2,
+///   where frames for other streams arrive at any time.
+/// - `idle` runs from the last byte sent or received while no request is in flight.
+/// - `write` runs while a write is blocked.
+///
+/// Nothing is enforced while a request is being handled or after the connection
+/// was upgraded.
+pub(crate) struct TimeoutStream<S> {
+    inner: S,
+    timeouts: ConnectionTimeouts,
+    activity: Arc<ConnectionActivity>,
+    last_activity: Instant,
+    head_started: Option<Instant>,
+    seen_requests: usize,
+    http2: Option<bool>,
+    write_blocked_since: Option<Instant>,
+    read_timer: Pin<Box<Sleep>>,
+    write_timer: Pin<Box<Sleep>>,
+}
+
+impl<S> TimeoutStream<S> {
+    pub(crate) fn new(
+        inner: S,
+        timeouts: ConnectionTimeouts,
+        activity: Arc<ConnectionActivity>,
+    ) -> Self {
+        let now = Instant::now();
+        TimeoutStream {
+            inner,
+            timeouts,
+            activity,
+            last_activity: now,
+            head_started: None,
+            seen_requests: 0,
+            http2: None,
+            write_blocked_since: None,
+            read_timer: Box::pin(tokio::time::sleep_until(now)),
+            write_timer: Box::pin(tokio::time::sleep_until(now)),
+        }
+    }
+
+    /// Notices requests the service has received since the last poll.
+    fn sync_requests(&mut self) {
+        let started = self.activity.started.load(Ordering::SeqCst);
+        if started != self.seen_requests {
+            self.seen_requests = started;
+            self.head_started = None;
+            self.last_activity = Instant::now();
+        }
+    }
+
+    fn is_exempt(&self) -> bool {
+        self.activity.upgraded.load(Ordering::SeqCst)
+            || self.activity.in_flight.load(Ordering::SeqCst) > 0
+    }
+
+    fn on_read(&mut self, bytes: &[u8]) {
+        let now = Instant::now();
+        if self.http2.is_none() {
+            self.http2 = Some(bytes.starts_with(b"PRI "));
+        }
+        self.sync_requests();
+        if self.head_started.is_none() && self.http2 == Some(false) && !self.is_exempt() {
+            self.head_started = Some(now);
+        }
+        self.last_activity = now;
+    }
+
+    fn read_deadline(&mut self) -> Option<Instant> {
+        self.sync_requests();
+        if self.is_exempt() {
+            return None;
+        }
+        match (self.head_started, self.timeouts.read) {
+            (Some(started), Some(read)) => Some(started + read),
+            _ => self.timeouts.idle.map(|idle| self.last_activity + idle),
+        }
+    }
+
+    /// Applies the write timeout to the outcome of a write or flush.
+    ///
+    /// Only bytes written count as activity: hyper flushes an idle connection
+    /// each time it is woken, which must not keep it alive.
+    fn on_write_poll(
+        &mut self,
+        cx: &mut Context<'_>,
+        poll: Poll<io::Result<usize>>,
+    ) -> Poll<io::Result<usize>> {
+        match poll {
+            Poll::Ready(result) => {
+                self.write_blocked_since = None;
+                if matches!(result, Ok(n) if n > 0) {
+                    self.last_activity = Instant::now();
+                }
+                Poll::Ready(result)
+            }
+            Poll::Pending => {
+                let Some(write) = self.timeouts.write else {
+                    return Poll::Pending;
+                };
+                if self.activity.upgraded.load(Ordering::SeqCst) {
+                    return Poll::Pending;
+                }
+                let since = *self.write_blocked_since.get_or_insert_with(Instant::now);
+                if expired(&mut self.write_timer, since + write, cx) {
+                    return Poll::Ready(Err(timed_out("write")));
+                }
+                Poll::Pending
+            }
+        }
+    }
+}
+
+/// Points `timer` at `deadline` and reports whether it has passed, registering
+/// the task to be woken when it does.
+fn expired(timer: &mut Pin<Box<Sleep>>, deadline: Instant, cx: &mut Context<'_>) -> bool {
+    if timer.deadline() != deadline {
+        timer.as_mut().reset(deadline);
+    }
+    timer.as_mut().poll(cx).is_ready()
+}
+
+fn timed_out(what: &str) -> io::Error {
+    io::Error::new(
+        io::ErrorKind::TimedOut,
+        format!("connection {} timed out", what),
+    )
+}
+
+impl<S: AsyncRead + Unpin> AsyncRead for TimeoutStream<S> {
+    fn poll_read(
+        self: Pin<&mut Self>,
+        cx: &mut Context<'_>,
+        buf: &mut ReadBuf<'_>,
+    ) -> Poll<io::Result<()>> {
+        let this = self.get_mut();
+        let filled = buf.filled().len();
+
+        match Pin::new(&mut this.inner).poll_read(cx, buf) {
+            Poll::Ready(Ok(())) => {
+                let read = &buf.filled()[filled..];
+                if !read.is_empty() {
+                    this.on_read(read);
+                }
+                Poll::Ready(Ok(()))
+            }
+            Poll::Ready(Err(e)) => Poll::Ready(Err(e)),
+            Poll::Pending => {
+                if let Some(deadline) = this.read_deadline() {
+                    if expired(&mut this.read_timer, deadline, cx) {
+                        return Poll::Ready(Err(timed_out("read")));
+                    }
+                }
+                Poll::Pending
+            }
+        }
+    }
+}
+
+impl<S: AsyncWrite + Unpin> AsyncWrite for TimeoutStream<S> {
+    fn poll_write(
+        self: Pin<&mut Self>,
+        cx: &mut Context<'_>,
+        buf: &[u8],
+    ) -> Poll<io::Result<usize>> {
+        let this = self.get_mut();
+        let poll = Pin::new(&mut this.inner).poll_write(cx, buf);
+        this.on_write_poll(cx, poll)
+    }
+
+    fn poll_write_vectored(
+        self: Pin<&mut Self>,
+        cx: &mut Context<'_>,
+        bufs: &[io::IoSlice<'_>],
+    ) -> Poll<io::Result<usize>> {
+        let this = self.get_mut();
+        let poll = Pin::new(&mut this.inner).poll_write_vectored(cx, bufs);
+        this.on_write_poll(cx, poll)
+    }
+
+    fn is_write_vectored(&self) -> bool {
+        self.inner.is_write_vectored()
+    }
+
+    fn poll_flush(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<io::Result<()>> {
+        let this = self.get_mut();
+        let poll = Pin::new(&mut this.inner).poll_flush(cx).map_ok(|()| 0);
+        this.on_write_poll(cx, poll).map_ok(|_| ())
+    }
+
+    fn poll_shutdown(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<io::Result<()>> {
+        Pin::new(&mut self.get_mut().inner).poll_shutdown(cx)
+    }
+}
@@ -1,47 +1,232 @@
-use std::sync::Arc;
+use std::{
+    cell::RefCell,
+    net::IpAddr,
+    sync::{
+        atomic::{AtomicUsize, Ordering},
+        Arc,
+    },
+    time::Instant,
+};
 
-use crate::app::{api_error::ApiError, App, Http2Config};
+use crate::app::{
+    api_error::{ApiError, ERROR_FORMAT},
+    connection::{ConnectionActivity, ConnectionConfig, ReceivedAt, TimeoutStream},
+    settings::RequestSettings,
+    App, Http2Config,
+};
+use crate::middlewares::body_limit::{declares_more_than, payload_too_large, read_body};
+use crate::next::{PENDING_COOKIES, PENDING_HEADERS};
+use crate::req::body::incoming::IncomingBody;
+use crate::res::conversions::{into_outgoing_response, OutgoingBody};
 use bytes::Bytes;
 use http_body_util::Full;
-use hyper::{server::conn::http1, service::Service};
+use hyper::{
+    body::Incoming,
+    header::{HeaderValue, CONNECTION, HOST},
+    server::conn::http1,
+    service::Service,
+    Method, Request, Uri, Version,
+};
 use hyper_util::{
-    rt::{TokioExecutor, TokioIo},
+    rt::{TokioExecutor, TokioIo, TokioTimer},
     server::conn::auto::{Builder, Http2Builder},
 };
 use routerify_ng::RouterService;
+use tokio::io::{AsyncRead, AsyncWrite};
 
 impl App {
     pub(crate) async fn handle_connection(
         stream: tokio::net::TcpStream,
         service: Arc<RouterService<ApiError>>,
-        http2_enabled: bool,
-        http2_config: Http2Config,
+        config: ConnectionConfig,
     ) {
         let request_service = match service.call(&stream).await {
             Ok(svc) => svc,
             Err(err) => {
-                eprintln!("Error creating per-connection service: {:?}", err);
+                tracing::error!("Error creating per-connection service: {:?}", err);
                 return;
             }
         };
+        let peer = stream.peer_addr().ok().map(|addr| addr.ip());
+        Self::serve_connection(stream, peer, request_service, config).await;
+    }
+
+    /// Serves HTTP on an accepted stream with its per-connection router service.
+    ///
+    /// `peer` is the client's address, `None` when the transport has none.
+    pub(crate) async fn serve_connection<T, R>(
+        stream: T,
+        peer: Option<IpAddr>,
+        request_service: R,
+        config: ConnectionConfig,
+    ) where
+        T: AsyncRead + AsyncWrite + Unpin + Send + 'static,
+        R: Service<Request<Full<Bytes>>, Response = hyper::Response<Full<Bytes>>>
+            + Send
+            + Sync
+            + 'static,
+        R::Future: Send + 'static,
+        R::Error: Into<Box<dyn std::error::Error + Send + Sync>>,
+    {
+        let scheme = config.scheme();
+        let request_settings = config.request.for_peer(config.trust_proxy.as_deref(), peer);
+        // Task locals are scoped per request rather than per connection, since
+        // HTTP/2 streams run on their own tasks.
+        let error_format = config.error_format.clone();
+        let activity = Arc::new(ConnectionActivity::default());
+        let service_activity = Arc::clone(&activity);
+        let metrics = config.metrics.clone();
+        #[cfg(feature = "session")]
+        let sessions = config.sessions.clone();
+        let body_limits = Arc::clone(&config.body_limits);
+        let state = config.state.clone();
+        let max_requests = config.max_requests;
+        let served = AtomicUsize::new(0);
+        let request_service = Arc::new(request_service);
+        let request_service = hyper::service::service_fn(move |mut req: Request<Incoming>| {
+            route_authority_form(&mut req);
+            // Hyper closes an HTTP/1 connection after a response marked `Connection: close`.
+            let last_request = req.version() < Version::HTTP_2
+                && max_requests
+                    .is_some_and(|max| served.fetch_add(1, Ordering::Relaxed) + 1 >= max);
+            let body_limit = body_limits.resolve(req.method(), req.uri().path());
+            let stream_body = body_limits.streams(req.method(), req.uri().path());
+            req.extensions_mut().insert(ReceivedAt(Instant::now()));
+            req.extensions_mut().insert(scheme);
+            req.extensions_mut().insert(RequestSettings {
+                body_limit,
+                ..request_settings
+            });
+            req.extensions_mut().insert(state.clone());
+            let method = req.method().clone();
+            #[cfg(feature = "session")]
+            let session = sessions.clone().map(|sessions| {
+                let session = sessions.start(req.headers());
+                req.extensions_mut().insert(session.clone());
+                (sessions, session)
+            });
+            let request_service = Arc::clone(&request_service);
+            let response = async move {
+                let req = if stream_body {
+                    // Left on the connection for `HttpRequest::body_stream`.
+                    if let Some(limit) = body_limit {
+                        if declares_more_than(&req, limit) {
+                            let res = payload_too_large(limit);
+                            return Ok(res.to_hyper_response().await.unwrap());
+                        }
+                    }
+                    let (mut parts, body) = req.into_parts();
+                    parts.extensions.insert(IncomingBody::new(body));
+                    Request::from_parts(parts, Full::new(Bytes::new()))
+                } else {
+                    match read_body(req, body_limit).await {
+                        Ok(req) => req,
+                        Err(res) => return Ok(res.to_hyper_response().await.unwrap()),
+                    }
+                };
+                let mut response = request_service.call(req).await?;
+                if last_request {
+                    response
+                        .headers_mut()
+                        .insert(CONNECTION, HeaderValue::from_static("close"));
+                }
+                Ok::<_, R::Error>(response)
+            };
+            let response = ERROR_FORMAT.scope(
+                error_format.clone(),
+                PENDING_HEADERS.scope(
+                    RefCell::new(Vec::new()),
+                    PENDING_COOKIES.scope(RefCell::new(Vec::new()), response),
+                ),
+            );
+            #[cfg(feature = "session")]
+            let response = async move {
+                let mut response = response.await?;
+                if let Some((sessions, session)) = session {
+                    sessions.commit(&session, &mut response).await;
+                }
+                Ok::<_, R::Error>(response)
+            };
+            let response = service_activity.track(response);
+            let metrics = metrics.clone();
+            async move {
+                let response = match metrics {
+                    Some(metrics) => metrics.track(&method, response).await,
+                    None => response.await,
+                };
+                response.map(into_outgoing_response)
+            }
+        });
+
+        #[cfg(feature = "tls")]
+        if let Some(acceptor) = &config.tls {
+            let handshake = acceptor.accept(stream);
+            let stream = match config.timeouts.read {
+                Some(limit) => match tokio::time::timeout(limit, handshake).await {
+                    Ok(result) => result,
+                    Err(_) => return,
+                },
+                None => handshake.await,
+            };
+            match stream {
+                Ok(stream) => {
+                    Self::serve_stream(stream, request_service, activity, &config).await;
+                }
+                Err(err) => tracing::debug!("TLS handshake failed: {}", err),
+            }
+            return;
+        }
 
-        let io = TokioIo::new(stream);
+        Self::serve_stream(stream, request_service, activity, &config).await;
+    }
+
+    /// Serves HTTP over an accepted (and, for HTTPS, decrypted) stream,
+    /// enforcing the connection timeouts if any are set.
+    async fn serve_stream<T, S>(
+        stream: T,
+        request_service: S,
+        activity: Arc<ConnectionActivity>,
+        config: &ConnectionConfig,
+    ) where
+        T: AsyncRead + AsyncWrite + Unpin + Send + 'static,
+        S: Service<Request<Incoming>, Response = hyper::Response<OutgoingBody>> + Send + 'static,
+        S::Future: Send + 'static,
+        S::Error: Into<Box<dyn std::error::Error + Send + Sync>>,
+    {
+        if config.timeouts.is_enabled() {
+            let stream = TimeoutStream::new(stream, config.timeouts, activity);
+            Self::serve(TokioIo::new(stream), request_service, config).await;
+        } else {
+            Self::serve(TokioIo::new(stream), request_service, config).await;
+        }
+    }
 
-        if http2_enabled {
+    async fn serve<I, S>(io: I, request_service: S, config: &ConnectionConfig)
+    where
+        I: hyper::rt::Read + hyper::rt::Write + Unpin + Send + 'static,
+        S: Service<hyper::Request<hyper::body::Incoming>, Response = hyper::Response<OutgoingBody>>
+            + Send
+            + 'static,
+        S::Future: Send + 'static,
+        S::Error: Into<Box<dyn std::error::Error + Send + Sync>>,
+    {
+        let http2_config = &config.http2_config;
+        if http2_config.is_enabled {
             if http2_config.http2_only {
-                Self::serve_http2_only(io, request_service, &http2_config).await;
+                Self::serve_http2_only(io, request_service, http2_config).await;
             } else {
-                Self::serve_http1_and_http2(io, request_service, &http2_config).await;
+                Self::serve_http1_and_http2(io, request_service, http2_config, config.keep_alive)
+                    .await;
             }
         } else {
-            Self::serve_http1_and_http2_default(io, request_service).await;
+            Self::serve_http1_and_http2_default(io, request_service, config.keep_alive).await;
         }
     }
 
     async fn serve_http2_only<I, S>(io: I, service: S, cfg: &Http2Config)
     where
         I: hyper::rt::Read + hyper::rt::Write + Unpin + Send + 'static,
-        S: Service<hyper::Request<hyper::body::Incoming>, Response = hyper::Response<Full<Bytes>>>
+        S: Service<hyper::Request<hyper::body::Incoming>, Response = hyper::Response<OutgoingBody>>
             + Send
             + 'static,
         S::Future: Send + 'static,
@@ -54,14 +239,14 @@ impl App {
         h2.enable_connect_protocol();
 
         if let Err(err) = h2.serve_connection(io, service).await {
-            eprintln!("Error serving HTTP/2-only connection: {:?}", err);
+            tracing::debug!("Error serving HTTP/2-only connection: {:?}", err);
         }
     }
 
-    async fn serve_http1_and_http2<I, S>(io: I, service: S, cfg: &Http2Config)
+    async fn serve_http1_and_http2<I, S>(io: I, service: S, cfg: &Http2Config, keep_alive: bool)
     where
         I: hyper::rt::Read + hyper::rt::Write + Unpin + Send + 'static,
-        S: Service<hyper::Request<hyper::body::Incoming>, Response = hyper::Response<Full<Bytes>>>
+        S: Service<hyper::Request<hyper::body::Incoming>, Response = hyper::Response<OutgoingBody>>
             + Send
             + 'static,
         S::Future: Send + 'static,
@@ -69,31 +254,31 @@ impl App {
     {
         let mut builder = Builder::new(TokioExecutor::new());
 
-        builder.http1().keep_alive(true);
+        builder.http1().keep_alive(keep_alive);
 
         let mut h2 = builder.http2();
         Self::apply_http2_config(&mut h2, cfg);
         h2.enable_connect_protocol();
 
         if let Err(err) = builder.serve_connection_with_upgrades(io, service).await {
-            eprintln!("Error serving HTTP/1 + HTTP/2 connection: {:?}", err);
+            tracing::debug!("Error serving HTTP/1 + HTTP/2 connection: {:?}", err);
         }
     }
 
-    async fn serve_http1_and_http2_default<I, S>(io: I, service: S)
+    async fn serve_http1_and_http2_default<I, S>(io: I, service: S, keep_alive: bool)
     where
         I: hyper::rt::Read + hyper::rt::Write + Unpin + Send + 'static,
-        S: Service<hyper::Request<hyper::body::Incoming>, Response = hyper::Response<Full<Bytes>>>
+        S: Service<hyper::Request<hyper::body::Incoming>, Response = hyper::Response<OutgoingBody>>
             + Send
             + 'static,
         S::Future: Send + 'static,
         S::Error: Into<Box<dyn std::error::Error + Send + Sync>>,
     {
         let mut builder = Builder::new(TokioExecutor::new());
-        builder.http1().keep_alive(true);
+        builder.http1().keep_alive(keep_alive);
 
         if let Err(err) = builder.serve_connection_with_upgrades(io, service).await {
-            eprintln!("Error serving default connection: {:?}", err);
+            tracing::debug!("Error serving default connection: {:?}", err);
         }
     }
 
@@ -101,7 +286,7 @@ impl App {
     async fn serve_http1_only<I, S>(io: I, service: S)
     where
         I: hyper::rt::Read + hyper::rt::Write + Unpin + Send + 'static,
-        S: Service<hyper::Request<hyper::body::Incoming>, Response = hyper::Response<Full<Bytes>>>
+        S: Service<hyper::Request<hyper::body::Incoming>, Response = hyper::Response<OutgoingBody>>
             + Send
             + 'static,
         S::Future: Send + 'static,
@@ -111,11 +296,14 @@ impl App {
         builder.keep_alive(true);
 
         if let Err(err) = builder.serve_connection(io, service).with_upgrades().await {
-            eprintln!("Error serving HTTP/1-only connection: {:?}", err);
+            tracing::debug!("Error serving HTTP/1-only connection: {:?}", err);
         }
     }
 
     fn apply_http2_config(h2: &mut Http2Builder<'_, TokioExecutor>, cfg: &Http2Config) {
+        // Keep-alive PINGs are scheduled on this timer; without it hyper panics
+        // as soon as `keep_alive_interval` is set.
+        h2.timer(TokioTimer::new());
         if let Some(v) = cfg.max_concurrent_streams {
             h2.max_concurrent_streams(v);
         }
@@ -142,3 +330,21 @@ impl App {
         }
     }
 }
+
+/// Routes a CONNECT request in authority form (`CONNECT example.com:443`) to
+/// `/`, since its target has no path, and keeps the target in `Host` if the
+/// client didn't send one.
+pub(crate) fn route_authority_form<B>(req: &mut Request<B>) {
+    if req.method() != Method::CONNECT || !req.uri().path().is_empty() {
+        return;
+    }
+    let Some(authority) = req.uri().authority().cloned() else {
+        return;
+    };
+    if !req.headers().contains_key(HOST) {
+        if let Ok(host) = HeaderValue::from_str(authority.as_str()) {
+            req.headers_mut().insert(HOST, host);
+        }
+    }
+    *req.uri_mut() = Uri::from_static("/");
+}
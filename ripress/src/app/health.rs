@@ -0,0 +1,124 @@
+#![warn(missing_docs)]
+use std::{future::Future, pin::Pin, sync::Arc};
+
+use serde_json::json;
+
+use crate::{
+    app::App,
+    types::{HttpMethods, RouterFns},
+};
+
+/// The outcome of a readiness check registered with [`App::readiness`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum HealthStatus {
+    /// The dependency can serve traffic
+    Healthy,
+    /// The dependency can't serve traffic, with a reason reported in the probe response
+    Unhealthy(String),
+}
+
+impl HealthStatus {
+    /// Returns `true` for [`HealthStatus::Healthy`].
+    pub fn is_healthy(&self) -> bool {
+        matches!(self, HealthStatus::Healthy)
+    }
+}
+
+impl From<bool> for HealthStatus {
+    fn from(healthy: bool) -> Self {
+        if healthy {
+            HealthStatus::Healthy
+        } else {
+            HealthStatus::Unhealthy("check failed".to_string())
+        }
+    }
+}
+
+impl<E: std::fmt::Display> From<Result<(), E>> for HealthStatus {
+    fn from(result: Result<(), E>) -> Self {
+        match result {
+            Ok(()) => HealthStatus::Healthy,
+            Err(e) => HealthStatus::Unhealthy(e.to_string()),
+        }
+    }
+}
+
+pub(crate) type ReadinessCheck =
+    Arc<dyn Fn() -> Pin<Box<dyn Future<Output = HealthStatus> + Send>> + Send + Sync>;
+
+impl App {
+    /// Registers a liveness probe that answers `GET` with `200 OK` while the server is up.
+    ///
+    /// ## Example
+    ///
+    /// ```
+    /// use ripress::app::App;
+    ///
+    /// let mut app = App::new();
+    /// app.health("/healthz");
+    /// ```
+    pub fn health(&mut self, path: &str) -> &mut Self {
+        self.add_route(HttpMethods::GET, path, |_req, res| async move {
+            res.ok().json(json!({ "status": "ok" }))
+        });
+        self
+    }
+
+    /// Registers a readiness probe that runs `check` on every `GET` to `path`.
+    ///
+    /// Calling it again with the same path adds another check; the probe answers
+    /// `200 OK` only when every check is healthy, and `503 Service Unavailable`
+    /// with the reasons of the failing checks otherwise. Checks run concurrently.
+    ///
+    /// ## Example
+    ///
+    /// ```
+    /// use ripress::app::{health::HealthStatus, App};
+    ///
+    /// let mut app = App::new();
+    ///
+    /// app.readiness("/readyz", || async {
+    ///     // e.g. ping the database
+    ///     HealthStatus::Healthy
+    /// });
+    /// app.readiness("/readyz", || async {
+    ///     HealthStatus::Unhealthy("cache unreachable".to_string())
+    /// });
+    /// // GET /readyz -> 503 {"status":"unavailable","errors":["cache unreachable"]}
+    /// ```
+    pub fn readiness<F, Fut>(&mut self, path: &str, check: F) -> &mut Self
+    where
+        F: Fn() -> Fut + Send + Sync + 'static,
+        Fut: Future<Output = HealthStatus> + Send + 'static,
+    {
+        let checks = self
+            .settings
+            .readiness_checks
+            .entry(path.to_string())
+            .or_default();
+        checks.push(Arc::new(move || Box::pin(check())));
+        let checks: Arc<[ReadinessCheck]> = checks.clone().into();
+
+        self.add_route(HttpMethods::GET, path, move |_req, res| {
+            let checks = Arc::clone(&checks);
+            async move {
+                let results = futures::future::join_all(checks.iter().map(|check| check())).await;
+                let errors: Vec<String> = results
+                    .into_iter()
+                    .filter_map(|status| match status {
+                        HealthStatus::Healthy => None,
+                        HealthStatus::Unhealthy(reason) => Some(reason),
+                    })
+                    .collect();
+
+                if errors.is_empty() {
+                    res.ok().json(json!({ "status": "ok" }))
+                } else {
+                    res.service_unavailable()
+                        .json(json!({ "status": "unavailable", "errors": errors }))
+                }
+            }
+        });
+        self
+    }
+}
@@ -7,16 +7,23 @@ use http_body_util::Full;
 use crate::middlewares::compression::CompressionConfig;
 #[cfg(feature = "logger")]
 use crate::middlewares::logger::LoggerConfig;
+#[cfg(feature = "request-id")]
+use crate::middlewares::request_id::RequestIdConfig;
+#[cfg(feature = "session")]
+use crate::middlewares::session::SessionConfig;
 use crate::middlewares::{
-    body_limit::body_limit,
-    cors::{cors, CorsConfig},
+    auth::{basic_auth, BasicAuthVerifier},
+    body_limit::{body_limit, DEFAULT_BODY_LIMIT},
+    cors::{cors, CorsConfig, OriginMatcher},
+    etag::{etag, EtagConfig},
+    metrics::{Metrics, MetricsConfig},
     rate_limiter::{rate_limiter, RateLimiterConfig},
     shield::{config::ShieldConfig, shield},
-    Middleware, MiddlewareType,
+    AroundMiddleware, Middleware, MiddlewareType,
 };
 use crate::req::HttpRequest;
 use crate::res::HttpResponse;
-use crate::types::MiddlewareHandler;
+use crate::types::{AroundNext, HttpMethods, MiddlewareHandler, RouterFns};
 use crate::{app::App, next::Next};
 
 #[cfg(feature = "with-wynd")]
@@ -241,6 +248,55 @@ impl App {
         self
     }
 
+    /// Adds a middleware that wraps the route handler.
+    ///
+    /// The middleware receives the request and `next`, the rest of the chain.
+    /// Calling `next(req)` runs the remaining around middlewares and the route
+    /// handler and resolves to their response, so the code before and after it
+    /// sees the request and the response in one scope. Returning without calling
+    /// `next` skips the handler. This suits timing, auth and transaction
+    /// middlewares that must wrap the handler symmetrically.
+    ///
+    /// Around middlewares run after every pre-middleware and before every
+    /// post-middleware. Among themselves, the first one registered is the
+    /// outermost. They only run for requests matched by a route.
+    ///
+    /// ## Arguments
+    ///
+    /// * `path` - Optional path prefix where the middleware should apply. If `None`, defaults to "/" (all paths)
+    /// * `middleware` - The middleware function that receives `(HttpRequest, AroundNext)` and returns a future
+    ///   resolving to the [`HttpResponse`] to send.
+    ///
+    /// ## Example
+    ///
+    /// ```
+    /// use ripress::app::App;
+    /// use std::time::Instant;
+    ///
+    /// let mut app = App::new();
+    ///
+    /// app.use_around(None, |req, next| async move {
+    ///     let path = req.path.clone();
+    ///     let started = Instant::now();
+    ///     let res = next(req).await;
+    ///     println!("{} took {:?}", path, started.elapsed());
+    ///     res
+    /// });
+    /// ```
+    pub fn use_around<F, Fut, P>(&mut self, path: P, middleware: F) -> &mut Self
+    where
+        P: Into<Option<&'static str>>,
+        F: Fn(HttpRequest, AroundNext) -> Fut + Send + Sync + 'static,
+        Fut: std::future::Future<Output = HttpResponse> + Send + 'static,
+    {
+        let path = path.into().unwrap_or("/").to_string();
+        self.around_middlewares.push(Arc::new(AroundMiddleware {
+            func: Arc::new(move |req, next| Box::pin(middleware(req, next))),
+            path,
+        }));
+        self
+    }
+
     /// Adds a logger middleware to the application.
     ///
     /// The logger middleware logs incoming HTTP requests with configurable options.
@@ -280,7 +336,8 @@ impl App {
     /// - Logs to the `info` level
     /// - Includes HTTP method, path, and response status
     /// - Applied to all routes ("/")
-    /// - Executed as post-middleware (after route handling)
+    /// - Runs as post-middleware (after route handling), so `duration_ms` covers
+    ///   the time from receiving the request to the handler's response
     #[cfg(feature = "logger")]
     pub fn use_logger(&mut self, config: Option<LoggerConfig>) -> &mut Self {
         use crate::middlewares::logger::logger;
@@ -317,7 +374,7 @@ impl App {
     ///
     /// // Use custom CORS configuration
     /// app.use_cors(Some(CorsConfig {
-    ///     allowed_origin: "https://example.com",
+    ///     allowed_origins: "https://example.com".into(),
     ///     allowed_methods: "GET, POST, PUT, DELETE, OPTIONS, HEAD",
     ///     allowed_headers: "Content-Type, Authorization",
     ///     ..Default::default()
@@ -331,7 +388,16 @@ impl App {
     /// - Applied to all routes ("/")
     /// - Executed as pre-middleware
     /// - Automatically handles OPTIONS preflight requests
+    /// - `allow_credentials` is ignored, with a warning, when all origins are
+    ///   allowed; list the trusted origins to use credentials
     pub fn use_cors(&mut self, config: Option<CorsConfig>) -> &mut Self {
+        if config.as_ref().is_some_and(|config| {
+            config.allow_credentials && matches!(config.allowed_origins, OriginMatcher::Any)
+        }) {
+            tracing::warn!(
+                "CORS allow_credentials is ignored when any origin is allowed; list the trusted origins instead"
+            );
+        }
         self.middlewares.push(Arc::new(Middleware {
             func: Self::middleware_from_closure(cors(config)),
             path: "/".to_string(),
@@ -371,10 +437,15 @@ impl App {
     /// ## Behavior
     ///
     /// - Applied to all routes ("/")
-    /// - Executed as pre-middleware (before route processing)
+    /// - Enforced while the body is read from the connection: a `Content-Length`
+    ///   over the limit is refused up front, and a chunked or mislabelled body is
+    ///   cut off as soon as it goes over
+    /// - Also runs as pre-middleware, covering requests that don't come from a
+    ///   connection, such as those sent with [`TestClient`](crate::app::test_client::TestClient)
     /// - Returns 413 Payload Too Large for requests exceeding the limit
     /// - Does not affect GET requests or requests without bodies
     pub fn use_body_limit(&mut self, config: Option<usize>) -> &mut Self {
+        self.settings.body_limit = Some(config.unwrap_or(DEFAULT_BODY_LIMIT));
         self.middlewares.push(Arc::new(Middleware {
             func: Self::middleware_from_closure(body_limit(config)),
             path: "/".to_string(),
@@ -519,9 +590,129 @@ impl App {
     /// - `X-RateLimit-Remaining`: Requests remaining in current window
     /// - `X-RateLimit-Reset`: Time when the rate limit window resets
     pub fn use_rate_limiter(&mut self, config: Option<RateLimiterConfig>) -> &mut Self {
+        self.use_rate_limiter_on(None, config)
+    }
+
+    /// Adds a rate limiting middleware scoped to a path prefix.
+    ///
+    /// Works like [`App::use_rate_limiter`], but only requests whose path matches
+    /// `path` (using the same prefix matching as [`App::use_pre_middleware`]) are
+    /// counted and limited. Every call tracks clients independently, so different
+    /// parts of the application can have different limits.
+    ///
+    /// ## Arguments
+    ///
+    /// * `path` - Optional path prefix the limit applies to. If `None`, defaults to "/" (all paths)
+    /// * `config` - Optional [`RateLimiterConfig`] for this path. If `None`, default settings are used.
+    ///
+    /// ## Example
+    ///
+    /// ```no_run
+    /// use ripress::app::App;
+    /// use ripress::middlewares::rate_limiter::RateLimiterConfig;
+    /// use std::time::Duration;
+    ///
+    /// let mut app = App::new();
+    ///
+    /// // Tight limit for an expensive endpoint
+    /// app.use_rate_limiter_on(Some("/api/search"), Some(RateLimiterConfig {
+    ///     max_requests: 5,
+    ///     window_ms: Duration::from_secs(60),
+    ///     ..Default::default()
+    /// }));
+    ///
+    /// // Loose limit for health checks
+    /// app.use_rate_limiter_on(Some("/api/health"), Some(RateLimiterConfig {
+    ///     max_requests: 1000,
+    ///     window_ms: Duration::from_secs(60),
+    ///     ..Default::default()
+    /// }));
+    /// ```
+    ///
+    /// ## Default Behavior
+    ///
+    /// - Executed as pre-middleware
+    /// - Limits from overlapping prefixes (including a global [`App::use_rate_limiter`]) all apply
+    pub fn use_rate_limiter_on<P>(
+        &mut self,
+        path: P,
+        config: Option<RateLimiterConfig>,
+    ) -> &mut Self
+    where
+        P: Into<Option<&'static str>>,
+    {
+        let path = path.into().unwrap_or("/").to_string();
         self.middlewares.push(Arc::new(Middleware {
             func: Self::middleware_from_closure(rate_limiter(config)),
-            path: "/".to_string(),
+            path,
+            middleware_type: MiddlewareType::Pre,
+        }));
+        self
+    }
+
+    /// Adds an HTTP Basic authentication middleware to the application.
+    ///
+    /// Requests must carry an `Authorization: Basic` header whose credentials the
+    /// verifier accepts. Anything else is answered with `401 Unauthorized` and a
+    /// `WWW-Authenticate` challenge for `realm`, so browsers prompt for a login.
+    ///
+    /// ## Arguments
+    ///
+    /// * `users` - A [`BasicAuthVerifier`]: a `HashMap` of usernames to passwords, or an
+    ///   async closure taking the username and password and returning whether they are valid
+    /// * `realm` - Name of the protected area, shown in the browser's login dialog
+    ///
+    /// ## Example
+    ///
+    /// ```
+    /// use std::collections::HashMap;
+    ///
+    /// use ripress::app::App;
+    ///
+    /// let mut app = App::new();
+    /// let users = HashMap::from([("admin".to_string(), "s3cret".to_string())]);
+    /// app.use_basic_auth(users, "Internal tools");
+    /// ```
+    ///
+    /// ## Default Behavior
+    ///
+    /// - Applied to all routes ("/"); use [`App::use_basic_auth_on`] to protect a prefix
+    /// - Executed as pre-middleware
+    /// - Passwords in a `HashMap` are compared in constant time
+    /// - Malformed headers (bad Base64, non-UTF-8 credentials, no `:`) are rejected with `401`
+    pub fn use_basic_auth<V>(&mut self, users: V, realm: &str) -> &mut Self
+    where
+        V: BasicAuthVerifier,
+    {
+        self.use_basic_auth_on(None, users, realm)
+    }
+
+    /// Adds an HTTP Basic authentication middleware scoped to a path prefix.
+    ///
+    /// Works like [`App::use_basic_auth`], but only requests whose path matches
+    /// `path` (using the same prefix matching as [`App::use_pre_middleware`]) must
+    /// authenticate.
+    ///
+    /// ## Example
+    ///
+    /// ```
+    /// use std::collections::HashMap;
+    ///
+    /// use ripress::app::App;
+    ///
+    /// let mut app = App::new();
+    /// let users = HashMap::from([("admin".to_string(), "s3cret".to_string())]);
+    /// app.use_basic_auth_on(Some("/admin"), users, "Admin");
+    /// ```
+    pub fn use_basic_auth_on<P, V>(&mut self, path: P, users: V, realm: &str) -> &mut Self
+    where
+        P: Into<Option<&'static str>>,
+        V: BasicAuthVerifier,
+    {
+        let path = path.into().unwrap_or("/").to_string();
+        self.middlewares.push(Arc::new(Middleware {
+            func: Self::middleware_from_closure(basic_auth(Arc::new(users), realm)),
+            path,
             middleware_type: MiddlewareType::Pre,
         }));
         self
@@ -588,6 +779,88 @@ impl App {
         self
     }
 
+    /// Adds an ETag middleware to the application.
+    ///
+    /// The ETag middleware computes a validator from each successful `GET`/`HEAD` response
+    /// body, sets it as the `ETag` header, and replies with `304 Not Modified` (and no body)
+    /// when the client's `If-None-Match` header already carries that tag.
+    ///
+    /// ## Arguments
+    ///
+    /// * `config` - Optional [`EtagConfig`] to customize tag generation.
+    ///   If `None`, weak ETags are generated.
+    ///
+    /// ## Example
+    ///
+    /// ```
+    /// use ripress::app::App;
+    /// use ripress::middlewares::etag::EtagConfig;
+    ///
+    /// let mut app = App::new();
+    ///
+    /// // Weak ETags (recommended for dynamic responses)
+    /// app.use_etag(None);
+    ///
+    /// // Strong ETags
+    /// app.use_etag(Some(EtagConfig { weak: false }));
+    /// ```
+    ///
+    /// ## Default Behavior
+    ///
+    /// - Applied to all routes ("/")
+    /// - Executed as post-middleware
+    /// - Only `2xx` responses to `GET` and `HEAD` requests are tagged
+    /// - An `ETag` header set by the handler is kept and used for comparison
+    pub fn use_etag(&mut self, config: Option<EtagConfig>) -> &mut Self {
+        self.middlewares.push(Arc::new(Middleware {
+            func: Self::middleware_from_closure(etag(config)),
+            path: "/".to_string(),
+            middleware_type: MiddlewareType::Post,
+        }));
+        self
+    }
+
+    /// Adds a request ID middleware to the application.
+    ///
+    /// Every request gets a correlation ID, taken from the incoming `X-Request-Id`
+    /// header or generated as a UUID. Handlers read it with
+    /// [`HttpRequest::request_id`], the response echoes it, and the logger includes it.
+    ///
+    /// ## Arguments
+    ///
+    /// * `config` - Optional [`RequestIdConfig`] to customize the header name and whether
+    ///   incoming IDs are trusted. If `None`, `X-Request-Id` is used and incoming IDs are reused.
+    ///
+    /// ## Example
+    ///
+    /// ```
+    /// use ripress::{app::App, req::HttpRequest, types::RouterFns};
+    ///
+    /// let mut app = App::new();
+    /// app.use_request_id(None);
+    ///
+    /// app.get("/", |req: HttpRequest, res| async move {
+    ///     let id = req.request_id().unwrap_or_default();
+    ///     res.ok().text(format!("request {}", id))
+    /// });
+    /// ```
+    ///
+    /// ## Default Behavior
+    ///
+    /// - Applied to all routes ("/")
+    /// - Executed as pre-middleware; register it first so short-circuited responses carry the ID
+    #[cfg(feature = "request-id")]
+    pub fn use_request_id(&mut self, config: Option<RequestIdConfig>) -> &mut Self {
+        use crate::middlewares::request_id::request_id;
+
+        self.middlewares.push(Arc::new(Middleware {
+            func: Self::middleware_from_closure(request_id(config)),
+            path: "/".to_string(),
+            middleware_type: MiddlewareType::Pre,
+        }));
+        self
+    }
+
     /// Adds a compression middleware to the application.
     ///
     /// Compression middleware automatically compresses response bodies using algorithms
@@ -646,11 +919,119 @@ impl App {
         self
     }
 
+    /// Adds Prometheus metrics to the application.
+    ///
+    /// Every request is counted and timed, labeled by method and by the pattern of the
+    /// route it matched (e.g. `/users/:id`), and a `GET` route serving the metrics in
+    /// the Prometheus text format is registered.
+    ///
+    /// ## Arguments
+    ///
+    /// * `config` - Optional [`MetricsConfig`] to customize the scrape path and latency
+    ///   buckets. If `None`, metrics are served on `/metrics` with the default buckets.
+    ///
+    /// ## Example
+    ///
+    /// ```
+    /// use ripress::{app::App, req::HttpRequest, types::RouterFns};
+    ///
+    /// let mut app = App::new();
+    /// app.use_metrics(None);
+    ///
+    /// app.get("/users/:id", |_req: HttpRequest, res| async move { res.ok() });
+    /// // Scraping /metrics now reports
+    /// // http_requests_total{method="GET",path="/users/:id"} <count>
+    /// ```
+    ///
+    /// ## Default Behavior
+    ///
+    /// - Metrics are served on `/metrics`, and scrapes are recorded like any other request
+    /// - Requests that matched no route are labeled `path="<unmatched>"`
+    /// - Calling it again replaces the previous metrics and configuration
+    pub fn use_metrics(&mut self, config: Option<MetricsConfig>) -> &mut Self {
+        let config = config.unwrap_or_default();
+        let metrics = Arc::new(Metrics::new(&config.buckets));
+        self.settings.metrics = Some(Arc::clone(&metrics));
+
+        self.add_route(HttpMethods::GET, &config.path, move |_req, res| {
+            let body = metrics.render();
+            async move {
+                res.ok()
+                    .text(body)
+                    .set_header("Content-Type", "text/plain; version=0.0.4; charset=utf-8")
+            }
+        });
+        self
+    }
+
+    /// Adds cookie-based sessions to the application.
+    ///
+    /// Each client gets a random session ID in a signed, `HttpOnly` cookie, and
+    /// handlers read and change its data through [`HttpRequest::session`]. Changes
+    /// are saved to the store, and the cookie is set, once the response is ready.
+    ///
+    /// ## Arguments
+    ///
+    /// * `config` - Optional [`SessionConfig`] with the signing secret, cookie
+    ///   attributes, expiry and store. If `None`, sessions are kept in memory and
+    ///   signed with a random secret, so they don't survive a restart.
+    ///
+    /// ## Example
+    ///
+    /// ```
+    /// use ripress::{app::App, req::HttpRequest, types::RouterFns};
+    ///
+    /// let mut app = App::new();
+    /// app.use_session(None);
+    ///
+    /// app.post("/login", |req: HttpRequest, res| async move {
+    ///     // Issue a fresh ID on login so a planted one can't be reused
+    ///     req.session().regenerate();
+    ///     req.session().set("uid", 42).unwrap();
+    ///     res.ok()
+    /// });
+    ///
+    /// app.get("/me", |req: HttpRequest, res| async move {
+    ///     match req.session().get::<u64>("uid") {
+    ///         Some(uid) => res.ok().json(uid),
+    ///         None => res.unauthorized(),
+    ///     }
+    /// });
+    /// ```
+    ///
+    /// ## Default Behavior
+    ///
+    /// - Applied to all routes ("/")
+    /// - Executed as pre-middleware, which loads the session from the store; register
+    ///   it before middlewares that use the session
+    /// - A session is only saved, and the cookie only set, once it holds data
+    /// - Sessions expire 24 hours after they were last changed
+    /// - Calling it again replaces the previous configuration
+    ///
+    /// # Panics
+    ///
+    /// Panics if the configured secret is shorter than
+    /// [`MIN_SECRET_LEN`](crate::middlewares::session::MIN_SECRET_LEN) bytes.
+    #[cfg(feature = "session")]
+    pub fn use_session(&mut self, config: Option<SessionConfig>) -> &mut Self {
+        use crate::middlewares::session::{session, Sessions};
+
+        let sessions = Arc::new(Sessions::new(config.unwrap_or_default()));
+        if self.settings.sessions.replace(sessions).is_none() {
+            self.middlewares.push(Arc::new(Middleware {
+                func: Self::middleware_from_closure(session()),
+                path: "/".to_string(),
+                middleware_type: MiddlewareType::Pre,
+            }));
+        }
+        self
+    }
+
     /// Converts a closure into a middleware handler function.
     ///
     /// This is an internal helper method that wraps user-provided middleware functions
     /// into the expected format for the middleware system.
-    fn middleware_from_closure<F, Fut>(f: F) -> MiddlewareHandler
+    pub(crate) fn middleware_from_closure<F, Fut>(f: F) -> MiddlewareHandler
     where
         F: Fn(HttpRequest, HttpResponse, Next) -> Fut + Send + Sync + 'static,
         Fut: std::future::Future<Output = (HttpRequest, Option<HttpResponse>)> + Send + 'static,
@@ -35,35 +35,77 @@
 
 #![warn(missing_docs)]
 
-use crate::app::{api_error::ApiError, settings::Http2Config};
-use std::cell::RefCell;
+use crate::app::{
+    api_error::{error_response, ApiError},
+    connection::{ConnectionConfig, ConnectionLimiter, ConnectionTimeouts, ReceivedAt},
+    settings::{
+        ErrorFormat, Http2Config, JsonErrorDetail, MultipartLimits, RuntimeConfig, StaticMounts,
+        StaticOptions, TrustProxy,
+    },
+};
 
 use crate::{
-    helpers::{exec_post_middleware, exec_pre_middleware},
-    middlewares::{Middleware, MiddlewareType},
-    req::HttpRequest,
-    res::HttpResponse,
-    router::Router,
-    types::{HttpMethods, RouterFns, Routes},
+    helpers::{exec_post_middleware, exec_pre_middleware, parse_quality_list, path_matches},
+    middlewares::{body_limit::BodyLimits, AroundMiddleware, Middleware, MiddlewareType},
+    req::{request_data::RequestData, HttpRequest},
+    res::{conversions::StreamedBody, HttpResponse},
+    router::{
+        join_paths,
+        route_pattern::{MatchedPath, RouteMatch, RouteTable},
+        Router,
+    },
+    types::{
+        AroundNext, HttpMethods, RouteBodyLimits, RouteNames, RouterFns, Routes, StreamedBodyRoutes,
+    },
 };
 use bytes::Bytes;
-use http_body_util::{BodyExt, Full};
-use hyper::{header, http::StatusCode, Method, Request, Response};
-use hyper_staticfile::Static;
-use routerify_ng::{ext::RequestExt, RouterService};
+use http_body_util::Full;
+use hyper::{body::Body, header, http::StatusCode, Method, Request, Response};
+use hyper_staticfile::{AcceptEncoding, ResolveResult, Resolver};
+use routerify_ng::RouterService;
 use settings::AppSettings;
-use std::{collections::HashMap, net::SocketAddr, path::Path, sync::Arc};
+use static_files::{
+    accepted_precompressed, file_response, sanitize_static_path, strip_multi_range,
+};
+use std::{
+    collections::{HashMap, HashSet},
+    future::Future,
+    io,
+    path::Path,
+    sync::Arc,
+    time::Duration,
+};
 use tokio::net::TcpListener;
 
+#[cfg(feature = "openapi")]
+use crate::types::RouteDocs;
+
 pub(crate) mod api_error;
 
+pub(crate) mod connection;
+
 mod h2;
+/// Liveness and readiness probe endpoints, for [`App::health`] and [`App::readiness`].
+pub mod health;
+/// OpenAPI documents generated from the registered routes, for [`App::openapi`].
+#[cfg(feature = "openapi")]
+pub mod openapi;
 /// Handler module for managing server connections, HTTP/2/1 serving logic, and connection-level configuration.
 pub mod handler;
 /// Middleware support for the App struct, including common and user-defined middleware functionality.
 pub mod middlewares;
 /// Module for defining the settings of the App Struct.
 pub mod settings;
+/// Shared state registered with [`App::state`] and the [`State`](state::State) extractor.
+pub mod state;
+pub(crate) mod static_files;
+/// In-memory test client for dispatching requests to an App without a server.
+pub mod test_client;
+/// TLS configuration for serving HTTPS with [`App::listen_tls`].
+#[cfg(feature = "tls")]
+pub mod tls;
+#[cfg(unix)]
+pub(crate) mod unix;
 
 /// The App struct is the core of Ripress, providing a simple interface for creating HTTP servers and handling requests.
 ///
@@ -113,8 +155,14 @@ pub mod settings;
 /// }
 /// ```
 pub struct App {
-    routes: Routes,
+    pub(crate) routes: Routes,
+    pub(crate) route_names: RouteNames,
+    pub(crate) route_body_limits: RouteBodyLimits,
+    pub(crate) streamed_body_routes: StreamedBodyRoutes,
+    #[cfg(feature = "openapi")]
+    pub(crate) route_docs: RouteDocs,
     pub(crate) middlewares: Vec<Arc<Middleware>>,
+    pub(crate) around_middlewares: Vec<Arc<AroundMiddleware>>,
     pub(crate) settings: AppSettings,
 }
 
@@ -122,6 +170,23 @@ impl RouterFns for App {
     fn routes(&mut self) -> &mut Routes {
         &mut self.routes
     }
+
+    fn route_names(&mut self) -> &mut RouteNames {
+        &mut self.route_names
+    }
+
+    fn route_body_limits(&mut self) -> &mut RouteBodyLimits {
+        &mut self.route_body_limits
+    }
+
+    fn streamed_body_routes(&mut self) -> &mut StreamedBodyRoutes {
+        &mut self.streamed_body_routes
+    }
+
+    #[cfg(feature = "openapi")]
+    fn route_docs(&mut self) -> &mut RouteDocs {
+        &mut self.route_docs
+    }
 }
 
 impl App {
@@ -140,7 +205,13 @@ impl App {
     pub fn new() -> Self {
         App {
             routes: HashMap::new(),
+            route_names: HashMap::new(),
+            route_body_limits: HashMap::new(),
+            streamed_body_routes: HashSet::new(),
+            #[cfg(feature = "openapi")]
+            route_docs: HashMap::new(),
             middlewares: Vec::new(),
+            around_middlewares: Vec::new(),
             settings: AppSettings::default(),
         }
     }
@@ -151,11 +222,12 @@ impl App {
     /// By default, the server binds to `"0.0.0.0"` (all interfaces). You may want to bind to
     /// `"127.0.0.1"` (localhost only) or an external IP for remote access, depending on your deployment requirements.
     ///
-    /// **Note:** If you use an empty string (`""`), the server may not bind properly. Use valid IPv4 or IPv6 addresses.
+    /// **Note:** If you use an empty string (`""`), the server may not bind properly. Use valid IPv4 or IPv6
+    /// addresses, or a hostname such as `"localhost"`, which binds the first address it resolves to.
     ///
     /// # Arguments
     ///
-    /// * `host` - The host address (e.g., `"127.0.0.1"`, `"0.0.0.0"`, or an IPv6 address like `"::1"`).
+    /// * `host` - The host address (e.g., `"127.0.0.1"`, `"0.0.0.0"`, or an IPv6 address like `"::1"`, without brackets).
     ///
     /// # Examples
     ///
@@ -192,10 +264,334 @@ impl App {
     ///     });
     /// ```
     pub fn http2_config(&mut self, config: Http2Config) -> &mut Self {
+        match (config.keep_alive_while_idle, config.keep_alive_interval) {
+            (Some(true), None) => tracing::warn!(
+                "Http2Config::keep_alive_while_idle has no effect without keep_alive_interval"
+            ),
+            (Some(false), Some(_)) => tracing::warn!(
+                "Http2Config::keep_alive_while_idle can't be disabled, idle HTTP/2 connections are pinged every keep_alive_interval"
+            ),
+            _ => {}
+        }
         self.settings.http2_config = config;
         self
     }
 
+    /// Sets how errors generated by the framework are rendered.
+    ///
+    /// Covers the router's own responses (`404`, `405`, a `400` for an unparseable
+    /// body, unhandled `500`s) and rejections from built-in middlewares like the
+    /// body limit. Defaults to [`ErrorFormat::Text`].
+    ///
+    /// ## Example
+    ///
+    /// ```
+    /// use ripress::app::{settings::ErrorFormat, App};
+    ///
+    /// let mut app = App::new();
+    /// app.use_body_limit(Some(1024));
+    ///
+    /// // A body over 1 KiB now gets `{ "error": "..." }` with status 413
+    /// app.error_format(ErrorFormat::Json);
+    /// ```
+    pub fn error_format(&mut self, format: ErrorFormat) -> &mut Self {
+        self.settings.error_format = format;
+        self
+    }
+
+    /// Sets whether route paths are matched case-sensitively. Defaults to `true`.
+    ///
+    /// When disabled, the literal parts of a route ignore ASCII case, so
+    /// `/Users` matches `/users`. Params keep the case the client sent, and
+    /// middleware and static file prefixes are still case-sensitive.
+    ///
+    /// ## Example
+    ///
+    /// ```
+    /// use ripress::{app::App, req::HttpRequest, types::RouterFns};
+    ///
+    /// let mut app = App::new();
+    /// app.case_sensitive_routing(false);
+    ///
+    /// // `GET /USERS/Alice` is handled here, with `id` = "Alice"
+    /// app.get("/users/:id", |req: HttpRequest, res| async move {
+    ///     let id = req.params.get("id").unwrap_or_default().to_string();
+    ///     res.ok().text(id)
+    /// });
+    /// ```
+    pub fn case_sensitive_routing(&mut self, enabled: bool) -> &mut Self {
+        self.settings.case_sensitive_routing = enabled;
+        self
+    }
+
+    /// Sets how many labels at the end of the host name form the domain, for
+    /// [`HttpRequest::subdomains`](crate::req::HttpRequest::subdomains).
+    ///
+    /// Defaults to `2`, which suits hosts like `example.com`. Use `3` for
+    /// domains such as `example.co.uk`.
+    ///
+    /// ## Example
+    ///
+    /// ```
+    /// use ripress::{app::App, req::HttpRequest, types::RouterFns};
+    ///
+    /// let mut app = App::new();
+    /// app.subdomain_offset(3);
+    ///
+    /// // `tenant.example.co.uk` gives `["tenant"]`
+    /// app.get("/", |req: HttpRequest, res| async move {
+    ///     let tenant = req.subdomains().first().map(|s| s.to_string());
+    ///     res.ok().text(tenant.unwrap_or_default())
+    /// });
+    /// ```
+    pub fn subdomain_offset(&mut self, offset: usize) -> &mut Self {
+        self.settings.request.subdomain_offset = offset;
+        self
+    }
+
+    /// Sets the limits applied while parsing `multipart/form-data` bodies.
+    ///
+    /// See [`MultipartLimits`] for the defaults and the responses sent when a
+    /// limit is exceeded.
+    ///
+    /// ## Example
+    ///
+    /// ```
+    /// use ripress::app::{settings::MultipartLimits, App};
+    ///
+    /// let mut app = App::new();
+    /// app.multipart_limits(MultipartLimits {
+    ///     max_fields: 50,
+    ///     ..Default::default()
+    /// });
+    /// ```
+    pub fn multipart_limits(&mut self, limits: MultipartLimits) -> &mut Self {
+        self.settings.request.multipart_limits = limits;
+        self
+    }
+
+    /// Sets how much detail JSON deserialization errors expose.
+    ///
+    /// With [`JsonErrorDetail::Terse`], [`HttpRequest::json`](crate::req::HttpRequest::json)
+    /// and the `JsonBody` extractor report just `Invalid JSON`, and the full error
+    /// is logged instead. Defaults to [`JsonErrorDetail::Full`].
+    ///
+    /// ## Example
+    ///
+    /// ```
+    /// use ripress::app::{settings::JsonErrorDetail, App};
+    ///
+    /// let mut app = App::new();
+    /// if std::env::var("APP_ENV").as_deref() == Ok("production") {
+    ///     app.json_error_detail(JsonErrorDetail::Terse);
+    /// }
+    /// ```
+    pub fn json_error_detail(&mut self, level: JsonErrorDetail) -> &mut Self {
+        self.settings.request.json_error_detail = level;
+        self
+    }
+
+    /// Registers a value that handlers can extract with [`State<T>`](state::State).
+    ///
+    /// Values are keyed by type, so registering a second value of the same type
+    /// replaces the first. Each request gets its own clone of the value, so share
+    /// mutable data through an `Arc`.
+    ///
+    /// ## Example
+    ///
+    /// ```
+    /// use std::sync::{Arc, Mutex};
+    ///
+    /// use ripress::{app::{state::State, App}, types::RouterFns};
+    ///
+    /// #[derive(Clone, Default)]
+    /// struct Db(Arc<Mutex<Vec<String>>>);
+    ///
+    /// let mut app = App::new();
+    /// app.state(Db::default());
+    ///
+    /// app.get("/posts", |State(db): State<Db>, res| async move {
+    ///     let count = db.0.lock().unwrap().len();
+    ///     res.ok().text(count.to_string())
+    /// });
+    /// ```
+    pub fn state<T: Clone + Send + Sync + 'static>(&mut self, value: T) -> &mut Self {
+        self.settings.state.insert(value);
+        self
+    }
+
+    /// Registers a value that every request can read with
+    /// [`HttpRequest::extension`](crate::req::HttpRequest::extension).
+    ///
+    /// Values are keyed by type and shared by all requests, which only get a
+    /// reference, so they are read-only once the app is serving. Values that
+    /// are `Clone` can also be extracted with [`State<T>`](state::State).
+    ///
+    /// ## Example
+    ///
+    /// ```
+    /// use std::sync::Arc;
+    ///
+    /// use ripress::app::App;
+    ///
+    /// struct HttpClient;
+    ///
+    /// let mut app = App::new();
+    /// app.extension(Arc::new(HttpClient));
+    /// ```
+    pub fn extension<T: Send + Sync + 'static>(&mut self, value: T) -> &mut Self {
+        self.settings.state.insert(value);
+        self
+    }
+
+    /// Sets which peers are trusted to report the original client, host and
+    /// scheme through `X-Forwarded-*` headers.
+    ///
+    /// Trusted forwarding headers are used for [`HttpRequest::ip`], the
+    /// request's `protocol` and `origin_url`, and [`HttpRequest::hostname`].
+    /// Until this is called every peer may report the client address and
+    /// scheme, but the host and port in `origin_url` come from the request
+    /// itself; see [`TrustProxy`] for the options.
+    ///
+    /// [`HttpRequest::ip`]: crate::req::HttpRequest::ip
+    /// [`HttpRequest::hostname`]: crate::req::HttpRequest::hostname
+    ///
+    /// ## Example
+    ///
+    /// ```
+    /// use ripress::app::{settings::TrustProxy, App};
+    ///
+    /// let mut app = App::new();
+    ///
+    /// // Only the load balancer on the private network may set `X-Forwarded-*`
+    /// app.trust_proxy(TrustProxy::peers(["10.0.0.0/8"]).unwrap());
+    /// ```
+    pub fn trust_proxy(&mut self, trust: TrustProxy) -> &mut Self {
+        self.settings.trust_proxy = Some(Arc::new(trust));
+        self
+    }
+
+    /// Limits how long a route handler may run.
+    ///
+    /// A handler still running after `limit` is dropped, cancelling whatever it was
+    /// awaiting, and the client gets `503 Service Unavailable` rendered with the
+    /// app's [`ErrorFormat`]. Tasks the handler spawned itself keep running.
+    /// Middlewares are not counted towards the limit.
+    ///
+    /// ## Example
+    ///
+    /// ```
+    /// use ripress::app::App;
+    /// use std::time::Duration;
+    ///
+    /// let mut app = App::new();
+    /// app.use_timeout(Duration::from_secs(30));
+    /// ```
+    pub fn use_timeout(&mut self, limit: Duration) -> &mut Self {
+        self.settings.handler_timeout = Some(limit);
+        self
+    }
+
+    /// Sets per-connection timeouts, protecting against clients that hold
+    /// connections open by trickling bytes.
+    ///
+    /// - `read`: time allowed from the first byte of an HTTP/1 request until its
+    ///   headers are complete. A client sending its headers one byte at a time is
+    ///   disconnected once this passes.
+    /// - `write`: time a write may stay blocked because the client isn't reading.
+    /// - `idle`: time a keep-alive connection may wait for its next request.
+    ///
+    /// `None` disables a timeout; all three are disabled by default. Time spent in
+    /// handlers never counts, and upgraded (WebSocket) connections are exempt, so
+    /// pair this with [`App::use_timeout`] to bound slow handlers.
+    ///
+    /// ## Example
+    ///
+    /// ```
+    /// use ripress::app::App;
+    /// use std::time::Duration;
+    ///
+    /// let mut app = App::new();
+    /// app.connection_timeouts(
+    ///     Some(Duration::from_secs(10)),
+    ///     Some(Duration::from_secs(30)),
+    ///     Some(Duration::from_secs(75)),
+    /// );
+    /// ```
+    pub fn connection_timeouts(
+        &mut self,
+        read: Option<Duration>,
+        write: Option<Duration>,
+        idle: Option<Duration>,
+    ) -> &mut Self {
+        self.settings.connection_timeouts = ConnectionTimeouts { read, write, idle };
+        self
+    }
+
+    /// Sets how long an HTTP/1 connection is kept open waiting for its next request.
+    ///
+    /// `Some(limit)` keeps connections alive and closes them once they have been
+    /// idle for `limit`. `None` turns keep-alive off, so every response carries
+    /// `Connection: close` and the connection is closed after it is sent.
+    ///
+    /// By default connections are kept alive with no idle limit. When an idle
+    /// timeout is also set with [`App::connection_timeouts`], the shorter applies.
+    ///
+    /// ## Example
+    ///
+    /// ```
+    /// use ripress::app::App;
+    /// use std::time::Duration;
+    ///
+    /// let mut app = App::new();
+    /// app.keep_alive(Some(Duration::from_secs(5)));
+    /// ```
+    pub fn keep_alive(&mut self, idle: Option<Duration>) -> &mut Self {
+        self.settings.keep_alive = idle.is_some();
+        self.settings.keep_alive_idle = idle;
+        self
+    }
+
+    /// Closes an HTTP/1 connection after it has served `max` requests, so
+    /// long-lived clients are spread across servers behind a load balancer.
+    ///
+    /// The last response carries `Connection: close`. `None`, the default, allows
+    /// any number of requests. HTTP/2 connections are not limited.
+    ///
+    /// ## Example
+    ///
+    /// ```
+    /// use ripress::app::App;
+    ///
+    /// let mut app = App::new();
+    /// app.max_requests_per_connection(Some(1000));
+    /// ```
+    pub fn max_requests_per_connection(&mut self, max: Option<usize>) -> &mut Self {
+        self.settings.max_requests_per_connection = max;
+        self
+    }
+
+    /// Caps the connections a single client address may hold open at once,
+    /// protecting against clients that exhaust the server by opening many idle
+    /// connections.
+    ///
+    /// A connection over the limit is closed as soon as it is accepted, before
+    /// any of it is read. The limit applies to the TCP peer address, so clients
+    /// behind the same proxy share it. It doesn't apply to [`App::listen_unix`].
+    ///
+    /// ## Example
+    ///
+    /// ```
+    /// use ripress::app::App;
+    ///
+    /// let mut app = App::new();
+    /// app.max_connections_per_ip(100);
+    /// ```
+    pub fn max_connections_per_ip(&mut self, max: usize) -> &mut Self {
+        self.settings.max_connections_per_ip = Some(max);
+        self
+    }
+
     /// Enables graceful shutdown for the application.
     ///
     /// When graceful shutdown is enabled, the server will listen for a shutdown signal
@@ -223,6 +619,10 @@ impl App {
     /// or splitting logic into modules. The router's routes are incorporated into the main
     /// application's route table, and will take precedence over static file handlers.
     ///
+    /// Middleware attached to the router is scoped to its base path and appended to the
+    /// application's middleware chain here, so it runs after middleware registered on the
+    /// app before this call and before middleware registered after it.
+    ///
     /// # Example
     /// ```
     /// use ripress::{app::App, router::Router};
@@ -251,7 +651,7 @@ impl App {
     ///
     /// This method does not panic.
     pub fn router(&mut self, mut router: Router) {
-        let base_path = router.base_path;
+        let base_path = std::mem::take(&mut router.base_path);
         for (path, methods) in router.routes() {
             for (method, handler) in methods.to_owned() {
                 if path == "/" {
@@ -266,6 +666,175 @@ impl App {
                 }
             }
         }
+
+        for (name, path) in std::mem::take(router.route_names()) {
+            let full_path = if path == "/" {
+                base_path.clone()
+            } else {
+                format!("{}{}", base_path, path)
+            };
+            self.route_names.insert(name, full_path);
+        }
+
+        for ((path, method), limit) in std::mem::take(router.route_body_limits()) {
+            let full_path = if path == "/" {
+                base_path.clone()
+            } else {
+                format!("{}{}", base_path, path)
+            };
+            self.route_body_limits.insert((full_path, method), limit);
+        }
+
+        for (path, method) in std::mem::take(router.streamed_body_routes()) {
+            let full_path = if path == "/" {
+                base_path.clone()
+            } else {
+                format!("{}{}", base_path, path)
+            };
+            self.streamed_body_routes.insert((full_path, method));
+        }
+
+        #[cfg(feature = "openapi")]
+        for ((path, method), operation) in std::mem::take(router.route_docs()) {
+            let full_path = if path == "/" {
+                base_path.clone()
+            } else {
+                format!("{}{}", base_path, path)
+            };
+            self.route_docs.insert((full_path, method), operation);
+        }
+
+        for mut middleware in router.middlewares {
+            middleware.path = join_paths(&base_path, &middleware.path);
+            self.middlewares.push(Arc::new(middleware));
+        }
+    }
+
+    /// Mounts a sub-application under a path prefix.
+    ///
+    /// Unlike [`App::router`], a sub-app brings its own middleware stack and static
+    /// file mounts. Its routes, middleware and static mounts are re-registered on this
+    /// application with `prefix` prepended, so the sub-app's middleware only runs for
+    /// requests under `prefix` and never for sibling routes. Like router middleware, it
+    /// is appended to this application's chain at the point of the call.
+    ///
+    /// Server-level settings of the sub-app, such as its host, HTTP/2 configuration,
+    /// graceful shutdown and WebSocket handler, are ignored.
+    ///
+    /// # Example
+    /// ```
+    /// use ripress::{app::App, req::HttpRequest, types::RouterFns};
+    ///
+    /// let mut blog = App::new();
+    /// blog.use_pre_middleware(None, |req: HttpRequest, res, next| async move {
+    ///     println!("blog request: {}", req.path);
+    ///     next.call(req, res).await
+    /// });
+    /// blog.get("/posts/:slug", |req: HttpRequest, res| async move {
+    ///     let slug = req.params.get("slug").unwrap_or_default().to_string();
+    ///     res.ok().text(slug)
+    /// });
+    ///
+    /// let mut app = App::new();
+    /// app.mount("/blog", blog); // serves GET /blog/posts/:slug
+    /// ```
+    ///
+    /// # Arguments
+    ///
+    /// * `prefix` - The path the sub-app is mounted at, e.g. `"/blog"`.
+    /// * `sub_app` - The application to mount.
+    pub fn mount(&mut self, prefix: &str, sub_app: App) {
+        for (path, methods) in sub_app.routes {
+            let full_path = join_paths(prefix, &path);
+            let entry = self.routes.entry(full_path).or_default();
+            for (method, handler) in methods {
+                entry.insert(method, handler);
+            }
+        }
+
+        for (name, path) in sub_app.route_names {
+            self.route_names.insert(name, join_paths(prefix, &path));
+        }
+
+        for ((path, method), limit) in sub_app.route_body_limits {
+            self.route_body_limits
+                .insert((join_paths(prefix, &path), method), limit);
+        }
+
+        for (path, method) in sub_app.streamed_body_routes {
+            self.streamed_body_routes
+                .insert((join_paths(prefix, &path), method));
+        }
+
+        #[cfg(feature = "openapi")]
+        for ((path, method), operation) in sub_app.route_docs {
+            self.route_docs
+                .insert((join_paths(prefix, &path), method), operation);
+        }
+
+        for middleware in sub_app.middlewares {
+            let mut middleware = Middleware::clone(&middleware);
+            middleware.path = join_paths(prefix, &middleware.path);
+            self.middlewares.push(Arc::new(middleware));
+        }
+
+        for middleware in sub_app.around_middlewares {
+            let mut middleware = AroundMiddleware::clone(&middleware);
+            middleware.path = join_paths(prefix, &middleware.path);
+            self.around_middlewares.push(Arc::new(middleware));
+        }
+
+        for (mount_path, static_mount) in sub_app.settings.static_files {
+            self.settings
+                .static_files
+                .insert(join_paths(prefix, &mount_path), static_mount);
+        }
+    }
+
+    /// Returns every registered route as `(method, pattern)`, sorted by pattern
+    /// and then method.
+    ///
+    /// Routes added through [`App::router`] and [`App::mount`] are listed with
+    /// their full path. Static file mounts are listed as `GET` routes ending in
+    /// `/*`, e.g. `/assets/*`.
+    ///
+    /// ## Example
+    ///
+    /// ```
+    /// use ripress::{app::App, req::HttpRequest, router::Router, types::RouterFns};
+    ///
+    /// let mut api = Router::new("/api");
+    /// api.post("/users", |_req: HttpRequest, res| async move { res.created() });
+    ///
+    /// let mut app = App::new();
+    /// app.get("/", |_req: HttpRequest, res| async move { res.ok() });
+    /// app.router(api);
+    /// app.static_files("/assets", "./public").unwrap();
+    ///
+    /// for (method, pattern) in app.route_list() {
+    ///     println!("{:<7} {}", method, pattern);
+    /// }
+    /// ```
+    pub fn route_list(&self) -> Vec<(HttpMethods, String)> {
+        let routes = self.routes.iter().flat_map(|(path, methods)| {
+            methods
+                .keys()
+                .map(move |method| (method.clone(), path.clone()))
+        });
+        let static_mounts = self.settings.static_files.keys().map(|mount| {
+            let pattern = if mount == "/" {
+                "/*".to_string()
+            } else {
+                format!("{}/*", mount.trim_end_matches('/'))
+            };
+            (HttpMethods::GET, pattern)
+        });
+
+        let mut list: Vec<_> = routes.chain(static_mounts).collect();
+        list.sort_by(|(a_method, a_path), (b_method, b_path)| {
+            a_path.cmp(b_path).then_with(|| a_method.cmp(b_method))
+        });
+        list
     }
 
     /// Configures static file serving for the application.
@@ -320,6 +889,8 @@ impl App {
     /// - **Fallback Serving**: When mounted at "/", static files serve as fallback for unmatched routes
     /// - **MIME Types**: Automatically sets appropriate `Content-Type` headers based on file extensions
     /// - **Caching**: Includes `Cache-Control` and `ETag` headers for efficient browser caching
+    /// - **Range Requests**: Honors single `Range: bytes=...` requests with `206 Partial Content`,
+    ///   answers unsatisfiable ranges with `416`, and serves multi-range requests in full
     /// - **Security**: Prevents directory traversal attacks and blocks serving from filesystem root
     ///
     /// ## File System Layout Example
@@ -344,9 +915,58 @@ impl App {
     /// - Consider using a reverse proxy like nginx for serving static files in production
     pub fn static_files(
         &mut self,
-        path: &'static str,
-        file: &'static str,
+        path: impl Into<String>,
+        file: impl Into<String>,
+    ) -> Result<(), &'static str> {
+        self.static_files_with_options(path, file, StaticOptions::default())
+    }
+
+    /// Configures static file serving with custom caching options.
+    ///
+    /// Works like [`App::static_files`], but lets each mount choose its own
+    /// `Cache-Control` policy and whether `Last-Modified` is sent. This is useful
+    /// when fingerprinted assets should be cached forever while HTML entry points
+    /// must always be revalidated.
+    ///
+    /// ## Arguments
+    ///
+    /// * `path` - The URL path where static files should be mounted
+    /// * `file` - The filesystem directory path containing the static files
+    /// * `options` - The [`StaticOptions`] applied to every file served from this mount
+    ///
+    /// ## Errors
+    ///
+    /// Returns the same validation errors as [`App::static_files`].
+    ///
+    /// ## Example
+    ///
+    /// ```
+    /// use ripress::app::{App, settings::StaticOptions};
+    ///
+    /// let mut app = App::new();
+    ///
+    /// // Hashed asset filenames: cache for a year, never revalidate
+    /// app.static_files_with_options("/assets", "./dist/assets", StaticOptions {
+    ///     max_age: 31536000,
+    ///     immutable: true,
+    ///     ..Default::default()
+    /// }).unwrap();
+    ///
+    /// // index.html and friends: always revalidate, and serve index.html
+    /// // for client-side routes such as /dashboard/settings
+    /// app.static_files_with_options("/", "./dist", StaticOptions {
+    ///     no_cache: true,
+    ///     spa_fallback: Some("index.html"),
+    ///     ..Default::default()
+    /// }).unwrap();
+    /// ```
+    pub fn static_files_with_options(
+        &mut self,
+        path: impl Into<String>,
+        file: impl Into<String>,
+        options: StaticOptions,
     ) -> Result<(), &'static str> {
+        let (path, file) = (path.into(), file.into());
         if file == "/" {
             return Err("Serving from filesystem root '/' is not allowed for security reasons");
         }
@@ -359,7 +979,9 @@ impl App {
         if !path.starts_with('/') {
             return Err("Mount path must start with '/'");
         }
-        self.settings.static_files.insert(path, file);
+        self.settings
+            .static_files
+            .insert(path, (file, options));
         Ok(())
     }
 
@@ -461,6 +1083,159 @@ impl App {
     /// - Configure reverse proxy (nginx, Apache) for production
     /// - Enable logging middleware to monitor requests
     pub async fn listen<F: FnOnce()>(&self, port: u16, cb: F) {
+        let router_service = self.build_router_service();
+        cb();
+        self.accept_connections(port, router_service, self.connection_config())
+            .await;
+    }
+
+    /// Builds a multi-threaded Tokio runtime from `runtime` and runs
+    /// [`App::listen`] on it, blocking the calling thread until the server stops.
+    ///
+    /// This is for a plain `fn main` that wants to choose the worker count. Apps
+    /// that already run inside a runtime, e.g. under `#[tokio::main]`, should
+    /// call [`App::listen`] instead: calling `run` from within a runtime returns
+    /// an error rather than nesting a second one.
+    ///
+    /// ## Example
+    ///
+    /// ```no_run
+    /// use ripress::app::{settings::RuntimeConfig, App};
+    /// use ripress::{req::HttpRequest, res::HttpResponse, types::RouterFns};
+    ///
+    /// fn main() -> std::io::Result<()> {
+    ///     let mut app = App::new();
+    ///     app.get("/", |_req: HttpRequest, res: HttpResponse| async move {
+    ///         res.ok().text("Hello")
+    ///     });
+    ///
+    ///     app.run(RuntimeConfig { workers: 4 }, 3000, || {
+    ///         println!("Server running on http://localhost:3000")
+    ///     })
+    /// }
+    /// ```
+    pub fn run<F: FnOnce()>(&self, runtime: RuntimeConfig, port: u16, cb: F) -> io::Result<()> {
+        if tokio::runtime::Handle::try_current().is_ok() {
+            return Err(io::Error::other(
+                "App::run was called from within a Tokio runtime, use App::listen instead",
+            ));
+        }
+
+        tokio::runtime::Builder::new_multi_thread()
+            .worker_threads(runtime.workers.max(1))
+            .enable_all()
+            .build()?
+            .block_on(self.listen(port, cb));
+        Ok(())
+    }
+
+    /// Starts an HTTPS server on the specified port, terminating TLS with `rustls`.
+    ///
+    /// Behaves like [`App::listen`], except every accepted connection completes
+    /// a TLS handshake before any HTTP is read. ALPN advertises `h2` and
+    /// `http/1.1`, or only `h2` when [`Http2Config::http2_only`] is set.
+    /// Requests served this way report
+    /// `req.protocol == "https"` and `req.is_secure() == true`.
+    ///
+    /// A handshake that fails, or outlasts the read timeout set with
+    /// [`App::connection_timeouts`], closes the connection.
+    ///
+    /// Requires the `tls` feature.
+    ///
+    /// ## Example
+    ///
+    /// ```no_run
+    /// use ripress::app::{tls::TlsConfig, App};
+    /// use ripress::req::HttpRequest;
+    /// use ripress::types::RouterFns;
+    ///
+    /// #[tokio::main]
+    /// async fn main() {
+    ///     let mut app = App::new();
+    ///
+    ///     app.get("/", |req: HttpRequest, res| async move {
+    ///         res.ok().text(format!("secure: {}", req.is_secure()))
+    ///     });
+    ///
+    ///     let tls = TlsConfig::from_pem_files("cert.pem", "key.pem").unwrap();
+    ///     app.listen_tls(443, tls, || println!("Server running on https://localhost"))
+    ///         .await;
+    /// }
+    /// ```
+    #[cfg(feature = "tls")]
+    pub async fn listen_tls<F: FnOnce()>(&self, port: u16, tls: tls::TlsConfig, cb: F) {
+        let router_service = self.build_router_service();
+        let mut config = self.connection_config();
+        config.tls = Some(tls.acceptor(&self.settings.http2_config));
+        cb();
+        self.accept_connections(port, router_service, config).await;
+    }
+
+    /// Starts the server on a Unix domain socket instead of a TCP port.
+    ///
+    /// Meant for running behind a reverse proxy on the same host, e.g. nginx
+    /// with `proxy_pass http://unix:/run/app.sock;`. Requests are served exactly
+    /// as with [`App::listen`]; since the peer has no IP address, put the proxy
+    /// in charge of `X-Forwarded-For` and `X-Forwarded-Proto`.
+    ///
+    /// A socket file left behind by a previous run is removed before binding.
+    /// Binding fails if `path` is a socket another process still accepts on,
+    /// or any other kind of file. The socket file is removed again when the
+    /// server stops after a graceful shutdown.
+    ///
+    /// Only available on Unix platforms.
+    ///
+    /// ## Example
+    ///
+    /// ```no_run
+    /// use ripress::app::App;
+    /// use ripress::req::HttpRequest;
+    /// use ripress::types::RouterFns;
+    ///
+    /// #[tokio::main]
+    /// async fn main() {
+    ///     let mut app = App::new();
+    ///
+    ///     app.get("/", |_req: HttpRequest, res| async move { res.ok().text("Hello") });
+    ///
+    ///     app.listen_unix("/run/ripress.sock", || println!("Listening on /run/ripress.sock"))
+    ///         .await;
+    /// }
+    /// ```
+    #[cfg(unix)]
+    pub async fn listen_unix<F: FnOnce()>(&self, path: impl AsRef<Path>, cb: F) {
+        let path = path.as_ref();
+        let builder = routerify_ng::RequestServiceBuilder::new(self.build_router()).unwrap();
+        let builder = std::sync::Mutex::new(builder);
+        cb();
+
+        let listener = match unix::bind(path) {
+            Ok(listener) => listener,
+            Err(e) => {
+                eprintln!("Error binding to socket {}: {}", path.display(), e);
+                return;
+            }
+        };
+        let _socket_file = unix::SocketFile::new(path);
+        let listener = &listener;
+        let config = self.connection_config();
+
+        self.serve_connections(
+            move || async move { listener.accept().await.map(|(stream, _)| stream) },
+            |stream| {
+                let request_service = builder.lock().unwrap().build(unix::UNIX_PEER_ADDR);
+                Self::serve_connection(stream, None, request_service, config.clone())
+            },
+        )
+        .await;
+    }
+
+    pub(crate) fn build_router_service(&self) -> Arc<RouterService<ApiError>> {
+        Arc::new(RouterService::new(self.build_router()).unwrap())
+    }
+
+    /// Builds the router with all configured routes, middleware and static file handlers.
+    fn build_router(&self) -> routerify_ng::Router<ApiError> {
         let mut router = routerify_ng::Router::<ApiError>::builder();
 
         #[cfg(feature = "with-wynd")]
@@ -490,60 +1265,105 @@ impl App {
             }
         }
 
-        for (path, methods) in &self.routes {
-            for (method, handler) in methods {
-                let handler = Arc::clone(handler);
-
-                let method = match method {
-                    HttpMethods::GET => Method::GET,
-                    HttpMethods::POST => Method::POST,
-                    HttpMethods::PUT => Method::PUT,
-                    HttpMethods::DELETE => Method::DELETE,
-                    HttpMethods::PATCH => Method::PATCH,
-                    HttpMethods::HEAD => Method::HEAD,
-                    HttpMethods::OPTIONS => Method::OPTIONS,
-                };
+        let route_table = Arc::new(RouteTable::new(
+            &self.routes,
+            self.settings.case_sensitive_routing,
+        ));
+        let static_mounts = Arc::new(self.settings.static_files.clone());
+        let around: Arc<[Arc<AroundMiddleware>]> = self.around_middlewares.clone().into();
+        let timeout = self.settings.handler_timeout;
+
+        if !self.settings.case_sensitive_routing {
+            // `routerify_ng` compares paths case-sensitively, so every request is
+            // handed to `route_request`, which matches it against the route table.
+            let methods: Vec<Method> = route_table
+                .methods()
+                .iter()
+                .filter_map(|method| Method::try_from(method).ok())
+                .collect();
+            let route_table = Arc::clone(&route_table);
+            let static_mounts = Arc::clone(&static_mounts);
+            let around = Arc::clone(&around);
+            router = router.add("/*", methods, move |req| {
+                let route_table = Arc::clone(&route_table);
+                let static_mounts = Arc::clone(&static_mounts);
+                let around = Arc::clone(&around);
+                async move {
+                    Self::route_request(req, &route_table, &static_mounts, &around, timeout).await
+                }
+            });
+        }
+
+        let mut registered = HashSet::new();
 
-                router = router.add(path, vec![method], move |mut req| {
-                    let handler = Arc::clone(&handler);
+        for entry in route_table.entries() {
+            let path = entry.pattern.routerify_path();
+            // GET routes also answer HEAD; `route_request` falls back to the GET handler.
+            let implicit_head = entry
+                .methods
+                .iter()
+                .any(|(method, _)| *method == HttpMethods::GET)
+                .then_some(&HttpMethods::HEAD);
+            let methods = entry.methods.iter().map(|(method, _)| method);
+            for method in methods.chain(implicit_head) {
+                // Routes differing only in param names or constraints share a
+                // routerify path; the route table picks the handler.
+                if !registered.insert((path.clone(), method.clone())) {
+                    continue;
+                }
 
+                // `add_route` refuses invalid extension methods, so this only
+                // skips routes inserted into the table directly.
+                let Ok(method) = Method::try_from(method) else {
+                    continue;
+                };
+
+                let route_table = Arc::clone(&route_table);
+                let static_mounts = Arc::clone(&static_mounts);
+                let around = Arc::clone(&around);
+                router = router.add(path.clone(), vec![method], move |req| {
+                    let route_table = Arc::clone(&route_table);
+                    let static_mounts = Arc::clone(&static_mounts);
+                    let around = Arc::clone(&around);
                     async move {
-                        let mut our_req = match HttpRequest::from_hyper_request(&mut req).await {
-                            Ok(r) => r,
-                            Err(e) => {
-                                return Err(ApiError::Generic(
-                                    HttpResponse::new().bad_request().text(e.to_string()),
-                                ));
-                            }
-                        };
-
-                        req.params().iter().for_each(|(key, value)| {
-                            our_req.set_param(key, value);
-                        });
-
-                        let mut response = handler(our_req, HttpResponse::new()).await;
-
-                        let _ = crate::next::PENDING_HEADERS.try_with(|pending| {
-                            for (k, v) in pending.borrow_mut().drain(..) {
-                                response = std::mem::take(&mut response).set_header(k, v);
-                            }
-                        });
-                        let _ = crate::next::PENDING_COOKIES.try_with(|pending| {
-                            for cookie in pending.borrow_mut().drain(..) {
-                                response = std::mem::take(&mut response).set_cookie_raw(cookie);
-                            }
-                        });
-
-                        let hyper_response = response.to_hyper_response().await;
-                        Ok(hyper_response.unwrap())
+                        Self::route_request(req, &route_table, &static_mounts, &around, timeout)
+                            .await
                     }
                 });
             }
         }
 
-        for (mount_path, serve_from) in self.settings.static_files.iter() {
-            let serve_from = (*serve_from).to_string();
-            let mount_root = (*mount_path).to_string();
+        // The remaining methods of every path reach `route_request` too, so it can
+        // answer `405 Method Not Allowed` and `OPTIONS` with an `Allow` header.
+        let paths: HashSet<String> = registered.iter().map(|(path, _)| path.clone()).collect();
+        for path in paths {
+            let methods: Vec<Method> = route_table
+                .methods()
+                .into_iter()
+                .filter(|method| !registered.contains(&(path.clone(), method.clone())))
+                .filter_map(|method| Method::try_from(&method).ok())
+                .collect();
+            if methods.is_empty() {
+                continue;
+            }
+
+            let route_table = Arc::clone(&route_table);
+            let static_mounts = Arc::clone(&static_mounts);
+            let around = Arc::clone(&around);
+            router = router.add(path, methods, move |req| {
+                let route_table = Arc::clone(&route_table);
+                let static_mounts = Arc::clone(&static_mounts);
+                let around = Arc::clone(&around);
+                async move {
+                    Self::route_request(req, &route_table, &static_mounts, &around, timeout).await
+                }
+            });
+        }
+
+        for (mount_path, (serve_from, options)) in self.settings.static_files.iter() {
+            let serve_from = serve_from.clone();
+            let mount_root = mount_path.clone();
+            let options = options.clone();
 
             let route_pattern_owned = if mount_root == "/" {
                 "/*".to_string()
@@ -557,41 +1377,138 @@ impl App {
             router = router.get(route_pattern_owned, move |req| {
                 let serve_from = serve_from_clone.clone();
                 let mount_root = mount_root_clone.clone();
+                let options = options.clone();
                 async move {
-                    match Self::serve_static_with_headers(req, mount_root, serve_from).await {
+                    match Self::serve_static_with_options(req, mount_root, serve_from, &options)
+                        .await
+                    {
                         Ok(res) => Ok(res),
-                        Err(e) => Err(ApiError::Generic(
-                            HttpResponse::new()
-                                .internal_server_error()
-                                .text(e.to_string()),
-                        )),
+                        Err(e) => Err(ApiError::Generic(error_response(
+                            HttpResponse::new(),
+                            500,
+                            &e.to_string(),
+                        ))),
                     }
                 }
             });
         }
 
+        // Unmatched requests would get routerify's empty 404; `route_request`
+        // renders it in the configured error format instead.
+        router = router.any(move |req| {
+            let route_table = Arc::clone(&route_table);
+            let static_mounts = Arc::clone(&static_mounts);
+            let around = Arc::clone(&around);
+            async move {
+                Self::route_request(req, &route_table, &static_mounts, &around, timeout).await
+            }
+        });
+
         router = router.err_handler(Self::error_handler);
-        let router = router.build().unwrap();
-        cb();
+        router.build().unwrap()
+    }
 
-        let addr = format!("{}:{}", self.settings.host, port)
-            .parse::<SocketAddr>()
-            .unwrap();
+    /// The app-wide body limit along with the routes overriding it.
+    pub(crate) fn body_limits(&self) -> BodyLimits {
+        let routes = RouteTable::new(&self.routes, self.settings.case_sensitive_routing);
+        BodyLimits::new(
+            self.settings.body_limit,
+            self.route_body_limits.clone(),
+            self.streamed_body_routes.clone(),
+            Arc::new(routes),
+        )
+    }
+
+    pub(crate) fn connection_config(&self) -> ConnectionConfig {
+        ConnectionConfig {
+            http2_config: self.settings.http2_config.clone(),
+            error_format: self.settings.error_format.clone(),
+            timeouts: ConnectionTimeouts {
+                idle: match (
+                    self.settings.connection_timeouts.idle,
+                    self.settings.keep_alive_idle,
+                ) {
+                    (Some(a), Some(b)) => Some(a.min(b)),
+                    (a, b) => a.or(b),
+                },
+                ..self.settings.connection_timeouts
+            },
+            keep_alive: self.settings.keep_alive,
+            max_requests: self.settings.max_requests_per_connection,
+            request: self.settings.request,
+            trust_proxy: self.settings.trust_proxy.clone(),
+            body_limits: Arc::new(self.body_limits()),
+            metrics: self.settings.metrics.clone(),
+            state: self.settings.state.clone(),
+            #[cfg(feature = "session")]
+            sessions: self.settings.sessions.clone(),
+            #[cfg(feature = "tls")]
+            tls: None,
+        }
+    }
 
-        let listener = TcpListener::bind(addr).await;
+    /// Binds `port` and serves TCP connections on it.
+    async fn accept_connections(
+        &self,
+        port: u16,
+        router_service: Arc<RouterService<ApiError>>,
+        config: ConnectionConfig,
+    ) {
+        // Binding the `(host, port)` pair accepts bare IPv6 addresses such as
+        // `::1` and hostnames such as `localhost`.
+        let host = self.settings.host.as_str();
+        let listener = TcpListener::bind((host, port)).await;
 
         if let Err(e) = listener {
-            eprintln!("Error binding to address {}: {}", addr, e);
+            eprintln!("Error binding to host {} on port {}: {}", host, port, e);
             return;
         }
 
         let listener = listener.unwrap();
+        let listener = &listener;
+        let limiter = self
+            .settings
+            .max_connections_per_ip
+            .map(ConnectionLimiter::new);
+        let limiter = &limiter;
 
-        let router_service = Arc::new(RouterService::new(router).unwrap());
-
-        let http2_enabled = self.settings.http2_config.is_enabled;
-        let http2_config = self.settings.http2_config.clone();
+        self.serve_connections(
+            move || async move {
+                loop {
+                    let (stream, peer) = listener.accept().await?;
+                    let Some(limiter) = limiter else {
+                        return Ok((stream, None));
+                    };
+                    match limiter.acquire(peer.ip()) {
+                        Some(permit) => return Ok((stream, Some(permit))),
+                        // Dropping the stream closes it without waiting on the client.
+                        None => tracing::debug!("Refused connection from {}: too many open", peer),
+                    }
+                }
+            },
+            |(stream, permit)| {
+                let connection =
+                    Self::handle_connection(stream, Arc::clone(&router_service), config.clone());
+                async move {
+                    connection.await;
+                    drop(permit);
+                }
+            },
+        )
+        .await;
+    }
 
+    /// Serves connections from `accept` until the process ends or, with
+    /// graceful shutdown enabled, Ctrl+C is received.
+    ///
+    /// Each accepted stream is handed to `handle` on its own task.
+    async fn serve_connections<S, A, AFut, H, HFut>(&self, accept: A, handle: H)
+    where
+        A: Fn() -> AFut,
+        AFut: Future<Output = io::Result<S>>,
+        H: Fn(S) -> HFut,
+        HFut: Future<Output = ()> + Send + 'static,
+    {
         let mut shutdown = if self.settings.graceful_shutdown {
             Some(Box::pin(tokio::signal::ctrl_c()))
         } else {
@@ -601,31 +1518,19 @@ impl App {
         loop {
             let accept_result = if let Some(ref mut sig) = shutdown {
                 tokio::select! {
-                    result = listener.accept() => Some(result),
+                    result = accept() => Some(result),
                     _ = sig.as_mut() => None,
                 }
             } else {
-                Some(listener.accept().await)
+                Some(accept().await)
             };
 
             match accept_result {
-                Some(Ok((stream, _))) => {
-                    let service = Arc::clone(&router_service);
-                    let http2_config = http2_config.clone();
-
-                    tokio::task::spawn(async move {
-                        crate::next::PENDING_HEADERS.scope(
-                            RefCell::new(Vec::new()),
-                            crate::next::PENDING_COOKIES.scope(
-                                RefCell::new(Vec::new()),
-                                Self::handle_connection(stream, service, http2_enabled, http2_config),
-                            ),
-                        )
-                        .await;
-                    });
+                Some(Ok(stream)) => {
+                    tokio::task::spawn(handle(stream));
                 }
                 Some(Err(e)) => {
-                    eprintln!("Error accepting connection: {}", e);
+                    tracing::warn!("Error accepting connection: {}", e);
                 }
                 None => {
                     break;
@@ -634,6 +1539,207 @@ impl App {
         }
     }
 
+    /// Routes a request to the most specific matching handler.
+    ///
+    /// Falls back to the static mounts when no route matches. If routes exist for
+    /// the path under other methods, `OPTIONS` gets `204 No Content` and anything
+    /// else `405 Method Not Allowed`, both with an `Allow` header listing them.
+    /// Otherwise the response is `404 Not Found`, e.g. because a param constraint
+    /// rejected the path.
+    ///
+    /// `around` wraps the matched handler; see [`App::use_around`]. `timeout`
+    /// bounds the handler's run time; see [`App::use_timeout`].
+    ///
+    /// The [`RequestData`] set by pre-middlewares and the time the request was
+    /// received are attached to the response, so post-middlewares see them even
+    /// though `RequestInfo` doesn't carry them.
+    pub(crate) async fn route_request(
+        req: Request<Full<Bytes>>,
+        route_table: &RouteTable,
+        static_mounts: &StaticMounts,
+        around: &[Arc<AroundMiddleware>],
+        timeout: Option<Duration>,
+    ) -> Result<Response<Full<Bytes>>, ApiError> {
+        let data = req.extensions().get::<RequestData>().cloned();
+        let received_at = req.extensions().get::<ReceivedAt>().copied();
+        let mut res =
+            Self::route_to_handler(req, route_table, static_mounts, around, timeout).await?;
+        if let Some(data) = data {
+            res.extensions_mut().insert(data);
+        }
+        if let Some(received_at) = received_at {
+            res.extensions_mut().insert(received_at);
+        }
+        Ok(res)
+    }
+
+    async fn route_to_handler(
+        req: Request<Full<Bytes>>,
+        route_table: &RouteTable,
+        static_mounts: &StaticMounts,
+        around: &[Arc<AroundMiddleware>],
+        timeout: Option<Duration>,
+    ) -> Result<Response<Full<Bytes>>, ApiError> {
+        let path = req.uri().path().to_string();
+        let method = HttpMethods::from(req.method());
+
+        if let Some(route) = route_table.find(&method, &path) {
+            return Self::run_route_handler(req, route, around, timeout).await;
+        }
+
+        if method == HttpMethods::HEAD {
+            if let Some(route) = route_table.find(&HttpMethods::GET, &path) {
+                let res = Self::run_route_handler(req, route, around, timeout).await?;
+                return Ok(Self::strip_body(res));
+            }
+        }
+
+        if let Some(result) = Self::serve_static_mount(req, static_mounts, &method, &path).await {
+            return result;
+        }
+
+        let allowed = route_table.allowed_methods(&path);
+        let res = if allowed.is_empty() {
+            error_response(HttpResponse::new(), 404, "Not Found")
+        } else {
+            let allow = allowed
+                .iter()
+                .map(ToString::to_string)
+                .collect::<Vec<_>>()
+                .join(", ");
+            if method == HttpMethods::OPTIONS {
+                HttpResponse::new().no_content().set_header("Allow", allow)
+            } else {
+                let res = HttpResponse::new().set_header("Allow", allow);
+                error_response(res, 405, "Method Not Allowed")
+            }
+        };
+        Ok(res.to_hyper_response().await.unwrap())
+    }
+
+    /// Turns a GET response into a HEAD response by dropping the body.
+    ///
+    /// `Content-Length` is set to the length the body would have had, unless the
+    /// handler set it already, the body is streamed or the status never carries a body.
+    fn strip_body(res: Response<Full<Bytes>>) -> Response<Full<Bytes>> {
+        let (mut parts, body) = res.into_parts();
+        let status = parts.status;
+        let has_body = !(status.is_informational()
+            || status == StatusCode::NO_CONTENT
+            || status == StatusCode::NOT_MODIFIED);
+        // A stream's length is unknown unless the handler declared it.
+        let streamed = parts.extensions.remove::<StreamedBody>().is_some();
+
+        if has_body && !streamed && !parts.headers.contains_key(header::CONTENT_LENGTH) {
+            let len = body.size_hint().exact().unwrap_or(0);
+            parts
+                .headers
+                .insert(header::CONTENT_LENGTH, header::HeaderValue::from(len));
+        }
+
+        Response::from_parts(parts, Full::new(Bytes::new()))
+    }
+
+    /// Serves `req` from the longest static mount covering `path`, if any.
+    async fn serve_static_mount(
+        req: Request<Full<Bytes>>,
+        static_mounts: &StaticMounts,
+        method: &HttpMethods,
+        path: &str,
+    ) -> Option<Result<Response<Full<Bytes>>, ApiError>> {
+        if *method != HttpMethods::GET {
+            return None;
+        }
+
+        let (mount_root, (serve_from, options)) = static_mounts
+            .iter()
+            .filter(|(mount, _)| path_matches(mount, path))
+            .max_by_key(|(mount, _)| mount.len())?;
+
+        let result = Self::serve_static_with_options(
+            req,
+            mount_root.to_string(),
+            serve_from.to_string(),
+            options,
+        )
+        .await
+        .map_err(|e| ApiError::Generic(error_response(HttpResponse::new(), 500, &e.to_string())));
+        Some(result)
+    }
+
+    /// Runs a matched route handler and converts its response for hyper.
+    ///
+    /// `params` are the route parameters extracted by the router. The around
+    /// middlewares whose path covers the request wrap the handler, first one
+    /// outermost. Headers and cookies queued by pre-middlewares through
+    /// [`Next`](crate::next::Next) are applied to the resulting response. A
+    /// handler still running after `timeout` is dropped and answered with
+    /// `503 Service Unavailable`, which the around middlewares see.
+    pub(crate) async fn run_route_handler(
+        mut req: Request<Full<Bytes>>,
+        route: RouteMatch,
+        around: &[Arc<AroundMiddleware>],
+        timeout: Option<Duration>,
+    ) -> Result<Response<Full<Bytes>>, ApiError> {
+        let path = req.uri().path().to_string();
+        let mut our_req = match HttpRequest::from_hyper_request(&mut req).await {
+            Ok(r) => r,
+            Err(ApiError::Generic(res)) => return Err(ApiError::Generic(res)),
+            Err(e) => {
+                return Err(ApiError::Generic(error_response(
+                    HttpResponse::new(),
+                    400,
+                    &e.to_string(),
+                )));
+            }
+        };
+
+        for (key, value) in &route.params {
+            our_req.set_param(key, value);
+        }
+        our_req.matched_path = Some(route.pattern.clone());
+
+        let handler = route.handler;
+        let mut chain: AroundNext = Box::new(move |req| {
+            let handler_future = handler(req, HttpResponse::new());
+            Box::pin(async move {
+                match timeout {
+                    Some(limit) => match tokio::time::timeout(limit, handler_future).await {
+                        Ok(response) => response,
+                        Err(_) => error_response(HttpResponse::new(), 503, "Request timed out"),
+                    },
+                    None => handler_future.await,
+                }
+            })
+        });
+        for middleware in around.iter().rev() {
+            if !path_matches(&middleware.path, &path) {
+                continue;
+            }
+            let func = Arc::clone(&middleware.func);
+            let next = chain;
+            chain = Box::new(move |req| func(req, next));
+        }
+        let mut response = chain(our_req).await;
+
+        let _ = crate::next::PENDING_HEADERS.try_with(|pending| {
+            for (k, v) in pending.borrow_mut().drain(..) {
+                response = std::mem::take(&mut response).set_header(k, v);
+            }
+        });
+        let _ = crate::next::PENDING_COOKIES.try_with(|pending| {
+            for cookie in pending.borrow_mut().drain(..) {
+                response = std::mem::take(&mut response).set_cookie_raw(cookie);
+            }
+        });
+
+        let mut hyper_response = response.to_hyper_response().await.unwrap();
+        hyper_response
+            .extensions_mut()
+            .insert(MatchedPath(route.pattern));
+        Ok(hyper_response)
+    }
+
     /// Internal error handler for the router.
     ///
     /// This method processes routing errors and converts them into appropriate HTTP responses.
@@ -642,23 +1748,37 @@ impl App {
         err: routerify_ng::RouteError,
     ) -> Response<Full<hyper::body::Bytes>> {
         let api_err = err.downcast::<ApiError>().unwrap_or_else(|_| {
-            return Box::new(ApiError::Generic(
-                HttpResponse::new()
-                    .internal_server_error()
-                    .text("Unhandled error"),
-            ));
+            return Box::new(ApiError::Generic(error_response(
+                HttpResponse::new(),
+                500,
+                "Unhandled error",
+            )));
         });
 
         match *api_err {
             ApiError::WebSocketUpgrade(response) => response,
-            ApiError::Generic(res) => {
-                let hyper_res = <HttpResponse as Clone>::clone(&res)
-                    .to_hyper_response()
+            ApiError::Generic(mut res) => {
+                // Headers and cookies queued by pre-middlewares that ran before the one
+                // that short-circuited still belong on this response.
+                let _ = crate::next::PENDING_HEADERS.try_with(|pending| {
+                    for (k, v) in pending.borrow_mut().drain(..) {
+                        if res.headers.get(&k).is_none() {
+                            res.headers.insert(k, v);
+                        }
+                    }
+                });
+                let _ = crate::next::PENDING_COOKIES.try_with(|pending| {
+                    for cookie in pending.borrow_mut().drain(..) {
+                        res = std::mem::take(&mut res).set_cookie_raw(cookie);
+                    }
+                });
+
+                // Moved rather than cloned: a clone would copy the body and drop
+                // a streamed one entirely.
+                res.to_hyper_response()
                     .await
                     .map_err(ApiError::from)
-                    .unwrap();
-
-                hyper_res
+                    .unwrap()
             }
         }
     }
@@ -668,6 +1788,10 @@ impl App {
     /// This method handles the complex logic of serving static files, including:
     /// - URL path rewriting to map mount points to filesystem paths
     /// - ETag-based conditional requests (304 Not Modified responses)
+    /// - Single `Range` requests (206 Partial Content, 416 Range Not Satisfiable)
+    /// - SPA fallback for unmatched HTML navigations (see [`StaticOptions::spa_fallback`])
+    /// - Generated directory indexes (see [`StaticOptions::directory_listing`])
+    /// - Pre-compressed `.br`/`.gz` siblings (see [`StaticOptions::precompressed`])
     /// - Proper caching headers
     /// - Error handling for missing files
     ///
@@ -679,13 +1803,31 @@ impl App {
     ///
     /// ## Returns
     ///
-    /// * `Ok(Response<Body>)` - Successfully served file or 304 Not Modified
+    /// * `Ok(Response<Body>)` - Successfully served file, partial content, or 304 Not Modified
     /// * `Err(std::io::Error)` - File not found or other I/O error
+    #[cfg(test)]
     pub(crate) async fn serve_static_with_headers<B>(
         req: Request<B>,
         mount_root: String,
         fs_root: String,
     ) -> Result<Response<Full<hyper::body::Bytes>>, std::io::Error>
+    where
+        B: hyper::body::Body<Data = hyper::body::Bytes> + Send + 'static,
+        B::Error: Into<Box<dyn std::error::Error + Send + Sync>>,
+    {
+        Self::serve_static_with_options(req, mount_root, fs_root, &StaticOptions::default()).await
+    }
+
+    /// Internal method for serving static files using the mount's [`StaticOptions`].
+    ///
+    /// Behaves like [`App::serve_static_with_headers`], with `Cache-Control` and
+    /// `Last-Modified` controlled by `options`.
+    pub(crate) async fn serve_static_with_options<B>(
+        req: Request<B>,
+        mount_root: String,
+        fs_root: String,
+        options: &StaticOptions,
+    ) -> Result<Response<Full<hyper::body::Bytes>>, std::io::Error>
     where
         B: hyper::body::Body<Data = hyper::body::Bytes> + Send + 'static,
         B::Error: Into<Box<dyn std::error::Error + Send + Sync>>,
@@ -693,11 +1835,6 @@ impl App {
         let (mut parts, body) = req.into_parts();
         let original_uri = parts.uri.clone();
         let original_path = original_uri.path();
-        let if_none_match = parts
-            .headers
-            .get(header::IF_NONE_MATCH)
-            .and_then(|v| v.to_str().ok())
-            .map(|s| s.to_string());
 
         let trimmed_path = if mount_root == "/" {
             original_path
@@ -727,7 +1864,7 @@ impl App {
         parts.uri = match new_path_and_query.parse() {
             Ok(uri) => uri,
             Err(e) => {
-                eprintln!(
+                tracing::debug!(
                     "Error parsing URI: {} (original: {}, mount_root: {}, trimmed: {}, normalized: {})",
                     e, original_path, mount_root, trimmed_path, normalized_path
                 );
@@ -738,48 +1875,57 @@ impl App {
             }
         };
 
+        strip_multi_range(&mut parts.headers);
+
         let rewritten_req = Request::from_parts(parts, body);
 
-        let static_service = Static::new(Path::new(fs_root.as_str()));
-
-        match static_service.serve(rewritten_req).await {
-            Ok(mut response) => {
-                response
-                    .headers_mut()
-                    .insert("Cache-Control", "public, max-age=86400".parse().unwrap());
-                response
-                    .headers_mut()
-                    .insert("X-Served-By", "hyper-staticfile".parse().unwrap());
-                if let Some(if_none_match_value) = if_none_match {
-                    if let Some(etag) = response.headers().get(header::ETAG) {
-                        if let Ok(etag_value) = etag.to_str() {
-                            if if_none_match_value == etag_value {
-                                let mut builder =
-                                    Response::builder().status(StatusCode::NOT_MODIFIED);
-                                if let Some(h) = builder.headers_mut() {
-                                    for (k, v) in response.headers().iter() {
-                                        h.insert(k.clone(), v.clone());
-                                    }
-                                    h.remove(header::CONTENT_LENGTH);
-                                }
-                                return Ok(builder.body(Full::from(Bytes::new())).unwrap());
-                            }
-                        }
-                    }
+        let mut resolver = Resolver::new(Path::new(fs_root.as_str()));
+        if options.precompressed {
+            resolver.allowed_encodings = accepted_precompressed(rewritten_req.headers());
+        }
+        let mut resolved = resolver.resolve_request(&rewritten_req).await?;
+
+        if matches!(resolved, ResolveResult::NotFound)
+            && options.directory_listing
+            && normalized_path.ends_with('/')
+        {
+            if let Some(relative) = sanitize_static_path(normalized_path) {
+                let dir = Path::new(fs_root.as_str()).join(relative);
+                if let Some(html) = Self::render_directory_listing(&dir, original_path).await? {
+                    return Ok(Response::builder()
+                        .status(StatusCode::OK)
+                        .header(header::CONTENT_TYPE, "text/html; charset=utf-8")
+                        .header(header::CACHE_CONTROL, options.cache_control())
+                        .body(Full::from(Bytes::from(html)))
+                        .unwrap());
                 }
-                let (parts, body) = response.into_parts();
-                let collected = body.collect().await.map_err(|e| {
-                    std::io::Error::new(
-                        std::io::ErrorKind::Other,
-                        format!("Failed to collect body: {}", e),
-                    )
-                })?;
-                let body_bytes = collected.to_bytes();
-                let full_body = Full::from(body_bytes);
-                Ok(Response::from_parts(parts, full_body))
             }
-            Err(e) => Err(e),
         }
+
+        if let (ResolveResult::NotFound, Some(fallback)) = (&resolved, options.spa_fallback) {
+            let accepts_html = rewritten_req
+                .headers()
+                .get(header::ACCEPT)
+                .and_then(|v| v.to_str().ok())
+                .map(|accept| {
+                    parse_quality_list(accept)
+                        .iter()
+                        .any(|(range, q)| *q > 0.0 && range.eq_ignore_ascii_case("text/html"))
+                })
+                .unwrap_or(false);
+
+            if accepts_html {
+                let fallback_path = format!("/{}", fallback.trim_start_matches('/'));
+                resolved = resolver
+                    .resolve_path(&fallback_path, AcceptEncoding::none())
+                    .await?;
+            }
+        }
+        let mut response = file_response(&rewritten_req, resolved, options).await?;
+        response
+            .headers_mut()
+            .insert("X-Served-By", "hyper-staticfile".parse().unwrap());
+        Ok(response)
     }
 
     /// Internal method for building a router instance.
@@ -0,0 +1,456 @@
+#![warn(missing_docs)]
+use schemars::{
+    generate::{SchemaGenerator, SchemaSettings},
+    JsonSchema, Schema,
+};
+use serde_json::{json, Map, Value};
+
+use crate::{
+    app::App,
+    req::{
+        body::json_data::{FromJson, JsonBody},
+        query_params::{FromQueryParam, QueryParam},
+        request_data::{Data, FromData},
+        request_headers::Headers,
+        route_params::{FromParams, Params},
+        HttpRequest,
+    },
+    router::route_pattern::RoutePattern,
+    types::HttpMethods,
+};
+
+/// Builds the schema of a type with the document's shared generator.
+type SchemaFn = fn(&mut SchemaGenerator) -> Schema;
+
+/// The OpenAPI docs of one route, attached with
+/// [`RouteHandle::openapi`](crate::router::route_handle::RouteHandle::openapi).
+///
+/// Params and bodies are described by types implementing
+/// [`schemars::JsonSchema`], usually the ones the handler extracts.
+///
+/// ## Example
+///
+/// ```
+/// use ripress::{
+///     app::{openapi::Operation, App},
+///     req::{body::json_data::JsonBody, route_params::Params},
+///     types::{HttpMethods, RouterFns},
+/// };
+/// use ripress_derive::{FromJson, FromParams};
+/// use schemars::JsonSchema;
+/// use serde::{Deserialize, Serialize};
+///
+/// #[derive(Deserialize, JsonSchema, FromParams)]
+/// struct UserId {
+///     id: u32,
+/// }
+///
+/// #[derive(Deserialize, Serialize, JsonSchema, FromJson)]
+/// struct User {
+///     name: String,
+/// }
+///
+/// let mut app = App::new();
+/// app.route(HttpMethods::PUT, "/users/:id")
+///     .openapi(
+///         Operation::new()
+///             .summary("Replace a user")
+///             .tag("users")
+///             .extractor::<(Params<UserId>, JsonBody<User>)>()
+///             .response::<User>(200, "The updated user")
+///             .empty_response(404, "No such user"),
+///     )
+///     .handler(|(_id, user): (Params<UserId>, JsonBody<User>), res| async move {
+///         res.ok().json(&*user)
+///     });
+/// ```
+#[derive(Clone, Debug, Default)]
+pub struct Operation {
+    summary: Option<String>,
+    description: Option<String>,
+    tags: Vec<String>,
+    path_params: Option<SchemaFn>,
+    query: Option<SchemaFn>,
+    json_body: Option<SchemaFn>,
+    responses: Vec<(u16, String, Option<SchemaFn>)>,
+}
+
+impl Operation {
+    /// Creates an operation with no docs.
+    pub fn new() -> Self {
+        Operation::default()
+    }
+
+    /// Sets a one-line summary of what the route does.
+    pub fn summary(mut self, summary: impl Into<String>) -> Self {
+        self.summary = Some(summary.into());
+        self
+    }
+
+    /// Sets a longer description, which may use Markdown.
+    pub fn description(mut self, description: impl Into<String>) -> Self {
+        self.description = Some(description.into());
+        self
+    }
+
+    /// Adds a tag, which Swagger UI uses to group routes.
+    pub fn tag(mut self, tag: impl Into<String>) -> Self {
+        self.tags.push(tag.into());
+        self
+    }
+
+    /// Documents what the extractor `P` reads from the request, e.g.
+    /// `Params<UserId>` or a tuple of extractors.
+    pub fn extractor<P: OpenApiExtractor>(self) -> Self {
+        P::document(self)
+    }
+
+    /// Documents the path params as the fields of `T`.
+    ///
+    /// Fields that aren't params of the route path are left out.
+    pub fn path_params<T: JsonSchema>(mut self) -> Self {
+        self.path_params = Some(SchemaGenerator::subschema_for::<T>);
+        self
+    }
+
+    /// Documents the query params as the fields of `T`; fields that aren't
+    /// `Option`s are required.
+    pub fn query<T: JsonSchema>(mut self) -> Self {
+        self.query = Some(SchemaGenerator::subschema_for::<T>);
+        self
+    }
+
+    /// Documents a required JSON request body of type `T`.
+    pub fn json_body<T: JsonSchema>(mut self) -> Self {
+        self.json_body = Some(SchemaGenerator::subschema_for::<T>);
+        self
+    }
+
+    /// Documents a response with a JSON body of type `T`.
+    pub fn response<T: JsonSchema>(mut self, status: u16, description: impl Into<String>) -> Self {
+        self.responses.push((
+            status,
+            description.into(),
+            Some(SchemaGenerator::subschema_for::<T>),
+        ));
+        self
+    }
+
+    /// Documents a response without a body.
+    pub fn empty_response(mut self, status: u16, description: impl Into<String>) -> Self {
+        self.responses.push((status, description.into(), None));
+        self
+    }
+}
+
+/// An extractor that can describe what it reads from a request, for
+/// [`Operation::extractor`].
+///
+/// Implemented for the built-in extractors whose inner type implements
+/// [`schemars::JsonSchema`], and for tuples of them.
+pub trait OpenApiExtractor {
+    /// Adds what the extractor reads to `operation`.
+    fn document(operation: Operation) -> Operation;
+}
+
+impl OpenApiExtractor for HttpRequest {
+    fn document(operation: Operation) -> Operation {
+        operation
+    }
+}
+
+impl OpenApiExtractor for Headers {
+    fn document(operation: Operation) -> Operation {
+        operation
+    }
+}
+
+impl<T: FromData> OpenApiExtractor for Data<T> {
+    fn document(operation: Operation) -> Operation {
+        operation
+    }
+}
+
+impl<T: FromParams + JsonSchema> OpenApiExtractor for Params<T> {
+    fn document(operation: Operation) -> Operation {
+        operation.path_params::<T>()
+    }
+}
+
+impl<T: FromQueryParam + JsonSchema> OpenApiExtractor for QueryParam<T> {
+    fn document(operation: Operation) -> Operation {
+        operation.query::<T>()
+    }
+}
+
+impl<T: FromJson + JsonSchema> OpenApiExtractor for JsonBody<T> {
+    fn document(operation: Operation) -> Operation {
+        operation.json_body::<T>()
+    }
+}
+
+#[cfg(feature = "validation")]
+impl<T: JsonSchema> OpenApiExtractor for crate::req::validated::Validated<T> {
+    fn document(operation: Operation) -> Operation {
+        operation.json_body::<T>()
+    }
+}
+
+#[cfg(feature = "validation")]
+impl<T: validator::Validate + JsonSchema> OpenApiExtractor
+    for crate::req::body::json_data::JsonBodyValidated<T>
+{
+    fn document(operation: Operation) -> Operation {
+        operation.json_body::<T>()
+    }
+}
+
+macro_rules! impl_openapi_extractor_tuples {
+    ($(($($T:ident),+)),+) => {
+        $(
+            impl<$($T: OpenApiExtractor),+> OpenApiExtractor for ($($T,)+) {
+                fn document(operation: Operation) -> Operation {
+                    $(let operation = $T::document(operation);)+
+                    operation
+                }
+            }
+        )+
+    };
+}
+
+impl_openapi_extractor_tuples!(
+    (A),
+    (A, B),
+    (A, B, C),
+    (A, B, C, D),
+    (A, B, C, D, E),
+    (A, B, C, D, E, F),
+    (A, B, C, D, E, F, G),
+    (A, B, C, D, E, F, G, H)
+);
+
+/// A route's schemas, generated before the shared definitions are final.
+struct PendingOperation<'a> {
+    pattern: RoutePattern,
+    method: &'static str,
+    operation: Option<&'a Operation>,
+    path_schema: Option<Schema>,
+    query_schema: Option<Schema>,
+    body_schema: Option<Schema>,
+    response_schemas: Vec<Option<Schema>>,
+}
+
+impl App {
+    /// Generates an OpenAPI 3.0 document describing the app's routes.
+    ///
+    /// Every route is listed with its path params. Routes documented with
+    /// [`RouteHandle::openapi`](crate::router::route_handle::RouteHandle::openapi)
+    /// also get their summary, query params, request body and responses, with
+    /// shared types under `components/schemas`. `CONNECT` and extension
+    /// methods can't be described in OpenAPI 3.0 and are left out, as are
+    /// static file mounts.
+    ///
+    /// The document's `info` has a placeholder title and version; set your
+    /// own on the returned value.
+    ///
+    /// ## Example
+    ///
+    /// ```
+    /// use ripress::{app::App, req::HttpRequest, types::RouterFns};
+    ///
+    /// let mut app = App::new();
+    /// app.get("/users/:id", |_req: HttpRequest, res| async move { res.ok() });
+    ///
+    /// let mut spec = app.openapi();
+    /// spec["info"]["title"] = "Users API".into();
+    ///
+    /// // Serve it for Swagger UI
+    /// app.get("/openapi.json", move |_req: HttpRequest, res| {
+    ///     let spec = spec.clone();
+    ///     async move { res.ok().json(spec) }
+    /// });
+    /// ```
+    pub fn openapi(&self) -> Value {
+        let mut generator = SchemaSettings::openapi3().into_generator();
+
+        let mut routes: Vec<(&String, &HttpMethods)> = self
+            .routes
+            .iter()
+            .flat_map(|(path, methods)| methods.keys().map(move |method| (path, method)))
+            .collect();
+        routes.sort();
+
+        let mut pending = Vec::new();
+        for (path, method) in routes {
+            let Some(method_name) = openapi_method(method) else {
+                continue;
+            };
+            let Ok(pattern) = RoutePattern::parse(path) else {
+                continue;
+            };
+            let operation = self.route_docs.get(&(path.clone(), method.clone()));
+            let mut schema = |schema_fn: Option<SchemaFn>| schema_fn.map(|f| f(&mut generator));
+            pending.push(PendingOperation {
+                pattern,
+                method: method_name,
+                operation,
+                path_schema: operation.and_then(|op| schema(op.path_params)),
+                query_schema: operation.and_then(|op| schema(op.query)),
+                body_schema: operation.and_then(|op| schema(op.json_body)),
+                response_schemas: operation
+                    .map(|op| op.responses.iter().map(|(_, _, f)| schema(*f)).collect())
+                    .unwrap_or_default(),
+            });
+        }
+
+        let definitions = generator.take_definitions(true);
+        let mut paths = Map::new();
+
+        for mut route in pending {
+            // Definitions were transformed by `take_definitions`; inline
+            // schemas still need it.
+            let inline = route
+                .body_schema
+                .iter_mut()
+                .chain(route.response_schemas.iter_mut().flatten());
+            for schema in inline {
+                for transform in generator.transforms_mut() {
+                    transform.transform(schema);
+                }
+            }
+
+            let (path, path_params) = route.pattern.openapi_path();
+            let object = operation_object(&route, &path_params, &definitions);
+            paths.entry(path).or_insert_with(|| json!({}))[route.method] = object;
+        }
+
+        let mut document = json!({
+            "openapi": "3.0.3",
+            "info": { "title": "API", "version": "1.0.0" },
+            "paths": paths,
+        });
+        if !definitions.is_empty() {
+            document["components"] = json!({ "schemas": definitions });
+        }
+        document
+    }
+}
+
+/// Returns the path item field of `method`, or `None` if OpenAPI 3.0 has none.
+fn openapi_method(method: &HttpMethods) -> Option<&'static str> {
+    match method {
+        HttpMethods::GET => Some("get"),
+        HttpMethods::POST => Some("post"),
+        HttpMethods::PUT => Some("put"),
+        HttpMethods::DELETE => Some("delete"),
+        HttpMethods::PATCH => Some("patch"),
+        HttpMethods::HEAD => Some("head"),
+        HttpMethods::OPTIONS => Some("options"),
+        HttpMethods::TRACE => Some("trace"),
+        HttpMethods::CONNECT | HttpMethods::Other(_) => None,
+    }
+}
+
+/// Builds the operation object of a route whose path has `path_params`.
+fn operation_object(
+    route: &PendingOperation<'_>,
+    path_params: &[(&str, Option<&str>)],
+    definitions: &Map<String, Value>,
+) -> Value {
+    let documented_path = route
+        .path_schema
+        .as_ref()
+        .map(|schema| parameters(schema.as_value(), "path", definitions))
+        .unwrap_or_default();
+
+    let mut parameters_list: Vec<Value> = path_params
+        .iter()
+        .map(|(name, constraint)| {
+            documented_path
+                .iter()
+                .find(|param| param["name"] == *name)
+                .cloned()
+                .unwrap_or_else(|| {
+                    let mut schema = json!({ "type": "string" });
+                    if let Some(constraint) = constraint {
+                        schema["pattern"] = (*constraint).into();
+                    }
+                    json!({ "name": name, "in": "path", "required": true, "schema": schema })
+                })
+        })
+        .collect();
+    if let Some(schema) = &route.query_schema {
+        parameters_list.extend(parameters(schema.as_value(), "query", definitions));
+    }
+
+    let mut object = json!({});
+    if !parameters_list.is_empty() {
+        object["parameters"] = parameters_list.into();
+    }
+
+    let mut responses = Map::new();
+    if let Some(operation) = route.operation {
+        if let Some(summary) = &operation.summary {
+            object["summary"] = summary.as_str().into();
+        }
+        if let Some(description) = &operation.description {
+            object["description"] = description.as_str().into();
+        }
+        if !operation.tags.is_empty() {
+            object["tags"] = operation.tags.clone().into();
+        }
+        if let Some(schema) = &route.body_schema {
+            object["requestBody"] = json!({
+                "required": true,
+                "content": { "application/json": { "schema": schema } },
+            });
+        }
+        for ((status, description, _), schema) in
+            operation.responses.iter().zip(&route.response_schemas)
+        {
+            let mut response = json!({ "description": description });
+            if let Some(schema) = schema {
+                response["content"] = json!({ "application/json": { "schema": schema } });
+            }
+            responses.insert(status.to_string(), response);
+        }
+    }
+    if responses.is_empty() {
+        responses.insert("default".to_string(), json!({ "description": "Response" }));
+    }
+    object["responses"] = responses.into();
+    object
+}
+
+/// Turns the fields of an object schema into parameters located `location`.
+fn parameters(schema: &Value, location: &str, definitions: &Map<String, Value>) -> Vec<Value> {
+    let schema = schema
+        .get("$ref")
+        .and_then(Value::as_str)
+        .and_then(|reference| reference.strip_prefix("#/components/schemas/"))
+        .and_then(|name| definitions.get(name))
+        .unwrap_or(schema);
+    let required: Vec<&str> = schema
+        .get("required")
+        .and_then(Value::as_array)
+        .map(|names| names.iter().filter_map(Value::as_str).collect())
+        .unwrap_or_default();
+
+    schema
+        .get("properties")
+        .and_then(Value::as_object)
+        .map(|properties| {
+            properties
+                .iter()
+                .map(|(name, property)| {
+                    json!({
+                        "name": name,
+                        "in": location,
+                        "required": location == "path" || required.contains(&name.as_str()),
+                        "schema": property,
+                    })
+                })
+                .collect()
+        })
+        .unwrap_or_default()
+}
@@ -1,10 +1,36 @@
 pub(crate) struct AppSettings {
     pub(crate) http2_config: Http2Config,
     pub(crate) graceful_shutdown: bool,
-    pub(crate) static_files: HashMap<&'static str, &'static str>,
+    pub(crate) static_files: StaticMounts,
     #[cfg(feature = "with-wynd")]
     pub(crate) wynd_config: Option<WyndConfig>,
     pub(crate) host: String,
+    pub(crate) error_format: ErrorFormat,
+    pub(crate) handler_timeout: Option<Duration>,
+    pub(crate) connection_timeouts: ConnectionTimeouts,
+    /// Set by [`App::keep_alive`](crate::app::App::keep_alive)
+    pub(crate) keep_alive: bool,
+    /// Set by [`App::keep_alive`](crate::app::App::keep_alive), the idle limit
+    /// on top of [`ConnectionTimeouts::idle`]
+    pub(crate) keep_alive_idle: Option<Duration>,
+    /// Set by [`App::max_requests_per_connection`](crate::app::App::max_requests_per_connection)
+    pub(crate) max_requests_per_connection: Option<usize>,
+    /// Set by [`App::max_connections_per_ip`](crate::app::App::max_connections_per_ip)
+    pub(crate) max_connections_per_ip: Option<usize>,
+    pub(crate) metrics: Option<Arc<Metrics>>,
+    pub(crate) readiness_checks: HashMap<String, Vec<ReadinessCheck>>,
+    #[cfg(feature = "session")]
+    pub(crate) sessions: Option<Arc<Sessions>>,
+    pub(crate) request: RequestSettings,
+    /// Set by [`App::trust_proxy`](crate::app::App::trust_proxy), see
+    /// [`RequestSettings::for_peer`] for what `None` trusts
+    pub(crate) trust_proxy: Option<Arc<TrustProxy>>,
+    pub(crate) case_sensitive_routing: bool,
+    /// Set by [`App::use_body_limit`](crate::app::App::use_body_limit), enforced
+    /// while the body is read from the connection
+    pub(crate) body_limit: Option<usize>,
+    /// Values registered with [`App::state`](crate::app::App::state)
+    pub(crate) state: TypeMap,
 }
 
 impl Default for AppSettings {
@@ -16,12 +42,39 @@ impl Default for AppSettings {
             #[cfg(feature = "with-wynd")]
             wynd_config: None,
             host: String::from("0.0.0.0"),
+            error_format: ErrorFormat::default(),
+            handler_timeout: None,
+            connection_timeouts: ConnectionTimeouts::default(),
+            keep_alive: true,
+            keep_alive_idle: None,
+            max_requests_per_connection: None,
+            max_connections_per_ip: None,
+            metrics: None,
+            readiness_checks: HashMap::new(),
+            #[cfg(feature = "session")]
+            sessions: None,
+            request: RequestSettings::default(),
+            trust_proxy: None,
+            case_sensitive_routing: true,
+            body_limit: None,
+            state: TypeMap::default(),
         }
     }
 }
 
-use std::{collections::HashMap, time::Duration};
+use std::{collections::HashMap, net::IpAddr, str::FromStr, sync::Arc, time::Duration};
 
+use crate::{
+    app::{connection::ConnectionTimeouts, health::ReadinessCheck, state::TypeMap},
+    middlewares::metrics::Metrics,
+    res::HttpResponse,
+};
+
+/// Static file mounts, keyed by mount path, with the directory and options for each.
+pub(crate) type StaticMounts = HashMap<String, (String, StaticOptions)>;
+
+#[cfg(feature = "session")]
+use crate::middlewares::session::Sessions;
 #[cfg(feature = "with-wynd")]
 use crate::types::WyndHandler;
 
@@ -51,15 +104,440 @@ pub struct Http2Config {
     pub keep_alive_interval: Option<Duration>,
     /// Timeout waiting for a PING ACK before considering the connection dead.
     pub keep_alive_timeout: Option<Duration>,
-    /// Whether to send keep-alive PINGs even when the connection is idle.
+    /// Whether to send keep-alive PINGs even when no streams are open.
+    ///
+    /// Hyper's server pings idle connections whenever `keep_alive_interval` is
+    /// set, so `Some(true)` needs an interval to have any effect, and
+    /// `Some(false)` can't be honored and is ignored with a warning.
     pub keep_alive_while_idle: Option<bool>,
     /// Whether to enable HTTP/2.
     pub is_enabled: bool,
 }
 
+/// Options controlling how a static file mount is served.
+///
+/// Passed to [`App::static_files_with_options`](crate::app::App::static_files_with_options).
+/// The defaults reproduce the behavior of [`App::static_files`](crate::app::App::static_files):
+/// `Cache-Control: public, max-age=86400` and a `Last-Modified` header.
+///
+/// # Examples
+///
+/// ```
+/// use ripress::app::settings::StaticOptions;
+///
+/// // Fingerprinted assets that never change
+/// let assets = StaticOptions {
+///     max_age: 31536000,
+///     immutable: true,
+///     ..Default::default()
+/// };
+/// assert_eq!(assets.cache_control(), "public, max-age=31536000, immutable");
+///
+/// // Always revalidate
+/// let html = StaticOptions {
+///     no_cache: true,
+///     ..Default::default()
+/// };
+/// assert_eq!(html.cache_control(), "public, no-cache");
+/// ```
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct StaticOptions {
+    /// Value of the `max-age` directive, in seconds.
+    pub max_age: u64,
+    /// Adds the `immutable` directive so browsers skip revalidation entirely.
+    pub immutable: bool,
+    /// Uses `public` when `true`, `private` when `false`.
+    pub public: bool,
+    /// Sends `no-cache` instead of `max-age`, forcing revalidation on every use.
+    pub no_cache: bool,
+    /// Whether to send the `Last-Modified` header.
+    pub last_modified: bool,
+    /// File (relative to the mount directory) served with `200 OK` when a request
+    /// that accepts `text/html` doesn't match any file, e.g. `Some("index.html")`
+    /// for single-page applications with client-side routing.
+    pub spa_fallback: Option<&'static str>,
+    /// Renders an HTML index (name, size, modified time) for directory requests
+    /// that have no `index.html`. When disabled such requests return `404`.
+    pub directory_listing: bool,
+    /// Serves a sibling `file.br` or `file.gz` (with `Content-Encoding` and the
+    /// original `Content-Type`) when the client's `Accept-Encoding` allows it.
+    pub precompressed: bool,
+}
+
+impl Default for StaticOptions {
+    fn default() -> Self {
+        StaticOptions {
+            max_age: 86400,
+            immutable: false,
+            public: true,
+            no_cache: false,
+            last_modified: true,
+            spa_fallback: None,
+            directory_listing: false,
+            precompressed: false,
+        }
+    }
+}
+
+impl StaticOptions {
+    /// Builds the `Cache-Control` header value for these options.
+    pub fn cache_control(&self) -> String {
+        let visibility = if self.public { "public" } else { "private" };
+
+        if self.no_cache {
+            return format!("{}, no-cache", visibility);
+        }
+
+        let mut value = format!("{}, max-age={}", visibility, self.max_age);
+        if self.immutable {
+            value.push_str(", immutable");
+        }
+        value
+    }
+}
+
+/// Builds an error response from a status code and message; see [`ErrorFormat::Custom`].
+pub type ErrorRenderer = Arc<dyn Fn(u16, &str) -> HttpResponse + Send + Sync>;
+
+/// How the framework renders the errors it generates itself.
+///
+/// Applies to router errors (`404`, `405`, a `400` for an unparseable request body,
+/// unhandled `500`s) and to rejections from the built-in middlewares, such as the
+/// body limit's `413` or the rate limiter's `429`. The file upload middleware's
+/// size and type rejections keep their detailed JSON body. Responses built by
+/// your own handlers and middlewares are never rewritten.
+///
+/// Set it with [`App::error_format`](crate::app::App::error_format).
+///
+/// # Examples
+///
+/// ```
+/// use ripress::app::{settings::ErrorFormat, App};
+///
+/// let mut app = App::new();
+/// app.error_format(ErrorFormat::Json);
+///
+/// // Or build the response yourself
+/// app.error_format(ErrorFormat::custom(|status, message| {
+///     ripress::context::HttpResponse::new()
+///         .status(status)
+///         .json(serde_json::json!({ "code": status, "detail": message }))
+/// }));
+/// ```
+#[derive(Clone, Default)]
+pub enum ErrorFormat {
+    /// A `text/plain` body containing the message.
+    #[default]
+    Text,
+    /// An `application/json` body of the form `{ "error": "<message>" }`.
+    Json,
+    /// Builds the response from the status code and message.
+    Custom(ErrorRenderer),
+}
+
+impl ErrorFormat {
+    /// Wraps a closure as [`ErrorFormat::Custom`].
+    pub fn custom<F>(f: F) -> Self
+    where
+        F: Fn(u16, &str) -> HttpResponse + Send + Sync + 'static,
+    {
+        ErrorFormat::Custom(Arc::new(f))
+    }
+
+    /// Renders an error with this format, starting from `res` so headers and
+    /// cookies already on it are kept.
+    pub(crate) fn render(&self, res: HttpResponse, status: u16, message: &str) -> HttpResponse {
+        match self {
+            ErrorFormat::Text => res.status(status).text(message),
+            ErrorFormat::Json => res
+                .status(status)
+                .json(serde_json::json!({ "error": message })),
+            ErrorFormat::Custom(f) => {
+                let mut custom = f(status, message);
+                for (name, value) in res.headers.iter() {
+                    if custom.headers.get(name).is_none() {
+                        custom.headers.insert(name, value);
+                    }
+                }
+                custom.cookies.extend(res.cookies);
+                custom
+            }
+        }
+    }
+}
+
+impl std::fmt::Debug for ErrorFormat {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            ErrorFormat::Text => f.write_str("Text"),
+            ErrorFormat::Json => f.write_str("Json"),
+            ErrorFormat::Custom(_) => f.write_str("Custom(<fn>)"),
+        }
+    }
+}
+
 #[cfg(feature = "with-wynd")]
 #[derive(Clone)]
 pub(crate) struct WyndConfig {
     pub func: WyndHandler,
     pub path: String,
 }
+
+/// Request extension carrying the app settings that `HttpRequest` accessors
+/// need, such as [`App::subdomain_offset`](crate::app::App::subdomain_offset),
+/// along with what the connection layer knows about the peer.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub(crate) struct RequestSettings {
+    /// Number of trailing host labels that are not subdomains
+    pub(crate) subdomain_offset: usize,
+    /// Whether the peer may set forwarding headers, see [`TrustProxy`]
+    pub(crate) trusted_proxy: bool,
+    /// Whether the peer may also report the public host and port, which
+    /// takes an explicit [`App::trust_proxy`](crate::app::App::trust_proxy)
+    pub(crate) trusted_host: bool,
+    /// Address of the peer, `None` for Unix sockets and requests built in-process
+    pub(crate) peer_ip: Option<IpAddr>,
+    /// Most bytes the body may have, see [`HttpRequest::body_limit`](crate::req::HttpRequest::body_limit)
+    pub(crate) body_limit: Option<usize>,
+    /// Limits applied while parsing `multipart/form-data` bodies
+    pub(crate) multipart_limits: MultipartLimits,
+    /// How much of a JSON deserialization error is returned to the handler
+    pub(crate) json_error_detail: JsonErrorDetail,
+}
+
+impl Default for RequestSettings {
+    fn default() -> Self {
+        RequestSettings {
+            subdomain_offset: 2,
+            trusted_proxy: true,
+            trusted_host: false,
+            peer_ip: None,
+            body_limit: None,
+            multipart_limits: MultipartLimits::default(),
+            json_error_detail: JsonErrorDetail::default(),
+        }
+    }
+}
+
+impl RequestSettings {
+    /// The settings for a request from `peer`, under the app's `trust_proxy`.
+    ///
+    /// Until [`App::trust_proxy`](crate::app::App::trust_proxy) is called every
+    /// peer may report the client's address and scheme, as before it existed,
+    /// but not the host and port, which would let a direct client choose the
+    /// request's `origin_url`.
+    pub(crate) fn for_peer(self, trust_proxy: Option<&TrustProxy>, peer: Option<IpAddr>) -> Self {
+        let trusted_proxy = trust_proxy.unwrap_or(&TrustProxy::All).trusts(peer);
+        RequestSettings {
+            trusted_proxy,
+            trusted_host: trust_proxy.is_some() && trusted_proxy,
+            peer_ip: peer,
+            ..self
+        }
+    }
+}
+
+/// How much of a JSON deserialization error is exposed.
+///
+/// Applies to [`HttpRequest::json`](crate::req::HttpRequest::json) and to the
+/// `400` sent when the [`JsonBody`](crate::req::body::json_data::JsonBody) extractor
+/// rejects a request. With [`JsonErrorDetail::Terse`] the message is just
+/// `Invalid JSON` and the full error is logged at `warn` level instead, so the
+/// shape of the expected type does not leak to clients.
+///
+/// Set it with [`App::json_error_detail`](crate::app::App::json_error_detail).
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum JsonErrorDetail {
+    /// The full serde error, such as ``missing field `name` ``. The default.
+    #[default]
+    Full,
+    /// `Invalid JSON`, with the full error only in the logs.
+    Terse,
+}
+
+impl JsonErrorDetail {
+    /// Returns the message to expose for a JSON error, logging the full
+    /// error when it is hidden.
+    pub(crate) fn message(self, error: String) -> String {
+        match self {
+            JsonErrorDetail::Full => error,
+            JsonErrorDetail::Terse => {
+                tracing::warn!("Rejected JSON body: {}", error);
+                String::from("Invalid JSON")
+            }
+        }
+    }
+}
+
+/// Limits applied while parsing `multipart/form-data` bodies.
+///
+/// They bound the work a single request can cause, whether the body is parsed
+/// for [`HttpRequest::form_data`](crate::req::HttpRequest::form_data) or by the
+/// [`file_upload`](crate::middlewares::file_upload::file_upload) middleware. A body
+/// with more parts than `max_fields` or a text field longer than
+/// `max_field_value_len` gets `413 Payload Too Large`, and a field name longer than
+/// `max_field_name_len` gets `400 Bad Request`.
+///
+/// Set them with [`App::multipart_limits`](crate::app::App::multipart_limits).
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct MultipartLimits {
+    /// Most parts, text fields and files together, a body may have. Defaults to 1000.
+    pub max_fields: usize,
+    /// Longest part name accepted, in bytes. Defaults to 256.
+    pub max_field_name_len: usize,
+    /// Longest text field value accepted, in bytes. Files are bounded by the
+    /// body limit and the upload middleware instead. Defaults to 1 MiB.
+    pub max_field_value_len: usize,
+}
+
+impl Default for MultipartLimits {
+    fn default() -> Self {
+        MultipartLimits {
+            max_fields: 1000,
+            max_field_name_len: 256,
+            max_field_value_len: 1024 * 1024,
+        }
+    }
+}
+
+/// The Tokio runtime [`App::run`](crate::app::App::run) builds to serve the app.
+///
+/// # Examples
+///
+/// ```
+/// use ripress::app::settings::RuntimeConfig;
+///
+/// let runtime = RuntimeConfig { workers: 4 };
+/// assert!(RuntimeConfig::default().workers >= 1);
+/// ```
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct RuntimeConfig {
+    /// Worker threads polling connections. Defaults to the number of CPUs, as
+    /// with `#[tokio::main]`.
+    pub workers: usize,
+}
+
+impl Default for RuntimeConfig {
+    fn default() -> Self {
+        RuntimeConfig {
+            workers: std::thread::available_parallelism().map_or(1, |n| n.get()),
+        }
+    }
+}
+
+/// Which peers may describe the original request through forwarding headers.
+///
+/// Behind a reverse proxy the client's address, the public host name and the
+/// scheme only reach the app through `X-Forwarded-For`, `X-Forwarded-Host`,
+/// `X-Forwarded-Port` and `X-Forwarded-Proto`. Those headers are honored only
+/// when the peer is trusted; otherwise the request is described by the
+/// connection and the `Host` header alone.
+///
+/// Set it with [`App::trust_proxy`](crate::app::App::trust_proxy). Until then
+/// every peer may set `X-Forwarded-For` and `X-Forwarded-Proto`, as with
+/// [`TrustProxy::All`], but the host and port are only taken from forwarding
+/// headers once a policy is set. Apps not behind a proxy should pick
+/// [`TrustProxy::Disabled`] or list their proxies.
+///
+/// # Examples
+///
+/// ```
+/// use ripress::app::{settings::TrustProxy, App};
+///
+/// let mut app = App::new();
+/// app.trust_proxy(TrustProxy::peers(["127.0.0.1", "10.0.0.0/8"]).unwrap());
+/// ```
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub enum TrustProxy {
+    /// Forwarding headers are honored from every peer.
+    #[default]
+    All,
+    /// Forwarding headers are always ignored.
+    Disabled,
+    /// Forwarding headers are honored from peers in these ranges, and on Unix
+    /// sockets, whose peer is always local.
+    Peers(Vec<IpRange>),
+}
+
+impl TrustProxy {
+    /// Trusts the listed addresses and CIDR ranges, such as `"10.0.0.0/8"`.
+    ///
+    /// Returns an error naming the first entry that can't be parsed.
+    pub fn peers<I, S>(peers: I) -> Result<Self, String>
+    where
+        I: IntoIterator<Item = S>,
+        S: AsRef<str>,
+    {
+        peers
+            .into_iter()
+            .map(|peer| peer.as_ref().parse())
+            .collect::<Result<_, _>>()
+            .map(TrustProxy::Peers)
+    }
+
+    /// Whether forwarding headers from `peer` are honored. `None` stands for a
+    /// peer without an IP address, such as the other end of a Unix socket.
+    pub fn trusts(&self, peer: Option<IpAddr>) -> bool {
+        match self {
+            TrustProxy::All => true,
+            TrustProxy::Disabled => false,
+            TrustProxy::Peers(ranges) => match peer {
+                Some(ip) => ranges.iter().any(|range| range.contains(ip)),
+                None => true,
+            },
+        }
+    }
+}
+
+/// An IP address or CIDR range, parsed from strings like `"192.168.0.1"` or
+/// `"fd00::/8"`.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct IpRange {
+    addr: IpAddr,
+    prefix: u8,
+}
+
+impl IpRange {
+    /// Whether `ip` falls within the range. IPv4-mapped IPv6 addresses match
+    /// the IPv4 ranges they map to.
+    pub fn contains(&self, ip: IpAddr) -> bool {
+        match (self.addr, ip.to_canonical()) {
+            (IpAddr::V4(range), IpAddr::V4(ip)) => {
+                let mask = u32::MAX
+                    .checked_shl(32 - u32::from(self.prefix))
+                    .unwrap_or(0);
+                u32::from(range) & mask == u32::from(ip) & mask
+            }
+            (IpAddr::V6(range), IpAddr::V6(ip)) => {
+                let mask = u128::MAX
+                    .checked_shl(128 - u32::from(self.prefix))
+                    .unwrap_or(0);
+                u128::from(range) & mask == u128::from(ip) & mask
+            }
+            _ => false,
+        }
+    }
+}
+
+impl FromStr for IpRange {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let invalid = || format!("invalid IP address or range: {}", s);
+        let (addr, prefix) = match s.trim().split_once('/') {
+            Some((addr, prefix)) => (addr, Some(prefix)),
+            None => (s.trim(), None),
+        };
+        let addr: IpAddr = addr.parse().map_err(|_| invalid())?;
+        let max = if addr.is_ipv4() { 32 } else { 128 };
+        let prefix = match prefix {
+            Some(prefix) => prefix
+                .parse()
+                .ok()
+                .filter(|p| *p <= max)
+                .ok_or_else(invalid)?,
+            None => max,
+        };
+        Ok(IpRange { addr, prefix })
+    }
+}
@@ -0,0 +1,107 @@
+#![warn(missing_docs)]
+use std::{
+    any::{type_name, Any, TypeId},
+    collections::HashMap,
+    fmt,
+    sync::Arc,
+};
+
+use crate::{
+    app::api_error::error_response, helpers::FromRequest, req::HttpRequest, res::HttpResponse,
+};
+
+/// Values keyed by their type, for [`App::state`](crate::app::App::state) and
+/// [`HttpRequest::extension`].
+///
+/// Cloning is cheap: clones share the map until one of them inserts a value.
+#[derive(Clone, Default)]
+pub(crate) struct TypeMap {
+    values: Arc<HashMap<TypeId, Arc<dyn Any + Send + Sync>>>,
+}
+
+impl TypeMap {
+    /// Stores `value`, replacing any earlier value of the same type.
+    pub(crate) fn insert<T: Send + Sync + 'static>(&mut self, value: T) {
+        Arc::make_mut(&mut self.values).insert(TypeId::of::<T>(), Arc::new(value));
+    }
+
+    /// Returns the value of type `T`, if one was registered.
+    pub(crate) fn get<T: 'static>(&self) -> Option<&T> {
+        self.values
+            .get(&TypeId::of::<T>())
+            .and_then(|value| value.downcast_ref())
+    }
+}
+
+impl fmt::Debug for TypeMap {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("TypeMap")
+            .field("len", &self.values.len())
+            .finish()
+    }
+}
+
+/// Request extension carrying the values set with [`HttpRequest::set_extension`]
+/// from one middleware stage to the next.
+#[derive(Clone, Debug, Default)]
+pub(crate) struct RequestExtensions(pub(crate) TypeMap);
+
+/// Extracts shared state registered with [`App::state`](crate::app::App::state).
+///
+/// Values registered with [`App::extension`](crate::app::App::extension) or set
+/// on the request with [`HttpRequest::set_extension`] can be extracted the same way.
+///
+/// The value is cloned out for each request, so wrap anything expensive to
+/// clone, like a connection pool, in an `Arc`. Asking for a type that was never
+/// registered is a bug in the app rather than the request, so the route
+/// responds with `500 Internal Server Error` and the missing type is logged.
+///
+/// ## Example
+///
+/// ```
+/// use std::sync::{
+///     atomic::{AtomicUsize, Ordering},
+///     Arc,
+/// };
+///
+/// use ripress::{app::{state::State, App}, types::RouterFns};
+///
+/// #[derive(Clone, Default)]
+/// struct Visits(Arc<AtomicUsize>);
+///
+/// let mut app = App::new();
+/// app.state(Visits::default());
+///
+/// app.get("/", |State(visits): State<Visits>, res| async move {
+///     let count = visits.0.fetch_add(1, Ordering::Relaxed) + 1;
+///     res.ok().text(format!("Visit #{}", count))
+/// });
+/// ```
+#[derive(Debug, Clone)]
+pub struct State<T>(pub T);
+
+impl<T: Clone + Send + Sync + 'static> FromRequest for State<T> {
+    type Error = String;
+
+    fn from_request(req: &HttpRequest) -> Result<Self, Self::Error> {
+        req.extension::<T>().cloned().map(State).ok_or_else(|| {
+            format!(
+                "No state of type `{}` was registered with App::state",
+                type_name::<T>()
+            )
+        })
+    }
+
+    fn rejection(error: Self::Error, res: HttpResponse) -> HttpResponse {
+        tracing::error!("{}", error);
+        error_response(res, 500, &error)
+    }
+}
+
+impl<T> std::ops::Deref for State<T> {
+    type Target = T;
+
+    fn deref(&self) -> &Self::Target {
+        &self.0
+    }
+}
@@ -0,0 +1,275 @@
+use std::{
+    path::{Component, Path, PathBuf},
+    time::SystemTime,
+};
+
+use bytes::Bytes;
+use http_body_util::{BodyExt, Full};
+use hyper::{header, http::StatusCode, HeaderMap, Request, Response};
+use hyper_staticfile::{AcceptEncoding, ResolveResult, ResponseBuilder};
+
+use crate::{
+    app::{settings::StaticOptions, App},
+    helpers::{negotiate, token_specificity},
+};
+
+/// A single row of a generated directory listing.
+struct ListingEntry {
+    name: String,
+    is_dir: bool,
+    size: u64,
+    modified: Option<SystemTime>,
+}
+
+impl App {
+    /// Renders an HTML index of `dir` for the directory listing option of static mounts.
+    ///
+    /// `display_path` is the URL path shown in the page title. Directories are listed
+    /// first, then files, each group sorted alphabetically. Returns `Ok(None)` when
+    /// `dir` is not a directory.
+    pub(crate) async fn render_directory_listing(
+        dir: &Path,
+        display_path: &str,
+    ) -> Result<Option<String>, std::io::Error> {
+        match tokio::fs::metadata(dir).await {
+            Ok(meta) if meta.is_dir() => {}
+            Ok(_) => return Ok(None),
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => return Ok(None),
+            Err(e) => return Err(e),
+        }
+
+        let mut entries = Vec::new();
+        let mut read_dir = tokio::fs::read_dir(dir).await?;
+        while let Some(entry) = read_dir.next_entry().await? {
+            let meta = match entry.metadata().await {
+                Ok(meta) => meta,
+                Err(_) => continue,
+            };
+            entries.push(ListingEntry {
+                name: entry.file_name().to_string_lossy().into_owned(),
+                is_dir: meta.is_dir(),
+                size: meta.len(),
+                modified: meta.modified().ok(),
+            });
+        }
+
+        entries.sort_by(|a, b| {
+            b.is_dir
+                .cmp(&a.is_dir)
+                .then_with(|| a.name.to_lowercase().cmp(&b.name.to_lowercase()))
+                .then_with(|| a.name.cmp(&b.name))
+        });
+
+        let title = escape_html(display_path);
+        let mut html = format!(
+            "<!DOCTYPE html>\n<html>\n<head>\n<meta charset=\"utf-8\">\n<title>Index of {title}</title>\n</head>\n<body>\n<h1>Index of {title}</h1>\n<table>\n<tr><th>Name</th><th>Size</th><th>Last Modified</th></tr>\n"
+        );
+
+        if display_path != "/" {
+            html.push_str("<tr><td><a href=\"../\">../</a></td><td></td><td></td></tr>\n");
+        }
+
+        for entry in &entries {
+            let suffix = if entry.is_dir { "/" } else { "" };
+            let size = if entry.is_dir {
+                "-".to_string()
+            } else {
+                entry.size.to_string()
+            };
+            let modified = entry
+                .modified
+                .map(httpdate::fmt_http_date)
+                .unwrap_or_default();
+
+            html.push_str(&format!(
+                "<tr><td><a href=\"{}{}\">{}{}</a></td><td>{}</td><td>{}</td></tr>\n",
+                encode_path_segment(&entry.name),
+                suffix,
+                escape_html(&entry.name),
+                suffix,
+                size,
+                modified
+            ));
+        }
+
+        html.push_str("</table>\n</body>\n</html>\n");
+        Ok(Some(html))
+    }
+}
+
+/// Drops a multi-range `Range` header, so the request falls back to a full `200`
+/// response instead of multipart/byteranges.
+pub(crate) fn strip_multi_range(headers: &mut HeaderMap) {
+    let is_multi_range = headers
+        .get(header::RANGE)
+        .and_then(|v| v.to_str().ok())
+        .map(|v| v.contains(','))
+        .unwrap_or(false);
+    if is_multi_range {
+        headers.remove(header::RANGE);
+    }
+}
+
+/// Builds the response for a resolved static file, honoring the request's
+/// conditional (`If-None-Match`, `If-Modified-Since`) and `Range` headers.
+///
+/// `Cache-Control` and `Last-Modified` follow `options`. Shared by static mounts
+/// and [`HttpResponse::send_file`](crate::res::HttpResponse::send_file).
+pub(crate) async fn file_response<B>(
+    req: &Request<B>,
+    resolved: ResolveResult,
+    options: &StaticOptions,
+) -> Result<Response<Full<Bytes>>, std::io::Error> {
+    let if_none_match = req
+        .headers()
+        .get(header::IF_NONE_MATCH)
+        .and_then(|v| v.to_str().ok())
+        .map(|s| s.to_string());
+
+    let file_size = match &resolved {
+        ResolveResult::Found(file) => Some(file.size),
+        _ => None,
+    };
+
+    let mut response = ResponseBuilder::new()
+        .request(req)
+        .build(resolved)
+        .map_err(std::io::Error::other)?;
+
+    if let Some(size) = file_size {
+        if options.precompressed {
+            response
+                .headers_mut()
+                .append(header::VARY, "Accept-Encoding".parse().unwrap());
+        }
+        match response.status() {
+            StatusCode::OK | StatusCode::PARTIAL_CONTENT => {
+                response
+                    .headers_mut()
+                    .insert(header::ACCEPT_RANGES, "bytes".parse().unwrap());
+            }
+            StatusCode::RANGE_NOT_SATISFIABLE => {
+                response.headers_mut().insert(
+                    header::CONTENT_RANGE,
+                    format!("bytes */{}", size).parse().unwrap(),
+                );
+            }
+            _ => {}
+        }
+    }
+
+    response
+        .headers_mut()
+        .insert("Cache-Control", options.cache_control().parse().unwrap());
+    if !options.last_modified {
+        response.headers_mut().remove(header::LAST_MODIFIED);
+    }
+    if let Some(if_none_match_value) = if_none_match {
+        if let Some(etag) = response.headers().get(header::ETAG) {
+            if let Ok(etag_value) = etag.to_str() {
+                if if_none_match_value == etag_value {
+                    let mut builder = Response::builder().status(StatusCode::NOT_MODIFIED);
+                    if let Some(h) = builder.headers_mut() {
+                        for (k, v) in response.headers().iter() {
+                            h.insert(k.clone(), v.clone());
+                        }
+                        h.remove(header::CONTENT_LENGTH);
+                        h.remove(header::CONTENT_RANGE);
+                    }
+                    return Ok(builder.body(Full::from(Bytes::new())).unwrap());
+                }
+            }
+        }
+    }
+    let (parts, body) = response.into_parts();
+    let collected = body.collect().await.map_err(|e| {
+        std::io::Error::new(
+            std::io::ErrorKind::Other,
+            format!("Failed to collect body: {}", e),
+        )
+    })?;
+    let body_bytes = collected.to_bytes();
+    let full_body = Full::from(body_bytes);
+    Ok(Response::from_parts(parts, full_body))
+}
+
+/// Determines which pre-compressed variants (`.br`, `.gz`) the client accepts,
+/// honoring q-values so that e.g. `br;q=0` disables brotli.
+pub(crate) fn accepted_precompressed(headers: &HeaderMap) -> AcceptEncoding {
+    match headers
+        .get(header::ACCEPT_ENCODING)
+        .and_then(|v| v.to_str().ok())
+    {
+        Some(accept) => AcceptEncoding {
+            gzip: negotiate(Some(accept), &["gzip"], token_specificity).is_some(),
+            br: negotiate(Some(accept), &["br"], token_specificity).is_some(),
+        },
+        None => AcceptEncoding::none(),
+    }
+}
+
+/// Maps a URL path onto a relative filesystem path, percent-decoding it and
+/// rejecting anything that could escape the static root (`..`, drive prefixes).
+pub(crate) fn sanitize_static_path(url_path: &str) -> Option<PathBuf> {
+    let decoded = percent_decode(url_path);
+    let mut result = PathBuf::new();
+
+    for component in Path::new(&decoded).components() {
+        match component {
+            Component::Normal(part) => result.push(part),
+            Component::RootDir | Component::CurDir => {}
+            Component::ParentDir | Component::Prefix(_) => return None,
+        }
+    }
+
+    Some(result)
+}
+
+fn percent_decode(input: &str) -> String {
+    let bytes = input.as_bytes();
+    let mut decoded = Vec::with_capacity(bytes.len());
+    let mut i = 0;
+
+    while i < bytes.len() {
+        if bytes[i] == b'%' && i + 2 < bytes.len() {
+            let hex = std::str::from_utf8(&bytes[i + 1..i + 3]).ok();
+            if let Some(byte) = hex.and_then(|h| u8::from_str_radix(h, 16).ok()) {
+                decoded.push(byte);
+                i += 3;
+                continue;
+            }
+        }
+        decoded.push(bytes[i]);
+        i += 1;
+    }
+
+    String::from_utf8_lossy(&decoded).into_owned()
+}
+
+fn encode_path_segment(segment: &str) -> String {
+    let mut encoded = String::with_capacity(segment.len());
+    for byte in segment.bytes() {
+        match byte {
+            b'A'..=b'Z' | b'a'..=b'z' | b'0'..=b'9' | b'-' | b'_' | b'.' | b'~' => {
+                encoded.push(byte as char)
+            }
+            _ => encoded.push_str(&format!("%{:02X}", byte)),
+        }
+    }
+    encoded
+}
+
+pub(crate) fn escape_html(input: &str) -> String {
+    let mut escaped = String::with_capacity(input.len());
+    for ch in input.chars() {
+        match ch {
+            '&' => escaped.push_str("&amp;"),
+            '<' => escaped.push_str("&lt;"),
+            '>' => escaped.push_str("&gt;"),
+            '"' => escaped.push_str("&quot;"),
+            '\'' => escaped.push_str("&#39;"),
+            _ => escaped.push(ch),
+        }
+    }
+    escaped
+}
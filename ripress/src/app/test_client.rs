@@ -0,0 +1,224 @@
+#![warn(missing_docs)]
+use std::{
+    net::{IpAddr, Ipv4Addr, SocketAddr},
+    sync::{Arc, Mutex},
+};
+
+use bytes::Bytes;
+use http_body_util::Full;
+use hyper::{Request, Response};
+use hyper_util::rt::TokioIo;
+use routerify_ng::RequestServiceBuilder;
+use serde::Serialize;
+
+use crate::{
+    app::{api_error::ApiError, connection::ConnectionConfig, settings::Http2Config, App},
+    client::{ClientResponse, RequestParts},
+    types::HttpMethods,
+};
+
+/// The address test requests appear to come from.
+const TEST_PEER_ADDR: SocketAddr = SocketAddr::new(IpAddr::V4(Ipv4Addr::LOCALHOST), 0);
+
+/// The app's router and the settings [`App::serve_connection`] needs, shared by
+/// every request of a [`TestClient`].
+struct TestServer {
+    builder: Mutex<RequestServiceBuilder<ApiError>>,
+    config: ConnectionConfig,
+}
+
+impl TestServer {
+    /// Opens an in-memory connection served by the app and sends `request` over it.
+    async fn send(&self, request: Request<Full<Bytes>>) -> Response<hyper::body::Incoming> {
+        let (client_io, server_io) = tokio::io::duplex(64 * 1024);
+        let request_service = self.builder.lock().unwrap().build(TEST_PEER_ADDR);
+        tokio::spawn(App::serve_connection(
+            server_io,
+            Some(TEST_PEER_ADDR.ip()),
+            request_service,
+            self.config.clone(),
+        ));
+
+        let (mut sender, connection) =
+            hyper::client::conn::http1::handshake(TokioIo::new(client_io))
+                .await
+                .expect("failed to open a test connection");
+        tokio::spawn(connection);
+        sender
+            .send_request(request)
+            .await
+            .expect("the app closed the test connection without responding")
+    }
+}
+
+/// An in-memory client for testing an [`App`] without binding a socket.
+///
+/// Requests are served by the same code as [`App::listen`], over an in-memory
+/// HTTP/1 connection instead of a socket: pre-middlewares, route matching, the
+/// handler, post-middlewares and error handling, including static file mounts.
+/// Nothing touches the network, so tests run fast and in parallel. Requests
+/// appear to come from `127.0.0.1`.
+///
+/// ## Example
+///
+/// ```
+/// use ripress::app::{test_client::TestClient, App};
+/// use ripress::types::RouterFns;
+/// use ripress::req::HttpRequest;
+///
+/// # #[tokio::main]
+/// # async fn main() {
+/// let mut app = App::new();
+/// app.get("/users/:id", |req: HttpRequest, res| async move {
+///     let id = req.params.get("id").unwrap_or_default().to_string();
+///     res.ok().json(serde_json::json!({ "id": id }))
+/// });
+///
+/// let client = TestClient::new(app);
+/// let res = client.get("/users/1").send().await;
+///
+/// assert_eq!(res.status(), 200);
+/// assert_eq!(res.json::<serde_json::Value>().unwrap()["id"], "1");
+/// # }
+/// ```
+pub struct TestClient {
+    server: Arc<TestServer>,
+}
+
+impl TestClient {
+    /// Creates a test client that dispatches requests to `app`.
+    ///
+    /// ## Panics
+    ///
+    /// Panics if a route path is invalid, as [`App::listen`] would.
+    pub fn new(app: App) -> Self {
+        let builder = RequestServiceBuilder::new(app.build_router()).unwrap();
+        let config = ConnectionConfig {
+            // The test connection speaks HTTP/1.
+            http2_config: Http2Config::default(),
+            ..app.connection_config()
+        };
+        TestClient {
+            server: Arc::new(TestServer {
+                builder: Mutex::new(builder),
+                config,
+            }),
+        }
+    }
+
+    /// Starts building a request with the given method and path.
+    ///
+    /// The path may include a query string, e.g. `"/search?q=rust"`.
+    pub fn request(&self, method: HttpMethods, path: &str) -> TestRequest {
+        TestRequest {
+            server: Arc::clone(&self.server),
+            parts: RequestParts::new(method, path),
+        }
+    }
+
+    /// Starts building a `GET` request.
+    pub fn get(&self, path: &str) -> TestRequest {
+        self.request(HttpMethods::GET, path)
+    }
+
+    /// Starts building a `POST` request.
+    pub fn post(&self, path: &str) -> TestRequest {
+        self.request(HttpMethods::POST, path)
+    }
+
+    /// Starts building a `PUT` request.
+    pub fn put(&self, path: &str) -> TestRequest {
+        self.request(HttpMethods::PUT, path)
+    }
+
+    /// Starts building a `PATCH` request.
+    pub fn patch(&self, path: &str) -> TestRequest {
+        self.request(HttpMethods::PATCH, path)
+    }
+
+    /// Starts building a `DELETE` request.
+    pub fn delete(&self, path: &str) -> TestRequest {
+        self.request(HttpMethods::DELETE, path)
+    }
+
+    /// Starts building a `HEAD` request.
+    pub fn head(&self, path: &str) -> TestRequest {
+        self.request(HttpMethods::HEAD, path)
+    }
+
+    /// Starts building an `OPTIONS` request.
+    pub fn options(&self, path: &str) -> TestRequest {
+        self.request(HttpMethods::OPTIONS, path)
+    }
+}
+
+/// A request being built by a [`TestClient`].
+pub struct TestRequest {
+    server: Arc<TestServer>,
+    parts: RequestParts,
+}
+
+impl TestRequest {
+    /// Adds a request header.
+    pub fn header(mut self, name: &str, value: &str) -> Self {
+        self.parts.header(name, value);
+        self
+    }
+
+    /// Appends a query parameter, URL-encoding the key and value.
+    pub fn query(mut self, key: &str, value: &str) -> Self {
+        self.parts.query(key, value);
+        self
+    }
+
+    /// Sets a JSON body and the `Content-Type: application/json` header.
+    ///
+    /// ## Panics
+    ///
+    /// Panics if `value` cannot be serialized to JSON.
+    pub fn json<T: Serialize>(mut self, value: &T) -> Self {
+        self.parts
+            .json(value)
+            .expect("failed to serialize JSON test body");
+        self
+    }
+
+    /// Sets a plain text body and the `Content-Type: text/plain` header.
+    pub fn text(mut self, text: impl Into<String>) -> Self {
+        self.parts.text(text);
+        self
+    }
+
+    /// Sets a URL-encoded form body and the matching `Content-Type` header.
+    pub fn form(mut self, fields: &[(&str, &str)]) -> Self {
+        self.parts.form(fields);
+        self
+    }
+
+    /// Sets the raw request body without changing any headers.
+    pub fn body(mut self, body: impl Into<Bytes>) -> Self {
+        self.parts.body = body.into();
+        self
+    }
+
+    /// Dispatches the request to the app and returns its response.
+    ///
+    /// ## Panics
+    ///
+    /// Panics if the path, a header name or a header value is invalid.
+    pub async fn send(self) -> TestResponse {
+        let request = self
+            .parts
+            .into_request(Full::from)
+            .expect("invalid test request");
+
+        let response = self.server.send(request).await;
+        ClientResponse::read(response, None)
+            .await
+            .expect("failed to read the test response body")
+    }
+}
+
+/// The response returned by [`TestRequest::send`], the same type a
+/// [`ClientRequest`](crate::client::ClientRequest) returns.
+pub type TestResponse = ClientResponse;
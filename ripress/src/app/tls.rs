@@ -0,0 +1,113 @@
+//! TLS termination for [`App::listen_tls`](crate::app::App::listen_tls), backed by `rustls`.
+
+use std::{fmt, io, path::Path, sync::Arc};
+
+use tokio_rustls::{
+    rustls::{
+        crypto::ring,
+        pki_types::{pem::PemObject, CertificateDer, PrivateKeyDer},
+        ServerConfig,
+    },
+    TlsAcceptor,
+};
+
+use crate::app::settings::Http2Config;
+
+/// A certificate chain and private key for serving HTTPS.
+///
+/// The certificate and key are checked against each other when the config is
+/// built, so a mismatched pair is reported before the server starts.
+///
+/// ## Example
+///
+/// ```no_run
+/// use ripress::app::{tls::TlsConfig, App};
+///
+/// # #[tokio::main]
+/// # async fn main() {
+/// let app = App::new();
+/// let tls = TlsConfig::from_pem_files("certs/cert.pem", "certs/key.pem")
+///     .expect("failed to load the TLS certificate");
+///
+/// app.listen_tls(443, tls, || println!("Serving on https://localhost")).await;
+/// # }
+/// ```
+#[derive(Clone)]
+pub struct TlsConfig {
+    server_config: Arc<ServerConfig>,
+}
+
+impl TlsConfig {
+    /// Loads a PEM certificate chain and a PEM private key from disk.
+    ///
+    /// The certificate file may hold intermediates after the leaf certificate.
+    /// The key may be PKCS#8, PKCS#1 (RSA) or SEC1 (EC).
+    pub fn from_pem_files(
+        cert_path: impl AsRef<Path>,
+        key_path: impl AsRef<Path>,
+    ) -> io::Result<Self> {
+        let certs = CertificateDer::pem_file_iter(cert_path.as_ref())
+            .and_then(|certs| certs.collect::<Result<Vec<_>, _>>())
+            .map_err(|e| pem_error(cert_path.as_ref(), e))?;
+        let key = PrivateKeyDer::from_pem_file(key_path.as_ref())
+            .map_err(|e| pem_error(key_path.as_ref(), e))?;
+        Self::new(certs, key)
+    }
+
+    /// Builds a config from an in-memory PEM certificate chain and private key.
+    pub fn from_pem(cert_pem: &[u8], key_pem: &[u8]) -> io::Result<Self> {
+        let certs = CertificateDer::pem_slice_iter(cert_pem)
+            .collect::<Result<Vec<_>, _>>()
+            .map_err(invalid_input)?;
+        let key = PrivateKeyDer::from_pem_slice(key_pem).map_err(invalid_input)?;
+        Self::new(certs, key)
+    }
+
+    fn new(certs: Vec<CertificateDer<'static>>, key: PrivateKeyDer<'static>) -> io::Result<Self> {
+        if certs.is_empty() {
+            return Err(invalid_input("no certificates found"));
+        }
+
+        let server_config = ServerConfig::builder_with_provider(Arc::new(ring::default_provider()))
+            .with_safe_default_protocol_versions()
+            .and_then(|builder| builder.with_no_client_auth().with_single_cert(certs, key))
+            .map_err(invalid_input)?;
+
+        Ok(TlsConfig {
+            server_config: Arc::new(server_config),
+        })
+    }
+
+    /// Builds an acceptor whose ALPN list matches the app's protocol settings.
+    pub(crate) fn acceptor(&self, http2_config: &Http2Config) -> TlsAcceptor {
+        let mut server_config = (*self.server_config).clone();
+        server_config.alpn_protocols = alpn_protocols(http2_config);
+        TlsAcceptor::from(Arc::new(server_config))
+    }
+}
+
+impl fmt::Debug for TlsConfig {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("TlsConfig").finish_non_exhaustive()
+    }
+}
+
+/// Protocols offered during the handshake, most preferred first.
+///
+/// Connections are served by hyper's auto-detecting builder unless HTTP/2 is
+/// configured as the only protocol, so both are offered by default.
+fn alpn_protocols(http2_config: &Http2Config) -> Vec<Vec<u8>> {
+    if http2_config.is_enabled && http2_config.http2_only {
+        vec![b"h2".to_vec()]
+    } else {
+        vec![b"h2".to_vec(), b"http/1.1".to_vec()]
+    }
+}
+
+fn pem_error(path: &Path, e: impl fmt::Display) -> io::Error {
+    invalid_input(format!("{}: {}", path.display(), e))
+}
+
+fn invalid_input(e: impl fmt::Display) -> io::Error {
+    io::Error::new(io::ErrorKind::InvalidInput, e.to_string())
+}
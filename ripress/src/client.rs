@@ -0,0 +1,494 @@
+#![warn(missing_docs)]
+//! A small HTTP client for calling other services from handlers.
+//!
+//! Requests are sent over a connection pool shared by the whole process, so
+//! repeated calls to the same host reuse connections. `https://` URLs need the
+//! `client-tls` feature, which verifies certificates against the system's
+//! trust store. Each request gives up after [`DEFAULT_TIMEOUT`] and reads at
+//! most [`DEFAULT_RESPONSE_LIMIT`] of response body unless told otherwise.
+//!
+//! ## Example
+//!
+//! ```no_run
+//! use ripress::{app::App, client::Client, req::HttpRequest, types::RouterFns};
+//! use serde_json::Value;
+//!
+//! let mut app = App::new();
+//! app.get("/weather", |_req: HttpRequest, res| async move {
+//!     let upstream = Client::get("http://127.0.0.1:4000/forecast")
+//!         .header("accept", "application/json")
+//!         .send()
+//!         .await;
+//!
+//!     match upstream.map(|upstream| upstream.json::<Value>()) {
+//!         Ok(Ok(forecast)) => res.ok().json(forecast),
+//!         _ => res.status(502).text("Forecast unavailable"),
+//!     }
+//! });
+//! ```
+
+use std::{fmt, sync::OnceLock, time::Duration};
+
+use bytes::{Bytes, BytesMut};
+use http_body_util::{BodyExt, Full};
+use hyper::{
+    body::Body,
+    header::{HeaderName, HeaderValue, CONTENT_LENGTH, CONTENT_TYPE},
+    HeaderMap, Request, Response, Uri,
+};
+use hyper_util::{client::legacy::connect::HttpConnector, rt::TokioExecutor};
+use serde::{de::DeserializeOwned, Serialize};
+
+use crate::{
+    helpers::{determine_content_type_response, proxy::strip_hop_by_hop},
+    res::{
+        conversions::OutgoingBody, response_headers::ResponseHeaders, HttpResponse, ResponseBody,
+        ResponseBodyType,
+    },
+    types::HttpMethods,
+};
+
+#[cfg(feature = "client-tls")]
+type Connector = hyper_rustls::HttpsConnector<HttpConnector>;
+#[cfg(not(feature = "client-tls"))]
+type Connector = HttpConnector;
+
+/// The pooled client behind [`Client`] and [`proxy::forward`](crate::helpers::proxy::forward).
+pub(crate) fn pool() -> &'static hyper_util::client::legacy::Client<Connector, OutgoingBody> {
+    static POOL: OnceLock<hyper_util::client::legacy::Client<Connector, OutgoingBody>> =
+        OnceLock::new();
+    POOL.get_or_init(|| {
+        #[cfg(feature = "client-tls")]
+        let connector = hyper_rustls::HttpsConnectorBuilder::new()
+            .with_tls_config(tls_config())
+            .https_or_http()
+            .enable_http1()
+            .build();
+        #[cfg(not(feature = "client-tls"))]
+        let connector = HttpConnector::new();
+        hyper_util::client::legacy::Client::builder(TokioExecutor::new()).build(connector)
+    })
+}
+
+/// The `rustls` config for `https://` upstreams, with the same `ring` provider
+/// as [`App::listen_tls`](crate::app::App::listen_tls) and the system's root
+/// certificates.
+///
+/// Without any root certificates every `https://` request fails verification,
+/// which is logged once here rather than failing the pool.
+#[cfg(feature = "client-tls")]
+fn tls_config() -> tokio_rustls::rustls::ClientConfig {
+    use hyper_rustls::ConfigBuilderExt;
+    use tokio_rustls::rustls::{crypto::ring, ClientConfig, RootCertStore};
+
+    let builder = || {
+        ClientConfig::builder_with_provider(std::sync::Arc::new(ring::default_provider()))
+            .with_safe_default_protocol_versions()
+            .expect("ring supports the default protocol versions")
+    };
+    match builder().with_native_roots() {
+        Ok(builder) => builder.with_no_client_auth(),
+        Err(err) => {
+            tracing::warn!("No root certificates for https:// requests: {}", err);
+            builder()
+                .with_root_certificates(RootCertStore::empty())
+                .with_no_client_auth()
+        }
+    }
+}
+
+/// Whether `uri` has a scheme the pool can connect to.
+pub(crate) fn supports_scheme(uri: &Uri) -> bool {
+    match uri.scheme_str() {
+        Some("https") => cfg!(feature = "client-tls"),
+        scheme => scheme == Some("http"),
+    }
+}
+
+/// Entry point for outbound requests.
+///
+/// Every request shares one connection pool, so there is nothing to construct
+/// or keep around: start a request with [`Client::get`] or one of its siblings.
+pub struct Client;
+
+impl Client {
+    /// Starts building a request with the given method and URL.
+    ///
+    /// The URL may include a query string, e.g. `"http://api/search?q=rust"`.
+    pub fn request(method: HttpMethods, url: &str) -> ClientRequest {
+        ClientRequest {
+            parts: RequestParts::new(method, url),
+            response_limit: DEFAULT_RESPONSE_LIMIT,
+            timeout: DEFAULT_TIMEOUT,
+        }
+    }
+
+    /// Starts building a `GET` request.
+    pub fn get(url: &str) -> ClientRequest {
+        Self::request(HttpMethods::GET, url)
+    }
+
+    /// Starts building a `POST` request.
+    pub fn post(url: &str) -> ClientRequest {
+        Self::request(HttpMethods::POST, url)
+    }
+
+    /// Starts building a `PUT` request.
+    pub fn put(url: &str) -> ClientRequest {
+        Self::request(HttpMethods::PUT, url)
+    }
+
+    /// Starts building a `PATCH` request.
+    pub fn patch(url: &str) -> ClientRequest {
+        Self::request(HttpMethods::PATCH, url)
+    }
+
+    /// Starts building a `DELETE` request.
+    pub fn delete(url: &str) -> ClientRequest {
+        Self::request(HttpMethods::DELETE, url)
+    }
+
+    /// Starts building a `HEAD` request.
+    pub fn head(url: &str) -> ClientRequest {
+        Self::request(HttpMethods::HEAD, url)
+    }
+}
+
+/// Largest response body [`ClientRequest::send`] reads unless changed with
+/// [`ClientRequest::response_limit`].
+pub const DEFAULT_RESPONSE_LIMIT: usize = 10 * 1024 * 1024;
+
+/// How long [`ClientRequest::send`] waits unless changed with
+/// [`ClientRequest::timeout`].
+pub const DEFAULT_TIMEOUT: Duration = Duration::from_secs(30);
+
+/// A request being built by [`Client`].
+pub struct ClientRequest {
+    parts: RequestParts,
+    response_limit: usize,
+    timeout: Duration,
+}
+
+impl ClientRequest {
+    /// Adds a request header.
+    pub fn header(mut self, name: &str, value: &str) -> Self {
+        self.parts.header(name, value);
+        self
+    }
+
+    /// Appends a query parameter, URL-encoding the key and value.
+    pub fn query(mut self, key: &str, value: &str) -> Self {
+        self.parts.query(key, value);
+        self
+    }
+
+    /// Sets a JSON body and the `Content-Type: application/json` header.
+    ///
+    /// ## Errors
+    ///
+    /// Returns an error if `value` cannot be serialized to JSON.
+    pub fn json<T: Serialize>(mut self, value: &T) -> Result<Self, ClientError> {
+        self.parts.json(value)?;
+        Ok(self)
+    }
+
+    /// Sets a plain text body and the `Content-Type: text/plain` header.
+    pub fn text(mut self, text: impl Into<String>) -> Self {
+        self.parts.text(text);
+        self
+    }
+
+    /// Sets a URL-encoded form body and the matching `Content-Type` header.
+    pub fn form(mut self, fields: &[(&str, &str)]) -> Self {
+        self.parts.form(fields);
+        self
+    }
+
+    /// Sets the raw request body without changing any headers.
+    pub fn body(mut self, body: impl Into<Bytes>) -> Self {
+        self.parts.body = body.into();
+        self
+    }
+
+    /// Sets the most bytes of response body to read, [`DEFAULT_RESPONSE_LIMIT`]
+    /// by default. A larger response fails with [`ClientError::ResponseTooLarge`].
+    pub fn response_limit(mut self, max_bytes: usize) -> Self {
+        self.response_limit = max_bytes;
+        self
+    }
+
+    /// Sets how long to wait for the whole exchange, from connecting to reading
+    /// the last byte of the response, [`DEFAULT_TIMEOUT`] by default. A slower
+    /// one fails with [`ClientError::Timeout`].
+    pub fn timeout(mut self, timeout: Duration) -> Self {
+        self.timeout = timeout;
+        self
+    }
+
+    /// Sends the request and reads the whole response.
+    ///
+    /// A response with an error status is still `Ok`; check
+    /// [`ClientResponse::status`].
+    ///
+    /// ## Errors
+    ///
+    /// Returns an error if the URL or a header is invalid, the server can't be
+    /// reached, the response can't be read or is over the
+    /// [`response_limit`](Self::response_limit), or the
+    /// [`timeout`](Self::timeout) passes first.
+    pub async fn send(self) -> Result<ClientResponse, ClientError> {
+        let request = self.parts.into_request(|body| {
+            Full::new(body)
+                .map_err(|never| match never {})
+                .boxed_unsync()
+        })?;
+        if !supports_scheme(request.uri()) {
+            return Err(ClientError::InvalidUrl(format!(
+                "{}: only http:// URLs are supported, and https:// with the `client-tls` feature",
+                request.uri()
+            )));
+        }
+
+        let response_limit = self.response_limit;
+        let exchange = async move {
+            let response = pool()
+                .request(request)
+                .await
+                .map_err(|e| ClientError::Request(e.to_string()))?;
+            ClientResponse::read(response, Some(response_limit)).await
+        };
+        tokio::time::timeout(self.timeout, exchange)
+            .await
+            .map_err(|_| ClientError::Timeout(self.timeout))?
+    }
+}
+
+/// The method, URL, headers and body of a request being built, shared by
+/// [`ClientRequest`] and [`TestRequest`](crate::app::test_client::TestRequest).
+pub(crate) struct RequestParts {
+    method: HttpMethods,
+    url: String,
+    query: Vec<(String, String)>,
+    headers: Vec<(String, String)>,
+    pub(crate) body: Bytes,
+}
+
+impl RequestParts {
+    pub(crate) fn new(method: HttpMethods, url: &str) -> Self {
+        RequestParts {
+            method,
+            url: url.to_string(),
+            query: Vec::new(),
+            headers: Vec::new(),
+            body: Bytes::new(),
+        }
+    }
+
+    pub(crate) fn header(&mut self, name: &str, value: &str) {
+        self.headers.push((name.to_string(), value.to_string()));
+    }
+
+    pub(crate) fn query(&mut self, key: &str, value: &str) {
+        self.query.push((key.to_string(), value.to_string()));
+    }
+
+    pub(crate) fn json<T: Serialize>(&mut self, value: &T) -> Result<(), ClientError> {
+        self.body = serde_json::to_vec(value)
+            .map_err(|e| ClientError::InvalidBody(e.to_string()))?
+            .into();
+        self.header("content-type", "application/json");
+        Ok(())
+    }
+
+    pub(crate) fn text(&mut self, text: impl Into<String>) {
+        self.body = text.into().into();
+        self.header("content-type", "text/plain");
+    }
+
+    pub(crate) fn form(&mut self, fields: &[(&str, &str)]) {
+        self.body = url::form_urlencoded::Serializer::new(String::new())
+            .extend_pairs(fields)
+            .finish()
+            .into();
+        self.header("content-type", "application/x-www-form-urlencoded");
+    }
+
+    /// Builds the request, with the query parameters appended to the URL and
+    /// the body wrapped by `body`.
+    pub(crate) fn into_request<B>(
+        self,
+        body: impl FnOnce(Bytes) -> B,
+    ) -> Result<Request<B>, ClientError> {
+        let mut url = self.url;
+        if !self.query.is_empty() {
+            let query = url::form_urlencoded::Serializer::new(String::new())
+                .extend_pairs(&self.query)
+                .finish();
+            url.push(if url.contains('?') { '&' } else { '?' });
+            url.push_str(&query);
+        }
+        let uri: Uri = url
+            .parse()
+            .map_err(|e| ClientError::InvalidUrl(format!("{}: {}", url, e)))?;
+
+        let mut builder = Request::builder()
+            .method(self.method.to_string().as_str())
+            .uri(uri);
+        for (name, value) in &self.headers {
+            let name = HeaderName::from_bytes(name.as_bytes())
+                .map_err(|_| ClientError::InvalidHeader(name.clone()))?;
+            let value = HeaderValue::from_str(value)
+                .map_err(|_| ClientError::InvalidHeader(name.to_string()))?;
+            builder = builder.header(name, value);
+        }
+        builder
+            .body(body(self.body))
+            .map_err(|e| ClientError::InvalidUrl(e.to_string()))
+    }
+}
+
+/// A response received by a [`ClientRequest`], or by a
+/// [`TestRequest`](crate::app::test_client::TestRequest).
+///
+/// Converts into an [`HttpResponse`], so a handler can pass an upstream
+/// response on as its own.
+#[derive(Debug)]
+pub struct ClientResponse {
+    status: u16,
+    headers: HeaderMap,
+    body: Bytes,
+}
+
+impl ClientResponse {
+    /// Reads the whole of `response`, failing once its body is over `limit` bytes.
+    pub(crate) async fn read<B>(
+        response: Response<B>,
+        limit: Option<usize>,
+    ) -> Result<Self, ClientError>
+    where
+        B: Body<Data = Bytes> + Unpin,
+        B::Error: fmt::Display,
+    {
+        let (parts, mut body) = response.into_parts();
+        let mut buffered = BytesMut::new();
+        while let Some(frame) = body.frame().await {
+            let frame = frame.map_err(|e| ClientError::Request(e.to_string()))?;
+            if let Ok(data) = frame.into_data() {
+                if let Some(limit) = limit.filter(|limit| buffered.len() + data.len() > *limit) {
+                    return Err(ClientError::ResponseTooLarge(limit));
+                }
+                buffered.extend_from_slice(&data);
+            }
+        }
+
+        Ok(ClientResponse {
+            status: parts.status.as_u16(),
+            headers: parts.headers,
+            body: buffered.freeze(),
+        })
+    }
+
+    /// Returns the response status code.
+    pub fn status(&self) -> u16 {
+        self.status
+    }
+
+    /// Returns the first value of a response header, if present and valid UTF-8.
+    pub fn header(&self, name: &str) -> Option<&str> {
+        self.headers.get(name).and_then(|value| value.to_str().ok())
+    }
+
+    /// Returns all response headers, including repeated ones like `Set-Cookie`.
+    pub fn headers(&self) -> &HeaderMap {
+        &self.headers
+    }
+
+    /// Returns the raw response body as sent on the wire.
+    pub fn bytes(&self) -> &Bytes {
+        &self.body
+    }
+
+    /// Returns the response body as text, replacing invalid UTF-8 sequences.
+    pub fn text(&self) -> String {
+        String::from_utf8_lossy(&self.body).into_owned()
+    }
+
+    /// Deserializes the response body as JSON.
+    pub fn json<T: DeserializeOwned>(&self) -> Result<T, serde_json::Error> {
+        serde_json::from_slice(&self.body)
+    }
+}
+
+impl From<ClientResponse> for HttpResponse {
+    /// Keeps the status, headers and body. Hop-by-hop headers and
+    /// `Content-Length` are dropped, since they describe the upstream connection.
+    fn from(upstream: ClientResponse) -> Self {
+        let ClientResponse {
+            status,
+            mut headers,
+            body,
+        } = upstream;
+
+        let body_type = headers
+            .get(CONTENT_TYPE)
+            .and_then(|value| value.to_str().ok())
+            .map(determine_content_type_response)
+            .unwrap_or(ResponseBodyType::BINARY);
+        let body = match body_type {
+            ResponseBodyType::JSON => serde_json::from_slice(&body)
+                .map(ResponseBody::new_json::<serde_json::Value>)
+                .unwrap_or_else(|_| ResponseBody::new_binary(body)),
+            ResponseBodyType::TEXT | ResponseBodyType::HTML => {
+                match String::from_utf8(body.to_vec()) {
+                    Ok(text) if body_type == ResponseBodyType::HTML => ResponseBody::new_html(text),
+                    Ok(text) => ResponseBody::new_text(text),
+                    Err(_) => ResponseBody::new_binary(body),
+                }
+            }
+            ResponseBodyType::BINARY => ResponseBody::new_binary(body),
+        };
+
+        strip_hop_by_hop(&mut headers);
+        headers.remove(CONTENT_LENGTH);
+
+        let mut res = HttpResponse::new().status(status);
+        res.body = body;
+        res.headers = ResponseHeaders::from(headers);
+        res
+    }
+}
+
+/// Why a [`ClientRequest`] failed.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum ClientError {
+    /// The URL could not be parsed or has an unsupported scheme.
+    InvalidUrl(String),
+    /// A header name or value is invalid. Contains the header name.
+    InvalidHeader(String),
+    /// The request body could not be serialized.
+    InvalidBody(String),
+    /// Connecting, sending the request or reading the response failed.
+    Request(String),
+    /// The response body was over [`ClientRequest::response_limit`]. Contains
+    /// the limit in bytes.
+    ResponseTooLarge(usize),
+    /// The exchange took longer than [`ClientRequest::timeout`].
+    Timeout(Duration),
+}
+
+impl std::error::Error for ClientError {}
+
+impl fmt::Display for ClientError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ClientError::InvalidUrl(e) => write!(f, "Invalid URL: {}", e),
+            ClientError::InvalidHeader(name) => write!(f, "Invalid header: {}", name),
+            ClientError::InvalidBody(e) => write!(f, "Invalid body: {}", e),
+            ClientError::Request(e) => write!(f, "Request failed: {}", e),
+            ClientError::ResponseTooLarge(limit) => {
+                write!(f, "Response body exceeded the limit of {} bytes", limit)
+            }
+            ClientError::Timeout(timeout) => write!(f, "Request timed out after {:?}", timeout),
+        }
+    }
+}
@@ -1,7 +1,11 @@
 use std::fmt::Display;
 
-use crate::req::{
-    body::text_data::TextDataError, query_params::QueryParamError, route_params::ParamError,
+use crate::{
+    app::api_error::error_response,
+    req::{
+        body::text_data::TextDataError, query_params::QueryParamError, route_params::ParamError,
+    },
+    res::HttpResponse,
 };
 
 /// Represents the category or type of error that can occurr in the Ripress.
@@ -16,7 +20,17 @@ use crate::req::{
 /// - `ParseError`: An error occurred while parsing data (e.g., query params, body).
 /// - `InvalidInput`: The input provided was invalid or malformed.
 /// - `NotFound`: The requested resource or parameter was not found.
+/// - `Unauthorized`: The request lacks valid authentication.
+/// - `Forbidden`: The client is authenticated but not allowed to do this.
+/// - `Conflict`: The request conflicts with the current state of a resource.
+/// - `TooManyRequests`: The client has sent too many requests.
+/// - `PayloadTooLarge`: The request body is larger than allowed.
+/// - `UnsupportedMediaType`: The request body has a content type that isn't accepted.
+/// - `ServiceUnavailable`: A dependency or the server itself is temporarily unavailable.
+///
+/// New variants may be added in minor releases, so matches need a wildcard arm.
 #[derive(Debug, PartialEq, Eq)]
+#[non_exhaustive]
 pub enum RipressErrorKind {
     /// An input/output error, such as file or network failure.
     IO,
@@ -26,6 +40,61 @@ pub enum RipressErrorKind {
     InvalidInput,
     /// The requested resource or parameter was not found.
     NotFound,
+    /// The request lacks valid authentication.
+    Unauthorized,
+    /// The client is authenticated but not allowed to perform the action.
+    Forbidden,
+    /// The request conflicts with the current state of a resource.
+    Conflict,
+    /// The client has sent too many requests.
+    TooManyRequests,
+    /// The request body is larger than allowed.
+    PayloadTooLarge,
+    /// The request body's content type isn't accepted.
+    UnsupportedMediaType,
+    /// A dependency or the server itself is temporarily unavailable.
+    ServiceUnavailable,
+}
+
+impl RipressErrorKind {
+    /// Returns the HTTP status code a response for this kind of error should use.
+    ///
+    /// | Kind | Status |
+    /// |------|--------|
+    /// | `ParseError`, `InvalidInput` | `400 Bad Request` |
+    /// | `Unauthorized` | `401 Unauthorized` |
+    /// | `Forbidden` | `403 Forbidden` |
+    /// | `NotFound` | `404 Not Found` |
+    /// | `Conflict` | `409 Conflict` |
+    /// | `PayloadTooLarge` | `413 Payload Too Large` |
+    /// | `UnsupportedMediaType` | `415 Unsupported Media Type` |
+    /// | `TooManyRequests` | `429 Too Many Requests` |
+    /// | `IO` | `500 Internal Server Error` |
+    /// | `ServiceUnavailable` | `503 Service Unavailable` |
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use ripress::error::RipressErrorKind;
+    ///
+    /// assert_eq!(RipressErrorKind::NotFound.status_code(), 404);
+    /// assert_eq!(RipressErrorKind::InvalidInput.status_code(), 400);
+    /// ```
+    pub fn status_code(&self) -> u16 {
+        match self {
+            RipressErrorKind::IO => 500,
+            RipressErrorKind::ParseError => 400,
+            RipressErrorKind::InvalidInput => 400,
+            RipressErrorKind::NotFound => 404,
+            RipressErrorKind::Unauthorized => 401,
+            RipressErrorKind::Forbidden => 403,
+            RipressErrorKind::Conflict => 409,
+            RipressErrorKind::TooManyRequests => 429,
+            RipressErrorKind::PayloadTooLarge => 413,
+            RipressErrorKind::UnsupportedMediaType => 415,
+            RipressErrorKind::ServiceUnavailable => 503,
+        }
+    }
 }
 
 impl Display for RipressErrorKind {
@@ -35,6 +104,13 @@ impl Display for RipressErrorKind {
             RipressErrorKind::ParseError => write!(f, "Parse error"),
             RipressErrorKind::InvalidInput => write!(f, "Invalid input"),
             RipressErrorKind::NotFound => write!(f, "Not found"),
+            RipressErrorKind::Unauthorized => write!(f, "Unauthorized"),
+            RipressErrorKind::Forbidden => write!(f, "Forbidden"),
+            RipressErrorKind::Conflict => write!(f, "Conflict"),
+            RipressErrorKind::TooManyRequests => write!(f, "Too many requests"),
+            RipressErrorKind::PayloadTooLarge => write!(f, "Payload too large"),
+            RipressErrorKind::UnsupportedMediaType => write!(f, "Unsupported media type"),
+            RipressErrorKind::ServiceUnavailable => write!(f, "Service unavailable"),
         }
     }
 }
@@ -123,6 +199,50 @@ impl RipressError {
     pub fn kind(&self) -> &RipressErrorKind {
         &self.kind
     }
+
+    /// Returns the HTTP status code for this error, based on its kind.
+    ///
+    /// See [`RipressErrorKind::status_code`] for the mapping.
+    pub fn status_code(&self) -> u16 {
+        self.kind.status_code()
+    }
+
+    /// Converts the error into a response with the matching status code.
+    ///
+    /// The body is the error message, rendered with the app's
+    /// [`ErrorFormat`](crate::app::settings::ErrorFormat) (plain text by default).
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use ripress::context::{HttpRequest, HttpResponse};
+    /// use ripress::error::{RipressError, RipressErrorKind};
+    ///
+    /// fn find_user(id: &str) -> Result<String, RipressError> {
+    ///     Err(RipressError::new(RipressErrorKind::NotFound, format!("no user {}", id)))
+    /// }
+    ///
+    /// async fn handler(req: HttpRequest, res: HttpResponse) -> HttpResponse {
+    ///     match find_user(req.params.get("id").unwrap_or_default()) {
+    ///         Ok(user) => res.ok().text(user),
+    ///         Err(e) => e.into_response(),
+    ///     }
+    /// }
+    ///
+    /// let err = RipressError::new(RipressErrorKind::NotFound, "no user".to_string());
+    /// let res = err.into_response();
+    /// assert_eq!(res.status_code(), 404);
+    /// assert_eq!(res.body_text().unwrap(), "no user");
+    /// ```
+    pub fn into_response(self) -> HttpResponse {
+        error_response(HttpResponse::new(), self.status_code(), &self.message)
+    }
+}
+
+impl From<RipressError> for HttpResponse {
+    fn from(err: RipressError) -> Self {
+        err.into_response()
+    }
 }
 
 impl Display for RipressError {
@@ -171,6 +291,10 @@ impl From<QueryParamError> for RipressError {
                     param, target_type, value
                 ),
             },
+            QueryParamError::Deserialize(message) => Self {
+                kind: RipressErrorKind::ParseError,
+                message: format!("Failed to deserialize query params: {}", message),
+            },
         }
     }
 }
@@ -208,6 +332,10 @@ impl From<TextDataError> for RipressError {
                 kind: RipressErrorKind::InvalidInput,
                 message: format!("Text too large: {} bytes (limit: {} bytes)", size, limit),
             },
+            TextDataError::InvalidEncoding(charset) => Self {
+                kind: RipressErrorKind::ParseError,
+                message: format!("Invalid {} text", charset),
+            },
         }
     }
 }
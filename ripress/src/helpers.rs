@@ -7,18 +7,25 @@ use crate::next::Next;
 use crate::req::body::RequestBodyType;
 use crate::res::ResponseBodyType;
 use crate::{
-    app::api_error::ApiError,
+    app::{
+        api_error::{error_response, ApiError},
+        connection::ReceivedAt,
+        settings::MultipartLimits,
+    },
     middlewares::Middleware,
-    req::{query_params::QueryParams, HttpRequest},
+    req::{query_params::QueryParams, request_data::RequestData, HttpRequest},
     res::HttpResponse,
+    router::route_pattern::MatchedPath,
     types::RouteHandlerReturnType,
 };
-use http_body_util::Full;
+use http_body_util::{BodyExt, Full};
 use hyper::{body::Bytes, Request, Response};
 use mime::Mime;
 use routerify_ng::RequestInfo;
 use url::form_urlencoded::Serializer;
 
+pub mod proxy;
+
 pub(crate) async fn exec_pre_middleware(
     mut req: Request<Full<Bytes>>,
     middleware: Arc<Middleware>,
@@ -49,12 +56,10 @@ pub(crate) async fn exec_pre_middleware(
 }
 
 pub(crate) async fn exec_post_middleware(
-    mut res: Response<Full<Bytes>>,
+    res: Response<Full<Bytes>>,
     middleware: Arc<Middleware>,
     info: RequestInfo,
 ) -> Result<Response<Full<Bytes>>, ApiError> {
-    let mw_func = &middleware.func;
-
     let mut our_req = HttpRequest::from_request_info(&info);
 
     if let Some(data) = info.data::<routerify_ng::RouteParams>() {
@@ -63,22 +68,67 @@ pub(crate) async fn exec_post_middleware(
         });
     }
 
+    run_post_middleware(res, middleware, our_req).await
+}
+
+/// Runs a post-middleware against a response, given the request it answers.
+///
+/// The [`RequestData`] attached to the response by the router, or by an earlier
+/// post-middleware, takes precedence over the request's own, and the data the
+/// middleware leaves on the request is attached for the next one.
+pub(crate) async fn run_post_middleware(
+    res: Response<Full<Bytes>>,
+    middleware: Arc<Middleware>,
+    mut our_req: HttpRequest,
+) -> Result<Response<Full<Bytes>>, ApiError> {
+    if !path_matches(middleware.path.as_str(), &our_req.path) {
+        return Ok(res);
+    }
+
+    let matched_path = res.extensions().get::<MatchedPath>().cloned();
+    our_req.matched_path = matched_path.as_ref().map(|m| m.0.clone());
+    our_req.received_at = res.extensions().get::<ReceivedAt>().map(|at| at.0);
+    if let Some(data) = res.extensions().get::<RequestData>() {
+        our_req.data = data.clone();
+    }
+
+    let mw_func = &middleware.func;
+
+    // Converting drains the body (and, without `with-wynd`, the headers), so
+    // keep copies for when the middleware passes the original response through.
+    let (parts, body) = res.into_parts();
+    let body = body.collect().await?.to_bytes();
+    let headers = parts.headers.clone();
+    let mut res = Response::from_parts(parts, Full::from(body.clone()));
+
     let our_res = match HttpResponse::from_hyper_response(&mut res).await {
         Ok(res) => res,
         Err(e) => {
-            return Err(ApiError::Generic(
-                HttpResponse::new()
-                    .internal_server_error()
-                    .text(e.to_string()),
-            ));
+            return Err(ApiError::Generic(error_response(
+                HttpResponse::new(),
+                500,
+                &e.to_string(),
+            )));
         }
     };
 
-    let (_, maybe_res) = mw_func(our_req, our_res, Next {}).await;
+    let (our_req, maybe_res) = mw_func(our_req, our_res, Next {}).await;
     match maybe_res {
-        None => Ok(res),
+        None => {
+            *res.headers_mut() = headers;
+            *res.body_mut() = Full::from(body);
+            res.extensions_mut().insert(our_req.data);
+            Ok(res)
+        }
         Some(res) => {
-            let hyper_res = res.to_hyper_response().await.unwrap();
+            let mut hyper_res = res.to_hyper_response().await.unwrap();
+            if let Some(matched_path) = matched_path {
+                hyper_res.extensions_mut().insert(matched_path);
+            }
+            if let Some(received_at) = our_req.received_at {
+                hyper_res.extensions_mut().insert(ReceivedAt(received_at));
+            }
+            hyper_res.extensions_mut().insert(our_req.data);
             return Ok(hyper_res);
         }
     }
@@ -95,11 +145,11 @@ pub(crate) async fn exec_wynd_middleware(
 
         match response {
             Err(_e) => {
-                return Err(ApiError::Generic(
-                    HttpResponse::new()
-                        .internal_server_error()
-                        .text("WebSocket handler error"),
-                ));
+                return Err(ApiError::Generic(error_response(
+                    HttpResponse::new(),
+                    500,
+                    "WebSocket handler error",
+                )));
             }
             Ok(res) => {
                 if res.status() == hyper::StatusCode::SWITCHING_PROTOCOLS {
@@ -130,12 +180,20 @@ pub(crate) fn path_matches(prefix: &str, path: &str) -> bool {
 
 pub(crate) fn get_all_query(queries: &QueryParams) -> String {
     let mut ser = Serializer::new(String::new());
-    for (k, v) in queries.iter() {
-        ser.append_pair(k, v);
+    for (k, values) in queries.iter_all() {
+        for v in values {
+            ser.append_pair(k, v);
+        }
     }
     ser.finish()
 }
 
+pub(crate) fn content_type_charset(content_type: &str) -> Option<String> {
+    let mime = content_type.parse::<mime::Mime>().ok()?;
+    mime.get_param(mime::CHARSET)
+        .map(|c| c.as_str().to_string())
+}
+
 pub(crate) fn extract_boundary(content_type: &str) -> Option<String> {
     if let Ok(m) = content_type.parse::<mime::Mime>() {
         if m.type_() == mime::MULTIPART {
@@ -173,10 +231,77 @@ pub(crate) fn find_subsequence(haystack: &[u8], needle: &[u8]) -> Option<usize>
         .position(|window| window == needle)
 }
 
+/// A [`MultipartLimits`] bound exceeded while parsing a multipart body.
+#[derive(Debug, Clone, PartialEq)]
+pub(crate) enum MultipartLimitError {
+    /// The body has more parts than `max_fields`
+    TooManyFields(usize),
+    /// A part name is longer than `max_field_name_len`
+    FieldNameTooLong(usize),
+    /// The value of the named text field is longer than `max_field_value_len`
+    FieldValueTooLong(String, usize),
+}
+
+impl MultipartLimitError {
+    /// Builds the rejection for this error, `413` for sizes and counts and `400`
+    /// for an overlong field name.
+    pub(crate) fn into_response(self, res: HttpResponse) -> HttpResponse {
+        let (status, message) = match self {
+            MultipartLimitError::TooManyFields(limit) => (
+                413,
+                format!(
+                    "Multipart body exceeded the configured limit of {} fields",
+                    limit
+                ),
+            ),
+            MultipartLimitError::FieldNameTooLong(limit) => (
+                400,
+                format!(
+                    "Multipart field name exceeded the configured limit of {} bytes",
+                    limit
+                ),
+            ),
+            MultipartLimitError::FieldValueTooLong(name, limit) => (
+                413,
+                format!(
+                    "Multipart field '{}' exceeded the configured limit of {} bytes",
+                    name, limit
+                ),
+            ),
+        };
+        error_response(res, status, &message)
+    }
+}
+
+/// Checks the name of a part that just started, given how many parts came
+/// before it.
+pub(crate) fn check_multipart_part(
+    index: usize,
+    name: Option<&str>,
+    limits: &MultipartLimits,
+) -> Result<(), MultipartLimitError> {
+    if index >= limits.max_fields {
+        return Err(MultipartLimitError::TooManyFields(limits.max_fields));
+    }
+    match name {
+        Some(name) if name.len() > limits.max_field_name_len => Err(
+            MultipartLimitError::FieldNameTooLong(limits.max_field_name_len),
+        ),
+        _ => Ok(()),
+    }
+}
+
 pub(crate) fn parse_multipart_form<'a>(
     body: &'a [u8],
     boundary: &String,
-) -> (Vec<(&'a str, &'a str)>, Vec<(Vec<u8>, Option<&'a str>)>) {
+    limits: &MultipartLimits,
+) -> Result<
+    (
+        Vec<(&'a str, &'a str)>,
+        Vec<(Vec<u8>, MultipartPartHeaders)>,
+    ),
+    MultipartLimitError,
+> {
     let boundary_start = format!("--{}", boundary);
     let boundary_start_bytes = boundary_start.as_bytes();
     let boundary_next = format!("\r\n--{}", boundary);
@@ -186,7 +311,7 @@ pub(crate) fn parse_multipart_form<'a>(
 
     let mut pos = match find_subsequence(body, boundary_start_bytes) {
         Some(p) => p + boundary_start_bytes.len(),
-        None => return (Vec::new(), Vec::new()),
+        None => return Ok((Vec::new(), Vec::new())),
     };
 
     if body.get(pos..pos + 2) == Some(b"\r\n") {
@@ -194,17 +319,18 @@ pub(crate) fn parse_multipart_form<'a>(
     }
 
     let mut fields: Vec<(&'a str, &'a str)> = Vec::new();
-    let mut file_parts: Vec<(Vec<u8>, Option<&'a str>)> = Vec::new();
+    let mut file_parts: Vec<(Vec<u8>, MultipartPartHeaders)> = Vec::new();
+    let mut part_count = 0;
 
     loop {
         let header_end_rel = match find_subsequence(&body[pos..], b"\r\n\r\n") {
             Some(i) => i,
-            None => return (fields, file_parts),
+            None => return Ok((fields, file_parts)),
         };
         let headers_bytes = &body[pos..pos + header_end_rel];
         let headers_str = match std::str::from_utf8(headers_bytes) {
             Ok(s) => s,
-            Err(_) => return (fields, file_parts),
+            Err(_) => return Ok((fields, file_parts)),
         };
         let content_start = pos + header_end_rel + 4;
 
@@ -213,12 +339,14 @@ pub(crate) fn parse_multipart_form<'a>(
             Some(i) => i,
             None => match find_subsequence(&body[content_start..], boundary_close_bytes) {
                 Some(i2) => i2,
-                None => return (fields, file_parts),
+                None => return Ok((fields, file_parts)),
             },
         };
         let content_end = content_start + next_boundary_rel;
 
-        let mut is_file_part = false;
+        let part_headers = parse_part_headers(headers_str);
+        check_multipart_part(part_count, part_headers.name.as_deref(), limits)?;
+        part_count += 1;
         let mut field_name: Option<&'a str> = None;
         for line in headers_str.lines() {
             let l = line.trim();
@@ -257,22 +385,24 @@ pub(crate) fn parse_multipart_form<'a>(
                             continue;
                         };
 
-                    match key.as_str() {
-                        "name" if !val_str.is_empty() => field_name = Some(val_str),
-                        "filename" | "filename*" if !val_str.is_empty() => {
-                            is_file_part = true;
-                        }
-                        _ => {}
+                    if key == "name" && !val_str.is_empty() {
+                        field_name = Some(val_str);
                     }
                 }
             }
         }
 
-        if is_file_part {
+        if part_headers.is_file() {
             let file_bytes = trim_trailing_crlf(&body[content_start..content_end]).to_vec();
-            file_parts.push((file_bytes, field_name));
+            file_parts.push((file_bytes, part_headers));
         } else if let Some(name) = field_name {
             let value_bytes = trim_trailing_crlf(&body[content_start..content_end]);
+            if value_bytes.len() > limits.max_field_value_len {
+                return Err(MultipartLimitError::FieldValueTooLong(
+                    name.to_string(),
+                    limits.max_field_value_len,
+                ));
+            }
             if let Ok(value_str) = std::str::from_utf8(value_bytes) {
                 fields.push((name, value_str));
             }
@@ -282,11 +412,11 @@ pub(crate) fn parse_multipart_form<'a>(
         if body.get(pos..pos + boundary_next_bytes.len()) == Some(boundary_next_bytes) {
             pos += boundary_next_bytes.len();
         } else if body.get(pos..pos + boundary_close_bytes.len()) == Some(boundary_close_bytes) {
-            return (fields, file_parts);
+            return Ok((fields, file_parts));
         } else {
             match find_subsequence(&body[pos..], boundary_next_bytes) {
                 Some(rel) => pos += rel + boundary_next_bytes.len(),
-                None => return (fields, file_parts),
+                None => return Ok((fields, file_parts)),
             }
         }
 
@@ -318,6 +448,372 @@ pub(crate) fn extract_quoted_or_token(input: &str) -> &str {
     }
 }
 
+/// Largest header block accepted for a single multipart part.
+#[cfg(feature = "file-upload")]
+const MAX_PART_HEADER_SIZE: usize = 16 * 1024;
+
+/// Headers of a single `multipart/form-data` part.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub(crate) struct MultipartPartHeaders {
+    /// The `name` parameter of `Content-Disposition`
+    pub(crate) name: Option<String>,
+    /// The `filename` parameter of `Content-Disposition`, if non-empty
+    pub(crate) filename: Option<String>,
+    /// The part's own `Content-Type` header
+    pub(crate) content_type: Option<String>,
+}
+
+impl MultipartPartHeaders {
+    /// Returns true if the part carries a file rather than a text field
+    pub(crate) fn is_file(&self) -> bool {
+        self.filename.is_some()
+    }
+}
+
+/// Parses the header block of a multipart part.
+///
+/// An RFC 5987 `filename*` parameter takes precedence over a plain `filename`,
+/// as required by RFC 6266. A `filename*` in an unsupported charset is kept
+/// undecoded so the part is still treated as a file.
+pub(crate) fn parse_part_headers(headers: &str) -> MultipartPartHeaders {
+    let mut parsed = MultipartPartHeaders::default();
+
+    for line in headers.lines() {
+        let (key, value) = match line.split_once(':') {
+            Some((k, v)) => (k.trim(), v.trim()),
+            None => continue,
+        };
+
+        if key.eq_ignore_ascii_case("content-type") {
+            if !value.is_empty() {
+                parsed.content_type = Some(value.to_string());
+            }
+        } else if key.eq_ignore_ascii_case("content-disposition") {
+            let mut extended_filename = None;
+            for (k, v) in parse_header_params(value) {
+                if v.is_empty() {
+                    continue;
+                }
+                match k.as_str() {
+                    "name" => parsed.name = Some(v),
+                    "filename" => parsed.filename = Some(v),
+                    "filename*" => extended_filename = Some(decode_rfc5987(&v).unwrap_or(v)),
+                    _ => {}
+                }
+            }
+            if let Some(filename) = extended_filename.filter(|f| !f.is_empty()) {
+                parsed.filename = Some(filename);
+            }
+        }
+    }
+
+    parsed
+}
+
+/// Splits the `;`-separated parameters of a header value such as
+/// `Content-Disposition` into `(lowercase name, value)` pairs.
+///
+/// The leading value (e.g. `form-data`) is skipped. Quoted values may contain
+/// `;` and backslash-escaped quotes, which are unescaped.
+pub(crate) fn parse_header_params(value: &str) -> Vec<(String, String)> {
+    let mut segments = Vec::new();
+    let mut current = String::new();
+    let mut in_quotes = false;
+    let mut escaped = false;
+
+    for ch in value.chars() {
+        if escaped {
+            current.push(ch);
+            escaped = false;
+            continue;
+        }
+        match ch {
+            '\\' if in_quotes => {
+                current.push(ch);
+                escaped = true;
+            }
+            '"' => {
+                current.push(ch);
+                in_quotes = !in_quotes;
+            }
+            ';' if !in_quotes => segments.push(std::mem::take(&mut current)),
+            _ => current.push(ch),
+        }
+    }
+    segments.push(current);
+
+    segments
+        .into_iter()
+        .skip(1)
+        .filter_map(|segment| {
+            let (k, v) = segment.split_once('=')?;
+            Some((k.trim().to_ascii_lowercase(), unquote(v.trim())))
+        })
+        .collect()
+}
+
+/// Removes surrounding quotes from a header parameter value and unescapes
+/// `\"` and `\\` inside it. Unquoted values are returned as-is.
+fn unquote(value: &str) -> String {
+    let inner = match value.strip_prefix('"') {
+        Some(rest) => rest,
+        None => return value.to_string(),
+    };
+
+    let mut unquoted = String::with_capacity(inner.len());
+    let mut chars = inner.chars();
+    while let Some(ch) = chars.next() {
+        match ch {
+            '\\' => {
+                if let Some(next) = chars.next() {
+                    unquoted.push(next);
+                }
+            }
+            '"' => break,
+            _ => unquoted.push(ch),
+        }
+    }
+    unquoted
+}
+
+/// Decodes an RFC 5987 extended parameter value (`charset'language'value`).
+///
+/// Only the `UTF-8` and `ISO-8859-1` charsets are supported. Returns `None` for
+/// other charsets or when the decoded bytes are not valid in the charset.
+pub(crate) fn decode_rfc5987(value: &str) -> Option<String> {
+    let mut parts = value.splitn(3, '\'');
+    let charset = parts.next()?.trim();
+    let _language = parts.next()?;
+    let encoded = parts.next()?;
+
+    let bytes = crate::url::decode_binary(encoded.as_bytes());
+    if charset.eq_ignore_ascii_case("utf-8") {
+        String::from_utf8(bytes.into_owned()).ok()
+    } else if charset.eq_ignore_ascii_case("iso-8859-1") {
+        Some(bytes.iter().map(|&b| b as char).collect())
+    } else {
+        None
+    }
+}
+
+/// Percent-encodes a value for an RFC 5987 extended parameter such as `filename*`.
+pub(crate) fn encode_rfc5987(value: &str) -> String {
+    let mut encoded = String::with_capacity(value.len());
+    for byte in value.bytes() {
+        if byte.is_ascii_alphanumeric() || b"!#$&+-.^_`|~".contains(&byte) {
+            encoded.push(byte as char);
+        } else {
+            encoded.push_str(&format!("%{:02X}", byte));
+        }
+    }
+    encoded
+}
+
+/// Builds a `Content-Disposition` header value such as `attachment; filename="a.pdf"`.
+///
+/// Names that are not plain ASCII also get an RFC 5987 `filename*` parameter, with
+/// an ASCII fallback in `filename` for clients that don't support it.
+pub(crate) fn content_disposition(disposition: &str, filename: &str) -> String {
+    let fallback: String = filename
+        .chars()
+        .map(|c| {
+            if c.is_ascii() && !c.is_ascii_control() && c != '"' && c != '\\' {
+                c
+            } else {
+                '_'
+            }
+        })
+        .collect();
+
+    if fallback == filename {
+        format!("{}; filename=\"{}\"", disposition, filename)
+    } else {
+        format!(
+            "{}; filename=\"{}\"; filename*=UTF-8''{}",
+            disposition,
+            fallback,
+            encode_rfc5987(filename)
+        )
+    }
+}
+
+/// Guesses a file's MIME type from its extension, then from its leading bytes.
+///
+/// Falls back to `application/octet-stream`.
+pub(crate) fn guess_content_type(path: &std::path::Path) -> String {
+    if let Some(mime) = mime_guess::from_path(path).first() {
+        return mime.essence_str().to_string();
+    }
+    infer::get_from_path(path)
+        .ok()
+        .flatten()
+        .map(|kind| kind.mime_type().to_string())
+        .unwrap_or_else(|| "application/octet-stream".to_string())
+}
+
+/// Events emitted by [`MultipartParser`] as the body is fed in.
+#[cfg(feature = "file-upload")]
+#[derive(Debug, PartialEq)]
+pub(crate) enum MultipartEvent {
+    /// A new part begins
+    PartStart(MultipartPartHeaders),
+    /// A chunk of the current part's content
+    Data(Bytes),
+    /// The current part is complete
+    PartEnd,
+}
+
+#[cfg(feature = "file-upload")]
+#[derive(Debug, PartialEq)]
+enum MultipartState {
+    Preamble,
+    AfterBoundary,
+    Headers,
+    Body,
+    Done,
+}
+
+/// Incremental `multipart/form-data` parser.
+///
+/// Unlike [`parse_multipart_form`], it does not need the whole body up front:
+/// chunks are passed to [`feed`](Self::feed) as they arrive and part content is
+/// emitted as soon as it can no longer be the start of a boundary, so only a
+/// boundary's worth of data is held back between chunks.
+#[cfg(feature = "file-upload")]
+pub(crate) struct MultipartParser {
+    delimiter: Vec<u8>,
+    buffer: Vec<u8>,
+    state: MultipartState,
+}
+
+#[cfg(feature = "file-upload")]
+impl MultipartParser {
+    /// Creates a parser for the given boundary (without the leading dashes).
+    pub(crate) fn new(boundary: &str) -> Self {
+        Self {
+            delimiter: format!("\r\n--{}", boundary).into_bytes(),
+            // The first boundary may start the body without a preceding CRLF.
+            buffer: b"\r\n".to_vec(),
+            state: MultipartState::Preamble,
+        }
+    }
+
+    /// Feeds the next chunk of the body, returning the events it completes.
+    pub(crate) fn feed(&mut self, chunk: &[u8]) -> Result<Vec<MultipartEvent>, String> {
+        self.buffer.extend_from_slice(chunk);
+        let mut events = Vec::new();
+
+        loop {
+            match self.state {
+                MultipartState::Preamble => match find_subsequence(&self.buffer, &self.delimiter) {
+                    Some(i) => {
+                        self.buffer.drain(..i + self.delimiter.len());
+                        self.state = MultipartState::AfterBoundary;
+                    }
+                    None => {
+                        let keep = self.delimiter.len().min(self.buffer.len());
+                        self.buffer.drain(..self.buffer.len() - keep);
+                        return Ok(events);
+                    }
+                },
+                MultipartState::AfterBoundary => {
+                    if self.buffer.len() < 2 {
+                        return Ok(events);
+                    }
+                    if self.buffer.starts_with(b"--") {
+                        self.buffer.clear();
+                        self.state = MultipartState::Done;
+                        continue;
+                    }
+                    match find_subsequence(&self.buffer, b"\r\n") {
+                        Some(i) => {
+                            self.buffer.drain(..i + 2);
+                            self.state = MultipartState::Headers;
+                        }
+                        None => return Ok(events),
+                    }
+                }
+                MultipartState::Headers => {
+                    let end = if self.buffer.starts_with(b"\r\n") {
+                        Some((0, 2))
+                    } else {
+                        find_subsequence(&self.buffer, b"\r\n\r\n").map(|i| (i, i + 4))
+                    };
+                    match end {
+                        Some((header_end, content_start)) => {
+                            let headers = std::str::from_utf8(&self.buffer[..header_end])
+                                .map_err(|_| "Multipart part headers are not valid UTF-8")?;
+                            events.push(MultipartEvent::PartStart(parse_part_headers(headers)));
+                            self.buffer.drain(..content_start);
+                            self.state = MultipartState::Body;
+                        }
+                        None if self.buffer.len() > MAX_PART_HEADER_SIZE => {
+                            return Err("Multipart part headers are too large".to_string());
+                        }
+                        None => return Ok(events),
+                    }
+                }
+                MultipartState::Body => match find_subsequence(&self.buffer, &self.delimiter) {
+                    Some(i) => {
+                        if i > 0 {
+                            events.push(MultipartEvent::Data(Bytes::copy_from_slice(
+                                &self.buffer[..i],
+                            )));
+                        }
+                        events.push(MultipartEvent::PartEnd);
+                        self.buffer.drain(..i + self.delimiter.len());
+                        self.state = MultipartState::AfterBoundary;
+                    }
+                    None => {
+                        // Hold back enough to recognise a boundary (or a closing
+                        // boundary missing its CRLF) split across chunks.
+                        let keep = (self.delimiter.len() + 3).min(self.buffer.len());
+                        let emit = self.buffer.len() - keep;
+                        if emit > 0 {
+                            events.push(MultipartEvent::Data(Bytes::copy_from_slice(
+                                &self.buffer[..emit],
+                            )));
+                            self.buffer.drain(..emit);
+                        }
+                        return Ok(events);
+                    }
+                },
+                MultipartState::Done => {
+                    self.buffer.clear();
+                    return Ok(events);
+                }
+            }
+        }
+    }
+
+    /// Signals the end of the body.
+    ///
+    /// Tolerates a closing boundary that is not preceded by a CRLF, and fails
+    /// if the body ended in the middle of a part.
+    pub(crate) fn finish(&mut self) -> Result<Vec<MultipartEvent>, String> {
+        if self.state == MultipartState::Body {
+            let close = [&self.delimiter[2..], b"--"].concat();
+            let data = trim_trailing_crlf(&self.buffer);
+            if let Some(content) = data.strip_suffix(close.as_slice()) {
+                let mut events = Vec::new();
+                let content = trim_trailing_crlf(content);
+                if !content.is_empty() {
+                    events.push(MultipartEvent::Data(Bytes::copy_from_slice(content)));
+                }
+                events.push(MultipartEvent::PartEnd);
+                self.buffer.clear();
+                self.state = MultipartState::Done;
+                return Ok(events);
+            }
+        }
+
+        match self.state {
+            MultipartState::Done | MultipartState::Preamble => Ok(Vec::new()),
+            _ => Err("Unexpected end of multipart body".to_string()),
+        }
+    }
+}
+
 pub(crate) fn box_future<F>(future: F) -> RouteHandlerReturnType
 where
     F: Future<Output = HttpResponse> + Send + 'static,
@@ -356,6 +852,15 @@ pub trait FromRequest: Sized {
     ///
     /// Returns `Ok(Self)` if extraction is successful, or `Err(Self::Error)` if it fails.
     fn from_request(req: &HttpRequest) -> Result<Self, Self::Error>;
+
+    /// Builds the response sent instead of calling the route handler when
+    /// extraction fails.
+    ///
+    /// Defaults to `400 Bad Request` with the error message, rendered with the
+    /// app's [`ErrorFormat`](crate::app::settings::ErrorFormat).
+    fn rejection(error: Self::Error, res: HttpResponse) -> HttpResponse {
+        error_response(res, 400, &format!("Extraction failed: {}", error))
+    }
 }
 
 /// A helper trait for extracting parameters from an owned `HttpRequest`.
@@ -390,6 +895,27 @@ pub trait ExtractFromOwned: Sized {
     /// For `HttpRequest`, this simply moves the request (no clone).
     /// For other types, this uses `FromRequest` which may clone the extracted type.
     fn extract_from_owned(req: HttpRequest) -> Result<Self, Self::Error>;
+
+    /// Builds the response sent when extraction fails; see [`FromRequest::rejection`].
+    fn rejection(error: Self::Error, res: HttpResponse) -> HttpResponse {
+        error_response(res, 400, &format!("Extraction failed: {}", error))
+    }
+}
+
+/// The error of a tuple of extractors, from the first one that failed.
+///
+/// Displays which extractor failed and why, and keeps the response that
+/// extractor asked for, so e.g. a validation failure keeps its status code.
+#[derive(Debug)]
+pub struct TupleRejection {
+    message: String,
+    response: HttpResponse,
+}
+
+impl Display for TupleRejection {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str(&self.message)
+    }
 }
 
 impl ExtractFromOwned for HttpRequest {
@@ -409,6 +935,10 @@ where
     fn extract_from_owned(req: HttpRequest) -> Result<Self, Self::Error> {
         T::from_request(&req)
     }
+
+    fn rejection(error: Self::Error, res: HttpResponse) -> HttpResponse {
+        T::rejection(error, res)
+    }
 }
 
 /// Macro to generate tuple implementations for ExtractFromOwned up to N.
@@ -419,26 +949,33 @@ macro_rules! impl_extract_from_owned_tuples {
             where
                 $($T: ExtractFromOwned + Send + 'static),+
             {
-                type Error = String;
+                type Error = TupleRejection;
 
                 fn extract_from_owned(req: HttpRequest) -> Result<Self, Self::Error> {
                     $(
                         #[allow(non_snake_case)]
                         let $T = {
                             $T::extract_from_owned(req.clone())
-                                .map_err(|e| format!(
-                                    concat!(
-                                        "Failed to extract ",
-                                        stringify!($T),
-                                        " parameter: {}"
+                                .map_err(|e| TupleRejection {
+                                    message: format!(
+                                        concat!(
+                                            "Failed to extract ",
+                                            stringify!($T),
+                                            " parameter: {}"
+                                        ),
+                                        e
                                     ),
-                                    e
-                                ))?
+                                    response: $T::rejection(e, HttpResponse::new()),
+                                })?
                         };
                     )+
 
                     Ok(($($T,)+))
                 }
+
+                fn rejection(error: Self::Error, _res: HttpResponse) -> HttpResponse {
+                    error.response
+                }
             }
         )+
     };
@@ -471,14 +1008,20 @@ pub(crate) fn determine_content_type_request(content_type: &str) -> RequestBodyT
                 RequestBodyType::FORM
             }
             (mime::MULTIPART, subtype) if subtype == "form-data" => RequestBodyType::MultipartForm,
+            (mime::TEXT, mime::XML) => RequestBodyType::XML,
             (mime::TEXT, _) => RequestBodyType::TEXT,
             (mime::APPLICATION, subtype) if subtype.as_str().ends_with("+json") => {
                 RequestBodyType::JSON
             }
             (mime::APPLICATION, subtype)
-                if subtype == "xml" || subtype.as_str().ends_with("+xml") =>
+                if subtype == mime::XML || mime_type.suffix() == Some(mime::XML) =>
+            {
+                RequestBodyType::XML
+            }
+            (mime::APPLICATION, subtype)
+                if subtype == "msgpack" || subtype == "x-msgpack" || subtype == "vnd.msgpack" =>
             {
-                RequestBodyType::TEXT
+                RequestBodyType::MSGPACK
             }
             _ => RequestBodyType::BINARY,
         },
@@ -512,3 +1055,114 @@ pub(crate) fn determine_content_type_response(content_type: &str) -> ResponseBod
         Err(_) => ResponseBodyType::BINARY,
     }
 }
+
+/// Parses a quality-value list such as `text/html, application/json;q=0.9` into
+/// `(value, q)` pairs. Entries with a malformed or out-of-range q-value are skipped.
+pub(crate) fn parse_quality_list(header: &str) -> Vec<(&str, f32)> {
+    header
+        .split(',')
+        .filter_map(|entry| {
+            let mut parts = entry.split(';');
+            let value = parts.next()?.trim();
+            if value.is_empty() {
+                return None;
+            }
+
+            let mut quality = 1.0;
+            for param in parts {
+                if let Some((key, raw)) = param.split_once('=') {
+                    if key.trim().eq_ignore_ascii_case("q") {
+                        quality = raw
+                            .trim()
+                            .parse::<f32>()
+                            .ok()
+                            .filter(|q| (0.0..=1.0).contains(q))?;
+                    }
+                }
+            }
+
+            Some((value, quality))
+        })
+        .collect()
+}
+
+/// Returns how specifically the media range `range` matches the `offered` type:
+/// `2` for an exact match, `1` for `type/*` and `0` for `*/*`.
+pub(crate) fn media_type_specificity(range: &str, offered: &str) -> Option<u8> {
+    let (range_type, range_subtype) = range.split_once('/')?;
+    let offered = offered.split(';').next().unwrap_or(offered).trim();
+    let (offered_type, offered_subtype) = offered.split_once('/')?;
+
+    if range_type == "*" && range_subtype == "*" {
+        Some(0)
+    } else if !range_type.eq_ignore_ascii_case(offered_type) {
+        None
+    } else if range_subtype == "*" {
+        Some(1)
+    } else if range_subtype.eq_ignore_ascii_case(offered_subtype) {
+        Some(2)
+    } else {
+        None
+    }
+}
+
+/// Returns how specifically a token (charset, encoding) matches the `offered` one:
+/// `1` for an exact, case-insensitive match and `0` for `*`.
+pub(crate) fn token_specificity(range: &str, offered: &str) -> Option<u8> {
+    if range == "*" {
+        Some(0)
+    } else if range.eq_ignore_ascii_case(offered) {
+        Some(1)
+    } else {
+        None
+    }
+}
+
+/// Picks the best of `offered` for a negotiation header such as `Accept`.
+///
+/// Each offer takes the q-value of the most specific range that matches it. The offer
+/// with the highest q wins; at equal q the more specific match wins, then the earlier
+/// offer. Offers that only match with `q=0` are never returned. When the header is
+/// absent every offer is acceptable, so the first one is returned.
+pub(crate) fn negotiate<'a, F>(
+    header: Option<&str>,
+    offered: &[&'a str],
+    specificity: F,
+) -> Option<&'a str>
+where
+    F: Fn(&str, &str) -> Option<u8>,
+{
+    let header = match header {
+        Some(header) => header,
+        None => return offered.first().copied(),
+    };
+
+    let accepted = parse_quality_list(header);
+    let mut best: Option<(&'a str, f32, u8)> = None;
+
+    for &offer in offered {
+        let matched = accepted
+            .iter()
+            .filter_map(|(range, quality)| specificity(range, offer).map(|s| (s, *quality)))
+            .max_by_key(|(s, _)| *s);
+
+        if let Some((spec, quality)) = matched {
+            if quality <= 0.0 {
+                continue;
+            }
+
+            let is_better = match best {
+                None => true,
+                Some((_, best_quality, best_spec)) => {
+                    quality > best_quality || (quality == best_quality && spec > best_spec)
+                }
+            };
+
+            if is_better {
+                best = Some((offer, quality, spec));
+            }
+        }
+    }
+
+    best.map(|(offer, _, _)| offer)
+}
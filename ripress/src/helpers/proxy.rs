@@ -0,0 +1,210 @@
+#![warn(missing_docs)]
+//! Reverse proxying requests to another HTTP service.
+//!
+//! [`forward`] sends the request a handler received to an upstream server and
+//! returns the upstream's response, so a route can put another service behind
+//! the app:
+//!
+//! ```no_run
+//! use ripress::{app::App, helpers::proxy, req::HttpRequest, types::RouterFns};
+//!
+//! let mut app = App::new();
+//! app.get("/api/*path", |req: HttpRequest, _res| async move {
+//!     proxy::forward(&req, "http://127.0.0.1:4000").await
+//! });
+//! ```
+
+use futures::StreamExt;
+use http_body_util::{BodyExt, StreamBody};
+use hyper::{
+    body::Frame,
+    header::{HeaderName, HeaderValue, CONNECTION, CONTENT_TYPE, HOST},
+    HeaderMap, Request, Uri,
+};
+
+use crate::{
+    app::api_error::error_response,
+    client::{pool, supports_scheme},
+    req::HttpRequest,
+    res::{response_headers::ResponseHeaders, HttpResponse, HttpResponseError},
+};
+
+/// Headers that describe a single connection rather than the message, which a
+/// proxy must not pass on (RFC 7230, section 6.1).
+const HOP_BY_HOP: [&str; 8] = [
+    "connection",
+    "keep-alive",
+    "proxy-authenticate",
+    "proxy-authorization",
+    "te",
+    "trailer",
+    "transfer-encoding",
+    "upgrade",
+];
+
+/// Forwards `req` to `upstream` and returns the upstream's response.
+///
+/// The request path and query are appended to `upstream`, so forwarding
+/// `GET /api/users?page=2` to `http://users:4000` requests
+/// `http://users:4000/api/users?page=2`, and to `http://users:4000/v1` requests
+/// `http://users:4000/v1/api/users?page=2`. The method, headers and body are
+/// sent as received, except that:
+///
+/// - Hop-by-hop headers (`Connection`, `Transfer-Encoding`, `Upgrade` and the
+///   others listed in RFC 7230, plus any named in `Connection`) are removed, in
+///   both directions.
+/// - `Host` names the upstream.
+/// - The client's address is appended to `X-Forwarded-For`, and
+///   `X-Forwarded-Host` and `X-Forwarded-Proto` describe the original request.
+///   Values the client sent are kept only when the peer is a trusted proxy (see
+///   [`App::trust_proxy`](crate::app::App::trust_proxy)), and its
+///   `X-Forwarded-Host` only once a policy has been set.
+///
+/// The upstream's status, headers and body are returned as they are. Without a
+/// `Content-Type` from the upstream, the response is `application/octet-stream`.
+/// The upstream's body is streamed to the client as it arrives; if the upstream
+/// fails mid-response, the failure is logged and the connection is closed. The
+/// request body is streamed upstream too on routes marked with
+/// [`RouteHandle::stream_body`](crate::router::route_handle::RouteHandle::stream_body),
+/// and otherwise sent as it was read before the handler ran.
+/// Upstream connections share [`Client`](crate::client::Client)'s pool, and
+/// `https://` upstreams need the `client-tls` feature. If the upstream can't be
+/// reached, the response is `502 Bad Gateway`; an `upstream` that isn't a valid
+/// URL is logged and answered with `500 Internal Server Error`.
+pub async fn forward(req: &HttpRequest, upstream: &str) -> HttpResponse {
+    let mut request = match req.to_hyper_request() {
+        Ok(request) => request,
+        Err(e) => {
+            tracing::error!("Could not rebuild the request for {}: {}", upstream, e);
+            return error_response(HttpResponse::new(), 500, "Could not forward request");
+        }
+    };
+    let uri = match upstream_uri(upstream, request.uri()) {
+        Ok(uri) => uri,
+        Err(e) => {
+            tracing::error!("Invalid upstream URL {:?}: {}", upstream, e);
+            return error_response(HttpResponse::new(), 500, "Invalid upstream URL");
+        }
+    };
+    request.extensions_mut().clear();
+    *request.uri_mut() = uri;
+    let headers = request.headers_mut();
+    strip_hop_by_hop(headers);
+    set_forwarded_headers(headers, req);
+    headers.remove(HOST);
+
+    let (parts, body) = request.into_parts();
+    let body = if req.incoming.is_some() {
+        StreamBody::new(req.body_stream().map(|chunk| {
+            chunk
+                .map(Frame::data)
+                .map_err(|e| Box::new(e) as Box<dyn std::error::Error + Send + Sync>)
+        }))
+        .boxed_unsync()
+    } else {
+        body.map_err(|never| match never {}).boxed_unsync()
+    };
+
+    let response = match pool().request(Request::from_parts(parts, body)).await {
+        Ok(response) => response,
+        Err(e) => {
+            tracing::warn!("Upstream {} failed: {}", upstream, e);
+            return error_response(HttpResponse::new(), 502, "Bad Gateway");
+        }
+    };
+
+    let (mut parts, body) = response.into_parts();
+    strip_hop_by_hop(&mut parts.headers);
+    if !parts.headers.contains_key(CONTENT_TYPE) {
+        parts.headers.insert(
+            CONTENT_TYPE,
+            HeaderValue::from_static("application/octet-stream"),
+        );
+    }
+
+    let upstream = upstream.to_string();
+    let body = body.into_data_stream().map(move |chunk| {
+        chunk.map_err(|e| {
+            tracing::warn!("Upstream {} failed mid-response: {}", upstream, e);
+            HttpResponseError::IoError(std::io::Error::other(e))
+        })
+    });
+
+    let mut res = HttpResponse::new().status(parts.status.as_u16());
+    res.headers = ResponseHeaders::from(parts.headers);
+    res.stream = Some(Box::pin(body));
+    res
+}
+
+/// `upstream` with the path and query of `original` appended.
+fn upstream_uri(upstream: &str, original: &Uri) -> Result<Uri, String> {
+    let base: Uri = upstream.parse().map_err(|e| format!("{}", e))?;
+    let (scheme, authority) = match (base.scheme_str(), base.authority()) {
+        (Some(scheme), Some(authority)) if supports_scheme(&base) => (scheme, authority),
+        _ => {
+            return Err(String::from(
+                "expected an http:// URL, or https:// with the `client-tls` feature",
+            ))
+        }
+    };
+
+    let path_and_query = original.path_and_query().map_or("/", |pq| pq.as_str());
+    format!(
+        "{}://{}{}{}",
+        scheme,
+        authority,
+        base.path().trim_end_matches('/'),
+        path_and_query
+    )
+    .parse()
+    .map_err(|e| format!("{}", e))
+}
+
+pub(crate) fn strip_hop_by_hop(headers: &mut HeaderMap) {
+    let named: Vec<HeaderName> = headers
+        .get_all(CONNECTION)
+        .iter()
+        .filter_map(|value| value.to_str().ok())
+        .flat_map(|value| value.split(','))
+        .filter_map(|name| HeaderName::from_bytes(name.trim().as_bytes()).ok())
+        .collect();
+    for name in named {
+        headers.remove(name);
+    }
+    for name in HOP_BY_HOP {
+        headers.remove(name);
+    }
+}
+
+fn set_forwarded_headers(headers: &mut HeaderMap, req: &HttpRequest) {
+    let trusted = req.settings.trusted_proxy;
+
+    if let Some(peer) = req.settings.peer_ip {
+        let forwarded_for = match headers.get("x-forwarded-for").and_then(|v| v.to_str().ok()) {
+            Some(existing) if trusted => format!("{}, {}", existing, peer),
+            _ => peer.to_string(),
+        };
+        if let Ok(value) = HeaderValue::from_str(&forwarded_for) {
+            headers.insert("x-forwarded-for", value);
+        }
+    } else if !trusted {
+        headers.remove("x-forwarded-for");
+    }
+
+    if !req.settings.trusted_host || !headers.contains_key("x-forwarded-host") {
+        match headers.get(HOST).cloned() {
+            Some(host) => {
+                headers.insert("x-forwarded-host", host);
+            }
+            None => {
+                headers.remove("x-forwarded-host");
+            }
+        }
+    }
+
+    if !trusted || !headers.contains_key("x-forwarded-proto") {
+        if let Ok(proto) = HeaderValue::from_str(&req.protocol) {
+            headers.insert("x-forwarded-proto", proto);
+        }
+    }
+}
@@ -53,6 +53,7 @@
 //! - **`compression`**: Response compression middleware (gzip/deflate)
 //! - **`file-upload`**: File upload middleware for multipart form data
 //! - **`logger`**: Request/response logging middleware
+//! - **`request-id`**: Correlation ID middleware (`X-Request-Id`)
 //! - **`with-wynd`**: WebSocket support via the `wynd` crate
 //!
 //! ## Advanced Examples
@@ -279,6 +280,11 @@
 /// ```
 pub mod app;
 
+/// An HTTP client for calling other services from handlers.
+///
+/// See [`client::Client`] for building and sending requests.
+pub mod client;
+
 /// The HTTP request struct and its methods for extracting data from requests.
 ///
 /// `HttpRequest` provides comprehensive access to incoming HTTP request data including
@@ -378,6 +384,7 @@ pub mod helpers;
 /// - **Body Limit**: Request body size limiting
 /// - **Shield**: Security headers and protection
 /// - **File Upload**: Multipart form data and file upload handling (requires `file-upload` feature)
+/// - **Request ID**: Correlation IDs via `X-Request-Id` (requires `request-id` feature)
 ///
 /// # Examples
 ///
@@ -418,6 +425,10 @@ pub mod router;
 /// - `RequestBodyType`: Enum for request body types
 pub mod types;
 
+// Lets the derive macros' `::ripress::` paths resolve in the crate's own tests.
+#[cfg(test)]
+extern crate self as ripress;
+
 /// Internal test module for framework testing.
 mod tests;
 
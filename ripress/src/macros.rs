@@ -38,4 +38,4 @@ macro_rules! middlewares {
 }
 
 #[doc(inline)]
-pub use ripress_derive::{FromData, FromJson, FromParams, FromQueryParam};
+pub use ripress_derive::{FromData, FromJson, FromParams, FromQueryParam, FromRequest};
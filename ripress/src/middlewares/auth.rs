@@ -0,0 +1,123 @@
+#![warn(missing_docs)]
+use std::{collections::HashMap, future::Future, hash::BuildHasher, sync::Arc};
+
+use futures::future::BoxFuture;
+
+use crate::{
+    app::api_error::error_response, context::HttpResponse, next::Next,
+    req::request_headers::Authorization, req::HttpRequest, types::MiddlewareOutput,
+};
+
+/// Checks the username and password of a `Basic` `Authorization` header.
+///
+/// Implemented for a `HashMap` of usernames to passwords, which compares
+/// credentials in constant time, and for async closures taking the username
+/// and password, e.g. to look users up in a database.
+///
+/// ## Example
+///
+/// ```
+/// use std::collections::HashMap;
+///
+/// use ripress::app::App;
+///
+/// let mut app = App::new();
+///
+/// // A fixed set of users
+/// let users = HashMap::from([("admin".to_string(), "s3cret".to_string())]);
+/// app.use_basic_auth(users, "Admin area");
+///
+/// // Any async check
+/// app.use_basic_auth(
+///     |username: String, password: String| async move {
+///         username == "ops" && password == std::env::var("OPS_PASSWORD").unwrap_or_default()
+///     },
+///     "Ops",
+/// );
+/// ```
+pub trait BasicAuthVerifier: Send + Sync + 'static {
+    /// Returns `true` if `password` is correct for `username`.
+    fn verify<'a>(&'a self, username: &'a str, password: &'a str) -> BoxFuture<'a, bool>;
+}
+
+impl<K, V, S> BasicAuthVerifier for HashMap<K, V, S>
+where
+    K: AsRef<str> + Send + Sync + 'static,
+    V: AsRef<str> + Send + Sync + 'static,
+    S: BuildHasher + Send + Sync + 'static,
+{
+    fn verify<'a>(&'a self, username: &'a str, password: &'a str) -> BoxFuture<'a, bool> {
+        // Every entry is compared so the time taken doesn't reveal which
+        // usernames exist.
+        let mut valid = false;
+        for (user, pass) in self {
+            valid |= constant_time_eq(user.as_ref().as_bytes(), username.as_bytes())
+                & constant_time_eq(pass.as_ref().as_bytes(), password.as_bytes());
+        }
+        Box::pin(futures::future::ready(valid))
+    }
+}
+
+impl<F, Fut> BasicAuthVerifier for F
+where
+    F: Fn(String, String) -> Fut + Send + Sync + 'static,
+    Fut: Future<Output = bool> + Send + 'static,
+{
+    fn verify<'a>(&'a self, username: &'a str, password: &'a str) -> BoxFuture<'a, bool> {
+        Box::pin(self(username.to_string(), password.to_string()))
+    }
+}
+
+/// Compares two byte strings in time that depends only on their lengths.
+fn constant_time_eq(a: &[u8], b: &[u8]) -> bool {
+    let mut diff = a.len() ^ b.len();
+    for i in 0..a.len().max(b.len()) {
+        let x = a.get(i).copied().unwrap_or(0);
+        let y = b.get(i).copied().unwrap_or(0);
+        diff |= usize::from(x ^ y);
+    }
+    diff == 0
+}
+
+/// The `WWW-Authenticate` challenge for `realm`, quoting it as RFC 7617 requires.
+fn challenge(realm: &str) -> String {
+    let realm = realm.replace('\\', "\\\\").replace('"', "\\\"");
+    format!("Basic realm=\"{}\", charset=\"UTF-8\"", realm)
+}
+
+/// Creates a middleware that requires HTTP Basic authentication
+///
+/// Requests without valid credentials get `401 Unauthorized` with a
+/// `WWW-Authenticate` challenge, which makes browsers show a login dialog.
+/// Credentials that aren't valid Base64, aren't UTF-8 or lack the `:`
+/// separator are rejected the same way.
+///
+/// # Arguments
+///
+/// * `verifier` - Checks the decoded username and password
+/// * `realm` - Shown by browsers in the login dialog
+///
+/// # Returns
+///
+/// A middleware function that lets authenticated requests through
+pub(crate) fn basic_auth(
+    verifier: Arc<dyn BasicAuthVerifier>,
+    realm: &str,
+) -> impl Fn(HttpRequest, HttpResponse, Next) -> MiddlewareOutput + Send + Sync + 'static {
+    let challenge = challenge(realm);
+    move |req: HttpRequest, res, _next| {
+        let verifier = Arc::clone(&verifier);
+        let challenge = challenge.clone();
+        Box::pin(async move {
+            if let Some(Authorization::Basic { username, password }) = req.authorization() {
+                if verifier.verify(&username, &password).await {
+                    return (req, None);
+                }
+            }
+
+            let res =
+                error_response(res, 401, "Unauthorized").set_header("WWW-Authenticate", challenge);
+            (req, Some(res))
+        })
+    }
+}
@@ -1,14 +1,24 @@
 #![warn(missing_docs)]
 use crate::{
-    context::HttpResponse, next::Next, req::HttpRequest, res::response_status::StatusCode,
-    types::MiddlewareOutput,
+    app::api_error::error_response,
+    context::HttpResponse,
+    next::Next,
+    req::HttpRequest,
+    res::response_status::StatusCode,
+    router::route_pattern::RouteTable,
+    types::{HttpMethods, MiddlewareOutput, RouteBodyLimits, StreamedBodyRoutes},
 };
+use bytes::{Bytes, BytesMut};
+use http_body_util::{BodyExt, Full};
+use hyper::{body::Body, header::CONTENT_LENGTH, Method, Request};
+use std::sync::Arc;
 
 /// Middleware for limiting the maximum allowed size of the HTTP request body.
 ///
 /// This middleware checks the length of the incoming request body and rejects requests
 /// whose body exceeds the configured limit. If the body is too large, it returns a
-/// `413 Payload Too Large` response with a JSON error message.
+/// `413 Payload Too Large` response rendered with the app's
+/// [`ErrorFormat`](crate::app::settings::ErrorFormat).
 ///
 /// # Arguments
 ///
@@ -34,17 +44,15 @@ use crate::{
 ///
 /// # Error Response
 ///
-/// If the body is too large, the response will be:
+/// If the body is too large, the message is rendered as plain text by default, or
+/// like this with [`ErrorFormat::Json`](crate::app::settings::ErrorFormat::Json):
 ///
 /// ```json
 /// {
-///   "error": "Request body too large",
-///   "message": "Request body exceeded the configured limit of 1048576 bytes",
-///   "limit": 1048576,
-///   "received": 2097152
+///   "error": "Request body of 2097152 bytes exceeded the configured limit of 1048576 bytes"
 /// }
 /// ```
-const DEFAULT_BODY_LIMIT: usize = 1024 * 1024;
+pub(crate) const DEFAULT_BODY_LIMIT: usize = 1024 * 1024;
 
 pub(crate) fn body_limit(
     config: Option<usize>,
@@ -52,24 +60,141 @@ pub(crate) fn body_limit(
     let config = config.unwrap_or(DEFAULT_BODY_LIMIT);
     move |req: HttpRequest, res, _| {
         Box::pin(async move {
+            // A route's own limit replaces the app-wide one.
+            let config = req.body_limit().unwrap_or(config);
             let body = req.clone().body;
 
             if body.len() > config {
-                eprintln!(
+                tracing::debug!(
                     "Body limit exceeded: {} bytes > {} bytes",
                     body.len(),
                     config
                 );
 
-                return (req, Some(res.status(StatusCode::PayloadTooLarge.as_u16()).json(serde_json::json!({
-                    "error": "Request body too large",
-                    "message": format!("Request body exceeded the configured limit of {} bytes", config),
-                    "limit": config,
-                    "received": body.len()
-                }))));
+                let res = body_too_large(res, body.len(), config);
+                return (req, Some(res));
             }
 
             return (req, None);
         })
     }
 }
+
+/// The body limit of each request: the app-wide one unless the matched route
+/// sets its own. Also knows which routes stream their body.
+pub(crate) struct BodyLimits {
+    default: Option<usize>,
+    routes: RouteBodyLimits,
+    streamed: StreamedBodyRoutes,
+    table: Arc<RouteTable>,
+}
+
+impl BodyLimits {
+    pub(crate) fn new(
+        default: Option<usize>,
+        routes: RouteBodyLimits,
+        streamed: StreamedBodyRoutes,
+        table: Arc<RouteTable>,
+    ) -> Self {
+        BodyLimits {
+            default,
+            routes,
+            streamed,
+            table,
+        }
+    }
+
+    /// Whether the route matching a request leaves its body to be streamed.
+    pub(crate) fn streams(&self, method: &Method, path: &str) -> bool {
+        if self.streamed.is_empty() {
+            return false;
+        }
+        let method = HttpMethods::from(method);
+        self.table
+            .find(&method, path)
+            .is_some_and(|route| self.streamed.contains(&(route.pattern, method)))
+    }
+
+    /// The limit for a request, found before its body is read.
+    pub(crate) fn resolve(&self, method: &Method, path: &str) -> Option<usize> {
+        if !self.routes.is_empty() {
+            let method = HttpMethods::from(method);
+            if let Some(route) = self.table.find(&method, path) {
+                if let Some(limit) = self.routes.get(&(route.pattern, method)) {
+                    return Some(*limit);
+                }
+            }
+        }
+        self.default
+    }
+}
+
+/// Reads the body of `req` into a `Full` body, up to `limit` bytes if set.
+///
+/// Unlike the middleware, this doesn't trust the declared length: reading stops
+/// as soon as the bytes received exceed `limit`, so a chunked body or one with a
+/// false `Content-Length` is cut off mid-stream. A `Content-Length` over the limit
+/// is refused before anything is read. The error is the response to send.
+pub(crate) async fn read_body<B>(
+    req: Request<B>,
+    limit: Option<usize>,
+) -> Result<Request<Full<Bytes>>, HttpResponse>
+where
+    B: Body<Data = Bytes> + Unpin,
+    B::Error: std::fmt::Display,
+{
+    if let Some(limit) = limit.filter(|limit| declares_more_than(&req, *limit)) {
+        return Err(payload_too_large(limit));
+    }
+
+    let (parts, mut body) = req.into_parts();
+    let mut buffered = BytesMut::new();
+    while let Some(frame) = body.frame().await {
+        let frame = frame.map_err(|e| {
+            error_response(
+                HttpResponse::new(),
+                400,
+                &format!("Failed to read request body: {}", e),
+            )
+        })?;
+        if let Ok(data) = frame.into_data() {
+            if let Some(limit) = limit.filter(|limit| buffered.len() + data.len() > *limit) {
+                tracing::debug!("Body limit exceeded while reading: > {} bytes", limit);
+                return Err(payload_too_large(limit));
+            }
+            buffered.extend_from_slice(&data);
+        }
+    }
+
+    Ok(Request::from_parts(parts, Full::from(buffered.freeze())))
+}
+
+/// Whether the `Content-Length` of `req` is over `limit`, known before its body is read.
+pub(crate) fn declares_more_than<B>(req: &Request<B>, limit: usize) -> bool {
+    req.headers()
+        .get(CONTENT_LENGTH)
+        .and_then(|value| value.to_str().ok())
+        .and_then(|value| value.parse::<u64>().ok())
+        .is_some_and(|len| len > limit as u64)
+}
+
+pub(crate) fn payload_too_large(limit: usize) -> HttpResponse {
+    let message = format!(
+        "Request body exceeded the configured limit of {} bytes",
+        limit
+    );
+    error_response(
+        HttpResponse::new(),
+        StatusCode::PayloadTooLarge.as_u16(),
+        &message,
+    )
+}
+
+/// Builds the 413 response for a fully read body of `len` bytes.
+pub(crate) fn body_too_large(res: HttpResponse, len: usize, limit: usize) -> HttpResponse {
+    let message = format!(
+        "Request body of {} bytes exceeded the configured limit of {} bytes",
+        len, limit
+    );
+    error_response(res, StatusCode::PayloadTooLarge.as_u16(), &message)
+}
@@ -12,6 +12,12 @@ pub struct CompressionConfig {
     pub threshold: usize,
     /// Compression level (0-9, where 6 is default, 9 is maximum compression)
     pub level: u8,
+    /// Additional content types to compress on top of the built-in text-based types.
+    /// Entries are `type/subtype` patterns and may use wildcards such as `text/*`
+    pub compressible_types: Vec<String>,
+    /// Content types that are never compressed, even if they match the built-in
+    /// types or `compressible_types`. Supports the same wildcard patterns
+    pub incompressible_types: Vec<String>,
 }
 
 impl Default for CompressionConfig {
@@ -19,10 +25,31 @@ impl Default for CompressionConfig {
         Self {
             threshold: 1024,
             level: 6,
+            compressible_types: Vec::new(),
+            incompressible_types: Vec::new(),
         }
     }
 }
 
+impl CompressionConfig {
+    /// Checks whether a response with the given `Content-Type` should be compressed
+    pub fn is_compressible(&self, content_type: &str) -> bool {
+        if self
+            .incompressible_types
+            .iter()
+            .any(|pattern| content_type_matches(pattern, content_type))
+        {
+            return false;
+        }
+
+        should_compress_content_type(content_type)
+            || self
+                .compressible_types
+                .iter()
+                .any(|pattern| content_type_matches(pattern, content_type))
+    }
+}
+
 /// Creates a compression middleware that gzip compresses response bodies
 /// when the client accepts gzip encoding and the response meets size threshold
 ///
@@ -46,14 +73,23 @@ pub(crate) fn compression(
                 .map(|v| accepts_gzip_encoding(v))
                 .unwrap_or(false);
 
-            if !accepts_gzip {
+            // Streamed bodies are sent as they are produced, uncompressed.
+            if !accepts_gzip || res.stream.is_some() {
                 return next.call(req, res).await;
             }
             if res
                 .headers
                 .get("Content-Encoding")
-                .or_else(|| res.headers.get("content-encoding"))
-                .is_some()
+                .map(|encoding| !encoding.trim().eq_ignore_ascii_case("identity"))
+                .unwrap_or(false)
+            {
+                return next.call(req, res).await;
+            }
+            if res
+                .headers
+                .get_all("Cache-Control")
+                .iter()
+                .any(|value| has_no_transform(value))
             {
                 return next.call(req, res).await;
             }
@@ -66,9 +102,13 @@ pub(crate) fn compression(
                 return next.call(req, res).await;
             }
 
-            let content_type = &res.headers.get("Content-Type").unwrap();
+            let compressible = res
+                .headers
+                .get("Content-Type")
+                .map(|content_type| config.is_compressible(content_type))
+                .unwrap_or(false);
 
-            if !should_compress_content_type(content_type) {
+            if !compressible {
                 return next.call(req, res).await;
             }
 
@@ -78,9 +118,10 @@ pub(crate) fn compression(
                         return next.call(req, res).await;
                     }
 
+                    // Appended so a `Vary: Origin` from CORS survives.
                     res = res
                         .set_header("Content-Encoding", "gzip")
-                        .set_header("Vary", "Accept-Encoding");
+                        .append_header("Vary", "Accept-Encoding");
 
                     res.headers.remove("Content-Length");
 
@@ -94,9 +135,16 @@ pub(crate) fn compression(
     }
 }
 
+/// Checks whether a `Cache-Control` value carries the `no-transform` directive
+pub(crate) fn has_no_transform(cache_control: &str) -> bool {
+    cache_control
+        .split(',')
+        .any(|directive| directive.trim().eq_ignore_ascii_case("no-transform"))
+}
+
 pub(crate) fn should_compress_content_type(content_type: &str) -> bool {
     let compressible_types = [
-        "text/",
+        "text/*",
         "application/json",
         "application/javascript",
         "application/xml",
@@ -106,10 +154,35 @@ pub(crate) fn should_compress_content_type(content_type: &str) -> bool {
         "image/svg+xml",
     ];
 
-    let content_type_lower = content_type.to_lowercase();
     compressible_types
         .iter()
-        .any(|&ct| content_type_lower.starts_with(ct))
+        .any(|pattern| content_type_matches(pattern, content_type))
+}
+
+/// Matches a `Content-Type` value against a `type/subtype` pattern.
+///
+/// Parameters such as `; charset=utf-8` are ignored and the comparison is
+/// case-insensitive. `type/*` matches any subtype and `*/*` matches everything.
+pub(crate) fn content_type_matches(pattern: &str, content_type: &str) -> bool {
+    let media_type = content_type
+        .split(';')
+        .next()
+        .unwrap_or("")
+        .trim()
+        .to_ascii_lowercase();
+    let pattern = pattern.trim().to_ascii_lowercase();
+
+    if pattern == "*/*" || pattern == "*" {
+        return true;
+    }
+
+    match pattern.strip_suffix("/*") {
+        Some(main_type) => media_type
+            .split_once('/')
+            .map(|(ty, _)| ty == main_type)
+            .unwrap_or(false),
+        None => media_type == pattern,
+    }
 }
 
 /// Compresses data using gzip
@@ -1,4 +1,6 @@
 #![warn(missing_docs)]
+use std::sync::Arc;
+
 use crate::{
     context::HttpResponse,
     next::Next,
@@ -16,8 +18,9 @@ use crate::{
 /// ## Features
 ///
 /// * **Preflight request handling** - Automatically responds to OPTIONS requests
-/// * **Dynamic origin reflection** - Can reflect requesting origin for flexible CORS
-/// * **Static configuration** - Set fixed allowed origins, methods, and headers
+/// * **Origin allowlists** - Allow any origin, an explicit list, or a predicate via [`OriginMatcher`]
+/// * **Origin reflection** - Echoes the matching request origin, so multiple origins work with credentials
+/// * **Static configuration** - Set fixed allowed methods and headers
 /// * **Credential support** - Optional credential allowing with security considerations
 /// * **Header optimization** - Minimal headers when not reflecting requests
 /// * **Vary header management** - Proper cache control for dynamic responses
@@ -28,11 +31,22 @@ use crate::{
 ///
 /// The middleware operates in two modes based on the presence of CORS request headers:
 ///
+/// In both modes `Access-Control-Allow-Origin` is resolved from `allowed_origins`:
+/// - [`OriginMatcher::Any`] sends `*`, or reflects the request `Origin` for preflights and
+///   when credentials are allowed
+/// - [`OriginMatcher::List`] and [`OriginMatcher::Predicate`] reflect the request `Origin` only
+///   if it matches, and add `Vary: Origin`
+/// - An origin that does not match gets no CORS headers at all, so the browser blocks the response
+///
 /// ### 1. Reflective Mode (Dynamic)
 /// When the request includes both `Origin` and `Access-Control-Request-Method` headers:
-/// - **Origin**: Reflects the requesting origin back in `Access-Control-Allow-Origin`
-/// - **Methods**: Reflects the requested method back in `Access-Control-Allow-Methods`  
-/// - **Headers**: Uses requested headers or falls back to configured `allowed_headers`
+/// - **Origin**: Reflects the allowed requesting origin back in `Access-Control-Allow-Origin`
+/// - **Methods**: Reflects the requested method back in `Access-Control-Allow-Methods`
+///   if it is listed in `allowed_methods`
+/// - **Headers**: Reflects `Access-Control-Request-Headers` if every requested header is
+///   listed in `allowed_headers`, or falls back to `allowed_headers` when none are requested
+/// - **Rejection**: A disallowed method or header omits all `Access-Control-Allow-*` headers,
+///   so the browser blocks the actual request
 /// - **Vary**: Adds `Vary` header for proper caching behavior
 ///
 /// ### 2. Static Mode (Default)
@@ -43,7 +57,7 @@ use crate::{
 ///
 /// ## Configuration Options
 ///
-/// * `allowed_origin` - Origin(s) allowed to access the resource (default: "*")
+/// * `allowed_origins` - Origin(s) allowed to access the resource (default: [`OriginMatcher::Any`])
 /// * `allowed_methods` - HTTP methods allowed for cross-origin requests (default: "GET, POST, PUT, DELETE, OPTIONS, HEAD")
 /// * `allowed_headers` - Headers allowed in cross-origin requests (default: "Content-Type, Authorization")
 /// * `allow_credentials` - Whether to allow credentials (cookies, auth headers) in CORS requests (default: false)
@@ -52,13 +66,14 @@ use crate::{
 ///
 /// ### Origin Validation
 /// - **Wildcard (`*`) origins** cannot be used with credentials for security
-/// - **Dynamic reflection** should be used carefully - validate origins in production
+/// - **Origin reflection** only happens for origins accepted by `allowed_origins`
 /// - **Subdomain policies** may require specific origin patterns
 ///
 /// ### Credential Handling
 /// - Setting `allow_credentials: true` has security implications
-/// - Cannot use wildcard origin with credentials
-/// - Consider implementing proper origin validation when using credentials
+/// - [`OriginMatcher::Any`] never allows credentials: `*` is sent without
+///   `Access-Control-Allow-Credentials`, so any origin can't make credentialed requests
+/// - Use [`OriginMatcher::List`] or [`OriginMatcher::Predicate`] to validate origins
 ///
 /// ### Header Exposure
 /// - Be conservative with `allowed_headers` - only allow what's needed
@@ -91,7 +106,7 @@ use crate::{
 ///
 /// let mut app = App::new();
 /// let config = CorsConfig {
-///     allowed_origin: "https://myapp.com",
+///     allowed_origins: "https://myapp.com".into(),
 ///     allowed_methods: "GET, POST, PUT, DELETE",
 ///     allowed_headers: "Content-Type, Authorization, X-API-Key",
 ///     allow_credentials: true,
@@ -106,24 +121,22 @@ use crate::{
 ///
 /// let mut app = App::new();
 /// let config = CorsConfig {
-///     allowed_origin: "*", // Note: Cannot use with credentials
+///     allowed_origins: "*".into(),
 ///     allowed_methods: "GET, POST, PUT, DELETE, OPTIONS, HEAD, PATCH",
 ///     allowed_headers: "Content-Type, Authorization, X-Requested-With, Accept, Origin",
-///     allow_credentials: false, // Must be false when origin is "*"
+///     allow_credentials: false,
 /// };
 /// app.use_cors(Some(config));
 /// ```
 ///
-/// Multiple origin support pattern (requires custom logic):
+/// Multiple origins with credentials (each origin is reflected back):
 ///
 /// ```rust
-/// use ripress::{app::App, middlewares::cors::CorsConfig};
+/// use ripress::{app::App, middlewares::cors::{CorsConfig, OriginMatcher}};
 ///
-/// // Note: For true multiple origin support, you may need custom middleware
-/// // This example shows single origin configuration
 /// let mut app = App::new();
 /// let config = CorsConfig {
-///     allowed_origin: "https://app.example.com",
+///     allowed_origins: OriginMatcher::list(["https://app.example.com", "https://admin.example.com"]),
 ///     allowed_methods: "GET, POST, PUT, DELETE, OPTIONS",
 ///     allowed_headers: "Content-Type, Authorization",
 ///     allow_credentials: true,
@@ -131,6 +144,19 @@ use crate::{
 /// app.use_cors(Some(config));
 /// ```
 ///
+/// Origins decided by a predicate:
+///
+/// ```rust
+/// use ripress::{app::App, middlewares::cors::{CorsConfig, OriginMatcher}};
+///
+/// let mut app = App::new();
+/// app.use_cors(Some(CorsConfig {
+///     allowed_origins: OriginMatcher::predicate(|origin| origin.ends_with(".example.com")),
+///     allow_credentials: true,
+///     ..Default::default()
+/// }));
+/// ```
+///
 /// Using default configuration:
 ///
 /// ```rust
@@ -147,7 +173,7 @@ use crate::{
 /// use ripress::middlewares::cors::CorsConfig;
 ///
 /// let config = CorsConfig {
-///     allowed_origin: "https://myapp.com",
+///     allowed_origins: "https://myapp.com".into(),
 ///     allowed_methods: "GET, POST, PUT, DELETE, PATCH",
 ///     allowed_headers: "Content-Type, Authorization",
 ///     allow_credentials: true,
@@ -159,10 +185,10 @@ use crate::{
 /// use ripress::middlewares::cors::CorsConfig;
 ///
 /// let config = CorsConfig {
-///     allowed_origin: "*",
+///     allowed_origins: "*".into(),
 ///     allowed_methods: "GET, POST",
 ///     allowed_headers: "Content-Type, X-API-Key",
-///     allow_credentials: false, // Must be false with "*"
+///     allow_credentials: false,
 /// };
 /// ```
 ///
@@ -171,7 +197,7 @@ use crate::{
 /// use ripress::middlewares::cors::CorsConfig;
 ///
 /// let config = CorsConfig {
-///     allowed_origin: "*",
+///     allowed_origins: "*".into(),
 ///     allowed_methods: "GET, POST, PUT, DELETE, OPTIONS, HEAD, PATCH",
 ///     allowed_headers: "Content-Type, Authorization, X-Requested-With, Accept, Origin, X-Custom-Header",
 ///     allow_credentials: false,
@@ -182,14 +208,17 @@ use crate::{
 ///
 /// The middleware adds the following headers based on configuration:
 ///
-/// ### Always Added
-/// - `Access-Control-Allow-Origin`: Configured origin or reflected origin
+/// ### Added For Allowed Origins
+/// - `Access-Control-Allow-Origin`: `*` or the reflected request origin
 /// - `Access-Control-Allow-Methods`: Configured methods or reflected method
-/// - `Access-Control-Allow-Headers`: Configured headers or requested headers
+/// - `Access-Control-Allow-Headers`: Configured headers or reflected requested headers
+///
+/// Preflights requesting a method or header that is not allowed receive none of these.
 ///
 /// ### Conditionally Added
 /// - `Access-Control-Allow-Credentials`: "true" (only when `allow_credentials: true`)
-/// - `Vary`: "Origin, Access-Control-Request-Method, Access-Control-Request-Headers" (only in reflective mode)
+/// - `Vary`: "Origin" whenever the allowed origin depends on the request, plus
+///   "Access-Control-Request-Method, Access-Control-Request-Headers" in reflective mode
 ///
 /// ## Browser Compatibility
 ///
@@ -203,8 +232,10 @@ use crate::{
 /// ## Debugging CORS Issues
 ///
 /// Common issues and solutions:
-/// - **"CORS policy" errors**: Check that `allowed_origin` matches the requesting domain exactly
-/// - **Credential issues**: Ensure `allowed_origin` is not "*" when `allow_credentials` is true
+/// - **"CORS policy" errors**: Check that `allowed_origins` matches the requesting origin exactly
+///   (scheme, host and port, without a trailing slash)
+/// - **Credential issues**: `allow_credentials` needs an [`OriginMatcher::List`] or
+///   [`OriginMatcher::Predicate`]; it is ignored with [`OriginMatcher::Any`]
 /// - **Method not allowed**: Verify the HTTP method is in `allowed_methods`
 /// - **Header not allowed**: Check that custom headers are included in `allowed_headers`
 /// - **Preflight failures**: Ensure OPTIONS requests reach the middleware
@@ -231,9 +262,9 @@ use crate::{
 ///
 /// ## Field Details
 ///
-/// All string fields use `&'static str` for efficiency, meaning they should be
+/// The method and header fields use `&'static str` for efficiency, meaning they should be
 /// string literals or static strings. This avoids unnecessary memory allocations
-/// during request processing.
+/// during request processing. Origins are described by an [`OriginMatcher`].
 ///
 /// ## Security Implications
 ///
@@ -253,17 +284,15 @@ use crate::{
 pub struct CorsConfig {
     /// The allowed origin(s) for cross-origin requests
     ///
-    /// Can be a specific origin like "https://example.com" or "*" for any origin.
-    /// When using "*", credentials cannot be allowed for security reasons.
+    /// Allowed request origins are reflected back in `Access-Control-Allow-Origin`;
+    /// any other origin receives no CORS headers.
     ///
     /// **Examples:**
-    /// - `"*"` - Allow any origin (development/public APIs)
-    /// - `"https://myapp.com"` - Allow only specific domain
-    /// - `"http://localhost:3000"` - Allow local development server
-    ///
-    /// **Note:** Some advanced CORS scenarios require multiple origins, which
-    /// may need custom middleware implementation.
-    pub allowed_origin: &'static str,
+    /// - `OriginMatcher::Any` or `"*".into()` - Allow any origin (development/public APIs)
+    /// - `"https://myapp.com".into()` - Allow only a specific origin
+    /// - `OriginMatcher::list(["https://a.com", "https://b.com"])` - Allow several origins
+    /// - `OriginMatcher::predicate(|o| o.ends_with(".myapp.com"))` - Custom validation
+    pub allowed_origins: OriginMatcher,
 
     /// The HTTP methods allowed for cross-origin requests
     ///
@@ -281,6 +310,8 @@ pub struct CorsConfig {
     /// - PATCH - Partial updates
     /// - HEAD - Metadata requests
     /// - OPTIONS - Always handled for preflights
+    ///
+    /// Use `"*"` to accept any method requested by a preflight.
     pub allowed_methods: &'static str,
 
     /// The headers allowed in cross-origin requests
@@ -299,13 +330,21 @@ pub struct CorsConfig {
     /// - X-Requested-With - For AJAX detection
     /// - Accept - For content negotiation
     ///
+    /// Preflights may only request headers from this list (compared
+    /// case-insensitively). Use `"*"` to reflect whatever headers the
+    /// preflight requests.
+    ///
     /// **Security note:** Avoid exposing sensitive headers unnecessarily.
     pub allowed_headers: &'static str,
 
     /// Whether to allow credentials (cookies, authorization headers) in CORS requests
     ///
-    /// When true, adds the `Access-Control-Allow-Credentials: true` header.
-    /// This cannot be used with `allowed_origin: "*"` for security reasons.
+    /// When true, adds the `Access-Control-Allow-Credentials: true` header and
+    /// reflects the request origin, since browsers reject `*` with credentials.
+    /// Only origins accepted by a [`OriginMatcher::List`] or
+    /// [`OriginMatcher::Predicate`] get credentials: with [`OriginMatcher::Any`]
+    /// this is ignored, as reflecting every origin would let any site make
+    /// credentialed requests.
     ///
     /// **When to use:**
     /// - Authentication via cookies
@@ -313,7 +352,7 @@ pub struct CorsConfig {
     /// - Client certificates
     ///
     /// **Security implications:**
-    /// - Must use specific origins, not "*"
+    /// - Needs specific origins; ignored with [`OriginMatcher::Any`]
     /// - Increases CSRF attack surface
     /// - Requires careful origin validation
     ///
@@ -324,7 +363,7 @@ pub struct CorsConfig {
 impl Default for CorsConfig {
     fn default() -> Self {
         CorsConfig {
-            allowed_origin: "*",
+            allowed_origins: OriginMatcher::Any,
             allowed_methods: "GET, POST, PUT, DELETE, OPTIONS, HEAD",
             allowed_headers: "Content-Type, Authorization",
             allow_credentials: false,
@@ -332,6 +371,96 @@ impl Default for CorsConfig {
     }
 }
 
+/// Describes which origins may access the resource
+///
+/// Converting from a string accepts `"*"` for any origin, a single origin, or a
+/// comma-separated list of origins. Origins are compared case-insensitively.
+#[derive(Clone, Default)]
+pub enum OriginMatcher {
+    /// Any origin is allowed
+    #[default]
+    Any,
+    /// Only the listed origins are allowed, e.g. `https://example.com`
+    List(Vec<String>),
+    /// Origins for which the predicate returns `true` are allowed
+    Predicate(Arc<dyn Fn(&str) -> bool + Send + Sync>),
+}
+
+impl OriginMatcher {
+    /// Creates a matcher allowing exactly the given origins
+    pub fn list<I, S>(origins: I) -> Self
+    where
+        I: IntoIterator<Item = S>,
+        S: Into<String>,
+    {
+        OriginMatcher::List(origins.into_iter().map(Into::into).collect())
+    }
+
+    /// Creates a matcher that asks `predicate` whether an origin is allowed
+    pub fn predicate<F>(predicate: F) -> Self
+    where
+        F: Fn(&str) -> bool + Send + Sync + 'static,
+    {
+        OriginMatcher::Predicate(Arc::new(predicate))
+    }
+
+    /// Returns `true` if `origin` is allowed by this matcher
+    pub fn matches(&self, origin: &str) -> bool {
+        match self {
+            OriginMatcher::Any => true,
+            OriginMatcher::List(origins) => origins
+                .iter()
+                .any(|allowed| allowed.eq_ignore_ascii_case(origin)),
+            OriginMatcher::Predicate(predicate) => predicate(origin),
+        }
+    }
+
+    /// Resolves the `Access-Control-Allow-Origin` value for a request.
+    ///
+    /// `reflect_any` makes [`OriginMatcher::Any`] echo the origin instead of
+    /// `*`. Returns the header value (if any) and whether it depends on the
+    /// request `Origin`, in which case `Vary: Origin` must be sent.
+    pub(crate) fn resolve(
+        &self,
+        origin: Option<&str>,
+        reflect_any: bool,
+    ) -> (Option<String>, bool) {
+        match (self, origin) {
+            (OriginMatcher::Any, Some(origin)) if reflect_any => (Some(origin.to_string()), true),
+            (OriginMatcher::Any, _) => (Some("*".to_string()), false),
+            (_, Some(origin)) if self.matches(origin) => (Some(origin.to_string()), true),
+            (OriginMatcher::List(origins), None) if origins.len() == 1 => {
+                (Some(origins[0].clone()), true)
+            }
+            _ => (None, true),
+        }
+    }
+}
+
+impl From<&str> for OriginMatcher {
+    fn from(origins: &str) -> Self {
+        if origins.trim() == "*" {
+            return OriginMatcher::Any;
+        }
+        OriginMatcher::list(
+            origins
+                .split(',')
+                .map(str::trim)
+                .filter(|origin| !origin.is_empty()),
+        )
+    }
+}
+
+impl std::fmt::Debug for OriginMatcher {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            OriginMatcher::Any => write!(f, "Any"),
+            OriginMatcher::List(origins) => f.debug_tuple("List").field(origins).finish(),
+            OriginMatcher::Predicate(_) => write!(f, "Predicate(..)"),
+        }
+    }
+}
+
 /// Creates a CORS middleware function
 ///
 /// Returns a middleware function that handles Cross-Origin Resource Sharing (CORS)
@@ -391,37 +520,91 @@ pub(crate) fn cors(
 ) -> impl Fn(HttpRequest, HttpResponse, Next) -> MiddlewareOutput + Send + Sync + Clone + 'static {
     move |req: HttpRequest, mut res, next| {
         let config = config.clone().unwrap_or_default();
-        let req_clone = req.clone();
         Box::pin(async move {
-            let origin = req.headers.get("Origin");
-            let allowed_methods = req.headers.get("Access-Control-Request-Method");
-            let requested_headers = req.headers.get("Access-Control-Request-Headers");
-            if let (Some(origin), Some(allowed_methods)) = (origin, allowed_methods) {
-                res = res
-                    .set_header("Access-Control-Allow-Origin", origin)
-                    .set_header("Access-Control-Allow-Methods", allowed_methods)
-                    .set_header(
-                        "Access-Control-Allow-Headers",
-                        requested_headers.unwrap_or(config.allowed_headers),
-                    )
-                    .set_header(
-                        "Vary",
-                        "Origin, Access-Control-Request-Method, Access-Control-Request-Headers",
-                    );
-            } else {
-                res = res
-                    .set_header("Access-Control-Allow-Origin", config.allowed_origin)
-                    .set_header("Access-Control-Allow-Methods", config.allowed_methods)
-                    .set_header("Access-Control-Allow-Headers", config.allowed_headers)
+            let origin = req.headers.get("Origin").map(str::to_string);
+            let requested_method = req
+                .headers
+                .get("Access-Control-Request-Method")
+                .map(str::to_string);
+            let requested_headers = req
+                .headers
+                .get("Access-Control-Request-Headers")
+                .map(str::to_string);
+            let preflight = origin.is_some() && requested_method.is_some();
+            let preflight_allowed = !preflight
+                || requested_method.as_deref().is_some_and(|method| {
+                    preflight_allowed(&config, method, requested_headers.as_deref())
+                });
+
+            // Any origin with credentials would let every site act as the user.
+            let allow_credentials =
+                config.allow_credentials && !matches!(config.allowed_origins, OriginMatcher::Any);
+            let (allow_origin, varies_by_origin) = config
+                .allowed_origins
+                .resolve(origin.as_deref(), preflight && !config.allow_credentials);
+
+            if let (Some(allow_origin), true) = (allow_origin, preflight_allowed) {
+                res = res.set_header("Access-Control-Allow-Origin", allow_origin);
+                if let (true, Some(method)) = (preflight, requested_method.as_deref()) {
+                    res = res
+                        .set_header("Access-Control-Allow-Methods", method)
+                        .set_header(
+                            "Access-Control-Allow-Headers",
+                            requested_headers
+                                .as_deref()
+                                .filter(|headers| !headers.trim().is_empty())
+                                .unwrap_or(config.allowed_headers),
+                        );
+                } else {
+                    res = res
+                        .set_header("Access-Control-Allow-Methods", config.allowed_methods)
+                        .set_header("Access-Control-Allow-Headers", config.allowed_headers);
+                }
+                if allow_credentials {
+                    res = res.set_header("Access-Control-Allow-Credentials", "true");
+                }
             }
-            if config.allow_credentials {
-                res = res.set_header("Access-Control-Allow-Credentials", "true");
+
+            if preflight {
+                res = res.set_header(
+                    "Vary",
+                    "Origin, Access-Control-Request-Method, Access-Control-Request-Headers",
+                );
+            } else if varies_by_origin {
+                res = res.set_header("Vary", "Origin");
             }
-            if req_clone.method == HttpMethods::OPTIONS {
-                return (req_clone, Some(res.ok()));
+
+            if req.method == HttpMethods::OPTIONS {
+                return (req, Some(res.ok()));
             }
 
-            return next.call(req_clone, res).await;
+            return next.call(req, res).await;
         })
     }
 }
+
+/// Checks a preflight's `Access-Control-Request-Method` and
+/// `Access-Control-Request-Headers` against the configured allowlists
+pub(crate) fn preflight_allowed(
+    config: &CorsConfig,
+    method: &str,
+    requested_headers: Option<&str>,
+) -> bool {
+    list_allows(config.allowed_methods, method)
+        && requested_headers.is_none_or(|headers| {
+            headers
+                .split(',')
+                .map(str::trim)
+                .filter(|header| !header.is_empty())
+                .all(|header| list_allows(config.allowed_headers, header))
+        })
+}
+
+/// Checks whether a comma-separated CORS list such as `allowed_methods` or
+/// `allowed_headers` contains `value`, ignoring ASCII case. A `*` entry allows
+/// any value.
+pub(crate) fn list_allows(list: &str, value: &str) -> bool {
+    list.split(',')
+        .map(str::trim)
+        .any(|entry| entry == "*" || entry.eq_ignore_ascii_case(value))
+}
@@ -0,0 +1,108 @@
+#![warn(missing_docs)]
+use crate::{
+    context::HttpResponse, next::Next, req::HttpRequest, res::ResponseBody,
+    types::{HttpMethods, MiddlewareOutput},
+};
+use bytes::Bytes;
+
+/// Configuration for the ETag middleware
+#[derive(Clone)]
+pub struct EtagConfig {
+    /// Emit weak validators (`W/"..."`) instead of strong ones
+    pub weak: bool,
+}
+
+impl Default for EtagConfig {
+    fn default() -> Self {
+        Self { weak: true }
+    }
+}
+
+/// Creates an ETag middleware that tags successful `GET`/`HEAD` responses
+/// with a validator derived from the body and answers matching
+/// `If-None-Match` requests with `304 Not Modified`
+///
+/// # Arguments
+///
+/// * `config` - Optional ETag configuration. Uses defaults if None.
+///
+/// # Returns
+///
+/// A middleware function that adds ETags and handles conditional GETs
+pub(crate) fn etag(
+    config: Option<EtagConfig>,
+) -> impl Fn(HttpRequest, HttpResponse, Next) -> MiddlewareOutput + Send + Sync + 'static {
+    let config = config.unwrap_or_default();
+    move |req: HttpRequest, mut res, next| {
+        let config = config.clone();
+        Box::pin(async move {
+            if !matches!(req.method, HttpMethods::GET | HttpMethods::HEAD) {
+                return next.call(req, res).await;
+            }
+
+            if !(200..300).contains(&res.status_code()) || res.stream.is_some() {
+                return next.call(req, res).await;
+            }
+
+            let tag = match res.headers.get("etag") {
+                Some(existing) => existing.to_string(),
+                None => {
+                    let tag = generate_etag(&res.body_bytes(), config.weak);
+                    res.headers.etag(&tag);
+                    tag
+                }
+            };
+
+            let matched = req
+                .headers
+                .get("if-none-match")
+                .map(|header| if_none_match(header, &tag))
+                .unwrap_or(false);
+
+            if matched {
+                res = res.status(304);
+                res.body = empty_body(&res.body);
+                res.headers.remove("content-length");
+            }
+
+            (req, Some(res))
+        })
+    }
+}
+
+/// Computes an ETag for the given bytes using a 64-bit FNV-1a hash
+pub(crate) fn generate_etag(data: &[u8], weak: bool) -> String {
+    let mut hash: u64 = 0xcbf29ce484222325;
+    for byte in data {
+        hash ^= *byte as u64;
+        hash = hash.wrapping_mul(0x100000001b3);
+    }
+
+    let tag = format!("\"{:x}-{:016x}\"", data.len(), hash);
+    if weak {
+        format!("W/{}", tag)
+    } else {
+        tag
+    }
+}
+
+/// Checks an `If-None-Match` header value against an ETag using weak comparison
+pub(crate) fn if_none_match(header: &str, etag: &str) -> bool {
+    let etag = strip_weak(etag.trim());
+    header.split(',').map(str::trim).any(|candidate| {
+        candidate == "*" || (!candidate.is_empty() && strip_weak(candidate) == etag)
+    })
+}
+
+fn strip_weak(tag: &str) -> &str {
+    tag.strip_prefix("W/").unwrap_or(tag)
+}
+
+fn empty_body(body: &ResponseBody) -> ResponseBody {
+    match body {
+        ResponseBody::TEXT(_) => ResponseBody::TEXT(String::new()),
+        ResponseBody::HTML(_) => ResponseBody::HTML(String::new()),
+        ResponseBody::JSON(_) => ResponseBody::JSON(serde_json::Value::Null),
+        ResponseBody::BINARY(_) => ResponseBody::BINARY(Bytes::new()),
+    }
+}
@@ -1,13 +1,25 @@
 #![warn(missing_docs)]
-use crate::helpers::{extract_boundary, parse_multipart_form};
+use std::path::{Path, PathBuf};
+
+use crate::app::api_error::error_response;
+use crate::helpers::{
+    check_multipart_part, extract_boundary, parse_multipart_form, MultipartEvent,
+    MultipartLimitError, MultipartParser, MultipartPartHeaders,
+};
 use crate::next::Next;
 use crate::req::body::FormData;
+use crate::req::uploaded_file::UploadedFile;
+use crate::res::response_status::StatusCode;
 use crate::url::encode;
 use crate::{context::HttpResponse, req::HttpRequest, types::MiddlewareOutput};
-use tokio::fs::{create_dir_all, File};
+use futures::StreamExt;
+use tokio::fs::{create_dir_all, remove_file, rename, File};
 use tokio::io::AsyncWriteExt;
 use uuid::Uuid;
 
+/// Number of leading bytes kept in memory to detect a streamed file's type
+const SNIFF_LEN: usize = 8192;
+
 /// Builtin File Upload Middleware
 ///
 /// This middleware handles file uploads by processing request bodies and saving
@@ -37,7 +49,11 @@ use uuid::Uuid;
 /// * `upload_dir` - Directory path for file uploads (default: "uploads")
 /// * `max_file_size` - Maximum size per file in bytes (default: 10 MB)
 /// * `max_files` - Maximum number of files per request (default: 100)
-/// * `allowed_file_types` - Vector of allowed file extensions (default: empty = all types allowed)
+/// * `allowed_file_types` - Allowed file extensions or MIME types, checked against the sniffed content (default: empty = all types allowed)
+/// * `stream_to_disk` - Write multipart file parts to disk chunk by chunk (default: false)
+///
+/// The number of parts and the length of field names and values are also bounded by
+/// the app's [`MultipartLimits`](crate::app::settings::MultipartLimits).
 ///
 /// ## Behavior
 ///
@@ -60,6 +76,12 @@ use uuid::Uuid;
 /// * **File field names** are populated with the generated filenames for uploaded files
 /// * **Single binary uploads** use "file" as the default field name
 ///
+/// Every stored file is also recorded as an
+/// [`UploadedFile`](crate::req::uploaded_file::UploadedFile) with its field name,
+/// original filename (RFC 5987 `filename*` values are decoded), client-supplied
+/// content type, size and stored path, available through
+/// [`HttpRequest::files`](crate::req::HttpRequest::files).
+///
 /// ## Examples
 ///
 /// Basic usage with default configuration:
@@ -82,10 +104,35 @@ use uuid::Uuid;
 ///     max_file_size: 1024 * 1024 * 5, // 5 MB
 ///     max_files: 10,
 ///     allowed_file_types: vec!["jpg".to_string(), "png".to_string(), "pdf".to_string()],
+///     ..Default::default()
 /// };
 /// app.use_pre_middleware("/upload", file_upload(Some(config)));
 /// ```
 ///
+/// Streaming large uploads to disk with a strict per-file limit, reading the body
+/// from the connection as it is parsed:
+///
+/// ```rust
+/// use ripress::{
+///     app::App,
+///     middlewares::file_upload::{file_upload, FileUploadConfiguration},
+///     req::HttpRequest,
+///     types::{HttpMethods, RouterFns},
+/// };
+///
+/// let mut app = App::new();
+/// app.use_pre_middleware("/upload", file_upload(Some(FileUploadConfiguration {
+///     max_file_size: 100 * 1024 * 1024, // 100 MB, larger files get 413
+///     stream_to_disk: true,
+///     ..Default::default()
+/// })));
+/// app.route(HttpMethods::POST, "/upload")
+///     .stream_body()
+///     .handler(|req: HttpRequest, res| async move {
+///         res.ok().text(format!("{} files", req.files().len()))
+///     });
+/// ```
+///
 /// Using default configuration (no argument needed):
 ///
 /// ```rust
@@ -151,13 +198,27 @@ use uuid::Uuid;
 /// * **Directory creation failures** are logged but allow the request to continue
 /// * **File size exceeded** - individual files are skipped with logging
 /// * **Too many files** - entire request is logged but continues without uploads
-/// * **Disallowed file types** - individual files are skipped with logging
 /// * **File write failures** are logged but don't short-circuit the request
 /// * **Body parsing failures** - logged and request continues without uploads
 ///
+/// Files whose detected type is not in `allowed_file_types` are always refused:
+/// the request is answered with `415 Unsupported Media Type` and a JSON body
+/// naming the rejected file (`field`, `filename`, `detected_type`), and no file
+/// from the request is kept.
+///
+/// With `stream_to_disk` enabled, multipart limits are enforced strictly instead:
+///
+/// * **File size exceeded** - `413 Payload Too Large` as soon as a part crosses `max_file_size`
+/// * **Too many files** - `413 Payload Too Large` when the part count crosses `max_files`
+/// * **Malformed multipart body** - `400 Bad Request`
+///
+/// In every case, files already written for the request, including the partial
+/// one, are deleted before responding.
+///
 /// ## Security Considerations
 ///
-/// * **File type validation** - Use `allowed_file_types` to restrict uploads
+/// * **File type validation** - Use `allowed_file_types` to restrict uploads; types are
+///   sniffed from file content, so a renamed executable is not accepted as `.png`
 /// * **Size limits** - Configure `max_file_size` and `max_files` appropriately
 /// * **Unique filenames** - UUID-based names prevent directory traversal and conflicts
 /// * **Directory isolation** - Files are saved only within the configured upload directory
@@ -182,7 +243,13 @@ use uuid::Uuid;
 /// ## Performance Notes
 ///
 /// * Files are processed sequentially, not in parallel
-/// * Large files are loaded entirely into memory before writing
+/// * Large files are copied into memory before writing, unless `stream_to_disk` is enabled
+/// * With `stream_to_disk`, each part is written as it is parsed, holding at most one
+///   chunk plus the first 8 KB (used for type detection) in memory per file
+/// * Request bodies are read in full before middleware runs, unless the upload route
+///   is registered with [`RouteHandle::stream_body`](crate::router::route_handle::RouteHandle::stream_body).
+///   With `stream_to_disk`, do so, so the body is parsed as it arrives and an
+///   oversized file is refused before the rest is received
 /// * Directory creation is checked on every request (consider pre-creating directories)
 /// * File type detection requires reading file headers
 ///
@@ -217,12 +284,32 @@ pub struct FileUploadConfiguration {
     /// Default is 100.
     pub max_files: u64,
 
-    /// List of allowed file extensions (without dots)
+    /// List of allowed file types
     ///
-    /// If empty, all file types are allowed. Extensions are detected automatically
-    /// using the `infer` crate based on file headers, not filenames.
+    /// Entries are extensions without dots (`"png"`), MIME types (`"image/png"`)
+    /// or MIME wildcards (`"image/*"`). If empty, all file types are allowed.
+    ///
+    /// The type is detected from the file's magic bytes using the `infer` crate;
+    /// the client-supplied filename and `Content-Type` are never trusted.
+    /// Content that cannot be identified is treated as `bin`
+    /// (`application/octet-stream`). A file whose detected type is not allowed
+    /// makes the middleware answer `415 Unsupported Media Type` and discard
+    /// every file of the request.
     /// Example: vec!["jpg".to_string(), "png".to_string(), "pdf".to_string()]
     pub allowed_file_types: Vec<String>,
+
+    /// Write multipart file parts to disk as they are parsed
+    ///
+    /// Each part goes to a temporary file in `upload_dir` and is renamed once
+    /// complete. Exceeding `max_file_size` or `max_files` aborts the upload with
+    /// `413 Payload Too Large` and deletes every file written for the request.
+    ///
+    /// Register the upload route with
+    /// [`RouteHandle::stream_body`](crate::router::route_handle::RouteHandle::stream_body)
+    /// so the body is read from the connection while it is parsed; otherwise it
+    /// has been read into memory in full before the middleware runs.
+    /// Default is false.
+    pub stream_to_disk: bool,
 }
 
 impl Default for FileUploadConfiguration {
@@ -232,6 +319,7 @@ impl Default for FileUploadConfiguration {
             max_file_size: 1024 * 1024 * 10,
             max_files: 100,
             allowed_file_types: Vec::new(),
+            stream_to_disk: false,
         }
     }
 }
@@ -267,7 +355,7 @@ pub fn file_upload(
         let config = config.clone();
         let upload_path = config.upload_dir.clone();
         Box::pin(async move {
-            let content_type = req.headers.content_type().unwrap_or_default();
+            let content_type = req.headers.content_type().unwrap_or_default().to_string();
             let is_multipart = content_type.to_lowercase().contains("multipart/form-data");
             let boundary = if is_multipart {
                 extract_boundary(&content_type)
@@ -275,11 +363,35 @@ pub fn file_upload(
                 None
             };
 
+            if let (true, Some(boundary)) = (config.stream_to_disk, boundary.as_deref()) {
+                return match stream_multipart_to_disk(&req, boundary, &config).await {
+                    Ok(stored) => {
+                        for (name, value) in stored.fields {
+                            req.insert_form_field(&name, &value);
+                        }
+                        for file in stored.files {
+                            if let (Some(field_name), Some(filename)) =
+                                (&file.field_name, file.stored_filename())
+                            {
+                                req.insert_form_field(field_name, filename);
+                            }
+                            req.push_file(file);
+                        }
+                        next.call(req, res).await
+                    }
+                    Err(UploadAbort::Io(e)) => {
+                        tracing::warn!("File upload middleware: Streaming upload failed: {}", e);
+                        next.call(req, res).await
+                    }
+                    Err(abort) => (req, Some(abort.into_response(res, &config))),
+                };
+            }
+
             let bytes_vec = if is_multipart {
                 match req.bytes() {
                     Ok(bytes) => bytes.to_vec(),
                     Err(e) => {
-                        eprintln!(
+                        tracing::warn!(
                             "File upload middleware: multipart/form-data detected but req.bytes() failed error: {}",
                             e
                         );
@@ -293,13 +405,13 @@ pub fn file_upload(
                         Ok(form_data) => {
                             let form_string = form_data_to_string(form_data);
                             if form_string.is_empty() {
-                                eprintln!("File upload middleware: No form data available");
+                                tracing::debug!("File upload middleware: No form data available");
                                 return next.call(req, res).await;
                             }
                             form_string.into_bytes()
                         }
                         Err(_) => {
-                            eprintln!(
+                            tracing::warn!(
                                 "File upload middleware: Both bytes() and form_data() failed"
                             );
                             return next.call(req, res).await;
@@ -309,7 +421,11 @@ pub fn file_upload(
             };
 
             let (fields, file_parts) = if let Some(ref boundary_str) = boundary {
-                parse_multipart_form(&bytes_vec, boundary_str)
+                let limits = req.settings.multipart_limits;
+                match parse_multipart_form(&bytes_vec, boundary_str, &limits) {
+                    Ok(parsed) => parsed,
+                    Err(e) => return (req, Some(e.into_response(res))),
+                }
             } else {
                 (Vec::new(), Vec::new())
             };
@@ -323,11 +439,16 @@ pub fn file_upload(
             } else if boundary.is_some() {
                 Vec::new()
             } else {
-                vec![(bytes_vec, Some("file"))]
+                let headers = MultipartPartHeaders {
+                    name: Some("file".to_string()),
+                    filename: None,
+                    content_type: Some(content_type.clone()).filter(|ct| !ct.is_empty()),
+                };
+                vec![(bytes_vec, headers)]
             };
 
             if files_to_process.len() > config.max_files as usize {
-                eprintln!(
+                tracing::debug!(
                     "File upload middleware: Too many files ({} > {})",
                     files_to_process.len(),
                     config.max_files
@@ -335,16 +456,10 @@ pub fn file_upload(
                 return next.call(req, res).await;
             }
 
-            if let Err(e) = create_dir_all(&upload_path).await {
-                eprintln!("Failed to create upload directory '{}': {}", upload_path, e);
-                return next.call(req, res).await;
-            }
-
-            let mut uploaded_files = Vec::new();
-
-            for (file_bytes, field_name_opt) in files_to_process {
+            let mut accepted_files = Vec::with_capacity(files_to_process.len());
+            for (file_bytes, part_headers) in files_to_process {
                 if file_bytes.len() > config.max_file_size as usize {
-                    eprintln!(
+                    tracing::debug!(
                         "File upload middleware: File too large ({} bytes > {} bytes)",
                         file_bytes.len(),
                         config.max_file_size
@@ -352,53 +467,58 @@ pub fn file_upload(
                     continue;
                 }
 
-                let (file_bytes, _original_filename, field_name) = match field_name_opt {
-                    Some(field) => (file_bytes, None::<String>, Some(field)),
-                    None => (file_bytes, None::<String>, None),
-                };
-                let extension = infer::get(&file_bytes)
-                    .map(|info| info.extension())
-                    .unwrap_or("bin");
-
-                if !config.allowed_file_types.is_empty() {
-                    let ext_norm = extension.to_ascii_lowercase();
-                    let ext_norm = if ext_norm == "jpg" {
-                        "jpeg".to_string()
-                    } else {
-                        ext_norm
-                    };
-                    let allowed = config.allowed_file_types.iter().any(|e| {
-                        let e = e.to_ascii_lowercase();
-                        e == ext_norm || (e == "jpg" && ext_norm == "jpeg")
-                    });
-                    if !allowed {
-                        eprintln!(
-                            "File upload middleware: File type '{}' not allowed (allowed types: {:?})",
-                            extension, config.allowed_file_types
-                        );
-                        continue;
-                    }
+                let sniffed = SniffedType::of(&file_bytes);
+                if !is_allowed_type(&sniffed, &config.allowed_file_types) {
+                    let abort =
+                        UploadAbort::DisallowedType(RejectedFile::new(part_headers, sniffed));
+                    return (req, Some(abort.into_response(res, &config)));
                 }
+                accepted_files.push((file_bytes, part_headers, sniffed.extension));
+            }
+
+            if let Err(e) = create_dir_all(&upload_path).await {
+                tracing::error!("Failed to create upload directory '{}': {}", upload_path, e);
+                return next.call(req, res).await;
+            }
 
+            for (file_bytes, part_headers, extension) in accepted_files {
                 let id = Uuid::new_v4();
                 let filename = format!("{}.{}", id, extension);
-                let filename_with_path = format!("{}/{}.{}", upload_path, id, extension);
+                let filename_with_path = Path::new(&upload_path).join(&filename);
 
                 match File::create(&filename_with_path).await {
                     Ok(mut file) => {
-                        if let Err(e) = file.write_all(&file_bytes).await {
-                            eprintln!("Failed to write file '{}': {}", filename_with_path, e);
+                        let written = match file.write_all(&file_bytes).await {
+                            Ok(()) => file.flush().await,
+                            Err(e) => Err(e),
+                        };
+                        if let Err(e) = written {
+                            tracing::error!(
+                                "Failed to write file '{}': {}",
+                                filename_with_path.display(),
+                                e
+                            );
                             continue;
                         }
 
-                        uploaded_files.push(filename.clone());
-
-                        if let Some(field_name) = field_name {
-                            req.insert_form_field(&field_name, &filename);
+                        if let Some(field_name) = &part_headers.name {
+                            req.insert_form_field(field_name, &filename);
                         }
+
+                        req.push_file(UploadedFile {
+                            field_name: part_headers.name,
+                            original_filename: part_headers.filename,
+                            content_type: part_headers.content_type,
+                            size: file_bytes.len() as u64,
+                            stored_path: filename_with_path,
+                        });
                     }
                     Err(e) => {
-                        eprintln!("Failed to create file '{}': {}", filename_with_path, e);
+                        tracing::error!(
+                            "Failed to create file '{}': {}",
+                            filename_with_path.display(),
+                            e
+                        );
                         continue;
                     }
                 }
@@ -409,6 +529,318 @@ pub fn file_upload(
     }
 }
 
+/// A file type detected from content by the `infer` crate
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub(crate) struct SniffedType {
+    /// Canonical extension, `bin` when the content is not recognized
+    pub(crate) extension: &'static str,
+    /// MIME type, `application/octet-stream` when the content is not recognized
+    pub(crate) mime_type: &'static str,
+}
+
+impl SniffedType {
+    /// Detects the type of `bytes` from its magic numbers.
+    pub(crate) fn of(bytes: &[u8]) -> Self {
+        match infer::get(bytes) {
+            Some(kind) => SniffedType {
+                extension: kind.extension(),
+                mime_type: kind.mime_type(),
+            },
+            None => SniffedType {
+                extension: "bin",
+                mime_type: "application/octet-stream",
+            },
+        }
+    }
+}
+
+/// Checks a sniffed type against `allowed_file_types`.
+///
+/// Entries containing `/` are matched against the MIME type (`type/*` matches
+/// any subtype), others against the extension, treating `jpg` and `jpeg` as the
+/// same type. An empty allowlist allows everything.
+pub(crate) fn is_allowed_type(sniffed: &SniffedType, allowed_file_types: &[String]) -> bool {
+    if allowed_file_types.is_empty() {
+        return true;
+    }
+
+    let ext_norm = sniffed.extension.to_ascii_lowercase();
+    let ext_norm = if ext_norm == "jpg" {
+        "jpeg".to_string()
+    } else {
+        ext_norm
+    };
+    allowed_file_types.iter().any(|e| {
+        let e = e.trim().to_ascii_lowercase();
+        if let Some(prefix) = e.strip_suffix("/*") {
+            sniffed
+                .mime_type
+                .split_once('/')
+                .is_some_and(|(top, _)| top.eq_ignore_ascii_case(prefix))
+        } else if e.contains('/') {
+            sniffed.mime_type.eq_ignore_ascii_case(&e)
+        } else {
+            e == ext_norm || (e == "jpg" && ext_norm == "jpeg")
+        }
+    })
+}
+
+/// A file refused because its sniffed type is not in `allowed_file_types`
+#[derive(Debug)]
+pub(crate) struct RejectedFile {
+    /// Form field the file was sent in
+    pub(crate) field_name: Option<String>,
+    /// Filename supplied by the client
+    pub(crate) filename: Option<String>,
+    /// The type detected from the file's content
+    pub(crate) detected: SniffedType,
+}
+
+impl RejectedFile {
+    fn new(headers: MultipartPartHeaders, detected: SniffedType) -> Self {
+        RejectedFile {
+            field_name: headers.name,
+            filename: headers.filename,
+            detected,
+        }
+    }
+}
+
+/// Text fields and saved files produced by a streamed multipart upload
+#[derive(Default)]
+pub(crate) struct StreamedUpload {
+    /// Text fields as `(name, value)`
+    pub(crate) fields: Vec<(String, String)>,
+    /// Saved files, in the order they appeared in the body
+    pub(crate) files: Vec<UploadedFile>,
+}
+
+/// Reasons a streamed multipart upload was aborted
+#[derive(Debug)]
+pub(crate) enum UploadAbort {
+    /// A file part exceeded `max_file_size`
+    FileTooLarge(Option<String>),
+    /// The request contained more than `max_files` file parts
+    TooManyFiles,
+    /// A file's sniffed type is not in `allowed_file_types`
+    DisallowedType(RejectedFile),
+    /// The multipart body could not be parsed
+    Malformed(String),
+    /// The multipart body exceeded the app's [`MultipartLimits`](crate::app::settings::MultipartLimits)
+    Limit(MultipartLimitError),
+    /// Reading the request body failed, or it exceeded the body limit
+    Body(std::io::Error),
+    /// Writing to the upload directory failed
+    Io(std::io::Error),
+}
+
+impl UploadAbort {
+    /// Builds the short-circuit response for this abort.
+    ///
+    /// I/O failures are handled by the caller, which logs them and lets the
+    /// request continue without uploads like in buffered mode.
+    fn into_response(self, res: HttpResponse, config: &FileUploadConfiguration) -> HttpResponse {
+        match self {
+            UploadAbort::FileTooLarge(field) => res
+                .status(StatusCode::PayloadTooLarge.as_u16())
+                .json(serde_json::json!({
+                    "error": "File too large",
+                    "message": format!(
+                        "Uploaded file exceeded the configured limit of {} bytes",
+                        config.max_file_size
+                    ),
+                    "limit": config.max_file_size,
+                    "field": field,
+                })),
+            UploadAbort::TooManyFiles => {
+                res.status(StatusCode::PayloadTooLarge.as_u16())
+                    .json(serde_json::json!({
+                        "error": "Too many files",
+                        "message": format!(
+                            "Request exceeded the configured limit of {} files",
+                            config.max_files
+                        ),
+                        "limit": config.max_files,
+                    }))
+            }
+            UploadAbort::DisallowedType(rejected) => {
+                let description = match (&rejected.filename, &rejected.field_name) {
+                    (Some(filename), _) => format!("File '{}'", filename),
+                    (None, Some(field)) => format!("File in field '{}'", field),
+                    (None, None) => "Uploaded file".to_string(),
+                };
+                res.status(415).json(serde_json::json!({
+                    "error": "File type not allowed",
+                    "message": format!(
+                        "{} was detected as {}, which is not an allowed file type",
+                        description, rejected.detected.mime_type
+                    ),
+                    "field": rejected.field_name,
+                    "filename": rejected.filename,
+                    "detected_type": rejected.detected.mime_type,
+                    "allowed": config.allowed_file_types,
+                }))
+            }
+            UploadAbort::Malformed(e) => error_response(res, 400, &e),
+            UploadAbort::Limit(e) => e.into_response(res),
+            UploadAbort::Body(e) if e.kind() == std::io::ErrorKind::InvalidData => {
+                error_response(res, StatusCode::PayloadTooLarge.as_u16(), &e.to_string())
+            }
+            UploadAbort::Body(e) => error_response(res, 400, &e.to_string()),
+            UploadAbort::Io(e) => error_response(res, 500, &format!("Upload failed: {}", e)),
+        }
+    }
+}
+
+/// A file part currently being written to disk
+struct PartFile {
+    headers: MultipartPartHeaders,
+    file: File,
+    temp_path: PathBuf,
+    id: Uuid,
+    size: u64,
+    head: Vec<u8>,
+}
+
+/// Parses a multipart body chunk by chunk, writing file parts straight to
+/// temporary files in `upload_dir` and enforcing the size and count limits
+/// while parsing.
+///
+/// On any error every file written for the request, including the partial
+/// one, is deleted before returning.
+pub(crate) async fn stream_multipart_to_disk(
+    req: &HttpRequest,
+    boundary: &str,
+    config: &FileUploadConfiguration,
+) -> Result<StreamedUpload, UploadAbort> {
+    let upload_dir = Path::new(&config.upload_dir);
+    create_dir_all(upload_dir).await.map_err(UploadAbort::Io)?;
+
+    let mut stored_paths: Vec<PathBuf> = Vec::new();
+    let mut current: Option<PartFile> = None;
+
+    let result = async {
+        let mut upload = StreamedUpload::default();
+        let mut parser = MultipartParser::new(boundary);
+        let mut field: Option<(String, Vec<u8>)> = None;
+        let mut file_count: u64 = 0;
+        let mut part_count = 0;
+        let limits = req.settings.multipart_limits;
+        let mut body = req.body_stream();
+
+        loop {
+            let (events, finished) = match body.next().await {
+                Some(chunk) => (parser.feed(&chunk.map_err(UploadAbort::Body)?), false),
+                None => (parser.finish(), true),
+            };
+            let events = events.map_err(UploadAbort::Malformed)?;
+
+            for event in events {
+                match event {
+                    MultipartEvent::PartStart(headers) => {
+                        check_multipart_part(part_count, headers.name.as_deref(), &limits)
+                            .map_err(UploadAbort::Limit)?;
+                        part_count += 1;
+                        if headers.is_file() {
+                            file_count += 1;
+                            if file_count > config.max_files {
+                                return Err(UploadAbort::TooManyFiles);
+                            }
+                            let id = Uuid::new_v4();
+                            let temp_path = upload_dir.join(format!("{}.part", id));
+                            let file = File::create(&temp_path).await.map_err(UploadAbort::Io)?;
+                            current = Some(PartFile {
+                                headers,
+                                file,
+                                temp_path,
+                                id,
+                                size: 0,
+                                head: Vec::new(),
+                            });
+                        } else if let Some(name) = headers.name {
+                            field = Some((name, Vec::new()));
+                        }
+                    }
+                    MultipartEvent::Data(data) => {
+                        if let Some(part) = current.as_mut() {
+                            part.size += data.len() as u64;
+                            if part.size > config.max_file_size {
+                                return Err(UploadAbort::FileTooLarge(part.headers.name.clone()));
+                            }
+                            if part.head.len() < SNIFF_LEN {
+                                let take = (SNIFF_LEN - part.head.len()).min(data.len());
+                                part.head.extend_from_slice(&data[..take]);
+                            }
+                            part.file.write_all(&data).await.map_err(UploadAbort::Io)?;
+                        } else if let Some((name, value)) = field.as_mut() {
+                            if value.len() + data.len() > limits.max_field_value_len {
+                                return Err(UploadAbort::Limit(
+                                    MultipartLimitError::FieldValueTooLong(
+                                        name.clone(),
+                                        limits.max_field_value_len,
+                                    ),
+                                ));
+                            }
+                            value.extend_from_slice(&data);
+                        }
+                    }
+                    MultipartEvent::PartEnd => {
+                        if let Some(mut part) = current.take() {
+                            part.file.flush().await.map_err(UploadAbort::Io)?;
+                            drop(part.file);
+
+                            let sniffed = SniffedType::of(&part.head);
+                            if !is_allowed_type(&sniffed, &config.allowed_file_types) {
+                                let _ = remove_file(&part.temp_path).await;
+                                return Err(UploadAbort::DisallowedType(RejectedFile::new(
+                                    part.headers,
+                                    sniffed,
+                                )));
+                            }
+
+                            let filename = format!("{}.{}", part.id, sniffed.extension);
+                            let final_path = upload_dir.join(&filename);
+                            if let Err(e) = rename(&part.temp_path, &final_path).await {
+                                let _ = remove_file(&part.temp_path).await;
+                                return Err(UploadAbort::Io(e));
+                            }
+                            stored_paths.push(final_path.clone());
+                            upload.files.push(UploadedFile {
+                                field_name: part.headers.name,
+                                original_filename: part.headers.filename,
+                                content_type: part.headers.content_type,
+                                size: part.size,
+                                stored_path: final_path,
+                            });
+                        } else if let Some((name, value)) = field.take() {
+                            if let Ok(value) = String::from_utf8(value) {
+                                upload.fields.push((name, value));
+                            }
+                        }
+                    }
+                }
+            }
+
+            if finished {
+                return Ok(upload);
+            }
+        }
+    }
+    .await;
+
+    if result.is_err() {
+        if let Some(part) = current.take() {
+            drop(part.file);
+            let _ = remove_file(&part.temp_path).await;
+        }
+        for path in stored_paths {
+            let _ = remove_file(&path).await;
+        }
+    }
+
+    result
+}
+
 /// Converts HashMap<String, String> form data to a string representation
 ///
 /// This is an internal helper function used as a fallback when binary data
@@ -1,5 +1,6 @@
 #![warn(missing_docs)]
 use crate::{context::HttpResponse, next::Next, req::HttpRequest, types::MiddlewareOutput};
+use serde_json::{Map, Value};
 use std::collections::HashMap;
 use tracing::info;
 
@@ -18,6 +19,8 @@ use tracing::info;
 /// * **Path exclusion** - Skip logging for specified paths (e.g., health checks)
 /// * **Query parameter logging** - Capture and log request query parameters
 /// * **Body size tracking** - Log response body size with stream detection
+/// * **Request IDs** - Includes `request_id` when the request ID middleware is enabled
+/// * **Latency tracking** - Log the time from when the connection layer received a request until the logger's post phase
 /// * **IP address logging** - Capture client IP addresses
 /// * **User agent detection** - Log client user agent strings
 /// * **Prefix-based exclusion** - Exclude paths using prefix matching
@@ -35,8 +38,17 @@ use tracing::info;
 /// * `ip` - Log client IP address - default: true
 /// * `headers` - List of specific headers to log - default: empty
 /// * `body_size` - Log response body size - default: true
+/// * `duration` - Log request latency as `duration_ms` - default: true
 /// * `query_params` - Log query parameters - default: true
 /// * `exclude_paths` - List of path prefixes to exclude from logging - default: empty
+/// * `format` - Output format, [`LogFormat::Text`] or [`LogFormat::Json`] - default: `Text`
+///
+/// ## Latency Measurement
+///
+/// The connection layer records a monotonic timestamp on each request as it
+/// arrives, and the logger's post-middleware reports the time elapsed since then
+/// as `duration_ms`, in milliseconds with microsecond precision. Because the
+/// timestamp travels with each request, concurrent requests never share timings.
 ///
 /// ## Path Exclusion Behavior
 ///
@@ -51,6 +63,7 @@ use tracing::info;
 /// The middleware outputs structured logs using the `tracing` crate with the following format:
 /// ```md
 /// path: /api/users,
+/// route: /api/users,
 /// user_agent: Mozilla/5.0...,
 /// ip: 192.168.1.1,
 /// custom-header: value,
@@ -60,6 +73,18 @@ use tracing::info;
 /// body_size: 1024
 /// ```
 ///
+/// ## JSON Output
+///
+/// With `format: LogFormat::Json` each request is logged as a single JSON object
+/// on one line. The middleware serializes the object itself, so the line is
+/// parseable with any `tracing` subscriber (configure the subscriber without
+/// timestamps or levels if the raw line should be the whole output). Disabled
+/// fields are omitted, and configured headers that are missing are `null`:
+///
+/// ```text
+/// {"body_size":512,"duration_ms":3.214,"headers":{"x-request-id":"abc-123"},"ip":"192.168.1.1","method":"GET","path":"/api/users","route":"/api/users","status":200}
+/// ```
+///
 /// ## Examples
 ///
 /// Basic usage with default configuration:
@@ -89,6 +114,7 @@ use tracing::info;
 ///     body_size: false,
 ///     query_params: false,
 ///     exclude_paths: vec![],
+///     ..Default::default()
 /// };
 /// app.use_logger(Some(config));
 /// ```
@@ -118,10 +144,29 @@ use tracing::info;
 ///         "/metrics".to_string(),
 ///         "/favicon.ico".to_string(),
 ///     ],
+///     ..Default::default()
 /// };
 /// app.use_logger(Some(config));
 /// ```
 ///
+/// JSON lines for log aggregation:
+///
+/// ```no_run
+/// use ripress::{
+///     app::App,
+///     middlewares::logger::{LogFormat, LoggerConfig},
+/// };
+///
+/// tracing_subscriber::fmt().without_time().with_level(false).with_target(false).init();
+///
+/// let mut app = App::new();
+/// app.use_logger(Some(LoggerConfig {
+///     format: LogFormat::Json,
+///     headers: vec!["x-request-id".to_string()],
+///     ..Default::default()
+/// }));
+/// ```
+///
 /// Using default configuration (recommended for development):
 ///
 /// ```no_run
@@ -159,6 +204,7 @@ use tracing::info;
 ///         "/metrics".to_string(),
 ///         "/internal".to_string(),
 ///     ],
+///     ..Default::default()
 /// };
 /// app.use_logger(Some(config));
 /// ```
@@ -174,6 +220,7 @@ use tracing::info;
 /// query_params: {"include": "profile", "format": "json"},
 /// method: GET,
 /// body_size: 2048
+/// duration_ms: 3.214
 /// ```
 ///
 /// Custom headers output:
@@ -241,14 +288,17 @@ use tracing::info;
 /// ## Field Details
 ///
 /// * `method` - Logs the HTTP method (GET, POST, PUT, DELETE, etc.)
-/// * `path` - Logs the request path (e.g., "/api/users/123")
+/// * `path` - Logs the request path (e.g., "/api/users/123") and, if a route matched,
+///   its pattern as `route` (e.g., "/api/users/:id") for grouping requests by route
 /// * `status` - Logs the HTTP response status code (200, 404, 500, etc.)
 /// * `user_agent` - Logs the User-Agent header sent by the client
 /// * `ip` - Logs the client's IP address
 /// * `headers` - A list of specific header names to log (case-insensitive)
 /// * `body_size` - Logs the size of the response body in bytes, or "stream" for streaming responses
+/// * `duration` - Logs the time spent handling the request as `duration_ms`
 /// * `query_params` - Logs URL query parameters as a structured format
 /// * `exclude_paths` - Path prefixes that should be excluded from logging entirely
+/// * `format` - Whether entries are written as text or as one JSON object per request
 ///
 /// ## Default Configuration
 ///
@@ -256,11 +306,12 @@ use tracing::info;
 /// - All boolean fields default to `true`
 /// - `headers` defaults to empty (no custom headers logged)
 /// - `exclude_paths` defaults to empty (all paths logged)
+/// - `format` defaults to [`LogFormat::Text`]
 #[derive(Clone)]
 pub struct LoggerConfig {
     /// Whether to log the HTTP method (GET, POST, etc.)
     pub method: bool,
-    /// Whether to log the request path
+    /// Whether to log the request path, and the matched route pattern as `route`
     pub path: bool,
     /// Whether to log the response status code
     pub status: bool,
@@ -275,8 +326,14 @@ pub struct LoggerConfig {
     pub headers: Vec<String>,
     /// Whether to log the response body size
     ///
-    /// Shows actual byte count for regular responses, "stream" for streaming responses.
+    /// Shows the response `Content-Length` when set, otherwise the byte count of the
+    /// body, or "stream" for streaming responses.
     pub body_size: bool,
+    /// Whether to log how long the request took, as `duration_ms`
+    ///
+    /// Measured with a monotonic clock from when the connection layer received
+    /// the request until the logger's post phase.
+    pub duration: bool,
     /// Whether to log URL query parameters
     ///
     /// Query parameters are logged in a structured JSON-like format for easy parsing.
@@ -286,6 +343,21 @@ pub struct LoggerConfig {
     /// Uses prefix matching: "/health" excludes "/health", "/health/live", etc.
     /// Useful for excluding health checks, metrics endpoints, and other high-frequency requests.
     pub exclude_paths: Vec<String>,
+    /// Output format of each log entry
+    ///
+    /// [`LogFormat::Json`] writes a single JSON object per request, which log
+    /// aggregators can ingest without a JSON-aware tracing subscriber.
+    pub format: LogFormat,
+}
+
+/// Output format of the logger middleware
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum LogFormat {
+    /// Human-readable `key: value` lines
+    #[default]
+    Text,
+    /// One JSON object per request, serialized by the middleware
+    Json,
 }
 
 impl Default for LoggerConfig {
@@ -298,12 +370,43 @@ impl Default for LoggerConfig {
             ip: true,
             headers: vec![],
             body_size: true,
+            duration: true,
             query_params: true,
             exclude_paths: vec![],
+            format: LogFormat::default(),
         }
     }
 }
 
+/// All values of a request header, joined with `", "` when it was sent more than once.
+fn header_value(req: &HttpRequest, name: &str) -> Option<String> {
+    let values = req.headers.get_all(name);
+    (!values.is_empty()).then(|| values.join(", "))
+}
+
+/// Milliseconds elapsed since the request was received, if that is known.
+pub(crate) fn elapsed_ms(req: &HttpRequest) -> Option<f64> {
+    let elapsed = req.received_at?.elapsed();
+    Some(elapsed.as_micros() as f64 / 1000.0)
+}
+
+/// Size of the response body in bytes, preferring the `Content-Length` header.
+/// Returns `None` for streaming responses without a declared length.
+pub(crate) fn response_bytes(res: &HttpResponse) -> Option<usize> {
+    if let Some(length) = res
+        .headers
+        .get("content-length")
+        .and_then(|v| v.trim().parse().ok())
+    {
+        return Some(length);
+    }
+    if res.stream.is_some() {
+        None
+    } else {
+        Some(res.body.len())
+    }
+}
+
 /// Creates a logger middleware function
 ///
 /// Returns a middleware function that logs HTTP request and response information
@@ -376,63 +479,139 @@ pub(crate) fn logger(
         }
 
         Box::pin(async move {
-            let path = req.path.clone();
-            let method = req.method.clone();
-            let user_agent = req.headers.user_agent().unwrap_or("Unknown").to_string();
-            let ip = req.ip();
-            let status_code = res.status_code;
-            let mut headers = HashMap::new();
+            let msg = match config.format {
+                LogFormat::Text => format_text(&config, &req, &res),
+                LogFormat::Json => format_json(&config, &req, &res),
+            };
 
-            if !config.headers.is_empty() {
-                for header in &config.headers {
-                    let key = header.to_ascii_lowercase();
-                    let value = req
-                        .headers
-                        .get(&key)
-                        .map(|v| v.to_string())
-                        .unwrap_or_else(|| "<missing>".to_string());
-                    headers.insert(key, value);
-                }
-            }
+            info!("{}", msg);
 
-            let query_params = req.query.clone();
+            return next.call(req, res).await;
+        })
+    }
+}
 
-            let mut msg = String::new();
+/// Formats a log entry as `key: value` lines.
+pub(crate) fn format_text(config: &LoggerConfig, req: &HttpRequest, res: &HttpResponse) -> String {
+    let mut msg = String::new();
 
-            if config.path {
-                msg.push_str(&format!("path: {}, \n", path));
-            }
-            if config.user_agent {
-                msg.push_str(&format!("user_agent: {}, \n", user_agent));
-            }
-            if config.ip {
-                msg.push_str(&format!("ip: {}, \n", ip));
-            }
-            for (key, value) in headers {
-                msg.push_str(&format!("{}: {}, \n", key, value));
-            }
-            if config.status {
-                msg.push_str(&format!("status_code: {}\n", status_code));
-            }
-            if config.query_params {
-                msg.push_str(&format!("query_params: {:?}, \n", query_params));
-            }
-            if config.method {
-                msg.push_str(&format!("method: {}, \n", method));
-            }
-            if config.body_size {
-                if res.stream.is_some() {
-                    msg.push_str("body_size: stream\n");
-                } else {
-                    msg.push_str(&format!("body_size: {}\n", res.body.len()));
-                }
-            }
+    if let Some(id) = req.request_id() {
+        msg.push_str(&format!("request_id: {}, \n", id));
+    }
+    if config.path {
+        msg.push_str(&format!("path: {}, \n", req.path));
+        if let Some(route) = &req.matched_path {
+            msg.push_str(&format!("route: {}, \n", route));
+        }
+    }
+    if config.user_agent {
+        let user_agent = req.headers.user_agent().unwrap_or("Unknown");
+        msg.push_str(&format!("user_agent: {}, \n", user_agent));
+    }
+    if config.ip {
+        msg.push_str(&format!("ip: {}, \n", req.ip()));
+    }
+    let mut headers = HashMap::new();
+    for header in &config.headers {
+        let key = header.to_ascii_lowercase();
+        let value = header_value(req, &key).unwrap_or_else(|| "<missing>".to_string());
+        headers.insert(key, value);
+    }
+    for (key, value) in headers {
+        msg.push_str(&format!("{}: {}, \n", key, value));
+    }
+    if config.status {
+        msg.push_str(&format!("status_code: {}\n", res.status_code));
+    }
+    if config.query_params {
+        msg.push_str(&format!("query_params: {:?}, \n", req.query));
+    }
+    if config.method {
+        msg.push_str(&format!("method: {}, \n", req.method));
+    }
+    if config.body_size {
+        match response_bytes(res) {
+            Some(bytes) => msg.push_str(&format!("body_size: {}\n", bytes)),
+            None => msg.push_str("body_size: stream\n"),
+        }
+    }
+    if config.duration {
+        if let Some(duration_ms) = elapsed_ms(req) {
+            msg.push_str(&format!("duration_ms: {:.3}\n", duration_ms));
+        }
+    }
 
-            let msg = msg.trim_end_matches([',', ' ', '\t', '\n']);
+    msg.trim_end_matches([',', ' ', '\t', '\n']).to_string()
+}
 
-            info!("{}", msg);
+/// Formats a log entry as a single-line JSON object.
+pub(crate) fn format_json(config: &LoggerConfig, req: &HttpRequest, res: &HttpResponse) -> String {
+    let mut entry = Map::new();
 
-            return next.call(req, res).await;
-        })
+    if let Some(id) = req.request_id() {
+        entry.insert("request_id".into(), Value::from(id));
+    }
+
+    if config.method {
+        entry.insert("method".into(), Value::from(req.method.to_string()));
+    }
+    if config.path {
+        entry.insert("path".into(), Value::from(req.path.clone()));
+        if let Some(route) = &req.matched_path {
+            entry.insert("route".into(), Value::from(route.clone()));
+        }
+    }
+    if config.status {
+        entry.insert("status".into(), Value::from(res.status_code.as_u16()));
+    }
+    if config.ip {
+        entry.insert("ip".into(), Value::from(req.ip().to_string()));
+    }
+    if config.user_agent {
+        let user_agent = req.headers.user_agent().map(Value::from);
+        entry.insert("user_agent".into(), user_agent.unwrap_or(Value::Null));
     }
+    if !config.headers.is_empty() {
+        let headers: Map<String, Value> = config
+            .headers
+            .iter()
+            .map(|header| {
+                let key = header.to_ascii_lowercase();
+                let value = header_value(req, &key)
+                    .map(Value::from)
+                    .unwrap_or(Value::Null);
+                (key, value)
+            })
+            .collect();
+        entry.insert("headers".into(), Value::Object(headers));
+    }
+    if config.query_params {
+        let query: Map<String, Value> = req
+            .query
+            .iter_all()
+            .map(|(key, values)| {
+                let value = match values.as_slice() {
+                    [single] => Value::from(single.clone()),
+                    _ => Value::from(values.clone()),
+                };
+                (key.clone(), value)
+            })
+            .collect();
+        entry.insert("query_params".into(), Value::Object(query));
+    }
+    if config.body_size {
+        let body_size = match response_bytes(res) {
+            Some(bytes) => Value::from(bytes),
+            None => Value::from("stream"),
+        };
+        entry.insert("body_size".into(), body_size);
+    }
+    if config.duration {
+        if let Some(duration_ms) = elapsed_ms(req) {
+            let rounded = (duration_ms * 1000.0).round() / 1000.0;
+            entry.insert("duration_ms".into(), Value::from(rounded));
+        }
+    }
+
+    Value::Object(entry).to_string()
 }
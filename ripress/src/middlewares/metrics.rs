@@ -0,0 +1,229 @@
+#![warn(missing_docs)]
+use std::{
+    collections::BTreeMap,
+    fmt::Write,
+    future::Future,
+    sync::{
+        atomic::{AtomicUsize, Ordering},
+        Arc, Mutex,
+    },
+    time::{Duration, Instant},
+};
+
+use crate::router::route_pattern::MatchedPath;
+
+/// Label used for requests that didn't match a route, such as 404s, static
+/// files and requests answered by a pre-middleware.
+pub(crate) const UNMATCHED_PATH: &str = "<unmatched>";
+
+/// Latency buckets, in seconds, used when none are configured. These are the
+/// Prometheus client defaults.
+pub const DEFAULT_BUCKETS: [f64; 11] = [
+    0.005, 0.01, 0.025, 0.05, 0.1, 0.25, 0.5, 1.0, 2.5, 5.0, 10.0,
+];
+
+/// Configuration for the metrics middleware
+///
+/// ## Example
+///
+/// ```
+/// use ripress::middlewares::metrics::MetricsConfig;
+///
+/// let config = MetricsConfig {
+///     path: "/internal/metrics".to_string(),
+///     buckets: vec![0.01, 0.1, 1.0],
+/// };
+/// ```
+#[derive(Debug, Clone, PartialEq)]
+pub struct MetricsConfig {
+    /// Path the Prometheus text exposition is served on
+    pub path: String,
+    /// Upper bounds of the latency histogram buckets, in seconds. A `+Inf`
+    /// bucket is always added; non-finite values are ignored.
+    pub buckets: Vec<f64>,
+}
+
+impl Default for MetricsConfig {
+    fn default() -> Self {
+        MetricsConfig {
+            path: "/metrics".to_string(),
+            buckets: DEFAULT_BUCKETS.to_vec(),
+        }
+    }
+}
+
+/// Request metrics collected by [`App::use_metrics`](crate::app::App::use_metrics).
+pub(crate) struct Metrics {
+    buckets: Vec<f64>,
+    in_flight: AtomicUsize,
+    series: Mutex<BTreeMap<(String, String), Series>>,
+}
+
+/// Counts and latencies for one method and route pattern.
+struct Series {
+    /// Observations per bucket, not cumulative; the last entry is `+Inf`
+    buckets: Vec<u64>,
+    sum: f64,
+    count: u64,
+}
+
+impl Metrics {
+    pub(crate) fn new(buckets: &[f64]) -> Self {
+        let mut buckets: Vec<f64> = buckets.iter().copied().filter(|b| b.is_finite()).collect();
+        buckets.sort_by(f64::total_cmp);
+        buckets.dedup();
+
+        Metrics {
+            buckets,
+            in_flight: AtomicUsize::new(0),
+            series: Mutex::new(BTreeMap::new()),
+        }
+    }
+
+    /// Counts a request as in flight until `response` resolves, then records it
+    /// under the route pattern found on the response.
+    pub(crate) fn track<F, B, E>(
+        self: &Arc<Self>,
+        method: &hyper::Method,
+        response: F,
+    ) -> impl Future<Output = Result<hyper::Response<B>, E>>
+    where
+        F: Future<Output = Result<hyper::Response<B>, E>>,
+    {
+        let method = method_label(method);
+        let start = Instant::now();
+        self.in_flight.fetch_add(1, Ordering::SeqCst);
+        let guard = InFlight(Arc::clone(self));
+
+        async move {
+            let result = response.await;
+            if let Ok(res) = &result {
+                let path = res
+                    .extensions()
+                    .get::<MatchedPath>()
+                    .map_or(UNMATCHED_PATH, |matched| matched.0.as_str());
+                guard.0.observe(method, path, start.elapsed());
+            }
+            drop(guard);
+            result
+        }
+    }
+
+    fn observe(&self, method: &str, path: &str, elapsed: Duration) {
+        let seconds = elapsed.as_secs_f64();
+        let bucket = self
+            .buckets
+            .iter()
+            .position(|&le| seconds <= le)
+            .unwrap_or(self.buckets.len());
+
+        let mut series = self.series.lock().unwrap();
+        let series = series
+            .entry((method.to_string(), path.to_string()))
+            .or_insert_with(|| Series {
+                buckets: vec![0; self.buckets.len() + 1],
+                sum: 0.0,
+                count: 0,
+            });
+        series.buckets[bucket] += 1;
+        series.sum += seconds;
+        series.count += 1;
+    }
+
+    /// Renders every metric in the Prometheus text exposition format.
+    pub(crate) fn render(&self) -> String {
+        let series = self.series.lock().unwrap();
+        let mut out = String::new();
+
+        out.push_str("# HELP http_requests_total Total number of HTTP requests.\n");
+        out.push_str("# TYPE http_requests_total counter\n");
+        for ((method, path), s) in series.iter() {
+            let _ = writeln!(
+                out,
+                "http_requests_total{{{}}} {}",
+                labels(method, path),
+                s.count
+            );
+        }
+
+        out.push_str("# HELP http_requests_in_flight Number of HTTP requests being served.\n");
+        out.push_str("# TYPE http_requests_in_flight gauge\n");
+        let _ = writeln!(
+            out,
+            "http_requests_in_flight {}",
+            self.in_flight.load(Ordering::SeqCst)
+        );
+
+        out.push_str("# HELP http_request_duration_seconds HTTP request latency in seconds.\n");
+        out.push_str("# TYPE http_request_duration_seconds histogram\n");
+        for ((method, path), s) in series.iter() {
+            let labels = labels(method, path);
+            let mut cumulative = 0;
+            for (i, count) in s.buckets.iter().enumerate() {
+                cumulative += count;
+                let le = match self.buckets.get(i) {
+                    Some(le) => le.to_string(),
+                    None => "+Inf".to_string(),
+                };
+                let _ = writeln!(
+                    out,
+                    "http_request_duration_seconds_bucket{{{},le=\"{}\"}} {}",
+                    labels, le, cumulative
+                );
+            }
+            let _ = writeln!(
+                out,
+                "http_request_duration_seconds_sum{{{}}} {}",
+                labels, s.sum
+            );
+            let _ = writeln!(
+                out,
+                "http_request_duration_seconds_count{{{}}} {}",
+                labels, s.count
+            );
+        }
+
+        out
+    }
+}
+
+/// Decrements the in-flight gauge when the response future completes or is dropped.
+struct InFlight(Arc<Metrics>);
+
+impl Drop for InFlight {
+    fn drop(&mut self) {
+        self.0.in_flight.fetch_sub(1, Ordering::SeqCst);
+    }
+}
+
+/// Keeps the `method` label bounded: extension methods share one series.
+fn method_label(method: &hyper::Method) -> &'static str {
+    match *method {
+        hyper::Method::GET => "GET",
+        hyper::Method::POST => "POST",
+        hyper::Method::PUT => "PUT",
+        hyper::Method::PATCH => "PATCH",
+        hyper::Method::DELETE => "DELETE",
+        hyper::Method::HEAD => "HEAD",
+        hyper::Method::OPTIONS => "OPTIONS",
+        hyper::Method::CONNECT => "CONNECT",
+        hyper::Method::TRACE => "TRACE",
+        _ => "OTHER",
+    }
+}
+
+fn labels(method: &str, path: &str) -> String {
+    format!(
+        "method=\"{}\",path=\"{}\"",
+        escape_label(method),
+        escape_label(path)
+    )
+}
+
+/// Escapes a label value as the exposition format requires.
+fn escape_label(value: &str) -> String {
+    value
+        .replace('\\', "\\\\")
+        .replace('"', "\\\"")
+        .replace('\n', "\\n")
+}
@@ -24,6 +24,8 @@
 //! | [`rate_limiter`] | Request rate limiting and DoS protection | Pre-execution |
 //! | [`body_limit`] | Request body size enforcement | Pre-execution |
 //! | [`compression`] | Response body compression (gzip) | Post-execution |
+//! | [`etag`] | ETag generation and conditional GET handling | Post-execution |
+//! | [`request_id`] | Correlation IDs via `X-Request-Id` | Pre-execution |
 //! | [`shield`] | Comprehensive security headers | Pre-execution |
 //!
 //! ## Middleware Execution Order
@@ -38,7 +40,8 @@
 //!    ├── Security headers
 //!    └── File uploads
 //!
-//! 2. Route handler execution
+//! 2. Around middlewares (first registered is outermost)
+//!    └── Route handler execution
 //!
 //! 3. Post-execution middlewares (in registration order)
 //!    ├── Response compression
@@ -62,7 +65,7 @@
 //!     // Add security and CORS (pre-execution)
 //!     app.use_shield(None);
 //!     app.use_cors(Some(CorsConfig {
-//!         allowed_origin: "https://myapp.com",
+//!         allowed_origins: "https://myapp.com".into(),
 //!         allowed_methods: "GET, POST, PUT, DELETE",
 //!         ..Default::default()
 //!     }));
@@ -148,7 +151,7 @@
 ///
 /// ## Security Considerations
 ///
-/// - **Avoid Wildcards in Production**: Using `*` for `allowed_origin` with credentials is dangerous
+/// - **Avoid Wildcards in Production**: Using `OriginMatcher::Any` with credentials reflects every origin
 /// - **Principle of Least Privilege**: Only allow the origins, methods, and headers you actually need
 /// - **Regular Auditing**: Review your CORS configuration regularly as your application evolves
 ///
@@ -156,7 +159,7 @@
 ///
 /// ```rust
 /// use ripress::app::App;
-/// use ripress::middlewares::cors::CorsConfig;
+/// use ripress::middlewares::cors::{CorsConfig, OriginMatcher};
 ///
 /// let mut app = App::new();
 ///
@@ -165,16 +168,17 @@
 ///
 /// // Production CORS configuration
 /// app.use_cors(Some(CorsConfig {
-///     allowed_origin: "https://myapp.com",
+///     allowed_origins: "https://myapp.com".into(),
 ///     allowed_methods: "GET, POST, PUT, DELETE, OPTIONS",
 ///     allowed_headers: "Content-Type, Authorization, X-Requested-With",
 ///     allow_credentials: true,
 ///     ..Default::default()
 /// }));
 ///
-/// // Multiple origins (comma-separated)
+/// // Multiple origins, each reflected back when it matches
 /// app.use_cors(Some(CorsConfig {
-///     allowed_origin: "https://app1.com, https://app2.com, https://localhost:3000",
+///     allowed_origins: OriginMatcher::list(["https://app1.com", "https://app2.com", "http://localhost:3000"]),
+///     allow_credentials: true,
 ///     ..Default::default()
 /// }));
 /// ```
@@ -230,7 +234,7 @@ pub mod cors;
 ///
 /// ```rust
 /// use ripress::app::App;
-/// use ripress::middlewares::logger::LoggerConfig;
+/// use ripress::middlewares::logger::{LogFormat, LoggerConfig};
 ///
 /// // Initialize tracing subscriber
 /// tracing_subscriber::fmt::init();
@@ -249,8 +253,10 @@ pub mod cors;
 ///     ip: true,
 ///     headers: vec!["content-type".to_string()],
 ///     body_size: true,
+///     duration: true,
 ///     query_params: true,
 ///     exclude_paths: vec!["/health".to_string()],
+///     format: LogFormat::Text,
 /// }));
 /// ```
 ///
@@ -331,6 +337,15 @@ pub mod logger;
 ///     max_file_size: 10 * 1024 * 1024, // 10MB per file
 ///     max_files: 100,
 ///     allowed_file_types: vec!["jpeg".to_string(), "png".to_string()],
+///     ..Default::default()
+/// })));
+///
+/// // Stream parts to disk and answer 413 as soon as a file crosses the limit;
+/// // the route must be registered with `RouteHandle::stream_body`
+/// app.use_pre_middleware(Some("/videos"), file_upload(Some(FileUploadConfiguration {
+///     max_file_size: 100 * 1024 * 1024,
+///     stream_to_disk: true,
+///     ..Default::default()
 /// })));
 /// ```
 ///
@@ -440,6 +455,9 @@ pub mod file_upload;
 /// ### Token Bucket
 /// Allows bursts while maintaining average rate limits over time.
 ///
+/// The strategy is selected with `RateLimiterConfig::strategy` and defaults to
+/// `RateLimitStrategy::FixedWindow`.
+///
 /// ## Configuration Examples
 ///
 /// ```no_run
@@ -610,7 +628,8 @@ pub mod rate_limiter;
 /// - **Default Limit**: 1 MiB (1,048,576 bytes) when no configuration is provided
 /// - **Status Code**: Returns HTTP 413 Payload Too Large for violations
 /// - **Scope**: Applied globally to all routes unless path-specific configuration is used
-/// - **Execution**: Runs as pre-middleware before route handlers and body parsing
+/// - **Execution**: Enforced while the body is read from the connection, so chunked
+///   bodies are capped too, and again as pre-middleware before route handlers
 ///
 /// ## Configuration Examples
 ///
@@ -670,13 +689,17 @@ pub mod rate_limiter;
 /// Request body too large
 /// ```
 ///
-/// For API applications, you might want to add custom error handling:
+/// For API applications, you might want to add custom error handling. A check like
+/// this only sees the declared `Content-Length`, which a client can leave out by
+/// sending the body chunked, so keep [`App::use_body_limit`](crate::app::App::use_body_limit)
+/// in place to enforce the cap:
 ///
 /// ```rust
 /// use ripress::app::App;
 /// use ripress::req::HttpRequest;
 ///
 /// let mut app = App::new();
+/// app.use_body_limit(Some(1024 * 1024));
 ///
 /// // Custom body limit with better error handling
 /// app.use_pre_middleware(None, |req: HttpRequest, res, next| async move {
@@ -798,6 +821,8 @@ pub mod body_limit;
 /// - **Content Types**: Automatically compresses text-based content types
 /// - **Client Support**: Only compresses when client supports gzip encoding
 /// - **Header Handling**: Adds `Content-Encoding: gzip` and appropriate `Vary` headers
+/// - **Already Encoded**: Leaves responses with a non-`identity` `Content-Encoding` untouched
+/// - **No-Transform**: Skips responses carrying `Cache-Control: no-transform`
 ///
 /// ## Configuration Examples
 ///
@@ -814,18 +839,21 @@ pub mod body_limit;
 /// app.use_compression(Some(CompressionConfig {
 ///     threshold: 512,          // Compress responses > 512 bytes
 ///     level: 9,                // Maximum compression (slower)
+///     ..Default::default()
 /// }));
 ///
 /// // Fast compression for high-traffic applications
 /// app.use_compression(Some(CompressionConfig {
 ///     threshold: 2048,         // Only compress larger responses
 ///     level: 1,                // Fast compression (less CPU usage)
+///     ..Default::default()
 /// }));
 ///
 /// // Conservative compression for legacy clients
 /// app.use_compression(Some(CompressionConfig {
 ///     threshold: 4096,         // Conservative threshold
 ///     level: 4,                // Moderate compression
+///     ..Default::default()
 /// }));
 /// ```
 ///
@@ -853,9 +881,19 @@ pub mod body_limit;
 /// - `application/pdf`
 ///
 /// ### Custom Content Type Configuration
+/// Use `compressible_types` to compress additional types and `incompressible_types`
+/// to opt types out. Both accept `type/subtype` patterns with wildcards like `text/*`,
+/// and the deny list always wins:
+///
 /// ```rust
 /// use ripress::middlewares::compression::CompressionConfig;
-/// let _cfg = CompressionConfig { threshold: 2048, level: 6 };
+/// let cfg = CompressionConfig {
+///     compressible_types: vec!["application/wasm".to_string(), "application/x-ndjson".to_string()],
+///     incompressible_types: vec!["text/event-stream".to_string()],
+///     ..Default::default()
+/// };
+/// assert!(cfg.is_compressible("application/x-ndjson"));
+/// assert!(!cfg.is_compressible("text/event-stream"));
 /// ```
 ///
 /// ## Compression Level Guidelines
@@ -873,9 +911,9 @@ pub mod body_limit;
 /// ```rust
 /// use ripress::middlewares::compression::CompressionConfig;
 /// // For high-CPU, unlimited bandwidth environments
-/// let _cfg_fast = CompressionConfig { level: 1, threshold: 8192 };
+/// let _cfg_fast = CompressionConfig { level: 1, threshold: 8192, ..Default::default() };
 /// // For limited bandwidth, adequate CPU environments
-/// let _cfg_tight = CompressionConfig { level: 8, threshold: 256 };
+/// let _cfg_tight = CompressionConfig { level: 8, threshold: 256, ..Default::default() };
 /// ```
 ///
 /// ### Memory Usage
@@ -884,7 +922,7 @@ pub mod body_limit;
 /// ```rust
 /// use ripress::middlewares::compression::CompressionConfig;
 /// // Streaming is internal; configure via threshold/level.
-/// let _cfg = CompressionConfig { threshold: 4096, level: 6 };
+/// let _cfg = CompressionConfig { threshold: 4096, level: 6, ..Default::default() };
 /// ```
 ///
 /// ## Caching Integration
@@ -915,7 +953,7 @@ pub mod body_limit;
 /// let mut app = App::new();
 ///
 /// // Default compression for most routes
-/// app.use_compression(Some(CompressionConfig { level: 6, threshold: 1024 }));
+/// app.use_compression(Some(CompressionConfig { level: 6, threshold: 1024, ..Default::default() }));
 ///
 /// // Route-specific skip example (identity)
 /// app.use_post_middleware(Some("/files"), |req: HttpRequest, res, next| async move {
@@ -962,6 +1000,227 @@ pub mod body_limit;
 #[cfg(feature = "compression")]
 pub mod compression;
 
+/// ETag / Conditional GET middleware
+///
+/// This module provides a post-execution middleware that tags successful responses with an
+/// `ETag` derived from the response body and answers revalidation requests with
+/// `304 Not Modified`. Clients and caches that already hold the current representation
+/// skip the body download entirely, while handlers stay unaware of caching.
+///
+/// ## Features
+///
+/// - **Automatic Validators**: Hashes the final response body into an `ETag` header
+/// - **Conditional GET**: Compares `If-None-Match` against the tag and returns `304` on match
+/// - **Handler Overrides**: An `ETag` already set by the handler is reused as-is
+/// - **Weak or Strong Tags**: Emits weak (`W/"..."`) validators by default, strong ones on request
+///
+/// ## Usage Examples
+///
+/// ```rust
+/// use ripress::app::App;
+/// use ripress::middlewares::etag::EtagConfig;
+///
+/// let mut app = App::new();
+///
+/// // Weak ETags for every GET/HEAD response
+/// app.use_etag(None);
+///
+/// // Strong ETags
+/// app.use_etag(Some(EtagConfig { weak: false }));
+/// ```
+///
+/// ## Default Behavior
+///
+/// - Only `GET` and `HEAD` requests with a `2xx` response are tagged
+/// - Streaming responses are passed through untouched
+/// - `If-None-Match: *` matches any tagged response
+/// - `304` responses keep all headers but carry no body
+///
+/// ## Ordering
+///
+/// Register the ETag middleware **before** [`compression`] so the tag is computed from the
+/// uncompressed representation and stays stable regardless of `Accept-Encoding`.
+pub mod etag;
+
+/// Request ID middleware
+///
+/// This module assigns every request a correlation ID so a single request can be
+/// followed through logs and across services. The ID is read from the incoming
+/// `X-Request-Id` header when present, or generated as a random UUID, and is made
+/// available to handlers, echoed on the response, and included in logger output.
+///
+/// ## Features
+///
+/// - **Propagation**: Reuses the ID sent by clients or upstream proxies
+/// - **Generation**: Falls back to a random UUID v4
+/// - **Handler Access**: Read the ID with `req.request_id()`
+/// - **Response Echo**: The same ID is set on the response header
+/// - **Logging**: The logger middleware adds a `request_id` field when an ID is present
+///
+/// ## Usage Examples
+///
+/// ```rust
+/// use ripress::app::App;
+/// use ripress::middlewares::request_id::RequestIdConfig;
+///
+/// let mut app = App::new();
+///
+/// // X-Request-Id, reusing incoming IDs
+/// app.use_request_id(None);
+///
+/// // Custom header, always generating a fresh ID
+/// app.use_request_id(Some(RequestIdConfig {
+///     header: "X-Correlation-Id".to_string(),
+///     trust_incoming: false,
+/// }));
+/// ```
+///
+/// ## Default Behavior
+///
+/// - Header name is `X-Request-Id`
+/// - Incoming IDs are reused if they are 1-128 visible ASCII characters, otherwise replaced
+///
+/// ## Ordering
+///
+/// Register the request ID middleware first so that responses short-circuited by later
+/// pre-middlewares (rate limiting, body limits, ...) also carry the header.
+#[cfg(feature = "request-id")]
+pub mod request_id;
+
+/// Prometheus metrics middleware
+///
+/// This module records request counts, an in-flight gauge and latency histograms,
+/// and serves them in the Prometheus text exposition format so the app can be
+/// scraped without extra instrumentation.
+///
+/// ## Metrics
+///
+/// - `http_requests_total{method, path}` - Counter of completed requests
+/// - `http_requests_in_flight` - Gauge of requests currently being served
+/// - `http_request_duration_seconds{method, path}` - Latency histogram
+///
+/// ## Label Cardinality
+///
+/// The `path` label is the pattern of the matched route, e.g. `/users/:id`, never the
+/// raw request path, so the number of series stays bounded by the number of routes.
+/// Requests that matched no route share the `<unmatched>` label, and non-standard
+/// methods share `OTHER`.
+///
+/// ## Usage Examples
+///
+/// ```rust
+/// use ripress::app::App;
+/// use ripress::middlewares::metrics::MetricsConfig;
+///
+/// let mut app = App::new();
+///
+/// // Served on /metrics with the default latency buckets
+/// app.use_metrics(None);
+///
+/// // Custom path and buckets, in seconds
+/// app.use_metrics(Some(MetricsConfig {
+///     path: "/internal/metrics".to_string(),
+///     buckets: vec![0.01, 0.05, 0.1, 0.5, 1.0],
+/// }));
+/// ```
+///
+/// ## Default Behavior
+///
+/// - Metrics are served on `/metrics`
+/// - Latency buckets are the Prometheus client defaults, from 5ms to 10s
+/// - Every request is recorded, including those answered by pre-middlewares or the
+///   error handler, from when the router receives it until its response is ready
+pub mod metrics;
+
+/// Session middleware
+///
+/// This module keeps per-client state across requests. Each client is given a random
+/// session ID in a signed cookie, and the data stored under that ID is loaded before
+/// the request is handled and saved once the response is ready.
+///
+/// ## Features
+///
+/// - **Signed Cookies**: The ID is signed with HMAC-SHA256, and cookies with a bad
+///   signature are ignored
+/// - **Pluggable Storage**: Sessions live in a [`session::MemoryStore`] by default;
+///   implement [`session::SessionStore`] to use Redis or a database
+/// - **Expiry**: Sessions expire a configurable time after they were last changed
+/// - **Fixation Prevention**: `req.session().regenerate()` moves the data to a new ID,
+///   and IDs unknown to the store are never adopted
+/// - **Handler Access**: Read and change the data with `req.session()`
+///
+/// ## Usage Examples
+///
+/// ```rust
+/// use std::time::Duration;
+///
+/// use ripress::app::App;
+/// use ripress::middlewares::session::SessionConfig;
+///
+/// let mut app = App::new();
+///
+/// // In-memory sessions signed with a random secret
+/// app.use_session(None);
+///
+/// // A fixed secret and a one-hour expiry
+/// app.use_session(Some(SessionConfig {
+///     secret: b"a secret of at least thirty-two bytes".to_vec(),
+///     ttl: Duration::from_secs(60 * 60),
+///     ..Default::default()
+/// }));
+/// ```
+///
+/// ## Default Behavior
+///
+/// - The cookie is named `ripress.sid` and is `HttpOnly`, `Secure` and `SameSite=Lax`
+/// - Sessions expire 24 hours after they were last changed
+/// - A session is only stored, and the cookie only set, once data is put in it
+/// - Destroying a session deletes it from the store and clears the cookie
+#[cfg(feature = "session")]
+pub mod session;
+
+/// HTTP Basic authentication middleware
+///
+/// This module protects routes with HTTP Basic authentication, which browsers
+/// support natively with a login dialog. It suits internal tools and admin pages
+/// served over HTTPS.
+///
+/// ## Features
+///
+/// - **Browser Prompt**: Failed requests get a `WWW-Authenticate` challenge with the realm
+/// - **Credential Store**: Check against a `HashMap` of usernames to passwords, compared
+///   in constant time
+/// - **Async Verifier**: Or check with any async closure, e.g. against a database
+/// - **Strict Parsing**: Credentials that aren't valid Base64 or UTF-8 are rejected
+///
+/// ## Usage Examples
+///
+/// ```rust
+/// use std::collections::HashMap;
+///
+/// use ripress::app::App;
+///
+/// let mut app = App::new();
+///
+/// // Whole app
+/// let users = HashMap::from([("admin".to_string(), "s3cret".to_string())]);
+/// app.use_basic_auth(users, "Internal tools");
+///
+/// // Only under /metrics, with an async check
+/// app.use_basic_auth_on(
+///     Some("/metrics"),
+///     |username: String, password: String| async move {
+///         username == "scraper" && password == "token"
+///     },
+///     "Metrics",
+/// );
+/// ```
+///
+/// ## Security
+///
+/// Basic credentials are only encoded, not encrypted, so serve protected routes over HTTPS.
+pub mod auth;
+
 /// Comprehensive Security Headers (Shield) middleware
 ///
 /// This module provides a comprehensive security middleware that sets multiple HTTP security
@@ -1106,6 +1365,20 @@ pub mod compression;
 /// let _csp = ContentSecurityPolicy { enabled: true, directives, report_only: false };
 /// ```
 ///
+/// ### Building a CSP with Nonces
+/// `{NONCE}` is replaced by a fresh value on every request, readable with `req.csp_nonce()`.
+/// ```rust
+/// use ripress::middlewares::shield::{ContentSecurityPolicy, Csp};
+/// let _csp: ContentSecurityPolicy = Csp::new()
+///     .default_src(["'none'"])
+///     .script_src(["'self'"])
+///     .style_src(["'self'"])
+///     .img_src(["'self'", "data:"])
+///     .report_uri("/csp-reports")
+///     .nonce()
+///     .build();
+/// ```
+///
 /// ## HSTS Configuration Guidelines
 ///
 /// ### Progressive HSTS Rollout
@@ -1129,9 +1402,9 @@ pub mod compression;
 /// let mut app = App::new();
 ///
 /// app.use_shield(Some(ShieldConfig {
-///     cross_origin_embedder_policy: CrossOriginEmbedderPolicy::default(),
-///     cross_origin_opener_policy: CrossOriginOpenerPolicy::default(),
-///     cross_origin_resource_policy: CrossOriginResourcePolicy::default(),
+///     cross_origin_embedder_policy: CrossOriginEmbedderPolicy::RequireCorp,
+///     cross_origin_opener_policy: CrossOriginOpenerPolicy::SameOrigin,
+///     cross_origin_resource_policy: CrossOriginResourcePolicy::SameOrigin,
 ///     ..Default::default()
 /// }));
 /// ```
@@ -1241,7 +1514,7 @@ pub mod compression;
 /// Use tools like security headers.com or Mozilla Observatory to validate your configuration.
 pub mod shield;
 
-use crate::types::MiddlewareHandler;
+use crate::types::{AroundHandler, MiddlewareHandler};
 
 /// Represents a middleware in the Ripress application.
 ///
@@ -1258,8 +1531,9 @@ use crate::types::MiddlewareHandler;
 /// ## Middleware Execution Order
 ///
 /// 1. Pre-middlewares (in registration order)
-/// 2. Route handler
-/// 3. Post-middlewares (in registration order)
+/// 2. Around middlewares, see [`AroundMiddleware`]
+/// 3. Route handler
+/// 4. Post-middlewares (in registration order)
 #[derive(Clone)]
 pub(crate) struct Middleware {
     /// The middleware function.
@@ -1287,3 +1561,18 @@ pub(crate) enum MiddlewareType {
     /// Middleware executed after the route handler.
     Post,
 }
+
+/// A middleware wrapping the route handler, registered with
+/// [`App::use_around`](crate::app::App::use_around).
+///
+/// Around middlewares run after the pre-middlewares and before the
+/// post-middlewares. The first one registered is the outermost: it gets the
+/// request first and the response last.
+#[derive(Clone)]
+pub(crate) struct AroundMiddleware {
+    /// The middleware function, given the request and the rest of the chain.
+    pub func: AroundHandler,
+
+    /// The path prefix this middleware applies to; "/" applies to all routes.
+    pub path: String,
+}
@@ -1,15 +1,20 @@
 #![warn(missing_docs)]
+use crate::app::api_error::error_response;
 use crate::next::Next;
 use crate::{context::HttpResponse, req::HttpRequest, types::MiddlewareOutput};
+use std::hash::{BuildHasher, RandomState};
 use std::sync::Arc;
 use std::time::Duration;
-use std::{collections::HashMap, time::Instant};
+use std::{
+    collections::{HashMap, VecDeque},
+    time::Instant,
+};
 use tokio::sync::Mutex;
-use tokio::time::interval;
+use tokio::time::{interval, MissedTickBehavior};
 
 /// Builtin Rate Limiter Middleware
 ///
-/// This middleware implements a sliding window rate limiter that controls the number
+/// This middleware implements a rate limiter that controls the number
 /// of requests clients can make within a specified time period. It's essential for
 /// protecting APIs from abuse, preventing DoS attacks, and ensuring fair resource
 /// usage across clients. The middleware uses an in-memory store with automatic cleanup
@@ -17,7 +22,7 @@ use tokio::time::interval;
 ///
 /// ## Features
 ///
-/// * **Sliding window algorithm** - More accurate than fixed windows, prevents burst traffic at window boundaries
+/// * **Pluggable strategies** - Fixed window, sliding window log, or token bucket via [`RateLimitStrategy`]
 /// * **Per-client tracking** - Individual rate limits based on client IP addresses
 /// * **Proxy support** - Extracts real client IPs from `X-Forwarded-For` headers
 /// * **Automatic cleanup** - Periodic cleanup of expired entries to prevent memory leaks
@@ -27,17 +32,29 @@ use tokio::time::interval;
 /// * **Memory efficient** - Lightweight tracking structure per client
 /// * **Graceful degradation** - Continues operation even under high load
 ///
-/// ## Rate Limiting Algorithm
+/// ## Rate Limiting Algorithms
 ///
-/// The middleware uses a **sliding window** approach:
+/// The algorithm is selected with [`RateLimiterConfig::strategy`]:
+///
+/// ### Fixed Window (default)
 /// 1. **First request** from a client starts a new window
 /// 2. **Subsequent requests** within the window are counted against the limit
 /// 3. **Window expiry** resets the counter and starts a new window
 /// 4. **Requests over limit** are rejected with 429 status until window resets
 ///
-/// This approach is more accurate than fixed windows because it doesn't allow
-/// burst traffic at window boundaries (e.g., 2x limit by making requests at
-/// the end of one window and start of the next).
+/// Cheap to track, but a client can make up to 2x the limit by bursting at the
+/// end of one window and the start of the next.
+///
+/// ### Sliding Window
+/// Keeps a log of the timestamps of accepted requests and only counts those
+/// made within the last `window_ms`. A request is allowed only if fewer than
+/// `max_requests` were accepted in any trailing window, which removes the
+/// boundary burst of fixed windows at the cost of one timestamp per request.
+///
+/// ### Token Bucket
+/// Each client has a bucket holding up to `max_requests` tokens that refills
+/// continuously at `max_requests` per `window_ms`. Every request consumes one
+/// token, so short bursts are allowed while the long-run rate stays bounded.
 ///
 /// ## Client Identification
 ///
@@ -55,10 +72,13 @@ use tokio::time::interval;
 /// ## Memory Management
 ///
 /// The middleware includes automatic cleanup to prevent memory leaks:
-/// * **Cleanup interval**: Every 5 minutes
-/// * **Cleanup criteria**: Removes entries older than the configured window
-/// * **Background task**: Runs independently without blocking requests
-/// * **Memory bounds**: Automatically limits growth of client tracking data
+/// * **Cleanup interval**: Once per window, clamped between 1 second and 5 minutes
+/// * **Cleanup criteria**: Removes entries that no longer affect the configured strategy
+/// * **Background task**: Sweeps one shard of the client map at a time, so requests
+///   hashed to other shards are never blocked by the sweep
+/// * **Memory bounds**: Entries from clients that stop sending requests are dropped
+///   within roughly two windows
+/// * **Lifetime**: The task stops once the middleware has been dropped
 ///
 /// ## Configuration Options
 ///
@@ -66,13 +86,14 @@ use tokio::time::interval;
 /// * `max_requests` - Maximum requests allowed per client per window (default: 10)
 /// * `proxy` - Whether to extract real IP from proxy headers (default: false)
 /// * `message` - Custom message returned when limit exceeded (default: "Too many requests")
+/// * `strategy` - Rate limiting algorithm (default: [`RateLimitStrategy::FixedWindow`])
 ///
 /// ## Response Headers
 ///
 /// The middleware adds standard rate limiting headers to all responses:
 /// * `X-RateLimit-Limit` - The maximum number of requests allowed in the window
-/// * `X-RateLimit-Remaining` - Number of requests remaining in current window
-/// * `X-RateLimit-Reset` - Seconds until the current window resets
+/// * `X-RateLimit-Remaining` - Number of requests the client can still make right now
+/// * `X-RateLimit-Reset` - Seconds until the client's full quota is available again
 /// * `Retry-After` - Seconds to wait before retrying (only when rate limited)
 ///
 /// These headers help clients implement proper backoff strategies and respect rate limits.
@@ -100,6 +121,7 @@ use tokio::time::interval;
 ///     max_requests: 100,
 ///     proxy: true, // Behind load balancer
 ///     message: "Rate limit exceeded. Please try again later.".to_string(),
+///     ..Default::default()
 /// };
 /// app.use_rate_limiter(Some(config));
 /// ```
@@ -116,6 +138,7 @@ use tokio::time::interval;
 ///     max_requests: 5, // Very restrictive
 ///     proxy: false,
 ///     message: "Too many attempts. Please wait before trying again.".to_string(),
+///     ..Default::default()
 /// };
 /// app.use_rate_limiter(Some(config));
 /// ```
@@ -132,6 +155,7 @@ use tokio::time::interval;
 ///     max_requests: 1000, // Very permissive for development
 ///     proxy: false,
 ///     message: "Development rate limit exceeded".to_string(),
+///     ..Default::default()
 /// };
 /// app.use_rate_limiter(Some(config));
 /// ```
@@ -150,6 +174,7 @@ use tokio::time::interval;
 ///     max_requests: 200,
 ///     proxy: true,
 ///     message: "Too many read requests".to_string(),
+///     ..Default::default()
 /// };
 /// app.use_rate_limiter(Some(read_config));
 ///
@@ -159,10 +184,29 @@ use tokio::time::interval;
 ///     max_requests: 50,
 ///     proxy: true,
 ///     message: "Too many write requests".to_string(),
+///     ..Default::default()
 /// };
 /// app.use_rate_limiter(Some(write_config));
 /// ```
 ///
+/// Smoothing out bursts with a sliding window:
+///
+/// ```no_run
+/// use ripress::{
+///     app::App,
+///     middlewares::rate_limiter::{RateLimitStrategy, RateLimiterConfig},
+/// };
+/// use std::time::Duration;
+///
+/// let mut app = App::new();
+/// app.use_rate_limiter(Some(RateLimiterConfig {
+///     window_ms: Duration::from_secs(60),
+///     max_requests: 100,
+///     strategy: RateLimitStrategy::SlidingWindow,
+///     ..Default::default()
+/// }));
+/// ```
+///
 /// Using default configuration:
 ///
 /// ```no_run
@@ -233,8 +277,8 @@ use tokio::time::interval;
 /// - **Memory bounds**: Automatically managed through cleanup task
 ///
 /// ### CPU Usage
-/// - **Lock contention**: Uses async mutex to minimize blocking
-/// - **Cleanup cost**: Background task runs every 5 minutes
+/// - **Lock contention**: Client map is split into shards, each behind its own async mutex
+/// - **Cleanup cost**: Background task sweeps one shard at a time, once per window
 /// - **Header parsing**: Minimal overhead for proxy IP extraction
 /// - **Time calculations**: Efficient duration-based comparisons
 ///
@@ -389,6 +433,14 @@ pub struct RateLimiterConfig {
     /// - "API rate limit reached. Upgrade your plan for higher limits."
     /// - "Authentication rate limit exceeded. Wait 5 minutes before retry."
     pub message: String,
+
+    /// The algorithm used to decide whether a request is within the limit
+    ///
+    /// Defaults to [`RateLimitStrategy::FixedWindow`]. Choose
+    /// [`RateLimitStrategy::SlidingWindow`] to avoid the double-rate burst at
+    /// window boundaries, or [`RateLimitStrategy::TokenBucket`] to allow short
+    /// bursts while bounding the average rate.
+    pub strategy: RateLimitStrategy,
 }
 
 impl Default for RateLimiterConfig {
@@ -398,27 +450,250 @@ impl Default for RateLimiterConfig {
             window_ms: Duration::from_millis(10_000),
             proxy: false,
             message: String::from("Too many requests"),
+            strategy: RateLimitStrategy::default(),
         }
     }
 }
 
-/// Internal structure for tracking client rate limit data
-///
-/// This lightweight structure stores the minimum information needed
-/// to track each client's request count and window timing. The
-/// `Copy` trait allows efficient updates without ownership issues.
-#[derive(Clone, Copy)]
-struct RateLimiterStruct {
-    /// When this client's current window started
-    window_started: Instant,
-    /// Number of requests made in the current window
-    requests: usize,
+/// Algorithm used by the rate limiter to count requests
+///
+/// All strategies are driven by `max_requests` and `window_ms` from
+/// [`RateLimiterConfig`]; they differ in how requests are attributed to a window.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum RateLimitStrategy {
+    /// Counts requests in consecutive windows that start with a client's first request.
+    /// Allows up to twice the limit across a window boundary.
+    #[default]
+    FixedWindow,
+
+    /// Counts requests made within the trailing `window_ms`, using a log of
+    /// request timestamps. Never allows more than `max_requests` in any window.
+    SlidingWindow,
+
+    /// Refills a bucket of `max_requests` tokens at `max_requests` per `window_ms`;
+    /// each request consumes one token.
+    TokenBucket,
+}
+
+/// Internal per-client rate limit state
+///
+/// Each variant stores the minimum information its strategy needs to
+/// decide whether the next request from a client is allowed.
+#[derive(Clone)]
+pub(crate) enum ClientState {
+    /// Fixed window: when the current window started and how many requests it holds
+    Fixed {
+        window_started: Instant,
+        requests: usize,
+    },
+    /// Sliding window log: timestamps of accepted requests, oldest first
+    Sliding { log: VecDeque<Instant> },
+    /// Token bucket: tokens available as of the last refill
+    Bucket { tokens: f64, last_refill: Instant },
+}
+
+/// Outcome of checking a request against a client's state
+pub(crate) struct Decision {
+    /// Whether the request is within the limit
+    pub(crate) allowed: bool,
+    /// Requests the client may still make right now
+    pub(crate) remaining: usize,
+    /// Time until the full quota is available again, or until the next
+    /// request would be allowed when `allowed` is false
+    pub(crate) reset: Duration,
+}
+
+impl ClientState {
+    pub(crate) fn new(strategy: RateLimitStrategy, now: Instant, max_requests: usize) -> Self {
+        match strategy {
+            RateLimitStrategy::FixedWindow => ClientState::Fixed {
+                window_started: now,
+                requests: 0,
+            },
+            RateLimitStrategy::SlidingWindow => ClientState::Sliding {
+                log: VecDeque::new(),
+            },
+            RateLimitStrategy::TokenBucket => ClientState::Bucket {
+                tokens: max_requests as f64,
+                last_refill: now,
+            },
+        }
+    }
+
+    /// Records a request made at `now` if it is within the limit.
+    pub(crate) fn check(&mut self, now: Instant, cfg: &RateLimiterConfig) -> Decision {
+        let max = cfg.max_requests;
+        let window = cfg.window_ms;
+
+        match self {
+            ClientState::Fixed {
+                window_started,
+                requests,
+            } => {
+                if now.duration_since(*window_started) > window {
+                    *window_started = now;
+                    *requests = 0;
+                }
+
+                let allowed = *requests < max;
+                if allowed {
+                    *requests += 1;
+                }
+
+                Decision {
+                    allowed,
+                    remaining: max.saturating_sub(*requests),
+                    reset: window.saturating_sub(now.duration_since(*window_started)),
+                }
+            }
+            ClientState::Sliding { log } => {
+                while log
+                    .front()
+                    .is_some_and(|t| now.duration_since(*t) >= window)
+                {
+                    log.pop_front();
+                }
+
+                if log.len() < max {
+                    log.push_back(now);
+                    let oldest = log.front().copied().unwrap_or(now);
+                    Decision {
+                        allowed: true,
+                        remaining: max - log.len(),
+                        reset: window.saturating_sub(now.duration_since(oldest)),
+                    }
+                } else {
+                    let reset = log
+                        .front()
+                        .map(|t| window.saturating_sub(now.duration_since(*t)))
+                        .unwrap_or(window);
+                    Decision {
+                        allowed: false,
+                        remaining: 0,
+                        reset,
+                    }
+                }
+            }
+            ClientState::Bucket {
+                tokens,
+                last_refill,
+            } => {
+                let capacity = max as f64;
+                let per_second = if window.is_zero() {
+                    f64::INFINITY
+                } else {
+                    capacity / window.as_secs_f64()
+                };
+
+                let elapsed = now.duration_since(*last_refill).as_secs_f64();
+                *tokens = (*tokens + elapsed * per_second).min(capacity);
+                *last_refill = now;
+
+                let allowed = *tokens >= 1.0;
+                if allowed {
+                    *tokens -= 1.0;
+                }
+
+                let missing = if allowed {
+                    capacity - *tokens
+                } else {
+                    1.0 - *tokens
+                };
+                let reset = if per_second.is_infinite() {
+                    Duration::ZERO
+                } else {
+                    Duration::from_secs_f64((missing / per_second).max(0.0))
+                };
+
+                Decision {
+                    allowed,
+                    remaining: tokens.floor() as usize,
+                    reset,
+                }
+            }
+        }
+    }
+
+    /// Whether this entry no longer affects rate limiting and can be dropped,
+    /// i.e. a fresh entry would behave identically.
+    pub(crate) fn is_expired(&self, now: Instant, cfg: &RateLimiterConfig) -> bool {
+        match self {
+            ClientState::Fixed { window_started, .. } => {
+                now.duration_since(*window_started) > cfg.window_ms
+            }
+            ClientState::Sliding { log } => log
+                .back()
+                .is_none_or(|t| now.duration_since(*t) >= cfg.window_ms),
+            ClientState::Bucket { last_refill, .. } => {
+                now.duration_since(*last_refill) >= cfg.window_ms
+            }
+        }
+    }
+}
+
+/// Number of independently locked shards in a [`ClientStore`]
+const SHARD_COUNT: usize = 16;
+
+/// Client state map split into independently locked shards
+///
+/// Requests only lock the shard their client key hashes to, and the cleanup
+/// sweep locks one shard at a time, yielding in between.
+pub(crate) struct ClientStore {
+    shards: Vec<Mutex<HashMap<String, ClientState>>>,
+    hasher: RandomState,
+}
+
+impl ClientStore {
+    pub(crate) fn new() -> Self {
+        ClientStore {
+            shards: (0..SHARD_COUNT)
+                .map(|_| Mutex::new(HashMap::new()))
+                .collect(),
+            hasher: RandomState::new(),
+        }
+    }
+
+    /// Returns the shard responsible for `key`.
+    pub(crate) fn shard(&self, key: &str) -> &Mutex<HashMap<String, ClientState>> {
+        let index = self.hasher.hash_one(key) as usize % self.shards.len();
+        &self.shards[index]
+    }
+
+    /// Removes expired entries from every shard and returns how many were dropped.
+    pub(crate) async fn sweep(&self, now: Instant, cfg: &RateLimiterConfig) -> usize {
+        let mut removed = 0;
+        for shard in &self.shards {
+            {
+                let mut map = shard.lock().await;
+                let before = map.len();
+                map.retain(|_, v| !v.is_expired(now, cfg));
+                removed += before - map.len();
+            }
+            tokio::task::yield_now().await;
+        }
+        removed
+    }
+
+    /// Total number of tracked clients across all shards.
+    #[cfg(test)]
+    pub(crate) async fn len(&self) -> usize {
+        let mut len = 0;
+        for shard in &self.shards {
+            len += shard.lock().await.len();
+        }
+        len
+    }
+}
+
+/// How often the cleanup task sweeps the client map for a given window.
+pub(crate) fn cleanup_interval(window: Duration) -> Duration {
+    window.clamp(Duration::from_secs(1), Duration::from_secs(300))
 }
 
 /// Creates a rate limiter middleware function
 ///
-/// Returns a middleware function that implements sliding window rate limiting
-/// based on client IP addresses. The middleware automatically handles window
+/// Returns a middleware function that rate limits clients by IP address using
+/// the configured [`RateLimitStrategy`]. The middleware automatically handles window
 /// expiration, client tracking, cleanup, and proper HTTP response codes and headers.
 ///
 /// ## Parameters
@@ -429,7 +704,7 @@ struct RateLimiterStruct {
 /// ## Returns
 ///
 /// A middleware function compatible with the ripress framework that:
-/// * Tracks request counts per client IP address using sliding windows
+/// * Tracks requests per client IP address using the configured strategy
 /// * Automatically rejects requests exceeding configured limits with 429 status
 /// * Adds standard rate limiting headers to all responses
 /// * Supports both direct and proxy-based client IP detection
@@ -453,10 +728,10 @@ struct RateLimiterStruct {
 /// ## Background Tasks
 ///
 /// The middleware spawns a cleanup task that:
-/// * Runs every 5 minutes to remove expired client entries
+/// * Runs once per window (between 1 second and 5 minutes) to remove expired client entries
 /// * Prevents memory leaks from accumulating client data
 /// * Runs independently without affecting request processing
-/// * Uses the same window duration for cleanup timing
+/// * Drops entries once they no longer affect the configured strategy
 ///
 /// ## Thread Safety and Performance
 ///
@@ -464,11 +739,10 @@ struct RateLimiterStruct {
 /// * **Arc sharing**: Efficient sharing of client data across requests
 /// * **Minimal locking**: Lock held only for map operations, not entire request
 /// * **Background cleanup**: Maintains performance under sustained load
-/// * **Copy semantics**: Efficient updates of client tracking data
 ///
 /// ## Memory Management
 ///
-/// * **Automatic cleanup**: Expired entries removed every 5 minutes
+/// * **Automatic cleanup**: Expired entries removed once per window
 /// * **Bounded growth**: Client map size naturally limited by cleanup
 /// * **Lightweight entries**: Minimal memory per tracked client
 /// * **Efficient operations**: Hash map provides O(1) average lookup/update
@@ -478,7 +752,7 @@ struct RateLimiterStruct {
 /// The middleware is designed to be robust and never panic:
 /// * **Missing headers**: Gracefully falls back to direct IP
 /// * **Invalid durations**: Uses saturating arithmetic to prevent underflow
-/// * **Lock contention**: Async mutex prevents deadlocks
+/// * **Lock contention**: Sharded async mutexes keep the sweep from stalling requests
 /// * **Memory pressure**: Background cleanup prevents unbounded growth
 ///
 /// ## Rate Limiting Headers
@@ -491,19 +765,21 @@ struct RateLimiterStruct {
 pub(crate) fn rate_limiter(
     config: Option<RateLimiterConfig>,
 ) -> impl Fn(HttpRequest, HttpResponse, Next) -> MiddlewareOutput + Send + Sync + 'static {
-    let client_map: Arc<Mutex<HashMap<String, RateLimiterStruct>>> =
-        Arc::new(Mutex::new(HashMap::new()));
+    let client_map = Arc::new(ClientStore::new());
     let cfg = config.unwrap_or_default();
 
-    let cleanup_map = client_map.clone();
-    let cleanup_window = cfg.window_ms;
+    let cleanup_map = Arc::downgrade(&client_map);
+    let cleanup_cfg = cfg.clone();
     tokio::spawn(async move {
-        let mut ticker = interval(Duration::from_secs(300));
+        let mut ticker = interval(cleanup_interval(cleanup_cfg.window_ms));
+        ticker.set_missed_tick_behavior(MissedTickBehavior::Delay);
+        ticker.tick().await;
         loop {
             ticker.tick().await;
-            let now = Instant::now();
-            let mut map = cleanup_map.lock().await;
-            map.retain(|_, v| now.duration_since(v.window_started) <= cleanup_window);
+            let Some(store) = cleanup_map.upgrade() else {
+                break;
+            };
+            store.sweep(Instant::now(), &cleanup_cfg).await;
         }
     });
 
@@ -523,58 +799,29 @@ pub(crate) fn rate_limiter(
                 req.ip().to_string()
             };
 
-            let mut map = client_map.lock().await;
+            let decision = {
+                let mut map = client_map.shard(&client_ip).lock().await;
+                map.entry(client_ip)
+                    .or_insert_with(|| ClientState::new(cfg.strategy, now, cfg.max_requests))
+                    .check(now, &cfg)
+            };
 
-            if let Some(client) = map.get_mut(&client_ip) {
-                if now.duration_since(client.window_started) > cfg.window_ms {
-                    *client = RateLimiterStruct {
-                        window_started: now,
-                        requests: 1,
-                    };
-                } else {
-                    if client.requests >= cfg.max_requests {
-                        let remaining_time = cfg
-                            .window_ms
-                            .saturating_sub(now.duration_since(client.window_started))
-                            .as_secs();
-
-                        let limit = cfg.max_requests.to_string();
-                        let retry = remaining_time.to_string();
-                        res = res
-                            .status(429)
-                            .text(cfg.message.clone())
-                            .set_header("X-RateLimit-Limit", &limit)
-                            .set_header("X-RateLimit-Remaining", "0")
-                            .set_header("X-RateLimit-Reset", &retry)
-                            .set_header("Retry-After", &retry);
-                        return (req, Some(res));
-                    } else {
-                        client.requests += 1;
-                    }
-                }
-            } else {
-                map.insert(
-                    client_ip.clone(),
-                    RateLimiterStruct {
-                        window_started: now,
-                        requests: 1,
-                    },
-                );
-            }
+            let limit = cfg.max_requests.to_string();
+            let reset = decision.reset.as_secs().to_string();
 
-            let client_data = map.get(&client_ip).unwrap();
-            let remaining_requests = cfg.max_requests.saturating_sub(client_data.requests);
-            let window_remaining = cfg
-                .window_ms
-                .saturating_sub(now.duration_since(client_data.window_started))
-                .as_secs();
+            if !decision.allowed {
+                res = error_response(res, 429, &cfg.message)
+                    .set_header("X-RateLimit-Limit", &limit)
+                    .set_header("X-RateLimit-Remaining", "0")
+                    .set_header("X-RateLimit-Reset", &reset)
+                    .set_header("Retry-After", &reset);
+                return (req, Some(res));
+            }
 
+            res.headers.insert("X-RateLimit-Limit", limit);
             res.headers
-                .insert("X-RateLimit-Limit", cfg.max_requests.to_string());
-            res.headers
-                .insert("X-RateLimit-Remaining", remaining_requests.to_string());
-            res.headers
-                .insert("X-RateLimit-Reset", window_remaining.to_string());
+                .insert("X-RateLimit-Remaining", decision.remaining.to_string());
+            res.headers.insert("X-RateLimit-Reset", reset);
 
             return next.call(req, res).await;
         })
@@ -0,0 +1,83 @@
+#![warn(missing_docs)]
+use crate::{
+    context::HttpResponse, next::Next, req::HttpRequest, req::REQUEST_ID_KEY,
+    types::MiddlewareOutput,
+};
+use uuid::Uuid;
+
+/// Maximum length of an incoming request ID that will be reused as-is
+const MAX_INCOMING_LEN: usize = 128;
+
+/// Configuration for the request ID middleware
+#[derive(Clone)]
+pub struct RequestIdConfig {
+    /// Header read from the request and echoed on the response
+    ///
+    /// Defaults to `X-Request-Id`.
+    pub header: String,
+    /// Whether to reuse an ID supplied by the client or an upstream proxy
+    ///
+    /// When `false`, a new ID is always generated. Incoming IDs are only reused
+    /// if they are at most 128 visible ASCII characters, so they are safe to log.
+    pub trust_incoming: bool,
+}
+
+impl Default for RequestIdConfig {
+    fn default() -> Self {
+        RequestIdConfig {
+            header: "X-Request-Id".to_string(),
+            trust_incoming: true,
+        }
+    }
+}
+
+/// Creates a request ID middleware that assigns every request a correlation ID
+///
+/// The ID is taken from the configured header when present and valid, or
+/// generated as a random UUID. It is stored in the request data (readable with
+/// [`HttpRequest::request_id`]) and echoed on the response header.
+///
+/// # Arguments
+///
+/// * `config` - Optional request ID configuration. Uses defaults if None.
+///
+/// # Returns
+///
+/// A middleware function that tags requests and responses with an ID
+pub(crate) fn request_id(
+    config: Option<RequestIdConfig>,
+) -> impl Fn(HttpRequest, HttpResponse, Next) -> MiddlewareOutput + Send + Sync + 'static {
+    let config = config.unwrap_or_default();
+    move |mut req: HttpRequest, mut res, next| {
+        let config = config.clone();
+        Box::pin(async move {
+            let incoming = if config.trust_incoming {
+                req.headers
+                    .get(&config.header)
+                    .map(str::trim)
+                    .filter(|id| is_valid_request_id(id))
+                    .map(str::to_string)
+            } else {
+                None
+            };
+
+            let id = incoming.unwrap_or_else(generate_request_id);
+
+            req.set_data(REQUEST_ID_KEY.to_string(), id.clone());
+            res.headers.insert(config.header.as_str(), id);
+
+            next.call(req, res).await
+        })
+    }
+}
+
+/// Generates a new random request ID
+pub(crate) fn generate_request_id() -> String {
+    Uuid::new_v4().to_string()
+}
+
+/// Checks that an incoming ID is non-empty, reasonably short and made only of
+/// visible ASCII characters
+pub(crate) fn is_valid_request_id(id: &str) -> bool {
+    !id.is_empty() && id.len() <= MAX_INCOMING_LEN && id.bytes().all(|b| b.is_ascii_graphic())
+}
@@ -0,0 +1,533 @@
+#![warn(missing_docs)]
+use std::{
+    collections::HashMap,
+    fmt,
+    sync::{Arc, Mutex, MutexGuard},
+    time::{Duration, Instant},
+};
+
+use base64::{engine::general_purpose::URL_SAFE_NO_PAD, Engine};
+use futures::future::BoxFuture;
+use hyper::{
+    header::{HeaderValue, COOKIE, SET_COOKIE},
+    HeaderMap,
+};
+use ring::{
+    hmac,
+    rand::{SecureRandom, SystemRandom},
+};
+use serde::{de::DeserializeOwned, Serialize};
+use serde_json::Value;
+
+use crate::{
+    app::api_error::error_response, context::HttpResponse, next::Next, req::HttpRequest,
+    res::CookieSameSiteOptions, types::MiddlewareOutput,
+};
+
+/// Minimum length of [`SessionConfig::secret`], in bytes
+pub const MIN_SECRET_LEN: usize = 32;
+
+/// Random bytes in a session ID
+const ID_LEN: usize = 32;
+
+/// The values stored in a session, keyed by name.
+pub type SessionData = HashMap<String, Value>;
+
+/// Error returned by a [`SessionStore`].
+pub type SessionError = Box<dyn std::error::Error + Send + Sync>;
+
+/// Storage backend for session data, keyed by session ID.
+///
+/// [`MemoryStore`] is used by default. Implement this trait to keep sessions
+/// in a shared store such as Redis, so they survive restarts and are visible
+/// to every instance of the app.
+///
+/// ## Example
+///
+/// ```
+/// use std::time::Duration;
+///
+/// use futures::future::BoxFuture;
+/// use ripress::middlewares::session::{SessionData, SessionError, SessionStore};
+///
+/// struct RedisStore {
+///     // client: redis::Client,
+/// }
+///
+/// impl SessionStore for RedisStore {
+///     fn load<'a>(
+///         &'a self,
+///         id: &'a str,
+///     ) -> BoxFuture<'a, Result<Option<SessionData>, SessionError>> {
+///         // GET session:{id}, then serde_json::from_str
+///         Box::pin(async move { Ok(None) })
+///     }
+///
+///     fn save<'a>(
+///         &'a self,
+///         id: &'a str,
+///         data: &'a SessionData,
+///         ttl: Duration,
+///     ) -> BoxFuture<'a, Result<(), SessionError>> {
+///         // SET session:{id} serde_json::to_string(data) EX ttl
+///         Box::pin(async move { Ok(()) })
+///     }
+///
+///     fn destroy<'a>(&'a self, id: &'a str) -> BoxFuture<'a, Result<(), SessionError>> {
+///         // DEL session:{id}
+///         Box::pin(async move { Ok(()) })
+///     }
+/// }
+/// ```
+pub trait SessionStore: Send + Sync + 'static {
+    /// Returns the data of a session, or `None` if it doesn't exist or has expired.
+    fn load<'a>(&'a self, id: &'a str) -> BoxFuture<'a, Result<Option<SessionData>, SessionError>>;
+
+    /// Stores the data of a session, replacing what was there, to expire after `ttl`.
+    fn save<'a>(
+        &'a self,
+        id: &'a str,
+        data: &'a SessionData,
+        ttl: Duration,
+    ) -> BoxFuture<'a, Result<(), SessionError>>;
+
+    /// Deletes a session. Deleting a session that doesn't exist is not an error.
+    fn destroy<'a>(&'a self, id: &'a str) -> BoxFuture<'a, Result<(), SessionError>>;
+}
+
+/// Session store that keeps sessions in process memory.
+///
+/// Sessions are lost on restart and aren't shared between instances, so this
+/// store suits development and single-instance deployments. Expired sessions
+/// are dropped when they are next read, or when another session is saved.
+#[derive(Default)]
+pub struct MemoryStore {
+    sessions: Mutex<HashMap<String, (SessionData, Instant)>>,
+}
+
+impl MemoryStore {
+    /// Creates an empty store.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    fn sessions(&self) -> MutexGuard<'_, HashMap<String, (SessionData, Instant)>> {
+        self.sessions.lock().unwrap_or_else(|e| e.into_inner())
+    }
+}
+
+impl SessionStore for MemoryStore {
+    fn load<'a>(&'a self, id: &'a str) -> BoxFuture<'a, Result<Option<SessionData>, SessionError>> {
+        let mut sessions = self.sessions();
+        let data = match sessions.get(id) {
+            Some((_, expires)) if *expires <= Instant::now() => {
+                sessions.remove(id);
+                None
+            }
+            Some((data, _)) => Some(data.clone()),
+            None => None,
+        };
+        Box::pin(futures::future::ready(Ok(data)))
+    }
+
+    fn save<'a>(
+        &'a self,
+        id: &'a str,
+        data: &'a SessionData,
+        ttl: Duration,
+    ) -> BoxFuture<'a, Result<(), SessionError>> {
+        let now = Instant::now();
+        let mut sessions = self.sessions();
+        sessions.retain(|_, (_, expires)| *expires > now);
+        sessions.insert(id.to_string(), (data.clone(), now + ttl));
+        Box::pin(futures::future::ready(Ok(())))
+    }
+
+    fn destroy<'a>(&'a self, id: &'a str) -> BoxFuture<'a, Result<(), SessionError>> {
+        self.sessions().remove(id);
+        Box::pin(futures::future::ready(Ok(())))
+    }
+}
+
+/// Configuration for the session middleware
+///
+/// ## Example
+///
+/// ```
+/// use std::{sync::Arc, time::Duration};
+///
+/// use ripress::middlewares::session::{MemoryStore, SessionConfig};
+///
+/// let config = SessionConfig {
+///     secret: std::env::var("SESSION_SECRET")
+///         .unwrap_or_else(|_| "x".repeat(32))
+///         .into_bytes(),
+///     ttl: Duration::from_secs(60 * 60),
+///     store: Arc::new(MemoryStore::new()),
+///     ..Default::default()
+/// };
+/// ```
+#[derive(Clone)]
+pub struct SessionConfig {
+    /// Key the session-id cookie is signed with, at least [`MIN_SECRET_LEN`] bytes
+    ///
+    /// Defaults to random bytes generated at startup, which invalidates every
+    /// session when the process restarts. Set it explicitly in production, and
+    /// share it between instances.
+    pub secret: Vec<u8>,
+    /// Name of the session-id cookie. Defaults to `ripress.sid`.
+    pub cookie_name: String,
+    /// How long a session lives after it was last changed. Defaults to 24 hours.
+    ///
+    /// Also sent as the cookie's `Max-Age`.
+    pub ttl: Duration,
+    /// Path attribute of the cookie. Defaults to `/`.
+    pub path: String,
+    /// Whether the cookie is only sent over HTTPS. Defaults to `true`.
+    pub secure: bool,
+    /// SameSite attribute of the cookie. Defaults to `Lax`.
+    pub same_site: CookieSameSiteOptions,
+    /// Where session data is kept. Defaults to a [`MemoryStore`].
+    pub store: Arc<dyn SessionStore>,
+}
+
+impl Default for SessionConfig {
+    fn default() -> Self {
+        let mut secret = vec![0; MIN_SECRET_LEN];
+        SystemRandom::new()
+            .fill(&mut secret)
+            .expect("system random number generator unavailable");
+
+        SessionConfig {
+            secret,
+            cookie_name: "ripress.sid".to_string(),
+            ttl: Duration::from_secs(24 * 60 * 60),
+            path: "/".to_string(),
+            secure: true,
+            same_site: CookieSameSiteOptions::Lax,
+            store: Arc::new(MemoryStore::new()),
+        }
+    }
+}
+
+impl fmt::Debug for SessionConfig {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("SessionConfig")
+            .field("secret", &"<redacted>")
+            .field("cookie_name", &self.cookie_name)
+            .field("ttl", &self.ttl)
+            .field("path", &self.path)
+            .field("secure", &self.secure)
+            .field("same_site", &self.same_site)
+            .finish_non_exhaustive()
+    }
+}
+
+/// The session of the current request, returned by [`HttpRequest::session`].
+///
+/// Clones share the same data, so changes made in a handler are seen when the
+/// response is sent, which is when they are saved to the store.
+#[derive(Clone, Debug, Default)]
+pub struct Session {
+    inner: Arc<Mutex<SessionState>>,
+}
+
+#[derive(Debug, Default)]
+struct SessionState {
+    /// Settings of the app the request arrived on; `None` for a detached session
+    sessions: Option<Arc<Sessions>>,
+    /// `None` for a session that hasn't been saved yet
+    id: Option<String>,
+    data: SessionData,
+    /// Whether the data was read from the store for this request
+    loaded: bool,
+    changed: bool,
+    destroyed: bool,
+    /// Whether the request sent a valid session cookie
+    had_cookie: bool,
+    /// IDs to delete from the store when the response is sent
+    stale_ids: Vec<String>,
+}
+
+impl Session {
+    fn state(&self) -> MutexGuard<'_, SessionState> {
+        self.inner.lock().unwrap_or_else(|e| e.into_inner())
+    }
+
+    /// Returns the session ID, or `None` for a new session that hasn't been saved yet.
+    pub fn id(&self) -> Option<String> {
+        self.state().id.clone()
+    }
+
+    /// Returns `true` if the request didn't belong to an existing session.
+    pub fn is_new(&self) -> bool {
+        self.state().id.is_none()
+    }
+
+    /// Returns the value stored under `key`, or `None` if it is missing or
+    /// isn't a `T`.
+    pub fn get<T: DeserializeOwned>(&self, key: &str) -> Option<T> {
+        let state = self.state();
+        T::deserialize(state.data.get(key)?).ok()
+    }
+
+    /// Returns `true` if a value is stored under `key`.
+    pub fn contains(&self, key: &str) -> bool {
+        self.state().data.contains_key(key)
+    }
+
+    /// Stores `value` under `key`, replacing any previous value.
+    ///
+    /// Fails only if `value` can't be represented as JSON, such as a map with
+    /// non-string keys.
+    pub fn set<T: Serialize>(&self, key: &str, value: T) -> Result<(), serde_json::Error> {
+        let value = serde_json::to_value(value)?;
+        let mut state = self.state();
+        state.data.insert(key.to_string(), value);
+        state.changed = true;
+        Ok(())
+    }
+
+    /// Removes the value stored under `key`, returning it.
+    pub fn remove(&self, key: &str) -> Option<Value> {
+        let mut state = self.state();
+        let value = state.data.remove(key);
+        state.changed |= value.is_some();
+        value
+    }
+
+    /// Removes every value, keeping the session ID.
+    pub fn clear(&self) {
+        let mut state = self.state();
+        state.changed |= !state.data.is_empty();
+        state.data.clear();
+    }
+
+    /// Moves the data to a new session ID, deleting the old one.
+    ///
+    /// Call this when the user logs in or their privileges change, so an ID an
+    /// attacker planted in the browser before login can't be used afterwards.
+    pub fn regenerate(&self) {
+        let mut state = self.state();
+        if let Some(old) = state.id.take() {
+            state.stale_ids.push(old);
+        }
+        state.id = Some(generate_id());
+        state.changed = true;
+    }
+
+    /// Deletes the session from the store and clears the cookie, e.g. on logout.
+    pub fn destroy(&self) {
+        let mut state = self.state();
+        if let Some(old) = state.id.take() {
+            state.stale_ids.push(old);
+        }
+        state.data.clear();
+        state.destroyed = true;
+    }
+}
+
+/// Session handling for [`App::use_session`](crate::app::App::use_session).
+///
+/// The session is attached to the request when it arrives, loaded from the
+/// store by a pre-middleware, and saved once the response, including every
+/// post-middleware, is ready.
+pub(crate) struct Sessions {
+    key: hmac::Key,
+    config: SessionConfig,
+}
+
+impl fmt::Debug for Sessions {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("Sessions")
+            .field("config", &self.config)
+            .finish_non_exhaustive()
+    }
+}
+
+impl Sessions {
+    /// # Panics
+    ///
+    /// Panics if the secret is shorter than [`MIN_SECRET_LEN`] bytes.
+    pub(crate) fn new(config: SessionConfig) -> Self {
+        assert!(
+            config.secret.len() >= MIN_SECRET_LEN,
+            "session secret must be at least {} bytes",
+            MIN_SECRET_LEN
+        );
+        Sessions {
+            key: hmac::Key::new(hmac::HMAC_SHA256, &config.secret),
+            config,
+        }
+    }
+
+    /// Creates the session handle for a request from its session-id cookie.
+    ///
+    /// Nothing is read from the store yet; a cookie with a bad signature is
+    /// treated as absent.
+    pub(crate) fn start(self: &Arc<Self>, headers: &HeaderMap) -> Session {
+        let id = headers
+            .get_all(COOKIE)
+            .iter()
+            .filter_map(|value| value.to_str().ok())
+            .flat_map(cookie::Cookie::split_parse)
+            .filter_map(Result::ok)
+            .filter(|cookie| cookie.name() == self.config.cookie_name)
+            .find_map(|cookie| self.unsign(cookie.value()));
+
+        Session {
+            inner: Arc::new(Mutex::new(SessionState {
+                sessions: Some(Arc::clone(self)),
+                had_cookie: id.is_some(),
+                id,
+                ..Default::default()
+            })),
+        }
+    }
+
+    /// Reads the session's data from the store, once per request.
+    ///
+    /// An ID the store doesn't know, e.g. because it expired, is dropped rather
+    /// than reused, so clients can't choose their own session ID.
+    pub(crate) async fn load(&self, session: &Session) -> Result<(), SessionError> {
+        let id = {
+            let mut state = session.state();
+            if state.loaded {
+                return Ok(());
+            }
+            state.loaded = true;
+            match state.id.clone() {
+                Some(id) => id,
+                None => return Ok(()),
+            }
+        };
+
+        let data = match self.config.store.load(&id).await {
+            Ok(data) => data,
+            Err(e) => {
+                session.state().loaded = false;
+                return Err(e);
+            }
+        };
+
+        let mut state = session.state();
+        match data {
+            // A handler may have regenerated the ID in the meantime.
+            Some(data) if state.id.as_deref() == Some(id.as_str()) => state.data = data,
+            Some(_) => {}
+            None => state.id = None,
+        }
+        Ok(())
+    }
+
+    /// Saves or deletes the session as the request left it, and sets or
+    /// clears the cookie on `res`.
+    ///
+    /// Store errors are logged; the response is sent without a new cookie.
+    pub(crate) async fn commit<B>(&self, session: &Session, res: &mut hyper::Response<B>) {
+        let (save, stale_ids, clear_cookie) = {
+            let mut state = session.state();
+            if !state.loaded {
+                return;
+            }
+            let stale_ids = std::mem::take(&mut state.stale_ids);
+            if state.destroyed || (state.changed && state.data.is_empty()) {
+                let ids = stale_ids.into_iter().chain(state.id.take()).collect();
+                (None, ids, state.had_cookie)
+            } else if state.changed {
+                let id = state.id.get_or_insert_with(generate_id).clone();
+                (Some((id, state.data.clone())), stale_ids, false)
+            } else {
+                (None, stale_ids, false)
+            }
+        };
+
+        for id in &stale_ids {
+            if let Err(e) = self.config.store.destroy(id).await {
+                tracing::error!("Error deleting session: {}", e);
+            }
+        }
+
+        if let Some((id, data)) = save {
+            match self.config.store.save(&id, &data, self.config.ttl).await {
+                Ok(()) => self.set_cookie(res, self.sign(&id), false),
+                Err(e) => tracing::error!("Error saving session: {}", e),
+            }
+        } else if clear_cookie {
+            self.set_cookie(res, String::new(), true);
+        }
+    }
+
+    fn set_cookie<B>(&self, res: &mut hyper::Response<B>, value: String, removal: bool) {
+        let mut cookie = cookie::Cookie::build((self.config.cookie_name.clone(), value))
+            .path(self.config.path.clone())
+            .http_only(true)
+            .secure(self.config.secure)
+            .same_site(match self.config.same_site {
+                CookieSameSiteOptions::Strict => cookie::SameSite::Strict,
+                CookieSameSiteOptions::Lax => cookie::SameSite::Lax,
+                CookieSameSiteOptions::None => cookie::SameSite::None,
+            })
+            .max_age(cookie::time::Duration::seconds(
+                i64::try_from(self.config.ttl.as_secs()).unwrap_or(i64::MAX),
+            ))
+            .build();
+        if removal {
+            cookie.make_removal();
+        }
+
+        if let Ok(value) = HeaderValue::from_str(&cookie.to_string()) {
+            res.headers_mut().append(SET_COOKIE, value);
+        }
+    }
+
+    fn sign(&self, id: &str) -> String {
+        let tag = hmac::sign(&self.key, id.as_bytes());
+        format!("{}.{}", id, URL_SAFE_NO_PAD.encode(tag.as_ref()))
+    }
+
+    /// Returns the ID of a signed cookie value, if the signature is valid.
+    fn unsign(&self, value: &str) -> Option<String> {
+        let (id, signature) = value.rsplit_once('.')?;
+        let signature = URL_SAFE_NO_PAD.decode(signature).ok()?;
+        hmac::verify(&self.key, id.as_bytes(), &signature).ok()?;
+        Some(id.to_string())
+    }
+}
+
+/// Generates a random, URL-safe session ID.
+fn generate_id() -> String {
+    let mut bytes = [0; ID_LEN];
+    SystemRandom::new()
+        .fill(&mut bytes)
+        .expect("system random number generator unavailable");
+    URL_SAFE_NO_PAD.encode(bytes)
+}
+
+/// Creates the pre-middleware that loads the request's session from the store
+///
+/// # Returns
+///
+/// A middleware function that answers `500 Internal Server Error` if the
+/// store can't be read
+pub(crate) fn session(
+) -> impl Fn(HttpRequest, HttpResponse, Next) -> MiddlewareOutput + Send + Sync + 'static {
+    move |req: HttpRequest, res, _next| {
+        Box::pin(async move {
+            let Some(session) = req.session.clone() else {
+                return (req, None);
+            };
+            let Some(sessions) = session.state().sessions.clone() else {
+                return (req, None);
+            };
+            match sessions.load(&session).await {
+                Ok(()) => (req, None),
+                Err(e) => {
+                    tracing::error!("Error loading session: {}", e);
+                    let res = error_response(res, 500, "Session store unavailable");
+                    (req, Some(res))
+                }
+            }
+        })
+    }
+}
@@ -501,6 +501,7 @@ impl Default for PermissionsPolicy {
 /// * **SameOrigin** - Isolate from cross-origin documents (most secure, default)
 /// * **SameOriginAllowPopups** - Isolate but allow popups to maintain references
 /// * **UnsafeNone** - No isolation (least secure, legacy compatibility)
+/// * **Disabled** - Omit the header entirely
 ///
 /// ## Compatibility Considerations
 ///
@@ -513,20 +514,17 @@ impl Default for PermissionsPolicy {
 ///
 /// The default is `SameOrigin` for maximum security. Consider `SameOriginAllowPopups`
 /// if your application uses popups for authentication or payments.
-#[derive(Clone)]
+#[derive(Clone, Default)]
 pub enum CrossOriginOpenerPolicy {
     /// Isolate from all cross-origin documents (most secure)
+    #[default]
     SameOrigin,
     /// Isolate but allow popups to maintain references
     SameOriginAllowPopups,
     /// No isolation (legacy compatibility, least secure)
     UnsafeNone,
-}
-
-impl Default for CrossOriginOpenerPolicy {
-    fn default() -> Self {
-        Self::SameOrigin
-    }
+    /// Do not send the Cross-Origin-Opener-Policy header
+    Disabled,
 }
 
 /// Cross-Origin-Resource-Policy configuration
@@ -548,6 +546,7 @@ impl Default for CrossOriginOpenerPolicy {
 /// * **SameOrigin** - Only allow same-origin resource inclusion (most secure, default)
 /// * **SameSite** - Allow same-site resource inclusion (includes subdomains)
 /// * **CrossOrigin** - Allow cross-origin resource inclusion (least secure)
+/// * **Disabled** - Omit the header entirely
 ///
 /// ## Use Cases
 ///
@@ -561,20 +560,17 @@ impl Default for CrossOriginOpenerPolicy {
 /// * **Image/media sharing** - Consider CrossOrigin for publicly shareable media
 /// * **API endpoints** - APIs typically need CrossOrigin policy
 /// * **Widget/embed services** - Embeddable content requires CrossOrigin
-#[derive(Clone)]
+#[derive(Clone, Default)]
 pub enum CrossOriginResourcePolicy {
     /// Only allow same-origin resource inclusion (most secure)
+    #[default]
     SameOrigin,
     /// Allow same-site resource inclusion (includes subdomains)
     SameSite,
     /// Allow cross-origin resource inclusion (required for public resources)
     CrossOrigin,
-}
-
-impl Default for CrossOriginResourcePolicy {
-    fn default() -> Self {
-        Self::SameOrigin
-    }
+    /// Do not send the Cross-Origin-Resource-Policy header
+    Disabled,
 }
 
 /// Content Security Policy configuration
@@ -635,6 +631,16 @@ impl Default for CrossOriginResourcePolicy {
 /// * `directives` - HashMap of directive names to source values
 /// * `report_only` - Whether to use report-only mode for testing (default: false)
 ///
+/// Use the [`Csp`] builder to construct a policy without filling the map by hand.
+///
+/// ## Nonces
+///
+/// Any directive value containing [`CSP_NONCE_PLACEHOLDER`] (for example
+/// `'self' 'nonce-{NONCE}'`) gets a fresh random nonce on every request. The
+/// nonce is available to handlers through
+/// [`HttpRequest::csp_nonce`](crate::req::HttpRequest::csp_nonce) so it can be
+/// added to inline `<script nonce="...">` tags.
+///
 /// ## Testing and Deployment
 ///
 /// 1. **Start with report-only mode** - Set `report_only: true` to test without breaking functionality
@@ -672,6 +678,242 @@ impl Default for ContentSecurityPolicy {
     }
 }
 
+/// Placeholder replaced by a per-request nonce in CSP directive values
+pub const CSP_NONCE_PLACEHOLDER: &str = "{NONCE}";
+
+/// Builder for a [`ContentSecurityPolicy`]
+///
+/// Each directive method takes the list of sources for that directive and
+/// replaces any sources set before. Sources are written exactly as they appear
+/// in the header, so keywords keep their quotes (`"'self'"`, `"'none'"`).
+///
+/// ## Example
+///
+/// ```rust
+/// use ripress::{app::App, middlewares::shield::{Csp, ShieldConfig}};
+///
+/// let mut app = App::new();
+///
+/// let csp = Csp::new()
+///     .default_src(["'self'"])
+///     .script_src(["'self'", "https://cdn.jsdelivr.net"])
+///     .object_src(["'none'"])
+///     .report_uri("/csp-reports")
+///     .nonce();
+///
+/// app.use_shield(Some(ShieldConfig {
+///     content_security_policy: csp.build(),
+///     ..Default::default()
+/// }));
+/// ```
+#[derive(Clone, Debug, Default)]
+pub struct Csp {
+    directives: Vec<(String, Vec<String>)>,
+    report_only: bool,
+    nonce: bool,
+}
+
+impl Csp {
+    /// Creates an empty policy
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Sets the sources of an arbitrary directive, e.g. `"manifest-src"`
+    pub fn directive<I, S>(mut self, name: &str, sources: I) -> Self
+    where
+        I: IntoIterator<Item = S>,
+        S: Into<String>,
+    {
+        let name = name.trim().to_ascii_lowercase();
+        let sources: Vec<String> = sources.into_iter().map(Into::into).collect();
+
+        match self.directives.iter_mut().find(|(n, _)| *n == name) {
+            Some((_, existing)) => *existing = sources,
+            None => self.directives.push((name, sources)),
+        }
+        self
+    }
+
+    /// Sets `default-src`, the fallback for other fetch directives
+    pub fn default_src<I, S>(self, sources: I) -> Self
+    where
+        I: IntoIterator<Item = S>,
+        S: Into<String>,
+    {
+        self.directive("default-src", sources)
+    }
+
+    /// Sets `script-src`
+    pub fn script_src<I, S>(self, sources: I) -> Self
+    where
+        I: IntoIterator<Item = S>,
+        S: Into<String>,
+    {
+        self.directive("script-src", sources)
+    }
+
+    /// Sets `style-src`
+    pub fn style_src<I, S>(self, sources: I) -> Self
+    where
+        I: IntoIterator<Item = S>,
+        S: Into<String>,
+    {
+        self.directive("style-src", sources)
+    }
+
+    /// Sets `img-src`
+    pub fn img_src<I, S>(self, sources: I) -> Self
+    where
+        I: IntoIterator<Item = S>,
+        S: Into<String>,
+    {
+        self.directive("img-src", sources)
+    }
+
+    /// Sets `connect-src`
+    pub fn connect_src<I, S>(self, sources: I) -> Self
+    where
+        I: IntoIterator<Item = S>,
+        S: Into<String>,
+    {
+        self.directive("connect-src", sources)
+    }
+
+    /// Sets `font-src`
+    pub fn font_src<I, S>(self, sources: I) -> Self
+    where
+        I: IntoIterator<Item = S>,
+        S: Into<String>,
+    {
+        self.directive("font-src", sources)
+    }
+
+    /// Sets `object-src`
+    pub fn object_src<I, S>(self, sources: I) -> Self
+    where
+        I: IntoIterator<Item = S>,
+        S: Into<String>,
+    {
+        self.directive("object-src", sources)
+    }
+
+    /// Sets `media-src`
+    pub fn media_src<I, S>(self, sources: I) -> Self
+    where
+        I: IntoIterator<Item = S>,
+        S: Into<String>,
+    {
+        self.directive("media-src", sources)
+    }
+
+    /// Sets `frame-src`
+    pub fn frame_src<I, S>(self, sources: I) -> Self
+    where
+        I: IntoIterator<Item = S>,
+        S: Into<String>,
+    {
+        self.directive("frame-src", sources)
+    }
+
+    /// Sets `worker-src`
+    pub fn worker_src<I, S>(self, sources: I) -> Self
+    where
+        I: IntoIterator<Item = S>,
+        S: Into<String>,
+    {
+        self.directive("worker-src", sources)
+    }
+
+    /// Sets `frame-ancestors`, which controls who may embed the page
+    pub fn frame_ancestors<I, S>(self, sources: I) -> Self
+    where
+        I: IntoIterator<Item = S>,
+        S: Into<String>,
+    {
+        self.directive("frame-ancestors", sources)
+    }
+
+    /// Sets `base-uri`
+    pub fn base_uri<I, S>(self, sources: I) -> Self
+    where
+        I: IntoIterator<Item = S>,
+        S: Into<String>,
+    {
+        self.directive("base-uri", sources)
+    }
+
+    /// Sets `form-action`
+    pub fn form_action<I, S>(self, sources: I) -> Self
+    where
+        I: IntoIterator<Item = S>,
+        S: Into<String>,
+    {
+        self.directive("form-action", sources)
+    }
+
+    /// Sets `report-uri`, where browsers send violation reports
+    pub fn report_uri(self, uri: impl Into<String>) -> Self {
+        self.directive("report-uri", [uri])
+    }
+
+    /// Adds the `upgrade-insecure-requests` directive
+    pub fn upgrade_insecure_requests(self) -> Self {
+        self.directive("upgrade-insecure-requests", Vec::<String>::new())
+    }
+
+    /// Sends the policy as `Content-Security-Policy-Report-Only`
+    pub fn report_only(mut self, report_only: bool) -> Self {
+        self.report_only = report_only;
+        self
+    }
+
+    /// Allows inline scripts carrying a per-request nonce
+    ///
+    /// Adds `'nonce-{NONCE}'` to `script-src` (seeded from `default-src` when
+    /// `script-src` is not set). Read the nonce in handlers with
+    /// [`HttpRequest::csp_nonce`](crate::req::HttpRequest::csp_nonce).
+    pub fn nonce(mut self) -> Self {
+        self.nonce = true;
+        self
+    }
+
+    /// Builds the [`ContentSecurityPolicy`] used by [`ShieldConfig`]
+    pub fn build(self) -> ContentSecurityPolicy {
+        let mut directives: HashMap<String, String> = self
+            .directives
+            .into_iter()
+            .map(|(name, sources)| (name, sources.join(" ")))
+            .collect();
+
+        if self.nonce {
+            let nonce_source = format!("'nonce-{}'", CSP_NONCE_PLACEHOLDER);
+            let fallback = directives.get("default-src").cloned();
+            let script_src = directives
+                .entry("script-src".to_string())
+                .or_insert_with(|| fallback.unwrap_or_default());
+            if !script_src.contains(&nonce_source) {
+                if !script_src.is_empty() {
+                    script_src.push(' ');
+                }
+                script_src.push_str(&nonce_source);
+            }
+        }
+
+        ContentSecurityPolicy {
+            enabled: true,
+            directives,
+            report_only: self.report_only,
+        }
+    }
+}
+
+impl From<Csp> for ContentSecurityPolicy {
+    fn from(csp: Csp) -> Self {
+        csp.build()
+    }
+}
+
 /// Cross-Origin-Embedder-Policy configuration
 ///
 /// The Cross-Origin-Embedder-Policy (COEP) header allows you to prevent a document
@@ -689,7 +931,10 @@ impl Default for ContentSecurityPolicy {
 /// ## Policy Options
 ///
 /// * **RequireCorp** - Require Cross-Origin-Resource-Policy header on all cross-origin resources
-/// * **UnsafeNone** - No requirements (default, maintains compatibility)
+/// * **Credentialless** - Load no-cors cross-origin resources without credentials instead of
+///   requiring CORP
+/// * **UnsafeNone** - No requirements, sent explicitly
+/// * **Disabled** - Omit the header entirely (default)
 ///
 /// ## Compatibility Impact
 ///
@@ -708,21 +953,21 @@ impl Default for ContentSecurityPolicy {
 ///
 /// ## Default Configuration
 ///
-/// Default is `UnsafeNone` to maintain compatibility. Only enable `RequireCorp`
-/// if you need SharedArrayBuffer or high-resolution timing APIs and have ensured
-/// all cross-origin resources have appropriate CORP headers.
-#[derive(Clone)]
+/// Default is `Disabled`, so no header is sent. Only enable `RequireCorp` (or
+/// `Credentialless`) if you need SharedArrayBuffer or high-resolution timing APIs
+/// and have ensured all cross-origin resources have appropriate CORP headers.
+/// Cross-origin isolation also requires `CrossOriginOpenerPolicy::SameOrigin`.
+#[derive(Clone, Default)]
 pub enum CrossOriginEmbedderPolicy {
     /// Require CORP header on all cross-origin resources
     RequireCorp,
+    /// Strip credentials from no-cors cross-origin requests instead of requiring CORP
+    Credentialless,
     /// No requirements (maintains compatibility)
     UnsafeNone,
-}
-
-impl Default for CrossOriginEmbedderPolicy {
-    fn default() -> Self {
-        Self::UnsafeNone
-    }
+    /// Do not send the Cross-Origin-Embedder-Policy header
+    #[default]
+    Disabled,
 }
 
 /// Origin-Agent-Cluster configuration
@@ -1,11 +1,17 @@
 #![warn(missing_docs)]
 
-use crate::{context::HttpResponse, next::Next, req::HttpRequest, types::MiddlewareOutput};
+use crate::{
+    context::HttpResponse,
+    next::Next,
+    req::{HttpRequest, CSP_NONCE_KEY},
+    types::MiddlewareOutput,
+};
 
 pub use crate::middlewares::shield::config::{
     ContentSecurityPolicy, CrossDomainPolicy, CrossOriginEmbedderPolicy, CrossOriginOpenerPolicy,
-    CrossOriginResourcePolicy, DnsPrefetchControl, Frameguard, HidePoweredBy, Hsts, IENoOpen,
+    CrossOriginResourcePolicy, Csp, DnsPrefetchControl, Frameguard, HidePoweredBy, Hsts, IENoOpen,
     NoSniff, OriginAgentCluster, PermissionsPolicy, ReferrerPolicy, ShieldConfig, XssFilter,
+    CSP_NONCE_PLACEHOLDER,
 };
 
 /// Builtin Shield Middleware
@@ -241,7 +247,7 @@ pub mod config;
 /// * **Permissions-Policy** - Restrictive policy disabling camera, microphone, etc.
 /// * **Cross-Origin-Opener-Policy** - same-origin for process isolation
 /// * **Cross-Origin-Resource-Policy** - same-origin for resource protection
-/// * **Cross-Origin-Embedder-Policy** - unsafe-none for compatibility
+/// * **Cross-Origin-Embedder-Policy** - not sent (opt in with `RequireCorp` for cross-origin isolation)
 /// * **Origin-Agent-Cluster** - ?1 for improved isolation
 /// * **X-Permitted-Cross-Domain-Policies** - none to disable legacy policies
 /// * **X-Powered-By** - Header removed to hide server information
@@ -297,11 +303,17 @@ pub(crate) fn shield(
     config: Option<ShieldConfig>,
 ) -> impl Fn(HttpRequest, HttpResponse, Next) -> MiddlewareOutput + Send + Sync + 'static {
     let config = std::sync::Arc::new(config.unwrap_or_default());
-    move |req: HttpRequest, mut res, next| {
+    move |mut req: HttpRequest, mut res, next| {
         let config = std::sync::Arc::clone(&config);
 
         Box::pin(async move {
-            set_content_security_policy(&mut res, &config.content_security_policy);
+            let csp = &config.content_security_policy;
+            let nonce = uses_nonce(csp).then(generate_nonce);
+            if let Some(nonce) = &nonce {
+                req.set_data(CSP_NONCE_KEY.to_string(), nonce.clone());
+            }
+
+            set_content_security_policy(&mut res, csp, nonce.as_deref());
             set_hsts(&mut res, &config.hsts);
             set_frameguard(&mut res, &config.frameguard);
             set_no_sniff(&mut res, &config.no_sniff);
@@ -326,7 +338,12 @@ pub(crate) fn shield(
 ///
 /// Constructs and sets the CSP header from the provided directives map.
 /// Uses Content-Security-Policy-Report-Only header when report_only is true.
-pub(crate) fn set_content_security_policy(res: &mut HttpResponse, csp: &ContentSecurityPolicy) {
+/// `nonce` replaces [`CSP_NONCE_PLACEHOLDER`] in directive values.
+pub(crate) fn set_content_security_policy(
+    res: &mut HttpResponse,
+    csp: &ContentSecurityPolicy,
+    nonce: Option<&str>,
+) {
     if !csp.enabled {
         return;
     }
@@ -341,13 +358,42 @@ pub(crate) fn set_content_security_policy(res: &mut HttpResponse, csp: &ContentS
     entries.sort_by(|a, b| a.0.cmp(b.0));
     let header_value = entries
         .into_iter()
-        .map(|(k, v)| format!("{} {}", k, v))
+        .map(|(k, v)| {
+            let v = v.trim();
+            if v.is_empty() {
+                k.to_string()
+            } else {
+                format!("{} {}", k, v)
+            }
+        })
         .collect::<Vec<_>>()
         .join("; ");
 
+    let header_value = match nonce {
+        Some(nonce) => header_value.replace(CSP_NONCE_PLACEHOLDER, nonce),
+        None => header_value,
+    };
+
     res.headers.insert(header_name, header_value);
 }
 
+/// Returns true if an enabled policy references [`CSP_NONCE_PLACEHOLDER`]
+pub(crate) fn uses_nonce(csp: &ContentSecurityPolicy) -> bool {
+    csp.enabled
+        && csp
+            .directives
+            .values()
+            .any(|value| value.contains(CSP_NONCE_PLACEHOLDER))
+}
+
+/// Generates a 128-bit nonce as 32 hex characters, from the operating
+/// system's random number generator
+pub(crate) fn generate_nonce() -> String {
+    let mut bytes = [0u8; 16];
+    getrandom::fill(&mut bytes).expect("system random number generator unavailable");
+    bytes.iter().map(|byte| format!("{:02x}", byte)).collect()
+}
+
 /// Sets Strict-Transport-Security header based on HSTS configuration
 ///
 /// Constructs HSTS header with max-age, includeSubDomains, and preload directives
@@ -498,8 +544,10 @@ pub(crate) fn set_permissions_policy(
     }
 
     let mut policies = Vec::new();
+    let mut features: Vec<_> = permissions_policy.features.iter().collect();
+    features.sort_by(|a, b| a.0.cmp(b.0));
 
-    for (feature, allowlist) in &permissions_policy.features {
+    for (feature, allowlist) in features {
         let policy_str = if allowlist.is_empty() {
             format!("{}=()", feature)
         } else {
@@ -528,6 +576,7 @@ pub(crate) fn set_permissions_policy(
 ///
 /// Maps the enum variant to the appropriate header value string.
 /// This header controls cross-origin window references and process isolation.
+/// The `Disabled` variant omits the header.
 pub(crate) fn set_cross_origin_opener_policy(
     res: &mut HttpResponse,
     cross_origin_opener_policy: &CrossOriginOpenerPolicy,
@@ -536,6 +585,7 @@ pub(crate) fn set_cross_origin_opener_policy(
         CrossOriginOpenerPolicy::SameOrigin => "same-origin",
         CrossOriginOpenerPolicy::SameOriginAllowPopups => "same-origin-allow-popups",
         CrossOriginOpenerPolicy::UnsafeNone => "unsafe-none",
+        CrossOriginOpenerPolicy::Disabled => return,
     };
 
     res.headers
@@ -546,6 +596,7 @@ pub(crate) fn set_cross_origin_opener_policy(
 ///
 /// Maps the enum variant to the appropriate header value string.
 /// This header controls cross-origin resource embedding permissions.
+/// The `Disabled` variant omits the header.
 pub(crate) fn set_cross_origin_resource_policy(
     res: &mut HttpResponse,
     cross_origin_resource_policy: &CrossOriginResourcePolicy,
@@ -554,6 +605,7 @@ pub(crate) fn set_cross_origin_resource_policy(
         CrossOriginResourcePolicy::SameOrigin => "same-origin",
         CrossOriginResourcePolicy::SameSite => "same-site",
         CrossOriginResourcePolicy::CrossOrigin => "cross-origin",
+        CrossOriginResourcePolicy::Disabled => return,
     };
 
     res.headers
@@ -564,13 +616,16 @@ pub(crate) fn set_cross_origin_resource_policy(
 ///
 /// Maps the enum variant to the appropriate header value string.
 /// This header controls cross-origin resource requirements for embedder isolation.
+/// The `Disabled` variant omits the header.
 pub(crate) fn set_cross_origin_embedder_policy(
     res: &mut HttpResponse,
     cross_origin_embedder_policy: &CrossOriginEmbedderPolicy,
 ) {
     let header_value = match cross_origin_embedder_policy {
         CrossOriginEmbedderPolicy::RequireCorp => "require-corp",
+        CrossOriginEmbedderPolicy::Credentialless => "credentialless",
         CrossOriginEmbedderPolicy::UnsafeNone => "unsafe-none",
+        CrossOriginEmbedderPolicy::Disabled => return,
     };
 
     res.headers
@@ -0,0 +1,66 @@
+use std::{
+    pin::Pin,
+    sync::{Arc, Mutex},
+};
+
+use bytes::Bytes;
+use futures::Stream;
+use http_body_util::BodyExt;
+use hyper::body::Incoming;
+
+/// The body chunks read from the connection.
+type BodyChunks = Pin<Box<dyn Stream<Item = Result<Bytes, std::io::Error>> + Send + 'static>>;
+
+/// A request extension holding the unread body of a route marked with
+/// [`RouteHandle::stream_body`](crate::router::route_handle::RouteHandle::stream_body).
+///
+/// Clones share the body, so it can be taken once by whichever copy of the
+/// request calls [`HttpRequest::body_stream`](crate::req::HttpRequest::body_stream) first.
+#[derive(Clone, Debug)]
+pub(crate) struct IncomingBody(Arc<Mutex<Option<Incoming>>>);
+
+impl IncomingBody {
+    pub(crate) fn new(body: Incoming) -> Self {
+        IncomingBody(Arc::new(Mutex::new(Some(body))))
+    }
+
+    /// Takes the body as a stream of its data chunks, unless it was taken already.
+    ///
+    /// The stream ends with an error once more than `limit` bytes were read, or
+    /// when reading from the connection fails.
+    pub(crate) fn take(&self, limit: Option<usize>) -> Option<BodyChunks> {
+        let body = self.0.lock().unwrap().take()?;
+
+        Some(Box::pin(futures::stream::unfold(
+            Some((body, 0)),
+            move |state| async move {
+                let (mut body, received) = state?;
+                loop {
+                    let data = match body.frame().await? {
+                        Ok(frame) => match frame.into_data() {
+                            Ok(data) => data,
+                            // Trailers carry no body bytes.
+                            Err(_) => continue,
+                        },
+                        Err(e) => return Some((Err(std::io::Error::other(e)), None)),
+                    };
+                    let received = received + data.len();
+                    if let Some(limit) = limit.filter(|limit| received > *limit) {
+                        let message = format!(
+                            "Request body exceeded the configured limit of {} bytes",
+                            limit
+                        );
+                        return Some((
+                            Err(std::io::Error::new(
+                                std::io::ErrorKind::InvalidData,
+                                message,
+                            )),
+                            None,
+                        ));
+                    }
+                    return Some((Ok(data), Some((body, received))));
+                }
+            },
+        )))
+    }
+}
@@ -32,8 +32,17 @@ impl<T: FromJson> FromRequest for JsonBody<T> {
     type Error = String;
 
     fn from_request(req: &crate::req::HttpRequest) -> Result<Self, Self::Error> {
-        let body = &req.body;
-        Ok(Self(T::from_json(body)?))
+        let body = &*req.body;
+        T::from_json(body)
+            .map(Self)
+            .map_err(|e| req.settings.json_error_detail.message(e))
+    }
+}
+
+impl<T> JsonBody<T> {
+    /// Returns the deserialized body.
+    pub fn into_inner(self) -> T {
+        self.0
     }
 }
 
@@ -84,10 +93,10 @@ impl<T: FromJson + Validate + for<'a> Deserialize<'a>> FromRequest for JsonBodyV
     type Error = String;
 
     fn from_request(req: &crate::req::HttpRequest) -> Result<Self, Self::Error> {
-        let body = &req.body;
+        let body = &*req.body;
         if let RequestBody::JSON(data) = body {
-            let parsed: T =
-                serde_json::from_value::<T>(data.to_owned()).map_err(|e| e.to_string())?;
+            let parsed: T = T::deserialize(data)
+                .map_err(|e| req.settings.json_error_detail.message(e.to_string()))?;
             parsed.validate().map_err(|err| err.to_string())?;
             return Ok(Self(parsed));
         } else {
@@ -0,0 +1,198 @@
+//! Item-by-item parsing of newline-delimited JSON and top-level JSON arrays.
+//!
+//! See [`HttpRequest::json_lines`](crate::req::HttpRequest::json_lines) and
+//! [`HttpRequest::json_items`](crate::req::HttpRequest::json_items).
+
+use std::{fmt, marker::PhantomData};
+
+use bytes::Bytes;
+use serde::de::DeserializeOwned;
+
+/// A record that could not be parsed, with the 1-based line it starts on.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct JsonLineError {
+    /// Line of the body the record starts on, counting from 1
+    pub line: usize,
+    /// What went wrong
+    pub message: String,
+}
+
+impl fmt::Display for JsonLineError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "line {}: {}", self.line, self.message)
+    }
+}
+
+impl std::error::Error for JsonLineError {}
+
+/// Iterator over the records of a newline-delimited JSON (NDJSON) body.
+///
+/// Each line is parsed only when the iterator reaches it. Blank lines are
+/// skipped, and a malformed line yields an error without ending iteration.
+pub struct JsonLines<T> {
+    body: Bytes,
+    pos: usize,
+    line: usize,
+    _item: PhantomData<fn() -> T>,
+}
+
+impl<T> JsonLines<T> {
+    pub(crate) fn new(body: Bytes) -> Self {
+        JsonLines {
+            body,
+            pos: 0,
+            line: 0,
+            _item: PhantomData,
+        }
+    }
+}
+
+impl<T: DeserializeOwned> Iterator for JsonLines<T> {
+    type Item = Result<T, JsonLineError>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        while self.pos < self.body.len() {
+            let rest = &self.body[self.pos..];
+            let end = rest.iter().position(|&b| b == b'\n').unwrap_or(rest.len());
+            let record = rest[..end].trim_ascii();
+            self.pos += end + 1;
+            self.line += 1;
+
+            if record.is_empty() {
+                continue;
+            }
+            return Some(serde_json::from_slice(record).map_err(|e| JsonLineError {
+                line: self.line,
+                message: describe(&e),
+            }));
+        }
+        None
+    }
+}
+
+/// Iterator over the elements of a top-level JSON array body.
+///
+/// Elements are deserialized one at a time, so only the current element is
+/// held as `T`. Iteration ends after the first error, since the position of
+/// the next element can't be known once the array is malformed.
+pub struct JsonItems<T> {
+    body: Bytes,
+    pos: usize,
+    /// Line of `pos`, and the offset up to which newlines were counted
+    line: usize,
+    counted: usize,
+    state: ArrayState,
+    _item: PhantomData<fn() -> T>,
+}
+
+#[derive(Clone, Copy, PartialEq, Eq)]
+enum ArrayState {
+    Start,
+    Next,
+    Done,
+}
+
+impl<T> JsonItems<T> {
+    pub(crate) fn new(body: Bytes) -> Self {
+        JsonItems {
+            body,
+            pos: 0,
+            line: 1,
+            counted: 0,
+            state: ArrayState::Start,
+            _item: PhantomData,
+        }
+    }
+
+    fn skip_whitespace(&mut self) {
+        while self
+            .body
+            .get(self.pos)
+            .is_some_and(|b| b.is_ascii_whitespace())
+        {
+            self.pos += 1;
+        }
+    }
+
+    fn current_line(&mut self) -> usize {
+        let end = self.pos.min(self.body.len());
+        self.line += self.body[self.counted..end]
+            .iter()
+            .filter(|&&b| b == b'\n')
+            .count();
+        self.counted = end;
+        self.line
+    }
+
+    fn fail(&mut self, message: impl Into<String>) -> Option<Result<T, JsonLineError>> {
+        self.state = ArrayState::Done;
+        Some(Err(JsonLineError {
+            line: self.current_line(),
+            message: message.into(),
+        }))
+    }
+}
+
+impl<T: DeserializeOwned> Iterator for JsonItems<T> {
+    type Item = Result<T, JsonLineError>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        self.skip_whitespace();
+        match self.state {
+            ArrayState::Done => return None,
+            ArrayState::Start => {
+                if self.body.get(self.pos) != Some(&b'[') {
+                    return self.fail("expected a JSON array");
+                }
+                self.pos += 1;
+                self.skip_whitespace();
+                if self.body.get(self.pos) == Some(&b']') {
+                    self.state = ArrayState::Done;
+                    return None;
+                }
+            }
+            ArrayState::Next => match self.body.get(self.pos) {
+                Some(b',') => {
+                    self.pos += 1;
+                    self.skip_whitespace();
+                }
+                Some(b']') => {
+                    self.state = ArrayState::Done;
+                    return None;
+                }
+                Some(_) => return self.fail("expected `,` or `]` after array element"),
+                None => return self.fail("unexpected end of array"),
+            },
+        }
+
+        let line = self.current_line();
+        let mut stream =
+            serde_json::Deserializer::from_slice(&self.body[self.pos..]).into_iter::<T>();
+        match stream.next() {
+            Some(Ok(item)) => {
+                self.pos += stream.byte_offset();
+                self.state = ArrayState::Next;
+                Some(Ok(item))
+            }
+            Some(Err(e)) => {
+                self.state = ArrayState::Done;
+                Some(Err(JsonLineError {
+                    line: line + e.line().saturating_sub(1),
+                    message: describe(&e),
+                }))
+            }
+            None => self.fail("unexpected end of array"),
+        }
+    }
+}
+
+/// The error message without serde_json's position, which is relative to the
+/// record rather than the body.
+fn describe(e: &serde_json::Error) -> String {
+    let message = e.to_string();
+    let position = format!(" at line {} column {}", e.line(), e.column());
+    match message.strip_suffix(&position) {
+        Some(message) => message.to_string(),
+        None => message,
+    }
+}
@@ -0,0 +1,168 @@
+use std::{
+    fmt,
+    ops::{Deref, DerefMut},
+    sync::OnceLock,
+};
+
+use bytes::Bytes;
+use serde_json::Value;
+
+use crate::{
+    helpers::{content_type_charset, determine_content_type_request},
+    req::body::{FormData, RequestBody, RequestBodyType, TextData},
+};
+
+/// A request body that is parsed the first time it is read.
+///
+/// The bytes read from the connection are kept as they are until a handler or
+/// middleware asks for the parsed body, so routes that never look at the body
+/// never pay for parsing it. Dereferences to the parsed [`RequestBody`].
+#[derive(Clone)]
+pub(crate) struct LazyBody {
+    /// The bytes and `Content-Type` to parse from, `None` for bodies built in-process
+    source: Option<(Bytes, Option<String>)>,
+    parsed: OnceLock<ParsedBody>,
+}
+
+#[derive(Clone)]
+struct ParsedBody {
+    body: RequestBody,
+    /// Why the body could not be parsed as the kind its `Content-Type` declares
+    error: Option<String>,
+}
+
+impl LazyBody {
+    /// A body that will be parsed from `bytes` according to `content_type`.
+    pub(crate) fn pending(bytes: Bytes, content_type: Option<String>) -> Self {
+        LazyBody {
+            source: Some((bytes, content_type)),
+            parsed: OnceLock::new(),
+        }
+    }
+
+    fn parsed(&self) -> &ParsedBody {
+        self.parsed.get_or_init(|| match &self.source {
+            Some((bytes, content_type)) => parse(bytes, content_type.as_deref()),
+            None => ParsedBody {
+                body: RequestBody::EMPTY,
+                error: None,
+            },
+        })
+    }
+
+    /// Why a urlencoded body could not be decoded, parsing the body if needed.
+    pub(crate) fn error(&self) -> Option<&str> {
+        self.parsed().error.as_deref()
+    }
+
+    /// The kind of body, from the `Content-Type` header while it is unparsed.
+    ///
+    /// A `text/*` body that turns out not to be valid text is still `TEXT` here,
+    /// though it parses as `BINARY`.
+    pub(crate) fn declared_type(&self) -> RequestBodyType {
+        match (self.parsed.get(), &self.source) {
+            (None, Some((_, content_type))) => match content_type
+                .as_deref()
+                .map(determine_content_type_request)
+                .unwrap_or(RequestBodyType::EMPTY)
+            {
+                RequestBodyType::XML => RequestBodyType::TEXT,
+                RequestBodyType::MSGPACK => RequestBodyType::BINARY,
+                body_type => body_type,
+            },
+            _ => self.parsed().body.body_type(),
+        }
+    }
+
+    /// Size of the body in bytes, without parsing it.
+    pub(crate) fn len(&self) -> usize {
+        match (self.parsed.get(), &self.source) {
+            (None, Some((bytes, _))) => bytes.len(),
+            _ => self.parsed().body.len(),
+        }
+    }
+}
+
+impl From<RequestBody> for LazyBody {
+    fn from(body: RequestBody) -> Self {
+        LazyBody {
+            source: None,
+            parsed: OnceLock::from(ParsedBody { body, error: None }),
+        }
+    }
+}
+
+impl Deref for LazyBody {
+    type Target = RequestBody;
+
+    fn deref(&self) -> &RequestBody {
+        &self.parsed().body
+    }
+}
+
+impl DerefMut for LazyBody {
+    /// Parses the body if needed. A body that is about to be modified no longer
+    /// reports the error from parsing it.
+    fn deref_mut(&mut self) -> &mut RequestBody {
+        self.parsed();
+        let parsed = self.parsed.get_mut().expect("body was just parsed");
+        parsed.error = None;
+        &mut parsed.body
+    }
+}
+
+impl fmt::Debug for LazyBody {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match (self.parsed.get(), &self.source) {
+            (Some(parsed), _) => fmt::Debug::fmt(&parsed.body, f),
+            (None, Some((bytes, _))) => write!(f, "<unparsed {} bytes>", bytes.len()),
+            (None, None) => fmt::Debug::fmt(&RequestBody::EMPTY, f),
+        }
+    }
+}
+
+fn parse(bytes: &Bytes, content_type: Option<&str>) -> ParsedBody {
+    let body_type = content_type
+        .map(determine_content_type_request)
+        .unwrap_or(RequestBodyType::EMPTY);
+
+    let mut error = None;
+    let body = match body_type {
+        RequestBodyType::FORM => match std::str::from_utf8(bytes)
+            .map_err(|e| e.to_string())
+            .and_then(FormData::from_query_string)
+        {
+            Ok(fd) => RequestBody::new_form(fd),
+            Err(e) => {
+                tracing::debug!("Invalid form body: {}", e);
+                error = Some(format!("Invalid form body: {}", e));
+                RequestBody::new_form(FormData::new())
+            }
+        },
+        RequestBodyType::JSON => {
+            let body_json = match serde_json::from_slice::<Value>(bytes) {
+                Ok(json) => json,
+                Err(e) => {
+                    tracing::debug!("Invalid JSON body, defaulting to null: {}", e);
+                    Value::Null
+                }
+            };
+            RequestBody::new_json(body_json)
+        }
+        RequestBodyType::TEXT | RequestBodyType::XML => {
+            let charset = content_type.and_then(content_type_charset);
+            match TextData::from_bytes_with_charset(bytes.to_vec(), charset.as_deref()) {
+                Ok(text) => RequestBody::new_text(text),
+                Err(_) => RequestBody::new_binary(bytes.clone()),
+            }
+        }
+        // Multipart bodies are parsed up front by `from_hyper_request`, so their
+        // limits apply before the handler runs.
+        RequestBodyType::BINARY | RequestBodyType::MSGPACK | RequestBodyType::MultipartForm => {
+            RequestBody::new_binary(bytes.clone())
+        }
+        RequestBodyType::EMPTY => RequestBody::EMPTY,
+    };
+
+    ParsedBody { body, error }
+}
@@ -54,6 +54,17 @@ pub enum RequestBodyType {
     EMPTY,
     /// Multipart form data with `multipart/form-data` content type
     MultipartForm,
+    /// XML content with `application/xml`, `text/xml` or a `+xml` content type
+    ///
+    /// The body is kept as text, so [`HttpRequest::text`](crate::req::HttpRequest::text)
+    /// reads it too.
+    XML,
+    /// MessagePack data with `application/msgpack` or `application/x-msgpack`
+    /// content type
+    ///
+    /// The body is kept as binary, so [`HttpRequest::bytes`](crate::req::HttpRequest::bytes)
+    /// reads it too.
+    MSGPACK,
 }
 
 impl Display for RequestBodyType {
@@ -65,6 +76,8 @@ impl Display for RequestBodyType {
             RequestBodyType::BINARY => write!(f, "application/octet-stream"),
             RequestBodyType::EMPTY => write!(f, ""),
             RequestBodyType::MultipartForm => write!(f, "multipart/form-data"),
+            RequestBodyType::XML => write!(f, "application/xml"),
+            RequestBodyType::MSGPACK => write!(f, "application/msgpack"),
         }
     }
 }
@@ -108,6 +121,23 @@ impl RequestBody {
             RequestBody::EMPTY => RequestBodyType::EMPTY,
         }
     }
+
+    /// Returns the body as raw bytes.
+    ///
+    /// Binary variants share their underlying buffer; the other variants are
+    /// serialized back into their wire format.
+    pub(crate) fn to_bytes(&self) -> Bytes {
+        match self {
+            RequestBody::TEXT(text) => Bytes::from(text.as_bytes().to_vec()),
+            RequestBody::JSON(json) => serde_json::to_vec(json)
+                .map(Bytes::from)
+                .unwrap_or_default(),
+            RequestBody::FORM(form_data) => Bytes::from(form_data.to_query_string()),
+            RequestBody::BINARY(bytes) => bytes.clone(),
+            RequestBody::BinaryWithFields(bytes, _form_data) => bytes.clone(),
+            RequestBody::EMPTY => Bytes::new(),
+        }
+    }
 }
 
 /// Module containing form data structures and utilities.
@@ -116,7 +146,15 @@ impl RequestBody {
 /// and URL-encoded data, with support for parsing and generating query strings.
 pub mod form_data;
 
+pub(crate) mod incoming;
 pub mod json_data;
+/// Module for parsing NDJSON and JSON array bodies one record at a time.
+///
+/// This module provides the [`JsonLines`] and [`JsonItems`] iterators returned by
+/// [`HttpRequest::json_lines`](crate::req::HttpRequest::json_lines) and
+/// [`HttpRequest::json_items`](crate::req::HttpRequest::json_items).
+pub mod json_lines;
+pub(crate) mod lazy;
 /// Module containing text data structures and utilities.
 ///
 /// This module provides the [`TextData`] type for handling plain text content
@@ -127,6 +165,7 @@ use std::fmt::Display;
 
 use bytes::Bytes;
 pub use form_data::FormData;
+pub use json_lines::{JsonItems, JsonLineError, JsonLines};
 pub use text_data::TextData;
 
 impl RequestBody {
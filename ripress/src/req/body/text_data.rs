@@ -25,6 +25,9 @@ pub(crate) enum TextDataError {
         /// How much was the limit set to
         limit: usize,
     },
+
+    /// The bytes are not valid in the charset they were declared with.
+    InvalidEncoding(String),
 }
 
 impl Display for TextDataError {
@@ -34,6 +37,7 @@ impl Display for TextDataError {
             TextDataError::TooLarge { size, limit } => {
                 write!(f, "Text too large: {} bytes (limit: {} bytes)", size, limit)
             }
+            TextDataError::InvalidEncoding(charset) => write!(f, "Invalid {} text", charset),
         }
     }
 }
@@ -183,6 +187,47 @@ impl TextData {
         Self::from_bytes(bytes)
     }
 
+    /// Creates `TextData` by decoding bytes in the given charset.
+    ///
+    /// The charset is the `charset` parameter of a `Content-Type` header, such as
+    /// `iso-8859-1` or `shift_jis`. The bytes are decoded into UTF-8, so the result
+    /// always reports a charset of "utf-8". A missing or unrecognised charset is
+    /// treated as UTF-8.
+    ///
+    /// # Returns
+    ///
+    /// * `Ok(TextData)` if the bytes decode cleanly
+    /// * `Err(TextDataError::InvalidEncoding)` if the bytes are malformed for a known charset
+    /// * `Err(TextDataError::InvalidUtf8)` if the charset is UTF-8 or unknown and the bytes are not valid UTF-8
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// use ripress::req::body::text_data::TextData;
+    ///
+    /// let text = TextData::from_bytes_with_charset(vec![0x63, 0x61, 0x66, 0xE9], Some("iso-8859-1")).unwrap();
+    /// assert_eq!(text.as_str().unwrap(), "café");
+    ///
+    /// let text = TextData::from_bytes_with_charset(b"plain".to_vec(), Some("x-unknown")).unwrap();
+    /// assert_eq!(text.as_str().unwrap(), "plain");
+    /// ```
+    pub fn from_bytes_with_charset(
+        bytes: Vec<u8>,
+        charset: Option<&str>,
+    ) -> Result<Self, RipressError> {
+        let encoding = match charset.and_then(|c| encoding_rs::Encoding::for_label(c.as_bytes())) {
+            Some(encoding) if encoding != encoding_rs::UTF_8 => encoding,
+            _ => return Self::from_bytes(bytes),
+        };
+
+        match encoding.decode_without_bom_handling_and_without_replacement(&bytes) {
+            Some(text) => Ok(Self::new(text.into_owned())),
+            None => Err(RipressError::from(TextDataError::InvalidEncoding(
+                encoding.name().to_string(),
+            ))),
+        }
+    }
+
     /// Creates `TextData` from raw bytes without UTF-8 validation.
     ///
     /// This method is useful when you need to store potentially invalid UTF-8 data
@@ -1,70 +1,66 @@
-use ahash::AHashMap;
 use bytes::Bytes;
 use http_body_util::{BodyExt, Full};
-use hyper::{header::HOST, Request};
+use hyper::{header::HOST, HeaderMap, Request, Uri};
 use routerify_ng::RequestInfo;
-use serde_json::Value;
 
 use crate::{
-    app::api_error::ApiError,
+    app::{
+        api_error::ApiError,
+        connection::{ReceivedAt, Scheme},
+        settings::RequestSettings,
+        state::{RequestExtensions, TypeMap},
+    },
     helpers::{
         determine_content_type_request, extract_boundary, get_all_query, parse_multipart_form,
     },
     req::{
-        body::{FormData, RequestBody, RequestBodyType, TextData},
-        origin_url::Url,
+        body::{incoming::IncomingBody, lazy::LazyBody, FormData, RequestBody, RequestBodyType},
+        forwarded,
+        origin_url::{strip_port, Url},
         query_params::QueryParams,
         request_data::RequestData,
         request_headers::RequestHeaders,
         route_params::RouteParams,
+        uploaded_file::UploadedFile,
         HttpRequest,
     },
+    res::HttpResponse,
     types::HttpMethods,
 };
 
+#[cfg(feature = "session")]
+use crate::middlewares::session::Session;
+
 impl HttpRequest {
     #[doc(hidden)]
     pub async fn from_hyper_request(req: &mut Request<Full<Bytes>>) -> Result<Self, ApiError> {
-        let origin_url = match req.uri().authority() {
-            Some(authority) => {
-                let scheme = req.uri().scheme_str().unwrap_or("http");
-                Url::new(format!("{}://{}", scheme, authority))
-            }
-            None => {
-                let uri_string = req
-                    .headers()
-                    .get(HOST)
-                    .and_then(|host| host.to_str().ok())
-                    .map(|host| {
-                        let scheme = "http";
-                        format!("{}://{}", scheme, host)
-                    })
-                    .unwrap_or(String::new());
-
-                Url::new(uri_string)
-            }
-        };
+        let settings = req
+            .extensions()
+            .get::<RequestSettings>()
+            .copied()
+            .unwrap_or_default();
+        let protocol = resolve_protocol(
+            req.extensions().get::<Scheme>(),
+            req.headers(),
+            settings.trusted_proxy,
+        );
+        let origin_url = resolve_origin(&protocol, req.uri(), req.headers(), settings.trusted_host);
 
         let query_string = req.uri().query().unwrap_or("");
         let queries = url::form_urlencoded::parse(query_string.as_bytes())
             .filter_map(|(key, value)| Some((key.to_string(), value.to_string())));
-        let query = QueryParams::from_iterator(queries);
+        let mut query = QueryParams::from_iterator(queries);
+        query.raw = query_string.to_string();
 
         let method = HttpMethods::from(req.method());
         let path = req.uri().path().to_string();
 
-        let cookie_str_opt = req
+        let cookies_map = req
             .headers()
             .get(hyper::header::COOKIE)
             .and_then(|v| v.to_str().ok())
-            .map(|s| s.to_string());
-
-        let x_forwarded_proto_str = req
-            .headers()
-            .get("x-forwarded-proto")
-            .and_then(|v| v.to_str().ok())
-            .unwrap_or("http")
-            .to_string();
+            .map(Self::cookies_from_header)
+            .unwrap_or_default();
 
         let content_type_str_opt = req
             .headers()
@@ -74,53 +70,37 @@ impl HttpRequest {
 
         let headers = RequestHeaders::from_header_map(std::mem::take(req.headers_mut()));
 
-        let mut cookies_map = AHashMap::new();
-        if let Some(cookie_str) = &cookie_str_opt {
-            for cookie_part in cookie_str.split(';') {
-                let trimmed = cookie_part.trim();
-                if let Some(equal_pos) = trimmed.find('=') {
-                    let name = &trimmed[..equal_pos];
-                    let value = &trimmed[equal_pos + 1..];
-                    if !name.is_empty() {
-                        cookies_map.insert(name.to_string(), value.to_string());
-                    }
-                }
-            }
-        }
-
         let mut data = RequestData::new();
         if let Some(ext_data) = req.extensions().get::<RequestData>() {
             data = ext_data.clone();
         }
+        let files = req
+            .extensions()
+            .get::<Vec<UploadedFile>>()
+            .cloned()
+            .unwrap_or_default();
 
         let content_type = content_type_str_opt
             .as_deref()
             .map(determine_content_type_request)
             .unwrap_or(RequestBodyType::EMPTY);
 
+        // Kept alongside the parsed body; binary bodies share this buffer.
+        let body_bytes = req.body_mut().collect().await?.to_bytes();
+
+        // Only multipart bodies are parsed here, so their limits apply before
+        // the handler runs; anything else is parsed when first read.
         let request_body = match content_type {
-            RequestBodyType::FORM => {
-                let collected = req.body_mut().collect().await?;
-                let body_bytes = collected.to_bytes();
-                match std::str::from_utf8(&body_bytes) {
-                    Ok(body_string) => match FormData::from_query_string(body_string) {
-                        Ok(fd) => RequestBody::new_form(fd),
-                        Err(_e) => RequestBody::new_form(FormData::new()),
-                    },
-                    Err(_e) => RequestBody::new_form(FormData::new()),
-                }
-            }
             RequestBodyType::MultipartForm => {
-                let collected = req.body_mut().collect().await?;
-                let body_bytes = collected.to_bytes();
-
                 let boundary = content_type_str_opt
                     .as_deref()
                     .filter(|ct| ct.to_lowercase().contains("multipart/form-data"))
                     .and_then(|ct| extract_boundary(&ct));
 
                 let (fields, file_parts) = if let Some(boundary) = boundary {
-                    let (field_refs, files) = parse_multipart_form(&body_bytes, &boundary);
+                    let (field_refs, files) =
+                        parse_multipart_form(&body_bytes, &boundary, &settings.multipart_limits)
+                            .map_err(|e| e.into_response(HttpResponse::new()))?;
                     let owned_fields = field_refs
                         .into_iter()
                         .map(|(k, v)| (k.to_string(), v.to_string()))
@@ -146,38 +126,12 @@ impl HttpRequest {
                 }
 
                 if !file_parts.is_empty() {
-                    RequestBody::new_binary_with_form_fields(body_bytes, form_data)
+                    RequestBody::new_binary_with_form_fields(body_bytes.clone(), form_data).into()
                 } else {
-                    RequestBody::new_form(form_data)
+                    RequestBody::new_form(form_data).into()
                 }
             }
-            RequestBodyType::JSON => {
-                let collected = req.body_mut().collect().await?;
-                let body_bytes = collected.to_bytes();
-                let body_json = match serde_json::from_slice::<serde_json::Value>(&body_bytes) {
-                    Ok(json) => json,
-                    Err(e) => {
-                        eprintln!("Error parsing JSON: {}", e);
-                        eprintln!("Defaulting to null JSON");
-                        Value::Null
-                    }
-                };
-                RequestBody::new_json(body_json)
-            }
-            RequestBodyType::TEXT => {
-                let collected = req.body_mut().collect().await?;
-                let body_bytes = collected.to_bytes();
-                match TextData::from_bytes(body_bytes.as_ref().to_vec()) {
-                    Ok(text) => RequestBody::new_text(text),
-                    Err(_) => RequestBody::new_binary(body_bytes),
-                }
-            }
-            RequestBodyType::BINARY => {
-                let collected = req.body_mut().collect().await?;
-                let body_bytes = collected.to_bytes();
-                RequestBody::new_binary(body_bytes)
-            }
-            RequestBodyType::EMPTY => RequestBody::EMPTY,
+            _ => LazyBody::pending(body_bytes.clone(), content_type_str_opt),
         };
 
         Ok(HttpRequest {
@@ -186,83 +140,102 @@ impl HttpRequest {
             origin_url,
             method,
             path,
-            protocol: x_forwarded_proto_str,
+            matched_path: None,
+            protocol,
             headers,
             data,
             body: request_body,
+            raw_body: Some(body_bytes),
             cookies: cookies_map,
+            files,
+            settings,
+            state: req
+                .extensions()
+                .get::<TypeMap>()
+                .cloned()
+                .unwrap_or_default(),
+            extensions: req
+                .extensions()
+                .get::<RequestExtensions>()
+                .map(|local| local.0.clone())
+                .unwrap_or_default(),
+            received_at: req.extensions().get::<ReceivedAt>().map(|at| at.0),
+            incoming: req.extensions().get::<IncomingBody>().cloned(),
+            #[cfg(feature = "session")]
+            session: req.extensions().get::<Session>().cloned(),
         })
     }
     pub(crate) fn from_request_info(req_info: &RequestInfo) -> Self {
-        let mut headers = RequestHeaders::new();
-
-        req_info.headers().iter().for_each(|(key, value)| {
-            if let Ok(v) = value.to_str() {
-                headers.insert(key.as_str(), v);
-            }
-        });
+        // Cloned whole so repeated headers keep every value.
+        let headers = RequestHeaders::from_header_map(req_info.headers().clone());
 
         let method = HttpMethods::from(req_info.method());
-        let origin_url = match req_info.uri().authority() {
-            Some(authority) => {
-                let scheme = req_info.uri().scheme_str().unwrap_or("http");
-                Url::new(format!("{}://{}", scheme, authority))
-            }
-            None => {
-                let uri_string = req_info
-                    .headers()
-                    .get(HOST)
-                    .and_then(|host: &hyper::header::HeaderValue| host.to_str().ok())
-                    .map(|host| {
-                        let scheme = "http";
-                        format!("{}://{}", scheme, host)
-                    })
-                    .unwrap_or(String::new());
-
-                Url::new(uri_string)
-            }
-        };
+        let settings = req_info
+            .data::<RequestSettings>()
+            .copied()
+            .unwrap_or_default();
+        let protocol = resolve_protocol(
+            req_info.data::<Scheme>(),
+            req_info.headers(),
+            settings.trusted_proxy,
+        );
+        let origin_url = resolve_origin(
+            &protocol,
+            req_info.uri(),
+            req_info.headers(),
+            settings.trusted_host,
+        );
 
         let query_string = req_info.uri().query().unwrap_or("");
 
         let queries = url::form_urlencoded::parse(query_string.as_bytes())
-            .filter_map(|(key, value)| Some((key.to_string(), value.to_string())))
-            .collect::<AHashMap<String, String>>();
+            .filter_map(|(key, value)| Some((key.to_string(), value.to_string())));
 
-        let query = QueryParams::from_ahashmap(queries);
+        let mut query = QueryParams::from_iterator(queries);
+        query.raw = query_string.to_string();
         let params = RouteParams::new();
 
-        let mut cookies_map = AHashMap::new();
-        let cookies = Self::get_cookies_from_req_info(&req_info);
-
-        cookies.iter().for_each(|cookie| {
-            let (name, value) = (cookie.name(), cookie.value());
-            cookies_map.insert(name.to_string(), value.to_string());
-        });
-
-        let protocol = req_info
+        let cookies_map = req_info
             .headers()
-            .get("x-forwarded-proto")
-            .and_then(|val| val.to_str().ok())
-            .unwrap_or("http")
-            .to_string();
+            .get(hyper::header::COOKIE)
+            .and_then(|v| v.to_str().ok())
+            .map(Self::cookies_from_header)
+            .unwrap_or_default();
 
         let mut data = RequestData::new();
         if let Some(ext_data) = req_info.data::<RequestData>() {
             data = ext_data.clone();
         }
+        let files = req_info
+            .data::<Vec<UploadedFile>>()
+            .cloned()
+            .unwrap_or_default();
 
         Self {
-            body: RequestBody::EMPTY,
+            body: RequestBody::EMPTY.into(),
+            raw_body: None,
             cookies: cookies_map,
             headers,
             method,
             origin_url,
             params,
             path: req_info.uri().path().to_string(),
+            matched_path: None,
             query,
             data,
             protocol,
+            files,
+            settings,
+            state: req_info.data::<TypeMap>().cloned().unwrap_or_default(),
+            extensions: req_info
+                .data::<RequestExtensions>()
+                .map(|local| local.0.clone())
+                .unwrap_or_default(),
+            // Set from the response by `run_post_middleware`.
+            received_at: None,
+            incoming: None,
+            #[cfg(feature = "session")]
+            session: req_info.data::<Session>().cloned(),
         }
     }
 
@@ -320,46 +293,81 @@ impl HttpRequest {
 
         if let Some(ext) = builder.extensions_mut() {
             ext.insert(data.clone());
-        }
-        let body = match &self.body {
-            RequestBody::JSON(json) => {
-                let json_str = serde_json::to_string(json)?;
-                builder
-                    .headers_mut()
-                    .unwrap()
-                    .insert(hyper::header::CONTENT_TYPE, "application/json".parse()?);
-                Full::from(hyper::body::Bytes::from(json_str))
+            if !self.files.is_empty() {
+                ext.insert(self.files.clone());
             }
-            RequestBody::TEXT(text) => {
-                builder
-                    .headers_mut()
-                    .unwrap()
-                    .insert(hyper::header::CONTENT_TYPE, "text/plain".parse()?);
-                Full::from(hyper::body::Bytes::from(text.as_bytes().to_vec()))
+            if self.is_secure() {
+                ext.insert(Scheme::Https);
             }
-            RequestBody::FORM(form) => {
-                let form_str = form.to_string();
-                builder.headers_mut().unwrap().insert(
-                    hyper::header::CONTENT_TYPE,
-                    "application/x-www-form-urlencoded".parse()?,
-                );
-                Full::from(hyper::body::Bytes::from(form_str))
+            ext.insert(self.settings);
+            ext.insert(self.state.clone());
+            ext.insert(RequestExtensions(self.extensions.clone()));
+            if let Some(received_at) = self.received_at {
+                ext.insert(ReceivedAt(received_at));
             }
-            RequestBody::BINARY(bytes) => {
-                builder.headers_mut().unwrap().insert(
-                    hyper::header::CONTENT_TYPE,
-                    "application/octet-stream".parse()?,
-                );
-                Full::from(bytes.clone())
+            if let Some(incoming) = &self.incoming {
+                ext.insert(incoming.clone());
             }
-            RequestBody::BinaryWithFields(bytes, _form_data) => {
-                builder
-                    .headers_mut()
-                    .unwrap()
-                    .insert(hyper::header::CONTENT_TYPE, "multipart/form-data".parse()?);
-                Full::from(bytes.clone())
+            #[cfg(feature = "session")]
+            if let Some(session) = &self.session {
+                ext.insert(session.clone());
             }
-            RequestBody::EMPTY => Full::from(Bytes::new()),
+        }
+        // An unmodified body is forwarded as received, with its original
+        // `Content-Type`, so the next stage parses exactly what the client sent.
+        let body = match &self.raw_body {
+            Some(raw) => {
+                let headers = builder.headers_mut().unwrap();
+                if !headers.contains_key(hyper::header::CONTENT_TYPE)
+                    && self.body.declared_type() != RequestBodyType::EMPTY
+                {
+                    headers.insert(
+                        hyper::header::CONTENT_TYPE,
+                        self.body.declared_type().to_string().parse()?,
+                    );
+                }
+                Full::from(raw.clone())
+            }
+            None => match &*self.body {
+                RequestBody::JSON(json) => {
+                    let json_str = serde_json::to_string(json)?;
+                    builder
+                        .headers_mut()
+                        .unwrap()
+                        .insert(hyper::header::CONTENT_TYPE, "application/json".parse()?);
+                    Full::from(hyper::body::Bytes::from(json_str))
+                }
+                RequestBody::TEXT(text) => {
+                    builder
+                        .headers_mut()
+                        .unwrap()
+                        .insert(hyper::header::CONTENT_TYPE, "text/plain".parse()?);
+                    Full::from(hyper::body::Bytes::from(text.as_bytes().to_vec()))
+                }
+                RequestBody::FORM(form) => {
+                    let form_str = form.to_string();
+                    builder.headers_mut().unwrap().insert(
+                        hyper::header::CONTENT_TYPE,
+                        "application/x-www-form-urlencoded".parse()?,
+                    );
+                    Full::from(hyper::body::Bytes::from(form_str))
+                }
+                RequestBody::BINARY(bytes) => {
+                    builder.headers_mut().unwrap().insert(
+                        hyper::header::CONTENT_TYPE,
+                        "application/octet-stream".parse()?,
+                    );
+                    Full::from(bytes.clone())
+                }
+                RequestBody::BinaryWithFields(bytes, _form_data) => {
+                    builder
+                        .headers_mut()
+                        .unwrap()
+                        .insert(hyper::header::CONTENT_TYPE, "multipart/form-data".parse()?);
+                    Full::from(bytes.clone())
+                }
+                RequestBody::EMPTY => Full::from(Bytes::new()),
+            },
         };
         let request = builder.body(body)?;
         Ok(request)
@@ -432,49 +440,157 @@ impl HttpRequest {
         let data = self.get_all_data();
         if let Some(ext) = builder.extensions_mut() {
             ext.insert(data.clone());
-        }
-        let body = match &self.body {
-            RequestBody::JSON(json) => {
-                let json_str = serde_json::to_string(json)?;
-
-                builder
-                    .headers_mut()
-                    .unwrap()
-                    .insert(hyper::header::CONTENT_TYPE, "application/json".parse()?);
-                Full::from(Bytes::from(json_str))
+            if !self.files.is_empty() {
+                ext.insert(self.files.clone());
             }
-            RequestBody::TEXT(text) => {
-                builder
-                    .headers_mut()
-                    .unwrap()
-                    .insert(hyper::header::CONTENT_TYPE, "text/plain".parse()?);
-                Full::from(Bytes::from(text.as_bytes().to_vec()))
+            if self.is_secure() {
+                ext.insert(Scheme::Https);
             }
-            RequestBody::FORM(form) => {
-                builder.headers_mut().unwrap().insert(
-                    hyper::header::CONTENT_TYPE,
-                    "application/x-www-form-urlencoded".parse()?,
-                );
-                Full::from(Bytes::from(form.to_string()))
+            ext.insert(self.settings);
+            ext.insert(self.state.clone());
+            ext.insert(RequestExtensions(self.extensions.clone()));
+            if let Some(received_at) = self.received_at {
+                ext.insert(ReceivedAt(received_at));
             }
-            RequestBody::BINARY(bytes) => {
-                builder.headers_mut().unwrap().insert(
-                    hyper::header::CONTENT_TYPE,
-                    "application/octet-stream".parse()?,
-                );
-                Full::from(bytes.clone())
+            if let Some(incoming) = &self.incoming {
+                ext.insert(incoming.clone());
             }
-            RequestBody::BinaryWithFields(bytes, _form_data) => {
-                builder
-                    .headers_mut()
-                    .unwrap()
-                    .insert(hyper::header::CONTENT_TYPE, "multipart/form-data".parse()?);
-                Full::from(bytes.clone())
+            #[cfg(feature = "session")]
+            if let Some(session) = &self.session {
+                ext.insert(session.clone());
             }
-            RequestBody::EMPTY => Full::from(Bytes::new()),
+        }
+        // An unmodified body is forwarded as received, with its original
+        // `Content-Type`, so the next stage parses exactly what the client sent.
+        let body = match &self.raw_body {
+            Some(raw) => {
+                let headers = builder.headers_mut().unwrap();
+                if !headers.contains_key(hyper::header::CONTENT_TYPE)
+                    && self.body.declared_type() != RequestBodyType::EMPTY
+                {
+                    headers.insert(
+                        hyper::header::CONTENT_TYPE,
+                        self.body.declared_type().to_string().parse()?,
+                    );
+                }
+                Full::from(raw.clone())
+            }
+            None => match &*self.body {
+                RequestBody::JSON(json) => {
+                    let json_str = serde_json::to_string(json)?;
+
+                    builder
+                        .headers_mut()
+                        .unwrap()
+                        .insert(hyper::header::CONTENT_TYPE, "application/json".parse()?);
+                    Full::from(Bytes::from(json_str))
+                }
+                RequestBody::TEXT(text) => {
+                    builder
+                        .headers_mut()
+                        .unwrap()
+                        .insert(hyper::header::CONTENT_TYPE, "text/plain".parse()?);
+                    Full::from(Bytes::from(text.as_bytes().to_vec()))
+                }
+                RequestBody::FORM(form) => {
+                    builder.headers_mut().unwrap().insert(
+                        hyper::header::CONTENT_TYPE,
+                        "application/x-www-form-urlencoded".parse()?,
+                    );
+                    Full::from(Bytes::from(form.to_string()))
+                }
+                RequestBody::BINARY(bytes) => {
+                    builder.headers_mut().unwrap().insert(
+                        hyper::header::CONTENT_TYPE,
+                        "application/octet-stream".parse()?,
+                    );
+                    Full::from(bytes.clone())
+                }
+                RequestBody::BinaryWithFields(bytes, _form_data) => {
+                    builder
+                        .headers_mut()
+                        .unwrap()
+                        .insert(hyper::header::CONTENT_TYPE, "multipart/form-data".parse()?);
+                    Full::from(bytes.clone())
+                }
+                RequestBody::EMPTY => Full::from(Bytes::new()),
+            },
         };
         let request = builder.body(body)?;
 
         Ok(request)
     }
 }
+
+/// Resolves the scheme a request was made with.
+///
+/// TLS terminated by this server is authoritative. On a plain connection the
+/// first `X-Forwarded-Proto` value is used if the peer is a trusted proxy,
+/// since it may have terminated TLS; otherwise the connection's own scheme.
+fn resolve_protocol(scheme: Option<&Scheme>, headers: &HeaderMap, trusted: bool) -> String {
+    if scheme == Some(&Scheme::Https) {
+        return Scheme::Https.as_str().to_string();
+    }
+
+    trusted
+        .then(|| {
+            forwarded_param(headers, "proto").or_else(|| first_value(headers, "x-forwarded-proto"))
+        })
+        .flatten()
+        .map(|proto| proto.to_ascii_lowercase())
+        .unwrap_or_else(|| scheme.unwrap_or(&Scheme::Http).as_str().to_string())
+}
+
+/// Builds the origin, `scheme://host[:port]`, the client sent the request to.
+///
+/// A proxy trusted to report the host (see [`RequestSettings::for_peer`])
+/// describes the public origin with the `host` of the first `Forwarded` hop,
+/// or with `X-Forwarded-Host` and `X-Forwarded-Port`, so those take precedence. Otherwise the host comes from the URI authority,
+/// which HTTP/2 and absolute-form requests carry, or the `Host` header. The
+/// URI's own scheme is whatever the client claimed, so `protocol` is used
+/// instead.
+fn resolve_origin(protocol: &str, uri: &Uri, headers: &HeaderMap, trusted: bool) -> Url {
+    if let Some(host) = trusted.then(|| forwarded_param(headers, "host")).flatten() {
+        return Url::new(format!("{}://{}", protocol, host));
+    }
+
+    let forwarded = |name| trusted.then(|| first_value(headers, name)).flatten();
+    let host = forwarded("x-forwarded-host")
+        .or_else(|| uri.authority().map(|authority| authority.as_str()))
+        .or_else(|| headers.get(HOST).and_then(|host| host.to_str().ok()));
+    let Some(host) = host else {
+        return Url::new("");
+    };
+
+    let port = forwarded("x-forwarded-port").filter(|port| port.parse::<u16>().is_ok());
+    match port {
+        Some(port) => {
+            let hostname = strip_port(host);
+            let default_port = if protocol == "https" { "443" } else { "80" };
+            if port == default_port {
+                Url::new(format!("{}://{}", protocol, hostname))
+            } else {
+                Url::new(format!("{}://{}:{}", protocol, hostname, port))
+            }
+        }
+        None => Url::new(format!("{}://{}", protocol, host)),
+    }
+}
+
+/// A parameter of the first hop in the `Forwarded` header.
+fn forwarded_param<'a>(headers: &'a HeaderMap, key: &str) -> Option<&'a str> {
+    headers
+        .get("forwarded")
+        .and_then(|v| v.to_str().ok())
+        .and_then(|v| forwarded::first_hop_param(v, key))
+}
+
+/// The first of a header's comma-separated values, as appended by proxies.
+fn first_value<'a>(headers: &'a HeaderMap, name: &str) -> Option<&'a str> {
+    headers
+        .get(name)
+        .and_then(|v| v.to_str().ok())
+        .and_then(|v| v.split(',').next())
+        .map(str::trim)
+        .filter(|v| !v.is_empty())
+}
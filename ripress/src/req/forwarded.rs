@@ -0,0 +1,152 @@
+#![warn(missing_docs)]
+//! Parsing of the standard `Forwarded` header, [RFC 7239].
+//!
+//! [RFC 7239]: https://www.rfc-editor.org/rfc/rfc7239
+
+use std::net::IpAddr;
+
+use crate::req::origin_url::strip_port;
+
+/// One hop of a `Forwarded` header, as added by a single proxy.
+///
+/// Parameters the proxy didn't send are `None`. Quoted values are unquoted,
+/// so `for="[2001:db8::1]:4711"` gives `Some("[2001:db8::1]:4711")`.
+///
+/// ## Example
+///
+/// ```
+/// use ripress::req::forwarded::Forwarded;
+///
+/// let hops = Forwarded::parse_list(r#"for=203.0.113.1;proto=https, for="[2001:db8::1]""#);
+/// assert_eq!(hops.len(), 2);
+/// assert_eq!(hops[0].proto.as_deref(), Some("https"));
+/// assert_eq!(hops[1].for_ip(), Some("2001:db8::1".parse().unwrap()));
+/// ```
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct Forwarded {
+    /// The client, or previous proxy, that made the request to this proxy
+    pub for_: Option<String>,
+    /// The interface on which this proxy received the request
+    pub by: Option<String>,
+    /// The `Host` header this proxy received
+    pub host: Option<String>,
+    /// The scheme this proxy received the request with, lowercased
+    pub proto: Option<String>,
+}
+
+impl Forwarded {
+    /// Parses a `Forwarded` header value into its hops, the one closest to the
+    /// client first.
+    ///
+    /// Unknown parameters are ignored, as are pairs without a `=`.
+    pub fn parse_list(value: &str) -> Vec<Forwarded> {
+        split_unquoted(value, ',')
+            .into_iter()
+            .filter(|element| !element.trim().is_empty())
+            .map(|element| {
+                let mut hop = Forwarded::default();
+                for (key, value) in pairs(element) {
+                    let value = unescape(value);
+                    match key.to_ascii_lowercase().as_str() {
+                        "for" => hop.for_ = Some(value),
+                        "by" => hop.by = Some(value),
+                        "host" => hop.host = Some(value),
+                        "proto" => hop.proto = Some(value.to_ascii_lowercase()),
+                        _ => {}
+                    }
+                }
+                hop
+            })
+            .collect()
+    }
+
+    /// Returns the IP address in `for`, without its port.
+    ///
+    /// `None` when `for` is missing or an obfuscated identifier such as
+    /// `unknown` or `_hidden`.
+    pub fn for_ip(&self) -> Option<IpAddr> {
+        self.for_.as_deref().and_then(node_ip)
+    }
+}
+
+/// Returns a parameter of the first hop in a `Forwarded` header value, with
+/// surrounding quotes removed.
+pub(crate) fn first_hop_param<'a>(value: &'a str, key: &str) -> Option<&'a str> {
+    let element = split_unquoted(value, ',').into_iter().next()?;
+    pairs(element)
+        .find(|(name, _)| name.eq_ignore_ascii_case(key))
+        .map(|(_, value)| strip_quotes(value))
+        .filter(|value| !value.is_empty())
+}
+
+/// Parses a node, e.g. `192.0.2.60`, `192.0.2.60:80` or `[2001:db8::1]:4711`,
+/// into its IP address.
+pub(crate) fn node_ip(node: &str) -> Option<IpAddr> {
+    if let Ok(ip) = node.parse() {
+        return Some(ip);
+    }
+    strip_port(node)
+        .trim_start_matches('[')
+        .trim_end_matches(']')
+        .parse()
+        .ok()
+}
+
+/// The `key=value` pairs of a hop, with values still quoted.
+fn pairs(element: &str) -> impl Iterator<Item = (&str, &str)> {
+    split_unquoted(element, ';')
+        .into_iter()
+        .filter_map(|pair| pair.split_once('='))
+        .map(|(key, value)| (key.trim(), value.trim()))
+}
+
+/// Splits on `separator`, except inside quoted strings.
+fn split_unquoted(value: &str, separator: char) -> Vec<&str> {
+    let mut parts = Vec::new();
+    let mut start = 0;
+    let mut quoted = false;
+    let mut escaped = false;
+
+    for (i, c) in value.char_indices() {
+        if escaped {
+            escaped = false;
+            continue;
+        }
+        match c {
+            '\\' if quoted => escaped = true,
+            '"' => quoted = !quoted,
+            c if c == separator && !quoted => {
+                parts.push(&value[start..i]);
+                start = i + c.len_utf8();
+            }
+            _ => {}
+        }
+    }
+    parts.push(&value[start..]);
+    parts
+}
+
+fn strip_quotes(value: &str) -> &str {
+    value
+        .strip_prefix('"')
+        .and_then(|value| value.strip_suffix('"'))
+        .unwrap_or(value)
+}
+
+/// Removes the quotes around a quoted string and resolves its `\` escapes.
+fn unescape(value: &str) -> String {
+    let inner = strip_quotes(value);
+    if inner.len() == value.len() {
+        return value.to_string();
+    }
+
+    let mut out = String::with_capacity(inner.len());
+    let mut chars = inner.chars();
+    while let Some(c) = chars.next() {
+        match c {
+            '\\' => out.extend(chars.next()),
+            c => out.push(c),
+        }
+    }
+    out
+}
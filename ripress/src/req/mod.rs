@@ -312,13 +312,28 @@ pub mod request_error;
 pub mod with_wynd;
 
 use crate::{
-    req::body::{FormData, RequestBody, RequestBodyType},
+    app::{settings::RequestSettings, state::TypeMap},
+    error::RipressError,
+    helpers::{
+        determine_content_type_request, media_type_specificity, negotiate, token_specificity,
+    },
+    req::body::{
+        incoming::IncomingBody, lazy::LazyBody, FormData, JsonItems, JsonLines, RequestBody,
+        RequestBodyType,
+    },
     types::HttpMethods,
 };
 use ahash::AHashMap;
-use cookie::Cookie;
-use routerify_ng::RequestInfo;
+use bytes::Bytes;
+use futures::{Stream, StreamExt};
 use std::net::{IpAddr, Ipv4Addr};
+use std::time::Instant;
+
+#[cfg(feature = "session")]
+use crate::middlewares::session::Session;
+
+/// Size of the chunks yielded by [`HttpRequest::body_stream`].
+const BODY_STREAM_CHUNK_SIZE: usize = 16 * 1024;
 
 /// A struct that represents the request headers.
 /// And it's methods.
@@ -328,10 +343,16 @@ pub mod request_headers;
 /// And it's methods.
 pub mod origin_url;
 
+/// Parsing of the `Forwarded` header set by proxies.
+pub mod forwarded;
+
 /// A struct that represents the query parameters of the request.
 /// And it's methods.
 pub mod query_params;
 
+/// Serde deserializer for query parameters with array and nested key support.
+pub(crate) mod query_deserializer;
+
 /// Structs that represents the body of the requests.
 /// And it's methods.
 pub mod body;
@@ -344,11 +365,25 @@ pub mod route_params;
 /// And it's methods.
 pub mod request_data;
 
+/// A struct that represents a file saved by the file upload middleware.
+pub mod uploaded_file;
+
+#[cfg(feature = "validation")]
+/// The [`Validated`](validated::Validated) extractor, which checks requests with the `validator` crate.
+pub mod validated;
+
 use request_data::RequestData;
+use uploaded_file::UploadedFile;
+
+/// Request data key under which the request ID middleware stores the request ID.
+pub(crate) const REQUEST_ID_KEY: &str = "request_id";
+
+/// Request data key under which the shield middleware stores the CSP nonce.
+pub(crate) const CSP_NONCE_KEY: &str = "csp_nonce";
 
 use origin_url::Url;
 use query_params::QueryParams;
-use request_headers::RequestHeaders;
+use request_headers::{Authorization, RequestHeaders};
 use route_params::RouteParams;
 
 /// Represents an incoming HTTP request with comprehensive access to request data.
@@ -404,7 +439,19 @@ pub struct HttpRequest {
     /// The requested endpoint path.
     pub path: String,
 
-    /// Protocol of the request (HTTP or HTTPs)
+    /// The pattern of the route that matched the request, e.g. `/users/:id`
+    ///
+    /// Set when the request is dispatched to a route handler, and visible to
+    /// post-middlewares. It is `None` in pre-middlewares, which run before
+    /// routing, and for requests no route matched. Group requests by this
+    /// rather than `path` to keep the number of distinct values in logs and
+    /// metrics bounded.
+    pub matched_path: Option<String>,
+
+    /// Scheme of the request, `http` or `https`
+    ///
+    /// Taken from the connection, unless a plain connection carries an
+    /// `X-Forwarded-Proto` header from a proxy.
     pub protocol: String,
 
     /// The request's headers
@@ -417,7 +464,33 @@ pub struct HttpRequest {
     pub(crate) data: RequestData,
 
     /// The request body, which may contain JSON, text, or form data or binary data.
-    pub(crate) body: RequestBody,
+    /// Parsed the first time it is read.
+    pub(crate) body: LazyBody,
+
+    /// The body as received, `None` once a middleware has modified the body
+    pub(crate) raw_body: Option<Bytes>,
+
+    /// Files saved by the file upload middleware
+    pub(crate) files: Vec<UploadedFile>,
+
+    /// App settings the accessors depend on, such as the subdomain offset
+    pub(crate) settings: RequestSettings,
+
+    /// Shared state registered with `App::state` and `App::extension`
+    pub(crate) state: TypeMap,
+
+    /// Values set for this request alone with `set_extension`
+    pub(crate) extensions: TypeMap,
+
+    /// When the connection layer received the request, used for the logger's latency
+    pub(crate) received_at: Option<Instant>,
+
+    /// The unread body of a route that streams it
+    pub(crate) incoming: Option<IncomingBody>,
+
+    /// The session attached by the session middleware
+    #[cfg(feature = "session")]
+    pub(crate) session: Option<Session>,
 }
 
 impl Default for HttpRequest {
@@ -449,11 +522,21 @@ impl HttpRequest {
             query: QueryParams::new(),
             method: HttpMethods::GET,
             path: String::new(),
+            matched_path: None,
             protocol: String::new(),
             headers: RequestHeaders::new(),
             data: RequestData::new(),
-            body: RequestBody::EMPTY,
+            body: RequestBody::EMPTY.into(),
+            raw_body: None,
             cookies: AHashMap::new(),
+            files: Vec::new(),
+            settings: RequestSettings::default(),
+            state: TypeMap::default(),
+            extensions: TypeMap::default(),
+            received_at: None,
+            incoming: None,
+            #[cfg(feature = "session")]
+            session: None,
         }
     }
 
@@ -466,6 +549,8 @@ impl HttpRequest {
     /// ## Returns
     ///
     /// Returns `Some(&String)` with the cookie value if found, or `None` if not found.
+    /// The value is returned whole even if it contains `=`, and without the
+    /// surrounding double quotes if it was sent quoted.
     ///
     /// ## Example
     /// ```rust
@@ -488,24 +573,275 @@ impl HttpRequest {
         self.headers.get("x-requested-with").is_some()
     }
 
-    /// Returns true if the request is secure.
+    /// Returns true if the request was made over HTTPS.
+    ///
+    /// This is the case when `App::listen_tls` terminated TLS, or when a proxy
+    /// reported `X-Forwarded-Proto: https`.
     pub fn is_secure(&self) -> bool {
-        self.headers.get("x-forwarded-proto").is_some()
+        self.protocol.eq_ignore_ascii_case("https")
+    }
+
+    /// Returns the host name the request was sent to, without the port.
+    ///
+    /// Read from the `host` of the first `Forwarded` hop or the first
+    /// `X-Forwarded-Host` value when set by a trusted proxy (see
+    /// [`App::trust_proxy`](crate::app::App::trust_proxy)), then the `Host`
+    /// header, then the request URL. IPv6 literals keep their brackets, so
+    /// `[::1]:8080` gives `[::1]`. Returns `""` when none of them names a host.
+    ///
+    /// ## Example
+    /// ```rust
+    /// use ripress::context::HttpRequest;
+    ///
+    /// let mut req = HttpRequest::new();
+    /// req.headers.insert("host", "api.example.com:8080");
+    /// assert_eq!(req.hostname(), "api.example.com");
+    /// ```
+    pub fn hostname(&self) -> &str {
+        self.forwarded_param("host")
+            .or_else(|| self.forwarded_header("x-forwarded-host"))
+            .or_else(|| self.headers.get("host"))
+            .map(|authority| origin_url::strip_port(authority.trim()))
+            .filter(|host| !host.is_empty())
+            .or_else(|| self.origin_url.hostname())
+            .unwrap_or("")
+    }
+
+    /// Returns the subdomains of [`hostname`](Self::hostname), nearest to the
+    /// root domain first.
+    ///
+    /// The last labels of the host name, two by default, are taken to be the
+    /// domain and are left out; change how many with
+    /// [`App::subdomain_offset`](crate::app::App::subdomain_offset). For
+    /// `api.v2.example.com` this returns `["v2", "api"]`. IP addresses have no
+    /// subdomains.
+    ///
+    /// ## Example
+    /// ```rust
+    /// use ripress::context::HttpRequest;
+    ///
+    /// let mut req = HttpRequest::new();
+    /// req.headers.insert("host", "api.v2.example.com");
+    /// assert_eq!(req.subdomains(), vec!["v2", "api"]);
+    /// ```
+    pub fn subdomains(&self) -> Vec<&str> {
+        let hostname = self.hostname();
+        if hostname.starts_with('[') || hostname.parse::<IpAddr>().is_ok() {
+            return Vec::new();
+        }
+
+        let labels: Vec<&str> = hostname.split('.').collect();
+        let count = labels.len().saturating_sub(self.settings.subdomain_offset);
+        labels[..count].iter().rev().copied().collect()
     }
 
     /// Returns the client's IP address.
+    ///
+    /// When the peer is a trusted proxy (see
+    /// [`App::trust_proxy`](crate::app::App::trust_proxy)), this is the `for`
+    /// address of the first `Forwarded` hop or the first `X-Forwarded-For`
+    /// value. Otherwise it is the peer's own address. Requests without either,
+    /// such as those arriving over a Unix socket without the headers, report
+    /// `127.0.0.1`.
     pub fn ip(&self) -> IpAddr {
-        self.headers
-            .get("x-forwarded-for")
-            .and_then(|v| {
-                v.split(',')
-                    .next()
-                    .map(|v| v.trim())
+        self.forwarded_param("for")
+            .and_then(forwarded::node_ip)
+            .or_else(|| {
+                self.forwarded_header("x-forwarded-for")
                     .and_then(|v| v.parse().ok())
             })
+            .or(self.settings.peer_ip)
             .unwrap_or_else(|| IpAddr::V4(Ipv4Addr::new(127, 0, 0, 1)))
     }
 
+    /// Returns every hop of the request's `Forwarded` headers, the one closest
+    /// to the client first.
+    ///
+    /// The headers are parsed whether or not the peer is a trusted proxy; it's
+    /// up to the caller to decide how far to believe them.
+    ///
+    /// ## Example
+    /// ```rust
+    /// use ripress::context::HttpRequest;
+    ///
+    /// let mut req = HttpRequest::new();
+    /// req.headers.insert("forwarded", "for=203.0.113.1;proto=https");
+    ///
+    /// let hops = req.forwarded();
+    /// assert_eq!(hops[0].for_ip(), Some("203.0.113.1".parse().unwrap()));
+    /// assert_eq!(hops[0].proto.as_deref(), Some("https"));
+    /// ```
+    pub fn forwarded(&self) -> Vec<forwarded::Forwarded> {
+        self.headers
+            .get_all("forwarded")
+            .into_iter()
+            .flat_map(forwarded::Forwarded::parse_list)
+            .collect()
+    }
+
+    /// A parameter of the first `Forwarded` hop, if the peer is trusted to set it.
+    fn forwarded_param(&self, key: &str) -> Option<&str> {
+        if !self.settings.trusted_proxy {
+            return None;
+        }
+        self.headers
+            .get("forwarded")
+            .and_then(|v| forwarded::first_hop_param(v, key))
+    }
+
+    /// The first value of a forwarding header, if the peer is trusted to set it.
+    fn forwarded_header(&self, name: &str) -> Option<&str> {
+        if !self.settings.trusted_proxy {
+            return None;
+        }
+        self.headers
+            .get(name)
+            .and_then(|v| v.split(',').next())
+            .map(str::trim)
+            .filter(|v| !v.is_empty())
+    }
+
+    /// Returns the best match among the offered content types based on the `Accept` header.
+    ///
+    /// Quality values (`q=`) and wildcards (`*/*`, `text/*`) are respected. When two offers
+    /// are accepted with the same quality, the one matched by the more specific media range
+    /// wins, then the one listed first. If the request has no `Accept` header, the first
+    /// offer is returned.
+    ///
+    /// ## Arguments
+    ///
+    /// * `types` - The content types the handler can produce, in order of preference
+    ///
+    /// ## Returns
+    ///
+    /// Returns `Some(&str)` with the chosen type, or `None` if none are acceptable.
+    ///
+    /// ## Example
+    /// ```rust
+    /// use ripress::context::HttpRequest;
+    ///
+    /// let req = HttpRequest::new();
+    ///
+    /// match req.accepts(&["application/json", "text/html"]) {
+    ///     Some("text/html") => println!("Render a page"),
+    ///     Some(_) => println!("Send JSON"),
+    ///     None => println!("406 Not Acceptable"),
+    /// }
+    /// ```
+
+    pub fn accepts<'a>(&self, types: &[&'a str]) -> Option<&'a str> {
+        negotiate(self.headers.accept(), types, media_type_specificity)
+    }
+
+    /// Returns the best match among the offered charsets based on the `Accept-Charset` header.
+    ///
+    /// Follows the same quality and tie-break rules as [`HttpRequest::accepts`], with `*`
+    /// as the wildcard. Charset names are compared case-insensitively.
+    ///
+    /// ## Example
+    /// ```rust
+    /// use ripress::context::HttpRequest;
+    ///
+    /// let req = HttpRequest::new();
+    /// let charset = req.accepts_charset(&["utf-8", "iso-8859-1"]);
+    /// assert_eq!(charset, Some("utf-8"));
+    /// ```
+
+    pub fn accepts_charset<'a>(&self, charsets: &[&'a str]) -> Option<&'a str> {
+        negotiate(
+            self.headers.get("accept-charset"),
+            charsets,
+            token_specificity,
+        )
+    }
+
+    /// Returns the best match among the offered encodings based on the `Accept-Encoding` header.
+    ///
+    /// Follows the same quality and tie-break rules as [`HttpRequest::accepts`], with `*`
+    /// as the wildcard. An encoding listed with `q=0` is never chosen.
+    ///
+    /// ## Example
+    /// ```rust
+    /// use ripress::context::HttpRequest;
+    ///
+    /// let req = HttpRequest::new();
+    /// let encoding = req.accepts_encoding(&["br", "gzip", "identity"]);
+    /// assert_eq!(encoding, Some("br"));
+    /// ```
+
+    pub fn accepts_encoding<'a>(&self, encodings: &[&'a str]) -> Option<&'a str> {
+        negotiate(
+            self.headers.get("accept-encoding"),
+            encodings,
+            token_specificity,
+        )
+    }
+
+    /// Returns true if the client's cached copy of `res` is still valid, like
+    /// Express's `req.fresh`.
+    ///
+    /// Only `GET` and `HEAD` requests answered with a `2xx` or `304` status can
+    /// be fresh. `If-None-Match` is compared against the response's `ETag`
+    /// using weak comparison, so `W/"a"` matches `"a"`, and `*` matches any
+    /// tagged response. Without `If-None-Match`, `If-Modified-Since` is compared
+    /// against `Last-Modified`. A request with `Cache-Control: no-cache` is
+    /// never fresh.
+    ///
+    /// ## Example
+    /// ```
+    /// use ripress::{app::App, req::HttpRequest, types::RouterFns};
+    ///
+    /// let mut app = App::new();
+    /// app.get("/report", |req: HttpRequest, res| async move {
+    ///     let res = res.set_header("ETag", "\"v42\"");
+    ///     if req.is_fresh(&res) {
+    ///         return res.status(304);
+    ///     }
+    ///     res.ok().text("the report")
+    /// });
+    /// ```
+    pub fn is_fresh(&self, res: &crate::res::HttpResponse) -> bool {
+        if !matches!(self.method, HttpMethods::GET | HttpMethods::HEAD) {
+            return false;
+        }
+        let status = res.get_status();
+        if !((200..300).contains(&status) || status == 304) {
+            return false;
+        }
+        if self.headers.get("cache-control").is_some_and(|value| {
+            value
+                .split(',')
+                .any(|directive| directive.trim().eq_ignore_ascii_case("no-cache"))
+        }) {
+            return false;
+        }
+
+        if let Some(if_none_match) = self.headers.get("if-none-match") {
+            return match res.get_header("etag") {
+                Some(etag) => crate::middlewares::etag::if_none_match(if_none_match, etag),
+                None => if_none_match.trim() == "*",
+            };
+        }
+
+        let since = self
+            .headers
+            .get("if-modified-since")
+            .and_then(|value| httpdate::parse_http_date(value).ok());
+        let last_modified = res
+            .get_header("last-modified")
+            .and_then(|value| httpdate::parse_http_date(value).ok());
+        match (since, last_modified) {
+            (Some(since), Some(last_modified)) => last_modified <= since,
+            _ => false,
+        }
+    }
+
+    /// Returns true if the client's cached copy of `res` is out of date; the
+    /// opposite of [`HttpRequest::is_fresh`].
+    pub fn is_stale(&self, res: &crate::res::HttpResponse) -> bool {
+        !self.is_fresh(res)
+    }
+
     /// Adds data from the middleware into the request.
     ///
     /// ## Arguments
@@ -565,8 +901,200 @@ impl HttpRequest {
         self.data.get(&data_key.into())
     }
 
+    /// Returns the value of type `T` stored as an extension, if any.
+    ///
+    /// A value set on this request with [`set_extension`](Self::set_extension)
+    /// takes precedence over one registered on the app with
+    /// [`App::extension`](crate::app::App::extension).
+    ///
+    /// ## Example
+    /// ```
+    /// use std::sync::Arc;
+    ///
+    /// use ripress::{app::App, req::HttpRequest, types::RouterFns};
+    ///
+    /// struct HttpClient {
+    ///     base_url: String,
+    /// }
+    ///
+    /// let mut app = App::new();
+    /// app.extension(Arc::new(HttpClient {
+    ///     base_url: "https://api.example.com".into(),
+    /// }));
+    ///
+    /// app.get("/", |req: HttpRequest, res| async move {
+    ///     match req.extension::<Arc<HttpClient>>() {
+    ///         Some(client) => res.ok().text(client.base_url.clone()),
+    ///         None => res.internal_server_error(),
+    ///     }
+    /// });
+    /// ```
+    pub fn extension<T: Send + Sync + 'static>(&self) -> Option<&T> {
+        self.extensions.get::<T>().or_else(|| self.state.get::<T>())
+    }
+
+    /// Stores a value of type `T` for the rest of this request, replacing any
+    /// earlier value of the same type.
+    ///
+    /// Middleware can use this to hand typed values, such as an authenticated
+    /// user, to the handlers after it. Other requests never see the value, and
+    /// values registered on the app are left untouched.
+    ///
+    /// ## Example
+    /// ```
+    /// let mut req = ripress::req::HttpRequest::new();
+    ///
+    /// struct UserId(u64);
+    /// req.set_extension(UserId(42));
+    ///
+    /// assert_eq!(req.extension::<UserId>().map(|id| id.0), Some(42));
+    /// ```
+    pub fn set_extension<T: Send + Sync + 'static>(&mut self, value: T) {
+        self.extensions.insert(value);
+    }
+
+    /// Returns the correlation ID assigned by the request ID middleware.
+    ///
+    /// ## Returns
+    ///
+    /// Returns `Some(id)` when [`App::use_request_id`](crate::app::App::use_request_id)
+    /// is enabled, or `None` otherwise.
+    ///
+    /// ## Example
+    /// ```
+    /// let req = ripress::req::HttpRequest::new();
+    /// if let Some(id) = req.request_id() {
+    ///     println!("Handling request {}", id);
+    /// }
+    /// ```
+    pub fn request_id(&self) -> Option<String> {
+        self.get_data(REQUEST_ID_KEY)
+    }
+
+    /// Returns the session of the client making the request.
+    ///
+    /// Sessions are enabled with [`App::use_session`](crate::app::App::use_session).
+    /// Without it, this returns an empty session whose changes are discarded.
+    ///
+    /// ## Example
+    /// ```
+    /// let req = ripress::req::HttpRequest::new();
+    /// let visits = req.session().get::<u32>("visits").unwrap_or(0);
+    /// req.session().set("visits", visits + 1).unwrap();
+    /// ```
+    #[cfg(feature = "session")]
+    pub fn session(&self) -> Session {
+        self.session.clone().unwrap_or_default()
+    }
+
+    /// Returns the Content-Security-Policy nonce generated for this request.
+    ///
+    /// ## Returns
+    ///
+    /// Returns `Some(nonce)` when [`App::use_shield`](crate::app::App::use_shield)
+    /// is enabled with a policy that uses nonces (see
+    /// [`Csp::nonce`](crate::middlewares::shield::Csp::nonce)), or `None` otherwise.
+    ///
+    /// ## Example
+    /// ```
+    /// let req = ripress::req::HttpRequest::new();
+    /// if let Some(nonce) = req.csp_nonce() {
+    ///     let html = format!("<script nonce=\"{}\">init()</script>", nonce);
+    ///     println!("{}", html);
+    /// }
+    /// ```
+    pub fn csp_nonce(&self) -> Option<String> {
+        self.get_data(CSP_NONCE_KEY)
+    }
+
+    /// Returns the files saved by the file upload middleware for this request.
+    ///
+    /// ## Returns
+    ///
+    /// Returns one [`UploadedFile`] per stored file, in the order they appeared in
+    /// the body, or an empty slice when no upload middleware ran.
+    ///
+    /// ## Example
+    /// ```
+    /// let req = ripress::req::HttpRequest::new();
+    /// for file in req.files() {
+    ///     println!("{:?} -> {}", file.original_filename, file.stored_path.display());
+    /// }
+    /// ```
+    pub fn files(&self) -> &[UploadedFile] {
+        &self.files
+    }
+
+    #[cfg(feature = "file-upload")]
+    pub(crate) fn push_file(&mut self, file: UploadedFile) {
+        self.files.push(file);
+    }
+
+    /// Parses the first value of a request header into `T`.
+    ///
+    /// Returns `None` if the header is absent, and `Some(Err(_))` if its value
+    /// can't be parsed.
+    ///
+    /// ## Example
+    /// ```rust
+    /// use ripress::req::HttpRequest;
+    ///
+    /// let req = HttpRequest::new();
+    /// let version = match req.header_typed::<u32>("x-api-version") {
+    ///     Some(Ok(version)) => version,
+    ///     Some(Err(_)) => panic!("malformed X-Api-Version"),
+    ///     None => 1,
+    /// };
+    /// ```
+    pub fn header_typed<T: std::str::FromStr>(&self, name: &str) -> Option<Result<T, T::Err>> {
+        self.headers.get_typed(name)
+    }
+
+    /// Returns the declared `Content-Length`, or `None` if it is absent or malformed.
+    pub fn content_length(&self) -> Option<u64> {
+        self.headers.content_length()
+    }
+
+    /// Returns the parsed `Content-Type`, or `None` if it is absent or malformed.
+    ///
+    /// ## Example
+    /// ```rust
+    /// use ripress::req::HttpRequest;
+    ///
+    /// let req = HttpRequest::new();
+    /// if let Some(content_type) = req.content_type() {
+    ///     let charset = content_type.get_param(mime::CHARSET);
+    ///     println!("{} ({:?})", content_type.essence_str(), charset);
+    /// }
+    /// ```
+    pub fn content_type(&self) -> Option<mime::Mime> {
+        self.headers.content_type()?.parse().ok()
+    }
+
+    /// Returns the parsed `Authorization` header, or `None` if it is absent or malformed.
+    ///
+    /// `Basic` credentials are decoded, and a `Bearer` token is returned as sent.
+    ///
+    /// ## Example
+    /// ```rust
+    /// use ripress::req::{request_headers::Authorization, HttpRequest};
+    ///
+    /// let req = HttpRequest::new();
+    /// match req.authorization() {
+    ///     Some(Authorization::Bearer(token)) => println!("token {}", token),
+    ///     Some(Authorization::Basic { username, .. }) => println!("user {}", username),
+    ///     _ => println!("anonymous"),
+    /// }
+    /// ```
+    pub fn authorization(&self) -> Option<Authorization> {
+        self.headers.get_typed("authorization")?.ok()
+    }
+
     /// Checks if the request body matches a specific content type.
     ///
+    /// Until the body has been read this is decided from the `Content-Type`
+    /// header, so checking does not parse the body.
+    ///
     /// ## Arguments
     ///
     /// * `content_type` - The `RequestBodyType` to check against
@@ -587,7 +1115,21 @@ impl HttpRequest {
     /// ```
 
     pub fn is(&self, content_type: RequestBodyType) -> bool {
-        return self.body.body_type() == content_type;
+        if matches!(
+            content_type,
+            RequestBodyType::XML | RequestBodyType::MSGPACK
+        ) {
+            return self.declares(content_type);
+        }
+        return self.body.declared_type() == content_type;
+    }
+
+    /// Whether the `Content-Type` header names `body_type`. XML and MessagePack
+    /// bodies are stored as text and binary, so the body alone can't tell.
+    fn declares(&self, body_type: RequestBodyType) -> bool {
+        self.headers
+            .content_type()
+            .is_some_and(|ct| determine_content_type_request(ct) == body_type)
     }
 
     /// Returns a read-only view of the raw request body when it is binary.
@@ -606,7 +1148,7 @@ impl HttpRequest {
     /// ```
 
     pub fn bytes(&self) -> Result<&[u8], String> {
-        let body = &self.body;
+        let body = &*self.body;
 
         if body.body_type() == RequestBodyType::BINARY {
             match &body {
@@ -631,7 +1173,9 @@ impl HttpRequest {
     /// ## Returns
     ///
     /// Returns `Ok(J)` with the deserialized value if successful, or
-    /// `Err(String)` with an error message if deserialization fails.
+    /// `Err(String)` with an error message if deserialization fails. The message
+    /// is just `Invalid JSON` when [`App::json_error_detail`](crate::app::App::json_error_detail)
+    /// is set to `Terse`.
     ///
     /// ## Example
     /// ```rust
@@ -653,22 +1197,330 @@ impl HttpRequest {
 
     pub fn json<J>(&self) -> Result<J, String>
     where
-        J: serde::de::DeserializeOwned + serde::Serialize,
+        J: serde::de::DeserializeOwned,
     {
-        let body = &self.body;
+        if self.body.declared_type() != RequestBodyType::JSON {
+            return Err(String::from("Wrong body type"));
+        }
 
-        if body.body_type() == RequestBodyType::JSON {
-            if let RequestBody::JSON(ref json_value) = body {
-                match serde_json::from_value::<J>(json_value.clone()) {
-                    Ok(serialized) => Ok(serialized),
-                    Err(e) => Err(format!("Failed to deserialize JSON: {}", e)),
-                }
-            } else {
-                Err(String::from("Invalid JSON content"))
+        let result = match &self.raw_body {
+            // An unmodified body is read straight from the bytes, so the
+            // intermediate `Value` is never built.
+            Some(raw) => serde_json::from_slice::<J>(raw),
+            None => match &*self.body {
+                RequestBody::JSON(json_value) => J::deserialize(json_value),
+                _ => return Err(String::from("Invalid JSON content")),
+            },
+        };
+
+        result.map_err(|e| {
+            self.settings
+                .json_error_detail
+                .message(format!("Failed to deserialize JSON: {}", e))
+        })
+    }
+
+    /// Deserializes an XML request body into `X`.
+    ///
+    /// The request must have an `application/xml`, `text/xml` or `+xml`
+    /// `Content-Type`; anything else is an error rather than a parse attempt.
+    /// Deserialization uses `quick-xml`'s serde support, so the root element's
+    /// children and attributes (as `@name` fields) map onto the struct fields.
+    ///
+    /// Requires the `xml` feature.
+    ///
+    /// ## Example
+    /// ```rust
+    /// use ripress::{app::App, req::HttpRequest, types::RouterFns};
+    /// use serde::Deserialize;
+    ///
+    /// #[derive(Deserialize)]
+    /// struct Order {
+    ///     id: u32,
+    ///     item: String,
+    /// }
+    ///
+    /// let mut app = App::new();
+    /// app.post("/orders", |req: HttpRequest, res| async move {
+    ///     match req.xml::<Order>() {
+    ///         Ok(order) => res.ok().text(format!("{} x {}", order.id, order.item)),
+    ///         Err(e) => res.bad_request().text(e),
+    ///     }
+    /// });
+    /// ```
+    #[cfg(feature = "xml")]
+    pub fn xml<X>(&self) -> Result<X, String>
+    where
+        X: serde::de::DeserializeOwned,
+    {
+        if !self.declares(RequestBodyType::XML) {
+            return Err(format!(
+                "Expected an XML body, got Content-Type: {}",
+                self.headers.content_type().unwrap_or("none")
+            ));
+        }
+
+        let text = match &*self.body {
+            RequestBody::TEXT(text) => text.as_str().map_err(|e| e.to_string())?,
+            RequestBody::EMPTY => "",
+            _ => return Err(String::from("Invalid XML content")),
+        };
+        quick_xml::de::from_str(text).map_err(|e| format!("Failed to deserialize XML: {}", e))
+    }
+
+    /// Deserializes a MessagePack request body into `M`.
+    ///
+    /// The request must have an `application/msgpack`, `application/x-msgpack`
+    /// or `application/vnd.msgpack` `Content-Type`; anything else is an error.
+    /// Structs may be encoded either as maps keyed by field name or as arrays
+    /// of field values.
+    ///
+    /// Requires the `msgpack` feature.
+    ///
+    /// ## Example
+    /// ```rust
+    /// use ripress::{app::App, req::HttpRequest, types::RouterFns};
+    /// use serde::{Deserialize, Serialize};
+    ///
+    /// #[derive(Deserialize, Serialize)]
+    /// struct Reading {
+    ///     sensor: String,
+    ///     value: f64,
+    /// }
+    ///
+    /// let mut app = App::new();
+    /// app.post("/readings", |req: HttpRequest, res| async move {
+    ///     let reading = match req.msgpack::<Reading>() {
+    ///         Ok(reading) => reading,
+    ///         Err(e) => return res.bad_request().text(e),
+    ///     };
+    ///     // Answer in the format the client prefers
+    ///     match req.accepts(&["application/json", "application/msgpack"]) {
+    ///         Some("application/msgpack") => res.created().msgpack(&reading),
+    ///         _ => res.created().json(&reading),
+    ///     }
+    /// });
+    /// ```
+    #[cfg(feature = "msgpack")]
+    pub fn msgpack<M>(&self) -> Result<M, String>
+    where
+        M: serde::de::DeserializeOwned,
+    {
+        if !self.declares(RequestBodyType::MSGPACK) {
+            return Err(format!(
+                "Expected a MessagePack body, got Content-Type: {}",
+                self.headers.content_type().unwrap_or("none")
+            ));
+        }
+
+        let bytes: &[u8] = match &*self.body {
+            RequestBody::BINARY(bytes) => bytes,
+            RequestBody::EMPTY => &[],
+            _ => return Err(String::from("Invalid MessagePack content")),
+        };
+        rmp_serde::from_slice(bytes)
+            .map_err(|e| format!("Failed to deserialize MessagePack: {}", e))
+    }
+
+    /// Deserializes the request body as JSON, rejecting bodies larger than `max` bytes.
+    ///
+    /// The declared `Content-Length` is checked first and the received length next,
+    /// both before any deserialization into `J` takes place.
+    ///
+    /// This check runs in the handler, after the body has been read from the
+    /// connection. To refuse an oversized body without reading it, give the route
+    /// its own limit with [`RouteHandle::body_limit`](crate::router::route_handle::RouteHandle::body_limit),
+    /// which is enforced while the body is being read.
+    ///
+    /// ## Arguments
+    ///
+    /// * `max` - The maximum accepted body size in bytes
+    ///
+    /// ## Returns
+    ///
+    /// Returns `Ok(J)` with the deserialized value, or `Err(String)` if the body is
+    /// too large or cannot be deserialized.
+    ///
+    /// ## Example
+    /// ```rust
+    /// use ripress::context::HttpRequest;
+    /// use serde::Deserialize;
+    ///
+    /// #[derive(Deserialize)]
+    /// struct Upload {
+    ///     name: String,
+    /// }
+    ///
+    /// let req = HttpRequest::new();
+    /// match req.json_with_limit::<Upload>(1024 * 1024) {
+    ///     Ok(upload) => println!("Upload: {}", upload.name),
+    ///     Err(e) => println!("Rejected: {}", e),
+    /// }
+    /// ```
+
+    pub fn json_with_limit<J>(&self, max: usize) -> Result<J, String>
+    where
+        J: serde::de::DeserializeOwned,
+    {
+        let declared = self
+            .headers
+            .get("content-length")
+            .and_then(|v| v.trim().parse::<usize>().ok());
+
+        if let Some(declared) = declared {
+            if declared > max {
+                return Err(format!(
+                    "JSON body too large: declared {} bytes, limit is {} bytes",
+                    declared, max
+                ));
             }
-        } else {
-            Err(String::from("Wrong body type"))
         }
+
+        if self.body.declared_type() != RequestBodyType::JSON {
+            return Err(String::from("Wrong body type"));
+        }
+
+        let received = self.body.len();
+        if received > max {
+            return Err(format!(
+                "JSON body too large: received {} bytes, limit is {} bytes",
+                received, max
+            ));
+        }
+
+        self.json()
+    }
+
+    /// Returns the request body exactly as it was received, whatever its content type.
+    ///
+    /// The bytes are shared with the parsed body where possible, so calling this
+    /// doesn't copy a binary or multipart body. It works alongside the typed
+    /// accessors: a handler can deserialize with [`json`](Self::json) and still
+    /// verify a signature over the raw bytes. If a middleware changed the body,
+    /// the modified body is returned in its wire format instead.
+    ///
+    /// ## Example
+    /// ```rust
+    /// use ripress::{app::App, req::HttpRequest, types::RouterFns};
+    ///
+    /// let mut app = App::new();
+    /// app.post("/webhook", |req: HttpRequest, res| async move {
+    ///     let raw = req.raw_body();
+    ///     // e.g. check an HMAC signature header against `raw`
+    ///     res.ok().text(format!("{} bytes", raw.len()))
+    /// });
+    /// ```
+    pub fn raw_body(&self) -> Bytes {
+        match &self.raw_body {
+            Some(raw) => raw.clone(),
+            None => self.body.to_bytes(),
+        }
+    }
+
+    /// Returns the request body as a stream of byte chunks.
+    ///
+    /// On a route marked with
+    /// [`RouteHandle::stream_body`](crate::router::route_handle::RouteHandle::stream_body),
+    /// the chunks are read from the connection as the stream is polled, so the
+    /// body is never held in memory as a whole. Such a body can be read once: it
+    /// belongs to the first clone of the request to call this method, and later
+    /// calls return an empty stream. When a body limit applies, the stream ends
+    /// with an [`InvalidData`](std::io::ErrorKind::InvalidData) error once more
+    /// bytes than the limit have been received.
+    ///
+    /// On other routes the body has already been read when a handler runs, so the
+    /// chunks are slices of [`raw_body`](Self::raw_body), sharing its bytes.
+    /// The stream's item type matches [`HttpResponse::write`](crate::res::HttpResponse::write),
+    /// which makes it easy to echo or proxy a body back out.
+    ///
+    /// ## Example
+    /// ```rust
+    /// use futures::StreamExt;
+    /// use ripress::context::HttpRequest;
+    ///
+    /// # async fn run() {
+    /// let req = HttpRequest::new();
+    /// let mut stream = req.body_stream();
+    ///
+    /// while let Some(chunk) = stream.next().await {
+    ///     let chunk = chunk.unwrap();
+    ///     println!("Received {} bytes", chunk.len());
+    /// }
+    /// # }
+    /// ```
+
+    pub fn body_stream(&self) -> impl Stream<Item = Result<Bytes, std::io::Error>> + Send + 'static {
+        if let Some(incoming) = &self.incoming {
+            return match incoming.take(self.settings.body_limit) {
+                Some(stream) => stream.left_stream().left_stream(),
+                None => futures::stream::empty().right_stream().left_stream(),
+            };
+        }
+
+        let body = self.raw_body();
+        let len = body.len();
+
+        futures::stream::iter((0..len).step_by(BODY_STREAM_CHUNK_SIZE).map(move |start| {
+            let end = (start + BODY_STREAM_CHUNK_SIZE).min(len);
+            Ok(body.slice(start..end))
+        }))
+        .right_stream()
+    }
+
+    /// Parses a newline-delimited JSON (NDJSON) body one record at a time.
+    ///
+    /// Records are deserialized lazily as the iterator advances, so a large upload
+    /// never has to be collected into a `Vec`. Blank lines are skipped, and a
+    /// malformed line yields a [`JsonLineError`] carrying its line number while the
+    /// remaining lines are still parsed. The content type is not checked, so both
+    /// `application/x-ndjson` and `application/jsonl` bodies work.
+    ///
+    /// ## Example
+    /// ```rust
+    /// use ripress::{app::App, req::HttpRequest, types::RouterFns};
+    /// use serde::Deserialize;
+    ///
+    /// #[derive(Deserialize)]
+    /// struct Event {
+    ///     name: String,
+    /// }
+    ///
+    /// let mut app = App::new();
+    /// app.post("/events", |req: HttpRequest, res| async move {
+    ///     let mut errors = Vec::new();
+    ///     for event in req.json_lines::<Event>() {
+    ///         match event {
+    ///             Ok(event) => println!("ingesting {}", event.name),
+    ///             Err(e) => errors.push(e.to_string()),
+    ///         }
+    ///     }
+    ///     res.ok().json(errors)
+    /// });
+    /// ```
+    pub fn json_lines<T: serde::de::DeserializeOwned>(&self) -> JsonLines<T> {
+        JsonLines::new(self.raw_body())
+    }
+
+    /// Parses a body holding a top-level JSON array one element at a time.
+    ///
+    /// Elements are deserialized lazily as the iterator advances. If the body is not
+    /// an array or an element is malformed, a [`JsonLineError`] with the line the
+    /// problem starts on is yielded and iteration stops.
+    ///
+    /// ## Example
+    /// ```rust
+    /// use ripress::req::HttpRequest;
+    ///
+    /// let req = HttpRequest::new();
+    /// for id in req.json_items::<u64>() {
+    ///     match id {
+    ///         Ok(id) => println!("id {}", id),
+    ///         Err(e) => println!("bad element: {}", e),
+    ///     }
+    /// }
+    /// ```
+    pub fn json_items<T: serde::de::DeserializeOwned>(&self) -> JsonItems<T> {
+        JsonItems::new(self.raw_body())
     }
 
     /// Returns request's text body.
@@ -684,7 +1536,7 @@ impl HttpRequest {
     /// Returns an `Result<String>`, where `Ok(String)` contains the body if it is valid text, or `Err(error)` if it is not.
 
     pub fn text(&self) -> Result<&str, String> {
-        let body = &self.body;
+        let body = &*self.body;
 
         if body.body_type() == RequestBodyType::TEXT {
             if let RequestBody::TEXT(ref text_value) = body {
@@ -714,9 +1566,13 @@ impl HttpRequest {
     ///
     /// This function returns a HashMap of the form data.
     /// Returns an `Result<HashMap<String, String>>`, where `Ok(HashMap<String, String>)` contains the form_data if it is valid form data, or `Err(error)` if it is not.
+    /// A urlencoded body that could not be decoded yields `Err` with the reason instead of an empty form.
 
     pub fn form_data(&self) -> Result<&FormData, String> {
-        let body = &self.body;
+        if let Some(error) = self.body.error() {
+            return Err(error.to_string());
+        }
+        let body = &*self.body;
 
         match body.body_type() {
             RequestBodyType::FORM => {
@@ -744,30 +1600,164 @@ impl HttpRequest {
     /// This is useful for middlewares that wish to expose computed values through
     /// the `form_data()` API, such as attaching file upload metadata.
     pub fn insert_form_field(&mut self, key: &str, value: &str) {
+        self.raw_body = None;
         if self.body.body_type() != RequestBodyType::FORM {
-            self.body = RequestBody::FORM(FormData::new());
+            self.body = RequestBody::FORM(FormData::new()).into();
         }
 
-        if let RequestBody::FORM(ref mut form_data) = self.body {
+        if let RequestBody::FORM(ref mut form_data) = *self.body {
             form_data.insert(key.to_string(), value.to_string());
         }
     }
 
+    /// Replaces the request body, e.g. after decrypting or decompressing it in a
+    /// pre-middleware.
+    ///
+    /// The `Content-Type` header is updated to match the new body, unless it already
+    /// describes a body of that kind (so an XML text body stays XML), and the stale
+    /// `Content-Length` is dropped. An empty body drops the `Content-Type` too.
+    ///
+    /// ## Example
+    /// ```
+    /// use ripress::req::{body::{RequestBody, RequestBodyType}, HttpRequest};
+    ///
+    /// let mut req = HttpRequest::new();
+    /// req.set_body(RequestBody::TEXT("hello".into()));
+    /// assert!(req.is(RequestBodyType::TEXT));
+    /// assert_eq!(req.headers.content_type(), Some("text/plain"));
+    /// ```
+    pub fn set_body(&mut self, body: RequestBody) {
+        self.raw_body = None;
+        self.headers.remove("content-length");
+
+        let body_type = body.body_type();
+        if body_type == RequestBodyType::EMPTY {
+            self.headers.remove("content-type");
+        } else {
+            let declared = self
+                .headers
+                .content_type()
+                .map(determine_content_type_request);
+            let consistent = declared.is_some_and(|declared| {
+                declared == body_type
+                    || (body_type == RequestBodyType::TEXT && declared == RequestBodyType::XML)
+                    || (body_type == RequestBodyType::BINARY
+                        && declared == RequestBodyType::MSGPACK)
+            });
+            if !consistent {
+                self.headers.insert("content-type", body_type.to_string());
+            }
+        }
+
+        self.body = body.into();
+    }
+
+    /// Replaces the request body with `json`, setting `Content-Type` to
+    /// `application/json`. See [`HttpRequest::set_body`].
+    ///
+    /// ## Panics
+    ///
+    /// Panics if `json` cannot be serialized, e.g. a map with non-string keys.
+    ///
+    /// ## Example
+    /// ```
+    /// use ripress::{app::App, req::HttpRequest};
+    /// # fn decrypt(ciphertext: &[u8]) -> Vec<u8> { ciphertext.to_vec() }
+    ///
+    /// let mut app = App::new();
+    /// app.use_pre_middleware(Some("/secure"), |mut req: HttpRequest, res, next| async move {
+    ///     let plaintext = decrypt(req.bytes().unwrap_or_default());
+    ///     match serde_json::from_slice::<serde_json::Value>(&plaintext) {
+    ///         Ok(json) => req.set_json(json),
+    ///         Err(_) => return (req, Some(res.bad_request().text("Invalid payload"))),
+    ///     }
+    ///     next.call(req, res).await
+    /// });
+    /// ```
+    pub fn set_json<J: serde::Serialize>(&mut self, json: J) {
+        let json = serde_json::to_value(json).expect("Failed to serialize to JSON");
+        self.set_body(RequestBody::JSON(json));
+    }
+
+    /// Returns the most bytes this request's body may have.
+    ///
+    /// This is the limit of the matched route when it sets one with
+    /// [`RouteHandle::body_limit`](crate::router::route_handle::RouteHandle::body_limit),
+    /// otherwise the one set with [`App::use_body_limit`](crate::app::App::use_body_limit),
+    /// and `None` when neither applies.
+    ///
+    /// ## Example
+    /// ```
+    /// use ripress::{app::App, req::HttpRequest, types::{HttpMethods, RouterFns}};
+    ///
+    /// let mut app = App::new();
+    /// app.use_body_limit(Some(1024 * 1024));
+    /// app.route(HttpMethods::POST, "/upload")
+    ///     .body_limit(50 * 1024 * 1024)
+    ///     .handler(|req: HttpRequest, res| async move {
+    ///         res.ok().text(format!("accepting up to {:?} bytes", req.body_limit()))
+    ///     });
+    /// ```
+    pub fn body_limit(&self) -> Option<usize> {
+        self.settings.body_limit
+    }
+
+    /// Returns the route parameter `name` parsed into `T`.
+    ///
+    /// Shorthand for [`RouteParams::get_parsed`]. A missing parameter gives an error of
+    /// kind [`NotFound`](crate::error::RipressErrorKind::NotFound) and a value that does
+    /// not parse gives one of kind [`ParseError`](crate::error::RipressErrorKind::ParseError),
+    /// so either can be returned with `?` and turned into a 404 or 400 response.
+    ///
+    /// ## Example
+    /// ```
+    /// use ripress::{app::App, context::{HttpRequest, HttpResponse}, error::RipressError, types::RouterFns};
+    ///
+    /// async fn show_user(req: HttpRequest, res: HttpResponse) -> Result<HttpResponse, RipressError> {
+    ///     let id: u32 = req.param("id")?;
+    ///     Ok(res.ok().json(serde_json::json!({ "id": id })))
+    /// }
+    ///
+    /// let mut app = App::new();
+    /// app.get("/users/:id", |req: HttpRequest, res| async move {
+    ///     show_user(req, res).await.unwrap_or_else(RipressError::into_response)
+    /// });
+    /// ```
+    pub fn param<T>(&self, name: &str) -> Result<T, RipressError>
+    where
+        T: std::str::FromStr,
+        T::Err: std::fmt::Debug,
+    {
+        self.params.get_parsed(name)
+    }
+
     pub(crate) fn set_param(&mut self, key: &str, value: &str) {
         self.params.insert(key.to_string(), value.to_string());
     }
 
-    fn get_cookies_from_req_info(req: &RequestInfo) -> Vec<Cookie<'_>> {
-        let mut cookies = Vec::new();
+    /// Parses a `Cookie` header into a name to value map.
+    ///
+    /// A value is everything after the first `=`, so values that contain `=`
+    /// themselves, such as base64 padding in a JWT, are kept whole. A value
+    /// wrapped in double quotes is stored without them, as RFC 6265 allows
+    /// quoting values. Pairs without a name are skipped.
+    pub(crate) fn cookies_from_header(header: &str) -> AHashMap<String, String> {
+        let mut cookies = AHashMap::new();
 
-        if let Some(header_value) = req.headers().get("cookie") {
-            if let Ok(header_str) = header_value.to_str() {
-                for cookie_str in header_str.split(';') {
-                    if let Ok(cookie) = Cookie::parse(cookie_str.trim()) {
-                        cookies.push(cookie);
-                    }
-                }
+        for pair in header.split(';') {
+            let Some((name, value)) = pair.split_once('=') else {
+                continue;
+            };
+            let name = name.trim();
+            if name.is_empty() {
+                continue;
             }
+            let value = value.trim();
+            let value = value
+                .strip_prefix('"')
+                .and_then(|unquoted| unquoted.strip_suffix('"'))
+                .unwrap_or(value);
+            cookies.insert(name.to_string(), value.to_string());
         }
 
         cookies
@@ -33,4 +33,34 @@ impl Url {
     pub fn value(&self) -> &String {
         &self.url_string
     }
+
+    /// Returns the host of the url, without the port.
+    ///
+    /// IPv6 literals keep their brackets, e.g. `[::1]` for `http://[::1]:8080/`.
+    pub fn hostname(&self) -> Option<&str> {
+        let rest = self
+            .url_string
+            .split_once("://")
+            .map_or(self.url_string.as_str(), |(_, rest)| rest);
+        let authority = rest.split(['/', '?', '#']).next().unwrap_or("");
+        let authority = authority
+            .rsplit_once('@')
+            .map_or(authority, |(_, host)| host);
+        Some(strip_port(authority)).filter(|host| !host.is_empty())
+    }
+}
+
+/// Removes a trailing `:port` from an authority, leaving IPv6 literals such as
+/// `[::1]` intact.
+pub(crate) fn strip_port(authority: &str) -> &str {
+    if authority.starts_with('[') {
+        return match authority.find(']') {
+            Some(end) => &authority[..=end],
+            None => authority,
+        };
+    }
+    match authority.rsplit_once(':') {
+        Some((host, port)) if port.bytes().all(|b| b.is_ascii_digit()) => host,
+        _ => authority,
+    }
 }
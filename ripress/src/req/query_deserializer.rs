@@ -0,0 +1,401 @@
+use std::{collections::BTreeMap, fmt};
+
+use serde::de::{
+    self, value::StrDeserializer, DeserializeSeed, Deserializer, IntoDeserializer, MapAccess,
+    SeqAccess, Visitor,
+};
+
+use crate::req::query_params::QueryParams;
+
+/// A query string parsed into a tree, so `tag=a&tag=b`, `tag[]=a` and
+/// `filter[status]=x` can be deserialized as sequences and maps.
+#[derive(Debug, Clone, PartialEq)]
+pub(crate) enum QueryValue {
+    Str(String),
+    Seq(Vec<QueryValue>),
+    Map(BTreeMap<String, QueryValue>),
+}
+
+impl QueryValue {
+    /// Builds the tree from parsed query params.
+    ///
+    /// Repeated keys and `key[]` become sequences, `key[sub]` becomes a nested map.
+    pub(crate) fn from_params(params: &QueryParams) -> Self {
+        let mut root = BTreeMap::new();
+        for (key, values) in params.iter_all() {
+            let path = split_key(key);
+            insert(&mut root, &path, values);
+        }
+        QueryValue::Map(root)
+    }
+
+    fn first_str(&self) -> Option<&str> {
+        match self {
+            QueryValue::Str(value) => Some(value),
+            QueryValue::Seq(values) => values.first().and_then(QueryValue::first_str),
+            QueryValue::Map(_) => None,
+        }
+    }
+
+    fn kind(&self) -> &'static str {
+        match self {
+            QueryValue::Str(_) => "a value",
+            QueryValue::Seq(_) => "a list",
+            QueryValue::Map(_) => "a map",
+        }
+    }
+}
+
+/// Splits `a[b][]` into `["a", "b", ""]`; keys without valid brackets are kept whole.
+fn split_key(key: &str) -> Vec<&str> {
+    let Some(open) = key.find('[') else {
+        return vec![key];
+    };
+    if open == 0 || !key.ends_with(']') {
+        return vec![key];
+    }
+
+    let mut path = vec![&key[..open]];
+    for part in key[open + 1..key.len() - 1].split("][") {
+        if part.contains('[') || part.contains(']') {
+            return vec![key];
+        }
+        path.push(part);
+    }
+    path
+}
+
+fn insert(map: &mut BTreeMap<String, QueryValue>, path: &[&str], values: &[String]) {
+    let (key, rest) = match path {
+        [key, rest @ ..] => (*key, rest),
+        [] => return,
+    };
+
+    match rest {
+        [] => {
+            let new_values = values.iter().cloned().map(QueryValue::Str);
+            match map.remove(key) {
+                Some(QueryValue::Seq(mut existing)) => {
+                    existing.extend(new_values);
+                    map.insert(key.to_string(), QueryValue::Seq(existing));
+                }
+                Some(existing @ QueryValue::Str(_)) => {
+                    let mut seq = vec![existing];
+                    seq.extend(new_values);
+                    map.insert(key.to_string(), QueryValue::Seq(seq));
+                }
+                // A key used both as a value and a map: the map wins.
+                Some(existing @ QueryValue::Map(_)) => {
+                    map.insert(key.to_string(), existing);
+                }
+                None if values.len() == 1 => {
+                    map.insert(key.to_string(), QueryValue::Str(values[0].clone()));
+                }
+                None => {
+                    map.insert(key.to_string(), QueryValue::Seq(new_values.collect()));
+                }
+            }
+        }
+        [""] => {
+            let entry = map
+                .entry(key.to_string())
+                .or_insert_with(|| QueryValue::Seq(Vec::new()));
+            match entry {
+                QueryValue::Seq(existing) => {
+                    existing.extend(values.iter().cloned().map(QueryValue::Str))
+                }
+                QueryValue::Str(_) => {
+                    let first = std::mem::replace(entry, QueryValue::Seq(Vec::new()));
+                    let mut seq = vec![first];
+                    seq.extend(values.iter().cloned().map(QueryValue::Str));
+                    *entry = QueryValue::Seq(seq);
+                }
+                QueryValue::Map(_) => {}
+            }
+        }
+        _ => {
+            let entry = map
+                .entry(key.to_string())
+                .or_insert_with(|| QueryValue::Map(BTreeMap::new()));
+            if !matches!(entry, QueryValue::Map(_)) {
+                *entry = QueryValue::Map(BTreeMap::new());
+            }
+            if let QueryValue::Map(nested) = entry {
+                insert(nested, rest, values);
+            }
+        }
+    }
+}
+
+/// Error produced while deserializing query params, naming the offending field.
+#[derive(Debug, Clone, PartialEq)]
+pub(crate) struct QueryDeError {
+    field: Option<String>,
+    message: String,
+}
+
+impl QueryDeError {
+    fn in_field(mut self, key: &str) -> Self {
+        self.field = Some(match self.field {
+            Some(inner) => format!("{}.{}", key, inner),
+            None => key.to_string(),
+        });
+        self
+    }
+}
+
+impl fmt::Display for QueryDeError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match &self.field {
+            Some(field) => write!(f, "field `{}`: {}", field, self.message),
+            None => write!(f, "{}", self.message),
+        }
+    }
+}
+
+impl std::error::Error for QueryDeError {}
+
+impl de::Error for QueryDeError {
+    fn custom<T: fmt::Display>(msg: T) -> Self {
+        QueryDeError {
+            field: None,
+            message: msg.to_string(),
+        }
+    }
+}
+
+macro_rules! deserialize_parsed {
+    ($($method:ident => $visit:ident,)*) => {
+        $(
+            fn $method<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value, Self::Error> {
+                let value = self.expect_str()?;
+                match value.parse() {
+                    Ok(parsed) => visitor.$visit(parsed),
+                    Err(e) => Err(de::Error::custom(format!("invalid value {:?}: {}", value, e))),
+                }
+            }
+        )*
+    };
+}
+
+impl QueryValue {
+    fn expect_str(&self) -> Result<&str, QueryDeError> {
+        self.first_str()
+            .ok_or_else(|| de::Error::custom(format!("expected a value, found {}", self.kind())))
+    }
+}
+
+impl<'de> Deserializer<'de> for QueryValue {
+    type Error = QueryDeError;
+
+    fn deserialize_any<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value, Self::Error> {
+        match self {
+            QueryValue::Str(value) => visitor.visit_string(value),
+            QueryValue::Seq(values) => visitor.visit_seq(QuerySeq(values.into_iter())),
+            QueryValue::Map(map) => visitor.visit_map(QueryMap::new(map)),
+        }
+    }
+
+    fn deserialize_bool<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value, Self::Error> {
+        let value = self.expect_str()?;
+        match value.to_lowercase().as_str() {
+            "true" | "1" | "yes" | "on" => visitor.visit_bool(true),
+            "false" | "0" | "no" | "off" | "" => visitor.visit_bool(false),
+            _ => Err(de::Error::custom(format!(
+                "invalid value {:?}: expected a boolean",
+                value
+            ))),
+        }
+    }
+
+    deserialize_parsed! {
+        deserialize_i8 => visit_i8,
+        deserialize_i16 => visit_i16,
+        deserialize_i32 => visit_i32,
+        deserialize_i64 => visit_i64,
+        deserialize_u8 => visit_u8,
+        deserialize_u16 => visit_u16,
+        deserialize_u32 => visit_u32,
+        deserialize_u64 => visit_u64,
+        deserialize_f32 => visit_f32,
+        deserialize_f64 => visit_f64,
+        deserialize_char => visit_char,
+    }
+
+    fn deserialize_str<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value, Self::Error> {
+        self.deserialize_string(visitor)
+    }
+
+    fn deserialize_string<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value, Self::Error> {
+        visitor.visit_string(self.expect_str()?.to_string())
+    }
+
+    fn deserialize_bytes<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value, Self::Error> {
+        visitor.visit_bytes(self.expect_str()?.as_bytes())
+    }
+
+    fn deserialize_byte_buf<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value, Self::Error> {
+        self.deserialize_bytes(visitor)
+    }
+
+    fn deserialize_option<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value, Self::Error> {
+        visitor.visit_some(self)
+    }
+
+    fn deserialize_unit<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value, Self::Error> {
+        visitor.visit_unit()
+    }
+
+    fn deserialize_unit_struct<V: Visitor<'de>>(
+        self,
+        _name: &'static str,
+        visitor: V,
+    ) -> Result<V::Value, Self::Error> {
+        visitor.visit_unit()
+    }
+
+    fn deserialize_newtype_struct<V: Visitor<'de>>(
+        self,
+        _name: &'static str,
+        visitor: V,
+    ) -> Result<V::Value, Self::Error> {
+        visitor.visit_newtype_struct(self)
+    }
+
+    fn deserialize_seq<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value, Self::Error> {
+        let values = match self {
+            QueryValue::Seq(values) => values,
+            QueryValue::Str(_) => vec![self],
+            // `a[0]=x&a[1]=y` arrives as a map keyed by index.
+            QueryValue::Map(map) => {
+                let mut indexed: Vec<(usize, QueryValue)> = Vec::with_capacity(map.len());
+                for (key, value) in map {
+                    let index = key.parse().map_err(|_| {
+                        de::Error::custom(format!("expected a list, found key {:?}", key))
+                    })?;
+                    indexed.push((index, value));
+                }
+                indexed.sort_by_key(|(index, _)| *index);
+                indexed.into_iter().map(|(_, value)| value).collect()
+            }
+        };
+        visitor.visit_seq(QuerySeq(values.into_iter()))
+    }
+
+    fn deserialize_tuple<V: Visitor<'de>>(
+        self,
+        _len: usize,
+        visitor: V,
+    ) -> Result<V::Value, Self::Error> {
+        self.deserialize_seq(visitor)
+    }
+
+    fn deserialize_tuple_struct<V: Visitor<'de>>(
+        self,
+        _name: &'static str,
+        _len: usize,
+        visitor: V,
+    ) -> Result<V::Value, Self::Error> {
+        self.deserialize_seq(visitor)
+    }
+
+    fn deserialize_map<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value, Self::Error> {
+        match self {
+            QueryValue::Map(map) => visitor.visit_map(QueryMap::new(map)),
+            other => Err(de::Error::custom(format!(
+                "expected a map, found {}",
+                other.kind()
+            ))),
+        }
+    }
+
+    fn deserialize_struct<V: Visitor<'de>>(
+        self,
+        _name: &'static str,
+        _fields: &'static [&'static str],
+        visitor: V,
+    ) -> Result<V::Value, Self::Error> {
+        self.deserialize_map(visitor)
+    }
+
+    fn deserialize_enum<V: Visitor<'de>>(
+        self,
+        _name: &'static str,
+        _variants: &'static [&'static str],
+        visitor: V,
+    ) -> Result<V::Value, Self::Error> {
+        let variant: StrDeserializer<'_, QueryDeError> = self.expect_str()?.into_deserializer();
+        variant.deserialize_enum(_name, _variants, visitor)
+    }
+
+    fn deserialize_identifier<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value, Self::Error> {
+        self.deserialize_string(visitor)
+    }
+
+    fn deserialize_ignored_any<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value, Self::Error> {
+        visitor.visit_unit()
+    }
+}
+
+struct QuerySeq(std::vec::IntoIter<QueryValue>);
+
+impl<'de> SeqAccess<'de> for QuerySeq {
+    type Error = QueryDeError;
+
+    fn next_element_seed<T: DeserializeSeed<'de>>(
+        &mut self,
+        seed: T,
+    ) -> Result<Option<T::Value>, Self::Error> {
+        self.0
+            .next()
+            .map(|value| seed.deserialize(value))
+            .transpose()
+    }
+
+    fn size_hint(&self) -> Option<usize> {
+        Some(self.0.len())
+    }
+}
+
+struct QueryMap {
+    entries: std::collections::btree_map::IntoIter<String, QueryValue>,
+    current: Option<(String, QueryValue)>,
+}
+
+impl QueryMap {
+    fn new(map: BTreeMap<String, QueryValue>) -> Self {
+        QueryMap {
+            entries: map.into_iter(),
+            current: None,
+        }
+    }
+}
+
+impl<'de> MapAccess<'de> for QueryMap {
+    type Error = QueryDeError;
+
+    fn next_key_seed<K: DeserializeSeed<'de>>(
+        &mut self,
+        seed: K,
+    ) -> Result<Option<K::Value>, Self::Error> {
+        match self.entries.next() {
+            Some((key, value)) => {
+                let parsed = seed.deserialize(QueryValue::Str(key.clone()))?;
+                self.current = Some((key, value));
+                Ok(Some(parsed))
+            }
+            None => Ok(None),
+        }
+    }
+
+    fn next_value_seed<V: DeserializeSeed<'de>>(
+        &mut self,
+        seed: V,
+    ) -> Result<V::Value, Self::Error> {
+        let (key, value) = self
+            .current
+            .take()
+            .ok_or_else(|| de::Error::custom("value requested before key"))?;
+        seed.deserialize(value).map_err(|e| e.in_field(&key))
+    }
+}
@@ -5,9 +5,12 @@ use std::ops::Deref;
 use std::str::FromStr;
 
 use ahash::AHashMap;
+use serde::de::DeserializeOwned;
+use url::form_urlencoded::byte_serialize;
 
 use crate::error::RipressError;
 use crate::helpers::FromRequest;
+use crate::req::query_deserializer::QueryValue;
 use crate::url::decode;
 
 /// Query parameters from URL query string with support for multiple values
@@ -17,6 +20,8 @@ pub struct QueryParams {
     /// Internal storage: parameter name -> list of values
     /// Supports multiple values for the same parameter (e.g., multiple tags)
     pub(crate) inner: AHashMap<String, Vec<String>>,
+    /// The query string as received, without the leading `?`
+    pub(crate) raw: String,
 }
 
 /// Error type for query parameter parsing and retrieval failures.
@@ -87,6 +92,11 @@ pub(crate) enum QueryParamError {
         /// The target type that parsing was attempted for
         target_type: String,
     },
+
+    /// The parameters could not be deserialized into the requested type.
+    ///
+    /// The contained `String` describes the failure and names the offending field.
+    Deserialize(String),
 }
 
 impl fmt::Display for QueryParamError {
@@ -104,6 +114,9 @@ impl fmt::Display for QueryParamError {
                     param, value, target_type
                 )
             }
+            QueryParamError::Deserialize(message) => {
+                write!(f, "Failed to deserialize query parameters: {}", message)
+            }
         }
     }
 }
@@ -115,6 +128,14 @@ impl QueryParams {
     pub fn new() -> Self {
         Self {
             inner: AHashMap::new(),
+            raw: String::new(),
+        }
+    }
+
+    fn from_inner(inner: AHashMap<String, Vec<String>>) -> Self {
+        Self {
+            inner,
+            raw: String::new(),
         }
     }
 
@@ -124,7 +145,7 @@ impl QueryParams {
         for (key, value) in map {
             params.insert(key, vec![value]);
         }
-        Self { inner: params }
+        Self::from_inner(params)
     }
 
     /// Create QueryParams from a single-value HashMap (for backward compatibility)
@@ -133,16 +154,16 @@ impl QueryParams {
         for (key, value) in map {
             params.insert(key, vec![value]);
         }
-        Self { inner: params }
+        Self::from_inner(params)
     }
 
     /// Create QueryParams from an iterator of key-value pairs
     pub fn from_iterator(iterator: impl Iterator<Item = (String, String)>) -> Self {
         let mut params = AHashMap::new();
         for (key, value) in iterator {
-            params.insert(key, vec![value]);
+            params.entry(key).or_insert_with(Vec::new).push(value);
         }
-        Self { inner: params }
+        Self::from_inner(params)
     }
 
     /// Parse query parameters from a query string
@@ -151,7 +172,7 @@ impl QueryParams {
         let mut params = AHashMap::new();
 
         if query_string.is_empty() {
-            return Self { inner: params };
+            return Self::from_inner(params);
         }
 
         for pair in query_string.split('&') {
@@ -169,7 +190,10 @@ impl QueryParams {
             }
         }
 
-        Self { inner: params }
+        Self {
+            inner: params,
+            raw: query_string.to_string(),
+        }
     }
 
     /// Insert a single parameter value (replaces existing)
@@ -193,14 +217,102 @@ impl QueryParams {
         self.inner.entry(key.into()).or_default().push(value.into());
     }
 
+    /// Returns the original query string, without the leading `?`
+    ///
+    /// This is exactly what the client sent, with its ordering, duplicates and
+    /// percent-encoding intact, so it can be forwarded as-is. It is empty for
+    /// params not parsed from a request or query string, and does not reflect
+    /// later changes; use `to_string()` to encode the current params.
+    pub fn raw(&self) -> &str {
+        &self.raw
+    }
+
     /// Get the first value for a parameter (most common case)
     pub fn get(&self, name: &str) -> Option<&str> {
         self.inner.get(name)?.first().map(|s| s.as_str())
     }
 
-    /// Get all values for a parameter
-    pub fn get_all(&self, name: &str) -> Option<&Vec<String>> {
-        self.inner.get(name)
+    /// Get all values for a parameter, in the order they appeared
+    ///
+    /// Returns an empty vector if the parameter is missing.
+    pub fn get_all(&self, name: &str) -> Vec<&str> {
+        self.inner
+            .get(name)
+            .map(|values| values.iter().map(String::as_str).collect())
+            .unwrap_or_default()
+    }
+
+    /// Deserialize all parameters into a type using serde
+    ///
+    /// Repeated keys (`tag=a&tag=b`) and `key[]` fill sequences, and bracketed keys
+    /// (`filter[status]=open`) fill nested structs or maps. Scalars are parsed from
+    /// their string values; booleans accept the same values as [`QueryParams::get_bool`].
+    /// Errors name the field that failed, e.g. `filter.status`.
+    ///
+    /// ## Example
+    /// ```
+    /// use ripress::req::query_params::QueryParams;
+    /// use serde::Deserialize;
+    ///
+    /// #[derive(Deserialize)]
+    /// struct Filter {
+    ///     status: String,
+    /// }
+    ///
+    /// #[derive(Deserialize)]
+    /// struct Search {
+    ///     tag: Vec<String>,
+    ///     page: u32,
+    ///     filter: Filter,
+    /// }
+    ///
+    /// let query = QueryParams::from_query_string("tag=a&tag=b&page=2&filter[status]=open");
+    /// let search: Search = query.deserialize().unwrap();
+    ///
+    /// assert_eq!(search.tag, vec!["a", "b"]);
+    /// assert_eq!(search.page, 2);
+    /// assert_eq!(search.filter.status, "open");
+    /// ```
+    pub fn deserialize<T>(&self) -> Result<T, RipressError>
+    where
+        T: DeserializeOwned,
+    {
+        T::deserialize(QueryValue::from_params(self))
+            .map_err(|e| RipressError::from(QueryParamError::Deserialize(e.to_string())))
+    }
+
+    /// Deserialize the parameters into a type, consuming them.
+    ///
+    /// The same as [`QueryParams::deserialize`]: missing `Option` fields become
+    /// `None`, unit enum variants are matched by name and numbers are parsed
+    /// from their string values. A value that fails to parse produces an error
+    /// naming its field.
+    ///
+    /// ## Example
+    /// ```
+    /// use ripress::req::query_params::QueryParams;
+    /// use serde::Deserialize;
+    ///
+    /// #[derive(Debug, Deserialize)]
+    /// struct Search {
+    ///     q: String,
+    ///     page: Option<u32>,
+    /// }
+    ///
+    /// let search: Search = QueryParams::from_query_string("q=rust&page=2").into().unwrap();
+    /// assert_eq!(search.q, "rust");
+    /// assert_eq!(search.page, Some(2));
+    ///
+    /// let err = QueryParams::from_query_string("q=rust&page=two")
+    ///     .into::<Search>()
+    ///     .unwrap_err();
+    /// assert!(err.message.contains("page"));
+    /// ```
+    pub fn into<T>(self) -> Result<T, RipressError>
+    where
+        T: DeserializeOwned,
+    {
+        self.deserialize()
     }
 
     /// Get the first value and parse it to a specific type
@@ -222,15 +334,47 @@ impl QueryParams {
         })
     }
 
+    /// Get the first value parsed to a specific type, or `None` if the parameter is absent
+    ///
+    /// Unlike [`get_parsed`](Self::get_parsed), a missing parameter and one that fails
+    /// to parse are told apart by the `Option` and the inner `Result`.
+    pub fn get_as<T: FromStr>(&self, name: &str) -> Option<Result<T, T::Err>> {
+        self.get(name).map(str::parse)
+    }
+
+    /// Get the first value parsed to a specific type, or `default` if the parameter is absent
+    ///
+    /// A value that is present but fails to parse is an error rather than the default.
+    ///
+    /// ```
+    /// use ripress::req::query_params::QueryParams;
+    ///
+    /// let query = QueryParams::from_query_string("page=3&limit=lots");
+    /// assert_eq!(query.get_or("page", 1u32).unwrap(), 3);
+    /// assert_eq!(query.get_or("offset", 0u32).unwrap(), 0);
+    /// assert!(query.get_or("limit", 10u32).is_err());
+    /// ```
+    pub fn get_or<T>(&self, name: &str, default: T) -> Result<T, RipressError>
+    where
+        T: FromStr,
+        T::Err: std::fmt::Debug,
+    {
+        match self.get(name) {
+            None => Ok(default),
+            Some(_) => self.get_parsed(name),
+        }
+    }
+
     /// Get all values and parse them to a specific type
     pub fn get_all_parsed<T>(&self, name: &str) -> Result<Vec<T>, RipressError>
     where
         T: FromStr,
         T::Err: std::fmt::Debug,
     {
-        let values = self
-            .get_all(name)
-            .ok_or_else(|| QueryParamError::NotFound(name.to_string()))?;
+        let values = self.get_all(name);
+        if values.is_empty() {
+            return Err(QueryParamError::NotFound(name.to_string()).into());
+        }
 
         let mut parsed_values = Vec::new();
         for value in values {
@@ -238,7 +382,7 @@ impl QueryParams {
                 .parse::<T>()
                 .map_err(|_| QueryParamError::ParseError {
                     param: name.to_string(),
-                    value: value.clone(),
+                    value: value.to_string(),
                     target_type: std::any::type_name::<T>().to_string(),
                 })?;
             parsed_values.push(parsed);
@@ -434,16 +578,20 @@ impl Default for QueryParams {
 }
 
 impl fmt::Display for QueryParams {
+    /// Encodes the params as a query string, percent-encoding names and values.
+    ///
+    /// Parameters with an empty value are written as a bare name.
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
         let param_strings: Vec<String> = self
             .inner
             .iter()
             .flat_map(|(name, values)| {
+                let name = encode_component(name);
                 values.iter().map(move |value| {
                     if value.is_empty() {
                         name.clone()
                     } else {
-                        format!("{}={}", name, value)
+                        format!("{}={}", name, encode_component(value))
                     }
                 })
             })
@@ -452,6 +600,16 @@ impl fmt::Display for QueryParams {
     }
 }
 
+/// Percent-encodes a query name or value.
+///
+/// Spaces become `%20` rather than `+`, so the result decodes the same way with
+/// [`QueryParams::from_query_string`] and with form-urlencoded parsers.
+fn encode_component(value: &str) -> String {
+    byte_serialize(value.as_bytes())
+        .collect::<String>()
+        .replace('+', "%20")
+}
+
 impl std::ops::Index<&str> for QueryParams {
     type Output = str;
 
@@ -1,8 +1,9 @@
 #![warn(missing_docs)]
 
-use std::ops::Deref;
+use std::{ops::Deref, str::FromStr};
 
 use crate::helpers::FromRequest;
+use base64::{engine::general_purpose::STANDARD, Engine};
 use hyper::header::{HeaderName, HeaderValue};
 use hyper::HeaderMap;
 
@@ -128,6 +129,30 @@ impl RequestHeaders {
         self.inner.get(&name)?.to_str().ok()
     }
 
+    /// Parses the first value of a header into `T`.
+    ///
+    /// Returns `None` if the header is absent or not valid UTF-8, and
+    /// `Some(Err(_))` if it is present but `T` can't be parsed from it.
+    ///
+    /// # Example
+    /// ```
+    /// use ripress::req::request_headers::RequestHeaders;
+    ///
+    /// let mut headers = RequestHeaders::new();
+    /// headers.insert("X-Page", "3");
+    /// headers.insert("X-Limit", "ten");
+    /// assert_eq!(headers.get_typed::<u32, _>("x-page"), Some(Ok(3)));
+    /// assert!(matches!(headers.get_typed::<u32, _>("x-limit"), Some(Err(_))));
+    /// assert_eq!(headers.get_typed::<u32, _>("x-missing"), None);
+    /// ```
+    pub fn get_typed<T, K>(&self, key: K) -> Option<Result<T, T::Err>>
+    where
+        T: FromStr,
+        K: AsRef<str>,
+    {
+        self.get(key).map(|value| value.trim().parse())
+    }
+
     /// Returns **all values** for the given header name.
     ///
     /// # Example
@@ -184,6 +209,16 @@ impl RequestHeaders {
         self.get("authorization")
     }
 
+    /// Returns the `Content-Length` header as a number, or `None` if it is
+    /// absent or not a plain decimal value.
+    pub fn content_length(&self) -> Option<u64> {
+        let value = self.get("content-length")?.trim();
+        if value.is_empty() || !value.bytes().all(|b| b.is_ascii_digit()) {
+            return None;
+        }
+        value.parse().ok()
+    }
+
     /// Returns the value of the `User-Agent` header, if present.
     pub fn user_agent(&self) -> Option<&str> {
         self.get("user-agent")
@@ -335,6 +370,98 @@ impl From<RequestHeaders> for HeaderMap {
     }
 }
 
+/// Credentials from an `Authorization` header.
+///
+/// Parse one with [`HttpRequest::authorization`](super::HttpRequest::authorization)
+/// or [`str::parse`]. Scheme names are matched case-insensitively.
+///
+/// ## Example
+///
+/// ```
+/// use ripress::req::request_headers::Authorization;
+///
+/// let auth: Authorization = "Basic dXNlcjpwYXNz".parse().unwrap();
+/// assert_eq!(
+///     auth,
+///     Authorization::Basic {
+///         username: "user".to_string(),
+///         password: "pass".to_string(),
+///     }
+/// );
+///
+/// let auth: Authorization = "Bearer abc.def".parse().unwrap();
+/// assert_eq!(auth.bearer_token(), Some("abc.def"));
+/// ```
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Authorization {
+    /// `Basic` credentials, decoded from base64
+    Basic {
+        /// The part before the first `:`
+        username: String,
+        /// Everything after the first `:`, which may itself contain `:`
+        password: String,
+    },
+    /// A `Bearer` token, as sent
+    Bearer(String),
+    /// Any other scheme, with its credentials left as sent
+    Other {
+        /// The scheme name, as sent
+        scheme: String,
+        /// Everything after the scheme
+        credentials: String,
+    },
+}
+
+impl Authorization {
+    /// Returns the token of a `Bearer` authorization.
+    pub fn bearer_token(&self) -> Option<&str> {
+        match self {
+            Authorization::Bearer(token) => Some(token),
+            _ => None,
+        }
+    }
+}
+
+impl FromStr for Authorization {
+    type Err = String;
+
+    fn from_str(value: &str) -> Result<Self, Self::Err> {
+        let (scheme, credentials) = value
+            .trim()
+            .split_once(' ')
+            .ok_or_else(|| String::from("Missing authorization credentials"))?;
+        let credentials = credentials.trim();
+        if scheme.is_empty() || credentials.is_empty() {
+            return Err(String::from("Missing authorization credentials"));
+        }
+
+        if scheme.eq_ignore_ascii_case("basic") {
+            let decoded = STANDARD
+                .decode(credentials)
+                .map_err(|e| format!("Invalid base64 in Basic credentials: {}", e))?;
+            let decoded = String::from_utf8(decoded)
+                .map_err(|_| String::from("Basic credentials are not valid UTF-8"))?;
+            let (username, password) = decoded
+                .split_once(':')
+                .ok_or_else(|| String::from("Basic credentials are missing `:`"))?;
+            Ok(Authorization::Basic {
+                username: username.to_string(),
+                password: password.to_string(),
+            })
+        } else if scheme.eq_ignore_ascii_case("bearer") {
+            if credentials.contains(char::is_whitespace) {
+                return Err(String::from("Bearer token must not contain whitespace"));
+            }
+            Ok(Authorization::Bearer(credentials.to_string()))
+        } else {
+            Ok(Authorization::Other {
+                scheme: scheme.to_string(),
+                credentials: credentials.to_string(),
+            })
+        }
+    }
+}
+
 /// A wrapper around [`RequestHeaders`] that allows extracting headers from an [`HttpRequest`] using the [`FromRequest`] trait.
 ///
 /// This enables ergonomic extraction of headers as a parameter in route handlers:
@@ -0,0 +1,51 @@
+#![warn(missing_docs)]
+use std::path::PathBuf;
+
+/// A file saved by the file upload middleware.
+///
+/// One entry is recorded per stored file and exposed through
+/// [`HttpRequest::files`](crate::req::HttpRequest::files).
+///
+/// ## Example
+/// ```
+/// use ripress::context::{HttpRequest, HttpResponse};
+///
+/// async fn upload(req: HttpRequest, res: HttpResponse) -> HttpResponse {
+///     let names: Vec<String> = req
+///         .files()
+///         .iter()
+///         .map(|file| {
+///             format!(
+///                 "{} ({} bytes, {})",
+///                 file.original_filename.as_deref().unwrap_or("unnamed"),
+///                 file.size,
+///                 file.content_type.as_deref().unwrap_or("unknown type"),
+///             )
+///         })
+///         .collect();
+///
+///     res.ok().json(names)
+/// }
+/// ```
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct UploadedFile {
+    /// Name of the form field the file was sent in, if any
+    pub field_name: Option<String>,
+    /// Filename supplied by the client, with RFC 5987 `filename*` values decoded
+    ///
+    /// This is untrusted input; it is never used to build `stored_path`.
+    pub original_filename: Option<String>,
+    /// The part's `Content-Type` header as sent by the client
+    pub content_type: Option<String>,
+    /// Size of the stored file in bytes
+    pub size: u64,
+    /// Where the file was written, inside the configured upload directory
+    pub stored_path: PathBuf,
+}
+
+impl UploadedFile {
+    /// Returns the generated filename the file was stored under.
+    pub fn stored_filename(&self) -> Option<&str> {
+        self.stored_path.file_name().and_then(|name| name.to_str())
+    }
+}
@@ -0,0 +1,197 @@
+#![warn(missing_docs)]
+//! Declarative request validation with the `validator` crate.
+//!
+//! See [`Validated`].
+
+use std::{collections::BTreeMap, fmt, ops::Deref};
+
+use serde::de::DeserializeOwned;
+use serde_json::{json, Value};
+use validator::{Validate, ValidationError, ValidationErrors, ValidationErrorsKind};
+
+use crate::{
+    app::api_error::error_response,
+    helpers::FromRequest,
+    req::{body::RequestBody, HttpRequest},
+    res::HttpResponse,
+};
+
+/// An extractor that deserializes the request and validates it with
+/// [`validator::Validate`].
+///
+/// The value is read from a JSON or form body, or from the query string when
+/// the request has no body. A request that can't be deserialized is answered
+/// with `400 Bad Request`; one that fails validation with
+/// `422 Unprocessable Entity` and a JSON body listing every violation:
+///
+/// ```json
+/// {
+///   "error": "Validation failed",
+///   "fields": {
+///     "email": [{ "code": "email", "message": "must be a valid email address" }]
+///   }
+/// }
+/// ```
+///
+/// Nested structs and lists are reported under paths such as
+/// `address.city` and `items[0].name`.
+///
+/// ## Example
+///
+/// ```
+/// use ripress::{app::App, req::validated::Validated, types::RouterFns};
+/// use serde::Deserialize;
+/// use validator::Validate;
+///
+/// #[derive(Deserialize, Validate)]
+/// struct CreateUser {
+///     #[validate(email)]
+///     email: String,
+///     #[validate(length(min = 3, max = 20))]
+///     username: String,
+/// }
+///
+/// let mut app = App::new();
+/// app.post("/users", |user: Validated<CreateUser>, res| async move {
+///     res.created().text(format!("created {}", user.username))
+/// });
+/// ```
+#[derive(Debug, Clone)]
+pub struct Validated<T>(T);
+
+impl<T> Validated<T> {
+    /// Returns the validated value.
+    pub fn into_inner(self) -> T {
+        self.0
+    }
+}
+
+impl<T> Deref for Validated<T> {
+    type Target = T;
+
+    fn deref(&self) -> &Self::Target {
+        &self.0
+    }
+}
+
+/// Why a [`Validated`] extractor rejected a request.
+#[derive(Debug)]
+pub enum ValidationRejection {
+    /// The request couldn't be deserialized into the target type
+    Deserialize(String),
+    /// The value was deserialized but failed validation
+    Invalid(ValidationErrors),
+}
+
+impl fmt::Display for ValidationRejection {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ValidationRejection::Deserialize(message) => write!(f, "{}", message),
+            ValidationRejection::Invalid(errors) => write!(f, "Validation failed: {}", errors),
+        }
+    }
+}
+
+impl std::error::Error for ValidationRejection {}
+
+impl<T: DeserializeOwned + Validate> FromRequest for Validated<T> {
+    type Error = ValidationRejection;
+
+    fn from_request(req: &HttpRequest) -> Result<Self, Self::Error> {
+        let value: T = match &*req.body {
+            RequestBody::JSON(json) => T::deserialize(json).map_err(|e| e.to_string()),
+            RequestBody::FORM(form) | RequestBody::BinaryWithFields(_, form) => {
+                serde_urlencoded::from_str(&form.to_query_string()).map_err(|e| e.to_string())
+            }
+            RequestBody::EMPTY => {
+                serde_urlencoded::from_str(req.query.raw()).map_err(|e| e.to_string())
+            }
+            RequestBody::TEXT(_) | RequestBody::BINARY(_) => {
+                Err("Expected a JSON or form body".to_string())
+            }
+        }
+        .map_err(ValidationRejection::Deserialize)?;
+
+        value.validate().map_err(ValidationRejection::Invalid)?;
+        Ok(Validated(value))
+    }
+
+    fn rejection(error: Self::Error, res: HttpResponse) -> HttpResponse {
+        match error {
+            ValidationRejection::Deserialize(message) => {
+                error_response(res, 400, &format!("Invalid request: {}", message))
+            }
+            ValidationRejection::Invalid(errors) => {
+                let mut fields = BTreeMap::new();
+                collect_errors("", &errors, &mut fields);
+                res.status(422).json(json!({
+                    "error": "Validation failed",
+                    "fields": fields,
+                }))
+            }
+        }
+    }
+}
+
+/// Flattens nested validation errors into `path -> violations`.
+fn collect_errors(prefix: &str, errors: &ValidationErrors, out: &mut BTreeMap<String, Vec<Value>>) {
+    for (field, kind) in errors.errors() {
+        let path = if prefix.is_empty() {
+            field.to_string()
+        } else {
+            format!("{}.{}", prefix, field)
+        };
+        match kind {
+            ValidationErrorsKind::Field(violations) => {
+                out.entry(path)
+                    .or_default()
+                    .extend(violations.iter().map(describe));
+            }
+            ValidationErrorsKind::Struct(nested) => collect_errors(&path, nested, out),
+            ValidationErrorsKind::List(items) => {
+                for (index, nested) in items {
+                    collect_errors(&format!("{}[{}]", path, index), nested, out);
+                }
+            }
+        }
+    }
+}
+
+fn describe(error: &ValidationError) -> Value {
+    let message = match &error.message {
+        Some(message) => message.to_string(),
+        None => default_message(error),
+    };
+    json!({ "code": error.code, "message": message })
+}
+
+/// A readable message for the built-in validators, used when the field's
+/// `#[validate]` attribute doesn't set one.
+fn default_message(error: &ValidationError) -> String {
+    let param = |name: &str| error.params.get(name).map(|v| v.to_string());
+    match error.code.as_ref() {
+        "email" => "must be a valid email address".to_string(),
+        "url" => "must be a valid URL".to_string(),
+        "required" => "is required".to_string(),
+        "must_match" => match param("other") {
+            Some(other) => format!("must match {}", other.trim_matches('"')),
+            None => "must match the other field".to_string(),
+        },
+        "length" => match (param("equal"), param("min"), param("max")) {
+            (Some(equal), _, _) => format!("length must be {}", equal),
+            (None, Some(min), Some(max)) => format!("length must be between {} and {}", min, max),
+            (None, Some(min), None) => format!("length must be at least {}", min),
+            (None, None, Some(max)) => format!("length must be at most {}", max),
+            (None, None, None) => "has an invalid length".to_string(),
+        },
+        "range" => match (param("min"), param("max")) {
+            (Some(min), Some(max)) => format!("must be between {} and {}", min, max),
+            (Some(min), None) => format!("must be at least {}", min),
+            (None, Some(max)) => format!("must be at most {}", max),
+            (None, None) => "is out of range".to_string(),
+        },
+        "contains" => "doesn't contain the required value".to_string(),
+        "regex" => "has an invalid format".to_string(),
+        code => format!("failed the `{}` check", code),
+    }
+}
@@ -11,29 +11,31 @@ impl AsyncWrite for HttpRequest {
         buf: &[u8],
     ) -> std::task::Poll<Result<usize, std::io::Error>> {
         let this = self.get_mut();
+        this.raw_body = None;
 
         let new_bytes = bytes::Bytes::copy_from_slice(buf);
 
-        match &mut this.body {
+        match &mut *this.body {
             RequestBody::BINARY(existing_bytes) => {
                 let mut combined = existing_bytes.to_vec();
                 combined.extend_from_slice(buf);
-                this.body = RequestBody::BINARY(combined.into());
+                this.body = RequestBody::BINARY(combined.into()).into();
             }
             RequestBody::BinaryWithFields(existing_bytes, form_data) => {
                 let mut combined = existing_bytes.to_vec();
                 combined.extend_from_slice(buf);
-                this.body = RequestBody::BinaryWithFields(combined.into(), form_data.clone());
+                this.body =
+                    RequestBody::BinaryWithFields(combined.into(), form_data.clone()).into();
             }
             RequestBody::TEXT(text_data) => {
                 if let Ok(new_text) = String::from_utf8(buf.to_vec()) {
                     let existing_text = text_data.as_str_lossy();
                     let combined_text = format!("{}{}", existing_text, new_text);
-                    this.body = RequestBody::TEXT(TextData::new(combined_text));
+                    this.body = RequestBody::TEXT(TextData::new(combined_text)).into();
                 } else {
                     let mut combined = text_data.as_bytes().to_vec();
                     combined.extend_from_slice(buf);
-                    this.body = RequestBody::BINARY(combined.into());
+                    this.body = RequestBody::BINARY(combined.into()).into();
                 }
             }
             RequestBody::JSON(json_value) => {
@@ -41,9 +43,9 @@ impl AsyncWrite for HttpRequest {
                 let mut combined = json_str.as_bytes().to_vec();
                 combined.extend_from_slice(buf);
                 if let Ok(combined_text) = String::from_utf8(combined.clone()) {
-                    this.body = RequestBody::TEXT(TextData::new(combined_text));
+                    this.body = RequestBody::TEXT(TextData::new(combined_text)).into();
                 } else {
-                    this.body = RequestBody::BINARY(combined.into());
+                    this.body = RequestBody::BINARY(combined.into()).into();
                 }
             }
             RequestBody::FORM(form_data) => {
@@ -51,13 +53,13 @@ impl AsyncWrite for HttpRequest {
                 let mut combined = form_str.as_bytes().to_vec();
                 combined.extend_from_slice(buf);
                 if let Ok(combined_text) = String::from_utf8(combined.clone()) {
-                    this.body = RequestBody::TEXT(TextData::new(combined_text));
+                    this.body = RequestBody::TEXT(TextData::new(combined_text)).into();
                 } else {
-                    this.body = RequestBody::BINARY(combined.into());
+                    this.body = RequestBody::BINARY(combined.into()).into();
                 }
             }
             RequestBody::EMPTY => {
-                this.body = RequestBody::BINARY(new_bytes);
+                this.body = RequestBody::BINARY(new_bytes).into();
             }
         }
 
@@ -86,8 +88,9 @@ impl AsyncRead for HttpRequest {
         buf: &mut tokio::io::ReadBuf<'_>,
     ) -> std::task::Poll<std::io::Result<()>> {
         let this = self.get_mut();
+        this.raw_body = None;
 
-        let body_bytes = match &this.body {
+        let body_bytes = match &*this.body {
             RequestBody::TEXT(text_data) => text_data.as_bytes().to_vec(),
             RequestBody::JSON(json_value) => serde_json::to_vec(json_value)
                 .map_err(|e| std::io::Error::new(std::io::ErrorKind::InvalidData, e))?,
@@ -97,9 +100,9 @@ impl AsyncRead for HttpRequest {
                 buf.put_slice(&bytes[..bytes_to_copy]);
 
                 if bytes_to_copy == bytes.len() {
-                    this.body = RequestBody::EMPTY;
+                    this.body = RequestBody::EMPTY.into();
                 } else {
-                    this.body = RequestBody::BINARY(bytes.slice(bytes_to_copy..));
+                    this.body = RequestBody::BINARY(bytes.slice(bytes_to_copy..)).into();
                 }
                 return std::task::Poll::Ready(Ok(()));
             }
@@ -115,39 +118,40 @@ impl AsyncRead for HttpRequest {
             buf.put_slice(&body_bytes[start_pos..end_pos]);
 
             if bytes_to_copy == body_bytes.len() {
-                this.body = RequestBody::EMPTY;
+                this.body = RequestBody::EMPTY.into();
             } else {
                 let remaining_bytes = body_bytes[end_pos..].to_vec();
-                match &this.body {
+                match &*this.body {
                     RequestBody::TEXT(_) => {
                         if let Ok(remaining_text) = String::from_utf8(remaining_bytes.clone()) {
-                            this.body = RequestBody::TEXT(TextData::new(remaining_text));
+                            this.body = RequestBody::TEXT(TextData::new(remaining_text)).into();
                         } else {
-                            this.body = RequestBody::BINARY(remaining_bytes.clone().into());
+                            this.body = RequestBody::BINARY(remaining_bytes.clone().into()).into();
                         }
                     }
                     RequestBody::JSON(_) => {
                         if let Ok(remaining_text) = String::from_utf8(remaining_bytes.clone()) {
-                            this.body = RequestBody::TEXT(TextData::new(remaining_text));
+                            this.body = RequestBody::TEXT(TextData::new(remaining_text)).into();
                         } else {
-                            this.body = RequestBody::BINARY(remaining_bytes.clone().into());
+                            this.body = RequestBody::BINARY(remaining_bytes.clone().into()).into();
                         }
                     }
                     RequestBody::FORM(_) => {
                         if let Ok(remaining_text) = String::from_utf8(remaining_bytes.clone()) {
-                            this.body = RequestBody::TEXT(TextData::new(remaining_text));
+                            this.body = RequestBody::TEXT(TextData::new(remaining_text)).into();
                         } else {
-                            this.body = RequestBody::BINARY(remaining_bytes.clone().into());
+                            this.body = RequestBody::BINARY(remaining_bytes.clone().into()).into();
                         }
                     }
                     RequestBody::BINARY(_) => {
-                        this.body = RequestBody::BINARY(remaining_bytes.into());
+                        this.body = RequestBody::BINARY(remaining_bytes.into()).into();
                     }
                     RequestBody::BinaryWithFields(_, form_data) => {
                         this.body = RequestBody::BinaryWithFields(
                             remaining_bytes.into(),
                             form_data.clone(),
-                        );
+                        )
+                        .into();
                     }
                     RequestBody::EMPTY => {}
                 }
@@ -14,12 +14,74 @@ use crate::helpers::determine_content_type_response;
 use crate::res::{
     response_headers::ResponseHeaders, response_status::StatusCode, ResponseBodyType,
 };
-use futures::StreamExt;
-use http_body_util::BodyExt;
+use futures::{Stream, StreamExt};
+use http_body_util::{combinators::UnsyncBoxBody, BodyExt, StreamBody};
 #[cfg(feature = "with-wynd")]
 use http_body_util::Full;
+use hyper::body::Frame;
 use hyper::header::{HeaderName, HeaderValue, CONTENT_LENGTH, SET_COOKIE};
-use std::convert::Infallible;
+use std::{
+    convert::Infallible,
+    pin::Pin,
+    sync::{Arc, Mutex},
+};
+
+/// The stream behind a streamed [`HttpResponse`].
+type BodyStream = Pin<Box<dyn Stream<Item = Result<Bytes, HttpResponseError>> + Send + 'static>>;
+
+/// The body type connections send, either a buffered body or a stream.
+pub(crate) type OutgoingBody = UnsyncBoxBody<Bytes, Box<dyn std::error::Error + Send + Sync>>;
+
+/// A response extension holding a streamed body.
+///
+/// Responses travel through the router as `Full` bodies, so a streamed response
+/// carries an empty one and parks its stream here until
+/// [`into_outgoing_response`] hands it to the connection.
+#[derive(Clone)]
+pub(crate) struct StreamedBody(Arc<Mutex<Option<BodyStream>>>);
+
+impl StreamedBody {
+    fn new(stream: BodyStream) -> Self {
+        StreamedBody(Arc::new(Mutex::new(Some(stream))))
+    }
+
+    fn take(&self) -> Option<BodyStream> {
+        self.0.lock().unwrap().take()
+    }
+
+    /// A stream that takes the parked one when first polled, so a post-middleware
+    /// can be handed the stream while the response it may pass through keeps it.
+    fn lazy(&self) -> BodyStream {
+        let slot = self.clone();
+        Box::pin(
+            futures::stream::once(async move {
+                slot.take()
+                    .unwrap_or_else(|| Box::pin(futures::stream::empty()))
+            })
+            .flatten(),
+        )
+    }
+}
+
+/// Turns a response leaving the router into the one sent on the connection,
+/// streaming the body parked in a [`StreamedBody`] if there is one.
+pub(crate) fn into_outgoing_response(res: Response<Full<Bytes>>) -> Response<OutgoingBody> {
+    let (mut parts, body) = res.into_parts();
+    let stream = parts
+        .extensions
+        .remove::<StreamedBody>()
+        .and_then(|streamed| streamed.take());
+    let body = match stream {
+        Some(stream) => StreamBody::new(stream.map(|chunk| {
+            chunk
+                .map(Frame::data)
+                .map_err(|e| Box::new(e) as Box<dyn std::error::Error + Send + Sync>)
+        }))
+        .boxed_unsync(),
+        None => body.map_err(|never| match never {}).boxed_unsync(),
+    };
+    Response::from_parts(parts, body)
+}
 
 impl HttpResponse {
     #[cfg(feature = "with-wynd")]
@@ -75,7 +137,7 @@ impl HttpResponse {
             status_code,
             headers,
             cookies: Vec::new(),
-            stream: None,
+            stream: res.extensions().get::<StreamedBody>().map(StreamedBody::lazy),
         })
     }
     #[cfg(not(feature = "with-wynd"))]
@@ -121,7 +183,7 @@ impl HttpResponse {
             status_code,
             headers,
             cookies: Vec::new(),
-            stream: None,
+            stream: res.extensions().get::<StreamedBody>().map(StreamedBody::lazy),
         })
     }
 
@@ -130,16 +192,18 @@ impl HttpResponse {
         let body = self.body;
 
         if self.stream.is_some() {
-            let response = Response::builder()
-                .status(self.status_code.as_u16())
-                .header("Content-Type", "text/event-stream")
-                .header("Connection", "keep-alive");
-
             let mut header_map = self.headers.into_header_map();
 
-            header_map.remove(hyper::header::CONTENT_TYPE);
+            let content_type = header_map
+                .remove(hyper::header::CONTENT_TYPE)
+                .unwrap_or_else(|| HeaderValue::from_static("text/event-stream"));
             header_map.remove(hyper::header::CONNECTION);
 
+            let response = Response::builder()
+                .status(self.status_code.as_u16())
+                .header("Content-Type", content_type)
+                .header("Connection", "keep-alive");
+
             for c in self.cookies.iter() {
                 match c {
                     Cookie::AddCookie(c) => {
@@ -189,31 +253,27 @@ impl HttpResponse {
                 }
             }
 
-            let collected_results: Vec<Result<Bytes, HttpResponseError>> =
-                self.stream.unwrap().collect().await;
-
-            let bytes = collected_results
-                .into_iter()
-                .collect::<Result<Vec<Bytes>, _>>()
-                .map(|chunks| chunks.concat().into())
-                .unwrap_or_else(|_| Bytes::new());
-
-            let mut hyper_response = response.body(Full::from(bytes)).unwrap();
+            let mut hyper_response = response.body(Full::new(Bytes::new())).unwrap();
 
             hyper_response.headers_mut().extend(header_map);
-
-            hyper_response.headers_mut().remove(CONTENT_LENGTH);
-            let header_value = HeaderValue::from_static("chunked");
             hyper_response
-                .headers_mut()
-                .insert(HeaderName::from_static("transfer-encoding"), header_value);
+                .extensions_mut()
+                .insert(StreamedBody::new(self.stream.unwrap()));
+
+            // A stream of known length, e.g. a file download, keeps its `Content-Length`.
+            if !hyper_response.headers().contains_key(CONTENT_LENGTH) {
+                let header_value = HeaderValue::from_static("chunked");
+                hyper_response
+                    .headers_mut()
+                    .insert(HeaderName::from_static("transfer-encoding"), header_value);
+            }
 
             return Ok(hyper_response);
         } else {
             let mut response = match body {
                 ResponseBody::JSON(json) => {
                     let json_bytes = serde_json::to_vec(&json).unwrap_or_else(|e| {
-                        println!("JSON serialization error: {:?}", e);
+                        tracing::error!("JSON serialization error: {:?}", e);
                         Vec::from(b"{}")
                     });
 
@@ -239,7 +299,12 @@ impl HttpResponse {
 
             let mut header_map = self.headers.into_header_map();
 
-            header_map.remove(hyper::header::CONTENT_TYPE);
+            // An explicit `Content-Type` overrides the one implied by the body.
+            if let Some(content_type) = header_map.remove(hyper::header::CONTENT_TYPE) {
+                response
+                    .headers_mut()
+                    .insert(hyper::header::CONTENT_TYPE, content_type);
+            }
 
             for c in self.cookies {
                 match c {
@@ -294,6 +359,12 @@ impl HttpResponse {
 
             response.headers_mut().extend(header_map);
 
+            if !self.status_code.allows_body() {
+                *response.body_mut() = Full::new(Bytes::new());
+                response.headers_mut().remove(hyper::header::CONTENT_TYPE);
+                response.headers_mut().remove(CONTENT_LENGTH);
+            }
+
             return Ok(response);
         }
     }
@@ -0,0 +1,117 @@
+#![warn(missing_docs)]
+use serde_json::{Map, Value};
+
+/// Options for serializing a JSON response with [`HttpResponse::json_with`](crate::res::HttpResponse::json_with).
+///
+/// ## Example
+/// ```
+/// use ripress::res::{HttpResponse, JsonConfig, RenameRule};
+/// use serde::Serialize;
+///
+/// #[derive(Serialize)]
+/// struct User {
+///     first_name: String,
+/// }
+///
+/// let config = JsonConfig {
+///     rename: Some(RenameRule::CamelCase),
+///     ..JsonConfig::default()
+/// };
+/// let res = HttpResponse::new().ok().json_with(
+///     User {
+///         first_name: "Ada".to_string(),
+///     },
+///     &config,
+/// );
+///
+/// assert_eq!(res.body_text().unwrap(), r#"{"firstName":"Ada"}"#);
+/// ```
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct JsonConfig {
+    /// Indent the output; meant for debugging endpoints, as it makes bodies larger
+    pub pretty: bool,
+    /// Renames every object key, including nested ones, before serializing
+    pub rename: Option<RenameRule>,
+}
+
+/// A field renaming policy applied to JSON object keys.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum RenameRule {
+    /// `first_name` becomes `firstName`
+    CamelCase,
+    /// `firstName` becomes `first_name`
+    SnakeCase,
+}
+
+impl RenameRule {
+    /// Applies the rule to a single key.
+    pub fn apply(&self, key: &str) -> String {
+        match self {
+            RenameRule::CamelCase => to_camel_case(key),
+            RenameRule::SnakeCase => to_snake_case(key),
+        }
+    }
+
+    /// Renames the keys of every object in `value`, recursively.
+    pub(crate) fn apply_to_value(&self, value: Value) -> Value {
+        match value {
+            Value::Object(map) => Value::Object(
+                map.into_iter()
+                    .map(|(key, value)| (self.apply(&key), self.apply_to_value(value)))
+                    .collect::<Map<String, Value>>(),
+            ),
+            Value::Array(values) => Value::Array(
+                values
+                    .into_iter()
+                    .map(|value| self.apply_to_value(value))
+                    .collect(),
+            ),
+            other => other,
+        }
+    }
+}
+
+/// Converts `snake_case` to `camelCase`, keeping leading underscores.
+fn to_camel_case(key: &str) -> String {
+    let trimmed = key.trim_start_matches('_');
+    let mut result = key[..key.len() - trimmed.len()].to_string();
+    let mut upper_next = false;
+
+    for c in trimmed.chars() {
+        if c == '_' {
+            upper_next = true;
+        } else if upper_next {
+            result.extend(c.to_uppercase());
+            upper_next = false;
+        } else {
+            result.push(c);
+        }
+    }
+    result
+}
+
+/// Converts `camelCase` or `PascalCase` to `snake_case`, treating acronyms
+/// like `userID` as one word.
+fn to_snake_case(key: &str) -> String {
+    let chars: Vec<char> = key.chars().collect();
+    let mut result = String::with_capacity(key.len() + 4);
+
+    for (i, &c) in chars.iter().enumerate() {
+        if c.is_uppercase() {
+            let prev = i.checked_sub(1).map(|p| chars[p]);
+            let next = chars.get(i + 1);
+            let starts_word = match prev {
+                Some(p) if p.is_lowercase() || p.is_ascii_digit() => true,
+                Some(p) if p.is_uppercase() => next.is_some_and(|n| n.is_lowercase()),
+                _ => false,
+            };
+            if starts_word && !result.ends_with('_') {
+                result.push('_');
+            }
+            result.extend(c.to_lowercase());
+        } else {
+            result.push(c);
+        }
+    }
+    result
+}
@@ -94,11 +94,23 @@
 
 #![warn(missing_docs)]
 
-use crate::res::{response_cookie::Cookie, response_status::StatusCode};
+use crate::app::api_error::error_response;
+use crate::{
+    app::{
+        settings::StaticOptions,
+        static_files::{file_response, strip_multi_range},
+    },
+    helpers::{content_disposition, guess_content_type},
+    req::HttpRequest,
+    res::{response_cookie::Cookie, response_status::StatusCode},
+};
 use bytes::Bytes;
 use futures::{Stream, StreamExt};
+use http_body_util::BodyExt;
+use hyper_staticfile::{ResolveResult, ResolvedFile};
 use serde::Serialize;
-use std::pin::Pin;
+use std::{path::Path, pin::Pin};
+use tokio::io::AsyncReadExt;
 
 mod response_body;
 pub(crate) use response_body::{ResponseBody, ResponseBodyType};
@@ -123,6 +135,12 @@ pub mod conversions;
 mod response_error;
 pub use response_error::HttpResponseError;
 
+mod json_config;
+pub use json_config::{JsonConfig, RenameRule};
+
+mod render;
+pub use render::Render;
+
 /// Represents an HTTP response being sent to the client.
 ///
 /// The HttpResponse struct provides methods to construct and manipulate HTTP responses
@@ -248,11 +266,21 @@ impl HttpResponse {
     }
 
     /// Sets the status code to 204 No Content.
+    ///
+    /// The response is sent without a body or `Content-Type`, whatever body was set.
     pub fn no_content(mut self) -> Self {
         self.status_code = StatusCode::NoContent;
         self
     }
 
+    /// Sets the status code to 304 Not Modified.
+    ///
+    /// Like [`no_content`](Self::no_content), the response is sent without a body.
+    pub fn not_modified(mut self) -> Self {
+        self.status_code = StatusCode::NotModified;
+        self
+    }
+
     /// Sets the status code to 400 Bad Request.
     pub fn bad_request(mut self) -> Self {
         self.status_code = StatusCode::BadRequest;
@@ -289,6 +317,24 @@ impl HttpResponse {
         return self;
     }
 
+    /// Sets the status code to 413 Payload Too Large.
+    pub fn payload_too_large(mut self) -> Self {
+        self.status_code = StatusCode::PayloadTooLarge;
+        self
+    }
+
+    /// Sets the status code to 422 Unprocessable Entity.
+    pub fn unprocessable_entity(mut self) -> Self {
+        self.status_code = StatusCode::UnprocessableEntity;
+        self
+    }
+
+    /// Sets the status code to 429 Too Many Requests.
+    pub fn too_many_requests(mut self) -> Self {
+        self.status_code = StatusCode::TooManyRequests;
+        self
+    }
+
     /// Sets the status code to 500 Internal Server Error.
     pub fn internal_server_error(mut self) -> Self {
         self.status_code = StatusCode::InternalServerError;
@@ -324,6 +370,79 @@ impl HttpResponse {
         self.status_code.as_u16()
     }
 
+    /// Returns the HTTP status code set on the response.
+    ///
+    /// # Example
+    /// ```rust
+    /// use ripress::context::HttpResponse;
+    ///
+    /// let res = HttpResponse::new().not_found();
+    /// assert_eq!(res.get_status(), 404);
+    /// ```
+    pub fn get_status(&self) -> u16 {
+        self.status_code.as_u16()
+    }
+
+    /// Returns the value of a response header, if set. Lookup is case-insensitive.
+    ///
+    /// # Example
+    /// ```rust
+    /// use ripress::context::HttpResponse;
+    ///
+    /// let res = HttpResponse::new().set_header("X-Powered-By", "Ripress");
+    /// assert_eq!(res.get_header("x-powered-by"), Some("Ripress"));
+    /// ```
+    pub fn get_header(&self, name: &str) -> Option<&str> {
+        self.headers.get(name)
+    }
+
+    /// Returns the response body as bytes, exactly as it would be sent.
+    ///
+    /// JSON bodies are serialized in their compact form. Streaming responses
+    /// created with [`HttpResponse::write`] are not buffered, so only the
+    /// non-streamed body (usually empty) is returned for them.
+    ///
+    /// # Example
+    /// ```rust
+    /// use ripress::context::HttpResponse;
+    ///
+    /// let res = HttpResponse::new().json(serde_json::json!({ "ok": true }));
+    /// assert_eq!(res.body_bytes(), r#"{"ok":true}"#);
+    /// ```
+    pub fn body_bytes(&self) -> Bytes {
+        match &self.body {
+            ResponseBody::TEXT(text) => Bytes::copy_from_slice(text.as_bytes()),
+            ResponseBody::HTML(html) => Bytes::copy_from_slice(html.as_bytes()),
+            ResponseBody::JSON(json) => serde_json::to_vec(json).unwrap_or_default().into(),
+            ResponseBody::BINARY(bytes) => bytes.clone(),
+        }
+    }
+
+    /// Returns a text, HTML or JSON body as a string.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`HttpResponseError::BinaryBody`] if the body was set with
+    /// [`HttpResponse::bytes`]; use [`HttpResponse::body_bytes`] for those.
+    ///
+    /// # Example
+    /// ```rust
+    /// use ripress::context::HttpResponse;
+    ///
+    /// let res = HttpResponse::new().text("Hello");
+    /// assert_eq!(res.body_text().unwrap(), "Hello");
+    ///
+    /// let res = HttpResponse::new().bytes(vec![0xff, 0xfe]);
+    /// assert!(res.body_text().is_err());
+    /// ```
+    pub fn body_text(&self) -> Result<String, HttpResponseError> {
+        match &self.body {
+            ResponseBody::TEXT(text) | ResponseBody::HTML(text) => Ok(text.clone()),
+            ResponseBody::JSON(json) => Ok(json.to_string()),
+            ResponseBody::BINARY(_) => Err(HttpResponseError::BinaryBody),
+        }
+    }
+
     /// Sets the response body to text.
     ///
     /// # Arguments
@@ -389,6 +508,180 @@ impl HttpResponse {
         return self;
     }
 
+    /// Sets the response body to a URL-encoded form.
+    ///
+    /// Accepts anything `serde_urlencoded` can serialize, such as a slice of
+    /// pairs, a map or a struct with scalar fields. Keys and values are
+    /// percent-encoded, with spaces written as `%20` so [`FormData`](crate::req::body::FormData) parses
+    /// them back unchanged, and `Content-Type` is `application/x-www-form-urlencoded`.
+    /// If `form` can't be serialized, e.g. because it is a struct with a nested
+    /// struct field, the error is logged and the response becomes a
+    /// `500 Internal Server Error` rendered like the framework's other errors.
+    ///
+    /// # Example
+    /// ```rust
+    /// use ripress::context::HttpResponse;
+    ///
+    /// let res = HttpResponse::new().ok().form(&[("a", "1"), ("b", "x&y")]);
+    ///
+    /// assert_eq!(res.body_text().unwrap(), "a=1&b=x%26y");
+    /// assert_eq!(
+    ///     res.get_header("Content-Type"),
+    ///     Some("application/x-www-form-urlencoded")
+    /// );
+    /// ```
+    pub fn form<T: Serialize>(mut self, form: T) -> Self {
+        match serde_urlencoded::to_string(form) {
+            Ok(body) => {
+                // A literal `+` is already encoded as `%2B`, so any `+` left is a space.
+                self.body = ResponseBody::new_text(body.replace('+', "%20"));
+                self.headers
+                    .insert("Content-Type", "application/x-www-form-urlencoded");
+                self
+            }
+            Err(e) => {
+                tracing::error!("Failed to serialize form: {}", e);
+                error_response(self, 500, "Internal Server Error")
+            }
+        }
+    }
+
+    /// Sets the response body to indented JSON.
+    ///
+    /// Meant for debugging endpoints; use [`HttpResponse::json`] in production,
+    /// as indentation makes bodies larger. `Content-Type` stays `application/json`.
+    ///
+    /// # Example
+    /// ```rust
+    /// use ripress::context::HttpResponse;
+    /// use serde_json::json;
+    ///
+    /// let res = HttpResponse::new().ok().json_pretty(json!({ "debug": true }));
+    ///
+    /// assert_eq!(res.body_text().unwrap(), "{\n  \"debug\": true\n}");
+    /// ```
+    pub fn json_pretty<T: Serialize>(self, json: T) -> Self {
+        let config = JsonConfig {
+            pretty: true,
+            ..JsonConfig::default()
+        };
+        self.json_with(json, &config)
+    }
+
+    /// Sets the response body to JSON serialized with the given [`JsonConfig`].
+    ///
+    /// The config can indent the output and rename every object key, e.g. to
+    /// camelCase, without annotating each struct. `Content-Type` stays
+    /// `application/json`.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `json` cannot be serialized, like [`HttpResponse::json`].
+    pub fn json_with<T: Serialize>(mut self, json: T, config: &JsonConfig) -> Self {
+        let mut value = serde_json::to_value(json).expect("Failed to serialize to JSON");
+        if let Some(rule) = config.rename {
+            value = rule.apply_to_value(value);
+        }
+
+        if config.pretty {
+            let text = serde_json::to_string_pretty(&value).expect("Failed to serialize to JSON");
+            self.body = ResponseBody::new_text(text);
+            self.headers.insert("Content-Type", "application/json");
+        } else {
+            self.body = ResponseBody::JSON(value);
+        }
+        self
+    }
+
+    /// Sets the response body to XML serialized with `quick-xml`.
+    ///
+    /// The document starts with an XML declaration, its root element is named
+    /// after the serialized type, and `Content-Type` is `application/xml`. If
+    /// `xml` can't be serialized, e.g. because it is a map or a bare sequence
+    /// with no element name, the error is logged and the response becomes a
+    /// `500 Internal Server Error` rendered like the framework's other errors.
+    ///
+    /// Requires the `xml` feature.
+    ///
+    /// # Example
+    /// ```rust
+    /// use ripress::context::HttpResponse;
+    /// use serde::Serialize;
+    ///
+    /// #[derive(Serialize)]
+    /// struct Book {
+    ///     #[serde(rename = "@id")]
+    ///     id: u32,
+    ///     title: String,
+    /// }
+    ///
+    /// let res = HttpResponse::new().ok().xml(&Book {
+    ///     id: 1,
+    ///     title: "Dune".to_string(),
+    /// });
+    ///
+    /// assert_eq!(res.get_header("Content-Type"), Some("application/xml"));
+    /// assert_eq!(
+    ///     res.body_text().unwrap(),
+    ///     r#"<?xml version="1.0" encoding="UTF-8"?><Book id="1"><title>Dune</title></Book>"#
+    /// );
+    /// ```
+    #[cfg(feature = "xml")]
+    pub fn xml<T: Serialize>(mut self, xml: T) -> Self {
+        match quick_xml::se::to_string(&xml) {
+            Ok(document) => {
+                self.body = ResponseBody::new_text(format!(
+                    r#"<?xml version="1.0" encoding="UTF-8"?>{}"#,
+                    document
+                ));
+                self.headers.insert("Content-Type", "application/xml");
+                self
+            }
+            Err(e) => {
+                tracing::error!("Failed to serialize XML: {}", e);
+                error_response(self, 500, "Internal Server Error")
+            }
+        }
+    }
+
+    /// Sets the response body to MessagePack serialized with `rmp-serde`.
+    ///
+    /// Structs are encoded as maps keyed by field name, so clients don't need
+    /// to know the field order. `Content-Type` is `application/msgpack`. If
+    /// `value` can't be serialized the error is logged and the response becomes
+    /// a `500 Internal Server Error` rendered like the framework's other errors.
+    ///
+    /// Requires the `msgpack` feature.
+    ///
+    /// # Example
+    /// ```rust
+    /// use ripress::context::HttpResponse;
+    /// use serde::Serialize;
+    ///
+    /// #[derive(Serialize)]
+    /// struct Point {
+    ///     x: i32,
+    ///     y: i32,
+    /// }
+    ///
+    /// let res = HttpResponse::new().ok().msgpack(&Point { x: 1, y: 2 });
+    /// assert_eq!(res.get_header("Content-Type"), Some("application/msgpack"));
+    /// ```
+    #[cfg(feature = "msgpack")]
+    pub fn msgpack<T: Serialize>(mut self, value: T) -> Self {
+        match rmp_serde::to_vec_named(&value) {
+            Ok(bytes) => {
+                self.body = ResponseBody::new_binary(bytes);
+                self.headers.insert("Content-Type", "application/msgpack");
+                self
+            }
+            Err(e) => {
+                tracing::error!("Failed to serialize MessagePack: {}", e);
+                error_response(self, 500, "Internal Server Error")
+            }
+        }
+    }
+
     /// Sets the response body to binary data.
     ///
     /// # Arguments
@@ -441,6 +734,71 @@ impl HttpResponse {
         self
     }
 
+    /// Adds a header to the response, keeping any values already set for it.
+    ///
+    /// Use this for headers that may repeat, such as `Set-Cookie`, `Link` or `Vary`;
+    /// [`set_header`](Self::set_header) replaces the existing values instead.
+    ///
+    /// # Example
+    /// ```
+    /// use ripress::context::HttpResponse;
+    ///
+    /// let res = HttpResponse::new()
+    ///     .append_header("Set-Cookie", "theme=dark")
+    ///     .append_header("Set-Cookie", "lang=en");
+    /// assert_eq!(res.headers.get_all("set-cookie"), vec!["theme=dark", "lang=en"]);
+    /// ```
+    pub fn append_header<K, V>(mut self, header_name: K, header_value: V) -> Self
+    where
+        K: AsRef<str>,
+        V: AsRef<str>,
+    {
+        self.headers.append(header_name, header_value);
+        self
+    }
+
+    /// Sets several headers at once, as if by calling [`set_header`](Self::set_header)
+    /// for each pair in order.
+    ///
+    /// Like `set_header`, a pair whose name or value is not a valid header is skipped,
+    /// and a later pair replaces an earlier one with the same name.
+    ///
+    /// # Example
+    /// ```
+    /// use ripress::context::HttpResponse;
+    ///
+    /// let res = HttpResponse::new().headers([("X-A", "1"), ("X-B", "2")]);
+    /// assert_eq!(res.get_header("x-a"), Some("1"));
+    /// assert_eq!(res.get_header("x-b"), Some("2"));
+    /// ```
+    pub fn headers<I, K, V>(mut self, headers: I) -> Self
+    where
+        I: IntoIterator<Item = (K, V)>,
+        K: AsRef<str>,
+        V: AsRef<str>,
+    {
+        for (name, value) in headers {
+            self.headers.insert(name, value);
+        }
+        self
+    }
+
+    /// Removes every value of a header from the response.
+    ///
+    /// # Example
+    /// ```
+    /// use ripress::context::HttpResponse;
+    ///
+    /// let res = HttpResponse::new()
+    ///     .set_header("X-Powered-By", "Ripress")
+    ///     .remove_header("x-powered-by");
+    /// assert_eq!(res.get_header("X-Powered-By"), None);
+    /// ```
+    pub fn remove_header(mut self, header_name: &str) -> Self {
+        self.headers.remove(header_name);
+        self
+    }
+
     /// Sets a cookie in the response.
     ///
     /// # Arguments
@@ -591,41 +949,184 @@ impl HttpResponse {
         self
     }
 
-    /// Sends the contents of a file as the response body.
-    /// This method reads the file at the given path asynchronously and sets the response body to its contents.
-    /// The content type is inferred from the file's bytes using the `infer` crate and then mapped to a MIME
-    /// type via `mime_guess`. If the type cannot be determined, it falls back to `application/octet-stream`.
+    /// Sets the response body to a rendered template.
     ///
-    /// # Arguments
+    /// The body is sent as `text/html`. If rendering fails the error is logged and
+    /// the response becomes a `500 Internal Server Error` without the error details,
+    /// so a broken template never panics the handler or leaks internals.
     ///
-    /// * `path` - The path to the file to be sent. Must be a static string slice.
+    /// # Example
+    /// ```rust
+    /// use ripress::context::{HttpRequest, HttpResponse};
+    /// use ripress::res::Render;
     ///
-    /// # Returns
+    /// struct Home;
+    ///
+    /// impl Render for Home {
+    ///     type Error = std::fmt::Error;
     ///
-    /// Returns `Self` for method chaining.
+    ///     fn render(&self) -> Result<String, Self::Error> {
+    ///         Ok("<h1>Home</h1>".to_string())
+    ///     }
+    /// }
+    ///
+    /// async fn handler(_req: HttpRequest, res: HttpResponse) -> HttpResponse {
+    ///     res.ok().render(Home)
+    /// }
+    /// ```
+    pub fn render<T: Render>(mut self, template: T) -> Self {
+        match template.render() {
+            Ok(html) => {
+                self.body = ResponseBody::new_html(html);
+                self
+            }
+            Err(e) => {
+                tracing::error!("Failed to render template: {}", e);
+                self.internal_server_error().text("Internal Server Error")
+            }
+        }
+    }
+
+    /// Serves a file inline, with the same caching and range handling as static mounts.
+    ///
+    /// Unlike a static mount, the path can be chosen per request. The response gets
+    /// `ETag`, `Last-Modified`, `Accept-Ranges` and a `Content-Type` inferred from the
+    /// file, and honors the request's conditional and range headers:
+    /// - a matching `If-None-Match` or `If-Modified-Since` gives `304 Not Modified`
+    /// - a single `Range` gives `206 Partial Content`, or `416` if it can't be satisfied
+    ///
+    /// `Cache-Control` is `public, max-age=0`; set it afterwards to change it. A
+    /// missing file gives `404 Not Found`.
     ///
     /// # Example
     /// ```no_run
-    /// use ripress::context::HttpResponse;
-    /// use ripress::context::HttpRequest;
+    /// use ripress::context::{HttpRequest, HttpResponse};
     ///
     /// async fn handler(req: HttpRequest, res: HttpResponse) -> HttpResponse {
-    ///     // Send a file as the response
-    ///     res.ok().send_file("static/image.png").await
+    ///     let id = req.params.get("id").unwrap_or_default().to_string();
+    ///     res.send_file(&req, format!("./data/{}.json", id)).await
     /// }
     /// ```
-    pub async fn send_file(mut self, path: &'static str) -> Self {
-        let file = tokio::fs::read(path).await;
+    pub async fn send_file<P: AsRef<Path>>(mut self, req: &HttpRequest, path: P) -> Self {
+        let path = path.as_ref();
+        let resolved = match open_file(path).await {
+            Ok(resolved) => resolved,
+            Err(e) => return self.internal_server_error().text(e.to_string()),
+        };
+
+        let mut request = hyper::Request::new(());
+        *request.method_mut() = hyper::Method::try_from(&req.method).unwrap_or_default();
+        *request.headers_mut() = req.headers.as_header_map().clone();
+        strip_multi_range(request.headers_mut());
+
+        let options = StaticOptions {
+            max_age: 0,
+            ..StaticOptions::default()
+        };
+        let response = match file_response(&request, resolved, &options).await {
+            Ok(response) => response,
+            Err(e) => return self.internal_server_error().text(e.to_string()),
+        };
 
-        match file {
-            Ok(file) => {
-                self.body = ResponseBody::new_binary(file);
+        let (parts, body) = response.into_parts();
+        let body = match body.collect().await {
+            Ok(collected) => collected.to_bytes(),
+            Err(never) => match never {},
+        };
+
+        self.status_code = StatusCode::from_u16(parts.status.as_u16());
+        for (name, value) in parts.headers.iter() {
+            if let Ok(value) = value.to_str() {
+                self.headers.insert(name.as_str(), value);
+            }
+        }
+        self.body = ResponseBody::new_binary(body);
+        self
+    }
+
+    /// Marks the response as a download with the given filename.
+    ///
+    /// Sets `Content-Disposition: attachment; filename="..."`, adding an RFC 5987
+    /// `filename*` parameter for non-ASCII names, and sets `Content-Type` from the
+    /// filename's extension when it is known.
+    ///
+    /// # Example
+    /// ```rust
+    /// use ripress::context::HttpResponse;
+    ///
+    /// let res = HttpResponse::new()
+    ///     .ok()
+    ///     .attachment("report.csv")
+    ///     .text("id,name\n1,ripress\n");
+    ///
+    /// assert_eq!(
+    ///     res.get_header("Content-Disposition"),
+    ///     Some("attachment; filename=\"report.csv\"")
+    /// );
+    /// assert_eq!(res.get_header("Content-Type"), Some("text/csv"));
+    /// ```
+    pub fn attachment(mut self, filename: &str) -> Self {
+        self.headers.insert(
+            "Content-Disposition",
+            content_disposition("attachment", filename),
+        );
+        if let Some(mime) = mime_guess::from_path(filename).first() {
+            self.headers.insert("Content-Type", mime.essence_str());
+        }
+        self
+    }
+
+    /// Sends a file as a download, prompting the browser to save it.
+    ///
+    /// `Content-Disposition` is set as by [`HttpResponse::attachment`] using the
+    /// file's name, `Content-Type` is inferred from its extension or contents and
+    /// `Content-Length` is its size.
+    ///
+    /// The file is opened here and streamed to the client in 64 KiB chunks as
+    /// the response is sent, so large files aren't held in memory.
+    ///
+    /// Responds with `404 Not Found` if the file doesn't exist and
+    /// `500 Internal Server Error` if it can't be read.
+    ///
+    /// # Example
+    /// ```no_run
+    /// use ripress::context::{HttpRequest, HttpResponse};
+    ///
+    /// async fn handler(_req: HttpRequest, res: HttpResponse) -> HttpResponse {
+    ///     res.ok().download("./report.pdf").await
+    /// }
+    /// ```
+    pub async fn download<P: AsRef<Path>>(mut self, path: P) -> Self {
+        let path = path.as_ref();
+        let file = match tokio::fs::File::open(path).await {
+            Ok(file) => file,
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => {
+                return self.not_found().text("Not Found");
             }
             Err(e) => {
-                eprintln!("Error reading file: {}", e);
+                return self.internal_server_error().text(e.to_string());
             }
-        }
+        };
+        let metadata = match file.metadata().await {
+            Ok(metadata) if metadata.is_file() => metadata,
+            Ok(_) => return self.not_found().text("Not Found"),
+            Err(e) => return self.internal_server_error().text(e.to_string()),
+        };
+
+        let filename = path
+            .file_name()
+            .map(|name| name.to_string_lossy().into_owned())
+            .unwrap_or_else(|| "download".to_string());
 
+        self.headers.insert(
+            "Content-Disposition",
+            content_disposition("attachment", &filename),
+        );
+        self.headers
+            .insert("Content-Type", guess_content_type(path));
+        self.headers
+            .insert("Content-Length", metadata.len().to_string());
+        self.stream = Some(Box::pin(file_stream(file)));
         self
     }
 
@@ -663,3 +1164,51 @@ impl HttpResponse {
         self
     }
 }
+
+/// Size of the chunks a file is read in by [`HttpResponse::download`].
+const FILE_CHUNK_SIZE: usize = 64 * 1024;
+
+/// Reads a file in [`FILE_CHUNK_SIZE`] chunks, ending after the first error.
+fn file_stream(
+    file: tokio::fs::File,
+) -> impl Stream<Item = Result<Bytes, HttpResponseError>> + Send + 'static {
+    futures::stream::unfold(Some(file), |file| async move {
+        let mut file = file?;
+        let mut buf = vec![0; FILE_CHUNK_SIZE];
+        match file.read(&mut buf).await {
+            Ok(0) => None,
+            Ok(n) => {
+                buf.truncate(n);
+                Some((Ok(Bytes::from(buf)), Some(file)))
+            }
+            Err(e) => Some((Err(HttpResponseError::from(e)), None)),
+        }
+    })
+}
+
+/// Opens a single file for [`HttpResponse::send_file`], mapping a missing,
+/// unreadable or non-regular file to the matching [`ResolveResult`].
+async fn open_file(path: &Path) -> std::io::Result<ResolveResult> {
+    let handle = match tokio::fs::File::open(path).await {
+        Ok(handle) => handle,
+        Err(e) if e.kind() == std::io::ErrorKind::NotFound => return Ok(ResolveResult::NotFound),
+        Err(e) if e.kind() == std::io::ErrorKind::PermissionDenied => {
+            return Ok(ResolveResult::PermissionDenied)
+        }
+        Err(e) => return Err(e),
+    };
+
+    let metadata = handle.metadata().await?;
+    if !metadata.is_file() {
+        return Ok(ResolveResult::NotFound);
+    }
+
+    Ok(ResolveResult::Found(ResolvedFile {
+        handle,
+        path: path.to_path_buf(),
+        size: metadata.len(),
+        modified: metadata.modified().ok(),
+        content_type: Some(guess_content_type(path)),
+        encoding: None,
+    }))
+}
@@ -0,0 +1,51 @@
+#![warn(missing_docs)]
+
+/// A template that renders to an HTML string, for use with
+/// [`HttpResponse::render`](crate::res::HttpResponse::render).
+///
+/// Ripress doesn't bundle a templating engine; implement this for your
+/// askama, tera or handwritten templates.
+///
+/// ## Example
+/// ```
+/// use ripress::res::{HttpResponse, Render};
+///
+/// struct Greeting<'a> {
+///     name: &'a str,
+/// }
+///
+/// impl Render for Greeting<'_> {
+///     type Error = std::fmt::Error;
+///
+///     fn render(&self) -> Result<String, Self::Error> {
+///         Ok(format!("<h1>Hello, {}!</h1>", self.name))
+///     }
+/// }
+///
+/// let res = HttpResponse::new().ok().render(Greeting { name: "Ada" });
+/// assert_eq!(res.body_text().unwrap(), "<h1>Hello, Ada!</h1>");
+/// ```
+///
+/// With askama, forward to its `Template` trait:
+/// ```ignore
+/// #[derive(askama::Template)]
+/// #[template(path = "page.html")]
+/// struct Page {
+///     title: String,
+/// }
+///
+/// impl ripress::res::Render for Page {
+///     type Error = askama::Error;
+///
+///     fn render(&self) -> Result<String, Self::Error> {
+///         askama::Template::render(self)
+///     }
+/// }
+/// ```
+pub trait Render {
+    /// The error returned when rendering fails
+    type Error: std::fmt::Display;
+
+    /// Renders the template to HTML.
+    fn render(&self) -> Result<String, Self::Error>;
+}
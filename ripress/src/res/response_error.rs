@@ -8,6 +8,7 @@
 ///
 /// - `IoError(std::io::Error)`: Represents an IO error that occurred, such as a failure
 ///   to read from or write to a stream.
+/// - `BinaryBody`: The response body is binary and cannot be read as text.
 /// - `_Other(&'static str)`: Represents a generic or custom error with a static string message.
 #[derive(Debug)]
 pub enum HttpResponseError {
@@ -15,6 +16,8 @@ pub enum HttpResponseError {
     IoError(std::io::Error),
     /// An expected HTTP header is missing. Contains the name of the missing header.
     MissingHeader(String),
+    /// The response body is binary and cannot be read as text.
+    BinaryBody,
     /// A generic or custom error with a static string message.
     _Other(&'static str),
 }
@@ -33,6 +36,7 @@ impl std::fmt::Display for HttpResponseError {
             HttpResponseError::IoError(e) => write!(f, "IO error: {}", e),
             HttpResponseError::_Other(e) => write!(f, "Error: {}", e),
             HttpResponseError::MissingHeader(h) => write!(f, "Missing header: {}", h),
+            HttpResponseError::BinaryBody => write!(f, "Response body is binary, not text"),
         }
     }
 }
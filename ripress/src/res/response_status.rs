@@ -41,6 +41,7 @@ use std::fmt::Display;
 /// ## 3xx Redirection
 /// - [`PermanentRedirect`](StatusCode::PermanentRedirect) (301) - Resource permanently moved
 /// - [`Redirect`](StatusCode::Redirect) (302) - Resource temporarily moved
+/// - [`NotModified`](StatusCode::NotModified) (304) - Cached copy is still valid
 ///
 /// ## 4xx Client Error
 /// - [`BadRequest`](StatusCode::BadRequest) (400) - Invalid request syntax
@@ -50,6 +51,7 @@ use std::fmt::Display;
 /// - [`MethodNotAllowed`](StatusCode::MethodNotAllowed) (405) - HTTP method not supported
 /// - [`Conflict`](StatusCode::Conflict) (409) - Request conflicts with current state
 /// - [`PayloadTooLarge`](StatusCode::PayloadTooLarge) (413) - Request payload too large
+/// - [`UnprocessableEntity`](StatusCode::UnprocessableEntity) (422) - Request content failed validation
 /// - [`TooManyRequests`](StatusCode::TooManyRequests) (429) - Too many requests
 ///
 /// ## 5xx Server Error
@@ -107,6 +109,12 @@ pub enum StatusCode {
     /// temporarily. Further changes in the URI might be made in the future.
     Redirect,
 
+    /// 304 Not Modified
+    ///
+    /// This response tells the client that its cached copy is still valid.
+    /// Like 204, it never carries a body.
+    NotModified,
+
     /// 400 Bad Request
     ///
     /// The server cannot or will not process the request due to an apparent client
@@ -150,6 +158,12 @@ pub enum StatusCode {
     /// before retrying. (Retry behavior is client-specific.)
     PayloadTooLarge,
 
+    /// 422 Unprocessable Entity
+    ///
+    /// The request was well-formed but its content could not be processed,
+    /// typically because it failed validation.
+    UnprocessableEntity,
+
     /// 429 Too Many Requests
     ///
     /// This response is sent when a request is rejected due to the user exceeding the rate limit.
@@ -228,6 +242,7 @@ impl Display for StatusCode {
                 StatusCode::NoContent => "No Content",
                 StatusCode::Redirect => "Found",
                 StatusCode::PermanentRedirect => "Moved Permanently",
+                StatusCode::NotModified => "Not Modified",
                 StatusCode::BadRequest => "Bad Request",
                 StatusCode::Unauthorized => "Unauthorized",
                 StatusCode::Forbidden => "Forbidden",
@@ -235,6 +250,7 @@ impl Display for StatusCode {
                 StatusCode::MethodNotAllowed => "Method Not Allowed",
                 StatusCode::Conflict => "Conflict",
                 StatusCode::PayloadTooLarge => "Payload Too Large",
+                StatusCode::UnprocessableEntity => "Unprocessable Entity",
                 StatusCode::TooManyRequests => "Too Many Requests",
                 StatusCode::InternalServerError => "Internal Server Error",
                 StatusCode::NotImplemented => "Not Implemented",
@@ -270,6 +286,7 @@ impl StatusCode {
 
             StatusCode::Redirect => 302,
             StatusCode::PermanentRedirect => 301,
+            StatusCode::NotModified => 304,
 
             StatusCode::BadRequest => 400,
             StatusCode::Unauthorized => 401,
@@ -278,6 +295,7 @@ impl StatusCode {
             StatusCode::MethodNotAllowed => 405,
             StatusCode::Conflict => 409,
             StatusCode::PayloadTooLarge => 413,
+            StatusCode::UnprocessableEntity => 422,
             StatusCode::TooManyRequests => 429,
 
             StatusCode::InternalServerError => 500,
@@ -322,6 +340,7 @@ impl StatusCode {
 
             302 => StatusCode::Redirect,
             301 => StatusCode::PermanentRedirect,
+            304 => StatusCode::NotModified,
 
             400 => StatusCode::BadRequest,
             401 => StatusCode::Unauthorized,
@@ -330,6 +349,7 @@ impl StatusCode {
             405 => StatusCode::MethodNotAllowed,
             409 => StatusCode::Conflict,
             413 => StatusCode::PayloadTooLarge,
+            422 => StatusCode::UnprocessableEntity,
             429 => StatusCode::TooManyRequests,
 
             500 => StatusCode::InternalServerError,
@@ -423,6 +443,23 @@ impl StatusCode {
         matches!(self.as_u16(), 100..=199)
     }
 
+    /// Returns `true` if responses with this status code may carry a body.
+    ///
+    /// Informational (1xx), 204 No Content and 304 Not Modified responses never do.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use ripress::res::response_status::StatusCode;
+    ///
+    /// assert!(StatusCode::Ok.allows_body());
+    /// assert!(!StatusCode::NoContent.allows_body());
+    /// assert!(!StatusCode::NotModified.allows_body());
+    /// ```
+    pub fn allows_body(&self) -> bool {
+        !matches!(self.as_u16(), 100..=199 | 204 | 304)
+    }
+
     /// Returns the canonical reason phrase for this status code.
     ///
     /// This returns the standard HTTP reason phrase associated with the status code,
@@ -445,6 +482,7 @@ impl StatusCode {
             StatusCode::NoContent => "No Content",
             StatusCode::Redirect => "Found",
             StatusCode::PermanentRedirect => "Moved Permanently",
+            StatusCode::NotModified => "Not Modified",
             StatusCode::BadRequest => "Bad Request",
             StatusCode::Unauthorized => "Unauthorized",
             StatusCode::Forbidden => "Forbidden",
@@ -452,6 +490,7 @@ impl StatusCode {
             StatusCode::MethodNotAllowed => "Method Not Allowed",
             StatusCode::Conflict => "Conflict",
             StatusCode::PayloadTooLarge => "Payload Too Large",
+            StatusCode::UnprocessableEntity => "Unprocessable Entity",
             StatusCode::TooManyRequests => "Too Many Requests",
             StatusCode::InternalServerError => "Internal Server Error",
             StatusCode::NotImplemented => "Not Implemented",
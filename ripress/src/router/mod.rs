@@ -10,6 +10,7 @@
 //! - **Composition**: Build routers in isolation and mount onto an `App`
 //! - **Versioning**: Create versioned APIs like `/v1`, `/v2`
 //! - **Familiar ergonomics**: Same `get/post/put/delete/patch/head/options` API as `App`
+//! - **Scoped middleware**: Middleware attached to a router only runs for its routes
 //!
 //! ## Basic Usage
 //!
@@ -29,6 +30,19 @@
 //! app.router(router);
 //! ```
 //!
+//! ## Route Patterns
+//!
+//! Route paths (on both routers and apps) support:
+//!
+//! - `:name` to capture a single segment, e.g. `/users/:id`
+//! - `:name(regex)` to capture a segment only when the whole segment matches the
+//!   regex, e.g. `/users/:id(\d+)`; other routes still get a chance to match
+//! - `*name` as the last segment to capture the rest of the path, e.g.
+//!   `/files/*path` gives `path == "a/b/c.txt"` for `/files/a/b/c.txt`
+//!
+//! When several routes match, literal segments win over constrained params,
+//! which win over plain params, which win over wildcards.
+//!
 //! ## Versioning Example
 //!
 //! ```rust
@@ -50,9 +64,20 @@
 #![warn(missing_docs)]
 use crate::{
     app::App,
-    types::{RouterFns, Routes},
+    middlewares::{Middleware, MiddlewareType},
+    next::Next,
+    req::HttpRequest,
+    res::HttpResponse,
+    types::{RouteBodyLimits, RouteNames, RouterFns, Routes, StreamedBodyRoutes},
 };
-use std::collections::HashMap;
+use std::collections::{HashMap, HashSet};
+
+#[cfg(feature = "openapi")]
+use crate::types::RouteDocs;
+
+/// The [`RouteHandle`](route_handle::RouteHandle) builder returned by [`RouterFns::route`].
+pub mod route_handle;
+pub(crate) mod route_pattern;
 
 /// A modular router for grouping and mounting routes under a common base path.
 ///
@@ -81,13 +106,31 @@ pub struct Router {
     ///
     /// All routes registered with this router will be prefixed with this path
     /// when mounted to an application.
-    pub(crate) base_path: &'static str,
+    pub(crate) base_path: String,
 
     /// The collection of routes registered on this router.
     ///
     /// This is a map from route paths (relative to the base path) to their
     /// associated HTTP method handlers.
     routes: Routes,
+
+    /// Route names, mapped to paths relative to the base path.
+    route_names: RouteNames,
+
+    /// Route body limits, keyed by paths relative to the base path.
+    route_body_limits: RouteBodyLimits,
+
+    /// Routes that stream their request body, keyed by paths relative to the base path.
+    streamed_body_routes: StreamedBodyRoutes,
+
+    /// OpenAPI docs of routes, keyed by paths relative to the base path.
+    #[cfg(feature = "openapi")]
+    route_docs: RouteDocs,
+
+    /// Middleware attached to this router, with paths relative to the base path.
+    ///
+    /// These are added to the app's middleware chain when the router is mounted.
+    pub(crate) middlewares: Vec<Middleware>,
 }
 
 impl Router {
@@ -110,13 +153,163 @@ impl Router {
     /// use ripress::router::Router;
     /// let router = Router::new("/api");
     /// ```
-    pub fn new(base_path: &'static str) -> Self {
+    pub fn new(base_path: impl Into<String>) -> Self {
         Router {
-            base_path,
+            base_path: base_path.into(),
             routes: HashMap::new(),
+            route_names: HashMap::new(),
+            route_body_limits: HashMap::new(),
+            streamed_body_routes: HashSet::new(),
+            #[cfg(feature = "openapi")]
+            route_docs: HashMap::new(),
+            middlewares: Vec::new(),
         }
     }
 
+    /// Mounts another router inside this one.
+    ///
+    /// The sub-router's routes are prefixed with its base path and merged into
+    /// this router, so they end up under both base paths once this router is
+    /// mounted on an app. Route params such as `:id` are kept as-is. If a route
+    /// already exists for the same path and method, it is replaced.
+    ///
+    /// # Arguments
+    ///
+    /// * `sub` - The router to nest, relative to this router's base path.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use ripress::{router::Router, app::App};
+    /// use ripress::{req::HttpRequest, res::HttpResponse};
+    /// use ripress::types::RouterFns;
+    ///
+    /// async fn user(req: HttpRequest, res: HttpResponse) -> HttpResponse {
+    ///     let id = req.params.get("id").unwrap_or_default().to_string();
+    ///     res.ok().text(id)
+    /// }
+    ///
+    /// let mut v1 = Router::new("/v1");
+    /// v1.get("/users/:id", user);
+    ///
+    /// let mut api = Router::new("/api");
+    /// api.router(v1);
+    ///
+    /// let mut app = App::new();
+    /// app.router(api); // serves GET /api/v1/users/:id
+    /// ```
+    pub fn router(&mut self, mut sub: Router) {
+        let base_path = std::mem::take(&mut sub.base_path);
+        for (path, methods) in sub.routes().drain() {
+            let full_path = join_paths(&base_path, &path);
+            let entry = self.routes.entry(full_path).or_default();
+            for (method, handler) in methods {
+                entry.insert(method, handler);
+            }
+        }
+
+        for (name, path) in sub.route_names {
+            self.route_names.insert(name, join_paths(&base_path, &path));
+        }
+
+        for ((path, method), limit) in sub.route_body_limits {
+            self.route_body_limits
+                .insert((join_paths(&base_path, &path), method), limit);
+        }
+
+        for (path, method) in sub.streamed_body_routes {
+            self.streamed_body_routes
+                .insert((join_paths(&base_path, &path), method));
+        }
+
+        #[cfg(feature = "openapi")]
+        for ((path, method), operation) in sub.route_docs {
+            self.route_docs
+                .insert((join_paths(&base_path, &path), method), operation);
+        }
+
+        for mut middleware in sub.middlewares {
+            middleware.path = join_paths(&base_path, &middleware.path);
+            self.middlewares.push(middleware);
+        }
+    }
+
+    /// Adds a pre-execution middleware that runs for this router's routes.
+    ///
+    /// The path is relative to the router's base path; `None` applies the
+    /// middleware to every route in the router. When the router is mounted with
+    /// [`App::router`], its middleware is appended to the app's chain at that
+    /// point, so it runs after app middleware registered before mounting and
+    /// before app middleware registered after it. Router middleware runs in the
+    /// order it was added.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use ripress::{router::Router, app::App};
+    /// use ripress::{req::HttpRequest, res::HttpResponse};
+    /// use ripress::types::RouterFns;
+    ///
+    /// async fn dashboard(_req: HttpRequest, res: HttpResponse) -> HttpResponse {
+    ///     res.ok().text("Welcome, admin")
+    /// }
+    ///
+    /// let mut admin = Router::new("/admin");
+    /// admin.use_pre_middleware(None, |req: HttpRequest, res, next| async move {
+    ///     if req.headers.get("authorization").is_none() {
+    ///         return (req, Some(res.unauthorized().text("Unauthorized")));
+    ///     }
+    ///     next.call(req, res).await
+    /// });
+    /// admin.get("/dashboard", dashboard);
+    ///
+    /// let mut app = App::new();
+    /// app.router(admin);
+    /// ```
+    pub fn use_pre_middleware<F, Fut, P>(&mut self, path: P, middleware: F) -> &mut Self
+    where
+        P: Into<Option<&'static str>>,
+        F: Fn(HttpRequest, HttpResponse, Next) -> Fut + Send + Sync + 'static,
+        Fut: std::future::Future<Output = (HttpRequest, Option<HttpResponse>)> + Send + 'static,
+    {
+        self.middlewares.push(Middleware {
+            func: App::middleware_from_closure(middleware),
+            path: path.into().unwrap_or("/").to_string(),
+            middleware_type: MiddlewareType::Pre,
+        });
+        self
+    }
+
+    /// Adds a post-execution middleware that runs for this router's routes.
+    ///
+    /// The path is relative to the router's base path; `None` applies the
+    /// middleware to every route in the router. Ordering follows the same rules
+    /// as [`Router::use_pre_middleware`].
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use ripress::{router::Router, req::HttpRequest};
+    ///
+    /// let mut api = Router::new("/api");
+    /// api.use_post_middleware(None, |req: HttpRequest, res, _next| async move {
+    ///     (req, Some(res.set_header("Cache-Control", "no-store")))
+    /// });
+    /// ```
+    pub fn use_post_middleware<F, Fut, P>(&mut self, path: P, middleware: F) -> &mut Self
+    where
+        P: Into<Option<&'static str>>,
+        F: Fn(HttpRequest, HttpResponse, Next) -> Fut + Send + Sync + 'static,
+        Fut: std::future::Future<Output = (HttpRequest, Option<HttpResponse>)> + Send + 'static,
+    {
+        self.middlewares.push(Middleware {
+            func: App::middleware_from_closure(middleware),
+            path: path.into().unwrap_or("/").to_string(),
+            middleware_type: MiddlewareType::Post,
+        });
+        self
+    }
+
     /// Registers a router with an app.
     ///
     /// ## Arguments
@@ -155,4 +348,33 @@ impl RouterFns for Router {
     fn routes(&mut self) -> &mut Routes {
         &mut self.routes
     }
+
+    fn route_names(&mut self) -> &mut RouteNames {
+        &mut self.route_names
+    }
+
+    fn route_body_limits(&mut self) -> &mut RouteBodyLimits {
+        &mut self.route_body_limits
+    }
+
+    fn streamed_body_routes(&mut self) -> &mut StreamedBodyRoutes {
+        &mut self.streamed_body_routes
+    }
+
+    #[cfg(feature = "openapi")]
+    fn route_docs(&mut self) -> &mut RouteDocs {
+        &mut self.route_docs
+    }
+}
+
+/// Joins a router base path and a route path without doubling or dropping
+/// the `/` between them.
+pub(crate) fn join_paths(base: &str, path: &str) -> String {
+    let base = base.trim_end_matches('/');
+    match path {
+        "" | "/" if base.is_empty() => "/".to_string(),
+        "" | "/" => base.to_string(),
+        _ if path.starts_with('/') => format!("{}{}", base, path),
+        _ => format!("{}/{}", base, path),
+    }
 }
@@ -0,0 +1,231 @@
+#![warn(missing_docs)]
+//! Chainable registration of a single route, see [`RouterFns::route`].
+
+use std::{future::Future, sync::Arc};
+
+use crate::{
+    app::App,
+    helpers::ExtractFromOwned,
+    next::Next,
+    req::HttpRequest,
+    res::HttpResponse,
+    types::{HttpMethods, MiddlewareHandler, RouteHandler, RouterFns},
+};
+
+#[cfg(feature = "openapi")]
+use crate::app::openapi::Operation;
+
+/// A route being configured before it is registered.
+///
+/// Created by [`RouterFns::route`]. Configure the route with
+/// [`name`](RouteHandle::name) and [`with_middleware`](RouteHandle::with_middleware),
+/// then register it by calling [`handler`](RouteHandle::handler); nothing is
+/// registered until then.
+///
+/// # Example
+///
+/// ```
+/// use ripress::{app::App, req::HttpRequest, types::{HttpMethods, RouterFns}};
+///
+/// let mut app = App::new();
+/// app.route(HttpMethods::GET, "/users/:id")
+///     .name("user")
+///     .with_middleware(|req: HttpRequest, res, next| async move {
+///         if req.headers.get("authorization").is_none() {
+///             return (req, Some(res.unauthorized()));
+///         }
+///         next.call(req, res).await
+///     })
+///     .handler(|req: HttpRequest, res| async move {
+///         let id = req.params.get("id").unwrap_or_default().to_string();
+///         res.ok().text(id)
+///     });
+///
+/// assert_eq!(app.url_for("user", &[("id", "42")]), Some("/users/42".to_string()));
+/// ```
+#[must_use = "the route is only registered by calling `.handler(...)`"]
+pub struct RouteHandle<'a, R: RouterFns> {
+    router: &'a mut R,
+    method: HttpMethods,
+    path: String,
+    name: Option<String>,
+    middlewares: Vec<MiddlewareHandler>,
+    body_limit: Option<usize>,
+    stream_body: bool,
+    #[cfg(feature = "openapi")]
+    operation: Option<Operation>,
+}
+
+impl<'a, R: RouterFns> RouteHandle<'a, R> {
+    pub(crate) fn new(router: &'a mut R, method: HttpMethods, path: &str) -> Self {
+        RouteHandle {
+            router,
+            method,
+            path: path.to_string(),
+            name: None,
+            middlewares: Vec::new(),
+            body_limit: None,
+            stream_body: false,
+            #[cfg(feature = "openapi")]
+            operation: None,
+        }
+    }
+
+    /// Names the route so its URL can be built with [`RouterFns::url_for`].
+    ///
+    /// Names are kept when a [`Router`](crate::router::Router) is mounted, with
+    /// the router's base path prepended. A name used twice refers to the route
+    /// registered last.
+    pub fn name(mut self, name: impl Into<String>) -> Self {
+        self.name = Some(name.into());
+        self
+    }
+
+    /// Adds a middleware that runs only for this route, just before its handler.
+    ///
+    /// Route middlewares run after the app and router middlewares, in the order
+    /// they were added. As with other middlewares, returning a response ends the
+    /// request, and headers and cookies passed to `next.call` are added to the
+    /// handler's response.
+    pub fn with_middleware<F, Fut>(mut self, middleware: F) -> Self
+    where
+        F: Fn(HttpRequest, HttpResponse, Next) -> Fut + Send + Sync + 'static,
+        Fut: Future<Output = (HttpRequest, Option<HttpResponse>)> + Send + 'static,
+    {
+        self.middlewares
+            .push(App::middleware_from_closure(middleware));
+        self
+    }
+
+    /// Sets the most bytes this route accepts in a request body, in place of
+    /// the limit set with [`App::use_body_limit`].
+    ///
+    /// Like that limit, it is enforced while the body is read, and handlers can
+    /// read it with [`HttpRequest::body_limit`].
+    pub fn body_limit(mut self, limit: usize) -> Self {
+        self.body_limit = Some(limit);
+        self
+    }
+
+    /// Leaves the request body on the connection for the handler to read with
+    /// [`HttpRequest::body_stream`], instead of reading it before the handler runs.
+    ///
+    /// The body accessors such as [`HttpRequest::json`] then see an empty body.
+    /// A body limit still applies, but is enforced as the stream is read.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use futures::StreamExt;
+    /// use ripress::{app::App, req::HttpRequest, types::{HttpMethods, RouterFns}};
+    ///
+    /// let mut app = App::new();
+    /// app.route(HttpMethods::POST, "/upload")
+    ///     .stream_body()
+    ///     .handler(|req: HttpRequest, res| async move {
+    ///         let mut body = req.body_stream();
+    ///         let mut received = 0;
+    ///         while let Some(chunk) = body.next().await {
+    ///             match chunk {
+    ///                 Ok(chunk) => received += chunk.len(),
+    ///                 Err(e) => return res.bad_request().text(e.to_string()),
+    ///             }
+    ///         }
+    ///         res.ok().text(format!("{} bytes", received))
+    ///     });
+    /// ```
+    pub fn stream_body(mut self) -> Self {
+        self.stream_body = true;
+        self
+    }
+
+    /// Documents the route for [`App::openapi`].
+    ///
+    /// Routes without docs are still listed, with their path params and a
+    /// default response.
+    #[cfg(feature = "openapi")]
+    pub fn openapi(mut self, operation: Operation) -> Self {
+        self.operation = Some(operation);
+        self
+    }
+
+    /// Registers the route with `handler`, which can use extractors like the
+    /// handlers passed to [`RouterFns::get`] and friends.
+    ///
+    /// Returns the router or app, so more routes can be chained.
+    pub fn handler<F, HFut, P>(self, handler: F) -> &'a mut R
+    where
+        F: Fn(P, HttpResponse) -> HFut + Send + Sync + 'static,
+        HFut: Future<Output = HttpResponse> + Send + 'static,
+        P: ExtractFromOwned + Send + 'static,
+    {
+        let RouteHandle {
+            router,
+            method,
+            path,
+            name,
+            middlewares,
+            body_limit,
+            stream_body,
+            #[cfg(feature = "openapi")]
+            operation,
+        } = self;
+
+        router.add_route_with_extraction(method.clone(), &path, handler);
+
+        if !middlewares.is_empty() {
+            let slot = router
+                .routes()
+                .get_mut(&path)
+                .and_then(|methods| methods.get_mut(&method))
+                .expect("route was just registered");
+            *slot = with_route_middlewares(Arc::clone(slot), middlewares.into());
+        }
+
+        if let Some(limit) = body_limit {
+            router
+                .route_body_limits()
+                .insert((path.clone(), method.clone()), limit);
+        }
+
+        if stream_body {
+            router
+                .streamed_body_routes()
+                .insert((path.clone(), method.clone()));
+        }
+
+        #[cfg(feature = "openapi")]
+        if let Some(operation) = operation {
+            router
+                .route_docs()
+                .insert((path.clone(), method), operation);
+        }
+
+        if let Some(name) = name {
+            router.route_names().insert(name, path);
+        }
+
+        router
+    }
+}
+
+/// Wraps `handler` so `middlewares` run before it.
+fn with_route_middlewares(
+    handler: RouteHandler,
+    middlewares: Arc<[MiddlewareHandler]>,
+) -> RouteHandler {
+    Arc::new(move |mut req: HttpRequest, res: HttpResponse| {
+        let handler = Arc::clone(&handler);
+        let middlewares = Arc::clone(&middlewares);
+        Box::pin(async move {
+            for middleware in middlewares.iter() {
+                let (next_req, early) = middleware(req, HttpResponse::new(), Next).await;
+                if let Some(response) = early {
+                    return response;
+                }
+                req = next_req;
+            }
+            handler(req, res).await
+        })
+    })
+}
@@ -0,0 +1,373 @@
+use std::sync::Arc;
+
+use regex::Regex;
+
+use crate::types::{HttpMethods, RouteHandler, Routes};
+
+/// A parsed route path such as `/users/:id(\d+)` or `/files/*path`.
+///
+/// Supported segment syntax:
+/// - `users` matches the segment literally
+/// - `:id` captures one segment into the `id` param
+/// - `:id(\d+)` captures one segment only if the whole segment matches the regex
+/// - `*path` captures the rest of the path into the `path` param; a bare `*`
+///   stores it under `*`. Wildcards must be the last segment.
+#[derive(Clone, Debug)]
+pub(crate) struct RoutePattern {
+    segments: Vec<Segment>,
+}
+
+#[derive(Clone, Debug)]
+enum Segment {
+    Static(String),
+    Param {
+        name: String,
+        constraint: Option<Regex>,
+    },
+    Wildcard(String),
+}
+
+impl RoutePattern {
+    /// Parses a route path, returning an error for an invalid regex constraint
+    /// or a wildcard that is not the last segment.
+    pub(crate) fn parse(pattern: &str) -> Result<Self, String> {
+        let raw_segments = split_segments(pattern.trim_end_matches('/'));
+        let last = raw_segments.len().saturating_sub(1);
+        let mut segments = Vec::with_capacity(raw_segments.len());
+
+        for (i, raw) in raw_segments.into_iter().enumerate() {
+            let segment = if let Some(name) = raw.strip_prefix('*') {
+                if i != last {
+                    return Err(format!("wildcard `{}` must be the last segment", raw));
+                }
+                let name = if name.is_empty() { "*" } else { name };
+                Segment::Wildcard(name.to_string())
+            } else if let Some(param) = raw.strip_prefix(':') {
+                match param.split_once('(') {
+                    Some((name, constraint)) => {
+                        let constraint = constraint
+                            .strip_suffix(')')
+                            .ok_or_else(|| format!("unclosed constraint in `{}`", raw))?;
+                        let regex = Regex::new(&format!("^(?:{})$", constraint))
+                            .map_err(|e| format!("invalid constraint in `{}`: {}", raw, e))?;
+                        Segment::Param {
+                            name: name.to_string(),
+                            constraint: Some(regex),
+                        }
+                    }
+                    None => Segment::Param {
+                        name: param.to_string(),
+                        constraint: None,
+                    },
+                }
+            } else {
+                Segment::Static(raw.to_string())
+            };
+            segments.push(segment);
+        }
+
+        Ok(RoutePattern { segments })
+    }
+
+    /// Returns the pattern in the plain `:name` / `*` syntax `routerify_ng` understands.
+    ///
+    /// Constraints are dropped here; they are checked by [`RoutePattern::matches`].
+    pub(crate) fn routerify_path(&self) -> String {
+        let path = self
+            .segments
+            .iter()
+            .map(|segment| match segment {
+                Segment::Static(text) => text.clone(),
+                Segment::Param { name, .. } => format!(":{}", name),
+                Segment::Wildcard(_) => "*".to_string(),
+            })
+            .collect::<Vec<_>>()
+            .join("/");
+
+        if path.is_empty() {
+            "/".to_string()
+        } else {
+            path
+        }
+    }
+
+    /// Returns the pattern in OpenAPI's `/users/{id}` syntax, with the name and
+    /// regex constraint of each param in order.
+    #[cfg(feature = "openapi")]
+    pub(crate) fn openapi_path(&self) -> (String, Vec<(&str, Option<&str>)>) {
+        let mut params = Vec::new();
+        let path = self
+            .segments
+            .iter()
+            .map(|segment| match segment {
+                Segment::Static(text) => text.clone(),
+                Segment::Param { name, constraint } => {
+                    params.push((name.as_str(), constraint.as_ref().map(Regex::as_str)));
+                    format!("{{{}}}", name)
+                }
+                Segment::Wildcard(name) => {
+                    params.push((name.as_str(), None));
+                    format!("{{{}}}", name)
+                }
+            })
+            .collect::<Vec<_>>()
+            .join("/");
+
+        let path = if path.is_empty() {
+            "/".to_string()
+        } else {
+            path
+        };
+        (path, params)
+    }
+
+    /// Fills in the pattern's params, percent-encoding their values.
+    ///
+    /// Returns `None` if a param is missing, empty or fails its constraint.
+    pub(crate) fn build(&self, params: &[(&str, &str)]) -> Option<String> {
+        let value = |name: &str| {
+            params
+                .iter()
+                .find(|(key, _)| *key == name)
+                .map(|(_, value)| *value)
+        };
+
+        let mut parts = Vec::with_capacity(self.segments.len());
+        for segment in &self.segments {
+            parts.push(match segment {
+                Segment::Static(text) => text.clone(),
+                Segment::Param { name, constraint } => {
+                    let value = value(name).filter(|value| !value.is_empty())?;
+                    if constraint
+                        .as_ref()
+                        .is_some_and(|regex| !regex.is_match(value))
+                    {
+                        return None;
+                    }
+                    crate::url::encode(value).into_owned()
+                }
+                Segment::Wildcard(name) => value(name)?
+                    .split('/')
+                    .map(crate::url::encode)
+                    .collect::<Vec<_>>()
+                    .join("/"),
+            });
+        }
+
+        let path = parts.join("/");
+        Some(if path.is_empty() {
+            "/".to_string()
+        } else {
+            path
+        })
+    }
+
+    /// Matches a request path, returning the percent-decoded params on success.
+    ///
+    /// Without `case_sensitive`, literal segments ignore ASCII case; params
+    /// still capture the path's own case.
+    pub(crate) fn matches(
+        &self,
+        path: &str,
+        case_sensitive: bool,
+    ) -> Option<Vec<(String, String)>> {
+        let path_segments: Vec<&str> = path.trim_end_matches('/').split('/').collect();
+        let mut params = Vec::new();
+
+        for (i, segment) in self.segments.iter().enumerate() {
+            match segment {
+                Segment::Wildcard(name) => {
+                    let rest = path_segments.get(i..).unwrap_or_default().join("/");
+                    params.push((name.clone(), decode(&rest)));
+                    return Some(params);
+                }
+                Segment::Static(text) => {
+                    let actual = path_segments.get(i)?;
+                    let matched = if case_sensitive {
+                        actual == text
+                    } else {
+                        actual.eq_ignore_ascii_case(text)
+                    };
+                    if !matched {
+                        return None;
+                    }
+                }
+                Segment::Param { name, constraint } => {
+                    let actual = path_segments.get(i)?;
+                    if actual.is_empty() {
+                        return None;
+                    }
+                    let value = decode(actual);
+                    if let Some(regex) = constraint {
+                        if !regex.is_match(&value) {
+                            return None;
+                        }
+                    }
+                    params.push((name.clone(), value));
+                }
+            }
+        }
+
+        (self.segments.len() == path_segments.len()).then_some(params)
+    }
+
+    /// Ranks how specific the pattern is; lower ranks win when several match.
+    ///
+    /// Segments are compared left to right: a literal beats a constrained param,
+    /// which beats a plain param, which beats a wildcard.
+    fn rank(&self) -> Vec<u8> {
+        self.segments
+            .iter()
+            .map(|segment| match segment {
+                Segment::Static(_) => 0,
+                Segment::Param {
+                    constraint: Some(_),
+                    ..
+                } => 1,
+                Segment::Param { .. } => 2,
+                Segment::Wildcard(_) => 3,
+            })
+            .collect()
+    }
+}
+
+/// All routes of an app, parsed once and ordered by specificity.
+pub(crate) struct RouteTable {
+    entries: Vec<RouteEntry>,
+    case_sensitive: bool,
+}
+
+/// A single registered path with its per-method handlers.
+pub(crate) struct RouteEntry {
+    pub(crate) path: String,
+    pub(crate) pattern: RoutePattern,
+    pub(crate) methods: Vec<(HttpMethods, RouteHandler)>,
+}
+
+/// The outcome of a successful [`RouteTable::find`].
+pub(crate) struct RouteMatch {
+    pub(crate) handler: RouteHandler,
+    pub(crate) params: Vec<(String, String)>,
+    /// The path the route was registered with, e.g. `/users/:id`
+    pub(crate) pattern: String,
+}
+
+/// Response extension carrying the pattern of the route that produced the
+/// response, so post-middlewares can see it.
+#[derive(Clone, Debug)]
+pub(crate) struct MatchedPath(pub(crate) String);
+
+impl RouteTable {
+    /// Builds a table from registered routes, matching literal segments
+    /// case-insensitively unless `case_sensitive` is set.
+    ///
+    /// # Panics
+    ///
+    /// Panics if a route path has an invalid regex constraint or a misplaced wildcard.
+    pub(crate) fn new(routes: &Routes, case_sensitive: bool) -> Self {
+        let mut entries: Vec<RouteEntry> = routes
+            .iter()
+            .map(|(path, methods)| {
+                let pattern = RoutePattern::parse(path)
+                    .unwrap_or_else(|e| panic!("invalid route path `{}`: {}", path, e));
+                let methods = methods
+                    .iter()
+                    .map(|(method, handler)| (method.clone(), Arc::clone(handler)))
+                    .collect();
+                RouteEntry {
+                    path: path.clone(),
+                    pattern,
+                    methods,
+                }
+            })
+            .collect();
+        // Ties are broken by path so the order doesn't depend on hash order.
+        entries.sort_by_cached_key(|entry| (entry.pattern.rank(), entry.path.clone()));
+        RouteTable {
+            entries,
+            case_sensitive,
+        }
+    }
+
+    /// Returns every registered route, most specific first.
+    pub(crate) fn entries(&self) -> &[RouteEntry] {
+        &self.entries
+    }
+
+    /// Returns the standard methods followed by any extension methods with a
+    /// registered route.
+    pub(crate) fn methods(&self) -> Vec<HttpMethods> {
+        let mut methods = vec![
+            HttpMethods::GET,
+            HttpMethods::POST,
+            HttpMethods::PUT,
+            HttpMethods::HEAD,
+            HttpMethods::DELETE,
+            HttpMethods::PATCH,
+            HttpMethods::OPTIONS,
+            HttpMethods::CONNECT,
+            HttpMethods::TRACE,
+        ];
+        for entry in &self.entries {
+            for (method, _) in &entry.methods {
+                if !methods.contains(method) {
+                    methods.push(method.clone());
+                }
+            }
+        }
+        methods
+    }
+
+    /// Returns the methods registered for any route matching `path`, sorted and deduplicated.
+    pub(crate) fn allowed_methods(&self, path: &str) -> Vec<HttpMethods> {
+        let mut methods: Vec<HttpMethods> = self
+            .entries
+            .iter()
+            .filter(|entry| entry.pattern.matches(path, self.case_sensitive).is_some())
+            .flat_map(|entry| entry.methods.iter().map(|(method, _)| method.clone()))
+            .collect();
+        methods.sort();
+        methods.dedup();
+        methods
+    }
+
+    /// Finds the most specific handler for `method` and `path`, with its params.
+    pub(crate) fn find(&self, method: &HttpMethods, path: &str) -> Option<RouteMatch> {
+        self.entries.iter().find_map(|entry| {
+            let (_, handler) = entry.methods.iter().find(|(m, _)| m == method)?;
+            let params = entry.pattern.matches(path, self.case_sensitive)?;
+            Some(RouteMatch {
+                handler: Arc::clone(handler),
+                params,
+                pattern: entry.path.clone(),
+            })
+        })
+    }
+}
+
+/// Splits a path on `/`, ignoring slashes inside constraint parentheses.
+fn split_segments(pattern: &str) -> Vec<&str> {
+    let mut segments = Vec::new();
+    let mut depth = 0usize;
+    let mut start = 0;
+
+    for (i, c) in pattern.char_indices() {
+        match c {
+            '(' => depth += 1,
+            ')' => depth = depth.saturating_sub(1),
+            '/' if depth == 0 => {
+                segments.push(&pattern[start..i]);
+                start = i + 1;
+            }
+            _ => {}
+        }
+    }
+    segments.push(&pattern[start..]);
+    segments
+}
+
+fn decode(value: &str) -> String {
+    crate::url::decode(value)
+        .map(|v| v.into_owned())
+        .unwrap_or_else(|_| value.to_string())
+}
@@ -3,7 +3,11 @@ mod tests {
     use crate::next::Next;
     use crate::res::ResponseBody;
     use crate::{
-        app::{api_error::ApiError, settings::Http2Config, App},
+        app::{
+            api_error::ApiError,
+            settings::{Http2Config, RuntimeConfig, StaticOptions},
+            App,
+        },
         context::HttpResponse,
         helpers::box_future,
         middlewares::MiddlewareType,
@@ -114,6 +118,24 @@ mod tests {
         assert_eq!(body_str, "Bad request test");
     }
 
+    #[tokio::test]
+    async fn test_error_handler_keeps_streamed_body() {
+        let chunks = futures::StreamExt::map(
+            futures::stream::iter(["Too ", "many ", "requests"]),
+            |chunk| Ok::<Bytes, std::io::Error>(Bytes::from(chunk)),
+        );
+        let response = HttpResponse::new().status(429).write(chunks);
+        let route_err = RouteError::from(ApiError::Generic(response));
+
+        let result = crate::res::conversions::into_outgoing_response(
+            crate::app::App::error_handler(route_err).await,
+        );
+
+        assert_eq!(result.status(), StatusCode::TOO_MANY_REQUESTS);
+        let body_bytes = result.into_body().collect().await.unwrap().to_bytes();
+        assert_eq!(&body_bytes[..], b"Too many requests");
+    }
+
     #[tokio::test]
     async fn test_error_handler_ws_error() {
         let response = Response::builder()
@@ -227,6 +249,303 @@ mod tests {
         assert_eq!(result.unwrap().status(), StatusCode::NOT_FOUND);
     }
 
+    #[tokio::test]
+    async fn test_serve_static_with_headers_range() {
+        let dir = tempdir().unwrap();
+        let file_path = dir.path().join("video.bin");
+        std::fs::write(&file_path, vec![b'x'; 4096]).unwrap();
+
+        let mount_root = "/static".to_string();
+        let fs_root = dir.path().to_str().unwrap().to_string();
+
+        let full = Request::builder()
+            .uri("/static/video.bin")
+            .body(Full::from(Bytes::new()))
+            .unwrap();
+        let resp =
+            crate::app::App::serve_static_with_headers(full, mount_root.clone(), fs_root.clone())
+                .await
+                .unwrap();
+        assert_eq!(resp.status(), StatusCode::OK);
+        assert_eq!(resp.headers().get(header::ACCEPT_RANGES).unwrap(), "bytes");
+
+        let partial = Request::builder()
+            .uri("/static/video.bin")
+            .header(header::RANGE, "bytes=0-1023")
+            .body(Full::from(Bytes::new()))
+            .unwrap();
+        let resp =
+            crate::app::App::serve_static_with_headers(partial, mount_root.clone(), fs_root.clone())
+                .await
+                .unwrap();
+        assert_eq!(resp.status(), StatusCode::PARTIAL_CONTENT);
+        assert_eq!(
+            resp.headers().get(header::CONTENT_RANGE).unwrap(),
+            "bytes 0-1023/4096"
+        );
+        let body_bytes = resp.into_body().collect().await.unwrap().to_bytes();
+        assert_eq!(body_bytes.len(), 1024);
+
+        let multi = Request::builder()
+            .uri("/static/video.bin")
+            .header(header::RANGE, "bytes=0-9, 20-29")
+            .body(Full::from(Bytes::new()))
+            .unwrap();
+        let resp =
+            crate::app::App::serve_static_with_headers(multi, mount_root.clone(), fs_root.clone())
+                .await
+                .unwrap();
+        assert_eq!(resp.status(), StatusCode::OK);
+        let body_bytes = resp.into_body().collect().await.unwrap().to_bytes();
+        assert_eq!(body_bytes.len(), 4096);
+
+        let unsatisfiable = Request::builder()
+            .uri("/static/video.bin")
+            .header(header::RANGE, "bytes=5000-6000")
+            .body(Full::from(Bytes::new()))
+            .unwrap();
+        let resp = crate::app::App::serve_static_with_headers(unsatisfiable, mount_root, fs_root)
+            .await
+            .unwrap();
+        assert_eq!(resp.status(), StatusCode::RANGE_NOT_SATISFIABLE);
+        assert_eq!(
+            resp.headers().get(header::CONTENT_RANGE).unwrap(),
+            "bytes */4096"
+        );
+    }
+
+    #[tokio::test]
+    async fn test_serve_static_with_options_cache_headers() {
+        let dir = tempdir().unwrap();
+        std::fs::write(dir.path().join("app.3f2a.js"), "console.log(1)").unwrap();
+        std::fs::write(dir.path().join("index.html"), "<h1>hi</h1>").unwrap();
+
+        let fs_root = dir.path().to_str().unwrap().to_string();
+
+        let immutable = StaticOptions {
+            max_age: 31536000,
+            immutable: true,
+            ..Default::default()
+        };
+        let req = Request::builder()
+            .uri("/assets/app.3f2a.js")
+            .body(Full::from(Bytes::new()))
+            .unwrap();
+        let resp = crate::app::App::serve_static_with_options(
+            req,
+            "/assets".to_string(),
+            fs_root.clone(),
+            &immutable,
+        )
+        .await
+        .unwrap();
+        assert_eq!(
+            resp.headers().get(header::CACHE_CONTROL).unwrap(),
+            "public, max-age=31536000, immutable"
+        );
+        assert!(resp.headers().get(header::LAST_MODIFIED).is_some());
+
+        let no_cache = StaticOptions {
+            no_cache: true,
+            public: false,
+            last_modified: false,
+            ..Default::default()
+        };
+        let req = Request::builder()
+            .uri("/index.html")
+            .body(Full::from(Bytes::new()))
+            .unwrap();
+        let resp =
+            crate::app::App::serve_static_with_options(req, "/".to_string(), fs_root, &no_cache)
+                .await
+                .unwrap();
+        assert_eq!(
+            resp.headers().get(header::CACHE_CONTROL).unwrap(),
+            "private, no-cache"
+        );
+        assert!(resp.headers().get(header::LAST_MODIFIED).is_none());
+    }
+
+    #[tokio::test]
+    async fn test_serve_static_spa_fallback() {
+        let dir = tempdir().unwrap();
+        std::fs::write(dir.path().join("index.html"), "<div id=app></div>").unwrap();
+
+        let fs_root = dir.path().to_str().unwrap().to_string();
+        let options = StaticOptions {
+            spa_fallback: Some("index.html"),
+            ..Default::default()
+        };
+
+        let navigation = Request::builder()
+            .uri("/dashboard/settings")
+            .header(header::ACCEPT, "text/html,application/xhtml+xml,*/*;q=0.8")
+            .body(Full::from(Bytes::new()))
+            .unwrap();
+        let resp = crate::app::App::serve_static_with_options(
+            navigation,
+            "/".to_string(),
+            fs_root.clone(),
+            &options,
+        )
+        .await
+        .unwrap();
+        assert_eq!(resp.status(), StatusCode::OK);
+        let body_bytes = resp.into_body().collect().await.unwrap().to_bytes();
+        assert_eq!(body_bytes, "<div id=app></div>");
+
+        let asset = Request::builder()
+            .uri("/assets/missing.js")
+            .header(header::ACCEPT, "*/*")
+            .body(Full::from(Bytes::new()))
+            .unwrap();
+        let resp =
+            crate::app::App::serve_static_with_options(asset, "/".to_string(), fs_root, &options)
+                .await
+                .unwrap();
+        assert_eq!(resp.status(), StatusCode::NOT_FOUND);
+    }
+
+    #[tokio::test]
+    async fn test_serve_static_directory_listing() {
+        let dir = tempdir().unwrap();
+        std::fs::create_dir(dir.path().join("b_dir")).unwrap();
+        std::fs::write(dir.path().join("a.txt"), "a").unwrap();
+        std::fs::write(dir.path().join("<script>.txt"), "x").unwrap();
+
+        let fs_root = dir.path().to_str().unwrap().to_string();
+        let listing_req = || {
+            Request::builder()
+                .uri("/files/")
+                .body(Full::from(Bytes::new()))
+                .unwrap()
+        };
+
+        let options = StaticOptions {
+            directory_listing: true,
+            ..Default::default()
+        };
+        let resp = crate::app::App::serve_static_with_options(
+            listing_req(),
+            "/files".to_string(),
+            fs_root.clone(),
+            &options,
+        )
+        .await
+        .unwrap();
+        assert_eq!(resp.status(), StatusCode::OK);
+        assert_eq!(
+            resp.headers().get(header::CONTENT_TYPE).unwrap(),
+            "text/html; charset=utf-8"
+        );
+
+        let body_bytes = resp.into_body().collect().await.unwrap().to_bytes();
+        let html = String::from_utf8(body_bytes.to_vec()).unwrap();
+        assert!(html.contains("Index of /files/"));
+        assert!(html.contains("<a href=\"a.txt\">a.txt</a>"));
+        assert!(html.contains("&lt;script&gt;.txt"));
+        assert!(!html.contains("<script>"));
+        assert!(html.find("b_dir/").unwrap() < html.find("a.txt").unwrap());
+
+        let resp = crate::app::App::serve_static_with_options(
+            listing_req(),
+            "/files".to_string(),
+            fs_root,
+            &StaticOptions::default(),
+        )
+        .await
+        .unwrap();
+        assert_eq!(resp.status(), StatusCode::NOT_FOUND);
+    }
+
+    #[tokio::test]
+    async fn test_serve_static_precompressed() {
+        let dir = tempdir().unwrap();
+        std::fs::write(dir.path().join("app.js"), "console.log('raw')").unwrap();
+        std::fs::write(dir.path().join("app.js.br"), "BR").unwrap();
+        std::fs::write(dir.path().join("app.js.gz"), "GZ").unwrap();
+
+        let fs_root = dir.path().to_str().unwrap().to_string();
+        let options = StaticOptions {
+            precompressed: true,
+            ..Default::default()
+        };
+
+        let cases = [
+            (Some("gzip, deflate, br"), "BR", Some("br")),
+            (Some("gzip"), "GZ", Some("gzip")),
+            (Some("br;q=0, gzip"), "GZ", Some("gzip")),
+            (None, "console.log('raw')", None),
+        ];
+
+        for (accept_encoding, expected_body, expected_encoding) in cases {
+            let mut builder = Request::builder().uri("/static/app.js");
+            if let Some(value) = accept_encoding {
+                builder = builder.header(header::ACCEPT_ENCODING, value);
+            }
+            let req = builder.body(Full::from(Bytes::new())).unwrap();
+
+            let resp = crate::app::App::serve_static_with_options(
+                req,
+                "/static".to_string(),
+                fs_root.clone(),
+                &options,
+            )
+            .await
+            .unwrap();
+
+            assert_eq!(resp.status(), StatusCode::OK);
+            assert_eq!(
+                resp.headers()
+                    .get(header::CONTENT_ENCODING)
+                    .map(|v| v.to_str().unwrap()),
+                expected_encoding
+            );
+            assert!(resp
+                .headers()
+                .get(header::CONTENT_TYPE)
+                .unwrap()
+                .to_str()
+                .unwrap()
+                .contains("javascript"));
+            assert_eq!(resp.headers().get(header::VARY).unwrap(), "Accept-Encoding");
+
+            let body_bytes = resp.into_body().collect().await.unwrap().to_bytes();
+            assert_eq!(body_bytes, expected_body);
+        }
+    }
+
+    #[test]
+    fn test_sanitize_static_path() {
+        use crate::app::static_files::sanitize_static_path;
+
+        assert_eq!(
+            sanitize_static_path("/docs/my%20files/"),
+            Some(std::path::PathBuf::from("docs/my files"))
+        );
+        assert_eq!(sanitize_static_path("/../etc/"), None);
+        assert_eq!(sanitize_static_path("/a/%2e%2e/b/"), None);
+    }
+
+    #[test]
+    fn test_static_files_with_options_mount() {
+        let mut app = App::new();
+        let options = StaticOptions {
+            no_cache: true,
+            ..Default::default()
+        };
+        assert!(app
+            .static_files_with_options("/", "dist", options.clone())
+            .is_ok());
+        assert_eq!(
+            app.settings.static_files.get("/"),
+            Some(&("dist".to_string(), options))
+        );
+        assert!(app
+            .static_files_with_options("/", "/", StaticOptions::default())
+            .is_err());
+    }
+
     fn dummy_request() -> HttpRequest {
         HttpRequest::new()
     }
@@ -568,12 +887,36 @@ mod tests {
         assert!(app.settings.wynd_config.unwrap().path == "/ws");
     }
 
+    #[test]
+    fn test_static_mounts_and_router_accept_runtime_paths() {
+        // Paths built at runtime, e.g. from configuration, don't need to be leaked.
+        let version = 2;
+        let mut app = App::new();
+        app.static_files(format!("/v{}/assets", version), String::from("public"))
+            .unwrap();
+        let mut router = Router::new(format!("/v{}", version));
+        router.get("/ping", _test_handler);
+        app.router(router);
+
+        assert!(app.get_routes("/v2/ping", HttpMethods::GET).is_some());
+        assert_eq!(
+            app.settings
+                .static_files
+                .get("/v2/assets")
+                .map(|(dir, _)| dir.as_str()),
+            Some("public")
+        );
+    }
+
     #[test]
     fn test_valid_static_file_mount() {
         let mut app = App::new();
         let result = app.static_files("/assets", "public");
         assert!(result.is_ok());
-        assert_eq!(app.settings.static_files.get("/assets"), Some(&"public"));
+        assert_eq!(
+            app.settings.static_files.get("/assets").map(|(dir, _)| dir.as_str()),
+            Some("public")
+        );
     }
 
     #[test]
@@ -626,6 +969,86 @@ mod tests {
         assert_eq!(app.settings.host, "");
     }
 
+    #[tokio::test]
+    async fn test_listen_binds_hostname() {
+        use tokio::io::{AsyncReadExt, AsyncWriteExt};
+
+        let port = std::net::TcpListener::bind("127.0.0.1:0")
+            .unwrap()
+            .local_addr()
+            .unwrap()
+            .port();
+        let mut app = build_test_app();
+        app.host("localhost");
+        let server = task::spawn(async move { app.listen(port, || {}).await });
+
+        let mut stream = None;
+        for _ in 0..50 {
+            if let Ok(s) = tokio::net::TcpStream::connect(("localhost", port)).await {
+                stream = Some(s);
+                break;
+            }
+            sleep(Duration::from_millis(20)).await;
+        }
+        let mut stream = stream.expect("server should bind `localhost`");
+        stream
+            .write_all(b"GET / HTTP/1.1\r\nHost: localhost\r\nConnection: close\r\n\r\n")
+            .await
+            .unwrap();
+        let mut response = String::new();
+        stream.read_to_string(&mut response).await.unwrap();
+        assert!(response.starts_with("HTTP/1.1 200"));
+        assert!(response.ends_with("Hello, world!"));
+
+        server.abort();
+    }
+
+    #[test]
+    fn test_run_serves_on_configured_runtime() {
+        use std::io::Read;
+
+        let port = std::net::TcpListener::bind("127.0.0.1:0")
+            .unwrap()
+            .local_addr()
+            .unwrap()
+            .port();
+        let mut app = App::new();
+        app.host("127.0.0.1");
+        app.get("/", |_req: HttpRequest, res: HttpResponse| async move {
+            let workers = tokio::runtime::Handle::current().metrics().num_workers();
+            res.ok().text(workers.to_string())
+        });
+        std::thread::spawn(move || app.run(RuntimeConfig { workers: 3 }, port, || {}));
+
+        let mut stream = None;
+        for _ in 0..50 {
+            if let Ok(s) = std::net::TcpStream::connect(("127.0.0.1", port)) {
+                stream = Some(s);
+                break;
+            }
+            std::thread::sleep(Duration::from_millis(20));
+        }
+        let mut stream = stream.expect("server should be listening");
+        stream
+            .write_all(b"GET / HTTP/1.1\r\nHost: localhost\r\nConnection: close\r\n\r\n")
+            .unwrap();
+        let mut response = String::new();
+        stream.read_to_string(&mut response).unwrap();
+        assert!(response.starts_with("HTTP/1.1 200"), "{}", response);
+        assert!(response.ends_with("\r\n\r\n3"), "{}", response);
+    }
+
+    #[tokio::test]
+    async fn test_run_refuses_to_nest_runtimes() {
+        let app = build_test_app();
+        let err = app
+            .run(RuntimeConfig::default(), 0, || {
+                panic!("server must not start")
+            })
+            .unwrap_err();
+        assert!(err.to_string().contains("App::listen"), "{}", err);
+    }
+
     #[test]
     fn test_http2_config() {
         let mut app = App::new();
@@ -714,6 +1137,44 @@ mod tests {
         assert_eq!(middleware.middleware_type, MiddlewareType::Pre);
     }
 
+    #[tokio::test]
+    async fn test_use_rate_limiter_on_path() {
+        use crate::middlewares::rate_limiter::RateLimiterConfig;
+
+        let mut app = App::new();
+        app.use_rate_limiter_on(
+            Some("/api/search"),
+            Some(RateLimiterConfig {
+                max_requests: 1,
+                ..Default::default()
+            }),
+        );
+        app.use_rate_limiter_on(
+            Some("/api/health"),
+            Some(RateLimiterConfig {
+                max_requests: 100,
+                ..Default::default()
+            }),
+        );
+
+        assert_eq!(app.middlewares.len(), 2);
+        assert_eq!(app.middlewares[0].path, "/api/search");
+        assert_eq!(app.middlewares[1].path, "/api/health");
+        assert_eq!(app.middlewares[0].middleware_type, MiddlewareType::Pre);
+
+        let search = app.middlewares[0].func.clone();
+        let (_, res) = search(dummy_request(), dummy_response(), Next {}).await;
+        assert!(res.is_none());
+        let (_, res) = search(dummy_request(), dummy_response(), Next {}).await;
+        assert_eq!(res.unwrap().status_code.as_u16(), 429);
+
+        let health = app.middlewares[1].func.clone();
+        for _ in 0..10 {
+            let (_, res) = health(dummy_request(), dummy_response(), Next {}).await;
+            assert!(res.is_none());
+        }
+    }
+
     #[test]
     fn test_multiple_use_middleware() {
         let mut app = App::new();
@@ -746,4 +1207,196 @@ mod tests {
         assert!(app.get_routes("/api", HttpMethods::GET).is_some());
         assert!(app.get_routes("/api/api", HttpMethods::GET).is_some());
     }
+
+    fn tagged_sub_app(tag: &'static str) -> App {
+        let mut sub = App::new();
+        sub.use_pre_middleware(None, move |mut req: HttpRequest, res, next| async move {
+            req.set_data("tag", tag);
+            next.call(req, res).await
+        });
+        sub.use_post_middleware(None, move |req: HttpRequest, res, _next| async move {
+            (req, Some(res.set_header("X-Sub-App", tag)))
+        });
+        sub.get("/", |req: HttpRequest, res| async move {
+            res.ok().text(req.get_data("tag").unwrap_or_default())
+        });
+        sub.get("/items/:id", |req: HttpRequest, res| async move {
+            let id = req.params.get("id").unwrap_or_default().to_string();
+            let tag = req.get_data("tag").unwrap_or_default();
+            res.ok().text(format!("{}:{}", tag, id))
+        });
+        sub
+    }
+
+    #[test]
+    fn test_mount_prefixes_routes_and_static_files() {
+        let mut blog = App::new();
+        blog.get("/posts", _test_handler);
+        blog.static_files("/assets", "public").unwrap();
+
+        let mut app = App::new();
+        app.mount("/blog", blog);
+
+        assert!(app.get_routes("/blog/posts", HttpMethods::GET).is_some());
+        assert!(app.get_routes("/posts", HttpMethods::GET).is_none());
+        assert_eq!(
+            app.settings
+                .static_files
+                .get("/blog/assets")
+                .map(|(dir, _)| dir.as_str()),
+            Some("public")
+        );
+        assert!(app
+            .middlewares
+            .iter()
+            .all(|middleware| middleware.path.starts_with("/blog")));
+    }
+
+    #[tokio::test]
+    async fn test_mounted_sub_apps_have_isolated_middleware() {
+        use crate::app::test_client::TestClient;
+
+        let mut app = App::new();
+        app.mount("/blog", tagged_sub_app("blog"));
+        app.mount("/shop", tagged_sub_app("shop"));
+        app.get("/blogroll", |req: HttpRequest, res| async move {
+            res.ok()
+                .text(req.get_data("tag").unwrap_or_else(|| "none".to_string()))
+        });
+        let client = TestClient::new(app);
+
+        let res = client.get("/blog/items/1").send().await;
+        assert_eq!(res.text(), "blog:1");
+        assert_eq!(res.header("x-sub-app"), Some("blog"));
+
+        let res = client.get("/shop").send().await;
+        assert_eq!(res.text(), "shop");
+        assert_eq!(res.header("x-sub-app"), Some("shop"));
+
+        let res = client.get("/blogroll").send().await;
+        assert_eq!(res.text(), "none");
+        assert_eq!(res.header("x-sub-app"), None);
+    }
+
+    #[test]
+    fn test_route_list_includes_routers_mounts_and_static_files() {
+        let mut api = Router::new("/api");
+        api.post("/users", _test_handler);
+        api.get("/users/:id", _test_handler);
+
+        let mut blog = App::new();
+        blog.get("/posts", _test_handler);
+
+        let mut app = App::new();
+        app.get("/", _test_handler);
+        app.delete("/api/users/:id", _test_handler);
+        app.router(api);
+        app.mount("/blog", blog);
+        app.static_files("/assets", "public").unwrap();
+
+        let list = app.route_list();
+        let list: Vec<(HttpMethods, &str)> = list
+            .iter()
+            .map(|(method, path)| (method.clone(), path.as_str()))
+            .collect();
+        assert_eq!(
+            list,
+            vec![
+                (HttpMethods::GET, "/"),
+                (HttpMethods::POST, "/api/users"),
+                (HttpMethods::GET, "/api/users/:id"),
+                (HttpMethods::DELETE, "/api/users/:id"),
+                (HttpMethods::GET, "/assets/*"),
+                (HttpMethods::GET, "/blog/posts"),
+            ]
+        );
+    }
+
+    #[tokio::test]
+    async fn test_around_middlewares_wrap_the_handler_in_order() {
+        use crate::app::test_client::TestClient;
+
+        let events = Arc::new(Mutex::new(Vec::new()));
+        let mut app = App::new();
+        for name in ["outer", "inner"] {
+            let events = Arc::clone(&events);
+            app.use_around(None, move |req, next| {
+                let events = Arc::clone(&events);
+                async move {
+                    events.lock().unwrap().push(format!("{} before {}", name, req.path));
+                    let res = next(req).await;
+                    events
+                        .lock()
+                        .unwrap()
+                        .push(format!("{} after {}", name, res.status_code.as_u16()));
+                    res.set_header(format!("x-{}", name), "seen")
+                }
+            });
+        }
+        app.use_pre_middleware(None, {
+            let events = Arc::clone(&events);
+            move |req: HttpRequest, res, next: Next| {
+                events.lock().unwrap().push("pre".to_string());
+                async move { next.call(req, res).await }
+            }
+        });
+        app.get("/items", {
+            let events = Arc::clone(&events);
+            move |_req: HttpRequest, res: HttpResponse| {
+                events.lock().unwrap().push("handler".to_string());
+                async move { res.status(201).text("created") }
+            }
+        });
+        let client = TestClient::new(app);
+
+        let res = client.get("/items").send().await;
+        assert_eq!(res.status(), 201);
+        assert_eq!(res.header("x-outer"), Some("seen"));
+        assert_eq!(res.header("x-inner"), Some("seen"));
+        assert_eq!(
+            *events.lock().unwrap(),
+            vec![
+                "pre",
+                "outer before /items",
+                "inner before /items",
+                "handler",
+                "inner after 201",
+                "outer after 201",
+            ]
+        );
+    }
+
+    #[tokio::test]
+    async fn test_around_middleware_can_skip_the_handler_and_is_path_scoped() {
+        use crate::app::test_client::TestClient;
+
+        let mut app = App::new();
+        app.use_around(Some("/admin"), |req, next| async move {
+            if req.headers.get("authorization").is_none() {
+                return HttpResponse::new().unauthorized().text("denied");
+            }
+            next(req).await
+        });
+        app.get("/admin/panel", |_req: HttpRequest, res| async move {
+            res.ok().text("panel")
+        });
+        app.get("/public", |_req: HttpRequest, res| async move {
+            res.ok().text("public")
+        });
+        let client = TestClient::new(app);
+
+        let res = client.get("/admin/panel").send().await;
+        assert_eq!(res.status(), 401);
+        assert_eq!(res.text(), "denied");
+
+        let res = client
+            .get("/admin/panel")
+            .header("authorization", "Bearer token")
+            .send()
+            .await;
+        assert_eq!(res.text(), "panel");
+
+        let res = client.get("/public").send().await;
+        assert_eq!(res.text(), "public");
+    }
 }
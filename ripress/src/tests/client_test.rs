@@ -0,0 +1,155 @@
+#[cfg(test)]
+mod test {
+    use std::sync::Arc;
+
+    use serde_json::{json, Value};
+    use tokio::net::TcpListener;
+
+    use crate::{
+        app::App,
+        client::{Client, ClientError},
+        req::HttpRequest,
+        res::HttpResponse,
+        types::RouterFns,
+    };
+
+    /// Serves `app` on an ephemeral port until the test ends.
+    async fn serve(app: App) -> u16 {
+        let router_service = app.build_router_service();
+        let config = app.connection_config();
+
+        let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let port = listener.local_addr().unwrap().port();
+        tokio::spawn(async move {
+            while let Ok((stream, _)) = listener.accept().await {
+                let connection =
+                    App::handle_connection(stream, Arc::clone(&router_service), config.clone());
+                tokio::spawn(connection);
+            }
+        });
+        port
+    }
+
+    fn upstream() -> App {
+        let mut app = App::new();
+        app.get("/users", |req: HttpRequest, res: HttpResponse| async move {
+            res.ok().set_header("x-upstream", "yes").json(json!({
+                "page": req.query.get("page"),
+                "token": req.headers.get("authorization"),
+            }))
+        });
+        app.post("/users", |req: HttpRequest, res: HttpResponse| async move {
+            match req.json::<Value>() {
+                Ok(user) => res.status(201).json(user),
+                Err(e) => res.bad_request().text(e),
+            }
+        });
+        app
+    }
+
+    #[tokio::test]
+    async fn test_client_fetches_json_from_upstream() {
+        let port = serve(upstream()).await;
+
+        let res = Client::get(&format!("http://127.0.0.1:{}/users", port))
+            .query("page", "2")
+            .header("authorization", "Bearer abc")
+            .send()
+            .await
+            .unwrap();
+        assert_eq!(res.status(), 200);
+        assert_eq!(res.header("x-upstream"), Some("yes"));
+        assert_eq!(
+            res.json::<Value>().unwrap(),
+            json!({ "page": "2", "token": "Bearer abc" })
+        );
+
+        let res = Client::post(&format!("http://127.0.0.1:{}/users", port))
+            .json(&json!({ "name": "Ada" }))
+            .unwrap()
+            .send()
+            .await
+            .unwrap();
+        assert_eq!(res.status(), 201);
+        assert_eq!(res.text(), r#"{"name":"Ada"}"#);
+    }
+
+    #[tokio::test]
+    async fn test_client_response_converts_into_http_response() {
+        let upstream_port = serve(upstream()).await;
+
+        let mut app = App::new();
+        app.get(
+            "/proxy",
+            move |_req: HttpRequest, res: HttpResponse| async move {
+                match Client::get(&format!("http://127.0.0.1:{}/users?page=3", upstream_port))
+                    .send()
+                    .await
+                {
+                    Ok(upstream) => HttpResponse::from(upstream),
+                    Err(e) => res.status(502).text(e.to_string()),
+                }
+            },
+        );
+        let port = serve(app).await;
+
+        let res = reqwest::get(format!("http://127.0.0.1:{}/proxy", port))
+            .await
+            .unwrap();
+        assert_eq!(res.status(), 200);
+        assert_eq!(res.headers()["x-upstream"], "yes");
+        let body: Value = res.json().await.unwrap();
+        assert_eq!(body, json!({ "page": "3", "token": null }));
+    }
+
+    #[tokio::test]
+    async fn test_client_reports_errors() {
+        let closed = {
+            let listener = std::net::TcpListener::bind("127.0.0.1:0").unwrap();
+            listener.local_addr().unwrap().port()
+        };
+        let err = Client::get(&format!("http://127.0.0.1:{}/", closed))
+            .send()
+            .await
+            .unwrap_err();
+        assert!(matches!(err, ClientError::Request(_)));
+
+        let err = Client::get("ftp://example.com/").send().await.unwrap_err();
+        assert!(matches!(err, ClientError::InvalidUrl(_)));
+
+        let err = Client::get("http://example.com/")
+            .header("bad header", "value")
+            .send()
+            .await
+            .unwrap_err();
+        assert_eq!(err, ClientError::InvalidHeader(String::from("bad header")));
+    }
+    #[tokio::test]
+    async fn test_client_enforces_response_limit_and_timeout() {
+        let mut app = upstream();
+        app.get("/slow", |_req: HttpRequest, res: HttpResponse| async move {
+            tokio::time::sleep(std::time::Duration::from_secs(5)).await;
+            res.ok().text("late")
+        });
+        let port = serve(app).await;
+        let users = format!("http://127.0.0.1:{}/users", port);
+
+        let err = Client::get(&users)
+            .response_limit(8)
+            .send()
+            .await
+            .unwrap_err();
+        assert_eq!(err, ClientError::ResponseTooLarge(8));
+
+        let res = Client::get(&users).response_limit(1024).send().await;
+        assert_eq!(res.unwrap().status(), 200);
+
+        let timeout = std::time::Duration::from_millis(100);
+        let err = Client::get(&format!("http://127.0.0.1:{}/slow", port))
+            .timeout(timeout)
+            .send()
+            .await
+            .unwrap_err();
+        assert_eq!(err, ClientError::Timeout(timeout));
+    }
+}
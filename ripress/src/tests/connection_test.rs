@@ -0,0 +1,303 @@
+#[cfg(test)]
+mod test {
+    use std::{io, sync::Arc, time::Duration};
+
+    use tokio::{
+        io::{duplex, AsyncReadExt, AsyncWriteExt, DuplexStream},
+        net::{TcpListener, TcpStream},
+    };
+
+    use crate::{
+        app::{
+            connection::{ConnectionActivity, ConnectionTimeouts, TimeoutStream},
+            settings::Http2Config,
+            App,
+        },
+        req::HttpRequest,
+        res::HttpResponse,
+        types::RouterFns,
+    };
+
+    fn wrap(
+        read: Option<Duration>,
+        idle: Option<Duration>,
+    ) -> (
+        DuplexStream,
+        TimeoutStream<DuplexStream>,
+        Arc<ConnectionActivity>,
+    ) {
+        let (client, server) = duplex(1024);
+        let activity = Arc::new(ConnectionActivity::default());
+        let timeouts = ConnectionTimeouts {
+            read,
+            write: None,
+            idle,
+        };
+        let stream = TimeoutStream::new(server, timeouts, Arc::clone(&activity));
+        (client, stream, activity)
+    }
+
+    async fn read_until_error(stream: &mut TimeoutStream<DuplexStream>) -> io::Error {
+        let mut buf = [0u8; 64];
+        loop {
+            if let Err(e) = stream.read(&mut buf).await {
+                return e;
+            }
+        }
+    }
+
+    #[tokio::test]
+    async fn test_trickled_headers_hit_read_timeout() {
+        let (mut client, mut stream, _) = wrap(
+            Some(Duration::from_millis(100)),
+            Some(Duration::from_secs(10)),
+        );
+
+        tokio::spawn(async move {
+            for byte in b"GET / HTTP/1.1\r\nHost: localhost\r\n".iter() {
+                if client.write_all(&[*byte]).await.is_err() {
+                    break;
+                }
+                tokio::time::sleep(Duration::from_millis(20)).await;
+            }
+        });
+
+        let err = tokio::time::timeout(Duration::from_secs(2), read_until_error(&mut stream))
+            .await
+            .expect("slow client was not dropped");
+        assert_eq!(err.kind(), io::ErrorKind::TimedOut);
+    }
+
+    #[tokio::test]
+    async fn test_idle_keep_alive_outlives_read_timeout() {
+        let (_client, mut stream, _) = wrap(
+            Some(Duration::from_millis(50)),
+            Some(Duration::from_millis(300)),
+        );
+
+        let mut buf = [0u8; 8];
+        let still_open =
+            tokio::time::timeout(Duration::from_millis(150), stream.read(&mut buf)).await;
+        assert!(
+            still_open.is_err(),
+            "idle connection closed by read timeout"
+        );
+
+        let err = stream.read(&mut buf).await.unwrap_err();
+        assert_eq!(err.kind(), io::ErrorKind::TimedOut);
+    }
+
+    #[tokio::test]
+    async fn test_in_flight_request_is_not_timed_out() {
+        let (_client, mut stream, activity) = wrap(None, Some(Duration::from_millis(50)));
+        let _handling = activity.track(std::future::pending::<Result<hyper::Response<()>, ()>>());
+
+        let mut buf = [0u8; 8];
+        let still_open =
+            tokio::time::timeout(Duration::from_millis(150), stream.read(&mut buf)).await;
+        assert!(
+            still_open.is_err(),
+            "connection closed while handling a request"
+        );
+    }
+
+    #[tokio::test]
+    async fn test_blocked_write_hits_write_timeout() {
+        let (_client, server) = duplex(16);
+        let timeouts = ConnectionTimeouts {
+            write: Some(Duration::from_millis(100)),
+            ..ConnectionTimeouts::default()
+        };
+        let mut stream =
+            TimeoutStream::new(server, timeouts, Arc::new(ConnectionActivity::default()));
+
+        // The client never reads, so the buffer fills up and the write blocks
+        let err = tokio::time::timeout(Duration::from_secs(2), stream.write_all(&[0; 1024]))
+            .await
+            .expect("blocked write was not timed out")
+            .unwrap_err();
+        assert_eq!(err.kind(), io::ErrorKind::TimedOut);
+    }
+
+    /// Serves one connection of `app` on an ephemeral port and connects to it.
+    async fn connect(mut app: App) -> TcpStream {
+        app.get("/", |_req: HttpRequest, res: HttpResponse| async move {
+            res.ok().text("ok")
+        });
+        let router_service = app.build_router_service();
+        let config = app.connection_config();
+
+        let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let port = listener.local_addr().unwrap().port();
+        tokio::spawn(async move {
+            let (stream, _) = listener.accept().await.unwrap();
+            App::handle_connection(stream, router_service, config).await;
+        });
+        TcpStream::connect(("127.0.0.1", port)).await.unwrap()
+    }
+
+    /// Reads until the server closes the connection.
+    async fn read_until_closed(stream: &mut TcpStream) -> String {
+        let mut response = String::new();
+        tokio::time::timeout(Duration::from_secs(2), stream.read_to_string(&mut response))
+            .await
+            .expect("connection was not closed")
+            .unwrap();
+        response
+    }
+
+    #[tokio::test]
+    async fn test_keep_alive_off_closes_after_each_response() {
+        let mut app = App::new();
+        app.keep_alive(None);
+        let mut stream = connect(app).await;
+
+        stream
+            .write_all(b"GET / HTTP/1.1\r\nHost: localhost\r\n\r\n")
+            .await
+            .unwrap();
+        let response = read_until_closed(&mut stream).await;
+
+        assert!(response.starts_with("HTTP/1.1 200"), "{}", response);
+        assert!(response.contains("connection: close"), "{}", response);
+    }
+
+    #[tokio::test]
+    async fn test_keep_alive_idle_closes_idle_connections() {
+        let mut app = App::new();
+        app.keep_alive(Some(Duration::from_millis(200)));
+        let mut stream = connect(app).await;
+
+        stream
+            .write_all(b"GET / HTTP/1.1\r\nHost: localhost\r\n\r\n")
+            .await
+            .unwrap();
+        let mut buf = [0u8; 1024];
+        let n = stream.read(&mut buf).await.unwrap();
+        let response = String::from_utf8_lossy(&buf[..n]);
+        assert!(response.starts_with("HTTP/1.1 200"), "{}", response);
+        assert!(!response.contains("connection: close"), "{}", response);
+
+        // Kept open for the next request, then closed once idle
+        tokio::time::sleep(Duration::from_millis(50)).await;
+        stream
+            .write_all(b"GET / HTTP/1.1\r\nHost: localhost\r\n\r\n")
+            .await
+            .unwrap();
+        let response = read_until_closed(&mut stream).await;
+        assert!(response.starts_with("HTTP/1.1 200"), "{}", response);
+    }
+
+    #[tokio::test]
+    async fn test_max_requests_per_connection() {
+        let mut app = App::new();
+        app.max_requests_per_connection(Some(2));
+        let mut stream = connect(app).await;
+
+        let request = b"GET / HTTP/1.1\r\nHost: localhost\r\n\r\n";
+        stream.write_all(&request.repeat(3)).await.unwrap();
+        let response = read_until_closed(&mut stream).await;
+
+        assert_eq!(response.matches("HTTP/1.1 200").count(), 2, "{}", response);
+        assert_eq!(response.matches("connection: close").count(), 1);
+    }
+
+    #[tokio::test]
+    async fn test_http2_pings_idle_connections() {
+        let mut app = App::new();
+        app.http2_config(Http2Config {
+            http2_only: true,
+            keep_alive_interval: Some(Duration::from_millis(100)),
+            keep_alive_while_idle: Some(true),
+            is_enabled: true,
+            ..Default::default()
+        });
+        let mut stream = connect(app).await;
+
+        // Connection preface, an empty SETTINGS frame and an ACK of the server's
+        stream
+            .write_all(b"PRI * HTTP/2.0\r\n\r\nSM\r\n\r\n")
+            .await
+            .unwrap();
+        stream
+            .write_all(&[0, 0, 0, 4, 0, 0, 0, 0, 0])
+            .await
+            .unwrap();
+        stream
+            .write_all(&[0, 0, 0, 4, 1, 0, 0, 0, 0])
+            .await
+            .unwrap();
+
+        let ping = tokio::time::timeout(Duration::from_secs(2), async {
+            loop {
+                let mut header = [0u8; 9];
+                stream.read_exact(&mut header).await.unwrap();
+                let len = u32::from_be_bytes([0, header[0], header[1], header[2]]) as usize;
+                let mut payload = vec![0u8; len];
+                stream.read_exact(&mut payload).await.unwrap();
+                // A PING without the ACK flag
+                if header[3] == 6 && header[4] & 1 == 0 {
+                    return payload;
+                }
+            }
+        })
+        .await
+        .expect("idle connection was not pinged");
+        assert_eq!(ping.len(), 8);
+    }
+
+    #[tokio::test]
+    async fn test_max_connections_per_ip() {
+        let port = std::net::TcpListener::bind("127.0.0.1:0")
+            .unwrap()
+            .local_addr()
+            .unwrap()
+            .port();
+        let mut app = App::new();
+        app.host("127.0.0.1");
+        app.max_connections_per_ip(2);
+        app.get("/", |_req: HttpRequest, res: HttpResponse| async move {
+            res.ok().text("ok")
+        });
+        let server = tokio::spawn(async move { app.listen(port, || {}).await });
+
+        let mut first = None;
+        for _ in 0..50 {
+            if let Ok(stream) = TcpStream::connect(("127.0.0.1", port)).await {
+                first = Some(stream);
+                break;
+            }
+            tokio::time::sleep(Duration::from_millis(20)).await;
+        }
+        let first = first.expect("server should be listening");
+        let mut second = TcpStream::connect(("127.0.0.1", port)).await.unwrap();
+        let mut refused = TcpStream::connect(("127.0.0.1", port)).await.unwrap();
+
+        // The third connection is closed without being served
+        let mut buf = [0u8; 64];
+        let n = tokio::time::timeout(Duration::from_secs(2), refused.read(&mut buf))
+            .await
+            .expect("connection over the limit was not closed")
+            .unwrap_or(0);
+        assert_eq!(n, 0);
+
+        second
+            .write_all(b"GET / HTTP/1.1\r\nHost: localhost\r\nConnection: close\r\n\r\n")
+            .await
+            .unwrap();
+        let response = read_until_closed(&mut second).await;
+        assert!(response.starts_with("HTTP/1.1 200"), "{}", response);
+
+        // Closing a connection frees its slot
+        drop(first);
+        tokio::time::sleep(Duration::from_millis(100)).await;
+        let mut next = TcpStream::connect(("127.0.0.1", port)).await.unwrap();
+        next.write_all(b"GET / HTTP/1.1\r\nHost: localhost\r\nConnection: close\r\n\r\n")
+            .await
+            .unwrap();
+        let response = read_until_closed(&mut next).await;
+        assert!(response.starts_with("HTTP/1.1 200"), "{}", response);
+
+        server.abort();
+    }
+}
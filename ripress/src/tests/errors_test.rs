@@ -5,6 +5,7 @@ mod tests {
         req::{
             body::text_data::TextDataError, query_params::QueryParamError, route_params::ParamError,
         },
+        res::HttpResponse,
     };
 
     #[test]
@@ -126,4 +127,51 @@ mod tests {
         assert_eq!(err.kind, RipressErrorKind::InvalidInput);
         assert_eq!(err.message, "Text too large: 1234 bytes (limit: 999 bytes)");
     }
+
+    #[test]
+    fn test_error_kind_status_codes() {
+        assert_eq!(RipressErrorKind::IO.status_code(), 500);
+        assert_eq!(RipressErrorKind::ParseError.status_code(), 400);
+        assert_eq!(RipressErrorKind::InvalidInput.status_code(), 400);
+        assert_eq!(RipressErrorKind::NotFound.status_code(), 404);
+        assert_eq!(RipressErrorKind::Unauthorized.status_code(), 401);
+        assert_eq!(RipressErrorKind::Forbidden.status_code(), 403);
+        assert_eq!(RipressErrorKind::Conflict.status_code(), 409);
+        assert_eq!(RipressErrorKind::PayloadTooLarge.status_code(), 413);
+        assert_eq!(RipressErrorKind::UnsupportedMediaType.status_code(), 415);
+        assert_eq!(RipressErrorKind::TooManyRequests.status_code(), 429);
+        assert_eq!(RipressErrorKind::ServiceUnavailable.status_code(), 503);
+    }
+
+    #[test]
+    fn test_http_error_kinds_into_response() {
+        let err = RipressError::new(RipressErrorKind::Conflict, "email taken".to_string());
+        assert_eq!(
+            err.to_string(),
+            "RipressError: { message: email taken, kind: Conflict }"
+        );
+
+        let res = err.into_response();
+        assert_eq!(res.status_code(), 409);
+        assert_eq!(res.body_text().unwrap(), "email taken");
+    }
+
+    #[test]
+    fn test_into_response_keeps_message() {
+        let err = RipressError::new(RipressErrorKind::NotFound, "no user".to_string());
+        let res = err.into_response();
+        assert_eq!(res.status_code(), 404);
+        assert_eq!(res.body_text().unwrap(), "no user");
+
+        let err: RipressError = QueryParamError::ParseError {
+            param: "page".to_string(),
+            value: "abc".to_string(),
+            target_type: "u32".to_string(),
+        }
+        .into();
+        let message = err.message().to_string();
+        let res = HttpResponse::from(err);
+        assert_eq!(res.status_code(), 400);
+        assert_eq!(res.body_text().unwrap(), message);
+    }
 }
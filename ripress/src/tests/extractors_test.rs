@@ -42,7 +42,7 @@ mod extractor_tests {
 
     fn create_request_with_json(json_value: serde_json::Value) -> HttpRequest {
         let mut req = HttpRequest::default();
-        req.body = RequestBody::new_json(json_value);
+        req.body = RequestBody::new_json(json_value).into();
         req
     }
 
@@ -312,3 +312,175 @@ mod extractor_tests {
         assert!(headers.get("authorization").is_some());
     }
 }
+
+#[cfg(test)]
+mod derived_extractor_tests {
+    use crate::app::{test_client::TestClient, App};
+    use crate::macros::{FromJson, FromParams, FromQueryParam, FromRequest};
+    use crate::req::body::json_data::JsonBody;
+    use crate::types::RouterFns;
+    use serde::Deserialize;
+    use serde_json::json;
+
+    #[derive(Debug, Deserialize, FromJson)]
+    struct Comment {
+        text: String,
+    }
+
+    #[derive(Debug, FromParams)]
+    struct PostPath {
+        post_id: u32,
+    }
+
+    #[derive(Debug, FromQueryParam)]
+    struct Notify {
+        notify: bool,
+    }
+
+    #[derive(FromRequest)]
+    struct NewComment {
+        #[from(path)]
+        path: PostPath,
+        #[from(query)]
+        query: Notify,
+        #[from(body)]
+        comment: JsonBody<Comment>,
+    }
+
+    fn client() -> TestClient {
+        let mut app = App::new();
+        app.post(
+            "/posts/:post_id/comments",
+            |input: NewComment, res| async move {
+                res.ok().json(json!({
+                    "post_id": input.path.post_id,
+                    "notify": input.query.notify,
+                    "text": input.comment.text,
+                }))
+            },
+        );
+        TestClient::new(app)
+    }
+
+    #[tokio::test]
+    async fn test_derived_extractor_gathers_path_query_and_body() {
+        let res = client()
+            .post("/posts/7/comments")
+            .query("notify", "true")
+            .json(&json!({ "text": "hi" }))
+            .send()
+            .await;
+        assert_eq!(res.status(), 200);
+        assert_eq!(
+            res.json::<serde_json::Value>().unwrap(),
+            json!({ "post_id": 7, "notify": true, "text": "hi" })
+        );
+    }
+
+    #[tokio::test]
+    async fn test_derived_extractor_rejects_bad_fields() {
+        let res = client()
+            .post("/posts/abc/comments")
+            .query("notify", "true")
+            .json(&json!({ "text": "hi" }))
+            .send()
+            .await;
+        assert_eq!(res.status(), 400);
+        assert!(res.text().contains("Failed to parse field 'post_id'"));
+
+        let res = client()
+            .post("/posts/7/comments")
+            .json(&json!({ "text": "hi" }))
+            .send()
+            .await;
+        assert_eq!(res.status(), 400);
+        assert!(res.text().contains("Missing query param field: notify"));
+
+        let res = client()
+            .post("/posts/7/comments")
+            .query("notify", "true")
+            .send()
+            .await;
+        assert_eq!(res.status(), 400);
+        assert!(res.text().contains("Request body is not JSON"));
+    }
+
+    #[tokio::test]
+    async fn test_derived_extractor_field_named_req_does_not_shadow_request() {
+        #[derive(FromRequest)]
+        struct Shadowing {
+            #[from(query)]
+            req: Notify,
+            #[from(path)]
+            path: PostPath,
+        }
+
+        let mut app = App::new();
+        app.get("/posts/:post_id", |input: Shadowing, res| async move {
+            res.ok()
+                .text(format!("{} {}", input.path.post_id, input.req.notify))
+        });
+
+        let res = TestClient::new(app)
+            .get("/posts/7")
+            .query("notify", "true")
+            .send()
+            .await;
+        assert_eq!(res.text(), "7 true");
+    }
+
+    #[tokio::test]
+    async fn test_terse_json_error_detail_hides_serde_message() {
+        use crate::{app::settings::JsonErrorDetail, req::HttpRequest};
+
+        #[derive(serde::Deserialize, serde::Serialize)]
+        struct Reply {
+            text: String,
+        }
+
+        let build = |detail| {
+            let mut app = App::new();
+            app.json_error_detail(detail);
+            app.post("/comments", |body: JsonBody<Comment>, res| async move {
+                res.ok().text(body.into_inner().text)
+            });
+            app.post("/raw", |req: HttpRequest, res| async move {
+                match req.json::<Reply>() {
+                    Ok(reply) => res.ok().text(reply.text),
+                    Err(error) => res.bad_request().text(error),
+                }
+            });
+            TestClient::new(app)
+        };
+
+        let full = build(JsonErrorDetail::Full);
+        let res = full
+            .post("/comments")
+            .json(&json!({ "body": 1 }))
+            .send()
+            .await;
+        assert_eq!(res.status(), 400);
+        assert!(res.text().contains("missing field `text`"));
+
+        let terse = build(JsonErrorDetail::Terse);
+        let res = terse
+            .post("/comments")
+            .json(&json!({ "body": 1 }))
+            .send()
+            .await;
+        assert_eq!(res.status(), 400);
+        assert_eq!(res.text(), "Extraction failed: Invalid JSON");
+
+        let res = terse.post("/raw").json(&json!({ "body": 1 })).send().await;
+        assert_eq!(res.status(), 400);
+        assert_eq!(res.text(), "Invalid JSON");
+
+        let res = terse
+            .post("/comments")
+            .json(&json!({ "text": "hi" }))
+            .send()
+            .await;
+        assert_eq!(res.status(), 200);
+        assert_eq!(res.text(), "hi");
+    }
+}
@@ -0,0 +1,64 @@
+#[cfg(test)]
+mod test {
+    use std::sync::atomic::{AtomicBool, Ordering};
+    use std::sync::Arc;
+
+    use serde_json::{json, Value};
+
+    use crate::app::{health::HealthStatus, test_client::TestClient, App};
+
+    #[tokio::test]
+    async fn test_health_reports_ok() {
+        let mut app = App::new();
+        app.health("/healthz");
+        let client = TestClient::new(app);
+
+        let res = client.get("/healthz").send().await;
+
+        assert_eq!(res.status(), 200);
+        assert_eq!(res.json::<Value>().unwrap(), json!({ "status": "ok" }));
+    }
+
+    #[tokio::test]
+    async fn test_readiness_fails_while_any_check_is_unhealthy() {
+        let db_up = Arc::new(AtomicBool::new(false));
+        let mut app = App::new();
+
+        app.readiness("/readyz", || async { HealthStatus::Healthy });
+        let db = Arc::clone(&db_up);
+        app.readiness("/readyz", move || {
+            let up = db.load(Ordering::SeqCst);
+            async move {
+                if up {
+                    Ok(())
+                } else {
+                    Err("database unreachable")
+                }
+                .into()
+            }
+        });
+        let client = TestClient::new(app);
+
+        let res = client.get("/readyz").send().await;
+        assert_eq!(res.status(), 503);
+        assert_eq!(
+            res.json::<Value>().unwrap(),
+            json!({ "status": "unavailable", "errors": ["database unreachable"] })
+        );
+
+        db_up.store(true, Ordering::SeqCst);
+        let res = client.get("/readyz").send().await;
+        assert_eq!(res.status(), 200);
+        assert_eq!(res.json::<Value>().unwrap(), json!({ "status": "ok" }));
+    }
+
+    #[test]
+    fn test_health_status_conversions() {
+        assert!(HealthStatus::from(true).is_healthy());
+        assert!(!HealthStatus::from(false).is_healthy());
+        assert_eq!(
+            HealthStatus::from(Err::<(), _>("timeout")),
+            HealthStatus::Unhealthy("timeout".to_string())
+        );
+    }
+}
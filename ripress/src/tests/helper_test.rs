@@ -1,10 +1,14 @@
 #[cfg(test)]
 mod tests {
     use crate::{
+        app::settings::MultipartLimits,
         helpers::{
-            extract_boundary, find_subsequence, get_all_query, parse_multipart_form, path_matches,
+            decode_rfc5987, extract_boundary, find_subsequence, get_all_query,
+            media_type_specificity, negotiate, parse_multipart_form, parse_part_headers,
+            parse_quality_list, path_matches, token_specificity, MultipartLimitError,
         },
         req::query_params::QueryParams,
+        res::HttpResponse,
     };
 
     #[test]
@@ -45,7 +49,7 @@ mod tests {
     fn test_trailing_slash_in_prefix() {
         assert!(path_matches("/api/", "/api/"));
         assert!(path_matches("/api/", "/api/foo"));
-        assert!(!path_matches("/api/", "/api")); 
+        assert!(!path_matches("/api/", "/api"));
     }
 
     #[test]
@@ -74,6 +78,13 @@ mod tests {
         assert!(result == expected1 || result == expected2);
     }
 
+    #[test]
+    fn test_get_all_query_keeps_repeated_keys() {
+        let queries = QueryParams::from_query_string("tag=a&tag=b");
+        let result = get_all_query(&queries);
+        assert_eq!(result, "tag=a&tag=b");
+    }
+
     #[test]
     fn test_get_all_query_url_encoding() {
         let mut queries = QueryParams::new();
@@ -229,7 +240,12 @@ mod tests {
         let body = format!(
             "--AaB03x\r\nContent-Disposition: form-data; name=\"submit-name\"\r\n\r\nLarry\r\n--AaB03x--"
         );
-        let (fields, files) = parse_multipart_form(body.as_bytes(), &boundary.to_string());
+        let (fields, files) = parse_multipart_form(
+            body.as_bytes(),
+            &boundary.to_string(),
+            &MultipartLimits::default(),
+        )
+        .unwrap();
         assert_eq!(fields, vec![("submit-name", "Larry")]);
         assert_eq!(files.len(), 0);
     }
@@ -240,7 +256,12 @@ mod tests {
         let body = format!(
             "--xyz\r\nContent-Disposition: form-data; name=\"f1\"\r\n\r\nv1\r\n--xyz\r\nContent-Disposition: form-data; name=\"f2\"\r\n\r\nv2\r\n--xyz--"
         );
-        let (fields, files) = parse_multipart_form(body.as_bytes(), &boundary.to_string());
+        let (fields, files) = parse_multipart_form(
+            body.as_bytes(),
+            &boundary.to_string(),
+            &MultipartLimits::default(),
+        )
+        .unwrap();
         assert_eq!(fields, vec![("f1", "v1"), ("f2", "v2")]);
         assert_eq!(files.len(), 0);
     }
@@ -253,11 +274,13 @@ mod tests {
             &[("desc", "mydesc", None), ("upload", "", Some(file_content))],
             boundary,
         );
-        let (fields, files) = parse_multipart_form(&body, &boundary.to_string());
+        let (fields, files) =
+            parse_multipart_form(&body, &boundary.to_string(), &MultipartLimits::default())
+                .unwrap();
         assert!(fields.contains(&("desc", "mydesc")));
         assert_eq!(files.len(), 1);
         assert_eq!(files[0].0, file_content);
-        assert_eq!(files[0].1, Some("upload"));
+        assert_eq!(files[0].1.name.as_deref(), Some("upload"));
     }
 
     #[test]
@@ -274,14 +297,16 @@ mod tests {
             ],
             boundary,
         );
-        let (fields, files) = parse_multipart_form(&body, &boundary.to_string());
+        let (fields, files) =
+            parse_multipart_form(&body, &boundary.to_string(), &MultipartLimits::default())
+                .unwrap();
         assert!(fields.contains(&("n1", "v1")));
         assert!(fields.contains(&("n2", "v2")));
         assert_eq!(files.len(), 2);
         assert_eq!(files[0].0, content1);
         assert_eq!(files[1].0, content2);
-        assert_eq!(files[0].1, Some("file1"));
-        assert_eq!(files[1].1, Some("file2"));
+        assert_eq!(files[0].1.name.as_deref(), Some("file1"));
+        assert_eq!(files[1].1.name.as_deref(), Some("file2"));
     }
 
     #[test]
@@ -290,10 +315,15 @@ mod tests {
         let body = format!(
             "--multistar\r\nContent-Disposition: form-data; name=\"file\"; filename*=\"myfile.txt\"\r\n\r\nabc\r\n--multistar--"
         );
-        let (fields, files) = parse_multipart_form(body.as_bytes(), &boundary.to_string());
+        let (fields, files) = parse_multipart_form(
+            body.as_bytes(),
+            &boundary.to_string(),
+            &MultipartLimits::default(),
+        )
+        .unwrap();
         assert_eq!(fields.len(), 0);
         assert_eq!(files.len(), 1);
-        assert_eq!(files[0].1, Some("file"));
+        assert_eq!(files[0].1.name.as_deref(), Some("file"));
         assert_eq!(files[0].0, b"abc");
     }
 
@@ -305,7 +335,12 @@ mod tests {
             "--wxc\r\nContent-Disposition: form-data; name=\"nm\"\r\n\r\n{}--wxc--",
             value
         );
-        let (fields, files) = parse_multipart_form(body.as_bytes(), &boundary.to_string());
+        let (fields, files) = parse_multipart_form(
+            body.as_bytes(),
+            &boundary.to_string(),
+            &MultipartLimits::default(),
+        )
+        .unwrap();
         assert_eq!(fields, vec![("nm", "a_line")]);
         assert_eq!(files.len(), 0);
     }
@@ -314,7 +349,8 @@ mod tests {
     fn returns_empty_for_missing_boundary() {
         let boundary = "abs";
         let body = b"--xxx\r\nContent-Disposition: form-data; name=\"nm\"\r\n\r\nvv\r\n--xxx--";
-        let (fields, files) = parse_multipart_form(body, &boundary.to_string());
+        let (fields, files) =
+            parse_multipart_form(body, &boundary.to_string(), &MultipartLimits::default()).unwrap();
         assert_eq!(fields.len(), 0);
         assert_eq!(files.len(), 0);
     }
@@ -324,7 +360,9 @@ mod tests {
         let boundary = "binary";
         let file_content = b"\xF0\x90\x80\x80\xFF";
         let body = make_body(&[("file", "", Some(file_content))], boundary);
-        let (_, files) = parse_multipart_form(&body, &boundary.to_string());
+        let (_, files) =
+            parse_multipart_form(&body, &boundary.to_string(), &MultipartLimits::default())
+                .unwrap();
         assert_eq!(files.len(), 1);
         assert_eq!(files[0].0, file_content);
     }
@@ -333,7 +371,8 @@ mod tests {
     fn handles_no_crlf_after_last_field() {
         let boundary = "plain";
         let body = b"--plain\r\nContent-Disposition: form-data; name=\"foo\"\r\n\r\nbar--plain--";
-        let (fields, files) = parse_multipart_form(body, &boundary.to_string());
+        let (fields, files) =
+            parse_multipart_form(body, &boundary.to_string(), &MultipartLimits::default()).unwrap();
         assert_eq!(fields, vec![("foo", "bar")]);
         assert_eq!(files.len(), 0);
     }
@@ -346,9 +385,259 @@ mod tests {
             "--def\r\nContent-Disposition: form-data; name=\"up\"; filename=\"f.txt\"\r\n\r\n{}--def--",
             std::str::from_utf8(file_content).unwrap()
         );
-        let (fields, files) = parse_multipart_form(body.as_bytes(), &boundary.to_string());
+        let (fields, files) = parse_multipart_form(
+            body.as_bytes(),
+            &boundary.to_string(),
+            &MultipartLimits::default(),
+        )
+        .unwrap();
         assert_eq!(fields.len(), 0);
         assert_eq!(files.len(), 1);
         assert_eq!(files[0].0, b"abc");
     }
+
+    #[cfg(feature = "file-upload")]
+    use crate::helpers::{MultipartEvent, MultipartParser};
+
+    #[cfg(feature = "file-upload")]
+    type StreamedPart = (Option<String>, Option<String>, Vec<u8>);
+
+    /// Feeds `body` to a [`MultipartParser`] in `chunk_size` pieces and
+    /// collects `(name, filename, content)` for every part.
+    #[cfg(feature = "file-upload")]
+    fn stream_parts(
+        body: &[u8],
+        boundary: &str,
+        chunk_size: usize,
+    ) -> Result<Vec<StreamedPart>, String> {
+        let mut parser = MultipartParser::new(boundary);
+        let mut events = Vec::new();
+        for chunk in body.chunks(chunk_size) {
+            events.extend(parser.feed(chunk)?);
+        }
+        events.extend(parser.finish()?);
+
+        let mut parts = Vec::new();
+        for event in events {
+            match event {
+                MultipartEvent::PartStart(headers) => {
+                    parts.push((headers.name, headers.filename, Vec::new()))
+                }
+                MultipartEvent::Data(data) => parts.last_mut().unwrap().2.extend_from_slice(&data),
+                MultipartEvent::PartEnd => {}
+            }
+        }
+        Ok(parts)
+    }
+
+    #[test]
+    #[cfg(feature = "file-upload")]
+    fn streaming_parser_matches_buffered_parser_for_any_chunk_size() {
+        let boundary = "chunked";
+        let content = b"\r\n--chunke\x00binary\r\n";
+        let body = make_body(
+            &[("desc", "hello", None), ("upload", "", Some(content))],
+            boundary,
+        );
+
+        for chunk_size in [1, 2, 3, 7, 64, body.len()] {
+            let parts = stream_parts(&body, boundary, chunk_size).unwrap();
+            assert_eq!(parts.len(), 2, "chunk size {}", chunk_size);
+            assert_eq!(parts[0].0.as_deref(), Some("desc"));
+            assert_eq!(parts[0].2, b"hello");
+            assert_eq!(parts[1].0.as_deref(), Some("upload"));
+            assert_eq!(parts[1].1.as_deref(), Some("file.bin"));
+            assert_eq!(parts[1].2, content);
+        }
+    }
+
+    #[test]
+    fn parses_extended_filenames() {
+        let headers = parse_part_headers(
+            "Content-Disposition: form-data; name=\"doc\"; filename*=UTF-8''%E2%82%AC%20rates.txt; filename=\"rates.txt\"",
+        );
+        assert_eq!(headers.filename.as_deref(), Some("\u{20ac} rates.txt"));
+
+        let headers = parse_part_headers(
+            "Content-Disposition: form-data; name=\"doc\"; filename=\"a;b \\\"c\\\".txt\"",
+        );
+        assert_eq!(headers.name.as_deref(), Some("doc"));
+        assert_eq!(headers.filename.as_deref(), Some("a;b \"c\".txt"));
+
+        assert_eq!(
+            decode_rfc5987("iso-8859-1'en'caf%E9.txt").as_deref(),
+            Some("caf\u{e9}.txt")
+        );
+        assert_eq!(decode_rfc5987("UTF-8''%FF"), None);
+        assert_eq!(decode_rfc5987("koi8-r''abc"), None);
+    }
+
+    #[test]
+    fn multipart_form_reports_file_headers() {
+        let body = b"--b\r\nContent-Disposition: form-data; name=\"pic\"; filename=\"me.png\"\r\nContent-Type: image/png\r\n\r\nPNG\r\n--b--";
+        let (_, files) =
+            parse_multipart_form(body, &"b".to_string(), &MultipartLimits::default()).unwrap();
+        assert_eq!(files.len(), 1);
+        assert_eq!(files[0].1.name.as_deref(), Some("pic"));
+        assert_eq!(files[0].1.filename.as_deref(), Some("me.png"));
+        assert_eq!(files[0].1.content_type.as_deref(), Some("image/png"));
+    }
+
+    fn text_parts(count: usize, name: &str, value: &str) -> Vec<u8> {
+        let mut body = Vec::new();
+        for _ in 0..count {
+            body.extend_from_slice(
+                format!(
+                    "--b\r\nContent-Disposition: form-data; name=\"{name}\"\r\n\r\n{value}\r\n"
+                )
+                .as_bytes(),
+            );
+        }
+        body.extend_from_slice(b"--b--\r\n");
+        body
+    }
+
+    #[test]
+    fn multipart_form_rejects_too_many_parts() {
+        let body = text_parts(100_000, "f", "v");
+        let err =
+            parse_multipart_form(&body, &"b".to_string(), &MultipartLimits::default()).unwrap_err();
+        assert_eq!(err, MultipartLimitError::TooManyFields(1000));
+
+        let limits = MultipartLimits {
+            max_fields: 3,
+            ..Default::default()
+        };
+        let body = text_parts(3, "f", "v");
+        let (fields, _) = parse_multipart_form(&body, &"b".to_string(), &limits).unwrap();
+        assert_eq!(fields.len(), 3);
+        assert!(parse_multipart_form(&text_parts(4, "f", "v"), &"b".to_string(), &limits).is_err());
+    }
+
+    #[test]
+    fn multipart_form_rejects_long_names_and_values() {
+        let limits = MultipartLimits {
+            max_field_name_len: 4,
+            max_field_value_len: 8,
+            ..Default::default()
+        };
+
+        let body = text_parts(1, "named", "v");
+        let err = parse_multipart_form(&body, &"b".to_string(), &limits).unwrap_err();
+        assert_eq!(err, MultipartLimitError::FieldNameTooLong(4));
+        assert_eq!(err.into_response(HttpResponse::new()).status_code(), 400);
+
+        let body = text_parts(1, "bio", "123456789");
+        let err = parse_multipart_form(&body, &"b".to_string(), &limits).unwrap_err();
+        assert_eq!(
+            err,
+            MultipartLimitError::FieldValueTooLong("bio".to_string(), 8)
+        );
+        assert_eq!(err.into_response(HttpResponse::new()).status_code(), 413);
+
+        let body = text_parts(1, "bio", "12345678");
+        assert!(parse_multipart_form(&body, &"b".to_string(), &limits).is_ok());
+    }
+
+    #[test]
+    #[cfg(feature = "file-upload")]
+    fn streaming_parser_tolerates_close_without_crlf() {
+        let body = b"--plain\r\nContent-Disposition: form-data; name=\"foo\"\r\n\r\nbar--plain--";
+        let parts = stream_parts(body, "plain", 4).unwrap();
+        assert_eq!(
+            parts,
+            vec![(Some("foo".to_string()), None, b"bar".to_vec())]
+        );
+    }
+
+    #[test]
+    #[cfg(feature = "file-upload")]
+    fn streaming_parser_rejects_truncated_body() {
+        let body = b"--t\r\nContent-Disposition: form-data; name=\"f\"; filename=\"a\"\r\n\r\nabc";
+        assert!(stream_parts(body, "t", 5).is_err());
+    }
+
+    #[test]
+    fn parses_part_headers() {
+        let headers = parse_part_headers(
+            "Content-Disposition: form-data; name=\"doc\"; filename=\"a.pdf\"\r\nContent-Type: application/pdf",
+        );
+        assert_eq!(headers.name.as_deref(), Some("doc"));
+        assert_eq!(headers.filename.as_deref(), Some("a.pdf"));
+        assert_eq!(headers.content_type.as_deref(), Some("application/pdf"));
+        assert!(headers.is_file());
+
+        let headers =
+            parse_part_headers("Content-Disposition: form-data; name=\"x\"; filename=\"\"");
+        assert!(!headers.is_file());
+    }
+
+    #[test]
+    fn test_parse_quality_list() {
+        let parsed = parse_quality_list("text/html, application/json;q=0.5, */*;q=abc");
+        assert_eq!(parsed, vec![("text/html", 1.0), ("application/json", 0.5)]);
+    }
+
+    #[test]
+    fn test_negotiate_media_types() {
+        let browser = "text/html,application/xhtml+xml,application/xml;q=0.9,*/*;q=0.8";
+        let offered = ["application/json", "text/html"];
+
+        assert_eq!(
+            negotiate(Some(browser), &offered, media_type_specificity),
+            Some("text/html")
+        );
+        assert_eq!(
+            negotiate(Some("application/json"), &offered, media_type_specificity),
+            Some("application/json")
+        );
+        assert_eq!(
+            negotiate(Some("image/png"), &offered, media_type_specificity),
+            None
+        );
+        assert_eq!(
+            negotiate(None, &offered, media_type_specificity),
+            Some("application/json")
+        );
+    }
+
+    #[test]
+    fn test_negotiate_specificity_tie_break() {
+        let offered = ["application/json", "text/plain"];
+
+        assert_eq!(
+            negotiate(Some("*/*, text/*"), &offered, media_type_specificity),
+            Some("text/plain")
+        );
+        assert_eq!(
+            negotiate(Some("text/*;q=0, */*"), &offered, media_type_specificity),
+            Some("application/json")
+        );
+        assert_eq!(
+            negotiate(
+                Some("text/plain;q=0, */*"),
+                &["text/plain"],
+                media_type_specificity
+            ),
+            None
+        );
+    }
+
+    #[test]
+    fn test_negotiate_tokens() {
+        let offered = ["gzip", "br"];
+
+        assert_eq!(
+            negotiate(Some("gzip;q=0.5, br"), &offered, token_specificity),
+            Some("br")
+        );
+        assert_eq!(
+            negotiate(Some("*;q=0.1, GZIP"), &offered, token_specificity),
+            Some("gzip")
+        );
+        assert_eq!(
+            negotiate(Some("identity"), &offered, token_specificity),
+            None
+        );
+    }
 }
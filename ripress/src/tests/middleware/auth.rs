@@ -0,0 +1,111 @@
+#[cfg(test)]
+mod test {
+    use std::collections::HashMap;
+
+    use base64::{engine::general_purpose::STANDARD, Engine};
+
+    use crate::app::{test_client::TestClient, App};
+    use crate::req::HttpRequest;
+    use crate::types::RouterFns;
+
+    fn basic(credentials: &[u8]) -> String {
+        format!("Basic {}", STANDARD.encode(credentials))
+    }
+
+    fn make_client(configure: impl FnOnce(&mut App)) -> TestClient {
+        let mut app = App::new();
+        configure(&mut app);
+        app.get("/admin/stats", |_req: HttpRequest, res| async move {
+            res.ok().text("stats")
+        });
+        app.get("/public", |_req: HttpRequest, res| async move {
+            res.ok().text("public")
+        });
+        TestClient::new(app)
+    }
+
+    #[tokio::test]
+    async fn test_basic_auth_challenges_and_accepts_valid_credentials() {
+        let users = HashMap::from([("admin".to_string(), "pa:ss wörd".to_string())]);
+        let client = make_client(|app| {
+            app.use_basic_auth(users, "Internal \"tools\"");
+        });
+
+        let res = client.get("/admin/stats").send().await;
+        assert_eq!(res.status(), 401);
+        assert_eq!(
+            res.header("www-authenticate"),
+            Some("Basic realm=\"Internal \\\"tools\\\"\", charset=\"UTF-8\"")
+        );
+
+        let res = client
+            .get("/admin/stats")
+            .header("Authorization", &basic("admin:pa:ss wörd".as_bytes()))
+            .send()
+            .await;
+        assert_eq!(res.status(), 200);
+        assert_eq!(res.text(), "stats");
+    }
+
+    #[tokio::test]
+    async fn test_basic_auth_rejects_wrong_and_malformed_credentials() {
+        let users = HashMap::from([("admin", "secret")]);
+        let client = make_client(|app| {
+            app.use_basic_auth(users, "Admin");
+        });
+
+        let headers = [
+            basic(b"admin:wrong"),
+            basic(b"admin:secre"),
+            basic(b"admin:secret2"),
+            basic(b"nobody:secret"),
+            basic(b"adminsecret"),
+            basic(b"admin:\xff"),
+            "Basic !!!not-base64".to_string(),
+            "Bearer secret".to_string(),
+        ];
+        for header in headers {
+            let res = client
+                .get("/admin/stats")
+                .header("Authorization", &header)
+                .send()
+                .await;
+            assert_eq!(res.status(), 401, "{}", header);
+            assert!(res.header("www-authenticate").is_some(), "{}", header);
+        }
+
+        let res = client
+            .get("/admin/stats")
+            .header(
+                "Authorization",
+                &basic(b"admin:secret").replace("Basic", "basic"),
+            )
+            .send()
+            .await;
+        assert_eq!(res.status(), 200);
+    }
+
+    #[tokio::test]
+    async fn test_basic_auth_with_async_verifier_on_prefix() {
+        let client = make_client(|app| {
+            app.use_basic_auth_on(
+                Some("/admin"),
+                |username: String, password: String| async move {
+                    tokio::task::yield_now().await;
+                    username == "ops" && password == "token"
+                },
+                "Ops",
+            );
+        });
+
+        assert_eq!(client.get("/public").send().await.status(), 200);
+        assert_eq!(client.get("/admin/stats").send().await.status(), 401);
+
+        let res = client
+            .get("/admin/stats")
+            .header("Authorization", &basic(b"ops:token"))
+            .send()
+            .await;
+        assert_eq!(res.status(), 200);
+    }
+}
@@ -1,7 +1,17 @@
 #[cfg(test)]
 mod test {
 
+    use std::time::Duration;
+
+    use futures::StreamExt;
+
+    use tokio::{
+        io::{AsyncReadExt, AsyncWriteExt},
+        net::{TcpListener, TcpStream},
+    };
+
     use crate::{
+        app::{test_client::TestClient, App},
         middlewares::body_limit::body_limit,
         next::Next,
         req::{
@@ -9,13 +19,14 @@ mod test {
             HttpRequest,
         },
         res::{response_status::StatusCode, HttpResponse},
+        types::{HttpMethods, RouterFns},
     };
 
     const DEFAULT_BODY_LIMIT: usize = 1024 * 1024;
 
     fn make_req_with_body(body: Vec<u8>) -> HttpRequest {
         HttpRequest {
-            body: RequestBody::new_binary(body),
+            body: RequestBody::new_binary(body).into(),
             ..Default::default()
         }
     }
@@ -91,4 +102,198 @@ mod test {
         assert_eq!(req_out.body.body_type(), RequestBodyType::BINARY);
         assert!(resp_opt.is_none());
     }
+
+    /// Serves one connection of `app` on an ephemeral port.
+    async fn serve_one(app: App) -> u16 {
+        let router_service = app.build_router_service();
+        let config = app.connection_config();
+        let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let port = listener.local_addr().unwrap().port();
+        tokio::spawn(async move {
+            let (stream, _) = listener.accept().await.unwrap();
+            App::handle_connection(stream, router_service, config).await;
+        });
+        port
+    }
+
+    /// Sends `head` and then `chunks`, leaving the body unfinished, and returns
+    /// what the server answers.
+    async fn send_unfinished(port: u16, head: &str, chunks: &[&str]) -> String {
+        let mut stream = TcpStream::connect(("127.0.0.1", port)).await.unwrap();
+        stream.write_all(head.as_bytes()).await.unwrap();
+        for chunk in chunks {
+            stream.write_all(chunk.as_bytes()).await.unwrap();
+        }
+
+        let mut response = vec![0u8; 1024];
+        let n = tokio::time::timeout(Duration::from_secs(2), stream.read(&mut response))
+            .await
+            .expect("server waited for the rest of the body")
+            .unwrap();
+        String::from_utf8_lossy(&response[..n]).into_owned()
+    }
+
+    fn echo_app(limit: usize) -> App {
+        let mut app = App::new();
+        app.use_body_limit(Some(limit));
+        app.post("/", |req: HttpRequest, res: HttpResponse| async move {
+            res.ok()
+                .text(String::from_utf8_lossy(&req.raw_body()).into_owned())
+        });
+        app
+    }
+
+    #[tokio::test]
+    async fn test_chunked_body_over_limit_is_rejected_mid_stream() {
+        let port = serve_one(echo_app(16)).await;
+        let head = "POST / HTTP/1.1\r\nHost: localhost\r\nTransfer-Encoding: chunked\r\n\r\n";
+        let chunk = "a\r\n0123456789\r\n";
+
+        let response = send_unfinished(port, head, &[chunk, chunk]).await;
+        assert!(response.starts_with("HTTP/1.1 413"), "{}", response);
+    }
+
+    #[tokio::test]
+    async fn test_declared_length_over_limit_is_rejected_before_reading() {
+        let port = serve_one(echo_app(16)).await;
+        let head = "POST / HTTP/1.1\r\nHost: localhost\r\nContent-Length: 1000\r\n\r\n";
+
+        let response = send_unfinished(port, head, &[]).await;
+        assert!(response.starts_with("HTTP/1.1 413"), "{}", response);
+    }
+
+    #[tokio::test]
+    async fn test_chunked_body_within_limit_reaches_handler() {
+        let port = serve_one(echo_app(16)).await;
+        let head = "POST / HTTP/1.1\r\nHost: localhost\r\nTransfer-Encoding: chunked\r\n\r\n";
+
+        let response = send_unfinished(port, head, &["5\r\nhello\r\n", "0\r\n\r\n"]).await;
+        assert!(response.starts_with("HTTP/1.1 200"), "{}", response);
+        assert!(response.ends_with("\r\n\r\nhello"), "{}", response);
+    }
+
+    fn upload_app(global: usize, upload: usize) -> App {
+        let mut app = App::new();
+        app.use_body_limit(Some(global));
+        app.route(HttpMethods::POST, "/upload")
+            .body_limit(upload)
+            .handler(|req: HttpRequest, res: HttpResponse| async move {
+                res.ok().text(format!("{:?}", req.body_limit()))
+            });
+        app.post("/api", |req: HttpRequest, res: HttpResponse| async move {
+            res.ok().text(format!("{:?}", req.body_limit()))
+        });
+        app
+    }
+
+    #[tokio::test]
+    async fn test_route_body_limit_overrides_global_limit() {
+        let client = TestClient::new(upload_app(1024 * 1024, 50 * 1024 * 1024));
+        let body = vec![b'a'; 20 * 1024 * 1024];
+
+        let res = client.post("/upload").body(body.clone()).send().await;
+        assert_eq!(res.status(), 200);
+        assert_eq!(res.text(), "Some(52428800)");
+
+        let res = client.post("/api").body(body).send().await;
+        assert_eq!(res.status(), 413);
+
+        let res = client.post("/api").body("small").send().await;
+        assert_eq!(res.status(), 200);
+        assert_eq!(res.text(), "Some(1048576)");
+    }
+
+    #[tokio::test]
+    async fn test_route_body_limit_refuses_large_json_before_reading() {
+        let mut app = App::new();
+        app.route(HttpMethods::POST, "/items")
+            .body_limit(1024 * 1024)
+            .handler(|req: HttpRequest, res: HttpResponse| async move {
+                match req.json_with_limit::<serde_json::Value>(1024 * 1024) {
+                    Ok(_) => res.ok().text("accepted"),
+                    Err(e) => res.status(413).text(e),
+                }
+            });
+        let port = serve_one(app).await;
+        let head = "POST /items HTTP/1.1\r\nHost: localhost\r\n\
+                    Content-Type: application/json\r\nContent-Length: 104857600\r\n\r\n";
+
+        // Only the start of the body is sent, so an answer means it wasn't awaited.
+        let response = send_unfinished(port, head, &["[1,"]).await;
+        assert!(response.starts_with("HTTP/1.1 413"), "{}", response);
+        assert!(!response.contains("accepted"), "{}", response);
+    }
+
+    #[tokio::test]
+    async fn test_route_body_limit_applies_while_streaming() {
+        let head = "Host: localhost\r\nTransfer-Encoding: chunked\r\n\r\n";
+        let chunk = "a\r\n0123456789\r\n";
+        let chunks = [chunk, chunk, "0\r\n\r\n"];
+
+        let port = serve_one(upload_app(16, 64)).await;
+        let response =
+            send_unfinished(port, &format!("POST /upload HTTP/1.1\r\n{}", head), &chunks).await;
+        assert!(response.starts_with("HTTP/1.1 200"), "{}", response);
+
+        let port = serve_one(upload_app(16, 64)).await;
+        let response =
+            send_unfinished(port, &format!("POST /api HTTP/1.1\r\n{}", head), &chunks).await;
+        assert!(response.starts_with("HTTP/1.1 413"), "{}", response);
+    }
+
+    fn streaming_app(limit: usize) -> App {
+        let mut app = App::new();
+        app.route(HttpMethods::POST, "/first")
+            .stream_body()
+            .handler(|req: HttpRequest, res: HttpResponse| async move {
+                let first = req.body_stream().next().await.unwrap().unwrap();
+                res.ok().text(String::from_utf8_lossy(&first).into_owned())
+            });
+        app.route(HttpMethods::POST, "/all")
+            .stream_body()
+            .body_limit(limit)
+            .handler(|req: HttpRequest, res: HttpResponse| async move {
+                let mut body = req.body_stream();
+                let mut received = Vec::new();
+                while let Some(chunk) = body.next().await {
+                    match chunk {
+                        Ok(chunk) => received.extend_from_slice(&chunk),
+                        Err(e) => return res.status(413).text(e.to_string()),
+                    }
+                }
+                res.ok()
+                    .text(String::from_utf8_lossy(&received).into_owned())
+            });
+        app
+    }
+
+    #[tokio::test]
+    async fn test_stream_body_reaches_handler_before_body_ends() {
+        let port = serve_one(streaming_app(64)).await;
+        let head = "POST /first HTTP/1.1\r\nHost: localhost\r\nTransfer-Encoding: chunked\r\n\r\n";
+
+        // The body is never finished, so an answer means it wasn't buffered.
+        let response = send_unfinished(port, head, &["5\r\nhello\r\n"]).await;
+        assert!(response.starts_with("HTTP/1.1 200"), "{}", response);
+        assert!(response.ends_with("\r\n\r\nhello"), "{}", response);
+    }
+
+    #[tokio::test]
+    async fn test_stream_body_applies_body_limit() {
+        let client = TestClient::new(streaming_app(16));
+        let res = client.post("/all").body("0123456789").send().await;
+        assert_eq!(res.status(), 200);
+        assert_eq!(res.text(), "0123456789");
+
+        // A declared length over the limit is refused before the handler runs.
+        let res = client.post("/all").body(vec![b'a'; 32]).send().await;
+        assert_eq!(res.status(), 413);
+
+        let port = serve_one(streaming_app(16)).await;
+        let head = "POST /all HTTP/1.1\r\nHost: localhost\r\nTransfer-Encoding: chunked\r\n\r\n";
+        let chunk = "a\r\n0123456789\r\n";
+        let response = send_unfinished(port, head, &[chunk, chunk, "0\r\n\r\n"]).await;
+        assert!(response.starts_with("HTTP/1.1 413"), "{}", response);
+        assert!(response.contains("limit of 16 bytes"), "{}", response);
+    }
 }
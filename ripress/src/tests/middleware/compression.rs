@@ -1,14 +1,18 @@
 #[cfg(feature = "compression")]
 #[cfg(test)]
 mod test {
+    use crate::app::{test_client::TestClient, App};
     use crate::context::HttpResponse;
     use crate::middlewares::compression::{
-        accepts_gzip_encoding, compress_data, compression, get_response_body_bytes,
-        set_response_body, should_compress_content_type, CompressionConfig,
+        accepts_gzip_encoding, compress_data, compression, content_type_matches,
+        get_response_body_bytes, has_no_transform, set_response_body, should_compress_content_type,
+        CompressionConfig,
     };
+    use crate::middlewares::cors::{CorsConfig, OriginMatcher};
     use crate::next::Next;
     use crate::req::HttpRequest;
     use crate::res::ResponseBody;
+    use crate::types::RouterFns;
 
     fn make_response_with_body(body: ResponseBody) -> HttpResponse {
         let mut res = HttpResponse::new();
@@ -103,6 +107,7 @@ mod test {
         let mw = compression(Some(CompressionConfig {
             threshold: 10,
             level: 6,
+            ..Default::default()
         }));
 
         let mut req = HttpRequest::new();
@@ -132,6 +137,7 @@ mod test {
         let mw = compression(Some(CompressionConfig {
             threshold: 10,
             level: 6,
+            ..Default::default()
         }));
 
         let req = HttpRequest::default();
@@ -148,6 +154,7 @@ mod test {
         let mw = compression(Some(CompressionConfig {
             threshold: 10,
             level: 6,
+            ..Default::default()
         }));
 
         let mut req = HttpRequest::default();
@@ -167,6 +174,7 @@ mod test {
         let mw = compression(Some(CompressionConfig {
             threshold: 100,
             level: 6,
+            ..Default::default()
         }));
 
         let mut req = HttpRequest::default();
@@ -186,6 +194,7 @@ mod test {
         let mw = compression(Some(CompressionConfig {
             threshold: 10,
             level: 6,
+            ..Default::default()
         }));
 
         let mut req = HttpRequest::default();
@@ -202,6 +211,100 @@ mod test {
         assert!(res_opt.is_none());
     }
 
+    #[tokio::test]
+    async fn test_compression_middleware_compresses_identity_encoding() {
+        let mw = compression(Some(CompressionConfig {
+            threshold: 10,
+            level: 6,
+            ..Default::default()
+        }));
+
+        let mut req = HttpRequest::default();
+        req.headers.insert("Accept-Encoding", "gzip");
+
+        let body = "hello hello hello hello hello hello hello hello";
+        let mut res = make_response_with_body(ResponseBody::TEXT(body.into()));
+        res.headers.insert("Content-Encoding", "identity");
+
+        let (_req_out, res_opt) = mw(req, res, make_next()).await;
+        let res = res_opt.unwrap();
+        assert_eq!(res.headers.get("Content-Encoding"), Some("gzip"));
+        assert_eq!(res.headers.get_all("Content-Encoding").len(), 1);
+    }
+
+    #[tokio::test]
+    async fn test_compression_middleware_skips_no_transform() {
+        let mw = compression(Some(CompressionConfig {
+            threshold: 10,
+            level: 6,
+            ..Default::default()
+        }));
+
+        let mut req = HttpRequest::default();
+        req.headers.insert("Accept-Encoding", "gzip");
+
+        let body = "hello hello hello hello hello hello hello hello";
+        let mut res = make_response_with_body(ResponseBody::TEXT(body.into()));
+        res.headers.insert("Cache-Control", "public, No-Transform");
+
+        let (_req_out, res_opt) = mw(req, res, make_next()).await;
+        assert!(res_opt.is_none());
+    }
+
+    #[test]
+    fn test_has_no_transform() {
+        assert!(has_no_transform("no-transform"));
+        assert!(has_no_transform("public, max-age=60, no-transform"));
+        assert!(!has_no_transform("no-cache"));
+    }
+
+    #[test]
+    fn test_content_type_matches() {
+        assert!(content_type_matches("text/*", "text/css; charset=utf-8"));
+        assert!(content_type_matches("application/json", "Application/JSON"));
+        assert!(content_type_matches("*/*", "application/wasm"));
+        assert!(!content_type_matches("text/*", "application/text"));
+        assert!(!content_type_matches(
+            "application/json",
+            "application/jsonx"
+        ));
+    }
+
+    #[test]
+    fn test_compression_config_custom_types() {
+        let config = CompressionConfig {
+            compressible_types: vec!["application/x-ndjson".to_string()],
+            incompressible_types: vec!["text/event-stream".to_string()],
+            ..Default::default()
+        };
+
+        assert!(config.is_compressible("application/x-ndjson"));
+        assert!(config.is_compressible("text/html"));
+        assert!(!config.is_compressible("text/event-stream"));
+        assert!(!config.is_compressible("application/wasm"));
+    }
+
+    #[tokio::test]
+    async fn test_compression_middleware_compresses_allowlisted_type() {
+        let mw = compression(Some(CompressionConfig {
+            threshold: 10,
+            compressible_types: vec!["application/x-ndjson".to_string()],
+            ..Default::default()
+        }));
+
+        let mut req = HttpRequest::default();
+        req.headers.insert("Accept-Encoding", "gzip");
+
+        let body = "{\"id\":1}\n{\"id\":2}\n{\"id\":3}\n{\"id\":4}\n";
+        let mut res =
+            make_response_with_body(ResponseBody::BINARY(body.as_bytes().to_vec().into()));
+        res.headers.insert("Content-Type", "application/x-ndjson");
+
+        let (_req_out, res_opt) = mw(req, res, make_next()).await;
+        let res = res_opt.unwrap();
+        assert_eq!(res.headers.get("Content-Encoding"), Some("gzip"));
+    }
+
     #[test]
     fn test_compress_data() {
         let original = b"Hello, World! ".repeat(100);
@@ -217,5 +320,42 @@ mod test {
         let config = CompressionConfig::default();
         assert_eq!(config.threshold, 1024);
         assert_eq!(config.level, 6);
+        assert!(config.compressible_types.is_empty());
+        assert!(config.incompressible_types.is_empty());
+    }
+
+    #[tokio::test]
+    async fn test_compression_keeps_cors_vary_origin() {
+        let mut app = App::new();
+        app.use_cors(Some(CorsConfig {
+            allowed_origins: OriginMatcher::list(["https://a.com", "https://b.com"]),
+            allow_credentials: true,
+            ..Default::default()
+        }));
+        app.use_compression(Some(CompressionConfig {
+            threshold: 10,
+            ..Default::default()
+        }));
+        app.get("/", |_req: HttpRequest, res: HttpResponse| async move {
+            res.ok().text("hello hello hello hello hello hello hello hello")
+        });
+        let client = TestClient::new(app);
+
+        let res = client
+            .get("/")
+            .header("Origin", "https://a.com")
+            .header("Accept-Encoding", "gzip")
+            .send()
+            .await;
+
+        assert_eq!(res.header("content-encoding"), Some("gzip"));
+        let vary: Vec<&str> = res
+            .headers()
+            .get_all("vary")
+            .iter()
+            .map(|value| value.to_str().unwrap())
+            .collect();
+        assert!(vary.contains(&"Origin"));
+        assert!(vary.contains(&"Accept-Encoding"));
     }
 }
@@ -1,7 +1,7 @@
 #[cfg(test)]
 mod test {
     use crate::{
-        middlewares::cors::{cors, CorsConfig},
+        middlewares::cors::{cors, list_allows, CorsConfig, OriginMatcher},
         next::Next,
         req::HttpRequest,
         res::HttpResponse,
@@ -24,6 +24,18 @@ mod test {
         futures::executor::block_on(mw(req, res, next))
     }
 
+    fn run_cors_with_origin(
+        method: HttpMethods,
+        origin: &str,
+        config: CorsConfig,
+    ) -> (HttpRequest, Option<HttpResponse>) {
+        let mut req = HttpRequest::new();
+        req.method = method;
+        req.set_header("Origin", origin);
+        let mw = cors(Some(config));
+        futures::executor::block_on(mw(req, HttpResponse::new(), make_next()))
+    }
+
     #[test]
     fn test_cors_headers_default_config() {
         let (_, maybe_res) = run_cors_middleware(HttpMethods::OPTIONS, None);
@@ -45,7 +57,7 @@ mod test {
     #[test]
     fn test_cors_headers_custom_config_with_credentials() {
         let config = CorsConfig {
-            allowed_origin: "https://example.com",
+            allowed_origins: "https://example.com".into(),
             allowed_methods: "GET, POST",
             allowed_headers: "X-Custom-Header",
             allow_credentials: true,
@@ -92,7 +104,7 @@ mod test {
     #[test]
     fn test_cors_options_preflight_with_credentials() {
         let config = CorsConfig {
-            allowed_origin: "https://foo.com",
+            allowed_origins: "https://foo.com".into(),
             allowed_methods: "OPTIONS",
             allowed_headers: "X-Token",
             allow_credentials: true,
@@ -118,4 +130,188 @@ mod test {
             Some("true")
         );
     }
+
+    fn run_preflight(method: &str, headers: Option<&str>, config: CorsConfig) -> HttpResponse {
+        let mut req = HttpRequest::new();
+        req.method = HttpMethods::OPTIONS;
+        req.set_header("Origin", "https://example.com");
+        req.set_header("Access-Control-Request-Method", method);
+        if let Some(headers) = headers {
+            req.set_header("Access-Control-Request-Headers", headers);
+        }
+        let mw = cors(Some(config));
+        let (_, maybe_res) = futures::executor::block_on(mw(req, HttpResponse::new(), make_next()));
+        maybe_res.unwrap()
+    }
+
+    #[test]
+    fn test_cors_reflects_each_allowlisted_origin_with_credentials() {
+        let config = CorsConfig {
+            allowed_origins: OriginMatcher::list(["https://a.com", "https://b.com"]),
+            allow_credentials: true,
+            ..Default::default()
+        };
+
+        for origin in ["https://a.com", "https://b.com"] {
+            let (_, maybe_res) = run_cors_with_origin(HttpMethods::OPTIONS, origin, config.clone());
+            let res = maybe_res.unwrap();
+            assert_eq!(res.headers.get("Access-Control-Allow-Origin"), Some(origin));
+            assert_eq!(
+                res.headers.get("Access-Control-Allow-Credentials"),
+                Some("true")
+            );
+            assert_eq!(res.headers.get("Vary"), Some("Origin"));
+        }
+    }
+
+    #[test]
+    fn test_cors_omits_headers_for_unlisted_origin() {
+        let config = CorsConfig {
+            allowed_origins: OriginMatcher::list(["https://a.com", "https://b.com"]),
+            allow_credentials: true,
+            ..Default::default()
+        };
+
+        let (_, maybe_res) = run_cors_with_origin(HttpMethods::OPTIONS, "https://evil.com", config);
+        let res = maybe_res.unwrap();
+        assert_eq!(res.headers.get("Access-Control-Allow-Origin"), None);
+        assert_eq!(res.headers.get("Access-Control-Allow-Credentials"), None);
+        assert_eq!(res.headers.get("Access-Control-Allow-Methods"), None);
+        assert_eq!(res.headers.get("Vary"), Some("Origin"));
+    }
+
+    #[test]
+    fn test_cors_predicate_origin_matcher() {
+        let config = CorsConfig {
+            allowed_origins: OriginMatcher::predicate(|origin| origin.ends_with(".example.com")),
+            ..Default::default()
+        };
+
+        let (_, maybe_res) = run_cors_with_origin(
+            HttpMethods::OPTIONS,
+            "https://api.example.com",
+            config.clone(),
+        );
+        assert_eq!(
+            maybe_res
+                .unwrap()
+                .headers
+                .get("Access-Control-Allow-Origin"),
+            Some("https://api.example.com")
+        );
+
+        let (_, maybe_res) =
+            run_cors_with_origin(HttpMethods::OPTIONS, "https://example.org", config);
+        assert_eq!(
+            maybe_res
+                .unwrap()
+                .headers
+                .get("Access-Control-Allow-Origin"),
+            None
+        );
+    }
+
+    #[test]
+    fn test_cors_any_origin_never_allows_credentials() {
+        let (_, maybe_res) =
+            run_cors_with_origin(HttpMethods::OPTIONS, "https://a.com", CorsConfig::default());
+        assert_eq!(
+            maybe_res
+                .unwrap()
+                .headers
+                .get("Access-Control-Allow-Origin"),
+            Some("*")
+        );
+
+        let config = CorsConfig {
+            allow_credentials: true,
+            ..Default::default()
+        };
+        let (_, maybe_res) =
+            run_cors_with_origin(HttpMethods::OPTIONS, "https://a.com", config.clone());
+        let res = maybe_res.unwrap();
+        assert_eq!(res.headers.get("Access-Control-Allow-Origin"), Some("*"));
+        assert_eq!(res.headers.get("Access-Control-Allow-Credentials"), None);
+
+        let res = run_preflight("POST", None, config);
+        assert_eq!(res.headers.get("Access-Control-Allow-Origin"), Some("*"));
+        assert_eq!(res.headers.get("Access-Control-Allow-Credentials"), None);
+    }
+
+    #[test]
+    fn test_origin_matcher_from_str() {
+        assert!(matches!(OriginMatcher::from("*"), OriginMatcher::Any));
+
+        let matcher = OriginMatcher::from("https://a.com, https://B.com");
+        assert!(matcher.matches("https://a.com"));
+        assert!(matcher.matches("https://b.com"));
+        assert!(!matcher.matches("https://c.com"));
+    }
+
+    #[test]
+    fn test_cors_preflight_reflects_permitted_headers() {
+        let config = CorsConfig {
+            allowed_headers: "Content-Type, X-Custom",
+            ..Default::default()
+        };
+        let res = run_preflight("PUT", Some("x-custom, content-type"), config);
+
+        assert_eq!(res.status_code.as_u16(), 200);
+        assert_eq!(
+            res.headers.get("Access-Control-Allow-Origin"),
+            Some("https://example.com")
+        );
+        assert_eq!(res.headers.get("Access-Control-Allow-Methods"), Some("PUT"));
+        assert_eq!(
+            res.headers.get("Access-Control-Allow-Headers"),
+            Some("x-custom, content-type")
+        );
+    }
+
+    #[test]
+    fn test_cors_preflight_rejects_unlisted_header() {
+        let res = run_preflight("POST", Some("X-Custom"), CorsConfig::default());
+
+        assert_eq!(res.headers.get("Access-Control-Allow-Origin"), None);
+        assert_eq!(res.headers.get("Access-Control-Allow-Methods"), None);
+        assert_eq!(res.headers.get("Access-Control-Allow-Headers"), None);
+        assert!(res.headers.get("Vary").is_some());
+    }
+
+    #[test]
+    fn test_cors_preflight_rejects_unlisted_method() {
+        let config = CorsConfig {
+            allowed_methods: "GET, POST",
+            allow_credentials: true,
+            ..Default::default()
+        };
+        let res = run_preflight("DELETE", None, config);
+
+        assert_eq!(res.headers.get("Access-Control-Allow-Origin"), None);
+        assert_eq!(res.headers.get("Access-Control-Allow-Methods"), None);
+        assert_eq!(res.headers.get("Access-Control-Allow-Credentials"), None);
+    }
+
+    #[test]
+    fn test_cors_preflight_wildcard_headers_reflects_any() {
+        let config = CorsConfig {
+            allowed_headers: "*",
+            ..Default::default()
+        };
+        let res = run_preflight("POST", Some("X-Anything, X-Trace"), config);
+
+        assert_eq!(
+            res.headers.get("Access-Control-Allow-Headers"),
+            Some("X-Anything, X-Trace")
+        );
+    }
+
+    #[test]
+    fn test_list_allows() {
+        assert!(list_allows("GET, POST", "post"));
+        assert!(!list_allows("GET, POST", "PUT"));
+        assert!(list_allows("Content-Type, X-Custom", "x-custom"));
+        assert!(list_allows("*", "X-Anything"));
+        assert!(!list_allows("", "X-Custom"));
+    }
 }
@@ -0,0 +1,103 @@
+#[cfg(test)]
+mod test {
+    use crate::context::HttpResponse;
+    use crate::middlewares::etag::{etag, generate_etag, if_none_match, EtagConfig};
+    use crate::next::Next;
+    use crate::req::HttpRequest;
+    use crate::res::ResponseBody;
+    use crate::types::HttpMethods;
+
+    fn make_next() -> Next {
+        Next {}
+    }
+
+    #[test]
+    fn test_generate_etag_is_stable() {
+        let a = generate_etag(b"hello world", true);
+        let b = generate_etag(b"hello world", true);
+        let c = generate_etag(b"hello world!", true);
+
+        assert_eq!(a, b);
+        assert_ne!(a, c);
+        assert!(a.starts_with("W/\"b-"));
+        assert!(generate_etag(b"hello world", false).starts_with("\"b-"));
+    }
+
+    #[test]
+    fn test_if_none_match() {
+        let tag = "W/\"5-abc\"";
+        assert!(if_none_match("W/\"5-abc\"", tag));
+        assert!(if_none_match("\"5-abc\"", tag));
+        assert!(if_none_match("\"other\", W/\"5-abc\"", tag));
+        assert!(if_none_match("*", tag));
+        assert!(!if_none_match("\"other\"", tag));
+        assert!(!if_none_match("", tag));
+    }
+
+    #[tokio::test]
+    async fn test_etag_middleware_sets_header() {
+        let mw = etag(None);
+        let req = HttpRequest::new();
+        let res = HttpResponse::new().ok().text("hello");
+
+        let (_, res_opt) = mw(req, res, make_next()).await;
+        let res = res_opt.unwrap();
+
+        assert_eq!(res.get_status_code(), 200);
+        assert_eq!(
+            res.headers.get("etag"),
+            Some(generate_etag(b"hello", true).as_str())
+        );
+    }
+
+    #[tokio::test]
+    async fn test_etag_middleware_returns_304_on_match() {
+        let mw = etag(None);
+        let tag = generate_etag(b"hello", true);
+
+        let mut req = HttpRequest::new();
+        req.set_header("if-none-match", &tag);
+        let res = HttpResponse::new().ok().text("hello");
+
+        let (_, res_opt) = mw(req, res, make_next()).await;
+        let res = res_opt.unwrap();
+
+        assert_eq!(res.get_status_code(), 304);
+        assert_eq!(res.headers.get("etag"), Some(tag.as_str()));
+        assert_eq!(res.get_body(), ResponseBody::TEXT(String::new()));
+    }
+
+    #[tokio::test]
+    async fn test_etag_middleware_keeps_handler_etag() {
+        let mw = etag(Some(EtagConfig { weak: false }));
+
+        let mut req = HttpRequest::new();
+        req.set_header("if-none-match", "\"v1\"");
+        let res = HttpResponse::new()
+            .ok()
+            .set_header("ETag", "\"v1\"")
+            .json(serde_json::json!({"id": 1}));
+
+        let (_, res_opt) = mw(req, res, make_next()).await;
+        let res = res_opt.unwrap();
+
+        assert_eq!(res.get_status_code(), 304);
+        assert_eq!(res.headers.get("etag"), Some("\"v1\""));
+    }
+
+    #[tokio::test]
+    async fn test_etag_middleware_skips_non_get_and_errors() {
+        let mw = etag(None);
+
+        let mut req = HttpRequest::new();
+        req.method = HttpMethods::POST;
+        let res = HttpResponse::new().ok().text("hello");
+        let (_, res_opt) = mw(req, res, make_next()).await;
+        assert!(res_opt.is_none());
+
+        let req = HttpRequest::new();
+        let res = HttpResponse::new().not_found().text("missing");
+        let (_, res_opt) = mw(req, res, make_next()).await;
+        assert!(res_opt.is_none());
+    }
+}
@@ -3,11 +3,13 @@ mod tests {
     use std::sync::Arc;
 
     use crate::{
-        app::api_error::ApiError,
-        helpers::exec_pre_middleware,
+        app::{api_error::ApiError, App},
+        helpers::{exec_pre_middleware, run_post_middleware},
         middlewares::{Middleware, MiddlewareType},
         req::HttpRequest,
         res::HttpResponse,
+        router::route_pattern::RouteTable,
+        types::RouterFns,
     };
 
     #[cfg(feature = "with-wynd")]
@@ -83,6 +85,57 @@ mod tests {
         }
     }
 
+    #[tokio::test]
+    async fn test_post_middlewares_see_data_set_by_pre_middleware() {
+        let mut app = App::new();
+        app.get("/", |_req: HttpRequest, res: HttpResponse| async move {
+            res.ok().text("ok")
+        });
+        let routes = RouteTable::new(&app.routes, true);
+
+        // The request as a pre-middleware hands it on.
+        let mut pre_req = HttpRequest::new();
+        pre_req.set_path("/".to_string());
+        pre_req.set_data("request_id", "abc-123");
+        let req = pre_req.to_hyper_request().unwrap();
+        let res = App::route_request(req, &routes, &Default::default(), &[], None)
+            .await
+            .unwrap();
+
+        let first = Arc::new(Middleware {
+            path: "/".to_string(),
+            func: Arc::new(|mut req: HttpRequest, res: HttpResponse, _| {
+                Box::pin(async move {
+                    let id = req.get_data("request_id").unwrap_or_default();
+                    req.set_data("logged", "yes");
+                    (req, Some(res.set_header("x-request-id", id)))
+                })
+            }),
+            middleware_type: MiddlewareType::Post,
+        });
+        let second = Arc::new(Middleware {
+            path: "/".to_string(),
+            func: Arc::new(|req: HttpRequest, res: HttpResponse, _| {
+                Box::pin(async move {
+                    let logged = req.get_data("logged").unwrap_or_default();
+                    (req, Some(res.set_header("x-logged", logged)))
+                })
+            }),
+            middleware_type: MiddlewareType::Post,
+        });
+
+        // Post-middlewares rebuild the request without its data.
+        let mut post_req = HttpRequest::new();
+        post_req.set_path("/".to_string());
+        let res = run_post_middleware(res, first, post_req.clone())
+            .await
+            .unwrap();
+        let res = run_post_middleware(res, second, post_req).await.unwrap();
+
+        assert_eq!(res.headers()["x-request-id"], "abc-123");
+        assert_eq!(res.headers()["x-logged"], "yes");
+    }
+
     #[cfg(feature = "with-wynd")]
     #[tokio::test]
     async fn test_exec_wynd_middleware_error_continues() {
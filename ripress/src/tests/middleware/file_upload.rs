@@ -1,13 +1,23 @@
 #[cfg(test)]
 #[cfg(feature = "file-upload")]
 mod test {
+    use std::time::Duration;
+
     use tempfile::TempDir;
+    use tokio::{
+        io::{AsyncReadExt, AsyncWriteExt},
+        net::{TcpListener, TcpStream},
+    };
 
     use crate::{
-        middlewares::file_upload::{file_upload, FileUploadConfiguration},
+        app::{settings::MultipartLimits, test_client::TestClient, App},
+        middlewares::file_upload::{
+            file_upload, is_allowed_type, FileUploadConfiguration, SniffedType,
+        },
         next::Next,
         req::HttpRequest,
         res::HttpResponse,
+        types::{HttpMethods, RouterFns},
     };
 
     fn make_next() -> Next {
@@ -185,8 +195,12 @@ mod test {
             crate::req::body::RequestBodyType::MultipartForm
         );
 
-        let (fields, file_parts) =
-            crate::helpers::parse_multipart_form(multipart_data.as_bytes(), &boundary);
+        let (fields, file_parts) = crate::helpers::parse_multipart_form(
+            multipart_data.as_bytes(),
+            &boundary,
+            &MultipartLimits::default(),
+        )
+        .unwrap();
 
         assert_eq!(fields.len(), 2);
         assert_eq!(file_parts.len(), 1);
@@ -197,7 +211,7 @@ mod test {
         assert_eq!(age_field.map(|(_, v)| v), Some(&"30"));
 
         let file_part = &file_parts[0];
-        assert_eq!(file_part.1.unwrap(), "file");
+        assert_eq!(file_part.1.name.as_deref(), Some("file"));
 
         let mut req = HttpRequest::new();
 
@@ -240,8 +254,12 @@ mod test {
             crate::req::body::RequestBodyType::MultipartForm
         );
 
-        let (fields, file_parts) =
-            crate::helpers::parse_multipart_form(multipart_data.as_bytes(), &boundary);
+        let (fields, file_parts) = crate::helpers::parse_multipart_form(
+            multipart_data.as_bytes(),
+            &boundary,
+            &MultipartLimits::default(),
+        )
+        .unwrap();
 
         assert_eq!(fields.len(), 2);
         assert_eq!(file_parts.len(), 1);
@@ -274,4 +292,458 @@ mod test {
             panic!("Expected BinaryWithFields variant");
         }
     }
+
+    fn multipart_request(boundary: &str, body: Vec<u8>) -> HttpRequest {
+        let mut req = HttpRequest::new();
+        req._set_binary(body);
+        req.set_header(
+            "content-type",
+            &format!("multipart/form-data; boundary={}", boundary),
+        );
+        req
+    }
+
+    fn file_part(boundary: &str, field: &str, content: &[u8]) -> Vec<u8> {
+        let mut part = format!(
+            "--{boundary}\r\nContent-Disposition: form-data; name=\"{field}\"; filename=\"{field}.bin\"\r\n\r\n"
+        )
+        .into_bytes();
+        part.extend_from_slice(content);
+        part.extend_from_slice(b"\r\n");
+        part
+    }
+
+    fn dir_entries(dir: &TempDir) -> Vec<String> {
+        std::fs::read_dir(dir.path())
+            .unwrap()
+            .map(|e| e.unwrap().file_name().to_string_lossy().into_owned())
+            .collect()
+    }
+
+    #[tokio::test]
+    async fn test_stream_to_disk_saves_files_and_fields() {
+        let temp_dir = TempDir::new().unwrap();
+        let upload_mw = file_upload(Some(FileUploadConfiguration {
+            upload_dir: temp_dir.path().to_string_lossy().to_string(),
+            stream_to_disk: true,
+            ..Default::default()
+        }));
+
+        let boundary = "streamb";
+        let mut body = format!(
+            "--{boundary}\r\nContent-Disposition: form-data; name=\"title\"\r\n\r\nholiday\r\n"
+        )
+        .into_bytes();
+        body.extend(file_part(boundary, "photo", b"not really a photo"));
+        body.extend_from_slice(format!("--{boundary}--\r\n").as_bytes());
+
+        let (req, res) = upload_mw(
+            multipart_request(boundary, body),
+            HttpResponse::new(),
+            make_next(),
+        )
+        .await;
+        assert!(res.is_none());
+
+        let form = req.form_data().unwrap();
+        assert_eq!(form.get("title"), Some("holiday"));
+        let stored = form.get("photo").unwrap().to_string();
+        assert!(stored.ends_with(".bin"));
+
+        let saved = std::fs::read(temp_dir.path().join(&stored)).unwrap();
+        assert_eq!(saved, b"not really a photo");
+        assert_eq!(dir_entries(&temp_dir), vec![stored]);
+    }
+
+    #[tokio::test]
+    async fn test_stream_to_disk_rejects_oversized_file_and_cleans_up() {
+        let temp_dir = TempDir::new().unwrap();
+        let upload_mw = file_upload(Some(FileUploadConfiguration {
+            upload_dir: temp_dir.path().to_string_lossy().to_string(),
+            max_file_size: 1024,
+            stream_to_disk: true,
+            ..Default::default()
+        }));
+
+        let boundary = "bigfile";
+        let mut body = file_part(boundary, "small", b"fits");
+        body.extend(file_part(boundary, "large", &vec![b'x'; 64 * 1024]));
+        body.extend_from_slice(format!("--{boundary}--\r\n").as_bytes());
+
+        let (_, res) = upload_mw(
+            multipart_request(boundary, body),
+            HttpResponse::new(),
+            make_next(),
+        )
+        .await;
+        let res = res.expect("oversized upload should short-circuit");
+
+        assert_eq!(res.status_code.as_u16(), 413);
+        let body: serde_json::Value = match res.get_body() {
+            crate::res::ResponseBody::JSON(json) => json,
+            other => panic!("expected JSON body, got {:?}", other),
+        };
+        assert_eq!(body["field"], "large");
+        assert_eq!(body["limit"], 1024);
+        assert!(
+            dir_entries(&temp_dir).is_empty(),
+            "partial and completed files should be removed"
+        );
+    }
+
+    fn streaming_upload_app(dir: &TempDir) -> App {
+        let mut app = App::new();
+        app.use_pre_middleware(
+            "/upload",
+            file_upload(Some(FileUploadConfiguration {
+                upload_dir: dir.path().to_string_lossy().to_string(),
+                max_file_size: 1024,
+                stream_to_disk: true,
+                ..Default::default()
+            })),
+        );
+        app.route(HttpMethods::POST, "/upload")
+            .stream_body()
+            .handler(|req: HttpRequest, res: HttpResponse| async move {
+                res.ok().text(format!("{} files", req.files().len()))
+            });
+        app
+    }
+
+    #[tokio::test]
+    async fn test_stream_to_disk_reads_stream_body_route_from_connection() {
+        let temp_dir = TempDir::new().unwrap();
+        let client = TestClient::new(streaming_upload_app(&temp_dir));
+        let boundary = "streamed";
+        let content_type = format!("multipart/form-data; boundary={}", boundary);
+
+        let mut body = file_part(boundary, "small", b"fits");
+        body.extend_from_slice(format!("--{boundary}--\r\n").as_bytes());
+        let res = client
+            .post("/upload")
+            .header("content-type", &content_type)
+            .body(body)
+            .send()
+            .await;
+        assert_eq!(res.status(), 200);
+        assert_eq!(res.text(), "1 files");
+        assert_eq!(dir_entries(&temp_dir).len(), 1);
+
+        let mut body = file_part(boundary, "large", &vec![b'x'; 64 * 1024]);
+        body.extend_from_slice(format!("--{boundary}--\r\n").as_bytes());
+        let res = client
+            .post("/upload")
+            .header("content-type", &content_type)
+            .body(body)
+            .send()
+            .await;
+        assert_eq!(res.status(), 413);
+        assert_eq!(dir_entries(&temp_dir).len(), 1);
+    }
+
+    #[tokio::test]
+    async fn test_stream_to_disk_refuses_oversized_part_before_body_ends() {
+        let temp_dir = TempDir::new().unwrap();
+        let app = streaming_upload_app(&temp_dir);
+        let router_service = app.build_router_service();
+        let config = app.connection_config();
+        let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let port = listener.local_addr().unwrap().port();
+        tokio::spawn(async move {
+            let (stream, _) = listener.accept().await.unwrap();
+            App::handle_connection(stream, router_service, config).await;
+        });
+
+        // Declares 2 GB but only sends the start of the oversized part.
+        let boundary = "huge";
+        let head = format!(
+            "POST /upload HTTP/1.1\r\nHost: localhost\r\n\
+             Content-Type: multipart/form-data; boundary={}\r\n\
+             Content-Length: 2147483648\r\n\r\n",
+            boundary
+        );
+        let part = file_part(boundary, "video", &vec![b'x'; 64 * 1024]);
+        let mut stream = TcpStream::connect(("127.0.0.1", port)).await.unwrap();
+        stream.write_all(head.as_bytes()).await.unwrap();
+        stream.write_all(&part).await.unwrap();
+
+        let mut response = vec![0u8; 1024];
+        let n = tokio::time::timeout(Duration::from_secs(2), stream.read(&mut response))
+            .await
+            .expect("server waited for the rest of the body")
+            .unwrap();
+        let response = String::from_utf8_lossy(&response[..n]);
+        assert!(response.starts_with("HTTP/1.1 413"), "{}", response);
+        assert!(dir_entries(&temp_dir).is_empty());
+    }
+
+    #[tokio::test]
+    async fn test_stream_to_disk_rejects_too_many_files() {
+        let temp_dir = TempDir::new().unwrap();
+        let upload_mw = file_upload(Some(FileUploadConfiguration {
+            upload_dir: temp_dir.path().to_string_lossy().to_string(),
+            max_files: 1,
+            stream_to_disk: true,
+            ..Default::default()
+        }));
+
+        let boundary = "manyfiles";
+        let mut body = file_part(boundary, "a", b"first");
+        body.extend(file_part(boundary, "b", b"second"));
+        body.extend_from_slice(format!("--{boundary}--\r\n").as_bytes());
+
+        let (_, res) = upload_mw(
+            multipart_request(boundary, body),
+            HttpResponse::new(),
+            make_next(),
+        )
+        .await;
+
+        assert_eq!(res.unwrap().status_code.as_u16(), 413);
+        assert!(dir_entries(&temp_dir).is_empty());
+    }
+
+    #[tokio::test]
+    async fn test_multipart_limits_apply_to_both_parsers() {
+        let temp_dir = TempDir::new().unwrap();
+        let boundary = "fields";
+        let mut body = Vec::new();
+        for i in 0..5 {
+            body.extend_from_slice(
+                format!(
+                    "--{boundary}\r\nContent-Disposition: form-data; name=\"f{i}\"\r\n\r\nv\r\n"
+                )
+                .as_bytes(),
+            );
+        }
+        body.extend_from_slice(format!("--{boundary}--\r\n").as_bytes());
+
+        for stream_to_disk in [false, true] {
+            let upload_mw = file_upload(Some(FileUploadConfiguration {
+                upload_dir: temp_dir.path().to_string_lossy().to_string(),
+                stream_to_disk,
+                ..Default::default()
+            }));
+            let mut req = multipart_request(boundary, body.clone());
+            req.settings.multipart_limits = MultipartLimits {
+                max_fields: 4,
+                ..Default::default()
+            };
+
+            let (_, res) = upload_mw(req, HttpResponse::new(), make_next()).await;
+            assert_eq!(res.unwrap().status_code.as_u16(), 413, "{}", stream_to_disk);
+        }
+        assert!(dir_entries(&temp_dir).is_empty());
+    }
+
+    #[tokio::test]
+    async fn test_stream_to_disk_rejects_truncated_body() {
+        let temp_dir = TempDir::new().unwrap();
+        let upload_mw = file_upload(Some(FileUploadConfiguration {
+            upload_dir: temp_dir.path().to_string_lossy().to_string(),
+            stream_to_disk: true,
+            ..Default::default()
+        }));
+
+        let boundary = "cut";
+        let mut body = file_part(boundary, "a", b"complete");
+        body.extend_from_slice(
+            format!("--{boundary}\r\nContent-Disposition: form-data; name=\"b\"; filename=\"b\"\r\n\r\npartial")
+                .as_bytes(),
+        );
+
+        let (_, res) = upload_mw(
+            multipart_request(boundary, body),
+            HttpResponse::new(),
+            make_next(),
+        )
+        .await;
+
+        assert_eq!(res.unwrap().status_code.as_u16(), 400);
+        assert!(dir_entries(&temp_dir).is_empty());
+    }
+
+    #[tokio::test]
+    async fn test_files_exposes_uploaded_file_metadata() {
+        for stream_to_disk in [false, true] {
+            let temp_dir = TempDir::new().unwrap();
+            let upload_mw = file_upload(Some(FileUploadConfiguration {
+                upload_dir: temp_dir.path().to_string_lossy().to_string(),
+                stream_to_disk,
+                ..Default::default()
+            }));
+
+            let boundary = "meta";
+            let body = format!(
+                "--{boundary}\r\n\
+                Content-Disposition: form-data; name=\"report\"; filename*=UTF-8''r%C3%A9sum%C3%A9.txt\r\n\
+                Content-Type: text/plain\r\n\
+                \r\n\
+                hello world\r\n\
+                --{boundary}--\r\n"
+            );
+
+            let (req, res) = upload_mw(
+                multipart_request(boundary, body.into_bytes()),
+                HttpResponse::new(),
+                make_next(),
+            )
+            .await;
+            assert!(res.is_none());
+
+            let files = req.files();
+            assert_eq!(files.len(), 1, "stream_to_disk: {}", stream_to_disk);
+            let file = &files[0];
+            assert_eq!(file.field_name.as_deref(), Some("report"));
+            assert_eq!(
+                file.original_filename.as_deref(),
+                Some("r\u{e9}sum\u{e9}.txt")
+            );
+            assert_eq!(file.content_type.as_deref(), Some("text/plain"));
+            assert_eq!(file.size, 11);
+            assert!(file.stored_path.starts_with(temp_dir.path()));
+            assert_eq!(std::fs::read(&file.stored_path).unwrap(), b"hello world");
+            assert_eq!(
+                req.form_data().unwrap().get("report"),
+                file.stored_filename()
+            );
+        }
+    }
+
+    #[tokio::test]
+    async fn test_files_survive_conversion_to_route_handler() {
+        let temp_dir = TempDir::new().unwrap();
+        let upload_mw = file_upload(Some(FileUploadConfiguration {
+            upload_dir: temp_dir.path().to_string_lossy().to_string(),
+            ..Default::default()
+        }));
+
+        let boundary = "handoff";
+        let mut body = file_part(boundary, "doc", b"contents");
+        body.extend_from_slice(format!("--{boundary}--\r\n").as_bytes());
+
+        let (req, _) = upload_mw(
+            multipart_request(boundary, body),
+            HttpResponse::new(),
+            make_next(),
+        )
+        .await;
+
+        let mut hyper_req = req.to_hyper_request().unwrap();
+        let handler_req = HttpRequest::from_hyper_request(&mut hyper_req)
+            .await
+            .unwrap();
+
+        assert_eq!(handler_req.files(), req.files());
+        assert_eq!(handler_req.files().len(), 1);
+    }
+
+    const PNG_MAGIC: &[u8] = b"\x89PNG\r\n\x1a\n\0\0\0\rIHDR";
+    const EXE_MAGIC: &[u8] = b"MZ\x90\0\x03\0\0\0\x04\0\0\0\xff\xff";
+
+    fn named_file_part(boundary: &str, field: &str, filename: &str, content: &[u8]) -> Vec<u8> {
+        let mut part = format!(
+            "--{boundary}\r\nContent-Disposition: form-data; name=\"{field}\"; filename=\"{filename}\"\r\nContent-Type: image/png\r\n\r\n"
+        )
+        .into_bytes();
+        part.extend_from_slice(content);
+        part.extend_from_slice(b"\r\n");
+        part
+    }
+
+    #[test]
+    fn test_is_allowed_type_matches_extensions_and_mime_types() {
+        let png = SniffedType::of(PNG_MAGIC);
+        assert_eq!(png.extension, "png");
+        assert_eq!(png.mime_type, "image/png");
+
+        let allow = |types: &[&str]| types.iter().map(|t| t.to_string()).collect::<Vec<_>>();
+        assert!(is_allowed_type(&png, &[]));
+        assert!(is_allowed_type(&png, &allow(&["PNG"])));
+        assert!(is_allowed_type(&png, &allow(&["image/png"])));
+        assert!(is_allowed_type(&png, &allow(&["image/*"])));
+        assert!(!is_allowed_type(
+            &png,
+            &allow(&["jpg", "application/pdf", "text/*"])
+        ));
+
+        let unknown = SniffedType::of(b"plain text");
+        assert_eq!(unknown.extension, "bin");
+        assert!(!is_allowed_type(&unknown, &allow(&["png"])));
+    }
+
+    #[tokio::test]
+    async fn test_rejects_executable_disguised_as_png() {
+        for stream_to_disk in [false, true] {
+            let temp_dir = TempDir::new().unwrap();
+            let upload_mw = file_upload(Some(FileUploadConfiguration {
+                upload_dir: temp_dir.path().to_string_lossy().to_string(),
+                allowed_file_types: vec!["png".to_string()],
+                stream_to_disk,
+                ..Default::default()
+            }));
+
+            let boundary = "sniff";
+            let mut body = named_file_part(boundary, "good", "real.png", PNG_MAGIC);
+            body.extend(named_file_part(boundary, "avatar", "cat.png", EXE_MAGIC));
+            body.extend_from_slice(format!("--{boundary}--\r\n").as_bytes());
+
+            let (req, res) = upload_mw(
+                multipart_request(boundary, body),
+                HttpResponse::new(),
+                make_next(),
+            )
+            .await;
+            let res = res.expect("disguised executable should be refused");
+
+            assert_eq!(res.status_code.as_u16(), 415);
+            let body: serde_json::Value = match res.get_body() {
+                crate::res::ResponseBody::JSON(json) => json,
+                other => panic!("expected JSON body, got {:?}", other),
+            };
+            assert_eq!(body["field"], "avatar");
+            assert_eq!(body["filename"], "cat.png");
+            assert_eq!(
+                body["detected_type"],
+                "application/vnd.microsoft.portable-executable"
+            );
+            assert!(req.files().is_empty());
+            assert!(
+                dir_entries(&temp_dir).is_empty(),
+                "stream_to_disk: {}",
+                stream_to_disk
+            );
+        }
+    }
+
+    #[tokio::test]
+    async fn test_accepts_file_matching_sniffed_type() {
+        let temp_dir = TempDir::new().unwrap();
+        let upload_mw = file_upload(Some(FileUploadConfiguration {
+            upload_dir: temp_dir.path().to_string_lossy().to_string(),
+            allowed_file_types: vec!["image/*".to_string()],
+            ..Default::default()
+        }));
+
+        let boundary = "sniffok";
+        let mut body = named_file_part(boundary, "avatar", "me.png", PNG_MAGIC);
+        body.extend_from_slice(format!("--{boundary}--\r\n").as_bytes());
+
+        let (req, res) = upload_mw(
+            multipart_request(boundary, body),
+            HttpResponse::new(),
+            make_next(),
+        )
+        .await;
+
+        assert!(res.is_none());
+        assert_eq!(req.files().len(), 1);
+        assert_eq!(
+            req.files()[0]
+                .stored_path
+                .extension()
+                .and_then(|e| e.to_str()),
+            Some("png")
+        );
+    }
 }
@@ -1,12 +1,17 @@
 #[cfg(test)]
 #[cfg(feature = "logger")]
 mod test {
+    use std::time::{Duration, Instant};
+
     use crate::{
-        middlewares::logger::{logger, LoggerConfig},
+        app::{test_client::TestClient, App},
+        middlewares::logger::{
+            elapsed_ms, format_json, format_text, logger, response_bytes, LogFormat, LoggerConfig,
+        },
         next::Next,
         req::HttpRequest,
         res::HttpResponse,
-        types::HttpMethods,
+        types::{HttpMethods, RouterFns},
     };
 
     fn make_next() -> Next {
@@ -85,4 +90,201 @@ mod test {
         assert_eq!(returned_req.method, HttpMethods::DELETE);
         assert!(maybe_res.is_none());
     }
+
+    #[test]
+    fn test_format_json_is_single_parseable_line() {
+        let config = LoggerConfig {
+            format: LogFormat::Json,
+            headers: vec!["X-Request-Id".to_string(), "x-missing".to_string()],
+            ..Default::default()
+        };
+
+        let mut req = HttpRequest::new();
+        req.path = "/api/users".to_string();
+        req.method = HttpMethods::GET;
+        req.set_header("x-request-id", "abc-123");
+        req.set_query("page", "2");
+        let res = HttpResponse::new().created().text("hello");
+
+        let line = format_json(&config, &req, &res);
+        assert!(!line.contains('\n'));
+
+        let value: serde_json::Value = serde_json::from_str(&line).unwrap();
+        assert_eq!(value["method"], "GET");
+        assert_eq!(value["path"], "/api/users");
+        assert_eq!(value["status"], 201);
+        assert_eq!(value["headers"]["x-request-id"], "abc-123");
+        assert!(value["headers"]["x-missing"].is_null());
+        assert_eq!(value["query_params"]["page"], "2");
+        assert_eq!(value["body_size"], 5);
+        assert!(value.get("ip").is_some());
+    }
+
+    #[test]
+    fn test_format_json_omits_disabled_fields() {
+        let config = LoggerConfig {
+            format: LogFormat::Json,
+            user_agent: false,
+            ip: false,
+            query_params: false,
+            body_size: false,
+            ..Default::default()
+        };
+
+        let req = HttpRequest::new();
+        let res = HttpResponse::new();
+        let value: serde_json::Value =
+            serde_json::from_str(&format_json(&config, &req, &res)).unwrap();
+
+        let keys: Vec<&str> = value
+            .as_object()
+            .unwrap()
+            .keys()
+            .map(|k| k.as_str())
+            .collect();
+        assert_eq!(keys, vec!["method", "path", "status"]);
+    }
+
+    #[test]
+    fn test_format_includes_matched_route() {
+        let mut req = HttpRequest::new();
+        req.path = "/users/123".to_string();
+        req.matched_path = Some("/users/:id".to_string());
+        let res = HttpResponse::new();
+
+        let text = format_text(&LoggerConfig::default(), &req, &res);
+        assert!(text.starts_with("path: /users/123, \nroute: /users/:id"));
+
+        let config = LoggerConfig {
+            format: LogFormat::Json,
+            ..Default::default()
+        };
+        let value: serde_json::Value =
+            serde_json::from_str(&format_json(&config, &req, &res)).unwrap();
+        assert_eq!(value["route"], "/users/:id");
+    }
+
+    #[test]
+    fn test_format_text() {
+        let config = LoggerConfig {
+            user_agent: false,
+            ip: false,
+            query_params: false,
+            ..Default::default()
+        };
+
+        let mut req = HttpRequest::new();
+        req.path = "/text".to_string();
+        let res = HttpResponse::new().text("hi");
+
+        let msg = format_text(&config, &req, &res);
+        assert!(msg.starts_with("path: /text"));
+        assert!(msg.contains("status_code: 200"));
+        assert!(msg.ends_with("body_size: 2"));
+    }
+
+    #[tokio::test]
+    async fn test_logger_records_duration() {
+        let mut req = HttpRequest::new();
+        assert!(elapsed_ms(&req).is_none());
+
+        req.received_at = Some(Instant::now());
+        tokio::time::sleep(Duration::from_millis(20)).await;
+        let elapsed = elapsed_ms(&req).unwrap();
+        assert!(elapsed >= 20.0, "elapsed {}", elapsed);
+
+        let config = LoggerConfig {
+            format: LogFormat::Json,
+            ..Default::default()
+        };
+        let res = HttpResponse::new().text("hello");
+        let value: serde_json::Value =
+            serde_json::from_str(&format_json(&config, &req, &res)).unwrap();
+        assert!(value["duration_ms"].as_f64().unwrap() >= 20.0);
+        assert_eq!(value["body_size"], 5);
+    }
+
+    #[tokio::test]
+    async fn test_logger_durations_are_per_request() {
+        let mut early = HttpRequest::new();
+        early.received_at = Some(Instant::now());
+        tokio::time::sleep(Duration::from_millis(30)).await;
+        let mut late = HttpRequest::new();
+        late.received_at = Some(Instant::now());
+
+        assert!(elapsed_ms(&early).unwrap() >= 30.0);
+        assert!(elapsed_ms(&late).unwrap() < elapsed_ms(&early).unwrap());
+    }
+
+    #[tokio::test]
+    async fn test_post_middleware_sees_when_request_was_received() {
+        let mut app = App::new();
+        app.get("/slow", |_req: HttpRequest, res: HttpResponse| async move {
+            tokio::time::sleep(Duration::from_millis(20)).await;
+            res.ok().text("done")
+        });
+        app.use_post_middleware("/", |req: HttpRequest, res: HttpResponse, _| async move {
+            let elapsed = elapsed_ms(&req).unwrap_or(-1.0);
+            let res = res.set_header("x-elapsed-ms", elapsed.to_string());
+            (req, Some(res))
+        });
+        let client = TestClient::new(app);
+
+        let res = client.get("/slow").send().await;
+
+        let elapsed: f64 = res.header("x-elapsed-ms").unwrap().parse().unwrap();
+        assert!(elapsed >= 20.0, "elapsed {}", elapsed);
+    }
+
+    #[test]
+    fn test_response_bytes_prefers_content_length() {
+        let res = HttpResponse::new().text("hello");
+        assert_eq!(response_bytes(&res), Some(5));
+
+        let res = HttpResponse::new()
+            .text("hello")
+            .set_header("Content-Length", "42");
+        assert_eq!(response_bytes(&res), Some(42));
+    }
+
+    #[test]
+    fn test_format_includes_request_id() {
+        let config = LoggerConfig {
+            format: LogFormat::Json,
+            ..Default::default()
+        };
+        let mut req = HttpRequest::new();
+        req.set_data("request_id", "abc-123");
+        let res = HttpResponse::new();
+
+        let value: serde_json::Value =
+            serde_json::from_str(&format_json(&config, &req, &res)).unwrap();
+        assert_eq!(value["request_id"], "abc-123");
+        assert!(
+            format_text(&LoggerConfig::default(), &req, &res).starts_with("request_id: abc-123")
+        );
+    }
+
+    #[test]
+    fn test_format_logs_every_value_of_repeated_header() {
+        let config = LoggerConfig {
+            format: LogFormat::Json,
+            headers: vec!["Forwarded".to_string()],
+            ..Default::default()
+        };
+        let mut req = HttpRequest::new();
+        req.headers.append("forwarded", "for=192.0.2.1");
+        req.headers.append("Forwarded", "for=198.51.100.7");
+        let res = HttpResponse::new();
+
+        let value: serde_json::Value =
+            serde_json::from_str(&format_json(&config, &req, &res)).unwrap();
+        assert_eq!(
+            value["headers"]["forwarded"],
+            "for=192.0.2.1, for=198.51.100.7"
+        );
+        assert!(
+            format_text(&config, &req, &res).contains("forwarded: for=192.0.2.1, for=198.51.100.7")
+        );
+    }
 }
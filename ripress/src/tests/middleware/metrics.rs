@@ -0,0 +1,88 @@
+#[cfg(test)]
+mod test {
+    use crate::app::{test_client::TestClient, App};
+    use crate::middlewares::metrics::MetricsConfig;
+    use crate::req::HttpRequest;
+    use crate::types::RouterFns;
+
+    fn make_app(config: Option<MetricsConfig>) -> App {
+        let mut app = App::new();
+        app.use_metrics(config);
+        app.get(
+            "/users/:id",
+            |_req: HttpRequest, res| async move { res.ok() },
+        );
+        app
+    }
+
+    #[tokio::test]
+    async fn test_metrics_are_labeled_by_route_pattern() {
+        let client = TestClient::new(make_app(None));
+
+        client.get("/users/1").send().await;
+        client.get("/users/2").send().await;
+        client.get("/missing").send().await;
+
+        let res = client.get("/metrics").send().await;
+        let body = res.text();
+
+        assert_eq!(res.status(), 200);
+        assert!(res
+            .header("content-type")
+            .unwrap()
+            .starts_with("text/plain; version=0.0.4"));
+        assert!(body.contains("# TYPE http_requests_total counter"));
+        assert!(body.contains("http_requests_total{method=\"GET\",path=\"/users/:id\"} 2"));
+        assert!(body.contains("http_requests_total{method=\"GET\",path=\"<unmatched>\"} 1"));
+        assert!(!body.contains("/users/1"));
+        // The scrape itself is the only request being served.
+        assert!(body.contains("http_requests_in_flight 1\n"));
+        assert!(body
+            .contains("http_request_duration_seconds_count{method=\"GET\",path=\"/users/:id\"} 2"));
+
+        let body = client.get("/metrics").send().await.text();
+        assert!(body.contains("http_requests_total{method=\"GET\",path=\"/metrics\"} 1"));
+    }
+
+    #[tokio::test]
+    async fn test_metrics_use_configured_path_and_buckets() {
+        let client = TestClient::new(make_app(Some(MetricsConfig {
+            path: "/internal/metrics".to_string(),
+            buckets: vec![1.0, f64::INFINITY, 0.5, 1.0],
+        })));
+
+        client.get("/users/1").send().await;
+        assert_eq!(client.get("/metrics").send().await.status(), 404);
+
+        let body = client.get("/internal/metrics").send().await.text();
+        let buckets: Vec<&str> = body
+            .lines()
+            .filter(|line| line.starts_with("http_request_duration_seconds_bucket"))
+            .filter(|line| line.contains("path=\"/users/:id\""))
+            .collect();
+
+        assert_eq!(
+            buckets,
+            vec![
+                "http_request_duration_seconds_bucket{method=\"GET\",path=\"/users/:id\",le=\"0.5\"} 1",
+                "http_request_duration_seconds_bucket{method=\"GET\",path=\"/users/:id\",le=\"1\"} 1",
+                "http_request_duration_seconds_bucket{method=\"GET\",path=\"/users/:id\",le=\"+Inf\"} 1",
+            ]
+        );
+    }
+
+    #[tokio::test]
+    async fn test_metrics_record_requests_answered_by_pre_middleware() {
+        let mut app = make_app(None);
+        app.use_pre_middleware(Some("/users"), |req: HttpRequest, res, _next| async move {
+            (req, Some(res.unauthorized()))
+        });
+        let client = TestClient::new(app);
+
+        assert_eq!(client.get("/users/1").send().await.status(), 401);
+
+        let body = client.get("/metrics").send().await.text();
+        assert!(body.contains("http_requests_total{method=\"GET\",path=\"<unmatched>\"} 1"));
+        assert!(body.contains("http_requests_in_flight 1\n"));
+    }
+}
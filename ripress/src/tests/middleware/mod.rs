@@ -1,8 +1,13 @@
+pub mod auth;
 pub mod body;
 pub mod compression;
 pub mod cors;
+pub mod etag;
 pub mod exec;
 pub mod file_upload;
 pub mod logger;
+pub mod metrics;
 pub mod rate_limiter;
+pub mod request_id;
+pub mod session;
 pub mod shield;
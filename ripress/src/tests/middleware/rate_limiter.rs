@@ -1,11 +1,14 @@
 #[cfg(test)]
 mod test {
-    use std::time::Duration;
+    use std::time::{Duration, Instant};
 
     use tokio::time::sleep;
 
     use crate::{
-        middlewares::rate_limiter::{rate_limiter, RateLimiterConfig},
+        middlewares::rate_limiter::{
+            cleanup_interval, rate_limiter, ClientState, ClientStore, RateLimitStrategy,
+            RateLimiterConfig,
+        },
         next::Next,
         req::{request_headers::RequestHeaders, HttpRequest},
         res::HttpResponse,
@@ -167,4 +170,187 @@ mod test {
         assert!(resp.headers.get("X-RateLimit-Reset").is_some());
         assert!(resp.headers.get("Retry-After").is_some());
     }
+
+    fn remaining(resp: &crate::res::HttpResponse) -> Option<String> {
+        resp.headers
+            .get("X-RateLimit-Remaining")
+            .map(|v| v.to_string())
+    }
+
+    #[tokio::test]
+    async fn fixed_window_allows_burst_at_boundary() {
+        let mw = rate_limiter(Some(RateLimiterConfig {
+            max_requests: 2,
+            window_ms: Duration::from_millis(200),
+            ..Default::default()
+        }));
+
+        let req = mock_req();
+        let (_req, resp) = mw(req.clone(), mock_res(), make_next()).await;
+        assert!(resp.is_none());
+
+        sleep(Duration::from_millis(150)).await;
+        let (_req, resp) = mw(req.clone(), mock_res(), make_next()).await;
+        assert!(resp.is_none());
+
+        sleep(Duration::from_millis(70)).await;
+        for _ in 0..2 {
+            let (_req, resp) = mw(req.clone(), mock_res(), make_next()).await;
+            assert!(resp.is_none());
+        }
+    }
+
+    #[tokio::test]
+    async fn sliding_window_smooths_boundary_burst() {
+        let mw = rate_limiter(Some(RateLimiterConfig {
+            max_requests: 2,
+            window_ms: Duration::from_millis(200),
+            strategy: RateLimitStrategy::SlidingWindow,
+            ..Default::default()
+        }));
+
+        let req = mock_req();
+        let (_req, resp) = mw(req.clone(), mock_res(), make_next()).await;
+        assert!(resp.is_none());
+
+        sleep(Duration::from_millis(150)).await;
+        let (_req, resp) = mw(req.clone(), mock_res(), make_next()).await;
+        assert!(resp.is_none());
+
+        // Only the first request has left the trailing window.
+        sleep(Duration::from_millis(70)).await;
+        let (_req, resp) = mw(req.clone(), mock_res(), make_next()).await;
+        assert!(resp.is_none());
+
+        let (_req, resp) = mw(req.clone(), mock_res(), make_next()).await;
+        let resp = resp.expect("second request after the boundary should be limited");
+        assert_eq!(resp.get_status_code(), 429);
+        assert_eq!(remaining(&resp), Some("0".to_string()));
+    }
+
+    #[test]
+    fn remaining_reflects_strategy() {
+        let cfg = RateLimiterConfig {
+            max_requests: 3,
+            window_ms: Duration::from_secs(60),
+            ..Default::default()
+        };
+        let start = Instant::now();
+
+        for strategy in [
+            RateLimitStrategy::FixedWindow,
+            RateLimitStrategy::SlidingWindow,
+            RateLimitStrategy::TokenBucket,
+        ] {
+            let mut state = ClientState::new(strategy, start, cfg.max_requests);
+            let remaining: Vec<usize> = (0..3)
+                .map(|_| state.check(start, &cfg))
+                .inspect(|d| assert!(d.allowed))
+                .map(|d| d.remaining)
+                .collect();
+            assert_eq!(remaining, vec![2, 1, 0], "{:?}", strategy);
+
+            let denied = state.check(start, &cfg);
+            assert!(!denied.allowed);
+            assert_eq!(denied.remaining, 0);
+        }
+
+        // Half a window later the token bucket has refilled half its tokens,
+        // the sliding log still counts every request.
+        let later = start + Duration::from_secs(30);
+        let mut bucket = ClientState::new(RateLimitStrategy::TokenBucket, start, 3);
+        let mut sliding = ClientState::new(RateLimitStrategy::SlidingWindow, start, 3);
+        for _ in 0..3 {
+            bucket.check(start, &cfg);
+            sliding.check(start, &cfg);
+        }
+        let decision = bucket.check(later, &cfg);
+        assert!(decision.allowed);
+        assert_eq!(decision.remaining, 0);
+        assert!(!sliding.check(later, &cfg).allowed);
+        assert!(!sliding.is_expired(later, &cfg));
+        assert!(sliding.is_expired(start + Duration::from_secs(60), &cfg));
+    }
+
+    #[tokio::test]
+    async fn token_bucket_refills_over_time() {
+        let mw = rate_limiter(Some(RateLimiterConfig {
+            max_requests: 2,
+            window_ms: Duration::from_millis(200),
+            strategy: RateLimitStrategy::TokenBucket,
+            ..Default::default()
+        }));
+
+        let req = mock_req();
+        for _ in 0..2 {
+            let (_req, resp) = mw(req.clone(), mock_res(), make_next()).await;
+            assert!(resp.is_none());
+        }
+
+        let (_req, resp) = mw(req.clone(), mock_res(), make_next()).await;
+        let resp = resp.expect("empty bucket should limit");
+        assert_eq!(resp.get_status_code(), 429);
+        assert_eq!(remaining(&resp), Some("0".to_string()));
+
+        // One token refills every 100ms.
+        sleep(Duration::from_millis(120)).await;
+        let (_req, resp) = mw(req.clone(), mock_res(), make_next()).await;
+        assert!(resp.is_none());
+
+        let (_req, resp) = mw(req.clone(), mock_res(), make_next()).await;
+        assert!(resp.is_some());
+    }
+
+    #[tokio::test]
+    async fn sweep_drops_expired_clients() {
+        let cfg = RateLimiterConfig {
+            window_ms: Duration::from_secs(10),
+            strategy: RateLimitStrategy::SlidingWindow,
+            ..Default::default()
+        };
+        let store = ClientStore::new();
+        let start = Instant::now();
+
+        for i in 0..10_000 {
+            let ip = format!("10.0.{}.{}", i / 256, i % 256);
+            let mut shard = store.shard(&ip).lock().await;
+            shard
+                .entry(ip)
+                .or_insert_with(|| ClientState::new(cfg.strategy, start, cfg.max_requests))
+                .check(start, &cfg);
+        }
+        let active = "192.168.0.1".to_string();
+        let mut state = ClientState::new(cfg.strategy, start, cfg.max_requests);
+        state.check(start + Duration::from_secs(8), &cfg);
+        store
+            .shard(&active)
+            .lock()
+            .await
+            .insert(active.clone(), state);
+        assert_eq!(store.len().await, 10_001);
+
+        assert_eq!(store.sweep(start + Duration::from_secs(5), &cfg).await, 0);
+        assert_eq!(
+            store.sweep(start + Duration::from_secs(11), &cfg).await,
+            10_000
+        );
+        assert_eq!(store.len().await, 1);
+        assert!(store.shard(&active).lock().await.contains_key(&active));
+    }
+
+    #[test]
+    fn cleanup_interval_follows_window() {
+        assert_eq!(
+            cleanup_interval(Duration::from_secs(60)),
+            Duration::from_secs(60)
+        );
+        assert_eq!(
+            cleanup_interval(Duration::from_millis(100)),
+            Duration::from_secs(1)
+        );
+        assert_eq!(
+            cleanup_interval(Duration::from_secs(3600)),
+            Duration::from_secs(300)
+        );
+    }
 }
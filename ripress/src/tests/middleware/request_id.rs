@@ -0,0 +1,82 @@
+#[cfg(feature = "request-id")]
+#[cfg(test)]
+mod test {
+    use crate::context::HttpResponse;
+    use crate::middlewares::request_id::{
+        generate_request_id, is_valid_request_id, request_id, RequestIdConfig,
+    };
+    use crate::next::{Next, PENDING_HEADERS};
+    use crate::req::HttpRequest;
+    use std::cell::RefCell;
+
+    fn make_next() -> Next {
+        Next {}
+    }
+
+    async fn run(
+        config: Option<RequestIdConfig>,
+        req: HttpRequest,
+    ) -> (HttpRequest, Vec<(String, String)>) {
+        let mw = request_id(config);
+        PENDING_HEADERS
+            .scope(RefCell::new(Vec::new()), async move {
+                let (req, res) = mw(req, HttpResponse::new(), make_next()).await;
+                assert!(res.is_none());
+                let headers = PENDING_HEADERS.with(|pending| pending.borrow().clone());
+                (req, headers)
+            })
+            .await
+    }
+
+    #[tokio::test]
+    async fn test_generates_id_when_missing() {
+        let (req, headers) = run(None, HttpRequest::new()).await;
+
+        let id = req.request_id().expect("request id should be set");
+        assert_eq!(id.len(), 36);
+        assert_eq!(
+            headers,
+            vec![("x-request-id".to_string(), id)],
+            "response should echo the id"
+        );
+    }
+
+    #[tokio::test]
+    async fn test_reuses_incoming_id() {
+        let mut req = HttpRequest::new();
+        req.set_header("x-request-id", "abc-123");
+
+        let (req, headers) = run(None, req).await;
+
+        assert_eq!(req.request_id().as_deref(), Some("abc-123"));
+        assert_eq!(headers[0].1, "abc-123");
+    }
+
+    #[tokio::test]
+    async fn test_ignores_incoming_id_when_untrusted_or_invalid() {
+        let mut req = HttpRequest::new();
+        req.set_header("x-correlation-id", "abc-123");
+        let config = RequestIdConfig {
+            header: "X-Correlation-Id".to_string(),
+            trust_incoming: false,
+        };
+        let (req, headers) = run(Some(config), req).await;
+        assert_ne!(req.request_id().as_deref(), Some("abc-123"));
+        assert_eq!(headers[0].0, "x-correlation-id");
+
+        let mut req = HttpRequest::new();
+        req.set_header("x-request-id", "bad id\twith spaces");
+        let (req, _) = run(None, req).await;
+        assert_ne!(req.request_id().as_deref(), Some("bad id\twith spaces"));
+    }
+
+    #[test]
+    fn test_request_id_validation() {
+        assert!(is_valid_request_id("abc-123"));
+        assert!(is_valid_request_id(&generate_request_id()));
+        assert!(!is_valid_request_id(""));
+        assert!(!is_valid_request_id("has space"));
+        assert!(!is_valid_request_id(&"a".repeat(129)));
+        assert_ne!(generate_request_id(), generate_request_id());
+    }
+}
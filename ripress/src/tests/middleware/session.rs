@@ -0,0 +1,158 @@
+#[cfg(test)]
+#[cfg(feature = "session")]
+mod test {
+    use std::{sync::Arc, time::Duration};
+
+    use futures::future::BoxFuture;
+
+    use crate::app::{test_client::TestClient, App};
+    use crate::middlewares::session::{SessionConfig, SessionData, SessionError, SessionStore};
+    use crate::req::HttpRequest;
+    use crate::types::RouterFns;
+
+    fn make_app(config: Option<SessionConfig>) -> App {
+        let mut app = App::new();
+        app.use_session(config);
+        app.post("/login", |req: HttpRequest, res| async move {
+            req.session().regenerate();
+            req.session().set("uid", 42).unwrap();
+            res.ok()
+        });
+        app.get("/me", |req: HttpRequest, res| async move {
+            match req.session().get::<u64>("uid") {
+                Some(uid) => res.ok().text(uid.to_string()),
+                None => res.unauthorized(),
+            }
+        });
+        app.post("/logout", |req: HttpRequest, res| async move {
+            req.session().destroy();
+            res.ok()
+        });
+        app
+    }
+
+    /// The `name=value` pair of the session cookie set on a response.
+    fn session_cookie(res: &crate::app::test_client::TestResponse) -> Option<String> {
+        let set_cookie = res.header("set-cookie")?;
+        Some(set_cookie.split(';').next()?.to_string())
+    }
+
+    async fn me_status(client: &TestClient, cookie: &str) -> u16 {
+        client
+            .get("/me")
+            .header("Cookie", cookie)
+            .send()
+            .await
+            .status()
+    }
+
+    #[tokio::test]
+    async fn test_session_persists_across_requests() {
+        let client = TestClient::new(make_app(None));
+
+        assert_eq!(client.get("/me").send().await.status(), 401);
+
+        let res = client.post("/login").send().await;
+        let set_cookie = res.header("set-cookie").unwrap();
+        assert!(set_cookie.starts_with("ripress.sid="));
+        assert!(set_cookie.contains("HttpOnly"));
+        assert!(set_cookie.contains("SameSite=Lax"));
+        assert!(set_cookie.contains("Max-Age=86400"));
+        let cookie = session_cookie(&res).unwrap();
+
+        let res = client.get("/me").header("Cookie", &cookie).send().await;
+        assert_eq!(res.text(), "42");
+        // Unchanged sessions aren't saved again.
+        assert!(res.header("set-cookie").is_none());
+    }
+
+    #[tokio::test]
+    async fn test_session_rejects_tampered_cookie() {
+        let client = TestClient::new(make_app(None));
+        let cookie = session_cookie(&client.post("/login").send().await).unwrap();
+
+        let (id, signature) = cookie.rsplit_once('.').unwrap();
+        let forged = format!("{}x.{}", id, signature);
+        assert_eq!(me_status(&client, &forged).await, 401);
+        assert_eq!(me_status(&client, id).await, 401);
+    }
+
+    #[tokio::test]
+    async fn test_session_regenerate_and_destroy_invalidate_old_id() {
+        let client = TestClient::new(make_app(None));
+        let first = session_cookie(&client.post("/login").send().await).unwrap();
+
+        let res = client.post("/login").header("Cookie", &first).send().await;
+        let second = session_cookie(&res).unwrap();
+        assert_ne!(first, second);
+        assert_eq!(me_status(&client, &first).await, 401);
+
+        let res = client
+            .post("/logout")
+            .header("Cookie", &second)
+            .send()
+            .await;
+        assert!(res.header("set-cookie").unwrap().contains("Max-Age=0"));
+        assert_eq!(me_status(&client, &second).await, 401);
+    }
+
+    #[tokio::test]
+    async fn test_session_expires_after_ttl() {
+        let client = TestClient::new(make_app(Some(SessionConfig {
+            ttl: Duration::from_millis(50),
+            ..Default::default()
+        })));
+        let cookie = session_cookie(&client.post("/login").send().await).unwrap();
+
+        assert_eq!(me_status(&client, &cookie).await, 200);
+        tokio::time::sleep(Duration::from_millis(80)).await;
+        assert_eq!(me_status(&client, &cookie).await, 401);
+    }
+
+    struct FailingStore;
+
+    impl SessionStore for FailingStore {
+        fn load<'a>(
+            &'a self,
+            _id: &'a str,
+        ) -> BoxFuture<'a, Result<Option<SessionData>, SessionError>> {
+            Box::pin(async { Err("connection refused".into()) })
+        }
+
+        fn save<'a>(
+            &'a self,
+            _id: &'a str,
+            _data: &'a SessionData,
+            _ttl: Duration,
+        ) -> BoxFuture<'a, Result<(), SessionError>> {
+            Box::pin(async { Ok(()) })
+        }
+
+        fn destroy<'a>(&'a self, _id: &'a str) -> BoxFuture<'a, Result<(), SessionError>> {
+            Box::pin(async { Ok(()) })
+        }
+    }
+
+    #[tokio::test]
+    async fn test_session_store_errors_fail_the_request() {
+        let config = SessionConfig {
+            store: Arc::new(FailingStore),
+            ..Default::default()
+        };
+        let client = TestClient::new(make_app(Some(config)));
+
+        // New sessions don't touch the store until they are saved.
+        let cookie = session_cookie(&client.post("/login").send().await).unwrap();
+
+        assert_eq!(me_status(&client, &cookie).await, 500);
+    }
+
+    #[test]
+    #[should_panic(expected = "session secret must be at least 32 bytes")]
+    fn test_session_rejects_short_secret() {
+        App::new().use_session(Some(SessionConfig {
+            secret: b"too short".to_vec(),
+            ..Default::default()
+        }));
+    }
+}
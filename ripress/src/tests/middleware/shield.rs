@@ -3,21 +3,24 @@ mod tests {
     use crate::{
         middlewares::shield::config::{
             ContentSecurityPolicy, CrossDomainPolicy, CrossOriginEmbedderPolicy,
-            CrossOriginOpenerPolicy, CrossOriginResourcePolicy, DnsPrefetchControl, Frameguard,
-            HidePoweredBy, Hsts, IENoOpen, NoSniff, OriginAgentCluster, PermissionsPolicy,
-            ReferrerPolicy, XssFilter,
+            CrossOriginOpenerPolicy, CrossOriginResourcePolicy, Csp, DnsPrefetchControl,
+            Frameguard, HidePoweredBy, Hsts, IENoOpen, NoSniff, OriginAgentCluster,
+            PermissionsPolicy, ReferrerPolicy, XssFilter,
         },
         middlewares::shield::{
-            set_content_security_policy, set_cross_domain_policy, set_cross_origin_embedder_policy,
-            set_cross_origin_opener_policy, set_cross_origin_resource_policy,
-            set_dns_prefetch_control, set_frameguard, set_hide_powered_by, set_hsts,
-            set_ie_no_open, set_no_sniff, set_origin_agent_cluster, set_permissions_policy,
-            set_referrer_policy, set_xss_filter,
+            generate_nonce, set_content_security_policy, set_cross_domain_policy,
+            set_cross_origin_embedder_policy, set_cross_origin_opener_policy,
+            set_cross_origin_resource_policy, set_dns_prefetch_control, set_frameguard,
+            set_hide_powered_by, set_hsts, set_ie_no_open, set_no_sniff, set_origin_agent_cluster,
+            set_permissions_policy, set_referrer_policy, set_xss_filter, shield, uses_nonce,
+            ShieldConfig,
         },
+        next::{Next, PENDING_HEADERS},
+        req::HttpRequest,
         res::HttpResponse,
     };
 
-    use std::collections::HashMap;
+    use std::{cell::RefCell, collections::HashMap};
 
     #[test]
     fn test_set_content_security_policy_enabled() {
@@ -30,7 +33,7 @@ mod tests {
             directives: directives.clone(),
             report_only: false,
         };
-        set_content_security_policy(&mut res, &csp);
+        set_content_security_policy(&mut res, &csp, None);
         let val = res.headers.get("content-security-policy").unwrap();
         assert_eq!(val, "default-src 'self'; script-src 'self'");
     }
@@ -45,7 +48,7 @@ mod tests {
             directives,
             report_only: true,
         };
-        set_content_security_policy(&mut res, &csp);
+        set_content_security_policy(&mut res, &csp, None);
         assert!(res
             .headers
             .get("content-security-policy-report-only")
@@ -60,10 +63,102 @@ mod tests {
             directives: HashMap::new(),
             report_only: false,
         };
-        set_content_security_policy(&mut res, &csp);
+        set_content_security_policy(&mut res, &csp, None);
         assert!(res.headers.get("content-security-policy").is_none());
     }
 
+    #[test]
+    fn test_csp_builder_serializes_directives() {
+        let mut res = HttpResponse::new();
+        let csp = Csp::new()
+            .default_src(["'self'"])
+            .script_src(["'self'", "https://cdn.example.com"])
+            .object_src(["'none'"])
+            .report_uri("/csp-reports")
+            .upgrade_insecure_requests()
+            .build();
+        set_content_security_policy(&mut res, &csp, None);
+
+        assert_eq!(
+            res.headers.get("content-security-policy"),
+            Some(
+                "default-src 'self'; object-src 'none'; report-uri /csp-reports; \
+                 script-src 'self' https://cdn.example.com; upgrade-insecure-requests"
+            )
+        );
+    }
+
+    #[test]
+    fn test_csp_builder_replaces_directive_and_report_only() {
+        let mut res = HttpResponse::new();
+        let csp = Csp::new()
+            .script_src(["'unsafe-inline'"])
+            .directive("Script-Src", ["'self'"])
+            .report_only(true)
+            .build();
+        set_content_security_policy(&mut res, &csp, None);
+
+        assert!(res.headers.get("content-security-policy").is_none());
+        assert_eq!(
+            res.headers.get("content-security-policy-report-only"),
+            Some("script-src 'self'")
+        );
+    }
+
+    #[test]
+    fn test_csp_builder_nonce() {
+        let csp = Csp::new().default_src(["'self'"]).nonce().build();
+        assert!(uses_nonce(&csp));
+        assert_eq!(
+            csp.directives.get("script-src").map(String::as_str),
+            Some("'self' 'nonce-{NONCE}'")
+        );
+
+        let mut res = HttpResponse::new();
+        set_content_security_policy(&mut res, &csp, Some("abc123"));
+        assert_eq!(
+            res.headers.get("content-security-policy"),
+            Some("default-src 'self'; script-src 'self' 'nonce-abc123'")
+        );
+    }
+
+    #[test]
+    fn test_generate_nonce_is_unique() {
+        let a = generate_nonce();
+        let b = generate_nonce();
+        assert_eq!(a.len(), 32);
+        assert!(a.chars().all(|c| c.is_ascii_hexdigit()));
+        assert_ne!(a, b);
+    }
+
+    #[tokio::test]
+    async fn test_shield_exposes_nonce_to_request() {
+        let mw = shield(Some(ShieldConfig {
+            content_security_policy: Csp::new().default_src(["'self'"]).nonce().build(),
+            ..Default::default()
+        }));
+
+        let (req, headers) = PENDING_HEADERS
+            .scope(RefCell::new(Vec::new()), async {
+                let (req, res) = mw(HttpRequest::new(), HttpResponse::new(), Next {}).await;
+                assert!(res.is_none());
+                let headers = PENDING_HEADERS.with(|pending| pending.borrow().clone());
+                (req, headers)
+            })
+            .await;
+
+        let nonce = req
+            .csp_nonce()
+            .expect("nonce should be stored on the request");
+        let csp = headers
+            .iter()
+            .find(|(name, _)| name.eq_ignore_ascii_case("content-security-policy"))
+            .map(|(_, value)| value.clone())
+            .unwrap();
+        assert_eq!(nonce.len(), 32);
+        assert!(csp.contains(&format!("'nonce-{}'", nonce)));
+    }
+
     #[test]
     fn test_set_hsts_enabled() {
         let mut res = HttpResponse::new();
@@ -382,6 +477,103 @@ mod tests {
         );
     }
 
+    #[test]
+    fn test_disabled_cross_origin_policies_omit_headers() {
+        let mut res = HttpResponse::new();
+        set_cross_origin_opener_policy(&mut res, &CrossOriginOpenerPolicy::Disabled);
+        set_cross_origin_resource_policy(&mut res, &CrossOriginResourcePolicy::Disabled);
+        set_cross_origin_embedder_policy(&mut res, &CrossOriginEmbedderPolicy::Disabled);
+
+        assert!(res.headers.get("cross-origin-opener-policy").is_none());
+        assert!(res.headers.get("cross-origin-resource-policy").is_none());
+        assert!(res.headers.get("cross-origin-embedder-policy").is_none());
+
+        set_cross_origin_embedder_policy(&mut res, &CrossOriginEmbedderPolicy::Credentialless);
+        assert_eq!(
+            res.headers.get("cross-origin-embedder-policy"),
+            Some("credentialless")
+        );
+    }
+
+    #[test]
+    fn test_set_permissions_policy_sorted_and_empty() {
+        let mut res = HttpResponse::new();
+        let mut features = HashMap::new();
+        features.insert("microphone".to_string(), vec![]);
+        features.insert("camera".to_string(), vec!["self".to_string()]);
+        set_permissions_policy(
+            &mut res,
+            &PermissionsPolicy {
+                enabled: true,
+                features,
+            },
+        );
+        assert_eq!(
+            res.headers.get("permissions-policy"),
+            Some("camera=(self), microphone=()")
+        );
+
+        let mut res = HttpResponse::new();
+        set_permissions_policy(
+            &mut res,
+            &PermissionsPolicy {
+                enabled: true,
+                features: HashMap::new(),
+            },
+        );
+        assert!(res.headers.get("permissions-policy").is_none());
+    }
+
+    #[tokio::test]
+    async fn test_shield_cross_origin_isolation_headers() {
+        let mw = shield(Some(ShieldConfig {
+            cross_origin_opener_policy: CrossOriginOpenerPolicy::SameOrigin,
+            cross_origin_embedder_policy: CrossOriginEmbedderPolicy::RequireCorp,
+            ..Default::default()
+        }));
+
+        let headers = PENDING_HEADERS
+            .scope(RefCell::new(Vec::new()), async {
+                mw(HttpRequest::new(), HttpResponse::new(), Next {}).await;
+                PENDING_HEADERS.with(|pending| pending.borrow().clone())
+            })
+            .await;
+        let header = |name: &str| {
+            headers
+                .iter()
+                .find(|(k, _)| k.eq_ignore_ascii_case(name))
+                .map(|(_, v)| v.clone())
+        };
+
+        assert_eq!(
+            header("cross-origin-opener-policy").as_deref(),
+            Some("same-origin")
+        );
+        assert_eq!(
+            header("cross-origin-embedder-policy").as_deref(),
+            Some("require-corp")
+        );
+    }
+
+    #[tokio::test]
+    async fn test_shield_default_omits_coep() {
+        let mw = shield(None);
+
+        let headers = PENDING_HEADERS
+            .scope(RefCell::new(Vec::new()), async {
+                mw(HttpRequest::new(), HttpResponse::new(), Next {}).await;
+                PENDING_HEADERS.with(|pending| pending.borrow().clone())
+            })
+            .await;
+
+        assert!(!headers
+            .iter()
+            .any(|(k, _)| k.eq_ignore_ascii_case("cross-origin-embedder-policy")));
+        assert!(headers
+            .iter()
+            .any(|(k, _)| k.eq_ignore_ascii_case("cross-origin-opener-policy")));
+    }
+
     #[test]
     fn test_set_origin_agent_cluster_enabled() {
         let mut res = HttpResponse::new();
@@ -1,13 +1,25 @@
 #![cfg(test)]
 
 mod app_test;
+mod client_test;
+mod connection_test;
 mod errors_test;
 mod extractors_test;
+mod health_test;
 mod helper_test;
 mod middleware;
+#[cfg(feature = "openapi")]
+mod openapi_test;
+mod proxy_test;
 mod request;
 mod response;
 mod router_test;
+mod state_test;
+mod test_client_test;
+#[cfg(feature = "tls")]
+mod tls_test;
+#[cfg(unix)]
+mod unix_test;
 #[cfg(feature = "validation")]
 mod validation_test;
 mod next_test;
\ No newline at end of file
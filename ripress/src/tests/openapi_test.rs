@@ -0,0 +1,168 @@
+#[cfg(test)]
+mod test {
+    use schemars::JsonSchema;
+    use serde::{Deserialize, Serialize};
+    use serde_json::json;
+
+    use crate::{
+        app::{openapi::Operation, App},
+        req::{
+            body::json_data::JsonBody, query_params::QueryParam, route_params::Params, HttpRequest,
+        },
+        res::HttpResponse,
+        router::Router,
+        types::{HttpMethods, RouterFns},
+    };
+    use ripress_derive::{FromJson, FromParams, FromQueryParam};
+
+    #[derive(Deserialize, JsonSchema, FromParams)]
+    struct UserId {
+        id: u32,
+    }
+
+    #[derive(Deserialize, JsonSchema, FromQueryParam)]
+    struct Paging {
+        page: u32,
+    }
+
+    #[derive(Deserialize, JsonSchema)]
+    struct Search {
+        q: Option<String>,
+    }
+
+    #[derive(Deserialize, Serialize, JsonSchema, FromJson)]
+    struct NewUser {
+        name: String,
+        email: Option<String>,
+    }
+
+    async fn ok(_req: HttpRequest, res: HttpResponse) -> HttpResponse {
+        res.ok()
+    }
+
+    #[test]
+    fn test_undocumented_routes_are_listed_with_path_params() {
+        let mut api = Router::new("/api");
+        api.get(r"/posts/:id(\d+)", ok);
+
+        let mut app = App::new();
+        app.get("/", ok);
+        app.delete("/files/*path", ok);
+        app.connect("/", ok);
+        app.router(api);
+        app.static_files("/assets", "public").unwrap();
+
+        let spec = app.openapi();
+        assert_eq!(spec["openapi"], "3.0.3");
+        assert_eq!(
+            spec["paths"]["/"],
+            json!({ "get": { "responses": { "default": { "description": "Response" } } } })
+        );
+        assert_eq!(
+            spec["paths"]["/files/{path}"]["delete"]["parameters"],
+            json!([{ "name": "path", "in": "path", "required": true, "schema": { "type": "string" } }])
+        );
+        assert_eq!(
+            spec["paths"]["/api/posts/{id}"]["get"]["parameters"][0]["schema"],
+            json!({ "type": "string", "pattern": r"^(?:\d+)$" })
+        );
+        assert_eq!(spec["paths"].as_object().unwrap().len(), 3);
+        assert!(spec.get("components").is_none());
+    }
+
+    #[test]
+    fn test_documented_route_uses_extractor_schemas() {
+        let mut app = App::new();
+        app.route(HttpMethods::POST, "/users/:id")
+            .openapi(
+                Operation::new()
+                    .summary("Create a user")
+                    .tag("users")
+                    .extractor::<(Params<UserId>, QueryParam<Paging>, JsonBody<NewUser>)>()
+                    .response::<NewUser>(201, "The new user")
+                    .empty_response(409, "Name taken"),
+            )
+            .handler(
+                |(id, paging, _user): (Params<UserId>, QueryParam<Paging>, JsonBody<NewUser>),
+                 res| async move {
+                    res.created().text(format!("{} {}", id.id, paging.page))
+                },
+            );
+
+        let spec = app.openapi();
+        let operation = &spec["paths"]["/users/{id}"]["post"];
+        assert_eq!(operation["summary"], "Create a user");
+        assert_eq!(operation["tags"], json!(["users"]));
+
+        let parameters = operation["parameters"].as_array().unwrap();
+        assert_eq!(parameters.len(), 2);
+        assert_eq!(parameters[0]["name"], "id");
+        assert_eq!(parameters[0]["in"], "path");
+        assert_eq!(parameters[0]["schema"]["type"], "integer");
+        assert_eq!(
+            (&parameters[1]["name"], &parameters[1]["required"]),
+            (&json!("page"), &json!(true))
+        );
+
+        let body_schema = json!({ "$ref": "#/components/schemas/NewUser" });
+        assert_eq!(
+            operation["requestBody"]["content"]["application/json"]["schema"],
+            body_schema
+        );
+        assert_eq!(
+            operation["responses"]["201"]["content"]["application/json"]["schema"],
+            body_schema
+        );
+        assert_eq!(
+            operation["responses"]["409"],
+            json!({ "description": "Name taken" })
+        );
+        assert_eq!(
+            spec["components"]["schemas"]["NewUser"]["properties"]["email"]["nullable"],
+            true
+        );
+    }
+
+    #[test]
+    fn test_optional_query_fields_are_not_required() {
+        let mut app = App::new();
+        app.route(HttpMethods::GET, "/search")
+            .openapi(Operation::new().query::<Search>())
+            .handler(|req: HttpRequest, res| async move {
+                let search: Search = serde_urlencoded::from_str(req.query.raw()).unwrap();
+                res.ok().text(search.q.unwrap_or_default())
+            });
+
+        let spec = app.openapi();
+        assert_eq!(
+            spec["paths"]["/search"]["get"]["parameters"][0],
+            json!({
+                "name": "q",
+                "in": "query",
+                "required": false,
+                "schema": { "type": "string", "nullable": true },
+            })
+        );
+    }
+
+    #[test]
+    fn test_docs_follow_mounted_routers() {
+        let mut users = Router::new("/users");
+        users
+            .route(HttpMethods::GET, "/:id")
+            .openapi(Operation::new().summary("Get a user"))
+            .handler(ok);
+
+        let mut v1 = App::new();
+        v1.router(users);
+
+        let mut app = App::new();
+        app.mount("/v1", v1);
+
+        let spec = app.openapi();
+        assert_eq!(
+            spec["paths"]["/v1/users/{id}"]["get"]["summary"],
+            "Get a user"
+        );
+    }
+}
@@ -0,0 +1,212 @@
+#[cfg(test)]
+mod test {
+    use std::{sync::Arc, time::Duration};
+
+    use bytes::Bytes;
+    use serde_json::{json, Value};
+    use tokio::net::TcpListener;
+
+    use crate::{
+        app::{test_client::TestClient, App},
+        helpers::proxy,
+        req::HttpRequest,
+        res::HttpResponse,
+        types::{HttpMethods, RouterFns},
+    };
+
+    /// Serves `app` on an ephemeral port until the test ends.
+    async fn serve(app: App) -> u16 {
+        let router_service = app.build_router_service();
+        let config = app.connection_config();
+
+        let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let port = listener.local_addr().unwrap().port();
+        tokio::spawn(async move {
+            while let Ok((stream, _)) = listener.accept().await {
+                let connection =
+                    App::handle_connection(stream, Arc::clone(&router_service), config.clone());
+                tokio::spawn(connection);
+            }
+        });
+        port
+    }
+
+    fn upstream() -> App {
+        let mut app = App::new();
+        app.get(
+            "/v1/api/echo",
+            |req: HttpRequest, res: HttpResponse| async move {
+                let header = |name: &str| req.headers.get(name).map(str::to_string);
+                res.ok()
+                    .set_header("keep-alive", "timeout=5")
+                    .set_header("x-upstream", "yes")
+                    .json(json!({
+                        "path": req.path,
+                        "page": req.query.get("page"),
+                        "host": header("host"),
+                        "forwarded_for": header("x-forwarded-for"),
+                        "forwarded_host": header("x-forwarded-host"),
+                        "forwarded_proto": header("x-forwarded-proto"),
+                        "private": header("x-private"),
+                    }))
+            },
+        );
+        app.post(
+            "/v1/api/echo",
+            |req: HttpRequest, res: HttpResponse| async move {
+                res.status(201).text(req.text().unwrap_or_default())
+            },
+        );
+        app
+    }
+
+    fn gateway(upstream: String) -> App {
+        let mut app = App::new();
+        let get_upstream = upstream.clone();
+        app.get("/api/*path", move |req: HttpRequest, _res: HttpResponse| {
+            let upstream = get_upstream.clone();
+            async move { proxy::forward(&req, &upstream).await }
+        });
+        app.post("/api/*path", move |req: HttpRequest, _res: HttpResponse| {
+            let upstream = upstream.clone();
+            async move { proxy::forward(&req, &upstream).await }
+        });
+        app
+    }
+
+    #[tokio::test]
+    async fn test_forward_proxies_requests_to_upstream() {
+        let upstream_port = serve(upstream()).await;
+        let port = serve(gateway(format!("http://127.0.0.1:{}/v1/", upstream_port))).await;
+        let client = reqwest::Client::new();
+
+        let res = client
+            .get(format!("http://localhost:{}/api/echo?page=2", port))
+            .header("x-forwarded-for", "203.0.113.7")
+            .header("connection", "x-private")
+            .header("x-private", "secret")
+            .send()
+            .await
+            .unwrap();
+        assert_eq!(res.status(), 200);
+        assert_eq!(res.headers()["x-upstream"], "yes");
+        assert_eq!(res.headers()["content-type"], "application/json");
+        assert!(res.headers().get("keep-alive").is_none());
+        assert!(res.headers().get("transfer-encoding").is_none());
+        let body: Value = res.json().await.unwrap();
+        assert_eq!(
+            body,
+            json!({
+                "path": "/v1/api/echo",
+                "page": "2",
+                "host": format!("127.0.0.1:{}", upstream_port),
+                "forwarded_for": "203.0.113.7, 127.0.0.1",
+                "forwarded_host": format!("localhost:{}", port),
+                "forwarded_proto": "http",
+                "private": null,
+            })
+        );
+
+        let res = client
+            .post(format!("http://localhost:{}/api/echo", port))
+            .header("content-type", "text/plain")
+            .body("hello upstream")
+            .send()
+            .await
+            .unwrap();
+        assert_eq!(res.status(), 201);
+        assert_eq!(res.text().await.unwrap(), "hello upstream");
+    }
+
+    #[tokio::test]
+    async fn test_forward_streams_request_body_of_stream_body_route() {
+        let upstream_port = serve(upstream()).await;
+        let upstream = format!("http://127.0.0.1:{}/v1/", upstream_port);
+        let mut app = App::new();
+        app.route(HttpMethods::POST, "/api/*path")
+            .stream_body()
+            .handler(move |req: HttpRequest, _res: HttpResponse| {
+                let upstream = upstream.clone();
+                async move { proxy::forward(&req, &upstream).await }
+            });
+        let client = TestClient::new(app);
+
+        let res = tokio::time::timeout(
+            Duration::from_secs(5),
+            client
+                .post("/api/echo")
+                .header("content-type", "text/plain")
+                .body("hello upstream")
+                .send(),
+        )
+        .await
+        .expect("the upstream should receive the whole body");
+        assert_eq!(res.status(), 201);
+        assert_eq!(res.text(), "hello upstream");
+    }
+
+    #[tokio::test]
+    async fn test_forward_streams_upstream_body() {
+        let release = Arc::new(tokio::sync::Notify::new());
+        let mut upstream = App::new();
+        let upstream_release = Arc::clone(&release);
+        upstream.get(
+            "/api/events",
+            move |_req: HttpRequest, res: HttpResponse| {
+                let release = Arc::clone(&upstream_release);
+                async move {
+                    // The second chunk is only produced once the client has the first.
+                    let chunks = futures::stream::unfold(0, move |n| {
+                        let release = Arc::clone(&release);
+                        async move {
+                            match n {
+                                0 => Some((Ok::<_, std::io::Error>(Bytes::from("first")), 1)),
+                                1 => {
+                                    release.notified().await;
+                                    Some((Ok(Bytes::from("second")), 2))
+                                }
+                                _ => None,
+                            }
+                        }
+                    });
+                    res.ok().write(chunks)
+                }
+            },
+        );
+        let upstream_port = serve(upstream).await;
+        let port = serve(gateway(format!("http://127.0.0.1:{}", upstream_port))).await;
+
+        let mut res = reqwest::get(format!("http://127.0.0.1:{}/api/events", port))
+            .await
+            .unwrap();
+        assert_eq!(res.status(), 200);
+        let first = tokio::time::timeout(Duration::from_secs(5), res.chunk())
+            .await
+            .expect("the first chunk should arrive before the upstream finishes")
+            .unwrap();
+        assert_eq!(first.as_deref(), Some(&b"first"[..]));
+
+        release.notify_one();
+        assert_eq!(res.text().await.unwrap(), "second");
+    }
+
+    #[tokio::test]
+    async fn test_forward_reports_unreachable_upstream() {
+        let closed = {
+            let listener = std::net::TcpListener::bind("127.0.0.1:0").unwrap();
+            listener.local_addr().unwrap().port()
+        };
+        let port = serve(gateway(format!("http://127.0.0.1:{}", closed))).await;
+
+        let res = reqwest::get(format!("http://127.0.0.1:{}/api/echo", port))
+            .await
+            .unwrap();
+        assert_eq!(res.status(), 502);
+
+        let port = serve(gateway(String::from("ftp://example.com"))).await;
+        let res = reqwest::get(format!("http://127.0.0.1:{}/api/echo", port))
+            .await
+            .unwrap();
+        assert_eq!(res.status(), 500);
+    }
+}
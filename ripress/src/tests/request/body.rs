@@ -35,6 +35,26 @@ mod tests {
         assert!(result.is_err());
     }
 
+    #[test]
+    fn test_from_bytes_with_charset() {
+        let latin1 = vec![0x63, 0x61, 0x66, 0xE9, 0x20, 0xA3];
+        let text = TextData::from_bytes_with_charset(latin1.clone(), Some("ISO-8859-1")).unwrap();
+        assert_eq!(text.as_str().unwrap(), "café £");
+        assert_eq!(text.charset(), Some("utf-8"));
+
+        let text = TextData::from_bytes_with_charset("café".into(), Some("utf-8")).unwrap();
+        assert_eq!(text.as_str().unwrap(), "café");
+
+        // Unknown charsets fall back to UTF-8.
+        let text = TextData::from_bytes_with_charset("café".into(), Some("x-bogus")).unwrap();
+        assert_eq!(text.as_str().unwrap(), "café");
+        assert!(TextData::from_bytes_with_charset(latin1, Some("x-bogus")).is_err());
+
+        let err = TextData::from_bytes_with_charset(vec![0x82, 0xA0, 0x82], Some("shift_jis"))
+            .unwrap_err();
+        assert_eq!(err.kind(), &RipressErrorKind::ParseError);
+    }
+
     #[test]
     fn test_size_limit() {
         let large_text = "x".repeat(1000);
@@ -810,4 +830,99 @@ mod tests {
         assert_eq!(form.get("a").unwrap(), "1_updated");
         assert_eq!(form.get("b").unwrap(), "2_updated");
     }
+
+    #[tokio::test]
+    async fn test_text_body_decoded_with_declared_charset() {
+        use crate::{app::test_client::TestClient, app::App, req::HttpRequest, types::RouterFns};
+
+        let mut app = App::new();
+        app.post("/text", |req: HttpRequest, res| async move {
+            match req.text() {
+                Ok(text) => res.ok().text(text.to_string()),
+                Err(error) => res.bad_request().text(error),
+            }
+        });
+        let client = TestClient::new(app);
+
+        let res = client
+            .post("/text")
+            .header("content-type", "text/html; charset=iso-8859-1")
+            .body(vec![0x3C, 0x70, 0x3E, 0x63, 0x61, 0x66, 0xE9])
+            .send()
+            .await;
+        assert_eq!(res.status(), 200);
+        assert_eq!(res.text(), "<p>café");
+
+        let res = client
+            .post("/text")
+            .header("content-type", "text/plain; charset=unknown-charset")
+            .body("plain")
+            .send()
+            .await;
+        assert_eq!(res.status(), 200);
+        assert_eq!(res.text(), "plain");
+    }
+
+    #[tokio::test]
+    async fn test_body_is_parsed_on_first_read() {
+        let mut hyper_req = hyper::Request::builder()
+            .method("POST")
+            .uri("/items")
+            .header("content-type", "application/json")
+            .body(http_body_util::Full::from(Bytes::from_static(
+                br#"{"name":"widget"}"#,
+            )))
+            .unwrap();
+        let req = crate::req::HttpRequest::from_hyper_request(&mut hyper_req)
+            .await
+            .unwrap();
+
+        assert_eq!(format!("{:?}", req.body), "<unparsed 17 bytes>");
+        assert!(req.is(RequestBodyType::JSON));
+        assert!(!req.is(RequestBodyType::FORM));
+        assert_eq!(req.body.len(), 17);
+        assert_eq!(format!("{:?}", req.body), "<unparsed 17 bytes>");
+
+        match &*req.body {
+            RequestBody::JSON(value) => assert_eq!(value, &json!({ "name": "widget" })),
+            other => panic!("expected a JSON body, got {:?}", other),
+        }
+        assert!(format!("{:?}", req.body).starts_with("JSON("));
+    }
+
+    #[tokio::test]
+    async fn test_json_reads_straight_from_the_body_bytes() {
+        let mut hyper_req = hyper::Request::builder()
+            .method("POST")
+            .uri("/items")
+            .header("content-type", "application/json")
+            .body(http_body_util::Full::from(Bytes::from_static(
+                br#"{"name":"widget"}"#,
+            )))
+            .unwrap();
+        let req = crate::req::HttpRequest::from_hyper_request(&mut hyper_req)
+            .await
+            .unwrap();
+
+        for _ in 0..2 {
+            let value = req.json::<serde_json::Value>().unwrap();
+            assert_eq!(value, json!({ "name": "widget" }));
+        }
+        assert_eq!(format!("{:?}", req.body), "<unparsed 17 bytes>");
+
+        let mut hyper_req = hyper::Request::builder()
+            .method("POST")
+            .uri("/items")
+            .header("content-type", "application/json")
+            .body(http_body_util::Full::from(Bytes::from_static(b"{not json")))
+            .unwrap();
+        let req = crate::req::HttpRequest::from_hyper_request(&mut hyper_req)
+            .await
+            .unwrap();
+
+        for _ in 0..2 {
+            let err = req.json::<serde_json::Value>().unwrap_err();
+            assert!(err.starts_with("Failed to deserialize JSON"), "{}", err);
+        }
+    }
 }
@@ -200,4 +200,89 @@ mod form_data_tests {
         assert_eq!(form.get("field2"), Some("updated"));
         assert_eq!(form.get("field3"), None);
     }
+
+    #[tokio::test]
+    async fn test_multipart_with_too_many_parts_is_rejected() {
+        use crate::{
+            app::{settings::MultipartLimits, test_client::TestClient, App},
+            req::HttpRequest,
+            types::RouterFns,
+        };
+
+        let mut app = App::new();
+        app.multipart_limits(MultipartLimits {
+            max_fields: 10,
+            ..Default::default()
+        });
+        app.post("/form", |req: HttpRequest, res| async move {
+            let count = req.form_data().map(|form| form.len()).unwrap_or(0);
+            res.ok().text(count.to_string())
+        });
+        let client = TestClient::new(app);
+
+        let body = |parts: usize| {
+            let mut body = String::new();
+            for i in 0..parts {
+                body.push_str(&format!(
+                    "--b\r\nContent-Disposition: form-data; name=\"f{}\"\r\n\r\nv\r\n",
+                    i
+                ));
+            }
+            body.push_str("--b--\r\n");
+            body
+        };
+
+        let res = client
+            .post("/form")
+            .header("content-type", "multipart/form-data; boundary=b")
+            .body(body(10))
+            .send()
+            .await;
+        assert_eq!(res.status(), 200);
+        assert_eq!(res.text(), "10");
+
+        let res = client
+            .post("/form")
+            .header("content-type", "multipart/form-data; boundary=b")
+            .body(body(100_000))
+            .send()
+            .await;
+        assert_eq!(res.status(), 413);
+        assert_eq!(
+            res.text(),
+            "Multipart body exceeded the configured limit of 10 fields"
+        );
+    }
+
+    #[tokio::test]
+    async fn test_invalid_urlencoded_body_reports_error() {
+        use crate::{app::test_client::TestClient, app::App, req::HttpRequest, types::RouterFns};
+
+        let mut app = App::new();
+        app.post("/form", |req: HttpRequest, res| async move {
+            match req.form_data() {
+                Ok(form) => res.ok().text(form.len().to_string()),
+                Err(error) => res.bad_request().text(error),
+            }
+        });
+        let client = TestClient::new(app);
+
+        let res = client
+            .post("/form")
+            .header("content-type", "application/x-www-form-urlencoded")
+            .body("name=%FF")
+            .send()
+            .await;
+        assert_eq!(res.status(), 400);
+        assert!(res.text().starts_with("Invalid form body"));
+
+        let res = client
+            .post("/form")
+            .header("content-type", "application/x-www-form-urlencoded")
+            .body("name=ok")
+            .send()
+            .await;
+        assert_eq!(res.status(), 200);
+        assert_eq!(res.text(), "1");
+    }
 }
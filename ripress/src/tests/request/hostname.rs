@@ -0,0 +1,78 @@
+#[cfg(test)]
+mod tests {
+    use crate::{
+        app::{test_client::TestClient, App},
+        req::{origin_url::Url, HttpRequest},
+        types::RouterFns,
+    };
+
+    fn request_with_host(host: &str) -> HttpRequest {
+        let mut req = HttpRequest::new();
+        req.set_header("host", host);
+        req
+    }
+
+    #[test]
+    fn test_hostname_strips_port() {
+        assert_eq!(request_with_host("example.com").hostname(), "example.com");
+        assert_eq!(
+            request_with_host("example.com:8080").hostname(),
+            "example.com"
+        );
+        assert_eq!(request_with_host("127.0.0.1:3000").hostname(), "127.0.0.1");
+    }
+
+    #[test]
+    fn test_hostname_keeps_ipv6_brackets() {
+        assert_eq!(request_with_host("[::1]:8080").hostname(), "[::1]");
+        assert_eq!(
+            request_with_host("[2001:db8::1]").hostname(),
+            "[2001:db8::1]"
+        );
+        assert!(request_with_host("[::1]:8080").subdomains().is_empty());
+    }
+
+    #[test]
+    fn test_hostname_prefers_forwarded_host() {
+        let mut req = request_with_host("internal:3000");
+        req.set_header("x-forwarded-host", "shop.example.com:443, proxy.local");
+        assert_eq!(req.hostname(), "shop.example.com");
+    }
+
+    #[test]
+    fn test_hostname_falls_back_to_url() {
+        let mut req = HttpRequest::new();
+        assert_eq!(req.hostname(), "");
+
+        req.set_origin_url(Url::new("https://user@example.com:8443/path?q=1"));
+        assert_eq!(req.hostname(), "example.com");
+    }
+
+    #[test]
+    fn test_subdomains() {
+        let req = request_with_host("api.v2.example.com");
+        assert_eq!(req.hostname(), "api.v2.example.com");
+        assert_eq!(req.subdomains(), vec!["v2", "api"]);
+
+        assert!(request_with_host("example.com").subdomains().is_empty());
+        assert!(request_with_host("localhost").subdomains().is_empty());
+        assert!(request_with_host("10.0.0.1").subdomains().is_empty());
+    }
+
+    #[tokio::test]
+    async fn test_subdomain_offset_is_configurable() {
+        let mut app = App::new();
+        app.subdomain_offset(3);
+        app.get("/", |req: HttpRequest, res| async move {
+            res.ok().text(req.subdomains().join(","))
+        });
+        let client = TestClient::new(app);
+
+        let res = client
+            .get("/")
+            .header("Host", "eu.tenant.example.co.uk")
+            .send()
+            .await;
+        assert_eq!(res.text(), "tenant,eu");
+    }
+}
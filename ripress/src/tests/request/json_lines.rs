@@ -0,0 +1,93 @@
+#[cfg(test)]
+mod tests {
+    use bytes::Bytes;
+    use http_body_util::Full;
+    use hyper::Request;
+    use serde::Deserialize;
+
+    use crate::req::{body::JsonLineError, HttpRequest};
+
+    #[derive(Debug, Deserialize, PartialEq)]
+    struct Record {
+        id: u32,
+    }
+
+    async fn convert(content_type: &str, body: &'static str) -> HttpRequest {
+        let mut req = Request::builder()
+            .method("POST")
+            .uri("/ingest")
+            .header("content-type", content_type)
+            .body(Full::from(Bytes::from_static(body.as_bytes())))
+            .unwrap();
+        HttpRequest::from_hyper_request(&mut req).await.unwrap()
+    }
+
+    #[tokio::test]
+    async fn test_json_lines_reports_malformed_line_numbers() {
+        let req = convert(
+            "application/x-ndjson",
+            "{\"id\":1}\n\n{\"id\":\n{\"id\":3}\r\n{\"name\":\"x\"}",
+        )
+        .await;
+
+        let results: Vec<Result<Record, JsonLineError>> = req.json_lines().collect();
+
+        assert_eq!(results.len(), 4);
+        assert_eq!(results[0], Ok(Record { id: 1 }));
+        assert_eq!(results[1].as_ref().unwrap_err().line, 3);
+        assert_eq!(results[2], Ok(Record { id: 3 }));
+        let err = results[3].as_ref().unwrap_err();
+        assert_eq!(err.line, 5);
+        assert_eq!(err.to_string(), "line 5: missing field `id`");
+    }
+
+    #[tokio::test]
+    async fn test_json_lines_is_lazy() {
+        let req = convert("application/x-ndjson", "{\"id\":1}\n{\"id\":2}\n").await;
+        let mut lines = req.json_lines::<Record>();
+
+        assert_eq!(lines.next(), Some(Ok(Record { id: 1 })));
+        assert_eq!(lines.next(), Some(Ok(Record { id: 2 })));
+        assert_eq!(lines.next(), None);
+    }
+
+    #[tokio::test]
+    async fn test_json_items_streams_array_elements() {
+        let req = convert("application/json", " [ {\"id\":1},\n {\"id\":2} ] ").await;
+
+        let ids: Vec<u32> = req.json_items::<Record>().map(|r| r.unwrap().id).collect();
+
+        assert_eq!(ids, vec![1, 2]);
+        assert_eq!(
+            convert("application/json", "[]")
+                .await
+                .json_items::<Record>()
+                .count(),
+            0
+        );
+    }
+
+    #[tokio::test]
+    async fn test_json_items_stops_at_malformed_element() {
+        let req = convert(
+            "application/json",
+            "[{\"id\":1},\n{\"id\":\"two\"},\n{\"id\":3}]",
+        )
+        .await;
+
+        let results: Vec<Result<Record, JsonLineError>> = req.json_items().collect();
+
+        assert_eq!(results.len(), 2);
+        assert_eq!(results[0], Ok(Record { id: 1 }));
+        assert_eq!(results[1].as_ref().unwrap_err().line, 2);
+
+        let req = convert("application/json", "{\"id\":1}").await;
+        let err = req.json_items::<Record>().next().unwrap().unwrap_err();
+        assert_eq!(err.to_string(), "line 1: expected a JSON array");
+
+        let req = convert("application/json", "[1 2]").await;
+        let results: Vec<Result<u32, JsonLineError>> = req.json_items().collect();
+        assert_eq!(results[0], Ok(1));
+        assert!(results[1].is_err());
+    }
+}
@@ -10,8 +10,14 @@ mod body;
 mod data;
 mod form_data_test;
 mod headers;
+mod hostname;
+mod json_lines;
+mod msgpack;
+mod protocol;
 mod query_param;
+mod raw_body;
 mod route_params;
+mod xml;
 
 #[cfg(test)]
 impl HttpRequest {
@@ -23,23 +29,16 @@ impl HttpRequest {
         self.headers.insert(key.to_string(), value.to_string());
     }
 
-    pub(crate) fn set_json<J>(&mut self, json: J)
-    where
-        J: serde::de::DeserializeOwned + serde::Serialize,
-    {
-        self.body = RequestBody::JSON(serde_json::to_value(json).unwrap());
-    }
-
     pub(crate) fn set_text(&mut self, text: TextData) {
-        self.body = RequestBody::TEXT(text)
+        self.body = RequestBody::TEXT(text).into()
     }
 
     pub(crate) fn set_binary(&mut self, bytes: Vec<u8>) {
-        self.body = RequestBody::BINARY(bytes.into());
+        self.body = RequestBody::BINARY(bytes.into()).into();
     }
 
     pub(crate) fn set_form(&mut self, key: &'static str, value: &'static str) {
-        match &mut self.body {
+        match &mut *self.body {
             RequestBody::FORM(existing) => {
                 existing.insert(key, value);
             }
@@ -47,7 +46,7 @@ impl HttpRequest {
                 let mut form_data = FormData::new();
                 form_data.insert(key, value);
 
-                self.body = RequestBody::FORM(form_data)
+                self.body = RequestBody::FORM(form_data).into()
             }
         }
     }
@@ -57,7 +56,7 @@ impl HttpRequest {
     // }
 
     pub(crate) fn _set_binary(&mut self, bytes: Vec<u8>) {
-        self.body = RequestBody::BINARY(bytes.into());
+        self.body = RequestBody::BINARY(bytes.into()).into();
     }
 
     pub(crate) fn set_method(&mut self, method: HttpMethods) {
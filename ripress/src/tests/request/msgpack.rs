@@ -0,0 +1,98 @@
+#[cfg(all(test, feature = "msgpack"))]
+mod tests {
+    use serde::{Deserialize, Serialize};
+
+    use crate::{
+        app::{test_client::TestClient, App},
+        req::{body::RequestBodyType, HttpRequest},
+        types::RouterFns,
+    };
+
+    #[derive(Debug, Serialize, Deserialize, PartialEq)]
+    struct Reading {
+        sensor: String,
+        value: f64,
+        tags: Vec<String>,
+    }
+
+    fn reading() -> Reading {
+        Reading {
+            sensor: "t-1".to_string(),
+            value: 21.5,
+            tags: vec!["indoor".to_string()],
+        }
+    }
+
+    fn client() -> TestClient {
+        let mut app = App::new();
+        app.post("/readings", |req: HttpRequest, res| async move {
+            let is_msgpack = req.is(RequestBodyType::MSGPACK);
+            let reading = match req.msgpack::<Reading>() {
+                Ok(reading) => reading,
+                Err(e) => return res.bad_request().text(e),
+            };
+            assert!(is_msgpack);
+            match req.accepts(&["application/json", "application/msgpack"]) {
+                Some("application/msgpack") => res.created().msgpack(&reading),
+                _ => res.created().json(&reading),
+            }
+        });
+        TestClient::new(app)
+    }
+
+    #[tokio::test]
+    async fn test_msgpack_request_and_negotiated_response() {
+        let client = client();
+
+        // Positional (array) encoding is accepted as well as named
+        for body in [
+            rmp_serde::to_vec_named(&reading()).unwrap(),
+            rmp_serde::to_vec(&reading()).unwrap(),
+        ] {
+            let res = client
+                .post("/readings")
+                .header("Content-Type", "application/msgpack")
+                .header("Accept", "application/msgpack")
+                .body(body)
+                .send()
+                .await;
+
+            assert_eq!(res.status(), 201);
+            assert_eq!(res.header("content-type"), Some("application/msgpack"));
+            let echoed: Reading = rmp_serde::from_slice(res.bytes()).unwrap();
+            assert_eq!(echoed, reading());
+        }
+
+        let res = client
+            .post("/readings")
+            .header("Content-Type", "application/x-msgpack")
+            .body(rmp_serde::to_vec_named(&reading()).unwrap())
+            .send()
+            .await;
+        assert_eq!(res.status(), 201);
+        assert_eq!(res.json::<Reading>().unwrap(), reading());
+    }
+
+    #[tokio::test]
+    async fn test_msgpack_rejects_other_content() {
+        let client = client();
+
+        let res = client.post("/readings").json(&reading()).send().await;
+        assert_eq!(res.status(), 400);
+        assert_eq!(
+            res.text(),
+            "Expected a MessagePack body, got Content-Type: application/json"
+        );
+
+        let res = client
+            .post("/readings")
+            .header("Content-Type", "application/msgpack")
+            .body(vec![0xc1])
+            .send()
+            .await;
+        assert_eq!(res.status(), 400);
+        assert!(res
+            .text()
+            .starts_with("Failed to deserialize MessagePack: "));
+    }
+}
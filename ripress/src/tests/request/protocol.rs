@@ -0,0 +1,359 @@
+#[cfg(test)]
+mod tests {
+    use bytes::Bytes;
+    use http_body_util::Full;
+    use hyper::Request;
+
+    use crate::{app::connection::Scheme, req::HttpRequest};
+
+    async fn convert(mut req: Request<Full<Bytes>>) -> HttpRequest {
+        HttpRequest::from_hyper_request(&mut req).await.unwrap()
+    }
+
+    fn request(scheme: Option<Scheme>, forwarded_proto: Option<&str>) -> Request<Full<Bytes>> {
+        let mut builder = Request::builder().uri("/").header("host", "example.com");
+        if let Some(proto) = forwarded_proto {
+            builder = builder.header("x-forwarded-proto", proto);
+        }
+        let mut req = builder.body(Full::from(Bytes::new())).unwrap();
+        if let Some(scheme) = scheme {
+            req.extensions_mut().insert(scheme);
+        }
+        req
+    }
+
+    #[tokio::test]
+    async fn test_tls_connection_is_secure_without_proxy_headers() {
+        let req = convert(request(Some(Scheme::Https), None)).await;
+
+        assert_eq!(req.protocol, "https");
+        assert!(req.is_secure());
+        assert_eq!(req.origin_url.to_string(), "https://example.com");
+    }
+
+    #[tokio::test]
+    async fn test_tls_connection_ignores_forwarded_proto() {
+        let req = convert(request(Some(Scheme::Https), Some("http"))).await;
+
+        assert_eq!(req.protocol, "https");
+        assert!(req.is_secure());
+    }
+
+    #[tokio::test]
+    async fn test_plain_connection_uses_forwarded_proto() {
+        let req = convert(request(Some(Scheme::Http), None)).await;
+        assert_eq!(req.protocol, "http");
+        assert!(!req.is_secure());
+
+        let req = convert(request(Some(Scheme::Http), Some("HTTPS, http"))).await;
+        assert_eq!(req.protocol, "https");
+        assert!(req.is_secure());
+        assert_eq!(req.origin_url.to_string(), "https://example.com");
+
+        let req = convert(request(Some(Scheme::Http), Some("http"))).await;
+        assert!(!req.is_secure());
+    }
+
+    #[tokio::test]
+    async fn test_client_supplied_uri_scheme_is_ignored() {
+        // HTTP/2 requests carry the client's `:scheme` in the URI.
+        let mut req = Request::builder()
+            .uri("https://example.com/")
+            .body(Full::from(Bytes::new()))
+            .unwrap();
+        req.extensions_mut().insert(Scheme::Http);
+        let req = convert(req).await;
+
+        assert_eq!(req.protocol, "http");
+        assert_eq!(req.origin_url.to_string(), "http://example.com");
+    }
+
+    #[tokio::test]
+    async fn test_secure_scheme_survives_round_trip() {
+        let req = convert(request(Some(Scheme::Https), None)).await;
+        let req = convert(req.to_hyper_request().unwrap()).await;
+
+        assert!(req.is_secure());
+    }
+}
+
+#[cfg(test)]
+mod trust_proxy_tests {
+    use std::net::IpAddr;
+
+    use bytes::Bytes;
+    use http_body_util::Full;
+    use hyper::Request;
+
+    use crate::{
+        app::{
+            settings::{IpRange, RequestSettings, TrustProxy},
+            test_client::TestClient,
+            App,
+        },
+        req::HttpRequest,
+        types::RouterFns,
+    };
+
+    async fn proxied(trusted: bool, headers: &[(&str, &str)]) -> HttpRequest {
+        let mut builder = Request::builder()
+            .uri("/orders?page=2")
+            .header("host", "10.0.0.5:3000");
+        for (name, value) in headers {
+            builder = builder.header(*name, *value);
+        }
+        let mut req = builder.body(Full::from(Bytes::new())).unwrap();
+        req.extensions_mut().insert(RequestSettings {
+            trusted_proxy: trusted,
+            trusted_host: trusted,
+            peer_ip: Some("10.0.0.1".parse().unwrap()),
+            ..RequestSettings::default()
+        });
+        HttpRequest::from_hyper_request(&mut req).await.unwrap()
+    }
+
+    const PROXY_HEADERS: [(&str, &str); 4] = [
+        ("x-forwarded-proto", "https"),
+        ("x-forwarded-host", "public.example.com"),
+        ("x-forwarded-port", "443"),
+        ("x-forwarded-for", "203.0.113.7, 10.0.0.1"),
+    ];
+
+    #[tokio::test]
+    async fn test_trusted_proxy_describes_public_origin() {
+        let req = proxied(true, &PROXY_HEADERS).await;
+
+        assert_eq!(req.origin_url.to_string(), "https://public.example.com");
+        assert_eq!(req.hostname(), "public.example.com");
+        assert_eq!(req.ip(), "203.0.113.7".parse::<IpAddr>().unwrap());
+        assert!(req.is_secure());
+    }
+
+    #[tokio::test]
+    async fn test_forwarded_port_is_kept_unless_default() {
+        let req = proxied(
+            true,
+            &[
+                ("x-forwarded-host", "public.example.com:9999"),
+                ("x-forwarded-port", "8443"),
+                ("x-forwarded-proto", "https"),
+            ],
+        )
+        .await;
+        assert_eq!(
+            req.origin_url.to_string(),
+            "https://public.example.com:8443"
+        );
+
+        let req = proxied(true, &[("x-forwarded-port", "8080")]).await;
+        assert_eq!(req.origin_url.to_string(), "http://10.0.0.5:8080");
+
+        let req = proxied(true, &[("x-forwarded-host", "[2001:db8::1]:8080")]).await;
+        assert_eq!(req.origin_url.to_string(), "http://[2001:db8::1]:8080");
+    }
+
+    #[tokio::test]
+    async fn test_untrusted_peer_cannot_forward() {
+        let req = proxied(false, &PROXY_HEADERS).await;
+
+        assert_eq!(req.origin_url.to_string(), "http://10.0.0.5:3000");
+        assert_eq!(req.hostname(), "10.0.0.5");
+        assert_eq!(req.ip(), "10.0.0.1".parse::<IpAddr>().unwrap());
+        assert!(!req.is_secure());
+    }
+
+    #[tokio::test]
+    async fn test_untrusted_peer_survives_round_trip() {
+        let req = proxied(false, &PROXY_HEADERS).await;
+        let mut hyper_req = req.to_hyper_request().unwrap();
+        let req = HttpRequest::from_hyper_request(&mut hyper_req)
+            .await
+            .unwrap();
+
+        assert_eq!(req.hostname(), "10.0.0.5");
+        assert_eq!(req.ip(), "10.0.0.1".parse::<IpAddr>().unwrap());
+    }
+
+    #[test]
+    fn test_ip_ranges() {
+        let range: IpRange = "10.0.0.0/8".parse().unwrap();
+        assert!(range.contains("10.20.30.40".parse().unwrap()));
+        assert!(range.contains("::ffff:10.0.0.1".parse().unwrap()));
+        assert!(!range.contains("11.0.0.1".parse().unwrap()));
+
+        let single: IpRange = "::1".parse().unwrap();
+        assert!(single.contains("::1".parse().unwrap()));
+        assert!(!single.contains("::2".parse().unwrap()));
+
+        let all: IpRange = "0.0.0.0/0".parse().unwrap();
+        assert!(all.contains("192.0.2.1".parse().unwrap()));
+
+        assert!("10.0.0.0/33".parse::<IpRange>().is_err());
+        assert!("proxy.local".parse::<IpRange>().is_err());
+    }
+
+    #[test]
+    fn test_trust_proxy_peers() {
+        let trust = TrustProxy::peers(["127.0.0.1", "fd00::/8"]).unwrap();
+        assert!(trust.trusts(Some("127.0.0.1".parse().unwrap())));
+        assert!(trust.trusts(Some("fd12::1".parse().unwrap())));
+        assert!(!trust.trusts(Some("203.0.113.7".parse().unwrap())));
+        assert!(trust.trusts(None));
+
+        assert!(!TrustProxy::Disabled.trusts(None));
+        assert!(TrustProxy::All.trusts(Some("203.0.113.7".parse().unwrap())));
+        assert_eq!(
+            TrustProxy::peers(["10.0.0.0/8", "nope"]).unwrap_err(),
+            "invalid IP address or range: nope"
+        );
+    }
+
+    #[tokio::test]
+    async fn test_disabled_trust_proxy_ignores_forwarded_headers() {
+        let mut app = App::new();
+        app.trust_proxy(TrustProxy::Disabled);
+        app.get("/", |req: HttpRequest, res| async move {
+            res.ok().text(req.origin_url.to_string())
+        });
+        let client = TestClient::new(app);
+
+        let res = client
+            .get("/")
+            .header("Host", "localhost:3000")
+            .header("X-Forwarded-Host", "public.example.com")
+            .header("X-Forwarded-Proto", "https")
+            .send()
+            .await;
+        assert_eq!(res.text(), "http://localhost:3000");
+    }
+
+    #[tokio::test]
+    async fn test_forwarded_host_needs_explicit_trust_proxy() {
+        fn app() -> App {
+            let mut app = App::new();
+            app.get("/", |req: HttpRequest, res| async move {
+                res.ok().text(req.origin_url.to_string())
+            });
+            app
+        }
+        let send = |client: TestClient| async move {
+            client
+                .get("/")
+                .header("Host", "localhost:3000")
+                .header("X-Forwarded-Host", "public.example.com")
+                .header("X-Forwarded-Port", "8443")
+                .header("X-Forwarded-Proto", "https")
+                .send()
+                .await
+                .text()
+        };
+
+        // By default the scheme is still taken from the proxy, but not the host
+        assert_eq!(send(TestClient::new(app())).await, "https://localhost:3000");
+
+        let mut trusting = app();
+        trusting.trust_proxy(TrustProxy::All);
+        assert_eq!(
+            send(TestClient::new(trusting)).await,
+            "https://public.example.com:8443"
+        );
+    }
+}
+
+#[cfg(test)]
+mod forwarded_tests {
+    use std::net::IpAddr;
+
+    use bytes::Bytes;
+    use http_body_util::Full;
+    use hyper::Request;
+
+    use crate::{
+        app::settings::RequestSettings,
+        req::{forwarded::Forwarded, HttpRequest},
+    };
+
+    async fn with_forwarded(trusted: bool, forwarded: &[&str]) -> HttpRequest {
+        let mut builder = Request::builder().uri("/").header("host", "10.0.0.5:3000");
+        for value in forwarded {
+            builder = builder.header("forwarded", *value);
+        }
+        let mut req = builder.body(Full::from(Bytes::new())).unwrap();
+        req.extensions_mut().insert(RequestSettings {
+            trusted_proxy: trusted,
+            trusted_host: trusted,
+            peer_ip: Some("10.0.0.1".parse().unwrap()),
+            ..RequestSettings::default()
+        });
+        HttpRequest::from_hyper_request(&mut req).await.unwrap()
+    }
+
+    #[tokio::test]
+    async fn test_forwarded_resolves_ip_and_protocol() {
+        let req = with_forwarded(true, &["for=203.0.113.1;proto=https"]).await;
+
+        assert_eq!(req.ip(), "203.0.113.1".parse::<IpAddr>().unwrap());
+        assert!(req.is_secure());
+        assert_eq!(req.origin_url.to_string(), "https://10.0.0.5:3000");
+    }
+
+    #[tokio::test]
+    async fn test_forwarded_uses_first_hop() {
+        let req = with_forwarded(
+            true,
+            &[
+                r#"for="[2001:db8:cafe::17]:4711";host="public.example.com:8443";proto=HTTPS"#,
+                "for=10.0.0.2;proto=http",
+            ],
+        )
+        .await;
+
+        assert_eq!(req.ip(), "2001:db8:cafe::17".parse::<IpAddr>().unwrap());
+        assert_eq!(req.protocol, "https");
+        assert_eq!(
+            req.origin_url.to_string(),
+            "https://public.example.com:8443"
+        );
+        assert_eq!(req.hostname(), "public.example.com");
+        assert_eq!(req.forwarded().len(), 2);
+    }
+
+    #[tokio::test]
+    async fn test_forwarded_ignored_from_untrusted_peer() {
+        let req = with_forwarded(false, &["for=203.0.113.1;proto=https;host=evil.example"]).await;
+
+        assert_eq!(req.ip(), "10.0.0.1".parse::<IpAddr>().unwrap());
+        assert!(!req.is_secure());
+        assert_eq!(req.hostname(), "10.0.0.5");
+        // Still available to handlers that want to inspect it
+        assert_eq!(req.forwarded()[0].host.as_deref(), Some("evil.example"));
+    }
+
+    #[tokio::test]
+    async fn test_obfuscated_forwarded_for_falls_back() {
+        let mut req = with_forwarded(true, &["for=_hidden;proto=https"]).await;
+        assert_eq!(req.ip(), "10.0.0.1".parse::<IpAddr>().unwrap());
+
+        req.headers.insert("x-forwarded-for", "198.51.100.4");
+        assert_eq!(req.ip(), "198.51.100.4".parse::<IpAddr>().unwrap());
+    }
+
+    #[test]
+    fn test_parse_quoted_values_and_hops() {
+        let hops = Forwarded::parse_list(
+            r#"For="198.51.100.17:80";by=203.0.113.43;host="a,b;c", for=unknown , ;proto=http"#,
+        );
+
+        assert_eq!(hops.len(), 3);
+        assert_eq!(hops[0].for_.as_deref(), Some("198.51.100.17:80"));
+        assert_eq!(hops[0].for_ip(), Some("198.51.100.17".parse().unwrap()));
+        assert_eq!(hops[0].by.as_deref(), Some("203.0.113.43"));
+        assert_eq!(hops[0].host.as_deref(), Some("a,b;c"));
+        assert_eq!(hops[1].for_.as_deref(), Some("unknown"));
+        assert_eq!(hops[1].for_ip(), None);
+        assert_eq!(hops[2].proto.as_deref(), Some("http"));
+
+        let hops = Forwarded::parse_list(r#"host="say \"hi\"""#);
+        assert_eq!(hops[0].host.as_deref(), Some(r#"say "hi""#));
+    }
+}
@@ -1,6 +1,8 @@
 #[cfg(test)]
 mod tests {
+    use crate::error::RipressErrorKind;
     use crate::req::query_params::{QueryParamError, QueryParams, SortDirection};
+    use serde::Deserialize;
     use std::collections::HashMap;
 
     #[test]
@@ -101,10 +103,8 @@ mod tests {
         assert_eq!(query.get("limit"), Some("10"));
         assert_eq!(query.get("active"), Some("true"));
 
-        let tags = query.get_all("tags").unwrap();
-        assert_eq!(tags.len(), 2);
-        assert!(tags.contains(&"rust".to_string()));
-        assert!(tags.contains(&"web".to_string()));
+        assert_eq!(query.get_all("tags"), vec!["rust", "web"]);
+        assert!(query.get_all("missing").is_empty());
     }
 
     #[test]
@@ -174,4 +174,208 @@ mod tests {
         assert_eq!(query.page(), 1); 
         assert_eq!(query.limit(), 20); 
     }
+
+    #[test]
+    fn test_typed_getters_tell_absent_from_unparsable() {
+        let query = QueryParams::from_query_string("page=3&limit=lots");
+
+        assert_eq!(query.get_as::<u32>("page"), Some(Ok(3)));
+        assert!(matches!(query.get_as::<u32>("limit"), Some(Err(_))));
+        assert_eq!(query.get_as::<u32>("offset"), None);
+
+        assert_eq!(query.get_or("page", 1u32).unwrap(), 3);
+        assert_eq!(query.get_or("offset", 1u32).unwrap(), 1);
+        let err = query.get_or("limit", 20u32).unwrap_err();
+        assert_eq!(err.kind(), &RipressErrorKind::ParseError);
+    }
+
+    #[test]
+    fn test_from_iterator_keeps_repeated_keys() {
+        let query = QueryParams::from_iterator(
+            url::form_urlencoded::parse(b"tag=a&tag=b&page=1").into_owned(),
+        );
+
+        assert_eq!(query.get("tag"), Some("a"));
+        assert_eq!(query.get_all("tag"), vec!["a", "b"]);
+        assert_eq!(query.get_all("page"), vec!["1"]);
+    }
+
+    #[derive(Debug, Deserialize, PartialEq)]
+    struct Filter {
+        status: String,
+        min_score: Option<u32>,
+    }
+
+    #[derive(Debug, Deserialize, PartialEq)]
+    struct Search {
+        q: String,
+        tag: Vec<String>,
+        page: Option<u32>,
+        active: bool,
+        filter: Filter,
+    }
+
+    #[test]
+    fn test_deserialize_arrays_and_nested_keys() {
+        let query = QueryParams::from_query_string(
+            "q=rust&tag=web&tag=backend&active=yes&filter[status]=open&filter[min_score]=3",
+        );
+
+        let search: Search = query.deserialize().unwrap();
+        assert_eq!(
+            search,
+            Search {
+                q: "rust".to_string(),
+                tag: vec!["web".to_string(), "backend".to_string()],
+                page: None,
+                active: true,
+                filter: Filter {
+                    status: "open".to_string(),
+                    min_score: Some(3),
+                },
+            }
+        );
+    }
+
+    #[test]
+    fn test_deserialize_bracket_arrays() {
+        #[derive(Deserialize)]
+        struct Ids {
+            ids: Vec<u32>,
+            single: Vec<String>,
+            indexed: Vec<String>,
+        }
+
+        let query =
+            QueryParams::from_query_string("ids[]=3&ids[]=1&single=only&indexed[1]=b&indexed[0]=a");
+        let ids: Ids = query.deserialize().unwrap();
+
+        assert_eq!(ids.ids, vec![3, 1]);
+        assert_eq!(ids.single, vec!["only"]);
+        assert_eq!(ids.indexed, vec!["a", "b"]);
+    }
+
+    #[test]
+    fn test_deserialize_into_map() {
+        let query = QueryParams::from_query_string("a=1&b=2");
+        let map: HashMap<String, i32> = query.deserialize().unwrap();
+
+        assert_eq!(map.get("a"), Some(&1));
+        assert_eq!(map.get("b"), Some(&2));
+    }
+
+    #[test]
+    fn test_deserialize_error_names_field() {
+        let query = QueryParams::from_query_string(
+            "q=rust&tag=web&active=true&filter[status]=open&filter[min_score]=high",
+        );
+
+        let err = query.deserialize::<Search>().unwrap_err();
+        assert_eq!(err.kind, RipressErrorKind::ParseError);
+        assert!(err.message.contains("filter.min_score"), "{}", err.message);
+        assert!(err.message.contains("high"), "{}", err.message);
+
+        let missing = QueryParams::from_query_string("tag=web")
+            .deserialize::<Search>()
+            .unwrap_err();
+        assert!(
+            missing.message.contains("missing field `q`"),
+            "{}",
+            missing.message
+        );
+    }
+
+    #[derive(Debug, Deserialize, PartialEq)]
+    #[serde(rename_all = "lowercase")]
+    enum Order {
+        Asc,
+        Desc,
+    }
+
+    #[derive(Debug, Deserialize, PartialEq)]
+    struct TypedSearch {
+        q: String,
+        page: Option<u32>,
+        order: Option<Order>,
+        min_price: Option<f64>,
+    }
+
+    #[test]
+    fn test_into_typed_struct() {
+        let query = QueryParams::from_query_string("q=rust&page=2&order=desc&min_price=9.5");
+        let search: TypedSearch = query.into().unwrap();
+
+        assert_eq!(
+            search,
+            TypedSearch {
+                q: "rust".to_string(),
+                page: Some(2),
+                order: Some(Order::Desc),
+                min_price: Some(9.5),
+            }
+        );
+    }
+
+    #[test]
+    fn test_into_missing_optionals_are_none() {
+        let search: TypedSearch = QueryParams::from_query_string("q=rust").into().unwrap();
+
+        assert_eq!(search.q, "rust");
+        assert_eq!(search.page, None);
+        assert_eq!(search.order, None);
+        assert_eq!(search.min_price, None);
+    }
+
+    #[test]
+    fn test_into_type_mismatch_names_field() {
+        let err = QueryParams::from_query_string("q=rust&page=two")
+            .into::<TypedSearch>()
+            .unwrap_err();
+        assert_eq!(err.kind, RipressErrorKind::ParseError);
+        assert!(err.message.contains("field `page`"), "{}", err.message);
+
+        let err = QueryParams::from_query_string("q=rust&order=sideways")
+            .into::<TypedSearch>()
+            .unwrap_err();
+        assert!(err.message.contains("field `order`"), "{}", err.message);
+
+        let err = QueryParams::from_query_string("page=1")
+            .into::<TypedSearch>()
+            .unwrap_err();
+        assert!(err.message.contains("missing field `q`"), "{}", err.message);
+    }
+
+    #[test]
+    fn test_raw_keeps_original_query_string() {
+        let raw = "b=2&a=1&a=%2Fx&q=rust+web";
+        let query = QueryParams::from_query_string(raw);
+
+        assert_eq!(query.raw(), raw);
+        assert_eq!(QueryParams::new().raw(), "");
+    }
+
+    #[test]
+    fn test_to_string_round_trips_encoding() {
+        let mut query = QueryParams::new();
+        query.append("q", "rust & tokio");
+        query.append("path", "/a/b?c=d");
+        query.append("sym", "100%+ café");
+        query.append("tag", "x");
+        query.append("tag", "y");
+
+        let encoded = query.to_string();
+        assert!(encoded.contains("q=rust%20%26%20tokio"), "{}", encoded);
+
+        for reparsed in [
+            QueryParams::from_query_string(&encoded),
+            QueryParams::from_iterator(
+                url::form_urlencoded::parse(encoded.as_bytes()).into_owned(),
+            ),
+        ] {
+            assert_eq!(reparsed.get("q"), Some("rust & tokio"));
+            assert_eq!(reparsed.get("path"), Some("/a/b?c=d"));
+            assert_eq!(reparsed.get("sym"), Some("100%+ café"));
+            assert_eq!(reparsed.get_all("tag"), vec!["x", "y"]);
+        }
+    }
 }
@@ -0,0 +1,153 @@
+#[cfg(test)]
+mod tests {
+    use bytes::Bytes;
+    use http_body_util::Full;
+    use hyper::Request;
+    use serde_json::Value;
+
+    use crate::{
+        app::{test_client::TestClient, App},
+        req::{
+            body::{RequestBody, RequestBodyType},
+            HttpRequest,
+        },
+        types::RouterFns,
+    };
+
+    async fn convert(content_type: Option<&str>, body: &'static [u8]) -> HttpRequest {
+        let mut builder = Request::builder().method("POST").uri("/");
+        if let Some(content_type) = content_type {
+            builder = builder.header("content-type", content_type);
+        }
+        let mut req = builder.body(Full::from(Bytes::from_static(body))).unwrap();
+        HttpRequest::from_hyper_request(&mut req).await.unwrap()
+    }
+
+    #[tokio::test]
+    async fn test_raw_body_keeps_exact_json_bytes() {
+        let req = convert(Some("application/json"), b"{ \"b\": 1,  \"a\": 2 }").await;
+
+        assert_eq!(req.json::<Value>().unwrap()["a"], 2);
+        assert_eq!(
+            req.raw_body(),
+            Bytes::from_static(b"{ \"b\": 1,  \"a\": 2 }")
+        );
+    }
+
+    #[tokio::test]
+    async fn test_raw_body_shares_binary_buffer() {
+        let req = convert(Some("application/octet-stream"), b"\x00\x01\x02").await;
+
+        assert_eq!(req.raw_body().as_ptr(), req.bytes().unwrap().as_ptr());
+    }
+
+    #[tokio::test]
+    async fn test_raw_body_without_content_type() {
+        let req = convert(None, b"untyped").await;
+
+        assert_eq!(req.raw_body(), Bytes::from_static(b"untyped"));
+    }
+
+    #[tokio::test]
+    async fn test_raw_body_reflects_middleware_changes() {
+        let mut req = convert(Some("application/x-www-form-urlencoded"), b"a=1").await;
+        req.insert_form_field("b", "2");
+
+        let raw = String::from_utf8(req.raw_body().to_vec()).unwrap();
+        assert!(raw.contains("a=1") && raw.contains("b=2"));
+    }
+
+    #[tokio::test]
+    async fn test_raw_body_survives_pre_middleware() {
+        let mut app = App::new();
+        app.use_pre_middleware(None, |req: HttpRequest, res, next| async move {
+            let _ = req.json::<Value>();
+            next.call(req, res).await
+        });
+        app.post("/webhook", |req: HttpRequest, res| async move {
+            let parsed = req.json::<Value>().unwrap();
+            res.ok().text(format!(
+                "{}|{}",
+                parsed["id"],
+                String::from_utf8_lossy(&req.raw_body())
+            ))
+        });
+        app.post("/upload", |req: HttpRequest, res| async move {
+            let name = req.form_data().unwrap().get("name").unwrap_or_default();
+            res.ok().text(name.to_string())
+        });
+        let client = TestClient::new(app);
+
+        let body = "{\"id\": 7,\n \"tags\": []}";
+        let res = client
+            .post("/webhook")
+            .header("content-type", "application/json")
+            .body(body)
+            .send()
+            .await;
+        assert_eq!(res.text(), format!("7|{}", body));
+
+        // The multipart boundary is kept when the body is forwarded.
+        let multipart =
+            "--XYZ\r\nContent-Disposition: form-data; name=\"name\"\r\n\r\nripress\r\n--XYZ--\r\n";
+        let res = client
+            .post("/upload")
+            .header("content-type", "multipart/form-data; boundary=XYZ")
+            .body(multipart)
+            .send()
+            .await;
+        assert_eq!(res.text(), "ripress");
+    }
+
+    #[tokio::test]
+    async fn test_set_body_updates_content_type() {
+        let mut req = convert(Some("application/octet-stream"), b"\x00\x01").await;
+        req.headers.insert("content-length", "2");
+
+        req.set_json(serde_json::json!({ "ok": true }));
+        assert!(req.is(RequestBodyType::JSON));
+        assert_eq!(req.headers.content_type(), Some("application/json"));
+        assert_eq!(req.headers.get("content-length"), None);
+        assert_eq!(req.raw_body(), Bytes::from_static(b"{\"ok\":true}"));
+
+        let mut req = convert(Some("application/xml; charset=utf-8"), b"<a/>").await;
+        req.set_body(RequestBody::TEXT("<b/>".into()));
+        assert_eq!(
+            req.headers.content_type(),
+            Some("application/xml; charset=utf-8")
+        );
+
+        req.set_body(RequestBody::EMPTY);
+        assert_eq!(req.headers.content_type(), None);
+    }
+
+    #[tokio::test]
+    async fn test_pre_middleware_can_replace_encrypted_body() {
+        let mut app = App::new();
+        app.use_pre_middleware(None, |mut req: HttpRequest, res, next| async move {
+            // Stand-in cipher: the payload is sent reversed.
+            let mut plaintext = req.bytes().unwrap_or_default().to_vec();
+            plaintext.reverse();
+            req.set_json(serde_json::from_slice::<Value>(&plaintext).unwrap());
+            next.call(req, res).await
+        });
+        app.post("/secure", |req: HttpRequest, res| async move {
+            let body = req.json::<Value>().unwrap();
+            res.ok().text(format!(
+                "{}|{}",
+                body["user"].as_str().unwrap(),
+                req.headers.content_type().unwrap()
+            ))
+        });
+        let client = TestClient::new(app);
+
+        let ciphertext: String = "{\"user\":\"ada\"}".chars().rev().collect();
+        let res = client
+            .post("/secure")
+            .header("content-type", "application/octet-stream")
+            .body(ciphertext)
+            .send()
+            .await;
+        assert_eq!(res.text(), "ada|application/json");
+    }
+}
@@ -228,4 +228,54 @@ mod tests {
         assert_eq!(map.get("id"), Some(&"123".to_string()));
         assert_eq!(map.len(), 1);
     }
+
+    #[test]
+    fn test_request_param_distinguishes_missing_from_invalid() {
+        use crate::req::HttpRequest;
+
+        let mut req = HttpRequest::new();
+        req.set_param("id", "42");
+        req.set_param("slug", "not-a-number");
+
+        let id: u32 = req.param("id").unwrap();
+        assert_eq!(id, 42);
+
+        let missing = req.param::<u32>("page").unwrap_err();
+        assert_eq!(missing.kind(), &RipressErrorKind::NotFound);
+        assert_eq!(missing.status_code(), 404);
+
+        let invalid = req.param::<u32>("slug").unwrap_err();
+        assert_eq!(invalid.kind(), &RipressErrorKind::ParseError);
+        assert_eq!(invalid.status_code(), 400);
+    }
+
+    #[tokio::test]
+    async fn test_request_param_with_question_mark_in_handler() {
+        use crate::{
+            app::{test_client::TestClient, App},
+            req::HttpRequest,
+            res::HttpResponse,
+            types::RouterFns,
+        };
+
+        async fn show(req: HttpRequest, res: HttpResponse) -> Result<HttpResponse, RipressError> {
+            let id: u32 = req.param("id")?;
+            Ok(res.ok().text(format!("user {}", id)))
+        }
+
+        let mut app = App::new();
+        app.get("/users/:id", |req: HttpRequest, res| async move {
+            show(req, res)
+                .await
+                .unwrap_or_else(RipressError::into_response)
+        });
+        let client = TestClient::new(app);
+
+        let res = client.get("/users/7").send().await;
+        assert_eq!(res.status(), 200);
+        assert_eq!(res.text(), "user 7");
+
+        let res = client.get("/users/seven").send().await;
+        assert_eq!(res.status(), 400);
+    }
 }
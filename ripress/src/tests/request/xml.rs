@@ -0,0 +1,99 @@
+#[cfg(test)]
+mod tests {
+    #[cfg(feature = "xml")]
+    use serde::Deserialize;
+
+    use crate::{
+        app::{test_client::TestClient, App},
+        req::{body::RequestBodyType, HttpRequest},
+        types::RouterFns,
+    };
+
+    const ORDER: &str = r#"<order currency="EUR"><id>7</id><item>coffee</item></order>"#;
+
+    #[cfg(feature = "xml")]
+    #[derive(Debug, Deserialize, PartialEq)]
+    struct Order {
+        #[serde(rename = "@currency")]
+        currency: String,
+        id: u32,
+        item: String,
+    }
+
+    fn echo_app() -> TestClient {
+        let mut app = App::new();
+        app.post("/text", |req: HttpRequest, res| async move {
+            let is_xml = req.is(RequestBodyType::XML);
+            match req.text() {
+                Ok(text) => res.ok().text(format!("{} {}", is_xml, text)),
+                Err(e) => res.bad_request().text(e),
+            }
+        });
+        #[cfg(feature = "xml")]
+        app.post("/orders", |req: HttpRequest, res| async move {
+            match req.xml::<Order>() {
+                Ok(order) => res
+                    .ok()
+                    .text(format!("{} {} {}", order.id, order.item, order.currency)),
+                Err(e) => res.bad_request().text(e),
+            }
+        });
+        TestClient::new(app)
+    }
+
+    #[tokio::test]
+    async fn test_xml_body_is_readable_as_text() {
+        let res = echo_app()
+            .post("/text")
+            .header("Content-Type", "application/xml")
+            .body(ORDER)
+            .send()
+            .await;
+
+        assert_eq!(res.status(), 200);
+        assert_eq!(res.text(), format!("true {}", ORDER));
+    }
+
+    #[cfg(feature = "xml")]
+    #[tokio::test]
+    async fn test_xml_deserializes_into_struct() {
+        let client = echo_app();
+
+        for content_type in ["application/xml", "text/xml; charset=utf-8"] {
+            let res = client
+                .post("/orders")
+                .header("Content-Type", content_type)
+                .body(ORDER)
+                .send()
+                .await;
+            assert_eq!(res.status(), 200);
+            assert_eq!(res.text(), "7 coffee EUR");
+        }
+    }
+
+    #[cfg(feature = "xml")]
+    #[tokio::test]
+    async fn test_xml_rejects_other_content() {
+        let client = echo_app();
+
+        let res = client
+            .post("/orders")
+            .json(&serde_json::json!({ "id": 7, "item": "coffee" }))
+            .send()
+            .await;
+        assert_eq!(res.status(), 400);
+        assert_eq!(
+            res.text(),
+            "Expected an XML body, got Content-Type: application/json"
+        );
+
+        let res = client
+            .post("/orders")
+            .header("Content-Type", "application/xml")
+            .body("<order><id>seven</id></order>")
+            .send()
+            .await;
+        assert_eq!(res.status(), 400);
+        assert!(res.text().starts_with("Failed to deserialize XML: "));
+    }
+}
@@ -133,4 +133,65 @@ mod test {
         assert!(all_cookies.contains(&"session=abc123; HttpOnly"));
         assert!(all_cookies.contains(&"theme=dark; Path=/"));
     }
+
+    #[tokio::test]
+    async fn test_append_header_emits_every_value() {
+        use crate::res::{response_cookie::CookieOptions, HttpResponse};
+
+        let res = HttpResponse::new()
+            .set_header("Vary", "Origin")
+            .append_header("Vary", "Accept-Encoding")
+            .append_header("Set-Cookie", "theme=dark")
+            .append_header("Set-Cookie", "lang=en")
+            .set_cookie("session", "abc", Some(CookieOptions::default()))
+            .to_hyper_response()
+            .await
+            .unwrap();
+
+        let vary: Vec<_> = res.headers().get_all("vary").iter().collect();
+        assert_eq!(vary, ["Origin", "Accept-Encoding"]);
+
+        let cookies: Vec<_> = res
+            .headers()
+            .get_all("set-cookie")
+            .iter()
+            .map(|v| v.to_str().unwrap())
+            .collect();
+        assert_eq!(cookies.len(), 3);
+        assert_eq!(&cookies[..2], ["theme=dark", "lang=en"]);
+        assert!(cookies[2].starts_with("session=abc"));
+    }
+
+    #[test]
+    fn test_set_header_replaces_appended_values() {
+        use crate::res::HttpResponse;
+
+        let res = HttpResponse::new()
+            .append_header("Link", "</a>; rel=preload")
+            .append_header("Link", "</b>; rel=preload")
+            .set_header("Link", "</c>; rel=preload");
+
+        assert_eq!(res.headers.get_all("link"), vec!["</c>; rel=preload"]);
+    }
+
+    #[test]
+    fn test_headers_sets_each_pair_and_skips_invalid_ones() {
+        use crate::res::HttpResponse;
+
+        let res = HttpResponse::new().set_header("X-A", "0").headers([
+            ("X-A", "1"),
+            ("X-B", "2"),
+            ("bad name", "3"),
+            ("X-C", "a\nb"),
+        ]);
+
+        assert_eq!(res.get_header("x-a"), Some("1"));
+        assert_eq!(res.get_header("x-b"), Some("2"));
+        assert_eq!(res.headers.len(), 2);
+
+        let owned = vec![("X-D".to_string(), "4".to_string())];
+        let res = res.headers(owned).remove_header("X-A");
+        assert_eq!(res.get_header("x-a"), None);
+        assert_eq!(res.get_header("x-d"), Some("4"));
+    }
 }
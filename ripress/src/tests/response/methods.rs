@@ -1,8 +1,15 @@
 #[cfg(test)]
 mod test {
+    use http_body_util::BodyExt;
     use hyper::Method;
+    use serde_json::json;
 
-    use crate::{req::HttpRequest, res::HttpResponse, types::HttpMethods};
+    use crate::{
+        app::App,
+        req::HttpRequest,
+        res::HttpResponse,
+        types::{HttpMethods, RouterFns},
+    };
 
     #[test]
     fn test_httpmethods_display() {
@@ -13,6 +20,8 @@ mod test {
         assert_eq!(HttpMethods::PATCH.to_string(), "PATCH");
         assert_eq!(HttpMethods::OPTIONS.to_string(), "OPTIONS");
         assert_eq!(HttpMethods::HEAD.to_string(), "HEAD");
+        assert_eq!(HttpMethods::CONNECT.to_string(), "CONNECT");
+        assert_eq!(HttpMethods::TRACE.to_string(), "TRACE");
     }
 
     #[test]
@@ -39,10 +48,30 @@ mod test {
         assert_eq!(method, HttpMethods::HEAD);
 
         let method = HttpMethods::from(&Method::CONNECT);
-        assert_eq!(method, HttpMethods::GET);
+        assert_eq!(method, HttpMethods::CONNECT);
 
         let method = HttpMethods::from(&Method::TRACE);
-        assert_eq!(method, HttpMethods::GET);
+        assert_eq!(method, HttpMethods::TRACE);
+
+        let purge = Method::from_bytes(b"PURGE").unwrap();
+        let method = HttpMethods::from(&purge);
+        assert_eq!(method, HttpMethods::Other("PURGE".to_string()));
+        assert_eq!(method.as_str(), "PURGE");
+        assert_eq!(method.to_string(), "PURGE");
+        assert_eq!(Method::try_from(&method).unwrap(), purge);
+    }
+
+    #[test]
+    fn test_invalid_extension_method_does_not_convert() {
+        assert!(Method::try_from(&HttpMethods::Other("NOT VALID".to_string())).is_err());
+    }
+
+    #[test]
+    #[should_panic(expected = "invalid HTTP method `NOT VALID` for route `/cache`")]
+    fn test_invalid_extension_method_panics_at_registration() {
+        let mut app = App::new();
+        app.route(HttpMethods::Other("NOT VALID".to_string()), "/cache")
+            .handler(|_req: HttpRequest, res| async move { res.no_content() });
     }
 
     #[test]
@@ -84,6 +113,49 @@ mod test {
             response.status_code.canonical_reason(),
             "Service Unavailable"
         );
+
+        let response = HttpResponse::new().not_modified();
+        assert_eq!(response.status_code.as_u16(), 304);
+        assert_eq!(response.status_code.canonical_reason(), "Not Modified");
+
+        let response = HttpResponse::new().unprocessable_entity();
+        assert_eq!(response.status_code.as_u16(), 422);
+        assert_eq!(
+            response.status_code.canonical_reason(),
+            "Unprocessable Entity"
+        );
+
+        let response = HttpResponse::new().payload_too_large();
+        assert_eq!(response.status_code.as_u16(), 413);
+
+        let response = HttpResponse::new().too_many_requests();
+        assert_eq!(response.status_code.as_u16(), 429);
+    }
+
+    #[tokio::test]
+    async fn test_bodyless_statuses_drop_body_and_content_type() {
+        let res = HttpResponse::new()
+            .no_content()
+            .to_hyper_response()
+            .await
+            .unwrap();
+        assert_eq!(res.status(), 204);
+        assert!(res.headers().get("content-type").is_none());
+        let body = res.into_body().collect().await.unwrap().to_bytes();
+        assert!(body.is_empty());
+
+        let res = HttpResponse::new()
+            .json(json!({ "ignored": true }))
+            .not_modified()
+            .set_header("ETag", "\"v1\"")
+            .to_hyper_response()
+            .await
+            .unwrap();
+        assert_eq!(res.status(), 304);
+        assert!(res.headers().get("content-type").is_none());
+        assert_eq!(res.headers()["etag"], "\"v1\"");
+        let body = res.into_body().collect().await.unwrap().to_bytes();
+        assert!(body.is_empty());
     }
 
     #[test]
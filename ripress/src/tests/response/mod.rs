@@ -10,6 +10,7 @@ mod methods;
 mod redirects_test;
 mod status_code;
 mod streaming_test;
+mod xml_test;
 
 #[cfg(test)]
 mod test {
@@ -79,6 +80,7 @@ mod tests {
     use crate::res::response_status::StatusCode;
     use crate::res::HttpResponse;
     use crate::res::HttpResponseError;
+    use crate::res::Render;
     use futures::stream;
     use serde_json::json;
 
@@ -135,6 +137,71 @@ mod tests {
         assert!(req.json::<User>().is_err());
     }
 
+    #[test]
+    fn test_json_with_limit() {
+        let mut req = HttpRequest::new();
+        req.set_json(json!({"name": "ripress"}));
+
+        let value = req.json_with_limit::<serde_json::Value>(1024).unwrap();
+        assert_eq!(value, json!({"name": "ripress"}));
+
+        let err = req.json_with_limit::<serde_json::Value>(4).unwrap_err();
+        assert!(err.contains("received"));
+
+        req.set_header("content-length", "104857600");
+        let err = req
+            .json_with_limit::<serde_json::Value>(1024 * 1024)
+            .unwrap_err();
+        assert!(err.contains("declared 104857600 bytes"));
+    }
+
+    #[tokio::test]
+    async fn test_body_stream() {
+        use futures::StreamExt;
+
+        let mut req = HttpRequest::new();
+        let data = vec![7u8; 40 * 1024];
+        req.set_binary(data.clone());
+
+        let chunks: Vec<_> = req.body_stream().collect().await;
+        assert_eq!(chunks.len(), 3);
+
+        let streamed: Vec<u8> = chunks
+            .into_iter()
+            .flat_map(|chunk| chunk.unwrap().to_vec())
+            .collect();
+        assert_eq!(streamed, data);
+
+        let empty = HttpRequest::new();
+        assert_eq!(empty.body_stream().count().await, 0);
+    }
+
+    #[test]
+    fn test_accepts() {
+        let mut req = HttpRequest::new();
+        req.set_header(
+            "accept",
+            "text/html,application/xhtml+xml,application/xml;q=0.9,*/*;q=0.8",
+        );
+        assert_eq!(
+            req.accepts(&["application/json", "text/html"]),
+            Some("text/html")
+        );
+
+        req.set_header("accept", "application/json");
+        assert_eq!(
+            req.accepts(&["application/json", "text/html"]),
+            Some("application/json")
+        );
+
+        req.set_header("accept-charset", "iso-8859-1;q=0.5, utf-8");
+        assert_eq!(req.accepts_charset(&["iso-8859-1", "utf-8"]), Some("utf-8"));
+
+        req.set_header("accept-encoding", "gzip, br;q=0");
+        assert_eq!(req.accepts_encoding(&["br", "gzip"]), Some("gzip"));
+        assert_eq!(req.accepts_encoding(&["br"]), None);
+    }
+
     #[test]
     fn test_binary_body() {
         let mut req = HttpRequest::new();
@@ -220,7 +287,19 @@ mod tests {
         assert_eq!(content_type, RequestBodyType::BINARY);
 
         let content_type = determine_content_type_request("application/xml");
-        assert_eq!(content_type, RequestBodyType::TEXT);
+        assert_eq!(content_type, RequestBodyType::XML);
+
+        let content_type = determine_content_type_request("text/xml; charset=utf-8");
+        assert_eq!(content_type, RequestBodyType::XML);
+
+        let content_type = determine_content_type_request("application/atom+xml");
+        assert_eq!(content_type, RequestBodyType::XML);
+
+        let content_type = determine_content_type_request("application/msgpack");
+        assert_eq!(content_type, RequestBodyType::MSGPACK);
+
+        let content_type = determine_content_type_request("application/x-msgpack");
+        assert_eq!(content_type, RequestBodyType::MSGPACK);
 
         let content_type = determine_content_type_request(
             "multipart/form-data; boundary=----WebKitFormBoundary7MA4YWxkTrZu0gW",
@@ -340,4 +419,348 @@ mod tests {
         assert_eq!(res.status_code, StatusCode::PermanentRedirect);
         assert_eq!(res.headers.get("Location"), Some("https://example.com"));
     }
+
+    #[tokio::test]
+    async fn test_getters_read_back_handler_output() {
+        async fn handler(_req: HttpRequest, res: HttpResponse) -> HttpResponse {
+            res.created()
+                .set_header("X-Handler", "users")
+                .json(serde_json::json!({ "id": 7 }))
+        }
+
+        let res = handler(HttpRequest::new(), HttpResponse::new()).await;
+
+        assert_eq!(res.get_status(), 201);
+        assert_eq!(res.get_header("x-handler"), Some("users"));
+        assert_eq!(res.get_header("x-missing"), None);
+        assert_eq!(res.body_bytes(), r#"{"id":7}"#);
+        assert_eq!(res.body_text().unwrap(), r#"{"id":7}"#);
+    }
+
+    #[test]
+    fn test_body_text_and_bytes_per_body_type() {
+        let res = HttpResponse::new().text("plain");
+        assert_eq!(res.body_text().unwrap(), "plain");
+        assert_eq!(res.body_bytes(), "plain");
+
+        let res = HttpResponse::new().html("<p>hi</p>");
+        assert_eq!(res.body_text().unwrap(), "<p>hi</p>");
+
+        let res = HttpResponse::new().bytes(vec![0xff, 0x00, 0xfe]);
+        assert_eq!(res.body_bytes(), vec![0xff, 0x00, 0xfe]);
+        assert!(matches!(
+            res.body_text(),
+            Err(HttpResponseError::BinaryBody)
+        ));
+
+        let res = HttpResponse::new().bytes("valid utf-8");
+        assert!(res.body_text().is_err());
+    }
+
+    #[test]
+    fn test_attachment_sets_disposition_and_type() {
+        let res = HttpResponse::new().attachment("report.pdf");
+        assert_eq!(
+            res.get_header("Content-Disposition"),
+            Some("attachment; filename=\"report.pdf\"")
+        );
+        assert_eq!(res.get_header("Content-Type"), Some("application/pdf"));
+
+        let res = HttpResponse::new().attachment("résumé \"final\".txt");
+        assert_eq!(
+            res.get_header("Content-Disposition"),
+            Some(
+                "attachment; filename=\"r_sum_ _final_.txt\"; \
+                 filename*=UTF-8''r%C3%A9sum%C3%A9%20%22final%22.txt"
+            )
+        );
+
+        let res = HttpResponse::new().attachment("data.unknownext");
+        assert_eq!(res.get_header("Content-Type"), None);
+    }
+
+    #[tokio::test]
+    async fn test_attachment_content_type_reaches_the_wire() {
+        let res = HttpResponse::new()
+            .attachment("export.csv")
+            .text("a,b\n")
+            .to_hyper_response()
+            .await
+            .unwrap();
+
+        assert_eq!(res.headers()["content-type"], "text/csv");
+    }
+
+    #[tokio::test]
+    async fn test_download_streams_file_with_headers() {
+        use crate::app::{test_client::TestClient, App};
+        use crate::types::RouterFns;
+
+        let dir = tempfile::TempDir::new().unwrap();
+        let path = dir.path().join("report.pdf");
+        let contents = vec![b'x'; 200 * 1024];
+        std::fs::write(&path, &contents).unwrap();
+        let path = path.to_string_lossy().into_owned();
+
+        let mut app = App::new();
+        app.get("/report", move |_req: HttpRequest, res: HttpResponse| {
+            let path = path.clone();
+            async move { res.ok().download(path).await }
+        });
+        app.get(
+            "/missing",
+            |_req: HttpRequest, res: HttpResponse| async move {
+                res.ok().download("./does-not-exist.pdf").await
+            },
+        );
+        let client = TestClient::new(app);
+
+        let res = client.get("/report").send().await;
+        assert_eq!(res.status(), 200);
+        assert_eq!(
+            res.header("Content-Disposition"),
+            Some("attachment; filename=\"report.pdf\"")
+        );
+        assert_eq!(res.header("Content-Type"), Some("application/pdf"));
+        assert_eq!(res.header("Content-Length"), Some("204800"));
+        assert_eq!(res.header("Transfer-Encoding"), None);
+        assert_eq!(res.bytes().as_ref(), contents.as_slice());
+
+        assert_eq!(client.get("/missing").send().await.status(), 404);
+    }
+
+    #[tokio::test]
+    async fn test_download_is_sent_in_chunks() {
+        use crate::res::conversions::into_outgoing_response;
+        use http_body_util::BodyExt;
+
+        let dir = tempfile::TempDir::new().unwrap();
+        let path = dir.path().join("large.bin");
+        std::fs::write(&path, vec![b'x'; 200 * 1024]).unwrap();
+
+        let res = HttpResponse::new().ok().download(&path).await;
+        let mut body = into_outgoing_response(res.to_hyper_response().await.unwrap()).into_body();
+
+        let mut frames = Vec::new();
+        while let Some(frame) = body.frame().await {
+            frames.push(frame.unwrap().into_data().unwrap().len());
+        }
+        assert_eq!(frames, [64 * 1024, 64 * 1024, 64 * 1024, 8 * 1024]);
+    }
+
+    #[tokio::test]
+    async fn test_send_file_handles_conditional_and_range_requests() {
+        use crate::app::{test_client::TestClient, App};
+        use crate::types::RouterFns;
+
+        let dir = tempfile::TempDir::new().unwrap();
+        std::fs::write(dir.path().join("42.json"), r#"{"id":42}"#).unwrap();
+        let data_dir = dir.path().to_path_buf();
+
+        let mut app = App::new();
+        app.get("/data/:id", move |req: HttpRequest, res: HttpResponse| {
+            let path = data_dir.join(format!("{}.json", req.params.get("id").unwrap()));
+            async move { res.send_file(&req, path).await }
+        });
+        let client = TestClient::new(app);
+
+        let res = client.get("/data/42").send().await;
+        assert_eq!(res.status(), 200);
+        assert_eq!(res.text(), r#"{"id":42}"#);
+        assert_eq!(res.header("Content-Type"), Some("application/json"));
+        assert_eq!(res.header("Accept-Ranges"), Some("bytes"));
+        assert_eq!(res.header("Content-Disposition"), None);
+        let etag = res.header("ETag").unwrap().to_string();
+        let last_modified = res.header("Last-Modified").unwrap().to_string();
+
+        let res = client
+            .get("/data/42")
+            .header("If-None-Match", &etag)
+            .send()
+            .await;
+        assert_eq!(res.status(), 304);
+        assert!(res.bytes().is_empty());
+
+        let res = client
+            .get("/data/42")
+            .header("If-Modified-Since", &last_modified)
+            .send()
+            .await;
+        assert_eq!(res.status(), 304);
+
+        let res = client
+            .get("/data/42")
+            .header("Range", "bytes=1-4")
+            .send()
+            .await;
+        assert_eq!(res.status(), 206);
+        assert_eq!(res.text(), r#""id""#);
+        assert_eq!(res.header("Content-Range"), Some("bytes 1-4/9"));
+
+        let res = client
+            .get("/data/42")
+            .header("Range", "bytes=100-200")
+            .send()
+            .await;
+        assert_eq!(res.status(), 416);
+
+        assert_eq!(client.get("/data/7").send().await.status(), 404);
+    }
+
+    #[tokio::test]
+    async fn test_json_pretty_indents_and_keeps_content_type() {
+        let res = HttpResponse::new()
+            .ok()
+            .json_pretty(json!({ "name": "ripress", "tags": ["a"] }));
+
+        assert_eq!(
+            res.body_text().unwrap(),
+            "{\n  \"name\": \"ripress\",\n  \"tags\": [\n    \"a\"\n  ]\n}"
+        );
+
+        let res = res.to_hyper_response().await.unwrap();
+        assert_eq!(res.headers()["content-type"], "application/json");
+    }
+
+    #[test]
+    fn test_json_with_renames_nested_keys() {
+        use crate::res::{JsonConfig, RenameRule};
+
+        #[derive(serde::Serialize)]
+        struct Item {
+            item_id: u32,
+            _private_note: &'static str,
+        }
+
+        #[derive(serde::Serialize)]
+        struct Order {
+            order_id: u32,
+            line_items: Vec<Item>,
+        }
+
+        let order = Order {
+            order_id: 1,
+            line_items: vec![Item {
+                item_id: 2,
+                _private_note: "x",
+            }],
+        };
+        let config = JsonConfig {
+            rename: Some(RenameRule::CamelCase),
+            ..JsonConfig::default()
+        };
+        let res = HttpResponse::new().json_with(order, &config);
+
+        assert_eq!(
+            res.body_text().unwrap(),
+            r#"{"lineItems":[{"_privateNote":"x","itemId":2}],"orderId":1}"#
+        );
+    }
+
+    #[test]
+    fn test_rename_rules() {
+        use crate::res::RenameRule;
+
+        assert_eq!(RenameRule::CamelCase.apply("first_name"), "firstName");
+        assert_eq!(RenameRule::CamelCase.apply("already"), "already");
+        assert_eq!(RenameRule::SnakeCase.apply("firstName"), "first_name");
+        assert_eq!(RenameRule::SnakeCase.apply("FirstName"), "first_name");
+        assert_eq!(RenameRule::SnakeCase.apply("userID"), "user_id");
+        assert_eq!(RenameRule::SnakeCase.apply("HTTPServer"), "http_server");
+        assert_eq!(RenameRule::SnakeCase.apply("snake_case"), "snake_case");
+    }
+
+    #[tokio::test]
+    async fn test_form_body_is_encoded_with_content_type() {
+        let res = HttpResponse::new().ok().form([("a", "1"), ("b", "2")]);
+        assert_eq!(res.body_text().unwrap(), "a=1&b=2");
+
+        let res = res.to_hyper_response().await.unwrap();
+        assert_eq!(
+            res.headers()["content-type"],
+            "application/x-www-form-urlencoded"
+        );
+    }
+
+    #[test]
+    fn test_form_body_from_struct_round_trips() {
+        use crate::req::body::FormData;
+
+        #[derive(serde::Serialize)]
+        struct Login {
+            user: &'static str,
+            note: &'static str,
+            remember: bool,
+        }
+
+        let res = HttpResponse::new().form(Login {
+            user: "ada lovelace",
+            note: "a=b&c/d?é+",
+            remember: true,
+        });
+        let body = res.body_text().unwrap();
+        assert_eq!(
+            body,
+            "user=ada%20lovelace&note=a%3Db%26c%2Fd%3F%C3%A9%2B&remember=true"
+        );
+
+        let parsed = FormData::from_query_string(&body).unwrap();
+        assert_eq!(parsed.get("note"), Some("a=b&c/d?é+"));
+        assert_eq!(parsed.get("user"), Some("ada lovelace"));
+    }
+
+    #[test]
+    fn test_form_serialization_error_is_a_500() {
+        #[derive(serde::Serialize)]
+        struct Inner {
+            a: u8,
+        }
+
+        #[derive(serde::Serialize)]
+        struct Outer {
+            inner: Inner,
+        }
+
+        let res = HttpResponse::new()
+            .ok()
+            .form(Outer { inner: Inner { a: 1 } });
+        assert_eq!(res.get_status_code(), 500);
+        assert_eq!(res.body_text().unwrap(), "Internal Server Error");
+        assert_ne!(
+            res.get_header("Content-Type"),
+            Some("application/x-www-form-urlencoded")
+        );
+    }
+
+    struct Page {
+        title: &'static str,
+    }
+
+    impl Render for Page {
+        type Error = String;
+
+        fn render(&self) -> Result<String, Self::Error> {
+            if self.title.is_empty() {
+                return Err("title is required".to_string());
+            }
+            Ok(format!("<title>{}</title>", self.title))
+        }
+    }
+
+    #[tokio::test]
+    async fn test_render_sets_html_body() {
+        let res = HttpResponse::new().ok().render(Page { title: "Home" });
+        assert_eq!(res.body_text().unwrap(), "<title>Home</title>");
+
+        let res = res.to_hyper_response().await.unwrap();
+        assert_eq!(res.status(), 200);
+        assert_eq!(res.headers()["content-type"], "text/html");
+    }
+
+    #[test]
+    fn test_render_error_becomes_500() {
+        let res = HttpResponse::new().ok().render(Page { title: "" });
+        assert_eq!(res.status_code(), 500);
+        assert_eq!(res.body_text().unwrap(), "Internal Server Error");
+    }
 }
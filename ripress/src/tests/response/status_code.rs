@@ -52,6 +52,7 @@ mod tests {
             (StatusCode::NoContent, "204 No Content"),
             (StatusCode::Redirect, "302 Found"),
             (StatusCode::PermanentRedirect, "301 Moved Permanently"),
+            (StatusCode::NotModified, "304 Not Modified"),
             (StatusCode::BadRequest, "400 Bad Request"),
             (StatusCode::Unauthorized, "401 Unauthorized"),
             (StatusCode::Forbidden, "403 Forbidden"),
@@ -59,6 +60,7 @@ mod tests {
             (StatusCode::MethodNotAllowed, "405 Method Not Allowed"),
             (StatusCode::Conflict, "409 Conflict"),
             (StatusCode::PayloadTooLarge, "413 Payload Too Large"),
+            (StatusCode::UnprocessableEntity, "422 Unprocessable Entity"),
             (StatusCode::TooManyRequests, "429 Too Many Requests"),
             (StatusCode::InternalServerError, "500 Internal Server Error"),
             (StatusCode::NotImplemented, "501 Not Implemented"),
@@ -88,6 +90,7 @@ mod tests {
             (204, StatusCode::NoContent),
             (302, StatusCode::Redirect),
             (301, StatusCode::PermanentRedirect),
+            (304, StatusCode::NotModified),
             (400, StatusCode::BadRequest),
             (401, StatusCode::Unauthorized),
             (403, StatusCode::Forbidden),
@@ -95,6 +98,7 @@ mod tests {
             (405, StatusCode::MethodNotAllowed),
             (409, StatusCode::Conflict),
             (413, StatusCode::PayloadTooLarge),
+            (422, StatusCode::UnprocessableEntity),
             (429, StatusCode::TooManyRequests),
             (500, StatusCode::InternalServerError),
             (501, StatusCode::NotImplemented),
@@ -0,0 +1,79 @@
+#[cfg(all(test, feature = "xml"))]
+mod tests {
+    use std::collections::HashMap;
+
+    use serde::{Deserialize, Serialize};
+    use serde_json::{json, Value};
+
+    use crate::{
+        app::{settings::ErrorFormat, test_client::TestClient, App},
+        req::HttpRequest,
+        res::HttpResponse,
+        types::RouterFns,
+    };
+
+    #[derive(Debug, Serialize, Deserialize, PartialEq)]
+    struct Record {
+        #[serde(rename = "@id")]
+        id: u32,
+        name: String,
+        tags: Vec<String>,
+    }
+
+    fn record() -> Record {
+        Record {
+            id: 3,
+            name: "a < b & c".to_string(),
+            tags: vec!["x".to_string(), "y".to_string()],
+        }
+    }
+
+    #[test]
+    fn test_xml_sets_body_and_content_type() {
+        let res = HttpResponse::new().ok().xml(record());
+
+        assert_eq!(res.get_status_code(), 200);
+        assert_eq!(res.get_header("Content-Type"), Some("application/xml"));
+        assert_eq!(
+            res.body_text().unwrap(),
+            concat!(
+                r#"<?xml version="1.0" encoding="UTF-8"?>"#,
+                r#"<Record id="3"><name>a &lt; b &amp; c</name><tags>x</tags><tags>y</tags></Record>"#
+            )
+        );
+    }
+
+    #[tokio::test]
+    async fn test_xml_round_trips_through_a_handler() {
+        let mut app = App::new();
+        app.get("/record", |_req: HttpRequest, res| async move {
+            res.ok().xml(record())
+        });
+        let res = TestClient::new(app).get("/record").send().await;
+
+        assert_eq!(res.status(), 200);
+        assert_eq!(res.header("content-type"), Some("application/xml"));
+        let parsed: Record = quick_xml::de::from_str(&res.text()).unwrap();
+        assert_eq!(parsed, record());
+    }
+
+    #[tokio::test]
+    async fn test_xml_serialization_error_is_a_500() {
+        let res = HttpResponse::new().ok().xml(HashMap::from([(1, "one")]));
+        assert_eq!(res.get_status_code(), 500);
+        assert_eq!(res.body_text().unwrap(), "Internal Server Error");
+
+        let mut app = App::new();
+        app.error_format(ErrorFormat::Json);
+        app.get("/broken", |_req: HttpRequest, res| async move {
+            res.ok().xml(vec![1, 2, 3])
+        });
+        let res = TestClient::new(app).get("/broken").send().await;
+
+        assert_eq!(res.status(), 500);
+        assert_eq!(
+            res.json::<Value>().unwrap(),
+            json!({ "error": "Internal Server Error" })
+        );
+    }
+}
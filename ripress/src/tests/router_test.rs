@@ -57,6 +57,156 @@ mod tests {
             .get_routes("/user/{id}", crate::types::HttpMethods::HEAD)
             .is_some());
     }
+    #[test]
+    fn test_add_connect_and_trace_routes() {
+        let mut router = Router::new("/");
+        router.connect("/", _test_handler);
+        router.trace("/debug", _test_handler);
+        assert!(router
+            .get_routes("/", crate::types::HttpMethods::CONNECT)
+            .is_some());
+        assert!(router
+            .get_routes("/debug", crate::types::HttpMethods::TRACE)
+            .is_some());
+    }
+
+    #[test]
+    fn test_connect_and_trace_round_trip_through_method() {
+        use crate::types::HttpMethods;
+        use hyper::Method;
+
+        for (method, name) in [
+            (HttpMethods::CONNECT, "CONNECT"),
+            (HttpMethods::TRACE, "TRACE"),
+        ] {
+            let hyper_method = Method::try_from(&method).unwrap();
+            assert_eq!(hyper_method.as_str(), name);
+            assert_eq!(HttpMethods::from(&hyper_method), method);
+            assert_eq!(method.to_string(), name);
+        }
+    }
+
+    #[tokio::test]
+    async fn test_connect_route_handles_tunnel_requests() {
+        use crate::app::test_client::TestClient;
+        use crate::req::HttpRequest;
+        use crate::res::HttpResponse;
+        use crate::types::HttpMethods;
+
+        let mut app = App::new();
+        // A successful CONNECT response has no body, so the host comes back in a header.
+        app.connect("/", |req: HttpRequest, res: HttpResponse| async move {
+            let host = req.hostname().to_string();
+            res.ok().set_header("x-target-host", host)
+        });
+        app.trace("/", |req: HttpRequest, res: HttpResponse| async move {
+            res.ok().text(req.method.to_string())
+        });
+        let client = TestClient::new(app);
+
+        let res = client
+            .request(HttpMethods::CONNECT, "/")
+            .header("Host", "internal.example.com:443")
+            .send()
+            .await;
+        assert_eq!(res.status(), 200);
+        assert_eq!(res.header("x-target-host"), Some("internal.example.com"));
+
+        let res = client.request(HttpMethods::TRACE, "/").send().await;
+        assert_eq!(res.text(), "TRACE");
+
+        let res = client.delete("/").send().await;
+        assert_eq!(res.status(), 405);
+        assert_eq!(res.header("Allow"), Some("CONNECT, TRACE"));
+    }
+
+    #[tokio::test]
+    async fn test_extension_method_is_routable() {
+        use crate::app::test_client::TestClient;
+        use crate::req::HttpRequest;
+        use crate::res::HttpResponse;
+        use crate::types::HttpMethods;
+
+        let purge = HttpMethods::Other("PURGE".to_string());
+        let mut app = App::new();
+        app.route(purge.clone(), "/cache/*path").handler(
+            |req: HttpRequest, res: HttpResponse| async move {
+                let path = req.params.get("path").unwrap_or_default().to_string();
+                res.ok().text(format!("{} {}", req.method, path))
+            },
+        );
+        app.get(
+            "/cache/*path",
+            |_req: HttpRequest, res: HttpResponse| async move { res.ok() },
+        );
+        let client = TestClient::new(app);
+
+        let res = client.request(purge, "/cache/images/logo.png").send().await;
+        assert_eq!(res.status(), 200);
+        assert_eq!(res.text(), "PURGE images/logo.png");
+
+        let res = client.delete("/cache/images/logo.png").send().await;
+        assert_eq!(res.status(), 405);
+        assert_eq!(res.header("Allow"), Some("GET, PURGE"));
+    }
+
+    #[tokio::test]
+    async fn test_case_insensitive_routing_keeps_param_case() {
+        use crate::app::test_client::TestClient;
+        use crate::req::HttpRequest;
+        use crate::res::HttpResponse;
+
+        let handler = |req: HttpRequest, res: HttpResponse| async move {
+            let id = req.params.get("id").unwrap_or_default().to_string();
+            res.ok().text(id)
+        };
+
+        let mut app = App::new();
+        app.case_sensitive_routing(false);
+        app.get("/users/:id", handler);
+        let client = TestClient::new(app);
+
+        let res = client.get("/USERS/42").send().await;
+        assert_eq!(res.status(), 200);
+        assert_eq!(res.text(), "42");
+
+        let res = client.get("/Users/AbC").send().await;
+        assert_eq!(res.text(), "AbC");
+
+        let res = client.post("/uSeRs/42").send().await;
+        assert_eq!(res.status(), 405);
+
+        let mut app = App::new();
+        app.get("/users/:id", handler);
+        let client = TestClient::new(app);
+
+        assert_eq!(client.get("/USERS/42").send().await.status(), 404);
+        assert_eq!(client.get("/users/42").send().await.status(), 200);
+    }
+
+    #[test]
+    fn test_authority_form_connect_is_routed_to_root() {
+        use crate::app::handler::route_authority_form;
+        use hyper::{header::HOST, Method, Request};
+
+        let mut req = Request::builder()
+            .method(Method::CONNECT)
+            .uri("example.com:443")
+            .body(())
+            .unwrap();
+        route_authority_form(&mut req);
+        assert_eq!(req.uri().path(), "/");
+        assert_eq!(req.headers()[HOST], "example.com:443");
+
+        let mut req = Request::builder()
+            .method(Method::GET)
+            .uri("/users")
+            .body(())
+            .unwrap();
+        route_authority_form(&mut req);
+        assert_eq!(req.uri().path(), "/users");
+        assert!(req.headers().get(HOST).is_none());
+    }
 
     #[test]
 
@@ -71,4 +221,534 @@ mod tests {
             .get_routes("//user/{id}", crate::types::HttpMethods::GET)
             .is_some());
     }
+
+    #[test]
+    fn test_nested_router_prefixes_routes() {
+        let mut v1 = Router::new("/v1");
+        v1.get("/users/:id", _test_handler);
+        v1.get("/", _test_handler);
+
+        let mut api = Router::new("/api");
+        api.router(v1);
+
+        assert!(api
+            .get_routes("/v1/users/:id", crate::types::HttpMethods::GET)
+            .is_some());
+
+        let mut app = App::new();
+        app.router(api);
+
+        assert!(app
+            .get_routes("/api/v1/users/:id", crate::types::HttpMethods::GET)
+            .is_some());
+        assert!(app
+            .get_routes("/api/v1", crate::types::HttpMethods::GET)
+            .is_some());
+    }
+
+    #[test]
+    fn test_nested_router_with_root_base_path() {
+        let mut inner = Router::new("/");
+        inner.post("/items", _test_handler);
+
+        let mut outer = Router::new("/shop");
+        outer.router(inner);
+
+        assert!(outer
+            .get_routes("/items", crate::types::HttpMethods::POST)
+            .is_some());
+    }
+
+    #[tokio::test]
+    async fn test_nested_router_preserves_params() {
+        use crate::app::test_client::TestClient;
+        use crate::req::HttpRequest;
+
+        let mut users = Router::new("/users");
+        users.get(
+            "/:id",
+            |req: HttpRequest, res: crate::res::HttpResponse| async move {
+                let id = req.params.get("id").unwrap_or_default().to_string();
+                res.ok().text(id)
+            },
+        );
+
+        let mut v1 = Router::new("/v1");
+        v1.router(users);
+
+        let mut api = Router::new("/api");
+        api.router(v1);
+
+        let mut app = App::new();
+        app.router(api);
+
+        let res = TestClient::new(app).get("/api/v1/users/42").send().await;
+
+        assert_eq!(res.status(), 200);
+        assert_eq!(res.text(), "42");
+    }
+
+    fn trace(req: &mut crate::req::HttpRequest, step: &str) {
+        let trace = req.get_data("trace").unwrap_or_default();
+        req.set_data("trace".to_string(), format!("{}{};", trace, step));
+    }
+
+    fn admin_app() -> App {
+        use crate::req::HttpRequest;
+        use crate::res::HttpResponse;
+
+        let mut app = App::new();
+        app.use_pre_middleware(None, |mut req: HttpRequest, res, next| async move {
+            trace(&mut req, "app-before");
+            next.call(req, res).await
+        });
+
+        let mut admin = Router::new("/admin");
+        admin.use_pre_middleware(None, |mut req: HttpRequest, res, next| async move {
+            if req.headers.get("authorization") != Some("Bearer admin") {
+                return (req, Some(res.unauthorized().text("Unauthorized")));
+            }
+            trace(&mut req, "router");
+            next.call(req, res).await
+        });
+        admin.use_post_middleware(None, |req: HttpRequest, res, _next| async move {
+            (req, Some(res.set_header("X-Admin", "true")))
+        });
+        admin.get(
+            "/dashboard",
+            |req: HttpRequest, res: HttpResponse| async move {
+                res.ok().text(req.get_data("trace").unwrap_or_default())
+            },
+        );
+        admin.get(
+            "/users/:id",
+            |req: HttpRequest, res: HttpResponse| async move {
+                res.ok()
+                    .text(req.params.get("id").unwrap_or_default().to_string())
+            },
+        );
+
+        app.router(admin);
+        app.use_pre_middleware(None, |mut req: HttpRequest, res, next| async move {
+            trace(&mut req, "app-after");
+            next.call(req, res).await
+        });
+
+        app.get(
+            "/public",
+            |_req: HttpRequest, res: HttpResponse| async move { res.ok().text("public") },
+        );
+        app
+    }
+
+    #[tokio::test]
+    async fn test_router_middleware_runs_for_every_router_route() {
+        use crate::app::test_client::TestClient;
+
+        let client = TestClient::new(admin_app());
+
+        let res = client.get("/admin/dashboard").send().await;
+        assert_eq!(res.status(), 401);
+
+        let res = client.get("/admin/users/7").send().await;
+        assert_eq!(res.status(), 401);
+
+        let res = client
+            .get("/admin/users/7")
+            .header("authorization", "Bearer admin")
+            .send()
+            .await;
+        assert_eq!(res.status(), 200);
+        assert_eq!(res.text(), "7");
+        assert_eq!(res.header("x-admin"), Some("true"));
+    }
+
+    #[tokio::test]
+    async fn test_router_middleware_does_not_leak_outside_base_path() {
+        use crate::app::test_client::TestClient;
+
+        let res = TestClient::new(admin_app()).get("/public").send().await;
+
+        assert_eq!(res.status(), 200);
+        assert_eq!(res.text(), "public");
+        assert_eq!(res.header("x-admin"), None);
+    }
+
+    #[tokio::test]
+    async fn test_router_middleware_runs_in_mount_order() {
+        use crate::app::test_client::TestClient;
+
+        let res = TestClient::new(admin_app())
+            .get("/admin/dashboard")
+            .header("authorization", "Bearer admin")
+            .send()
+            .await;
+
+        assert_eq!(res.text(), "app-before;router;app-after;");
+    }
+
+    #[tokio::test]
+    async fn test_nested_router_middleware_is_scoped_to_sub_router() {
+        use crate::app::test_client::TestClient;
+        use crate::req::HttpRequest;
+        use crate::res::HttpResponse;
+
+        let mut v1 = Router::new("/v1");
+        v1.use_post_middleware(None, |req: HttpRequest, res, _next| async move {
+            (req, Some(res.set_header("X-Version", "1")))
+        });
+        v1.get("/ping", |_req: HttpRequest, res: HttpResponse| async move {
+            res.ok().text("pong")
+        });
+
+        let mut api = Router::new("/api");
+        api.get(
+            "/health",
+            |_req: HttpRequest, res: HttpResponse| async move { res.ok().text("ok") },
+        );
+        api.router(v1);
+
+        let mut app = App::new();
+        app.router(api);
+        let client = TestClient::new(app);
+
+        let res = client.get("/api/v1/ping").send().await;
+        assert_eq!(res.text(), "pong");
+        assert_eq!(res.header("x-version"), Some("1"));
+
+        let res = client.get("/api/health").send().await;
+        assert_eq!(res.text(), "ok");
+        assert_eq!(res.header("x-version"), None);
+    }
+
+    #[test]
+    fn test_route_pattern_wildcard_and_constraints() {
+        use crate::router::route_pattern::RoutePattern;
+
+        let files = RoutePattern::parse("/files/*path").unwrap();
+        assert_eq!(files.routerify_path(), "/files/*");
+        assert_eq!(
+            files.matches("/files/a/b/c.txt", true),
+            Some(vec![("path".to_string(), "a/b/c.txt".to_string())])
+        );
+
+        let bare = RoutePattern::parse("/static/*").unwrap();
+        assert_eq!(
+            bare.matches("/static/app.js", true),
+            Some(vec![("*".to_string(), "app.js".to_string())])
+        );
+
+        let user = RoutePattern::parse(r"/users/:id(\d+)").unwrap();
+        assert_eq!(user.routerify_path(), "/users/:id");
+        assert_eq!(
+            user.matches("/users/42", true),
+            Some(vec![("id".to_string(), "42".to_string())])
+        );
+        assert_eq!(user.matches("/users/alice", true), None);
+        assert_eq!(user.matches("/users/42abc", true), None);
+
+        assert!(RoutePattern::parse(r"/users/:id([0-9)").is_err());
+        assert!(RoutePattern::parse("/files/*path/edit").is_err());
+    }
+
+    #[tokio::test]
+    async fn test_wildcard_param_captures_rest_of_path() {
+        use crate::app::test_client::TestClient;
+        use crate::req::HttpRequest;
+        use crate::res::HttpResponse;
+
+        let mut app = App::new();
+        app.get(
+            "/files/*path",
+            |req: HttpRequest, res: HttpResponse| async move {
+                res.ok()
+                    .text(req.params.get("path").unwrap_or_default().to_string())
+            },
+        );
+
+        let res = TestClient::new(app).get("/files/a/b/c.txt").send().await;
+
+        assert_eq!(res.status(), 200);
+        assert_eq!(res.text(), "a/b/c.txt");
+    }
+
+    #[tokio::test]
+    async fn test_constrained_param_does_not_shadow_siblings() {
+        use crate::app::test_client::TestClient;
+        use crate::req::HttpRequest;
+        use crate::res::HttpResponse;
+
+        let mut app = App::new();
+        app.get(
+            r"/users/:id(\d+)",
+            |req: HttpRequest, res: HttpResponse| async move {
+                res.ok()
+                    .text(format!("id {}", req.params.get("id").unwrap_or_default()))
+            },
+        );
+        app.get(
+            "/users/:name",
+            |req: HttpRequest, res: HttpResponse| async move {
+                res.ok().text(format!(
+                    "name {}",
+                    req.params.get("name").unwrap_or_default()
+                ))
+            },
+        );
+        app.get(
+            "/users/me",
+            |_req: HttpRequest, res: HttpResponse| async move { res.ok().text("me") },
+        );
+        app.get(
+            r"/orders/:id(\d+)",
+            |_req: HttpRequest, res: HttpResponse| async move { res.ok().text("order") },
+        );
+        let client = TestClient::new(app);
+
+        assert_eq!(client.get("/users/42").send().await.text(), "id 42");
+        assert_eq!(client.get("/users/alice").send().await.text(), "name alice");
+        assert_eq!(client.get("/users/me").send().await.text(), "me");
+        assert_eq!(client.get("/orders/abc").send().await.status(), 404);
+    }
+
+    #[tokio::test]
+    async fn test_head_is_answered_by_get_handler() {
+        use crate::app::test_client::TestClient;
+        use crate::req::HttpRequest;
+        use crate::res::HttpResponse;
+
+        let mut app = App::new();
+        app.get(
+            "/users",
+            |_req: HttpRequest, res: HttpResponse| async move {
+                res.ok()
+                    .set_header("X-Total", "2")
+                    .json(serde_json::json!(["alice", "bob"]))
+            },
+        );
+        app.get(
+            "/empty",
+            |_req: HttpRequest, res: HttpResponse| async move { res.status(204) },
+        );
+        let client = TestClient::new(app);
+
+        let get = client.get("/users").send().await;
+        let head = client.head("/users").send().await;
+
+        assert_eq!(head.status(), 200);
+        assert!(head.bytes().is_empty());
+        assert_eq!(head.header("X-Total"), Some("2"));
+        assert_eq!(head.header("Content-Type"), get.header("Content-Type"));
+        assert_eq!(
+            head.header("Content-Length"),
+            Some(get.bytes().len().to_string().as_str())
+        );
+
+        let head = client.head("/empty").send().await;
+        assert_eq!(head.status(), 204);
+        assert_eq!(head.header("Content-Length"), None);
+
+        assert_eq!(client.head("/missing").send().await.status(), 404);
+    }
+
+    #[tokio::test]
+    async fn test_explicit_head_route_wins_over_get() {
+        use crate::app::test_client::TestClient;
+        use crate::req::HttpRequest;
+        use crate::res::HttpResponse;
+
+        let mut app = App::new();
+        app.get("/ping", |_req: HttpRequest, res: HttpResponse| async move {
+            res.ok().text("pong")
+        });
+        app.head("/ping", |_req: HttpRequest, res: HttpResponse| async move {
+            res.ok().set_header("X-Head", "explicit")
+        });
+
+        let res = TestClient::new(app).head("/ping").send().await;
+
+        assert_eq!(res.header("X-Head"), Some("explicit"));
+    }
+
+    #[tokio::test]
+    async fn test_wrong_method_gets_405_with_allow() {
+        use crate::app::test_client::TestClient;
+        use crate::req::HttpRequest;
+        use crate::res::HttpResponse;
+
+        let mut app = App::new();
+        app.post(
+            "/users",
+            |_req: HttpRequest, res: HttpResponse| async move { res.created() },
+        );
+        app.get(
+            "/users",
+            |_req: HttpRequest, res: HttpResponse| async move { res.ok() },
+        );
+        let client = TestClient::new(app);
+
+        let res = client.delete("/users").send().await;
+        assert_eq!(res.status(), 405);
+        assert_eq!(res.header("Allow"), Some("GET, POST"));
+
+        let res = client.options("/users").send().await;
+        assert_eq!(res.status(), 204);
+        assert_eq!(res.header("Allow"), Some("GET, POST"));
+
+        assert_eq!(client.delete("/posts").send().await.status(), 404);
+    }
+
+    #[tokio::test]
+    async fn test_allow_collects_methods_across_matching_routes() {
+        use crate::app::test_client::TestClient;
+        use crate::req::HttpRequest;
+        use crate::res::HttpResponse;
+
+        let mut app = App::new();
+        app.get(
+            "/items/:id",
+            |_req: HttpRequest, res: HttpResponse| async move { res.ok() },
+        );
+        app.put(
+            r"/items/:id(\d+)",
+            |_req: HttpRequest, res: HttpResponse| async move { res.ok() },
+        );
+        app.options(
+            "/custom",
+            |_req: HttpRequest, res: HttpResponse| async move { res.ok().text("custom") },
+        );
+        let client = TestClient::new(app);
+
+        let res = client.delete("/items/7").send().await;
+        assert_eq!(res.header("Allow"), Some("GET, PUT"));
+
+        let res = client.delete("/items/abc").send().await;
+        assert_eq!(res.header("Allow"), Some("GET"));
+
+        let res = client.options("/custom").send().await;
+        assert_eq!(res.status(), 200);
+        assert_eq!(res.text(), "custom");
+    }
+
+    #[tokio::test]
+    async fn test_matched_path_is_the_route_pattern() {
+        use crate::app::test_client::TestClient;
+        use crate::req::HttpRequest;
+        use crate::res::HttpResponse;
+
+        let mut app = App::new();
+        app.use_pre_middleware(None, |req: HttpRequest, res, next| async move {
+            assert_eq!(req.matched_path, None);
+            next.call(req, res).await
+        });
+        app.use_post_middleware(
+            None,
+            |req: HttpRequest, res: HttpResponse, _next| async move {
+                let route = req.matched_path.clone().unwrap_or_default();
+                (req, Some(res.set_header("x-route", route)))
+            },
+        );
+        app.use_post_middleware(
+            None,
+            |req: HttpRequest, res: HttpResponse, _next| async move {
+                let route = req.matched_path.clone().unwrap_or_default();
+                (req, Some(res.set_header("x-route-again", route)))
+            },
+        );
+        app.get(
+            "/users/:id",
+            |req: HttpRequest, res: HttpResponse| async move {
+                res.ok().text(req.matched_path.unwrap_or_default())
+            },
+        );
+        let client = TestClient::new(app);
+
+        let res = client.get("/users/123").send().await;
+        assert_eq!(res.text(), "/users/:id");
+        assert_eq!(res.header("x-route"), Some("/users/:id"));
+        assert_eq!(res.header("x-route-again"), Some("/users/:id"));
+
+        let res = client.get("/missing").send().await;
+        assert_eq!(res.status(), 404);
+        assert_eq!(res.header("x-route"), Some(""));
+    }
+
+    #[tokio::test]
+    async fn test_route_builder_registers_named_route_with_middleware() {
+        use crate::app::test_client::TestClient;
+        use crate::req::HttpRequest;
+        use crate::types::HttpMethods;
+
+        let mut admin = Router::new("/admin");
+        admin
+            .route(HttpMethods::GET, "/users/:id")
+            .name("admin_user")
+            .with_middleware(|req: HttpRequest, res, next| async move {
+                if req.headers.get("authorization").is_none() {
+                    return (req, Some(res.unauthorized().text("no token")));
+                }
+                next.call(req, res.set_header("X-Checked", "yes")).await
+            })
+            .handler(|req: HttpRequest, res| async move {
+                let id = req.params.get("id").unwrap_or_default().to_string();
+                res.ok().text(id)
+            })
+            .get("/open", _test_handler);
+
+        let mut app = App::new();
+        app.route(HttpMethods::GET, "/")
+            .name("home")
+            .handler(_test_handler);
+        app.router(admin);
+
+        assert_eq!(app.url_for("home", &[]), Some("/".to_string()));
+        assert_eq!(
+            app.url_for("admin_user", &[("id", "a b")]),
+            Some("/admin/users/a%20b".to_string())
+        );
+        assert_eq!(app.url_for("admin_user", &[]), None);
+        assert_eq!(app.url_for("missing", &[]), None);
+
+        let client = TestClient::new(app);
+        let res = client.get("/admin/users/7").send().await;
+        assert_eq!(res.status(), 401);
+        assert_eq!(res.text(), "no token");
+
+        let res = client
+            .get("/admin/users/7")
+            .header("authorization", "Bearer x")
+            .send()
+            .await;
+        assert_eq!(res.status(), 200);
+        assert_eq!(res.text(), "7");
+        assert_eq!(res.header("x-checked"), Some("yes"));
+
+        // The middleware belongs to the route, not the router.
+        let res = client.get("/admin/open").send().await;
+        assert_eq!(res.status(), 200);
+        assert_eq!(res.header("x-checked"), None);
+    }
+
+    #[test]
+    fn test_url_for_checks_constraints_and_fills_wildcards() {
+        use crate::types::HttpMethods;
+
+        let mut app = App::new();
+        app.route(HttpMethods::GET, r"/users/:id(\d+)")
+            .name("user")
+            .handler(_test_handler);
+        app.route(HttpMethods::GET, "/files/*path")
+            .name("file")
+            .handler(_test_handler);
+
+        assert_eq!(
+            app.url_for("user", &[("id", "12")]),
+            Some("/users/12".to_string())
+        );
+        assert_eq!(app.url_for("user", &[("id", "abc")]), None);
+        assert_eq!(
+            app.url_for("file", &[("path", "docs/read me.txt")]),
+            Some("/files/docs/read%20me.txt".to_string())
+        );
+    }
 }
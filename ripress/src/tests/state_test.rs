@@ -0,0 +1,124 @@
+#[cfg(test)]
+mod test {
+    use std::sync::atomic::{AtomicUsize, Ordering};
+    use std::sync::Arc;
+
+    use crate::app::{state::State, test_client::TestClient, App};
+    use crate::req::HttpRequest;
+    use crate::types::RouterFns;
+
+    #[derive(Clone, Default)]
+    struct Counter(Arc<AtomicUsize>);
+
+    #[derive(Clone)]
+    struct Greeting(&'static str);
+
+    #[tokio::test]
+    async fn test_state_is_shared_between_requests() {
+        let mut app = App::new();
+        app.state(Counter::default());
+        app.get("/", |State(counter): State<Counter>, res| async move {
+            let count = counter.0.fetch_add(1, Ordering::SeqCst) + 1;
+            res.ok().text(count.to_string())
+        });
+        let client = TestClient::new(app);
+
+        assert_eq!(client.get("/").send().await.text(), "1");
+        assert_eq!(client.get("/").send().await.text(), "2");
+    }
+
+    #[tokio::test]
+    async fn test_state_is_keyed_by_type() {
+        let mut app = App::new();
+        app.state(Greeting("hello"));
+        app.state(Counter::default());
+        app.state(Greeting("hi"));
+        app.get(
+            "/",
+            |(State(greeting), State(counter)): (State<Greeting>, State<Counter>), res| async move {
+                let count = counter.0.load(Ordering::SeqCst);
+                res.ok().text(format!("{} {}", greeting.0, count))
+            },
+        );
+        let client = TestClient::new(app);
+
+        assert_eq!(client.get("/").send().await.text(), "hi 0");
+    }
+
+    #[tokio::test]
+    async fn test_state_survives_pre_middleware() {
+        let mut app = App::new();
+        app.state(Greeting("hello"));
+        app.use_pre_middleware(None, |req: HttpRequest, res, next| async move {
+            next.call(req, res).await
+        });
+        app.get("/", |State(greeting): State<Greeting>, res| async move {
+            res.ok().text(greeting.0)
+        });
+        let client = TestClient::new(app);
+
+        assert_eq!(client.get("/").send().await.text(), "hello");
+    }
+
+    #[tokio::test]
+    async fn test_missing_state_is_a_server_error() {
+        let mut app = App::new();
+        app.get("/", |State(greeting): State<Greeting>, res| async move {
+            res.ok().text(greeting.0)
+        });
+        let client = TestClient::new(app);
+
+        let res = client.get("/").send().await;
+        assert_eq!(res.status(), 500);
+        assert!(res.text().contains("Greeting"));
+        assert!(res.text().contains("App::state"));
+    }
+
+    struct HttpClient {
+        base_url: &'static str,
+    }
+
+    struct User(&'static str);
+
+    #[tokio::test]
+    async fn test_app_extension_is_readable_from_handlers() {
+        let mut app = App::new();
+        app.extension(Arc::new(HttpClient {
+            base_url: "https://api.example.com",
+        }));
+        app.get("/", |req: HttpRequest, res| async move {
+            let client = req.extension::<Arc<HttpClient>>().unwrap();
+            res.ok().text(client.base_url)
+        });
+        let client = TestClient::new(app);
+
+        assert_eq!(
+            client.get("/").send().await.text(),
+            "https://api.example.com"
+        );
+    }
+
+    #[tokio::test]
+    async fn test_request_extensions_are_isolated_per_request() {
+        let mut app = App::new();
+        app.extension(User("anonymous"));
+        app.use_pre_middleware(None, |mut req: HttpRequest, res, next| async move {
+            if req.headers.get("authorization").is_some() {
+                req.set_extension(User("alice"));
+            }
+            next.call(req, res).await
+        });
+        app.get("/", |req: HttpRequest, res| async move {
+            res.ok().text(req.extension::<User>().unwrap().0)
+        });
+        let client = TestClient::new(app);
+
+        let res = client
+            .get("/")
+            .header("authorization", "Bearer token")
+            .send()
+            .await;
+        assert_eq!(res.text(), "alice");
+        assert_eq!(client.get("/").send().await.text(), "anonymous");
+    }
+}
@@ -0,0 +1,262 @@
+#[cfg(test)]
+mod test {
+    use std::sync::atomic::{AtomicBool, Ordering};
+    use std::sync::Arc;
+    use std::time::{Duration, Instant};
+
+    use serde_json::{json, Value};
+
+    use crate::app::{settings::ErrorFormat, test_client::TestClient, App};
+    use crate::req::HttpRequest;
+    use crate::res::HttpResponse;
+    use crate::types::RouterFns;
+
+    fn make_app() -> App {
+        let mut app = App::new();
+
+        app.get("/users/:id", |req: HttpRequest, res| async move {
+            let id = req.params.get("id").unwrap_or_default().to_string();
+            res.ok().json(json!({ "id": id }))
+        });
+
+        app.get("/search", |req: HttpRequest, res| async move {
+            let q = req.query.get("q").unwrap_or_default().to_string();
+            let agent = req.headers.get("x-agent").unwrap_or_default().to_string();
+            res.ok().text(format!("{} from {}", q, agent))
+        });
+
+        app.post("/echo", |req: HttpRequest, res| async move {
+            match req.json::<Value>() {
+                Ok(body) => res.status(201).json(body),
+                Err(e) => res.bad_request().text(e),
+            }
+        });
+
+        app
+    }
+
+    #[tokio::test]
+    async fn test_client_dispatches_to_route_with_params() {
+        let client = TestClient::new(make_app());
+
+        let res = client.get("/users/42").send().await;
+
+        assert_eq!(res.status(), 200);
+        assert_eq!(res.json::<Value>().unwrap(), json!({ "id": "42" }));
+        assert!(res
+            .header("content-type")
+            .unwrap()
+            .starts_with("application/json"));
+    }
+
+    #[tokio::test]
+    async fn test_client_sends_query_and_headers() {
+        let client = TestClient::new(make_app());
+
+        let res = client
+            .get("/search")
+            .query("q", "rust & tokio")
+            .header("X-Agent", "tests")
+            .send()
+            .await;
+
+        assert_eq!(res.status(), 200);
+        assert_eq!(res.text(), "rust & tokio from tests");
+    }
+
+    #[tokio::test]
+    async fn test_client_keeps_repeated_query_params() {
+        let mut app = App::new();
+        app.get("/tags", |req: HttpRequest, res| async move {
+            let tags = req.query.get_all("tag").join(",");
+            res.ok().text(tags)
+        });
+        let client = TestClient::new(app);
+
+        let res = client.get("/tags?tag=a&tag=b").send().await;
+
+        assert_eq!(res.text(), "a,b");
+    }
+
+    #[tokio::test]
+    async fn test_client_exposes_raw_query() {
+        let mut app = App::new();
+        app.get("/proxy", |req: HttpRequest, res| async move {
+            let raw = req.query.raw().to_string();
+            res.ok().text(raw)
+        });
+        let client = TestClient::new(app);
+
+        let res = client.get("/proxy?z=1&a=%2F&a=b+c").send().await;
+
+        assert_eq!(res.text(), "z=1&a=%2F&a=b+c");
+    }
+
+    #[tokio::test]
+    async fn test_client_sends_json_body() {
+        let client = TestClient::new(make_app());
+
+        let res = client
+            .post("/echo")
+            .json(&json!({ "name": "ripress" }))
+            .send()
+            .await;
+
+        assert_eq!(res.status(), 201);
+        assert_eq!(res.json::<Value>().unwrap()["name"], "ripress");
+    }
+
+    #[tokio::test]
+    async fn test_client_returns_404_for_unknown_route() {
+        let client = TestClient::new(make_app());
+
+        assert_eq!(client.get("/missing").send().await.status(), 404);
+
+        let res = client.delete("/users/1").send().await;
+        assert_eq!(res.status(), 405);
+        assert_eq!(res.header("Allow"), Some("GET"));
+    }
+
+    #[tokio::test]
+    async fn test_client_runs_middleware_chain() {
+        let mut app = make_app();
+        app.use_pre_middleware("/users", |req: HttpRequest, res, next| async move {
+            if req.headers.get("authorization").is_none() {
+                return (req, Some(res.unauthorized().text("no token")));
+            }
+            next.call(req, res.set_header("x-checked", "yes")).await
+        });
+        app.use_post_middleware("/", |req: HttpRequest, res, _| async move {
+            (req, Some(res.set_header("x-post", "ran")))
+        });
+        app.use_post_middleware("/", |req: HttpRequest, _, _| async move { (req, None) });
+        let client = TestClient::new(app);
+
+        let denied = client.get("/users/1").send().await;
+        assert_eq!(denied.status(), 401);
+        assert_eq!(denied.text(), "no token");
+
+        let allowed = client
+            .get("/users/1")
+            .header("Authorization", "Bearer t")
+            .send()
+            .await;
+        assert_eq!(allowed.status(), 200);
+        assert_eq!(allowed.header("x-checked"), Some("yes"));
+        assert_eq!(allowed.header("x-post"), Some("ran"));
+        assert_eq!(allowed.json::<Value>().unwrap(), json!({ "id": "1" }));
+    }
+
+    #[tokio::test]
+    async fn test_post_middleware_passing_through_keeps_headers() {
+        let mut app = make_app();
+        app.use_post_middleware("/", |req: HttpRequest, _, _| async move { (req, None) });
+        let client = TestClient::new(app);
+
+        let res = client.get("/users/1").send().await;
+
+        assert_eq!(res.header("content-type"), Some("application/json"));
+        assert_eq!(res.json::<Value>().unwrap(), json!({ "id": "1" }));
+    }
+
+    #[tokio::test]
+    async fn test_error_format_defaults_to_text() {
+        let mut app = make_app();
+        app.use_body_limit(Some(4));
+        let client = TestClient::new(app);
+
+        let res = client.get("/missing").send().await;
+        assert_eq!(res.header("content-type"), Some("text/plain"));
+        assert_eq!(res.text(), "Not Found");
+
+        let res = client.post("/echo").text("too long").send().await;
+        assert_eq!(res.status(), 413);
+        assert_eq!(
+            res.text(),
+            "Request body exceeded the configured limit of 4 bytes"
+        );
+    }
+
+    #[tokio::test]
+    async fn test_error_format_json_applies_to_framework_errors() {
+        let mut app = make_app();
+        app.use_body_limit(Some(4));
+        app.error_format(ErrorFormat::Json);
+        let client = TestClient::new(app);
+
+        let res = client.post("/echo").text("too long").send().await;
+        assert_eq!(res.status(), 413);
+        assert_eq!(res.header("content-type"), Some("application/json"));
+        assert_eq!(
+            res.json::<Value>().unwrap(),
+            json!({ "error": "Request body exceeded the configured limit of 4 bytes" })
+        );
+
+        let res = client.delete("/users/1").send().await;
+        assert_eq!(res.status(), 405);
+        assert_eq!(res.header("Allow"), Some("GET"));
+        assert_eq!(
+            res.json::<Value>().unwrap(),
+            json!({ "error": "Method Not Allowed" })
+        );
+
+        // Handler responses are left alone
+        let res = client.post("/echo").text("{").send().await;
+        assert_eq!(res.status(), 400);
+        assert_ne!(res.header("content-type"), Some("application/json"));
+    }
+
+    #[tokio::test]
+    async fn test_error_format_custom() {
+        let mut app = make_app();
+        app.error_format(ErrorFormat::custom(|status, message| {
+            HttpResponse::new()
+                .status(status)
+                .json(json!({ "code": status, "detail": message }))
+        }));
+        let client = TestClient::new(app);
+
+        let res = client.delete("/users/1").send().await;
+        assert_eq!(res.status(), 405);
+        assert_eq!(res.header("Allow"), Some("GET"));
+        assert_eq!(
+            res.json::<Value>().unwrap(),
+            json!({ "code": 405, "detail": "Method Not Allowed" })
+        );
+    }
+
+    #[tokio::test]
+    async fn test_timeout_cancels_slow_handler() {
+        struct DropFlag(Arc<AtomicBool>);
+
+        impl Drop for DropFlag {
+            fn drop(&mut self) {
+                self.0.store(true, Ordering::SeqCst);
+            }
+        }
+
+        let dropped = Arc::new(AtomicBool::new(false));
+        let mut app = make_app();
+        let flag = Arc::clone(&dropped);
+        app.get("/slow", move |_req: HttpRequest, res| {
+            let guard = DropFlag(Arc::clone(&flag));
+            async move {
+                tokio::time::sleep(Duration::from_secs(10)).await;
+                drop(guard);
+                res.ok().text("done")
+            }
+        });
+        app.use_timeout(Duration::from_millis(50));
+        let client = TestClient::new(app);
+
+        let started = Instant::now();
+        let res = client.get("/slow").send().await;
+        assert_eq!(res.status(), 503);
+        assert_eq!(res.text(), "Request timed out");
+        assert!(started.elapsed() < Duration::from_secs(5));
+        assert!(dropped.load(Ordering::SeqCst));
+
+        // Fast handlers are unaffected
+        assert_eq!(client.get("/users/1").send().await.status(), 200);
+    }
+}
@@ -0,0 +1,65 @@
+#[cfg(test)]
+mod test {
+    use std::time::Duration;
+
+    use tokio::{
+        io::{AsyncReadExt, AsyncWriteExt},
+        net::UnixStream,
+    };
+
+    use crate::{app::unix, app::App, req::HttpRequest, types::RouterFns};
+
+    #[tokio::test]
+    async fn test_listen_unix_serves_requests_and_removes_socket() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("app.sock");
+        // A socket file left behind by a crashed run.
+        drop(std::os::unix::net::UnixListener::bind(&path).unwrap());
+
+        let mut app = App::new();
+        app.get("/", |req: HttpRequest, res| async move {
+            res.ok().text(format!("{} {}", req.path, req.protocol))
+        });
+        let server = tokio::spawn({
+            let path = path.clone();
+            async move { app.listen_unix(path, || {}).await }
+        });
+
+        let mut stream = loop {
+            match UnixStream::connect(&path).await {
+                Ok(stream) => break stream,
+                Err(_) => tokio::time::sleep(Duration::from_millis(10)).await,
+            }
+        };
+        stream
+            .write_all(b"GET / HTTP/1.1\r\nHost: localhost\r\nConnection: close\r\n\r\n")
+            .await
+            .unwrap();
+        let mut response = String::new();
+        stream.read_to_string(&mut response).await.unwrap();
+
+        assert!(response.starts_with("HTTP/1.1 200"), "{}", response);
+        assert!(response.ends_with("/ http"), "{}", response);
+
+        server.abort();
+        let _ = server.await;
+        assert!(!path.exists());
+    }
+
+    #[tokio::test]
+    async fn test_bind_refuses_live_socket_and_other_files() {
+        let dir = tempfile::tempdir().unwrap();
+
+        let live = dir.path().join("live.sock");
+        let _listener = unix::bind(&live).unwrap();
+        assert_eq!(
+            unix::bind(&live).unwrap_err().kind(),
+            std::io::ErrorKind::AddrInUse
+        );
+
+        let file = dir.path().join("not-a-socket");
+        std::fs::write(&file, "data").unwrap();
+        assert!(unix::bind(&file).is_err());
+        assert!(file.exists());
+    }
+}
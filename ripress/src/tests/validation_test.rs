@@ -69,13 +69,13 @@ mod validation_tests {
 
     fn create_json_request(json_value: serde_json::Value) -> HttpRequest {
         let mut req = HttpRequest::default();
-        req.body = RequestBody::JSON(json_value);
+        req.body = RequestBody::JSON(json_value).into();
         req
     }
 
     fn create_text_request(text: &str) -> HttpRequest {
         let mut req = HttpRequest::default();
-        req.body = RequestBody::TEXT(TextData::new(text.to_string()));
+        req.body = RequestBody::TEXT(TextData::new(text.to_string())).into();
         req
     }
 
@@ -353,3 +353,113 @@ mod validation_tests {
         assert_eq!(validated.age, 30);
     }
 }
+
+#[cfg(feature = "validation")]
+#[cfg(test)]
+mod validated_extractor_tests {
+    use crate::app::{test_client::TestClient, App};
+    use crate::req::validated::Validated;
+    use crate::types::RouterFns;
+    use serde::Deserialize;
+    use serde_json::{json, Value};
+    use validator::Validate;
+
+    #[derive(Debug, Deserialize, Validate)]
+    struct CreateUser {
+        #[validate(email)]
+        email: String,
+        #[validate(length(min = 3, max = 20))]
+        username: String,
+        #[validate(nested)]
+        address: Option<Address>,
+    }
+
+    #[derive(Debug, Deserialize, Validate)]
+    struct Address {
+        #[validate(length(min = 1, message = "city can't be empty"))]
+        city: String,
+    }
+
+    fn client() -> TestClient {
+        let mut app = App::new();
+        app.post("/users", |user: Validated<CreateUser>, res| async move {
+            res.created().text(user.into_inner().username)
+        });
+        app.get("/users", |user: Validated<CreateUser>, res| async move {
+            res.ok().text(user.email.clone())
+        });
+        TestClient::new(app)
+    }
+
+    #[tokio::test]
+    async fn test_validated_accepts_valid_json() {
+        let res = client()
+            .post("/users")
+            .json(&json!({ "email": "jane@example.com", "username": "jane" }))
+            .send()
+            .await;
+        assert_eq!(res.status(), 201);
+        assert_eq!(res.text(), "jane");
+    }
+
+    #[tokio::test]
+    async fn test_validated_rejects_bad_fields_with_422() {
+        let res = client()
+            .post("/users")
+            .json(&json!({
+                "email": "not-an-email",
+                "username": "jo",
+                "address": { "city": "" }
+            }))
+            .send()
+            .await;
+        assert_eq!(res.status(), 422);
+
+        let body: Value = res.json().unwrap();
+        assert_eq!(body["error"], "Validation failed");
+        let fields = &body["fields"];
+        assert_eq!(fields["email"][0]["code"], "email");
+        assert_eq!(
+            fields["email"][0]["message"],
+            "must be a valid email address"
+        );
+        assert_eq!(fields["username"][0]["code"], "length");
+        assert_eq!(
+            fields["username"][0]["message"],
+            "length must be between 3 and 20"
+        );
+        assert_eq!(fields["address.city"][0]["message"], "city can't be empty");
+    }
+
+    #[tokio::test]
+    async fn test_validated_reads_form_and_query() {
+        let res = client()
+            .post("/users")
+            .form(&[("email", "bad"), ("username", "jane")])
+            .send()
+            .await;
+        assert_eq!(res.status(), 422);
+
+        let res = client()
+            .get("/users")
+            .query("email", "jane@example.com")
+            .query("username", "jane")
+            .send()
+            .await;
+        assert_eq!(res.status(), 200);
+        assert_eq!(res.text(), "jane@example.com");
+    }
+
+    #[tokio::test]
+    async fn test_validated_malformed_body_is_400() {
+        let res = client()
+            .post("/users")
+            .json(&json!({ "email": "jane@example.com" }))
+            .send()
+            .await;
+        assert_eq!(res.status(), 400);
+
+        let res = client().post("/users").text("hello").send().await;
+        assert_eq!(res.status(), 400);
+    }
+}
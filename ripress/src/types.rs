@@ -3,12 +3,14 @@ use crate::helpers::{box_future, ExtractFromOwned};
 use crate::next::Next;
 use crate::req::HttpRequest;
 use crate::res::HttpResponse;
+use crate::router::route_handle::RouteHandle;
+use crate::router::route_pattern::RoutePattern;
 #[cfg(feature = "with-wynd")]
 use bytes::Bytes;
 #[cfg(feature = "with-wynd")]
 use http_body_util::Full;
 use hyper::Method;
-use std::collections::HashMap;
+use std::collections::{HashMap, HashSet};
 use std::fmt::Display;
 use std::future::Future;
 use std::pin::Pin;
@@ -30,7 +32,26 @@ pub(crate) type RouteHandler =
 /// - `DELETE`: The HTTP DELETE method, used to remove resources.
 /// - `PATCH`: The HTTP PATCH method, used for making partial updates to resources.
 /// - `OPTIONS`: The HTTP OPTIONS method, used to describe the communication options for the target resource.
-#[derive(Eq, Hash, PartialEq, Clone, Debug)]
+/// - `CONNECT`: The HTTP CONNECT method, used to ask a proxy to open a tunnel to another host.
+/// - `TRACE`: The HTTP TRACE method, used to echo the received request back for diagnostics.
+/// - `Other`: An extension method such as `PURGE`, by its case-sensitive name.
+///
+/// Converting from a [`Method`] never loses the name: standard methods get their
+/// own variant and anything else becomes `Other`.
+///
+/// ## Example
+///
+/// ```
+/// use ripress::{app::App, req::HttpRequest, types::{HttpMethods, RouterFns}};
+///
+/// let mut app = App::new();
+/// app.route(HttpMethods::Other("PURGE".to_string()), "/cache/*path")
+///     .handler(|_req: HttpRequest, res| async move { res.no_content() });
+///
+/// let method = hyper::Method::from_bytes(b"PURGE").unwrap();
+/// assert_eq!(HttpMethods::from(&method).as_str(), "PURGE");
+/// ```
+#[derive(Eq, Hash, PartialEq, PartialOrd, Ord, Clone, Debug)]
 pub enum HttpMethods {
     /// The HTTP GET method, typically used for retrieving resources.
     GET,
@@ -46,6 +67,34 @@ pub enum HttpMethods {
     PATCH,
     /// The HTTP OPTIONS method, used to describe the communication options for the target resource.
     OPTIONS,
+    /// The HTTP CONNECT method, used to ask a proxy to open a tunnel to another host.
+    CONNECT,
+    /// The HTTP TRACE method, used to echo the received request back for diagnostics.
+    TRACE,
+    /// An extension method, e.g. `PURGE` for CDN cache invalidation.
+    ///
+    /// The name must be a valid HTTP token and is case-sensitive. Standard
+    /// methods should use their own variant, since requests for them never
+    /// match `Other`.
+    Other(String),
+}
+
+impl HttpMethods {
+    /// Returns the method name as sent on the wire, e.g. `"GET"` or `"PURGE"`.
+    pub fn as_str(&self) -> &str {
+        match self {
+            HttpMethods::GET => "GET",
+            HttpMethods::PUT => "PUT",
+            HttpMethods::POST => "POST",
+            HttpMethods::DELETE => "DELETE",
+            HttpMethods::PATCH => "PATCH",
+            HttpMethods::HEAD => "HEAD",
+            HttpMethods::OPTIONS => "OPTIONS",
+            HttpMethods::CONNECT => "CONNECT",
+            HttpMethods::TRACE => "TRACE",
+            HttpMethods::Other(name) => name,
+        }
+    }
 }
 
 impl From<&Method> for HttpMethods {
@@ -58,34 +107,71 @@ impl From<&Method> for HttpMethods {
             &Method::PATCH => HttpMethods::PATCH,
             &Method::HEAD => HttpMethods::HEAD,
             &Method::OPTIONS => HttpMethods::OPTIONS,
-            _ => HttpMethods::GET,
+            &Method::CONNECT => HttpMethods::CONNECT,
+            &Method::TRACE => HttpMethods::TRACE,
+            other => HttpMethods::Other(other.as_str().to_string()),
         }
     }
 }
 
+/// Fails if an [`HttpMethods::Other`] name isn't a valid HTTP token.
+impl TryFrom<&HttpMethods> for Method {
+    type Error = hyper::http::method::InvalidMethod;
+
+    fn try_from(method: &HttpMethods) -> Result<Self, Self::Error> {
+        Ok(match method {
+            HttpMethods::GET => Method::GET,
+            HttpMethods::POST => Method::POST,
+            HttpMethods::PUT => Method::PUT,
+            HttpMethods::DELETE => Method::DELETE,
+            HttpMethods::PATCH => Method::PATCH,
+            HttpMethods::HEAD => Method::HEAD,
+            HttpMethods::OPTIONS => Method::OPTIONS,
+            HttpMethods::CONNECT => Method::CONNECT,
+            HttpMethods::TRACE => Method::TRACE,
+            HttpMethods::Other(name) => Method::from_bytes(name.as_bytes())?,
+        })
+    }
+}
+
 impl Display for HttpMethods {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
-        let method = match self {
-            HttpMethods::GET => "GET",
-            HttpMethods::PUT => "PUT",
-            HttpMethods::POST => "POST",
-            HttpMethods::DELETE => "DELETE",
-            HttpMethods::PATCH => "PATCH",
-            HttpMethods::HEAD => "HEAD",
-            HttpMethods::OPTIONS => "OPTIONS",
-        };
-        write!(f, "{}", method)
+        f.write_str(self.as_str())
     }
 }
 
 pub(crate) type Routes = HashMap<String, HashMap<HttpMethods, RouteHandler>>;
 
+/// Route names given with [`RouteHandle::name`], mapped to their paths.
+pub(crate) type RouteNames = HashMap<String, String>;
+
+/// Body limits given with [`RouteHandle::body_limit`], keyed by path and method.
+pub(crate) type RouteBodyLimits = HashMap<(String, HttpMethods), usize>;
+
+/// Routes marked with [`RouteHandle::stream_body`], by path and method.
+pub(crate) type StreamedBodyRoutes = HashSet<(String, HttpMethods)>;
+
+/// Docs given with [`RouteHandle::openapi`], keyed by path and method.
+#[cfg(feature = "openapi")]
+pub(crate) type RouteDocs = HashMap<(String, HttpMethods), crate::app::openapi::Operation>;
+
 pub(crate) type MiddlewareOutput =
     Pin<Box<dyn Future<Output = (HttpRequest, Option<HttpResponse>)> + Send + 'static>>;
 
 pub(crate) type MiddlewareHandler =
     Arc<dyn Fn(HttpRequest, HttpResponse, Next) -> MiddlewareOutput + Send + Sync + 'static>;
 
+/// The rest of the chain handed to an around middleware registered with
+/// [`App::use_around`](crate::app::App::use_around).
+///
+/// Calling it with the request runs the remaining around middlewares and the
+/// route handler, and resolves to their response.
+pub type AroundNext =
+    Box<dyn FnOnce(HttpRequest) -> Pin<Box<dyn Future<Output = HttpResponse> + Send>> + Send>;
+
+pub(crate) type AroundHandler =
+    Arc<dyn Fn(HttpRequest, AroundNext) -> RouteHandlerReturnType + Send + Sync + 'static>;
+
 #[cfg(feature = "with-wynd")]
 pub(crate) type WyndHandler = Arc<
     dyn Fn(
@@ -112,6 +198,65 @@ pub trait RouterFns {
     /// the underlying route storage for this type.
     fn routes(&mut self) -> &mut Routes;
 
+    /// Get a mutable reference to the names given to routes with [`RouteHandle::name`].
+    fn route_names(&mut self) -> &mut RouteNames;
+
+    /// Get a mutable reference to the body limits set on routes with [`RouteHandle::body_limit`].
+    fn route_body_limits(&mut self) -> &mut RouteBodyLimits;
+
+    /// Get a mutable reference to the routes marked with [`RouteHandle::stream_body`].
+    fn streamed_body_routes(&mut self) -> &mut StreamedBodyRoutes;
+
+    /// Get a mutable reference to the docs attached to routes with [`RouteHandle::openapi`].
+    #[cfg(feature = "openapi")]
+    fn route_docs(&mut self) -> &mut RouteDocs;
+
+    /// Start registering a route that can be configured before its handler is set.
+    ///
+    /// See [`RouteHandle`] for the available options.
+    ///
+    /// # Example
+    /// ```
+    /// use ripress::{app::App, req::HttpRequest, types::{HttpMethods, RouterFns}};
+    ///
+    /// let mut app = App::new();
+    /// app.route(HttpMethods::GET, "/")
+    ///     .name("home")
+    ///     .handler(|_req: HttpRequest, res| async move { res.ok().text("Home") });
+    /// ```
+    fn route(&mut self, method: HttpMethods, path: &str) -> RouteHandle<'_, Self>
+    where
+        Self: Sized,
+    {
+        RouteHandle::new(self, method, path)
+    }
+
+    /// Build the URL of a route named with [`RouteHandle::name`].
+    ///
+    /// `params` fill in the route's `:name` and `*name` segments and are
+    /// percent-encoded. Returns `None` if no route has that name, or if a param
+    /// is missing or doesn't satisfy its constraint.
+    ///
+    /// # Example
+    /// ```
+    /// use ripress::{app::App, req::HttpRequest, types::{HttpMethods, RouterFns}};
+    ///
+    /// let mut app = App::new();
+    /// app.route(HttpMethods::GET, "/posts/:slug")
+    ///     .name("post")
+    ///     .handler(|_req: HttpRequest, res| async move { res.ok() });
+    ///
+    /// assert_eq!(
+    ///     app.url_for("post", &[("slug", "hello world")]),
+    ///     Some("/posts/hello%20world".to_string())
+    /// );
+    /// assert_eq!(app.url_for("post", &[]), None);
+    /// ```
+    fn url_for(&mut self, name: &str, params: &[(&str, &str)]) -> Option<String> {
+        let path = self.route_names().get(name)?;
+        RoutePattern::parse(path).ok()?.build(params)
+    }
+
     /// Register a handler for a specific HTTP method/path.
     ///
     /// # Type Parameters
@@ -126,11 +271,18 @@ pub trait RouterFns {
     /// * `handler` - Handler function
     ///
     /// If a handler for a given method/path already exists, it is replaced.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `method` is an [`HttpMethods::Other`] whose name isn't a valid HTTP token.
     fn add_route<F, HFut>(&mut self, method: HttpMethods, path: &str, handler: F)
     where
         F: Fn(HttpRequest, HttpResponse) -> HFut + Send + Sync + 'static,
         HFut: Future<Output = HttpResponse> + Send + 'static,
     {
+        if let Err(e) = Method::try_from(&method) {
+            panic!("invalid HTTP method `{}` for route `{}`: {}", method, path, e);
+        }
         let routes = self.routes();
         let wrapped_handler =
             Arc::new(move |req: HttpRequest, res| box_future(handler(req, res))) as RouteHandler;
@@ -149,6 +301,9 @@ pub trait RouterFns {
 
     /// Register a GET handler for a path, with extractor integration.
     ///
+    /// The handler also answers `HEAD` requests for the path, unless a `HEAD`
+    /// handler is registered; the body is dropped but `Content-Length` is kept.
+    ///
     /// # Example
     /// ```
     /// use ripress::{app::App, context::{HttpRequest, HttpResponse}, types::RouterFns};
@@ -215,6 +370,8 @@ pub trait RouterFns {
     }
 
     /// Register a HEAD handler for a path, with extractor integration.
+    ///
+    /// Only needed to override the automatic `HEAD` response of a GET route.
     fn head<F, HFut, P>(&mut self, path: &str, handler: F) -> &mut Self
     where
         F: Fn(P, HttpResponse) -> HFut + Send + Sync + 'static,
@@ -236,6 +393,46 @@ pub trait RouterFns {
         self
     }
 
+    /// Register a CONNECT handler for a path, with extractor integration.
+    ///
+    /// A CONNECT request names its target as `host:port` rather than a path, so
+    /// it is routed to `/`, with the target in the `Host` header and
+    /// [`HttpRequest::hostname`].
+    ///
+    /// ## Example
+    ///
+    /// ```
+    /// use ripress::{app::App, req::HttpRequest, types::RouterFns};
+    ///
+    /// let mut app = App::new();
+    /// app.connect("/", |req: HttpRequest, res| async move {
+    ///     if req.headers.host() != Some("internal.example.com:443") {
+    ///         return res.status(403).text("Tunnel not allowed");
+    ///     }
+    ///     res.ok()
+    /// });
+    /// ```
+    fn connect<F, HFut, P>(&mut self, path: &str, handler: F) -> &mut Self
+    where
+        F: Fn(P, HttpResponse) -> HFut + Send + Sync + 'static,
+        HFut: Future<Output = HttpResponse> + Send + 'static,
+        P: ExtractFromOwned + Send + 'static,
+    {
+        self.add_route_with_extraction(HttpMethods::CONNECT, path, handler);
+        self
+    }
+
+    /// Register a TRACE handler for a path, with extractor integration.
+    fn trace<F, HFut, P>(&mut self, path: &str, handler: F) -> &mut Self
+    where
+        F: Fn(P, HttpResponse) -> HFut + Send + Sync + 'static,
+        HFut: Future<Output = HttpResponse> + Send + 'static,
+        P: ExtractFromOwned + Send + 'static,
+    {
+        self.add_route_with_extraction(HttpMethods::TRACE, path, handler);
+        self
+    }
+
     /// Retrieve the route handler for a given path/method, if one is registered.
     ///
     /// Returns `Some(&Handler)` if a matching handler exists, else `None`.
@@ -261,9 +458,7 @@ pub trait RouterFns {
             async move {
                 let extracted = match P::extract_from_owned(req) {
                     Ok(v) => v,
-                    Err(e) => {
-                        return res.bad_request().text(format!("Extraction failed: {}", e));
-                    }
+                    Err(e) => return P::rejection(e, res),
                 };
 
                 handler(extracted, res).await